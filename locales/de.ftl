@@ -0,0 +1,95 @@
+# apreads eigene Meldungen, auf Deutsch.
+
+## Antworten
+
+yes = ja
+no = nein
+answer-yes-or-no = Bitte mit ja oder nein antworten
+pick-one-of = Eines von { $choices } wählen
+
+## Diagnosen, nach der Timeline
+
+warning = Warnung
+note = Hinweis
+
+## apread doctor
+
+doctor-header = apread { $version } auf { $os } { $arch }
+
+## apread init
+
+init-handles = Konten zum Folgen, als id@domain, durch Leerzeichen getrennt
+init-no-handles = keine
+init-bad-handle = { $handle } ist kein Handle, das apread lesen kann
+init-format = Format
+init-width = Breite
+init-bad-width = Die Breite ist eine Anzahl Spalten, etwa 100
+init-links = Links
+init-screen-reader = Timelines für einen Screenreader schreiben?
+init-wrote = { $path } geschrieben
+init-added = { $handles } zu { $path } hinzugefügt
+init-login = Beim eigenen Server anmelden, um Listen und Benachrichtigungen zu lesen? Seine Domain
+init-skip = überspringen
+
+## Timelines
+
+posts = { $count ->
+    [one] { $number } Beitrag
+   *[other] { $number } Beiträge
+}
+accounts = { $count ->
+    [one] { $number } Konto
+   *[other] { $number } Konten
+}
+days = { $count ->
+    [one] { $number } Tag
+   *[other] { $number } Tagen
+}
+weeks = { $count ->
+    [one] { $number } Woche
+   *[other] { $number } Wochen
+}
+post-boosted = geteilt
+post-edited = bearbeitet
+post-bridged = gebrückt von { $bridge }
+digest-summary = { $posts } von { $accounts } in { $span }.
+digest-week = Woche vom { $day }
+digest-undated = Ohne Datum
+footer-showing = { $shown } gezeigt, insgesamt { $total } (mit --pages mehr sehen).
+
+## Fehler
+
+error = Fehler
+error-bad-handle = Das Handle ist nicht lesbar
+error-no-feed-link = Kein Link zu einem Feed
+error-no-last-page = Die Outbox verlinkt ihre älteste Seite nicht
+error-bad-url = Ungültige URL { $url }: { $reason }
+error-captured = { $error }; die Antwort liegt in { $bundle }, um sie einem Fehlerbericht anzuhängen
+error-not-recorded = Keine aufgezeichnete Antwort für { $url }
+error-no-post-id = Der Beitrag hat keine id
+error-not-archived = { $post } ist nicht im Archiv; zuerst archivieren oder mit einem Lesezeichen versehen
+error-not-an-account = { $source } ist kein Konto und hat daher keine Outbox zum Nachladen
+error-not-an-export = { $path } ist kein Kontoexport: Es fehlt outbox.json
+error-no-data-dir = Kein Datenverzeichnis für das Archiv
+error-empty-subscription = Ein Abonnement braucht ein Handle oder einen Feed
+error-nothing-to-read = Kein Handle angegeben und keine Abonnements zum Lesen
+error-no-such-post = Es gibt keinen Beitrag { $number } mit einer URL zum Öffnen
+error-wrong-format = { $what } lässt sich nicht als { $format } schreiben
+error-needs-tor = { $handle } liegt auf einem Onion-Dienst; mit --socks5 127.0.0.1:9050 über Tor lesen
+error-instance-down = { $instance } ist nicht erreichbar und wurde vorerst übersprungen
+error-no-unit-dir = Kein Konfigurationsverzeichnis für systemd-Units
+error-not-logged-in = nicht angemeldet; zuerst apread login <Server> ausführen
+error-no-such-list = keine Liste namens "{ $name }"; apread list zeigt die vorhandenen Listen
+error-no-such-account = nicht als { $name } angemeldet; apread accounts zeigt die vorhandenen Konten
+error-no-such-search = keine gespeicherte Suche namens "{ $name }"; unter [searches] in der Konfigurationsdatei hinzufügen
+error-no-clipboard = Kopieren nicht möglich: pbcopy, clip, wl-copy, xclip oder xsel installieren und prüfen, ob es die Zwischenablage erreicht
+error-nothing-to-play = Beitrag { $number } hat kein Video und keinen Ton zum Abspielen
+error-already-set-up = { $path } gibt es schon; mit --force ersetzen
+error-no-config-dir = Kein Konfigurationsverzeichnis für die Konfigurationsdatei; --config angeben
+error-no-retention = Nichts, wonach gekürzt werden kann; max_age, max_posts oder max_size unter [retention] in der Konfigurationsdatei setzen oder als Optionen angeben
+error-no-token = Der Schlüsselbund hat kein Token für { $name }; apread login erneut ausführen
+error-read-only = nur zum Lesen angemeldet; zum Posten apread login --write <Server> ausführen
+error-not-found = Dein Server hat keinen Beitrag unter { $url } gefunden
+error-empty-draft = Der Entwurf ist leer und wurde daher nicht behalten
+error-no-such-draft = Es gibt keinen Entwurf { $number }; apread draft list zeigt die vorhandenen Entwürfe
+error-bad-module = Mit { $path } kann nicht gefiltert werden: { $reason }
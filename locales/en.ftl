@@ -0,0 +1,101 @@
+# apread's own messages, in English, which every other language falls back
+# on. Each is written as `id = message`, with variables in braces, as in
+# `{ $path }`. Counts choose their form by `$count`, with a selector for
+# each of the language's plural categories, and show `$number`.
+
+## Answers
+
+yes = yes
+no = no
+answer-yes-or-no = Answer yes or no
+pick-one-of = Pick one of { $choices }
+
+## Diagnostics, written after the timeline
+
+warning = warning
+note = note
+
+## apread doctor
+
+doctor-header = apread { $version } on { $os } { $arch }
+
+## apread init
+
+init-handles = Accounts to follow, as id@domain, separated by spaces
+init-no-handles = none
+init-bad-handle = { $handle } isn't a handle apread can read
+init-format = Format
+init-width = Width
+init-bad-width = The width is a number of columns, as in 100
+init-links = Links
+init-screen-reader = Write timelines for a screen reader?
+init-wrote = Wrote { $path }
+init-added = Added { $handles } to { $path }
+init-login = Log in to your own instance, to read lists and notifications? Its domain
+init-skip = skip
+
+## Timelines
+
+posts = { $count ->
+    [one] { $number } post
+   *[other] { $number } posts
+}
+accounts = { $count ->
+    [one] { $number } account
+   *[other] { $number } accounts
+}
+days = { $count ->
+    [one] { $number } day
+   *[other] { $number } days
+}
+weeks = { $count ->
+    [one] { $number } week
+   *[other] { $number } weeks
+}
+post-boosted = boosted
+post-edited = edited
+post-bridged = bridged from { $bridge }
+digest-summary = { $posts } from { $accounts } over { $span }.
+digest-week = Week of { $day }
+digest-undated = Undated
+footer-showing = Showing { $shown } of { $total } (use --pages to see more).
+
+## Errors
+
+error = Error
+error-bad-handle = Unable to read handle
+error-no-feed-link = No feed link
+error-no-last-page = The outbox doesn't link to its oldest page
+error-bad-url = Bad url { $url }: { $reason }
+error-captured = { $error }; what came back is in { $bundle }, to attach to a bug report
+error-not-recorded = No recorded response for { $url }
+error-no-post-id = Post has no id
+error-not-archived = { $post } isn't in the archive; archive or bookmark it first
+error-not-an-account = { $source } isn't an account, so has no outbox to backfill
+error-not-an-export = { $path } isn't an account export: it has no outbox.json
+error-no-data-dir = No data directory for the archive
+error-empty-subscription = Subscription needs a handle or a feed
+error-nothing-to-read = No handle given, and no subscriptions to read
+error-no-such-post = There's no post { $number } with a url to open
+error-wrong-format = { $what } can't be written as { $format }
+error-needs-tor = { $handle } is on an onion service; read it through Tor with --socks5 127.0.0.1:9050
+error-instance-down = { $instance } is unreachable, so was skipped for now
+error-no-unit-dir = No config directory to install systemd units in
+error-not-logged-in = not logged in; run apread login <instance> first
+error-no-such-list = no list named "{ $name }"; apread list shows the lists there are
+error-no-such-account = not logged in as { $name }; apread accounts shows the accounts there are
+error-no-such-search = no saved search named "{ $name }"; add it under [searches] in the config file
+error-no-clipboard = couldn't copy: install one of pbcopy, clip, wl-copy, xclip or xsel, and check it can reach the clipboard
+error-nothing-to-play = post { $number } has no video or audio to play
+error-already-set-up = { $path } already exists; pass --force to replace it
+error-no-config-dir = No config directory to write the config file in; pass --config
+error-no-retention = nothing to prune by; set max_age, max_posts or max_size under [retention] in the config file, or pass them as flags
+error-no-token = the keyring has no token for { $name }; run apread login again
+error-read-only = logged in to read only; run apread login --write <instance> to post
+error-not-found = your instance couldn't find a post at { $url }
+error-empty-draft = the draft is empty, so it wasn't kept
+error-no-such-draft = there's no draft { $number }; apread draft list shows the drafts there are
+error-no-address = DNS-over-HTTPS found no address for { $name }
+error-hook-failed = Hook `{ $command }` failed: { $reason }
+error-webhook-failed = Webhook { $url } failed: { $reason }
+error-bad-module = Can't filter with { $path }: { $reason }
@@ -0,0 +1,612 @@
+//! ActivityStreams documents: actors, their outboxes, and what's in them.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+
+use crate::{
+  collection::{Collection, CollectionPage},
+  compose::Visibility,
+  i18n::{self, Localized},
+  math, mention, ApUrl, BadUrl,
+};
+
+/// Who a post addressed to everyone is addressed to.
+pub const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Raised when reading from an account's oldest post, but its outbox doesn't
+/// say where its last page is.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoLastPage;
+
+impl Localized for NoLastPage {
+  fn id(&self) -> &'static str {
+    "error-no-last-page"
+  }
+}
+
+/// An ActivityPub actor, as much of it as we need to find its posts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Actor {
+  /// The actor's own url.
+  pub id: Option<ApUrl>,
+  /// The url of the actor's outbox collection.
+  pub outbox: ApUrl,
+  /// The actor's profile page, where there is one.
+  #[serde(default, deserialize_with = "page")]
+  pub url: Option<String>,
+  /// The account id part of the actor's handle.
+  pub preferred_username: Option<String>,
+  /// The actor's display name.
+  pub name: Option<String>,
+  /// The actor's bio, as html.
+  pub summary: Option<String>,
+  /// The url of the collection of the actor's followers.
+  pub followers: Option<String>,
+  /// The url of the collection of accounts the actor follows.
+  pub following: Option<String>,
+  /// The url of the collection of the actor's pinned posts.
+  pub featured: Option<String>,
+  /// The fields on the actor's profile, and anything else attached to it.
+  #[serde(default, deserialize_with = "one_or_many")]
+  pub attachment: Vec<ActorAttachment>,
+}
+
+/// Something attached to an actor's profile.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum ActorAttachment {
+  /// A profile field, as in a website or pronouns.
+  PropertyValue {
+    /// What the field is, as in `Website`.
+    name: String,
+    /// What it says, as html.
+    value: String,
+  },
+  /// Anything else, such as an identity proof.
+  #[serde(other)]
+  Other,
+}
+
+impl Actor {
+  /// The actor's handle, as `id@domain`, when the actor says enough to
+  /// build one.
+  pub fn handle(&self) -> Option<String> {
+    Some(format!(
+      "{}@{}",
+      self.preferred_username.as_deref()?,
+      self.id.as_ref()?.host()
+    ))
+  }
+
+  /// Checks the actor's outbox is on the same server as the actor, which
+  /// was fetched from `url`.
+  pub fn check_origin(&self, url: &ApUrl) -> Result<(), BadUrl> {
+    self.outbox.check_origin(url)
+  }
+}
+
+/// The top level of an outbox, which points at its pages.
+pub type OutboxIndex = Collection<Item>;
+
+/// One page of an outbox.
+pub type Page = CollectionPage<Item>;
+
+impl Page {
+  /// The items on this page which are the account's own posts.
+  pub fn posts(&self) -> Vec<Item> {
+    let mut posts = vec![];
+
+    for candidate in &self.ordered_items {
+      if let Item::Post { .. } = candidate {
+        posts.push(candidate.clone());
+      }
+    }
+
+    posts
+  }
+}
+
+/// An activity found in an outbox.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum Item {
+  /// A post the account wrote.
+  #[serde(rename = "Create")]
+  Post {
+    /// The post itself.
+    object: Box<Post>,
+    /// When the post was published. Some servers only date the post itself,
+    /// not the activity.
+    published: Option<String>,
+  },
+  /// An edit to a post the account wrote.
+  #[serde(rename = "Update")]
+  Edit {
+    /// The post as it is now.
+    object: Box<Post>,
+    /// When the edit was made.
+    published: Option<String>,
+  },
+  /// A boost of someone else's post.
+  #[serde(rename = "Announce")]
+  Boost {
+    /// The boosted post's url.
+    #[serde(default, deserialize_with = "id")]
+    object: Option<String>,
+    /// Who the boost is addressed to, which on most servers includes the
+    /// boosted post's author.
+    #[serde(default, deserialize_with = "one_or_many")]
+    cc: Vec<String>,
+    /// When the boost was made.
+    published: Option<String>,
+  },
+  /// Anything else.
+  #[serde(other)]
+  Other,
+}
+
+impl Item {
+  /// The post's content, converted from html to markdown.
+  pub fn markdown_content(&self) -> String {
+    match self {
+      Self::Boost { .. } | Self::Other => String::new(),
+      Self::Post { object, .. } | Self::Edit { object, .. } => {
+        object.markdown_content()
+      }
+    }
+  }
+}
+
+/// The object of a `Create` activity.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Post {
+  /// The post's ActivityPub id.
+  pub id: Option<String>,
+  /// What sort of object the post is, as in `Note`, or `Video` for a
+  /// PeerTube video.
+  #[serde(rename = "type")]
+  pub kind: Option<String>,
+  /// The post's web page, where there is one.
+  #[serde(default, deserialize_with = "page")]
+  pub url: Option<String>,
+  /// The url of the actor who wrote the post.
+  pub attributed_to: Option<String>,
+  /// When the post was published.
+  pub published: Option<String>,
+  /// When the post was last edited, if it has been.
+  pub updated: Option<String>,
+  /// The post's content warning, if it has one.
+  pub summary: Option<String>,
+  /// The url of the post this one replies to, if it's a reply.
+  #[serde(default, deserialize_with = "id")]
+  pub in_reply_to: Option<String>,
+  /// The post body, as html.
+  #[serde(default)]
+  pub content: String,
+  /// Hashtags, mentions and the like attached to the post.
+  #[serde(default, deserialize_with = "one_or_many")]
+  pub tag: Vec<Tag>,
+  /// Images and other files attached to the post.
+  #[serde(default, deserialize_with = "one_or_many")]
+  pub attachment: Vec<Attachment>,
+  /// Who the post is addressed to.
+  #[serde(default, deserialize_with = "one_or_many")]
+  pub to: Vec<String>,
+  /// Who the post is copied to.
+  #[serde(default, deserialize_with = "one_or_many")]
+  pub cc: Vec<String>,
+  /// Whether the post's attachments are marked sensitive, to be hidden
+  /// until they're asked for.
+  #[serde(default)]
+  pub sensitive: bool,
+  /// How long the post plays for, in seconds, when it's a video or sound
+  /// itself.
+  #[serde(default, deserialize_with = "duration")]
+  pub duration: Option<f64>,
+  /// The post's title, which events, articles and videos have.
+  pub name: Option<String>,
+  /// When the event the post is starts, if it's one.
+  pub start_time: Option<String>,
+  /// When the event the post is ends, if it says.
+  pub end_time: Option<String>,
+  /// Where the event the post is happens, if it says.
+  pub location: Option<Place>,
+}
+
+impl Post {
+  /// Whether the post has been edited since it was published.
+  pub fn is_edited(&self) -> bool {
+    self.updated.is_some() && self.updated != self.published
+  }
+
+  /// Whether the post is itself a video or a sound, as PeerTube's and
+  /// podcasts' posts are, rather than a note with them attached.
+  pub fn is_media(&self) -> bool {
+    matches!(self.kind.as_deref(), Some("Video" | "Audio"))
+  }
+
+  /// Whether the post is an event, as Mobilizon's and Gancio's posts are,
+  /// with a time it starts.
+  pub fn is_event(&self) -> bool {
+    self.kind.as_deref() == Some("Event") && self.starts_at().is_some()
+  }
+
+  /// When the event the post is starts.
+  pub fn starts_at(&self) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(self.start_time.as_deref()?).ok()
+  }
+
+  /// When the event the post is ends.
+  pub fn ends_at(&self) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(self.end_time.as_deref()?).ok()
+  }
+
+  /// The url of the post's first video or sound, for handing to a player:
+  /// the first attached, or its own page if it's one itself.
+  pub fn playable(&self) -> Option<&str> {
+    self
+      .attachment
+      .iter()
+      .find(|attachment| attachment.is_playable())
+      .and_then(|attachment| attachment.url.as_deref())
+      .or_else(|| self.url.as_deref().filter(|_| self.is_media()))
+  }
+
+  /// Who the post is shown to, as its addressing has it: public when it's
+  /// addressed to everyone, unlisted when everyone's only copied, private
+  /// when it's to followers and direct otherwise. Posts which aren't
+  /// addressed at all, as those from feeds are, don't say.
+  pub fn visibility(&self) -> Option<Visibility> {
+    let public = |to: &[String]| {
+      to.iter()
+        .any(|to| matches!(to.as_str(), PUBLIC | "as:Public" | "Public"))
+    };
+
+    if self.to.is_empty() && self.cc.is_empty() {
+      return None;
+    }
+
+    Some(if public(&self.to) {
+      Visibility::Public
+    } else if public(&self.cc) {
+      Visibility::Unlisted
+    } else if self
+      .to
+      .iter()
+      .chain(&self.cc)
+      .any(|to| to.ends_with("/followers"))
+    {
+      Visibility::Private
+    } else {
+      Visibility::Direct
+    })
+  }
+
+  /// The names of the post's hashtags, without their `#`.
+  pub fn hashtags(&self) -> Vec<&str> {
+    self
+      .tag
+      .iter()
+      .filter_map(|tag| match tag {
+        Tag::Hashtag { name, .. } => Some(name.trim_start_matches('#')),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// The post body, converted from html to markdown, as
+  /// [`readable_content`](Self::readable_content) has it.
+  pub fn markdown_content(&self) -> String {
+    html2md::parse_html(&self.readable_content())
+  }
+
+  /// The post body as html, with mentions as full handles and MathML as
+  /// readable text.
+  pub fn readable_content(&self) -> String {
+    math::readable(&mention::unlinked(self))
+  }
+}
+
+/// Where an event happens.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Place {
+  /// The place's name, or its address.
+  pub name: Option<String>,
+}
+
+/// Something tagged on a post.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum Tag {
+  /// A hashtag.
+  Hashtag {
+    /// The tag, usually with its `#`.
+    name: String,
+    /// The tag's timeline on the post's instance.
+    href: Option<String>,
+  },
+  /// A mention of another account.
+  Mention {
+    /// The account's handle, usually as `@id@domain`.
+    name: Option<String>,
+    /// The account's actor url.
+    href: Option<String>,
+  },
+  /// Anything else, such as custom emoji.
+  #[serde(other)]
+  Other,
+}
+
+/// A file attached to a post, such as an image.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+  /// The file's media type, as in `image/png`.
+  pub media_type: Option<String>,
+  /// Where the file is.
+  #[serde(default, deserialize_with = "href")]
+  pub url: Option<String>,
+  /// The file's description, for anyone who can't see it: its alt text.
+  pub name: Option<String>,
+  /// A [BlurHash](crate::blurhash) of the image, to show in its place.
+  pub blurhash: Option<String>,
+  /// How long a video or sound plays for, in seconds.
+  #[serde(default, deserialize_with = "duration")]
+  pub duration: Option<f64>,
+  /// How wide an image or video is, in pixels.
+  pub width: Option<u64>,
+  /// How tall an image or video is, in pixels.
+  pub height: Option<u64>,
+  /// Whether to show only a blur of the file, until it's revealed.
+  #[serde(skip)]
+  pub hidden: bool,
+  /// Where the file's been downloaded to, once it has been.
+  #[serde(skip)]
+  pub saved: Option<PathBuf>,
+  /// A downloaded image drawn small, a line to each row of the terminal,
+  /// once it's been downloaded and if thumbnails are built in.
+  #[serde(skip)]
+  pub thumbnail: Option<Vec<String>>,
+}
+
+impl Attachment {
+  /// The attachment's alt text, unless it was left empty.
+  pub fn alt_text(&self) -> Option<&str> {
+    self
+      .name
+      .as_deref()
+      .map(str::trim)
+      .filter(|name| !name.is_empty())
+  }
+
+  /// What sort of file this is, in a word: an image, a video, a sound or
+  /// some other file.
+  pub fn kind(&self) -> &'static str {
+    match self
+      .media_type
+      .as_deref()
+      .and_then(|mime| mime.split('/').next())
+    {
+      Some("image") => "Image",
+      Some("video") => "Video",
+      Some("audio") => "Audio",
+      _ => "File",
+    }
+  }
+
+  /// Whether this is a video or a sound, which a player can play.
+  pub fn is_playable(&self) -> bool {
+    matches!(self.kind(), "Video" | "Audio")
+  }
+
+  /// What's known of a video or sound beyond its url, as in
+  /// `5:30, 1920×1080`, if anything is.
+  pub fn details(&self) -> Option<String> {
+    if !self.is_playable() {
+      return None;
+    }
+
+    let size = match (self.width, self.height) {
+      (Some(width), Some(height)) => Some(format!("{}×{}", width, height)),
+      _ => None,
+    };
+    let details: Vec<String> =
+      self.duration.map(clock).into_iter().chain(size).collect();
+
+    (!details.is_empty()).then(|| details.join(", "))
+  }
+}
+
+/// A length of time in seconds as a clock shows it, as in `5:30` or
+/// `1:02:03`.
+pub fn clock(seconds: f64) -> String {
+  let seconds = seconds.max(0.0).round() as u64;
+  let (hours, minutes, seconds) =
+    (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+  match hours {
+    0 => format!("{}:{:02}", minutes, seconds),
+    _ => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+  }
+}
+
+/// Seconds from an ISO 8601 duration, as ActivityStreams writes them:
+/// `PT5M30S`, or `P1DT2H`.
+pub fn parse_duration(duration: &str) -> Option<f64> {
+  let rest = duration.trim().strip_prefix('P')?;
+  let (days, time) = match rest.split_once('T') {
+    Some((days, time)) => (days, time),
+    None => (rest, ""),
+  };
+
+  Some(
+    seconds(days, &[('W', 604_800.0), ('D', 86_400.0)])?
+      + seconds(time, &[('H', 3600.0), ('M', 60.0), ('S', 1.0)])?,
+  )
+}
+
+/// The seconds in part of a duration, of counts each followed by one of
+/// `units`, in the order given.
+fn seconds(mut part: &str, units: &[(char, f64)]) -> Option<f64> {
+  let mut seconds = 0.0;
+
+  for (unit, scale) in units {
+    if let Some((count, rest)) = part.split_once(*unit) {
+      seconds += count.parse::<f64>().ok()? * scale;
+      part = rest;
+    }
+  }
+
+  part.is_empty().then_some(seconds)
+}
+
+/// Escapes plain text so html reads it as text rather than markup.
+pub(crate) fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+/// Turns a plain-text post into the html posts are kept as, one paragraph
+/// per blank-line-separated block.
+#[cfg(any(feature = "bsky", feature = "nostr"))]
+pub(crate) fn text_to_html(text: &str) -> String {
+  text
+    .split("\n\n")
+    .map(str::trim)
+    .filter(|paragraph| !paragraph.is_empty())
+    .map(|paragraph| {
+      format!("<p>{}</p>", escape(paragraph).replace('\n', "<br>"))
+    })
+    .collect()
+}
+
+/// Reads a field which servers send either as a single value or as a list
+/// of them. GoToSocial, for one, sends a lone tag or attachment bare.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+  D: Deserializer<'de>,
+  T: Deserialize<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+  }
+
+  Ok(match OneOrMany::deserialize(deserializer)? {
+    OneOrMany::One(one) => vec![one],
+    OneOrMany::Many(many) => many,
+  })
+}
+
+/// Reads an object's url, which servers send either bare or as the object
+/// itself. Anything else reads as no url at all.
+fn id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Id {
+    Bare(String),
+    Object { id: String },
+    Other(serde::de::IgnoredAny),
+  }
+
+  Ok(match Id::deserialize(deserializer)? {
+    Id::Bare(url) | Id::Object { id: url } => Some(url),
+    Id::Other(_) => None,
+  })
+}
+
+/// Reads a url which servers send either bare or as a link object, or as a
+/// list of those. Pleroma, for one, sends attachment urls as lists of links.
+/// A link to a page, which PeerTube gives as a list of links to the page
+/// and the files of its video; the page is the one that's html.
+fn page<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Url {
+    Bare(String),
+    Link {
+      href: String,
+      #[serde(rename = "mediaType")]
+      media_type: Option<String>,
+    },
+    Many(Vec<Url>),
+  }
+
+  let links = match Option::<Url>::deserialize(deserializer)? {
+    Some(Url::Many(many)) => many,
+    Some(page) => vec![page],
+    None => return Ok(None),
+  };
+  let links: Vec<(String, Option<String>)> = links
+    .into_iter()
+    .filter_map(|link| match link {
+      Url::Bare(href) => Some((href, None)),
+      Url::Link { href, media_type } => Some((href, media_type)),
+      Url::Many(_) => None,
+    })
+    .collect();
+  let html = links
+    .iter()
+    .find(|(_, media_type)| media_type.as_deref() == Some("text/html"));
+
+  Ok(html.or(links.first()).map(|(href, _)| href.clone()))
+}
+
+/// A duration, as ISO 8601 text or, as some servers write it, a number of
+/// seconds. Durations which are neither are left out.
+fn duration<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Duration {
+    Seconds(f64),
+    Text(String),
+    Other(serde::de::IgnoredAny),
+  }
+
+  Ok(match Option::<Duration>::deserialize(deserializer)? {
+    Some(Duration::Seconds(seconds)) => Some(seconds),
+    Some(Duration::Text(text)) => parse_duration(&text),
+    Some(Duration::Other(_)) | None => None,
+  })
+}
+
+fn href<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Href {
+    Bare(String),
+    Link { href: String },
+    Many(Vec<Href>),
+  }
+
+  fn first(href: Href) -> Option<String> {
+    match href {
+      Href::Bare(url) | Href::Link { href: url } => Some(url),
+      Href::Many(many) => many.into_iter().find_map(first),
+    }
+  }
+
+  Ok(Option::<Href>::deserialize(deserializer)?.and_then(first))
+}
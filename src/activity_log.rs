@@ -0,0 +1,97 @@
+//! Keeping every activity fetched, as it came, in a JSON Lines file.
+//!
+//! Given `--log-activities fetched.jsonl`, each activity in each outbox
+//! page apread fetches is appended to the file as one line:
+//!
+//! ```json
+//! {"fetched_at":"2023-02-14T18:03:11+00:00","source":"https://example.social/users/alice/outbox?page=true","activity":{"type":"Create",...}}
+//! ```
+//!
+//! The activity is exactly as the server sent it, so programs reading the
+//! log see everything apread ignores. The file is only ever appended to,
+//! so it can be shared by runs (and tailed while they write to it).
+
+use std::{
+  fs::{File, OpenOptions},
+  io::{self, Write},
+  path::Path,
+  sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// An append-only log of fetched activities, shared between a client's
+/// clones.
+#[derive(Clone, Debug)]
+pub struct ActivityLog {
+  file: Arc<Mutex<File>>,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+  fetched_at: String,
+  source: &'a str,
+  activity: &'a Value,
+}
+
+impl ActivityLog {
+  /// Opens the log at a path for appending, creating it if it's not there.
+  pub fn open(path: &Path) -> io::Result<Self> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+      std::fs::create_dir_all(dir)?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    Ok(Self {
+      file: Arc::new(Mutex::new(file)),
+    })
+  }
+
+  /// Appends the activities in a document fetched from `source`: the items
+  /// of a collection page, or of a collection's inline first page.
+  /// Anything else holds no activities, and isn't logged.
+  pub fn record(&self, source: &str, body: &str) -> io::Result<()> {
+    let Ok(document) = serde_json::from_str::<Value>(body) else {
+      return Ok(());
+    };
+    let fetched_at = chrono::Utc::now().to_rfc3339();
+    let mut lines = String::new();
+
+    for activity in activities(&document) {
+      let line = Line {
+        fetched_at: fetched_at.clone(),
+        source,
+        activity,
+      };
+
+      lines.push_str(&serde_json::to_string(&line)?);
+      lines.push('\n');
+    }
+
+    if lines.is_empty() {
+      return Ok(());
+    }
+
+    // Written all at once, so lines from clients writing at the same time
+    // never interleave.
+    let mut file = self.file.lock().expect("activity log lock poisoned");
+
+    file.write_all(lines.as_bytes())
+  }
+}
+
+/// The activities a document holds, leaving out those only linked to.
+fn activities(document: &Value) -> impl Iterator<Item = &Value> {
+  let page = match document.get("first") {
+    Some(first @ Value::Object(_)) => first,
+    _ => document,
+  };
+
+  ["orderedItems", "items"]
+    .into_iter()
+    .filter_map(|key| page.get(key)?.as_array())
+    .flatten()
+    .filter(|item| item.is_object())
+}
@@ -0,0 +1,135 @@
+//! Noticing when an account suddenly posts far more than usual, as a
+//! status account does when something breaks.
+//!
+//! `apread watch` counts each account's posts over a sliding window, and
+//! raises an alert when there are more than the `[posting_alert]` table
+//! allows:
+//!
+//! ```toml
+//! [posting_alert]
+//! posts = 10
+//! within = 3600
+//! command = "notify-send 'apread' \"$(jq -r .message)\""
+//! webhook = { url = "https://hooks.example/incidents" }
+//! ```
+//!
+//! Each alert is printed, and handed as JSON to the `command` on its stdin
+//! and to the `webhook`, either of which can be left out. An account
+//! alerts once per spike: not again until its posting has fallen back
+//! under the limit.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{hooks::Webhook, timeline::Entry};
+
+/// The seconds posts are counted over, unless the alert says otherwise.
+pub const DEFAULT_WITHIN: u64 = 3600;
+
+/// How much posting calls for an alert, and where to send it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct PostingAlert {
+  /// Alert when an account posts more than this many times...
+  pub posts: usize,
+  /// ...within this many seconds.
+  #[serde(default = "default_within")]
+  pub within: u64,
+  /// A command to run for each alert, with the alert as JSON on its
+  /// stdin.
+  pub command: Option<String>,
+  /// A webhook to POST each alert to, as JSON.
+  pub webhook: Option<Webhook>,
+}
+
+fn default_within() -> u64 {
+  DEFAULT_WITHIN
+}
+
+/// An account posting more than its alert allows.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct Spike {
+  /// The account, as it's written in subscriptions.
+  pub account: String,
+  /// How many posts it made within the window.
+  pub posts: usize,
+  /// The window, in seconds.
+  pub within: u64,
+  /// A sentence saying all that, for notifications.
+  pub message: String,
+}
+
+/// When each watched account posted lately.
+#[derive(Debug)]
+pub struct Rates {
+  posts: usize,
+  within: Duration,
+  seen: HashMap<String, VecDeque<DateTime<Utc>>>,
+  alerting: HashSet<String>,
+}
+
+impl Rates {
+  /// Rates to check against an alert's limit.
+  pub fn new(alert: &PostingAlert) -> Self {
+    let within = u32::try_from(alert.within).unwrap_or(u32::MAX);
+
+    Self {
+      posts: alert.posts,
+      within: Duration::seconds(i64::from(within)),
+      seen: HashMap::new(),
+      alerting: HashSet::new(),
+    }
+  }
+
+  /// Counts newly seen entries, and gives back a spike for each account
+  /// which has just gone over the limit, as of `now`.
+  pub fn observe(
+    &mut self,
+    entries: &[Entry],
+    now: DateTime<Utc>,
+  ) -> Vec<Spike> {
+    for entry in entries {
+      if let Ok(published) = DateTime::parse_from_rfc3339(&entry.published) {
+        self
+          .seen
+          .entry(entry.author.source.to_string())
+          .or_default()
+          .push_back(published.with_timezone(&Utc));
+      }
+    }
+
+    let since = now - self.within;
+    let mut spikes = vec![];
+
+    for (account, times) in &mut self.seen {
+      times.retain(|time| *time > since);
+
+      let posts = times.len();
+
+      if posts <= self.posts {
+        self.alerting.remove(account);
+        continue;
+      }
+
+      if self.alerting.insert(account.clone()) {
+        spikes.push(Spike {
+          account: account.clone(),
+          posts,
+          within: self.within.num_seconds().unsigned_abs(),
+          message: format!(
+            "{} posted {} times in the last {} minutes",
+            account,
+            posts,
+            self.within.num_minutes()
+          ),
+        });
+      }
+    }
+
+    self.seen.retain(|_, times| !times.is_empty());
+    spikes.sort_by(|a, b| a.account.cmp(&b.account));
+
+    spikes
+  }
+}
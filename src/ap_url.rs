@@ -0,0 +1,144 @@
+//! Urls found in ActivityPub documents, checked as they're read.
+//!
+//! Servers are trusted to say where an account's posts are, but not to
+//! say it well: an outbox url might be relative, empty, or on another
+//! server altogether. Reading those links as [`ApUrl`]s (rather than as
+//! plain strings) means a bad one is caught where it's found, rather than
+//! when reqwest fails to fetch it.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+use url::Url;
+
+use crate::i18n::{self, FluentValue, Localized};
+
+/// Raised when a document links to somewhere it shouldn't, or can't.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{}", i18n::english(self))]
+pub struct BadUrl {
+  /// The url, as given.
+  pub url: String,
+  /// What's wrong with it.
+  pub reason: &'static str,
+}
+
+impl Localized for BadUrl {
+  fn id(&self) -> &'static str {
+    "error-bad-url"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![
+      ("url", self.url.as_str().into()),
+      ("reason", self.reason.into()),
+    ]
+  }
+}
+
+/// An absolute http or https url, as linked from an ActivityPub document.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApUrl(Url);
+
+impl ApUrl {
+  /// Reads a url, which must be absolute, http or https, and name a host.
+  pub fn parse(text: &str) -> Result<Self, BadUrl> {
+    let bad = |reason| BadUrl {
+      url: text.to_owned(),
+      reason,
+    };
+    let url = Url::parse(text).map_err(|_| bad("not an absolute url"))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+      return Err(bad("not http or https"));
+    }
+
+    if url.host_str().is_none() {
+      return Err(bad("no host"));
+    }
+
+    Ok(Self(url))
+  }
+
+  /// The url, as text.
+  pub fn as_str(&self) -> &str {
+    self.0.as_str()
+  }
+
+  /// The url's host, which every `ApUrl` has.
+  pub fn host(&self) -> &str {
+    self.0.host_str().unwrap_or_default()
+  }
+
+  /// The url's host, with its port if it isn't the scheme's usual one, as
+  /// handles write their domain.
+  pub fn authority(&self) -> String {
+    match self.0.port() {
+      Some(port) => format!("{}:{}", self.host(), port),
+      None => self.host().to_owned(),
+    }
+  }
+
+  /// Whether the url is a Tor onion service's.
+  pub fn is_onion(&self) -> bool {
+    crate::handle::onion(self.host())
+  }
+
+  /// Whether the url is https.
+  pub fn is_https(&self) -> bool {
+    self.0.scheme() == "https"
+  }
+
+  /// Whether the url is on the same server as another: the same scheme,
+  /// host and port.
+  pub fn same_origin(&self, other: &ApUrl) -> bool {
+    self.0.origin() == other.0.origin()
+  }
+
+  /// Checks the url is https.
+  pub fn check_https(&self) -> Result<(), BadUrl> {
+    match self.is_https() {
+      true => Ok(()),
+      false => Err(self.bad("not https")),
+    }
+  }
+
+  /// Checks the url is on the same server as `base`, the document linking
+  /// to it.
+  pub fn check_origin(&self, base: &ApUrl) -> Result<(), BadUrl> {
+    match self.same_origin(base) {
+      true => Ok(()),
+      false => Err(self.bad("on a different server from what links to it")),
+    }
+  }
+
+  fn bad(&self, reason: &'static str) -> BadUrl {
+    BadUrl {
+      url: self.to_string(),
+      reason,
+    }
+  }
+}
+
+impl fmt::Display for ApUrl {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl AsRef<str> for ApUrl {
+  fn as_ref(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl<'de> Deserialize<'de> for ApUrl {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    let text = String::deserialize(deserializer)?;
+
+    Self::parse(&text).map_err(serde::de::Error::custom)
+  }
+}
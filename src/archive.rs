@@ -0,0 +1,1470 @@
+//! The local archive: a SQLite database of posts kept for offline reading.
+
+use std::{
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+  sync::Mutex,
+};
+
+use chrono::DateTime;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use thiserror::Error;
+
+use crate::{
+  activity::{Attachment, Post},
+  compose::Visibility,
+  i18n::{self, FluentValue, Localized},
+  links::Health,
+  profile::{Profile, Snapshot},
+  storage::{Store, Stored},
+  timeline::{self, Author, Source, Walk},
+  ApreadErrors, Client,
+};
+
+/// Schema changes, applied in order. A database's `user_version` records how
+/// many of these it has seen, so only add to the end of this list.
+const MIGRATIONS: &[&str] = &[
+  "
+  CREATE TABLE posts (
+    id TEXT PRIMARY KEY,
+    url TEXT,
+    author TEXT,
+    published TEXT,
+    summary TEXT,
+    content TEXT NOT NULL,
+    archived_at TEXT NOT NULL
+  );
+  CREATE TABLE bookmarks (
+    post_id TEXT PRIMARY KEY REFERENCES posts (id),
+    note TEXT,
+    created_at TEXT NOT NULL
+  );
+  CREATE TABLE bookmark_tags (
+    post_id TEXT NOT NULL REFERENCES bookmarks (post_id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (post_id, tag)
+  );
+",
+  "
+  CREATE TABLE queue (
+    position INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,
+    title TEXT,
+    author TEXT,
+    content TEXT NOT NULL,
+    added_at TEXT NOT NULL
+  );
+",
+  "
+  CREATE TABLE post_tags (
+    post_id TEXT NOT NULL REFERENCES posts (id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (post_id, tag)
+  );
+",
+  "
+  CREATE TABLE objects (
+    id TEXT PRIMARY KEY,
+    document TEXT NOT NULL,
+    fetched_at TEXT NOT NULL
+  );
+",
+  "
+  CREATE TABLE profiles (
+    actor TEXT NOT NULL,
+    profile TEXT NOT NULL,
+    taken_at TEXT NOT NULL
+  );
+  CREATE INDEX profiles_by_actor ON profiles (actor, taken_at);
+",
+  "
+  ALTER TABLE posts ADD COLUMN deleted_at TEXT;
+",
+  "
+  ALTER TABLE posts ADD COLUMN updated TEXT;
+  CREATE TABLE revisions (
+    post_id TEXT NOT NULL REFERENCES posts (id),
+    summary TEXT,
+    content TEXT NOT NULL,
+    edited_at TEXT,
+    archived_at TEXT NOT NULL
+  );
+",
+  "
+  CREATE TABLE backfills (
+    author TEXT PRIMARY KEY,
+    next TEXT NOT NULL,
+    saved_at TEXT NOT NULL
+  );
+",
+  "
+  CREATE TABLE drafts (
+    id INTEGER PRIMARY KEY,
+    text TEXT NOT NULL,
+    spoiler_text TEXT,
+    visibility TEXT NOT NULL,
+    saved_at TEXT NOT NULL
+  );
+",
+  "
+  CREATE TABLE boosts (
+    author TEXT NOT NULL,
+    post_id TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    PRIMARY KEY (author, post_id)
+  );
+",
+  "
+  CREATE TABLE post_keywords (
+    post_id TEXT NOT NULL REFERENCES posts (id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (post_id, tag)
+  );
+",
+  "
+  ALTER TABLE posts ADD COLUMN visibility TEXT;
+  CREATE TABLE post_audience (
+    post_id TEXT NOT NULL REFERENCES posts (id),
+    field TEXT NOT NULL,
+    address TEXT NOT NULL,
+    PRIMARY KEY (post_id, field, address)
+  );
+  CREATE TABLE post_attachments (
+    post_id TEXT NOT NULL REFERENCES posts (id),
+    position INTEGER NOT NULL,
+    media_type TEXT,
+    url TEXT,
+    name TEXT,
+    blurhash TEXT,
+    width INTEGER,
+    height INTEGER,
+    duration REAL,
+    PRIMARY KEY (post_id, position)
+  );
+",
+  "
+  ALTER TABLE posts ADD COLUMN checked_at TEXT;
+",
+  "
+  ALTER TABLE posts ADD COLUMN actor TEXT;
+",
+  "
+  ALTER TABLE posts ADD COLUMN imported INTEGER NOT NULL DEFAULT 0;
+",
+];
+
+/// How many posts older than the stretch of an outbox read are checked with
+/// their server each sync, unless asked for another number.
+pub const CHECKS: usize = 20;
+
+/// The columns [`read_post`] expects, in order, from a `posts` table
+/// aliased as `p`.
+const POST_COLUMNS: &str = "p.id, p.url, p.author, p.published, p.summary, p.content, p.visibility, p.actor";
+
+/// Picks out the posts whose authors are on the domain in `?1`, ignoring
+/// case.
+const ON_DOMAIN: &str =
+  "lower(substr(p.author, -length(?1) - 1)) = '@' || lower(?1)";
+
+/// Raised when a post has no id to file it under.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoPostId;
+
+impl Localized for NoPostId {
+  fn id(&self) -> &'static str {
+    "error-no-post-id"
+  }
+}
+
+/// Raised when asked about a post the archive doesn't have.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NotArchived(pub String);
+
+impl Localized for NotArchived {
+  fn id(&self) -> &'static str {
+    "error-not-archived"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("post", self.0.as_str().into())]
+  }
+}
+
+/// Raised when asked for a draft there isn't.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoSuchDraft(pub i64);
+
+impl Localized for NoSuchDraft {
+  fn id(&self) -> &'static str {
+    "error-no-such-draft"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("number", self.0.into())]
+  }
+}
+
+/// Raised when asked to backfill something which has no outbox, such as a
+/// feed.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NotAnAccount(pub String);
+
+impl Localized for NotAnAccount {
+  fn id(&self) -> &'static str {
+    "error-not-an-account"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("source", self.0.as_str().into())]
+  }
+}
+
+/// Raised when there's no platform data directory to keep the archive in.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoDataDir;
+
+impl Localized for NoDataDir {
+  fn id(&self) -> &'static str {
+    "error-no-data-dir"
+  }
+}
+
+/// A post saved in the archive.
+#[derive(Clone, Debug)]
+pub struct ArchivedPost {
+  /// The post's ActivityPub id.
+  pub id: String,
+  /// The post's web page, where there is one.
+  pub url: Option<String>,
+  /// Who wrote the post, as a handle where we could find one.
+  pub author: Option<String>,
+  /// Who wrote the post, as their actor's id, where the post gave it.
+  pub actor: Option<String>,
+  /// When the post was published.
+  pub published: Option<String>,
+  /// The post's content warning, if it has one.
+  pub summary: Option<String>,
+  /// The post body, as html.
+  pub content: String,
+  /// The post's hashtags, without their `#`.
+  pub hashtags: Vec<String>,
+  /// Images and other files attached to the post.
+  pub attachments: Vec<Attachment>,
+  /// Who the post is shown to, if it said when it was archived.
+  pub visibility: Option<Visibility>,
+  /// Who the post is addressed to.
+  pub to: Vec<String>,
+  /// Who the post is copied to.
+  pub cc: Vec<String>,
+}
+
+impl ArchivedPost {
+  /// A post as it would be archived, for posts fetched but not yet stored.
+  /// Posts without an id can't be archived, so give `None`.
+  pub fn from_post(post: &Post, author: Option<&str>) -> Option<Self> {
+    Some(Self {
+      id: post.id.clone()?,
+      url: post.url.clone(),
+      author: author.map(str::to_owned),
+      actor: post.attributed_to.clone(),
+      published: post.published.clone(),
+      summary: post.summary.clone(),
+      content: post.content.clone(),
+      hashtags: post.hashtags().into_iter().map(str::to_owned).collect(),
+      attachments: post.attachment.clone(),
+      visibility: post.visibility(),
+      to: post.to.clone(),
+      cc: post.cc.clone(),
+    })
+  }
+
+  /// The post body, converted from html to markdown.
+  pub fn markdown_content(&self) -> String {
+    html2md::parse_html(&self.content)
+  }
+}
+
+/// A bookmarked post, with whatever the reader filed it with.
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+  /// The post itself.
+  pub post: ArchivedPost,
+  /// Tags to find the bookmark by later.
+  pub tags: Vec<String>,
+  /// A note about why the post was worth saving.
+  pub note: Option<String>,
+  /// When the bookmark was made.
+  pub created_at: String,
+}
+
+/// One version of an archived post, as it was before (or since) an edit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Revision {
+  /// The post's content warning, in this version.
+  pub summary: Option<String>,
+  /// The post body, as html, in this version.
+  pub content: String,
+  /// When this version was written: the post's edit, or failing that its
+  /// publication.
+  pub edited_at: Option<String>,
+}
+
+impl Revision {
+  /// The post body, converted from html to markdown.
+  pub fn markdown_content(&self) -> String {
+    html2md::parse_html(&self.content)
+  }
+}
+
+/// An archived post which has since been deleted from its server.
+#[derive(Clone, Debug)]
+pub struct Deleted {
+  /// The post, as it was archived.
+  pub post: ArchivedPost,
+  /// When the post was found to be gone.
+  pub deleted_at: String,
+}
+
+/// What syncing an account into the archive found.
+#[derive(Clone, Debug, Default)]
+pub struct Synced {
+  /// How many posts were read from the outbox and archived.
+  pub archived: usize,
+  /// The ids of the archived posts found to have been deleted.
+  pub deleted: Vec<String>,
+}
+
+/// How far a backfill got.
+#[derive(Clone, Debug, Default)]
+pub struct Backfilled {
+  /// How many pages of the outbox were read this time.
+  pub pages: usize,
+  /// How many posts were archived from them.
+  pub archived: usize,
+  /// Whether the oldest page has been reached. If not, the next backfill
+  /// carries on from where this one stopped.
+  pub complete: bool,
+}
+
+/// A post being written, kept until it's sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedDraft {
+  /// The draft's number, for picking it out.
+  pub id: i64,
+  /// The post's text.
+  pub text: String,
+  /// Its content warning, if it has one.
+  pub spoiler_text: Option<String>,
+  /// Who it's to be shown to.
+  pub visibility: Visibility,
+  /// When it was last saved.
+  pub saved_at: String,
+}
+
+/// Something saved to the read-it-later queue: a post, or an article a post
+/// linked to.
+#[derive(Clone, Debug)]
+pub struct QueuedItem {
+  /// Where the item was read from.
+  pub url: String,
+  /// The item's title, for articles that have one.
+  pub title: Option<String>,
+  /// Who wrote the item, where we know.
+  pub author: Option<String>,
+  /// The item's content, as html.
+  pub content: String,
+}
+
+/// A handle on the archive database.
+#[derive(Debug)]
+pub struct Archive {
+  connection: Connection,
+}
+
+impl Archive {
+  /// Opens the archive in the platform data directory.
+  pub fn open_default() -> Result<Self, ApreadErrors> {
+    Self::open_in(&data_dir()?)
+  }
+
+  /// Opens the archive in a data directory, creating it as needed.
+  pub fn open_in(dir: &Path) -> Result<Self, ApreadErrors> {
+    fs::create_dir_all(dir)?;
+
+    Self::open(dir.join("archive.sqlite3"))
+  }
+
+  /// Opens (creating and migrating as needed) the archive at a path.
+  pub fn open(path: impl AsRef<Path>) -> Result<Self, ApreadErrors> {
+    let mut archive = Self {
+      connection: Connection::open(path)?,
+    };
+
+    archive.migrate()?;
+
+    Ok(archive)
+  }
+
+  fn migrate(&mut self) -> Result<(), ApreadErrors> {
+    let version: usize =
+      self
+        .connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+      let transaction = self.connection.transaction()?;
+      transaction.execute_batch(migration)?;
+      transaction.pragma_update(None, "user_version", index + 1)?;
+      transaction.commit()?;
+    }
+
+    Ok(())
+  }
+
+  /// Saves a post, replacing any earlier copy of it. An earlier copy which
+  /// reads differently is kept as a revision.
+  pub fn store(
+    &self,
+    post: &Post,
+    author: Option<&str>,
+  ) -> Result<String, ApreadErrors> {
+    let id = post.id.clone().ok_or(NoPostId)?;
+
+    self.connection.execute(
+      "INSERT INTO revisions (post_id, summary, content, edited_at, archived_at)
+       SELECT id, summary, content, coalesce(updated, published), archived_at
+       FROM posts WHERE id = ?1 AND (summary IS NOT ?2 OR content IS NOT ?3)",
+      params![id, post.summary, post.content],
+    )?;
+    self.connection.execute(
+      "INSERT INTO posts (
+         id, url, author, published, updated, summary, content, archived_at,
+         visibility, actor
+       )
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+       ON CONFLICT (id) DO UPDATE SET
+         url = excluded.url,
+         author = coalesce(excluded.author, posts.author),
+         published = excluded.published,
+         updated = excluded.updated,
+         summary = excluded.summary,
+         content = excluded.content,
+         archived_at = excluded.archived_at,
+         visibility = coalesce(excluded.visibility, posts.visibility),
+         actor = coalesce(excluded.actor, posts.actor),
+         deleted_at = NULL",
+      params![
+        id,
+        post.url,
+        author,
+        post.published,
+        post.updated,
+        post.summary,
+        post.content,
+        now(),
+        post.visibility().map(Visibility::name),
+        post.attributed_to,
+      ],
+    )?;
+
+    self
+      .connection
+      .execute("DELETE FROM post_tags WHERE post_id = ?1", params![id])?;
+
+    for tag in post.hashtags() {
+      self.connection.execute(
+        "INSERT OR IGNORE INTO post_tags (post_id, tag) VALUES (?1, ?2)",
+        params![id, tag],
+      )?;
+    }
+
+    self.store_attachments(&id, post)?;
+
+    #[cfg(feature = "topics")]
+    self.tag_keywords(
+      &id,
+      &crate::topics::keywords(&post.content, &post.hashtags()),
+    )?;
+
+    Ok(id)
+  }
+
+  /// Saves a post read from an account export, as [`Archive::store`] does,
+  /// noting it was imported. An export holds posts the author's outbox
+  /// doesn't show everyone, so syncing never takes those for deleted.
+  pub fn import(
+    &self,
+    post: &Post,
+    author: Option<&str>,
+  ) -> Result<String, ApreadErrors> {
+    let id = self.store(post, author)?;
+
+    self
+      .connection
+      .execute("UPDATE posts SET imported = 1 WHERE id = ?1", params![id])?;
+
+    Ok(id)
+  }
+
+  /// Keeps a post's attachments and addressing, replacing what it had.
+  /// Copies of a post which don't say who it's to, as a boost fetched
+  /// through an API can, leave its addressing as it was.
+  fn store_attachments(
+    &self,
+    id: &str,
+    post: &Post,
+  ) -> Result<(), ApreadErrors> {
+    self.connection.execute(
+      "DELETE FROM post_attachments WHERE post_id = ?1",
+      params![id],
+    )?;
+
+    for (position, attachment) in post.attachment.iter().enumerate() {
+      self.connection.execute(
+        "INSERT INTO post_attachments (
+           post_id, position, media_type, url, name, blurhash, width, height,
+           duration
+         )
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+          id,
+          position,
+          attachment.media_type,
+          attachment.url,
+          attachment.name,
+          attachment.blurhash,
+          attachment.width,
+          attachment.height,
+          attachment.duration,
+        ],
+      )?;
+    }
+
+    if post.to.is_empty() && post.cc.is_empty() {
+      return Ok(());
+    }
+
+    self
+      .connection
+      .execute("DELETE FROM post_audience WHERE post_id = ?1", params![id])?;
+
+    for (field, addresses) in [("to", &post.to), ("cc", &post.cc)] {
+      for address in addresses {
+        self.connection.execute(
+          "INSERT OR IGNORE INTO post_audience (post_id, field, address)
+           VALUES (?1, ?2, ?3)",
+          params![id, field, address],
+        )?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Fills in what [`read_post`] leaves out of an archived post: its
+  /// hashtags, attachments and addressing.
+  fn fill(&self, post: &mut ArchivedPost) -> Result<(), ApreadErrors> {
+    post.hashtags = self.tags("post_tags", &post.id)?;
+
+    let mut statement = self.connection.prepare(
+      "SELECT media_type, url, name, blurhash, width, height, duration
+       FROM post_attachments WHERE post_id = ?1 ORDER BY position",
+    )?;
+    post.attachments = statement
+      .query_map(params![post.id], |row| {
+        Ok(Attachment {
+          media_type: row.get(0)?,
+          url: row.get(1)?,
+          name: row.get(2)?,
+          blurhash: row.get(3)?,
+          width: row.get(4)?,
+          height: row.get(5)?,
+          duration: row.get(6)?,
+          hidden: false,
+          saved: None,
+          thumbnail: None,
+        })
+      })?
+      .collect::<Result<_, _>>()?;
+
+    let mut statement = self.connection.prepare(
+      "SELECT field, address FROM post_audience WHERE post_id = ?1
+       ORDER BY rowid",
+    )?;
+    let audience = statement
+      .query_map(params![post.id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get(1)?))
+      })?
+      .collect::<Result<Vec<_>, _>>()?;
+
+    for (field, address) in audience {
+      match field.as_str() {
+        "to" => post.to.push(address),
+        _ => post.cc.push(address),
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Tags an archived post with its keywords, replacing any it had.
+  #[cfg(feature = "topics")]
+  fn tag_keywords(
+    &self,
+    id: &str,
+    keywords: &[String],
+  ) -> Result<(), ApreadErrors> {
+    self
+      .connection
+      .execute("DELETE FROM post_keywords WHERE post_id = ?1", params![id])?;
+
+    for keyword in keywords {
+      self.connection.execute(
+        "INSERT OR IGNORE INTO post_keywords (post_id, tag) VALUES (?1, ?2)",
+        params![id, keyword],
+      )?;
+    }
+
+    Ok(())
+  }
+
+  /// The keywords an archived post was tagged with, or none if it hasn't
+  /// been tagged: it isn't archived, or was archived by a build without
+  /// the `topics` feature.
+  pub fn keywords(
+    &self,
+    id: &str,
+  ) -> Result<Option<Vec<String>>, ApreadErrors> {
+    let keywords = self.tags("post_keywords", id)?;
+
+    Ok((!keywords.is_empty()).then_some(keywords))
+  }
+
+  /// Every archived post, newest first.
+  pub fn posts(&self) -> Result<Vec<ArchivedPost>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT {} FROM posts p ORDER BY p.published DESC",
+      POST_COLUMNS
+    ))?;
+    let mut posts = statement
+      .query_map([], read_post)?
+      .collect::<Result<Vec<_>, _>>()?;
+
+    for post in &mut posts {
+      self.fill(post)?;
+    }
+
+    Ok(posts)
+  }
+
+  /// The accounts on a domain with posts archived, as `id@domain`, each
+  /// with how many, the most first.
+  pub fn accounts_on(
+    &self,
+    domain: &str,
+  ) -> Result<Vec<(String, usize)>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT p.author, count(*) FROM posts p WHERE {}
+       GROUP BY p.author ORDER BY count(*) DESC, p.author",
+      ON_DOMAIN
+    ))?;
+    let accounts = statement
+      .query_map(params![domain], |row| Ok((row.get(0)?, row.get(1)?)))?
+      .collect::<Result<_, _>>()?;
+
+    Ok(accounts)
+  }
+
+  /// The archived posts of the accounts on a domain, newest first, at most
+  /// `limit` of them if there's a limit.
+  pub fn posts_on(
+    &self,
+    domain: &str,
+    limit: Option<usize>,
+  ) -> Result<Vec<ArchivedPost>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT {} FROM posts p WHERE {}
+       ORDER BY p.published DESC LIMIT ?2",
+      POST_COLUMNS, ON_DOMAIN
+    ))?;
+    // SQLite takes a negative limit as none.
+    let limit = limit
+      .and_then(|limit| i64::try_from(limit).ok())
+      .unwrap_or(-1);
+    let mut posts = statement
+      .query_map(params![domain, limit], read_post)?
+      .collect::<Result<Vec<_>, _>>()?;
+
+    for post in &mut posts {
+      self.fill(post)?;
+    }
+
+    Ok(posts)
+  }
+
+  /// The archived posts by an author not yet known to be deleted, those
+  /// checked with their server longest ago first, then newest first.
+  /// Imported posts which weren't public are left out, since their outbox
+  /// was never going to show them.
+  fn standing(&self, author: &str) -> Result<Vec<ArchivedPost>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT {} FROM posts p WHERE p.author = ?1 AND p.deleted_at IS NULL
+         AND (NOT p.imported OR p.visibility IN ('public', 'unlisted'))
+       ORDER BY p.checked_at IS NOT NULL, p.checked_at, p.published DESC",
+      POST_COLUMNS
+    ))?;
+    let posts = statement
+      .query_map(params![author], read_post)?
+      .collect::<Result<_, _>>()?;
+
+    Ok(posts)
+  }
+
+  /// The hashtags of every archived post not known to be deleted, as its
+  /// author and the tag, once for each post using it.
+  pub fn hashtags_by_author(
+    &self,
+  ) -> Result<Vec<(String, String)>, ApreadErrors> {
+    let mut statement = self.connection.prepare(
+      "SELECT p.author, t.tag FROM post_tags t JOIN posts p ON p.id = t.post_id
+       WHERE p.author IS NOT NULL AND p.deleted_at IS NULL
+       ORDER BY p.author, t.tag",
+    )?;
+    let hashtags = statement
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+      .collect::<Result<_, _>>()?;
+
+    Ok(hashtags)
+  }
+
+  /// Every version archived of a post, found by its id or url, oldest first
+  /// and ending with the one archived last.
+  pub fn history(&self, post: &str) -> Result<Vec<Revision>, ApreadErrors> {
+    let read_revision = |row: &Row| {
+      Ok(Revision {
+        summary: row.get(1)?,
+        content: row.get(2)?,
+        edited_at: row.get(3)?,
+      })
+    };
+    let (id, current): (String, Revision) = self
+      .connection
+      .query_row(
+        "SELECT id, summary, content, coalesce(updated, published) FROM posts
+         WHERE id = ?1 OR url = ?1",
+        params![post],
+        |row| Ok((row.get(0)?, read_revision(row)?)),
+      )
+      .optional()?
+      .ok_or_else(|| NotArchived(post.to_owned()))?;
+    let mut statement = self.connection.prepare(
+      "SELECT post_id, summary, content, edited_at FROM revisions
+       WHERE post_id = ?1 ORDER BY rowid",
+    )?;
+    let mut revisions = statement
+      .query_map(params![id], read_revision)?
+      .collect::<Result<Vec<_>, _>>()?;
+
+    revisions.push(current);
+
+    Ok(revisions)
+  }
+
+  /// Marks an archived post as deleted from its server. It stays in the
+  /// archive, to be listed with the other deleted posts.
+  pub fn mark_deleted(&self, id: &str) -> Result<(), ApreadErrors> {
+    self.connection.execute(
+      "UPDATE posts SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+      params![id, now()],
+    )?;
+
+    Ok(())
+  }
+
+  /// Notes that an archived post has just been checked with its server.
+  fn mark_checked(&self, id: &str) -> Result<(), ApreadErrors> {
+    self.connection.execute(
+      "UPDATE posts SET checked_at = ?2 WHERE id = ?1",
+      params![id, now()],
+    )?;
+
+    Ok(())
+  }
+
+  /// Every archived post known to be deleted, most recently found first.
+  pub fn deleted(&self) -> Result<Vec<Deleted>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT {}, p.deleted_at FROM posts p WHERE p.deleted_at IS NOT NULL
+       ORDER BY p.deleted_at DESC",
+      POST_COLUMNS
+    ))?;
+    let mut deleted = statement
+      .query_map([], |row| {
+        Ok(Deleted {
+          post: read_post(row)?,
+          deleted_at: row.get(8)?,
+        })
+      })?
+      .collect::<Result<Vec<_>, _>>()?;
+
+    for found in &mut deleted {
+      self.fill(&mut found.post)?;
+    }
+
+    Ok(deleted)
+  }
+
+  /// Saves a post and bookmarks it. Bookmarking a post again replaces its
+  /// tags and note.
+  pub fn bookmark(
+    &mut self,
+    post: &Post,
+    author: Option<&str>,
+    tags: &[String],
+    note: Option<&str>,
+  ) -> Result<(), ApreadErrors> {
+    let id = self.store(post, author)?;
+    let transaction = self.connection.transaction()?;
+
+    transaction.execute(
+      "INSERT INTO bookmarks (post_id, note, created_at) VALUES (?1, ?2, ?3)
+       ON CONFLICT (post_id) DO UPDATE SET note = excluded.note",
+      params![id, note, now()],
+    )?;
+    transaction
+      .execute("DELETE FROM bookmark_tags WHERE post_id = ?1", params![id])?;
+
+    for tag in tags {
+      transaction.execute(
+        "INSERT OR IGNORE INTO bookmark_tags (post_id, tag) VALUES (?1, ?2)",
+        params![id, tag.trim_start_matches('#')],
+      )?;
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+  }
+
+  /// Bookmarks, newest first, optionally only those with a given tag.
+  pub fn bookmarks(
+    &self,
+    tag: Option<&str>,
+  ) -> Result<Vec<Bookmark>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT {}, b.note, b.created_at
+       FROM bookmarks b JOIN posts p ON p.id = b.post_id
+       WHERE ?1 IS NULL OR EXISTS (
+         SELECT 1 FROM bookmark_tags t WHERE t.post_id = b.post_id AND t.tag = ?1
+       )
+       ORDER BY b.created_at DESC",
+      POST_COLUMNS
+    ))?;
+
+    let rows = statement.query_map(
+      params![tag.map(|tag| tag.trim_start_matches('#'))],
+      |row| {
+        Ok(Bookmark {
+          post: read_post(row)?,
+          note: row.get(8)?,
+          created_at: row.get(9)?,
+          tags: vec![],
+        })
+      },
+    )?;
+
+    let mut bookmarks = rows.collect::<Result<Vec<_>, _>>()?;
+
+    for bookmark in &mut bookmarks {
+      bookmark.tags = self.tags("bookmark_tags", &bookmark.post.id)?;
+      self.fill(&mut bookmark.post)?;
+    }
+
+    Ok(bookmarks)
+  }
+
+  fn tags(&self, table: &str, id: &str) -> Result<Vec<String>, ApreadErrors> {
+    let mut statement = self.connection.prepare(&format!(
+      "SELECT tag FROM {} WHERE post_id = ?1 ORDER BY tag",
+      table
+    ))?;
+    let tags = statement
+      .query_map(params![id], |row| row.get(0))?
+      .collect::<Result<_, _>>()?;
+
+    Ok(tags)
+  }
+
+  /// A document fetched from elsewhere and kept, such as a boosted post, by
+  /// its id.
+  pub fn object(&self, id: &str) -> Result<Option<String>, ApreadErrors> {
+    Ok(
+      self
+        .connection
+        .query_row(
+          "SELECT document FROM objects WHERE id = ?1",
+          params![id],
+          |row| row.get(0),
+        )
+        .optional()?,
+    )
+  }
+
+  /// Keeps a document fetched from elsewhere, by its id, replacing any
+  /// earlier copy of it.
+  pub fn store_object(
+    &self,
+    id: &str,
+    document: &str,
+  ) -> Result<(), ApreadErrors> {
+    self.connection.execute(
+      "INSERT INTO objects (id, document, fetched_at) VALUES (?1, ?2, ?3)
+       ON CONFLICT (id) DO UPDATE SET
+         document = excluded.document,
+         fetched_at = excluded.fetched_at",
+      params![id, document, now()],
+    )?;
+
+    Ok(())
+  }
+
+  /// The outbox page a backfill of an author stopped before, to carry on
+  /// from.
+  pub fn checkpoint(
+    &self,
+    author: &str,
+  ) -> Result<Option<String>, ApreadErrors> {
+    Ok(
+      self
+        .connection
+        .query_row(
+          "SELECT next FROM backfills WHERE author = ?1",
+          params![author],
+          |row| row.get(0),
+        )
+        .optional()?,
+    )
+  }
+
+  /// Remembers the outbox page a backfill of an author is to carry on from,
+  /// or with `None`, that there's nothing left to carry on with.
+  pub fn save_checkpoint(
+    &self,
+    author: &str,
+    next: Option<&str>,
+  ) -> Result<(), ApreadErrors> {
+    match next {
+      Some(next) => self.connection.execute(
+        "INSERT INTO backfills (author, next, saved_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT (author) DO UPDATE SET
+           next = excluded.next,
+           saved_at = excluded.saved_at",
+        params![author, next, now()],
+      )?,
+      None => self
+        .connection
+        .execute("DELETE FROM backfills WHERE author = ?1", params![author])?,
+    };
+
+    Ok(())
+  }
+
+  /// Keeps a snapshot of an actor's profile, beside the earlier ones.
+  pub fn store_profile(
+    &self,
+    actor: &str,
+    profile: &Profile,
+  ) -> Result<(), ApreadErrors> {
+    self.connection.execute(
+      "INSERT INTO profiles (actor, profile, taken_at) VALUES (?1, ?2, ?3)",
+      params![actor, serde_json::to_string(profile)?, now()],
+    )?;
+
+    Ok(())
+  }
+
+  /// The latest snapshot kept of an actor's profile, if there is one.
+  pub fn profile(&self, actor: &str) -> Result<Option<Snapshot>, ApreadErrors> {
+    let kept: Option<(String, String)> = self
+      .connection
+      .query_row(
+        "SELECT profile, taken_at FROM profiles WHERE actor = ?1
+         ORDER BY taken_at DESC, rowid DESC LIMIT 1",
+        params![actor],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+      .optional()?;
+
+    match kept {
+      Some((profile, taken_at)) => Ok(Some(Snapshot {
+        profile: serde_json::from_str(&profile)?,
+        taken_at,
+      })),
+      None => Ok(None),
+    }
+  }
+
+  /// Adds an item to the end of the read-it-later queue.
+  pub fn enqueue(&self, item: &QueuedItem) -> Result<(), ApreadErrors> {
+    self.connection.execute(
+      "INSERT INTO queue (url, title, author, content, added_at)
+       VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![item.url, item.title, item.author, item.content, now()],
+    )?;
+
+    Ok(())
+  }
+
+  /// Everything in the queue, first in first.
+  pub fn queue(&self) -> Result<Vec<QueuedItem>, ApreadErrors> {
+    let mut statement = self.connection.prepare(
+      "SELECT url, title, author, content FROM queue ORDER BY position",
+    )?;
+    let items = statement
+      .query_map([], |row| {
+        Ok(QueuedItem {
+          url: row.get(0)?,
+          title: row.get(1)?,
+          author: row.get(2)?,
+          content: row.get(3)?,
+        })
+      })?
+      .collect::<Result<_, _>>()?;
+
+    Ok(items)
+  }
+
+  /// Takes the first item off the queue, if there is one.
+  pub fn pop(&mut self) -> Result<Option<QueuedItem>, ApreadErrors> {
+    let transaction = self.connection.transaction()?;
+    let item = transaction
+      .query_row(
+        "SELECT position, url, title, author, content FROM queue
+         ORDER BY position LIMIT 1",
+        [],
+        |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            QueuedItem {
+              url: row.get(1)?,
+              title: row.get(2)?,
+              author: row.get(3)?,
+              content: row.get(4)?,
+            },
+          ))
+        },
+      )
+      .optional()?;
+
+    if let Some((position, _)) = &item {
+      transaction
+        .execute("DELETE FROM queue WHERE position = ?1", params![position])?;
+    }
+
+    transaction.commit()?;
+
+    Ok(item.map(|(_, item)| item))
+  }
+
+  /// Keeps a new draft, giving back its number.
+  pub fn save_draft(
+    &self,
+    text: &str,
+    spoiler_text: Option<&str>,
+    visibility: Visibility,
+  ) -> Result<i64, ApreadErrors> {
+    self.connection.execute(
+      "INSERT INTO drafts (text, spoiler_text, visibility, saved_at)
+       VALUES (?1, ?2, ?3, ?4)",
+      params![text, spoiler_text, visibility.name(), now()],
+    )?;
+
+    Ok(self.connection.last_insert_rowid())
+  }
+
+  /// Replaces a draft's text, as it's rewritten.
+  pub fn update_draft(&self, id: i64, text: &str) -> Result<(), ApreadErrors> {
+    let updated = self.connection.execute(
+      "UPDATE drafts SET text = ?2, saved_at = ?3 WHERE id = ?1",
+      params![id, text, now()],
+    )?;
+
+    match updated {
+      0 => Err(NoSuchDraft(id).into()),
+      _ => Ok(()),
+    }
+  }
+
+  /// Every draft, oldest first.
+  pub fn drafts(&self) -> Result<Vec<SavedDraft>, ApreadErrors> {
+    let mut statement = self.connection.prepare(
+      "SELECT id, text, spoiler_text, visibility, saved_at FROM drafts
+       ORDER BY id",
+    )?;
+    let drafts = statement
+      .query_map([], read_draft)?
+      .collect::<Result<_, _>>()?;
+
+    Ok(drafts)
+  }
+
+  /// The draft with a number.
+  pub fn draft(&self, id: i64) -> Result<SavedDraft, ApreadErrors> {
+    self
+      .connection
+      .query_row(
+        "SELECT id, text, spoiler_text, visibility, saved_at FROM drafts
+         WHERE id = ?1",
+        params![id],
+        read_draft,
+      )
+      .optional()?
+      .ok_or_else(|| NoSuchDraft(id).into())
+  }
+
+  /// Notes that an author boosted a post, by the actor whose post it is.
+  /// Posts without an id or an author to count aren't noted.
+  pub fn store_boost(
+    &self,
+    author: &str,
+    post: &Post,
+  ) -> Result<(), ApreadErrors> {
+    let (Some(id), Some(actor)) = (&post.id, &post.attributed_to) else {
+      return Ok(());
+    };
+
+    self.connection.execute(
+      "INSERT OR IGNORE INTO boosts (author, post_id, actor)
+       VALUES (?1, ?2, ?3)",
+      params![author, id, actor],
+    )?;
+
+    Ok(())
+  }
+
+  /// Every boost noted, as the author who boosted and the actor url of the
+  /// account boosted.
+  pub fn boosts(&self) -> Result<Vec<(String, String)>, ApreadErrors> {
+    let mut statement = self
+      .connection
+      .prepare("SELECT author, actor FROM boosts ORDER BY author, actor")?;
+    let boosts = statement
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+      .collect::<Result<_, _>>()?;
+
+    Ok(boosts)
+  }
+
+  /// Throws a draft away, once it's sent or no longer wanted.
+  pub fn delete_draft(&self, id: i64) -> Result<(), ApreadErrors> {
+    let deleted = self
+      .connection
+      .execute("DELETE FROM drafts WHERE id = ?1", params![id])?;
+
+    match deleted {
+      0 => Err(NoSuchDraft(id).into()),
+      _ => Ok(()),
+    }
+  }
+
+  /// Removes the posts published before a time, as RFC 3339, giving how
+  /// many there were. Bookmarked posts are kept, however old.
+  pub fn prune_before(&self, cutoff: &str) -> Result<usize, ApreadErrors> {
+    self.prune("datetime(p.published) < datetime(?1)", params![cutoff])
+  }
+
+  /// Removes all but the newest `keep` posts by each author, giving how
+  /// many were removed. Bookmarked posts are kept, and don't count.
+  pub fn prune_beyond(&self, keep: usize) -> Result<usize, ApreadErrors> {
+    self.prune(
+      "p.id IN (
+         SELECT id FROM (
+           SELECT id, row_number() OVER (
+             PARTITION BY author ORDER BY published DESC
+           ) AS newness
+           FROM posts
+           WHERE id NOT IN (SELECT post_id FROM bookmarks)
+         )
+         WHERE newness > ?1
+       )",
+      params![keep],
+    )
+  }
+
+  /// Removes the oldest `count` posts, whoever wrote them, giving how many
+  /// were removed. Bookmarked posts are kept.
+  pub fn prune_oldest(&self, count: usize) -> Result<usize, ApreadErrors> {
+    self.prune(
+      "p.id IN (
+         SELECT id FROM posts
+         WHERE id NOT IN (SELECT post_id FROM bookmarks)
+         ORDER BY published ASC LIMIT ?1
+       )",
+      params![count],
+    )
+  }
+
+  /// Removes the posts a condition on `posts p` picks out, other than
+  /// bookmarked ones, along with their tags, keywords and revisions.
+  fn prune(
+    &self,
+    condition: &str,
+    params: &[&dyn rusqlite::ToSql],
+  ) -> Result<usize, ApreadErrors> {
+    self.connection.execute_batch(
+      "DROP TABLE IF EXISTS temp.pruning;
+       CREATE TEMP TABLE pruning (id TEXT PRIMARY KEY);",
+    )?;
+    let pruning = self.connection.execute(
+      &format!(
+        "INSERT INTO pruning SELECT p.id FROM posts p
+         WHERE ({}) AND p.id NOT IN (SELECT post_id FROM bookmarks)",
+        condition
+      ),
+      params,
+    )?;
+
+    self.connection.execute_batch(
+      "BEGIN;
+       DELETE FROM post_tags WHERE post_id IN (SELECT id FROM pruning);
+       DELETE FROM post_keywords WHERE post_id IN (SELECT id FROM pruning);
+       DELETE FROM revisions WHERE post_id IN (SELECT id FROM pruning);
+       DELETE FROM post_attachments WHERE post_id IN (SELECT id FROM pruning);
+       DELETE FROM post_audience WHERE post_id IN (SELECT id FROM pruning);
+       DELETE FROM posts WHERE id IN (SELECT id FROM pruning);
+       COMMIT;
+       DROP TABLE temp.pruning;",
+    )?;
+
+    Ok(pruning)
+  }
+
+  /// How many bytes the archive's database takes up.
+  pub fn size(&self) -> Result<u64, ApreadErrors> {
+    let size = self.connection.query_row(
+      "SELECT page_count * page_size
+       FROM pragma_page_count(), pragma_page_size()",
+      [],
+      |row| row.get(0),
+    )?;
+
+    Ok(size)
+  }
+
+  /// Gives the space pruned posts took back to the disk.
+  pub fn vacuum(&self) -> Result<(), ApreadErrors> {
+    self.connection.execute_batch("VACUUM")?;
+
+    Ok(())
+  }
+}
+
+fn read_draft(row: &Row) -> rusqlite::Result<SavedDraft> {
+  Ok(SavedDraft {
+    id: row.get(0)?,
+    text: row.get(1)?,
+    spoiler_text: row.get(2)?,
+    visibility: Visibility::from_name(&row.get::<_, String>(3)?),
+    saved_at: row.get(4)?,
+  })
+}
+
+/// Reads an [`ArchivedPost`] from a row starting with [`POST_COLUMNS`]. Its
+/// hashtags, attachments and addressing are left for [`Archive::fill`].
+fn read_post(row: &Row) -> rusqlite::Result<ArchivedPost> {
+  Ok(ArchivedPost {
+    id: row.get(0)?,
+    url: row.get(1)?,
+    author: row.get(2)?,
+    actor: row.get(7)?,
+    published: row.get(3)?,
+    summary: row.get(4)?,
+    content: row.get(5)?,
+    visibility: row
+      .get::<_, Option<String>>(6)?
+      .map(|name| Visibility::from_name(&name)),
+    hashtags: vec![],
+    attachments: vec![],
+    to: vec![],
+    cc: vec![],
+  })
+}
+
+/// Where apread keeps the archive, and the media imported alongside it.
+pub fn data_dir() -> Result<PathBuf, ApreadErrors> {
+  Ok(dirs::data_dir().ok_or(NoDataDir)?.join("apread"))
+}
+
+/// Archives an account's posts from its outbox, and marks deleted the posts
+/// archived before which have gone. Boosts, when the author's mix shows
+/// them, are noted rather than archived.
+///
+/// A post is taken to be deleted if it's missing from the stretch of the
+/// outbox just read, or if it's older than that stretch and its server
+/// answers 404 Not Found or 410 Gone for it. Only `checks` of the older
+/// posts are asked after, those checked longest ago first, so each sync
+/// costs a few requests and every post comes round in time; none are once
+/// the client is interrupted.
+pub async fn sync(
+  client: &Client,
+  archive: &Archive,
+  author: &Author,
+  walk: Walk,
+  checks: usize,
+) -> Result<Synced, ApreadErrors> {
+  let name = author.source.to_string();
+  let (boosts, entries): (Vec<_>, Vec<_>) =
+    timeline::walk(client, author, walk)
+      .await?
+      .into_iter()
+      .partition(|entry| entry.boosted);
+  let oldest = entries
+    .iter()
+    .filter_map(|entry| entry.published_at())
+    .min();
+  let mut seen = HashSet::new();
+  let mut synced = Synced::default();
+
+  for entry in &entries {
+    if let Ok(id) = archive.store(&entry.post, Some(&name)) {
+      seen.insert(id);
+      synced.archived += 1;
+    }
+  }
+
+  // Boosts aren't archived as posts, only noted for apread suggest.
+  for entry in &boosts {
+    archive.store_boost(&name, &entry.post)?;
+  }
+
+  let mut older = vec![];
+
+  for post in archive.standing(&name)? {
+    if seen.contains(&post.id) {
+      continue;
+    }
+
+    let published = post
+      .published
+      .as_deref()
+      .and_then(|published| DateTime::parse_from_rfc3339(published).ok());
+
+    match (published, oldest) {
+      (Some(published), Some(oldest)) if published >= oldest => {
+        archive.mark_deleted(&post.id)?;
+        synced.deleted.push(post.id);
+      }
+      _ => older.push(post.id),
+    }
+  }
+
+  for id in older.into_iter().take(checks) {
+    if client.interrupted() {
+      break;
+    }
+
+    let health = client.check(&id).await;
+
+    archive.mark_checked(&id)?;
+
+    if matches!(health, Health::Dead { code: 404 | 410 }) {
+      archive.mark_deleted(&id)?;
+      synced.deleted.push(id);
+    }
+  }
+
+  Ok(synced)
+}
+
+/// Archives an account's whole outbox, page by page from the newest.
+///
+/// After each page the archive keeps a checkpoint of the next, so a
+/// backfill cut short by Ctrl-C or a failed request carries on from there
+/// the next time, rather than from the newest page again. Once the oldest
+/// page is archived the checkpoint goes, and the next backfill starts over
+/// to catch up.
+pub async fn backfill(
+  client: &Client,
+  archive: &Archive,
+  author: &Author,
+) -> Result<Backfilled, ApreadErrors> {
+  let handle = match &author.source {
+    Source::Account(handle) => handle,
+    _ => return Err(NotAnAccount(author.source.to_string()).into()),
+  };
+  let name = author.source.to_string();
+  let mut backfilled = Backfilled::default();
+  let mut seen = HashSet::new();
+  let mut page = match archive.checkpoint(&name)? {
+    Some(next) => client.page(&next).await?,
+    None => client.first_page(handle).await?,
+  };
+
+  loop {
+    for entry in timeline::entries(author, &page) {
+      if archive.store(&entry.post, Some(&name)).is_ok() {
+        backfilled.archived += 1;
+      }
+    }
+
+    backfilled.pages += 1;
+
+    // Some servers link their last page back to itself, or round in a
+    // loop; a page seen before is as good as the end.
+    let next = page
+      .next
+      .as_ref()
+      .map(|next| next.as_str().to_owned())
+      .filter(|next| seen.insert(next.clone()));
+
+    archive.save_checkpoint(&name, next.as_deref())?;
+
+    match next {
+      Some(_) if client.interrupted() => break,
+      Some(next) => page = client.page(&next).await?,
+      None => {
+        backfilled.complete = true;
+        break;
+      }
+    }
+  }
+
+  Ok(backfilled)
+}
+
+/// The archive's kept documents, as a store for the client to cache
+/// boosted posts in. They're kept for good, like the rest of the archive.
+impl Store for Mutex<Archive> {
+  fn get(&self, url: &str) -> Option<Stored> {
+    let archive = self.lock().ok()?;
+    let (body, fetched_at): (String, String) = archive
+      .connection
+      .query_row(
+        "SELECT document, fetched_at FROM objects WHERE id = ?1",
+        params![url],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+      .ok()?;
+    let kept_at = chrono::DateTime::parse_from_rfc3339(&fetched_at)
+      .map_or(0, |at| at.timestamp().max(0) as u64);
+
+    Some(Stored { body, kept_at })
+  }
+
+  fn put(&self, url: &str, body: &str) -> Result<(), ApreadErrors> {
+    match self.lock() {
+      Ok(archive) => archive.store_object(url, body),
+      Err(_) => Ok(()),
+    }
+  }
+}
+
+fn now() -> String {
+  chrono::Utc::now().to_rfc3339()
+}
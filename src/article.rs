@@ -0,0 +1,41 @@
+//! Pulling the readable part out of an ordinary web page.
+
+use scraper::{Html, Selector};
+
+/// The main content of a web page, without its surroundings.
+#[derive(Clone, Debug)]
+pub struct Article {
+  /// The page's title, where it has one.
+  pub title: Option<String>,
+  /// The page's main content, as html.
+  pub content: String,
+}
+
+impl Article {
+  /// Finds the article in a page: its `<article>` if it has one, then its
+  /// `<main>`, and failing both, the whole `<body>`.
+  pub fn extract(html: &str) -> Self {
+    let document = Html::parse_document(html);
+    let title = first(&document, r#"meta[property="og:title"]"#)
+      .and_then(|meta| meta.value().attr("content").map(str::to_owned))
+      .or_else(|| first(&document, "title").map(|title| title.text().collect()))
+      .map(|title: String| title.trim().to_owned())
+      .filter(|title| !title.is_empty());
+    let content = ["article", "main", "body"]
+      .iter()
+      .find_map(|selector| first(&document, selector))
+      .map(|element| element.inner_html())
+      .unwrap_or_default();
+
+    Self { title, content }
+  }
+}
+
+fn first<'a>(
+  document: &'a Html,
+  selector: &str,
+) -> Option<scraper::ElementRef<'a>> {
+  let selector = Selector::parse(selector).expect("selectors are static");
+
+  document.select(&selector).next()
+}
@@ -0,0 +1,193 @@
+//! Auditing an account's own posts for the things posters tend to forget:
+//! alt text on attachments, content warnings on touchy subjects, and links
+//! which have since broken.
+
+use std::{collections::HashMap, fmt};
+
+use serde::Serialize;
+
+use crate::{
+  activity::Post,
+  links::{self, Health},
+  render::plain_text,
+  timeline::Entry,
+  Client,
+};
+
+/// Something an audit found wrong with a post.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum Problem {
+  /// An attachment without alt text.
+  MissingAlt {
+    /// What sort of attachment it is, as in `Image`.
+    kind: &'static str,
+    /// Where the attachment is.
+    url: Option<String>,
+  },
+  /// A keyword that calls for a content warning, in a post without one.
+  MissingWarning {
+    /// The keyword the post mentions.
+    keyword: String,
+  },
+  /// A link that no longer leads anywhere.
+  BrokenLink {
+    /// The link.
+    url: String,
+    /// What happened when it was followed.
+    health: Health,
+  },
+}
+
+impl fmt::Display for Problem {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::MissingAlt { kind, url } => {
+        write!(f, "{} with no alt text", kind)?;
+
+        match url {
+          Some(url) => write!(f, ": {}", url),
+          None => Ok(()),
+        }
+      }
+      Self::MissingWarning { keyword } => {
+        write!(f, "mentions \"{}\" without a content warning", keyword)
+      }
+      Self::BrokenLink { url, health } => {
+        write!(f, "broken link ({}): {}", health, url)
+      }
+    }
+  }
+}
+
+/// A post with problems, and what they are.
+#[derive(Clone, Debug, Serialize)]
+pub struct Finding {
+  /// The post's url, or its id if it has no web page.
+  pub post: String,
+  /// When the post was published.
+  pub published: String,
+  /// What's wrong with it.
+  pub problems: Vec<Problem>,
+}
+
+/// Everything an audit found.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+  /// How many posts were checked.
+  pub checked: usize,
+  /// The posts with problems, in the order they were checked.
+  pub findings: Vec<Finding>,
+}
+
+/// What an audit looks for.
+#[derive(Clone, Debug, Default)]
+pub struct Checks {
+  /// Keywords which posts mentioning them should have a content warning
+  /// for.
+  pub warn_on: Vec<String>,
+  /// Whether to follow every link, to find the broken ones.
+  pub links: bool,
+}
+
+/// Audits posts, following links if asked to. Each link is only followed
+/// once, however many posts share it, and once the client is interrupted
+/// no more are.
+pub async fn audit(
+  client: &Client,
+  entries: &[Entry],
+  checks: &Checks,
+) -> Report {
+  let mut health: HashMap<String, Health> = HashMap::new();
+  let mut findings = vec![];
+
+  for entry in entries {
+    let mut problems = missing_alt(&entry.post);
+    problems.extend(missing_warnings(&entry.post, &checks.warn_on));
+
+    if checks.links {
+      for url in links::extract(&entry.post.content) {
+        if !health.contains_key(&url) && !client.interrupted() {
+          health.insert(url.clone(), client.check(&url).await);
+        }
+
+        if let Some(found) = health.get(&url).filter(|found| found.is_broken())
+        {
+          problems.push(Problem::BrokenLink {
+            health: found.clone(),
+            url,
+          });
+        }
+      }
+    }
+
+    if !problems.is_empty() {
+      findings.push(Finding {
+        post: entry
+          .post
+          .url
+          .clone()
+          .or_else(|| entry.post.id.clone())
+          .unwrap_or_default(),
+        published: entry.published.clone(),
+        problems,
+      });
+    }
+  }
+
+  Report {
+    checked: entries.len(),
+    findings,
+  }
+}
+
+/// A problem for each of a post's attachments that has no alt text.
+pub fn missing_alt(post: &Post) -> Vec<Problem> {
+  post
+    .attachment
+    .iter()
+    .filter(|attachment| attachment.alt_text().is_none())
+    .map(|attachment| Problem::MissingAlt {
+      kind: attachment.kind(),
+      url: attachment.url.clone(),
+    })
+    .collect()
+}
+
+/// A problem for each keyword a post mentions, if it has no content
+/// warning. Keywords match whole words (or phrases), ignoring case.
+pub fn missing_warnings(post: &Post, keywords: &[String]) -> Vec<Problem> {
+  let warned = post
+    .summary
+    .as_deref()
+    .is_some_and(|summary| !summary.trim().is_empty());
+
+  if warned {
+    return vec![];
+  }
+
+  let text = plain_text(&post.content).to_lowercase();
+
+  keywords
+    .iter()
+    .filter(|keyword| mentions(&text, &keyword.to_lowercase()))
+    .map(|keyword| Problem::MissingWarning {
+      keyword: keyword.clone(),
+    })
+    .collect()
+}
+
+/// Whether lowercased text mentions a lowercased keyword as a whole word.
+fn mentions(text: &str, keyword: &str) -> bool {
+  if keyword.is_empty() {
+    return false;
+  }
+
+  text.match_indices(keyword).any(|(at, _)| {
+    let before = text[..at].chars().next_back();
+    let after = text[at + keyword.len()..].chars().next();
+
+    !before.is_some_and(char::is_alphanumeric)
+      && !after.is_some_and(char::is_alphanumeric)
+  })
+}
@@ -0,0 +1,106 @@
+//! Right-to-left text, such as Arabic and Hebrew, and keeping it in order.
+//!
+//! Terminals which lay out bidirectional text pick each line's direction
+//! from its first letter, so a wrapped Arabic paragraph whose second line
+//! starts with a url, or an English one whose second line starts with a
+//! Hebrew word, comes out scrambled. Lines of paragraphs with right-to-left
+//! text in them are started with a direction mark saying which way the
+//! paragraph runs; html gets `dir` attributes instead.
+
+use scraper::Html;
+
+/// Which way a paragraph runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+  /// Left to right, as in English.
+  Ltr,
+  /// Right to left, as in Arabic or Hebrew.
+  Rtl,
+}
+
+impl Direction {
+  /// The invisible mark which starts a line running this way.
+  pub fn mark(self) -> char {
+    match self {
+      Self::Ltr => '\u{200E}',
+      Self::Rtl => '\u{200F}',
+    }
+  }
+}
+
+/// Whether a character is written right to left.
+pub fn is_rtl(c: char) -> bool {
+  matches!(
+    c,
+    '\u{0590}'..='\u{08FF}'
+      | '\u{FB1D}'..='\u{FDFF}'
+      | '\u{FE70}'..='\u{FEFF}'
+      | '\u{10800}'..='\u{10FFF}'
+      | '\u{1E800}'..='\u{1EFFF}'
+  )
+}
+
+/// Which way text runs, going by its first letter, or `None` if it has no
+/// letters to go by.
+pub fn direction(text: &str) -> Option<Direction> {
+  text
+    .chars()
+    .find(|c| c.is_alphabetic())
+    .map(|c| match is_rtl(c) {
+      true => Direction::Rtl,
+      false => Direction::Ltr,
+    })
+}
+
+/// The mark to start each line of a paragraph with, if it needs one:
+/// paragraphs with no right-to-left text are left alone.
+pub fn mark(paragraph: &str) -> Option<char> {
+  match paragraph.chars().any(is_rtl) {
+    true => direction(paragraph).map(Direction::mark),
+    false => None,
+  }
+}
+
+/// Html with a `dir="rtl"` attribute on each paragraph, list item and
+/// quote that runs right to left.
+pub fn directed(html: &str) -> String {
+  let mut out = String::with_capacity(html.len());
+  let mut rest = html;
+
+  while let Some(start) = rest.find('<') {
+    out.push_str(&rest[..start]);
+    rest = &rest[start..];
+
+    let name: String = rest[1..]
+      .chars()
+      .take_while(char::is_ascii_alphanumeric)
+      .collect();
+    let opens = rest[..rest.find('>').unwrap_or(rest.len())].len();
+
+    if !matches!(name.as_str(), "p" | "li" | "blockquote")
+      || rest[..opens].contains("dir=")
+    {
+      out.push_str(&rest[..opens]);
+      rest = &rest[opens..];
+      continue;
+    }
+
+    let closing = format!("</{}>", name);
+    let end = rest.find(&closing).unwrap_or(rest.len());
+    let text: String = Html::parse_fragment(&rest[..end])
+      .root_element()
+      .text()
+      .collect();
+
+    out.push_str(&rest[..opens]);
+
+    if direction(&text) == Some(Direction::Rtl) {
+      out.push_str(" dir=\"rtl\"");
+    }
+
+    rest = &rest[opens..];
+  }
+
+  out.push_str(rest);
+  out
+}
@@ -0,0 +1,134 @@
+//! Decoding [BlurHash]es, the few characters Mastodon sends with each image
+//! to stand in for it while it loads, or while it's hidden as sensitive.
+//!
+//! A hash is a handful of the lowest frequencies of the image's cosine
+//! transform, in base 83. Decoding them gives back a blurred picture of any
+//! size, which the terminal draws as blocks of color, two pixels to a
+//! character cell, as it draws [thumbnails](crate::media) too.
+//!
+//! [BlurHash]: https://blurha.sh
+
+use std::f64::consts::PI;
+
+/// How many character cells wide the blurred image is drawn.
+pub const COLUMNS: usize = 20;
+
+/// How many character cells tall the blurred image is drawn.
+pub const ROWS: usize = 5;
+
+const DIGITS: &str =
+  "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// A pixel's red, green and blue.
+pub type Rgb = [u8; 3];
+
+/// The pixels of a hash's image at a size, row by row, or none if the hash
+/// isn't one.
+pub fn decode(hash: &str, width: usize, height: usize) -> Option<Vec<Rgb>> {
+  if !hash.is_ascii() || hash.len() < 6 {
+    return None;
+  }
+
+  let size = base83(&hash[..1])?;
+  let (across, down) = (size % 9 + 1, size / 9 + 1);
+
+  if hash.len() != 4 + 2 * across * down {
+    return None;
+  }
+
+  let maximum = (base83(&hash[1..2])? + 1) as f64 / 166.0;
+  let mut colors = vec![dc(base83(&hash[2..6])?)];
+
+  for index in 1..across * down {
+    let start = 4 + index * 2;
+    colors.push(ac(base83(&hash[start..start + 2])?, maximum));
+  }
+
+  let mut pixels = Vec::with_capacity(width * height);
+
+  for y in 0..height {
+    for x in 0..width {
+      let mut pixel = [0.0; 3];
+
+      for j in 0..down {
+        for i in 0..across {
+          let basis = (PI * x as f64 * i as f64 / width as f64).cos()
+            * (PI * y as f64 * j as f64 / height as f64).cos();
+          let color = colors[i + j * across];
+
+          for channel in 0..3 {
+            pixel[channel] += color[channel] * basis;
+          }
+        }
+      }
+
+      pixels.push(pixel.map(to_srgb));
+    }
+  }
+
+  Some(pixels)
+}
+
+/// A hash's image drawn in [`COLUMNS`] by [`ROWS`] character cells, with
+/// 24-bit ANSI colors, one line to a row.
+pub fn blocks(hash: &str) -> Option<Vec<String>> {
+  Some(draw(&decode(hash, COLUMNS, ROWS * 2)?, COLUMNS))
+}
+
+/// Pixels, row by row in rows `columns` wide, drawn with 24-bit ANSI
+/// colors as half blocks, two rows of pixels to a line.
+pub fn draw(pixels: &[Rgb], columns: usize) -> Vec<String> {
+  pixels
+    .chunks(columns * 2)
+    .filter(|rows| rows.len() == columns * 2)
+    .map(|rows| {
+      let (top, bottom) = rows.split_at(columns);
+      let cells: String = top
+        .iter()
+        .zip(bottom)
+        .map(|([r, g, b], [br, bg, bb])| {
+          format!("\x1b[38;2;{};{};{};48;2;{};{};{}m▀", r, g, b, br, bg, bb)
+        })
+        .collect();
+
+      format!("{}\x1b[0m", cells)
+    })
+    .collect()
+}
+
+fn base83(digits: &str) -> Option<usize> {
+  digits
+    .chars()
+    .try_fold(0, |value, digit| Some(value * 83 + DIGITS.find(digit)?))
+}
+
+/// The average color, which comes first.
+fn dc(value: usize) -> [f64; 3] {
+  [value >> 16, (value >> 8) & 255, value & 255].map(|channel| {
+    let channel = channel as f64 / 255.0;
+
+    match channel <= 0.04045 {
+      true => channel / 12.92,
+      false => ((channel + 0.055) / 1.055).powf(2.4),
+    }
+  })
+}
+
+/// One of the colors after the average, scaled by the largest of them.
+fn ac(value: usize, maximum: f64) -> [f64; 3] {
+  [value / (19 * 19), (value / 19) % 19, value % 19].map(|quantised| {
+    let signed = (quantised as f64 - 9.0) / 9.0;
+
+    signed.signum() * signed.powi(2) * maximum
+  })
+}
+
+fn to_srgb(linear: f64) -> u8 {
+  let linear = linear.clamp(0.0, 1.0);
+  let srgb = match linear <= 0.0031308 {
+    true => linear * 12.92,
+    false => 1.055 * linear.powf(1.0 / 2.4) - 0.055,
+  };
+
+  (srgb * 255.0).round() as u8
+}
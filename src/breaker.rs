@@ -0,0 +1,141 @@
+//! Leaving instances which are down alone for a while, rather than waiting
+//! on each of their accounts in turn.
+//!
+//! Reading every subscription means several accounts on any one instance,
+//! and when that instance is down, each of them would wait out its own
+//! timeout. The client counts failures (connections refused or timed out,
+//! and gateway errors) for each host. After [`THRESHOLD`] in a row the
+//! circuit for that host opens, and requests to it fail straight away with
+//! [`InstanceDown`] until it's been left alone long enough to try again:
+//! [`BACKOFF`] the first time, then twice as long each time it's still
+//! down, up to [`MAX_BACKOFF`]. One request getting through closes the
+//! circuit again.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::i18n::{self, FluentValue, Localized};
+
+/// How many failures in a row open a host's circuit.
+pub const THRESHOLD: u32 = 2;
+
+/// How long a host is left alone the first time its circuit opens.
+pub const BACKOFF: Duration = Duration::from_secs(30);
+
+/// The longest a host is left alone for.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Raised instead of sending a request to an instance which is down.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct InstanceDown(pub String);
+
+impl Localized for InstanceDown {
+  fn id(&self) -> &'static str {
+    "error-instance-down"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("instance", self.0.as_str().into())]
+  }
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+  failures: u32,
+  trips: u32,
+  open_until: Option<Instant>,
+}
+
+/// The failures of every host the client has sent requests to.
+#[derive(Clone, Debug, Default)]
+pub struct Breaker {
+  hosts: Arc<Mutex<HashMap<String, Circuit>>>,
+}
+
+impl Breaker {
+  /// Fails if a url's host is being left alone.
+  pub fn check(&self, url: &str) -> Result<(), InstanceDown> {
+    let host = match host(url) {
+      Some(host) => host,
+      None => return Ok(()),
+    };
+    let hosts = match self.hosts.lock() {
+      Ok(hosts) => hosts,
+      Err(_) => return Ok(()),
+    };
+    let open_until = hosts.get(&host).and_then(|circuit| circuit.open_until);
+
+    match open_until {
+      Some(until) if Instant::now() < until => Err(InstanceDown(host)),
+      _ => Ok(()),
+    }
+  }
+
+  /// Notes how a request to a url went.
+  pub fn record(&self, url: &str, sent: &reqwest::Result<reqwest::Response>) {
+    let failed = match sent {
+      Ok(response) => matches!(
+        response.status(),
+        StatusCode::BAD_GATEWAY
+          | StatusCode::SERVICE_UNAVAILABLE
+          | StatusCode::GATEWAY_TIMEOUT
+      ),
+      Err(error) => error.is_connect() || error.is_timeout(),
+    };
+
+    let (Some(host), Ok(mut hosts)) = (host(url), self.hosts.lock()) else {
+      return;
+    };
+    let circuit = hosts.entry(host).or_default();
+
+    if !failed {
+      *circuit = Circuit::default();
+      return;
+    }
+
+    circuit.failures += 1;
+
+    if circuit.failures >= THRESHOLD {
+      let backoff = BACKOFF
+        .saturating_mul(2u32.saturating_pow(circuit.trips))
+        .min(MAX_BACKOFF);
+
+      circuit.trips += 1;
+      circuit.open_until = Some(Instant::now() + backoff);
+    }
+  }
+}
+
+/// The hosts of the instances whose accounts were skipped for being down,
+/// each once, out of a list of errors.
+pub fn skipped<'a>(
+  errors: impl IntoIterator<Item = &'a crate::ApreadErrors>,
+) -> Vec<&'a str> {
+  let mut hosts = vec![];
+
+  for error in errors {
+    if let crate::ApreadErrors::InstanceDown(InstanceDown(host)) = error {
+      if !hosts.contains(&host.as_str()) {
+        hosts.push(host.as_str());
+      }
+    }
+  }
+
+  hosts
+}
+
+fn host(url: &str) -> Option<String> {
+  let url = reqwest::Url::parse(url).ok()?;
+
+  Some(match url.port() {
+    Some(port) => format!("{}:{}", url.host_str()?, port),
+    None => url.host_str()?.to_owned(),
+  })
+}
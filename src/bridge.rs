@@ -0,0 +1,50 @@
+//! Accounts bridged into the fediverse from elsewhere, by Bridgy Fed.
+//!
+//! Bridged accounts look like any other, but their documents are shaped a
+//! little differently: outboxes carry their first page inline, pages list
+//! `items` rather than `orderedItems`, and activities often leave their
+//! `published` date to the object. The activity model accepts all of that,
+//! so what's left here is telling bridged accounts apart, so readers know
+//! where a post really came from.
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Where a bridged account really lives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bridge {
+  /// A Bluesky account, bridged through `bsky.brid.gy`.
+  Bluesky,
+  /// A website, bridged through `web.brid.gy`.
+  Web,
+}
+
+impl Bridge {
+  /// The bridge a domain belongs to, if it's a bridge's domain at all.
+  pub fn from_domain(domain: &str) -> Option<Self> {
+    match domain.to_ascii_lowercase().as_str() {
+      "bsky.brid.gy" => Some(Self::Bluesky),
+      "web.brid.gy" => Some(Self::Web),
+      _ => None,
+    }
+  }
+
+  /// The bridge an actor url belongs to. Bridged websites can go by a
+  /// handle on their own domain, so this catches what the handle doesn't.
+  pub fn from_url(url: &str) -> Option<Self> {
+    let url = reqwest::Url::parse(url).ok()?;
+
+    Self::from_domain(url.host_str()?)
+  }
+}
+
+impl fmt::Display for Bridge {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Bluesky => f.write_str("Bluesky"),
+      Self::Web => f.write_str("Web"),
+    }
+  }
+}
@@ -0,0 +1,173 @@
+//! Bluesky accounts, read through a Bluesky AppView.
+//!
+//! Bluesky handles are bare domains such as `alice.bsky.social`. Their
+//! posts come from the AppView's `app.bsky.feed.getAuthorFeed` endpoint,
+//! which needs no login, and are read into the same posts as fediverse
+//! accounts. Reposts are skipped, the way boosts are.
+
+use serde::Deserialize;
+
+use crate::{
+  activity::{text_to_html, Post, Tag},
+  timeline::{Author, Entry},
+};
+
+/// The public AppView, used unless the client is pointed at another.
+pub const DEFAULT_APPVIEW: &str = "https://public.api.bsky.app";
+
+/// Whether some text looks like a Bluesky handle: a domain, with no `@`.
+pub fn is_handle(text: &str) -> bool {
+  let text = text.trim_start_matches('@');
+
+  text.contains('.')
+    && !text.contains(['@', '/', ':'])
+    && !text.starts_with('.')
+    && !text.ends_with('.')
+}
+
+/// The url of an account's newest posts, on the given AppView.
+pub fn author_feed_url(appview: &str, handle: &str) -> String {
+  format!(
+    "{}/xrpc/app.bsky.feed.getAuthorFeed?actor={}&filter=posts_no_replies",
+    appview.trim_end_matches('/'),
+    handle.trim_start_matches('@')
+  )
+}
+
+/// A page of an account's posts, as `getAuthorFeed` returns them.
+#[derive(Debug, Deserialize)]
+pub struct AuthorFeed {
+  /// The posts, newest first.
+  pub feed: Vec<FeedItem>,
+}
+
+/// One post in an author feed.
+#[derive(Debug, Deserialize)]
+pub struct FeedItem {
+  /// The post itself.
+  pub post: PostView,
+  /// Why the post is in the feed, when it isn't the author's own: a repost.
+  pub reason: Option<serde_json::Value>,
+}
+
+/// A post, with what the AppView knows about it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostView {
+  /// The post's `at://` uri.
+  pub uri: String,
+  /// Who posted it.
+  pub author: ProfileView,
+  /// What the author wrote.
+  pub record: Record,
+  /// When the AppView first saw the post.
+  pub indexed_at: Option<String>,
+}
+
+/// The part of a profile that posts carry.
+#[derive(Debug, Deserialize)]
+pub struct ProfileView {
+  /// The account's handle.
+  pub handle: String,
+}
+
+/// A post record, as its author wrote it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Record {
+  /// The post's plain text.
+  #[serde(default)]
+  pub text: String,
+  /// When the author says they wrote it.
+  pub created_at: Option<String>,
+  /// Rich text annotations over the text, such as links and hashtags.
+  #[serde(default)]
+  pub facets: Vec<Facet>,
+}
+
+/// A rich text annotation over part of a post's text.
+#[derive(Debug, Deserialize)]
+pub struct Facet {
+  /// What the annotated text is.
+  #[serde(default)]
+  pub features: Vec<Feature>,
+}
+
+/// What a stretch of annotated text is.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "$type")]
+pub enum Feature {
+  /// A hashtag.
+  #[serde(rename = "app.bsky.richtext.facet#tag")]
+  Tag {
+    /// The tag, without its `#`.
+    tag: String,
+  },
+  /// Anything else, such as a link or a mention.
+  #[serde(other)]
+  Other,
+}
+
+/// The entries for an author's own posts in an author feed.
+pub fn entries(author: &Author, feed: AuthorFeed) -> Vec<Entry> {
+  feed
+    .feed
+    .into_iter()
+    .filter(|item| item.reason.is_none())
+    .map(|item| to_entry(author, item.post))
+    .collect()
+}
+
+fn to_entry(author: &Author, post: PostView) -> Entry {
+  let published = post
+    .record
+    .created_at
+    .or(post.indexed_at)
+    .unwrap_or_default();
+  let url = post.uri.rsplit('/').next().map(|key| {
+    format!(
+      "https://bsky.app/profile/{}/post/{}",
+      post.author.handle, key
+    )
+  });
+  let tag = post
+    .record
+    .facets
+    .into_iter()
+    .flat_map(|facet| facet.features)
+    .filter_map(|feature| match feature {
+      Feature::Tag { tag } => Some(Tag::Hashtag {
+        name: tag,
+        href: None,
+      }),
+      Feature::Other => None,
+    })
+    .collect();
+
+  Entry {
+    author: author.clone(),
+    post: Post {
+      id: Some(post.uri),
+      kind: None,
+      url,
+      attributed_to: None,
+      published: Some(published.clone()),
+      updated: None,
+      summary: None,
+      content: text_to_html(&post.record.text),
+      tag,
+      attachment: vec![],
+      to: vec![],
+      cc: vec![],
+      sensitive: false,
+      duration: None,
+      name: None,
+      start_time: None,
+      end_time: None,
+      location: None,
+      in_reply_to: None,
+    },
+    published,
+    boosted: false,
+  }
+}
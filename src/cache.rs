@@ -0,0 +1,49 @@
+//! Caching what it takes to find an account's posts.
+//!
+//! Reading a timeline starts with discovery: a WebFinger lookup, then the
+//! actor document it points to. These rarely change, so the discovery
+//! cache keeps them for a while (a day, unless configured otherwise) and
+//! timelines go straight to the outbox. Entries expire rather than living
+//! forever, so an account which moves domains is noticed eventually.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{
+  storage::{Disk, Store},
+  ApreadErrors,
+};
+
+/// How long discovery documents are kept, unless configured otherwise.
+pub const DEFAULT_DISCOVERY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// WebFinger and actor documents, kept in a [`Store`] for a while.
+#[derive(Clone, Debug)]
+pub struct DiscoveryCache {
+  store: Arc<dyn Store>,
+  ttl: Duration,
+}
+
+impl DiscoveryCache {
+  /// A cache kept on disk in the given directory, whose entries last for
+  /// `ttl`.
+  pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+    Self::in_store(Arc::new(Disk::new(dir)), ttl)
+  }
+
+  /// A cache kept in the given store, whose entries last for `ttl`.
+  pub fn in_store(store: Arc<dyn Store>, ttl: Duration) -> Self {
+    Self { store, ttl }
+  }
+
+  /// The cached document for a url, if there's one younger than the TTL.
+  pub fn get(&self, url: &str) -> Option<String> {
+    let stored = self.store.get(url)?;
+
+    stored.fresh(self.ttl).then_some(stored.body)
+  }
+
+  /// Keeps a document for a url, replacing whatever was kept for it.
+  pub fn put(&self, url: &str, body: &str) -> Result<(), ApreadErrors> {
+    self.store.put(url, body)
+  }
+}
@@ -0,0 +1,166 @@
+//! Keeping the responses apread couldn't read, to attach to a bug report.
+//!
+//! Given `--capture-error DIR`, a successful response which isn't the
+//! document apread expected is written into the directory as a bundle, one
+//! JSON file of what came back, where from and which apread couldn't read
+//! it:
+//!
+//! ```json
+//! {"apread":"0.2.1","captured_at":"2023-02-14T18:03:11+00:00","url":"https://example.social/users/alice","error":"missing field `id` at line 1 column 20","body":{"type":"Person",...}}
+//! ```
+//!
+//! Error pages aren't captured, but reported as the HTTP errors they are.
+//! Bundles are meant to be shared, so they're sanitized on the way: the
+//! url loses any user and password, and any query parameter or field of
+//! the body named for a secret or for the reader's own details, as
+//! `access_token` or `email`, has its value replaced with `[redacted]`.
+//! A body which isn't JSON at all, as a login page or a bot check, could
+//! hide a secret anywhere, so it's left out and only its length is kept.
+
+use std::{fs, io, path::PathBuf};
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::i18n::{self, FluentValue, Localized};
+
+/// What a sanitized value is replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// The parts of a name which mark what it names as not to be shared.
+const SECRETS: &[&str] = &[
+  "token", "secret", "password", "private", "code", "email", "phone",
+];
+
+/// Raised in place of a response which couldn't be read, once it's been
+/// captured.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct Captured {
+  /// Where the bundle was written.
+  pub bundle: PathBuf,
+  /// Why the response couldn't be read.
+  pub source: serde_json::Error,
+}
+
+impl Localized for Captured {
+  fn id(&self) -> &'static str {
+    "error-captured"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![
+      ("error", self.source.to_string().into()),
+      ("bundle", self.bundle.display().to_string().into()),
+    ]
+  }
+}
+
+/// A directory to write bundles of responses which couldn't be read into.
+#[derive(Clone, Debug)]
+pub struct ErrorCapture {
+  dir: PathBuf,
+}
+
+/// What a bundle holds.
+#[derive(Serialize)]
+struct Bundle<'a> {
+  apread: &'a str,
+  captured_at: String,
+  url: String,
+  error: String,
+  body: Value,
+}
+
+impl ErrorCapture {
+  /// Bundles written into the given directory, created when the first one
+  /// is.
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self { dir: dir.into() }
+  }
+
+  /// Writes a bundle of a response fetched from `url` which couldn't be
+  /// read, giving where it was written.
+  pub fn capture(
+    &self,
+    url: &str,
+    body: &str,
+    error: &serde_json::Error,
+  ) -> io::Result<PathBuf> {
+    let now = Utc::now();
+    let bundle = Bundle {
+      apread: env!("CARGO_PKG_VERSION"),
+      captured_at: now.to_rfc3339(),
+      url: sanitize_url(url),
+      error: error.to_string(),
+      body: match serde_json::from_str(body) {
+        Ok(document) => sanitize(document),
+        Err(_) => Value::String(format!(
+          "[{} bytes which aren't JSON, left out]",
+          body.len()
+        )),
+      },
+    };
+    let path = self.dir.join(format!(
+      "apread-error-{}.json",
+      now.format("%Y%m%dT%H%M%S%.6f")
+    ));
+
+    fs::create_dir_all(&self.dir)?;
+    fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+
+    Ok(path)
+  }
+}
+
+/// A document with the values of any fields named for secrets redacted,
+/// however deep they are.
+pub fn sanitize(document: Value) -> Value {
+  match document {
+    Value::Object(fields) => Value::Object(
+      fields
+        .into_iter()
+        .map(|(name, value)| match is_secret(&name) {
+          true => (name, Value::String(REDACTED.to_owned())),
+          false => (name, sanitize(value)),
+        })
+        .collect(),
+    ),
+    Value::Array(items) => {
+      Value::Array(items.into_iter().map(sanitize).collect())
+    }
+    value => value,
+  }
+}
+
+/// A url without a user or password, and with the values of any query
+/// parameters named for secrets redacted.
+pub fn sanitize_url(url: &str) -> String {
+  let Ok(mut url) = reqwest::Url::parse(url) else {
+    return url.to_owned();
+  };
+  let _ = url.set_username("");
+  let _ = url.set_password(None);
+
+  if url.query().is_some() {
+    let pairs: Vec<(String, String)> = url
+      .query_pairs()
+      .map(|(name, value)| match is_secret(&name) {
+        true => (name.into_owned(), REDACTED.to_owned()),
+        false => (name.into_owned(), value.into_owned()),
+      })
+      .collect();
+
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+  }
+
+  url.into()
+}
+
+fn is_secret(name: &str) -> bool {
+  let name = name.to_lowercase();
+
+  SECRETS.iter().any(|secret| name.contains(secret))
+}
@@ -0,0 +1,1308 @@
+//! Fetching the documents between a handle and its posts.
+//!
+//! Every request asks for its response compressed, with gzip, deflate,
+//! brotli or zstd, and decodes whichever the server picks: outbox pages
+//! are mostly repetitive JSON, and shrink to a fraction of their size.
+
+use std::{
+  net::{IpAddr, Ipv4Addr, Ipv6Addr},
+  path::PathBuf,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use reqwest::{
+  header::{ACCEPT, CONTENT_TYPE},
+  Method, StatusCode,
+};
+use serde::de::DeserializeOwned;
+
+#[cfg(feature = "bsky")]
+use crate::bsky;
+#[cfg(feature = "matrix")]
+use crate::matrix;
+#[cfg(feature = "nostr")]
+use crate::nostr;
+use crate::{
+  activity::{Actor, NoLastPage, OutboxIndex, Page, Post},
+  activity_log::ActivityLog,
+  archive::Archive,
+  breaker::Breaker,
+  cache::DiscoveryCache,
+  capture::{Captured, ErrorCapture},
+  collection::{Collection, CollectionPage, PageRef},
+  compose::{self, NotFound},
+  diagnostics::{Diagnostic, Diagnostics, Kind},
+  doh::Doh,
+  fixtures::{self, Fixtures, Recorder},
+  handle::{BadHandleError, Handle, NeedsTor},
+  hashtag::Hashtag,
+  identities::Identities,
+  interrupt::Interrupt,
+  links::Health,
+  login::{self, Login, NotLoggedIn, Pending, ReadOnly},
+  mastodon, notifications,
+  settings::{IpVersion, Settings},
+  stats::Stats,
+  storage::{self, Store},
+  streaming::{self, Events, Stream},
+  throttle::Throttle,
+  webfinger::Webfinger,
+  ApUrl, ApreadErrors,
+};
+
+const ACTIVITY_JSON: &str = "application/activity+json";
+const ACTIVITY_STREAMS: &str =
+  "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"";
+
+/// Walks from a handle to its posts: WebFinger, actor, outbox, page.
+#[derive(Clone, Debug)]
+pub struct Client {
+  http: reqwest::Client,
+  scheme: &'static str,
+  tor: bool,
+  fixtures: Option<Fixtures>,
+  activity_log: Option<ActivityLog>,
+  capture: Option<ErrorCapture>,
+  discovery: Option<DiscoveryCache>,
+  objects: Option<Arc<dyn Store>>,
+  identities: Option<Identities>,
+  login: Option<Arc<Pending>>,
+  interrupt: Interrupt,
+  stats: Stats,
+  diagnostics: Diagnostics,
+  throttle: Throttle,
+  breaker: Breaker,
+  #[cfg(feature = "bsky")]
+  appview: String,
+  #[cfg(feature = "nostr")]
+  relays: Vec<String>,
+}
+
+/// A response as it came, headers and all, for checking rather than
+/// reading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Raw {
+  /// The HTTP status code.
+  pub status: u16,
+  /// The Content-Type it was served as, if it was given one.
+  pub content_type: Option<String>,
+  /// The body, as text.
+  pub body: String,
+}
+
+impl Default for Client {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Client {
+  /// A client which talks to instances over https.
+  pub fn new() -> Self {
+    Self {
+      http: reqwest::Client::new(),
+      scheme: "https",
+      tor: false,
+      fixtures: None,
+      activity_log: None,
+      capture: None,
+      discovery: None,
+      objects: None,
+      identities: None,
+      login: None,
+      interrupt: Interrupt::default(),
+      stats: Stats::default(),
+      diagnostics: Diagnostics::default(),
+      throttle: Throttle::default(),
+      breaker: Breaker::default(),
+      #[cfg(feature = "bsky")]
+      appview: bsky::DEFAULT_APPVIEW.to_owned(),
+      #[cfg(feature = "nostr")]
+      relays: nostr::DEFAULT_RELAYS
+        .iter()
+        .map(|relay| (*relay).to_owned())
+        .collect(),
+    }
+  }
+
+  /// A client which talks to instances over https, with the timeout,
+  /// proxy, DNS-over-HTTPS resolver and discovery cache (in the store) the
+  /// settings ask for, remembering identities in the same store.
+  pub fn from_settings(settings: &Settings) -> Result<Self, ApreadErrors> {
+    let mut http = builder(settings)?;
+
+    // The resolver is itself asked with a client set up as this one is,
+    // short of looking its own name up with itself.
+    if let Some(doh) = &settings.doh {
+      let through = builder(settings)?.build()?;
+
+      http = http.dns_resolver(Arc::new(Doh::new(doh).through(through)));
+    }
+
+    let discovery = match (settings.cache_dir(), settings.discovery_ttl) {
+      (Some(dir), ttl) if ttl > 0 => Some(DiscoveryCache::in_store(
+        storage::open(settings.storage, &dir, "discovery")?,
+        Duration::from_secs(ttl),
+      )),
+      _ => None,
+    };
+    let identities = match settings.cache_dir() {
+      Some(dir) => Some(Identities::new(storage::open(
+        settings.storage,
+        &dir,
+        "identities",
+      )?)),
+      None => None,
+    };
+
+    Ok(Self {
+      http: http.build()?,
+      tor: proxy(settings).is_some_and(|proxy| proxy.starts_with("socks5h://")),
+      discovery,
+      identities,
+      throttle: Throttle::new(settings.per_instance),
+      ..Self::new()
+    })
+  }
+
+  /// A client which looks handles up over plain http.
+  ///
+  /// This is only useful against local instances, like the fake one the
+  /// test suite runs.
+  pub fn insecure() -> Self {
+    Self {
+      scheme: "http",
+      ..Self::new()
+    }
+  }
+
+  /// Saves every response this client fetches with the given recorder.
+  pub fn recording(self, recorder: Recorder) -> Self {
+    Self {
+      fixtures: Some(Fixtures::Record(recorder)),
+      ..self
+    }
+  }
+
+  /// Appends every activity this client fetches to the given log.
+  pub fn logging_activities(self, log: ActivityLog) -> Self {
+    Self {
+      activity_log: Some(log),
+      ..self
+    }
+  }
+
+  /// Keeps every response this client can't read as a bundle, to attach
+  /// to a bug report.
+  pub fn capturing_errors(self, capture: ErrorCapture) -> Self {
+    Self {
+      capture: Some(capture),
+      ..self
+    }
+  }
+
+  /// Reads what needs a login, like lists, as the given account.
+  pub fn logged_in(self, login: Login) -> Self {
+    self.logging_in(login.into())
+  }
+
+  /// Reads what needs a login as the given account, looking its token up
+  /// only once something does.
+  pub fn logging_in(self, login: Pending) -> Self {
+    Self {
+      login: Some(Arc::new(login)),
+      ..self
+    }
+  }
+
+  /// Stops multi-page crawls early once the interrupt is triggered.
+  pub fn interruptible(self, interrupt: Interrupt) -> Self {
+    Self { interrupt, ..self }
+  }
+
+  /// Allows only `per_instance` requests in flight to each instance at
+  /// once, or any number if it's `0`, shared with the client's clones.
+  pub fn throttled(self, per_instance: usize) -> Self {
+    Self {
+      throttle: Throttle::new(per_instance),
+      ..self
+    }
+  }
+
+  /// Counts every request this client (and its clones) makes into the
+  /// given stats.
+  pub fn counting(self, stats: Stats) -> Self {
+    Self { stats, ..self }
+  }
+
+  /// What this client has fetched so far.
+  pub fn stats(&self) -> &Stats {
+    &self.stats
+  }
+
+  /// Collects what goes wrong without stopping a request into the given
+  /// diagnostics.
+  pub fn reporting(self, diagnostics: Diagnostics) -> Self {
+    Self {
+      diagnostics,
+      ..self
+    }
+  }
+
+  /// What's gone wrong without stopping a request so far.
+  pub fn diagnostics(&self) -> &Diagnostics {
+    &self.diagnostics
+  }
+
+  /// Whether crawls should stop where they are, because the client's
+  /// interrupt was triggered.
+  pub fn interrupted(&self) -> bool {
+    self.interrupt.is_triggered()
+  }
+
+  /// Serves every response from a fixture directory, never touching the
+  /// network.
+  pub fn replaying(self, dir: impl Into<PathBuf>) -> Self {
+    Self {
+      fixtures: Some(Fixtures::Replay(dir.into())),
+      ..self
+    }
+  }
+
+  /// Keeps WebFinger and actor documents in the given cache, so accounts
+  /// read recently skip discovery.
+  pub fn caching_discovery(self, cache: DiscoveryCache) -> Self {
+    Self {
+      discovery: Some(cache),
+      ..self
+    }
+  }
+
+  /// Keeps boosted posts in the archive once fetched, so a post boosted by
+  /// several accounts is only fetched once.
+  pub fn caching_objects(self, archive: Archive) -> Self {
+    self.caching_objects_in(Arc::new(Mutex::new(archive)))
+  }
+
+  /// Keeps boosted posts in the given store once fetched, as
+  /// [`caching_objects`](Self::caching_objects) keeps them in the archive.
+  pub fn caching_objects_in(self, store: Arc<dyn Store>) -> Self {
+    Self {
+      objects: Some(store),
+      ..self
+    }
+  }
+
+  /// Remembers which actor each handle resolves to in the given
+  /// identities, and falls back on them when WebFinger fails.
+  pub fn remembering_identities(self, identities: Identities) -> Self {
+    Self {
+      identities: Some(identities),
+      ..self
+    }
+  }
+
+  /// The identities to remember, unless replaying fixtures, which mustn't
+  /// be mixed up with what was really fetched.
+  fn identities(&self) -> Option<&Identities> {
+    match self.fixtures {
+      None | Some(Fixtures::Record(_)) => self.identities.as_ref(),
+      Some(Fixtures::Replay(_)) => None,
+    }
+  }
+
+  /// Reads Bluesky accounts through the given AppView rather than the
+  /// public one.
+  #[cfg(feature = "bsky")]
+  pub fn appview(self, url: impl Into<String>) -> Self {
+    Self {
+      appview: url.into(),
+      ..self
+    }
+  }
+
+  /// Asks the given relays for Nostr notes rather than the default ones.
+  #[cfg(feature = "nostr")]
+  pub fn relays(self, relays: Vec<String>) -> Self {
+    Self { relays, ..self }
+  }
+
+  async fn get<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    accept: &str,
+  ) -> Result<T, ApreadErrors> {
+    let body = self.get_text(url, accept).await?;
+
+    if let Some(log) = &self.activity_log {
+      log.record(url, &body)?;
+    }
+
+    self.parse(url, &body)
+  }
+
+  /// Notes a cache which couldn't be written to, which only makes reading
+  /// slower.
+  fn kept(&self, cache: &str, kept: Result<(), ApreadErrors>) {
+    if let Err(error) = kept {
+      self.diagnostics.push(Diagnostic::warning(
+        Kind::Cache,
+        None,
+        format!("couldn't write to the {}: {}", cache, error),
+      ));
+    }
+  }
+
+  /// Reads a document fetched from a url, capturing the response if it's
+  /// not the document expected and there's somewhere to capture it.
+  fn parse<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    body: &str,
+  ) -> Result<T, ApreadErrors> {
+    serde_json::from_str(body).map_err(|source| {
+      // A bundle which can't be written still leaves the error to report.
+      match self
+        .capture
+        .as_ref()
+        .map(|capture| capture.capture(url, body, &source))
+      {
+        Some(Ok(bundle)) => Captured { bundle, source }.into(),
+        _ => source.into(),
+      }
+    })
+  }
+
+  /// Like [`get`](Self::get), but as the account logged in.
+  async fn get_as<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    login: &Login,
+  ) -> Result<T, ApreadErrors> {
+    let body = self
+      .get_text_as(url, "application/json", Some(&login.access_token))
+      .await?;
+
+    self.parse(url, &body)
+  }
+
+  /// Sends a body as the account logged in, reading what comes back.
+  async fn send_as<T: DeserializeOwned>(
+    &self,
+    method: Method,
+    url: &str,
+    login: &Login,
+    content_type: &str,
+    body: Vec<u8>,
+  ) -> Result<T, ApreadErrors> {
+    let _permit = self.throttle.permit(url).await;
+
+    Ok(
+      self
+        .http
+        .request(method, url)
+        .bearer_auth(&login.access_token)
+        .header(CONTENT_TYPE, content_type)
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?,
+    )
+  }
+
+  /// Like [`get`](Self::get), but served from the discovery cache while
+  /// it's fresh. Only documents which parse are cached.
+  async fn get_discovery<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    accept: &str,
+  ) -> Result<T, ApreadErrors> {
+    let cache = match (&self.discovery, &self.fixtures) {
+      (Some(cache), None | Some(Fixtures::Record(_))) => cache,
+      _ => return self.get(url, accept).await,
+    };
+
+    if let Some(document) = cache
+      .get(url)
+      .and_then(|body| serde_json::from_str(&body).ok())
+    {
+      self.stats.cache_hit();
+      return Ok(document);
+    }
+
+    let body = self.get_text(url, accept).await?;
+    let document = self.parse(url, &body)?;
+
+    // A cache which can't be written to only makes reading slower.
+    self.kept("discovery cache", cache.put(url, &body));
+
+    Ok(document)
+  }
+
+  async fn get_text(
+    &self,
+    url: &str,
+    accept: &str,
+  ) -> Result<String, ApreadErrors> {
+    self.get_text_as(url, accept, None).await
+  }
+
+  /// Like [`get_text`](Self::get_text), but sending an access token if
+  /// there is one.
+  async fn get_text_as(
+    &self,
+    url: &str,
+    accept: &str,
+    token: Option<&str>,
+  ) -> Result<String, ApreadErrors> {
+    if let Some(Fixtures::Replay(dir)) = &self.fixtures {
+      return fixtures::replay(dir, url);
+    }
+
+    let _permit = self.throttle.permit(url).await;
+    // Checked once there's a permit, since the requests queued ahead may
+    // have found the instance down while this one waited.
+    self.breaker.check(url)?;
+
+    let started = Instant::now();
+    let mut request = self.http.get(url).header(ACCEPT, accept);
+
+    if let Some(token) = token {
+      request = request.bearer_auth(token);
+    }
+
+    let sent = request.send().await;
+
+    self.breaker.record(url, &sent);
+
+    let response = sent?;
+
+    self.stats.request(started.elapsed());
+    self.diagnostics.request(url, started.elapsed());
+
+    // An error page isn't the document asked for, so it's reported as the
+    // HTTP error it is, rather than read, captured or recorded.
+    let body = response.error_for_status()?.text().await?;
+
+    if let Some(Fixtures::Record(recorder)) = &self.fixtures {
+      recorder.record(url, &body)?;
+    }
+
+    Ok(body)
+  }
+
+  /// Looks up the WebFinger document for a handle.
+  pub async fn webfinger(
+    &self,
+    handle: &Handle,
+  ) -> Result<Webfinger, ApreadErrors> {
+    // Onion services are reached through Tor, which does the encrypting,
+    // so they seldom have certificates for https.
+    let scheme = match handle.is_onion() {
+      true if !self.tor => return Err(NeedsTor(handle.to_string()).into()),
+      true => "http",
+      false => self.scheme,
+    };
+
+    self
+      .get_discovery(&handle.to_webfinger_url(scheme), ACTIVITY_JSON)
+      .await
+  }
+
+  /// Reads the url of an ActivityPub document, which must be https unless
+  /// the client is [insecure](Self::insecure).
+  fn activity_url(&self, url: &str) -> Result<ApUrl, ApreadErrors> {
+    let url = ApUrl::parse(url)?;
+
+    if self.scheme == "https" && !url.is_onion() {
+      url.check_https()?;
+    }
+
+    Ok(url)
+  }
+
+  /// Fetches an actor document.
+  pub async fn actor(&self, url: &str) -> Result<Actor, ApreadErrors> {
+    let url = self.activity_url(url)?;
+    let actor: Actor =
+      self.get_discovery(url.as_str(), ACTIVITY_STREAMS).await?;
+
+    actor.check_origin(&url)?;
+
+    Ok(actor)
+  }
+
+  /// Fetches the top level of a collection, such as an actor's followers.
+  pub async fn collection<T: DeserializeOwned>(
+    &self,
+    url: &str,
+  ) -> Result<Collection<T>, ApreadErrors> {
+    let url = self.activity_url(url)?;
+    let collection: Collection<T> =
+      self.get(url.as_str(), ACTIVITY_STREAMS).await?;
+
+    collection.check_origin(&url)?;
+
+    Ok(collection)
+  }
+
+  /// Fetches a single page of a collection.
+  pub async fn collection_page<T: DeserializeOwned>(
+    &self,
+    url: &str,
+  ) -> Result<CollectionPage<T>, ApreadErrors> {
+    let url = self.activity_url(url)?;
+    let page: CollectionPage<T> =
+      self.get(url.as_str(), ACTIVITY_STREAMS).await?;
+
+    page.check_origin(&url)?;
+
+    Ok(page)
+  }
+
+  /// Fetches the first page of a collection, wherever it keeps it: linked,
+  /// inline, or in the collection itself.
+  pub async fn first_page_of<T: DeserializeOwned>(
+    &self,
+    collection: Collection<T>,
+  ) -> Result<CollectionPage<T>, ApreadErrors> {
+    match collection.first {
+      Some(PageRef::Url(url)) => self.collection_page(url.as_str()).await,
+      Some(PageRef::Inline(page)) => Ok(page),
+      None => Ok(collection.into_page()),
+    }
+  }
+
+  /// Fetches the top level of an outbox.
+  pub async fn outbox(&self, url: &str) -> Result<OutboxIndex, ApreadErrors> {
+    self.collection(url).await
+  }
+
+  /// Fetches a single outbox page.
+  pub async fn page(&self, url: &str) -> Result<Page, ApreadErrors> {
+    self.collection_page(url).await
+  }
+
+  /// Fetches a single post by its url.
+  pub async fn post(&self, url: &str) -> Result<Post, ApreadErrors> {
+    self
+      .get(self.activity_url(url)?.as_str(), ACTIVITY_STREAMS)
+      .await
+  }
+
+  /// Fetches a boosted post, from the archive if it's been fetched before.
+  /// Without an archive to keep it in, this is [`post`](Self::post).
+  pub async fn boosted(&self, url: &str) -> Result<Post, ApreadErrors> {
+    let store = match (&self.objects, &self.fixtures) {
+      (Some(store), None | Some(Fixtures::Record(_))) => store,
+      _ => return self.post(url).await,
+    };
+
+    if let Some(post) = store
+      .get(url)
+      .and_then(|stored| serde_json::from_str(&stored.body).ok())
+    {
+      self.stats.cache_hit();
+      return Ok(post);
+    }
+
+    let body = self
+      .get_text(self.activity_url(url)?.as_str(), ACTIVITY_STREAMS)
+      .await?;
+    let post = self.parse(url, &body)?;
+
+    // As with the discovery cache, failing to keep a post only means
+    // fetching it again next time.
+    self.kept("archive", store.put(url, &body));
+
+    Ok(post)
+  }
+
+  /// Fetches a document as ActivityStreams, keeping the response as it
+  /// came rather than reading it, for [`lint`](crate::lint).
+  pub async fn raw(&self, url: &str) -> Result<Raw, ApreadErrors> {
+    let _permit = self.throttle.permit(url).await;
+    let started = Instant::now();
+    let response = self
+      .http
+      .get(url)
+      .header(ACCEPT, ACTIVITY_STREAMS)
+      .send()
+      .await?;
+
+    self.stats.request(started.elapsed());
+
+    let status = response.status().as_u16();
+    let content_type = response
+      .headers()
+      .get(CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .map(str::to_owned);
+
+    Ok(Raw {
+      status,
+      content_type,
+      body: response.text().await?,
+    })
+  }
+
+  /// Downloads a file, such as an attachment, as it is.
+  pub async fn media(&self, url: &str) -> Result<Vec<u8>, ApreadErrors> {
+    let _permit = self.throttle.permit(url).await;
+    self.breaker.check(url)?;
+
+    let started = Instant::now();
+    let sent = self.http.get(url).send().await;
+
+    self.breaker.record(url, &sent);
+
+    let bytes = sent?.error_for_status()?.bytes().await?;
+
+    self.stats.request(started.elapsed());
+
+    Ok(bytes.to_vec())
+  }
+
+  /// Fetches an ordinary web page, as html.
+  pub async fn web_page(&self, url: &str) -> Result<String, ApreadErrors> {
+    self.get_text(url, "text/html").await
+  }
+
+  /// Fetches an RSS or Atom feed, as text.
+  pub async fn feed(&self, url: &str) -> Result<String, ApreadErrors> {
+    self
+      .get_text(
+        url,
+        "application/atom+xml, application/rss+xml, application/xml;q=0.9",
+      )
+      .await
+  }
+
+  /// Fetches a page of an instance's timeline for a hashtag, newest first,
+  /// from before the post with id `max_id` if there is one.
+  pub async fn tag_timeline(
+    &self,
+    tag: &Hashtag,
+    max_id: Option<&str>,
+  ) -> Result<Vec<mastodon::Status>, ApreadErrors> {
+    let url = tag.timeline_url(self.scheme, max_id)?;
+
+    self.get(&url, "application/json").await
+  }
+
+  /// The accounts an instance's profile directory lists, the most recently
+  /// active first.
+  pub async fn directory(
+    &self,
+    instance: &str,
+  ) -> Result<Vec<mastodon::Account>, ApreadErrors> {
+    let url = format!(
+      "{}://{}/api/v1/directory?order=active&limit={}",
+      self.scheme,
+      instance,
+      mastodon::PAGE_SIZE
+    );
+
+    self.get(&url, "application/json").await
+  }
+
+  /// The logged in account's lists.
+  pub async fn lists(&self) -> Result<Vec<mastodon::List>, ApreadErrors> {
+    let login = self.require_login()?;
+    let url = format!("{}://{}/api/v1/lists", self.scheme, login.instance);
+
+    self.get_as(&url, login).await
+  }
+
+  /// Fetches a page of one of the logged in account's lists, newest first,
+  /// from before the post with id `max_id` if there is one.
+  pub async fn list_timeline(
+    &self,
+    list: &mastodon::List,
+    max_id: Option<&str>,
+  ) -> Result<Vec<mastodon::Status>, ApreadErrors> {
+    let login = self.require_login()?;
+    let mut url = format!(
+      "{}://{}/api/v1/timelines/list/{}?limit={}",
+      self.scheme,
+      login.instance,
+      url::form_urlencoded::byte_serialize(list.id.as_bytes())
+        .collect::<String>(),
+      mastodon::PAGE_SIZE
+    );
+
+    if let Some(id) = max_id {
+      url.push_str("&max_id=");
+      url.extend(url::form_urlencoded::byte_serialize(id.as_bytes()));
+    }
+
+    self.get_as(&url, login).await
+  }
+
+  /// Searches the logged in account's instance for posts, giving back as
+  /// many as fit on a page, the most relevant first.
+  pub async fn search(
+    &self,
+    query: &str,
+  ) -> Result<Vec<mastodon::Status>, ApreadErrors> {
+    let login = self.require_login()?;
+    let query = url::form_urlencoded::Serializer::new(String::new())
+      .append_pair("q", query)
+      .append_pair("type", "statuses")
+      .append_pair("limit", &mastodon::PAGE_SIZE.to_string())
+      .finish();
+    let search = format!(
+      "{}://{}/api/v2/search?{}",
+      self.scheme, login.instance, query
+    );
+    let found: mastodon::Search = self.get_as(&search, login).await?;
+
+    Ok(found.statuses)
+  }
+
+  /// The account logged in as, if there's a login and its token could be
+  /// found. Without one, what's public is read all the same.
+  pub fn login(&self) -> Option<&Login> {
+    self.login.as_ref()?.get().ok()
+  }
+
+  /// The account logged in as, for what can't be read without one.
+  pub fn require_login(&self) -> Result<&Login, ApreadErrors> {
+    match &self.login {
+      Some(login) => Ok(login.get()?),
+      None => Err(NotLoggedIn.into()),
+    }
+  }
+
+  /// Opens one of the logged in instance's live streams, at the streaming
+  /// API's own host if the instance says it has one.
+  pub async fn open_stream(
+    &self,
+    stream: &Stream,
+  ) -> Result<Events, ApreadErrors> {
+    let login = self.require_login()?;
+    let instance_url =
+      format!("{}://{}/api/v2/instance", self.scheme, login.instance);
+    // Instances without the v2 document serve streams from their own host.
+    let base =
+      match self.get_as::<serde_json::Value>(&instance_url, login).await {
+        Ok(document) => document
+          .pointer("/configuration/urls/streaming")
+          .and_then(serde_json::Value::as_str)
+          .map(streaming::base_url),
+        Err(_) => None,
+      }
+      .unwrap_or_else(|| format!("{}://{}", self.scheme, login.instance));
+    let response = self
+      .http
+      .get(format!("{}{}", base, stream.path()))
+      .bearer_auth(&login.access_token)
+      .header(ACCEPT, "text/event-stream")
+      .send()
+      .await?
+      .error_for_status()?;
+
+    Ok(Events::new(response, &login.instance))
+  }
+
+  /// The logged in account's newest notifications, or only the mentions
+  /// among them.
+  pub async fn notifications(
+    &self,
+    mentions_only: bool,
+  ) -> Result<Vec<notifications::Raw>, ApreadErrors> {
+    let login = self.require_login()?;
+    let mut url = format!(
+      "{}://{}/api/v1/notifications?limit={}",
+      self.scheme,
+      login.instance,
+      mastodon::PAGE_SIZE
+    );
+
+    if mentions_only {
+      url.push_str("&types[]=mention");
+    }
+
+    self.get_as(&url, login).await
+  }
+
+  /// Registers apread as an OAuth app with an instance, to do what the
+  /// scopes say.
+  pub async fn register_app(
+    &self,
+    instance: &str,
+    scopes: &str,
+  ) -> Result<login::App, ApreadErrors> {
+    let body = serde_json::json!({
+      "client_name": "apread",
+      "redirect_uris": login::REDIRECT_URI,
+      "scopes": scopes,
+      "website": env!("CARGO_PKG_REPOSITORY"),
+    });
+    let url = format!("{}://{}/api/v1/apps", self.scheme, instance);
+
+    Ok(
+      self
+        .http
+        .post(url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?,
+    )
+  }
+
+  /// The page where an instance's user authorizes apread as an app.
+  pub fn authorize_url(
+    &self,
+    instance: &str,
+    app: &login::App,
+    scopes: &str,
+  ) -> String {
+    app.authorize_url(self.scheme, instance, scopes)
+  }
+
+  /// Trades the code an instance showed on authorizing apread for an
+  /// access token.
+  pub async fn access_token(
+    &self,
+    instance: &str,
+    app: &login::App,
+    code: &str,
+    scopes: &str,
+  ) -> Result<String, ApreadErrors> {
+    let body = serde_json::json!({
+      "grant_type": "authorization_code",
+      "code": code,
+      "client_id": app.client_id,
+      "client_secret": app.client_secret,
+      "redirect_uri": login::REDIRECT_URI,
+      "scope": scopes,
+    });
+    let url = format!("{}://{}/oauth/token", self.scheme, instance);
+    let token: login::Token = self
+      .http
+      .post(url)
+      .json(&body)
+      .send()
+      .await?
+      .error_for_status()?
+      .json()
+      .await?;
+
+    Ok(token.access_token)
+  }
+
+  /// The account an access token belongs to, on its instance.
+  pub async fn verify_credentials(
+    &self,
+    instance: &str,
+    access_token: &str,
+  ) -> Result<mastodon::Account, ApreadErrors> {
+    let url = format!(
+      "{}://{}/api/v1/accounts/verify_credentials",
+      self.scheme, instance
+    );
+    let body = self
+      .get_text_as(&url, "application/json", Some(access_token))
+      .await?;
+
+    self.parse(&url, &body)
+  }
+
+  /// Finds a post from anywhere on the logged in account's instance, by
+  /// its url, fetching it there if the instance hasn't seen it yet.
+  pub async fn find_status(
+    &self,
+    url: &str,
+  ) -> Result<mastodon::Status, ApreadErrors> {
+    let login = self.require_login()?;
+    let query = url::form_urlencoded::Serializer::new(String::new())
+      .append_pair("q", url)
+      .append_pair("type", "statuses")
+      .append_pair("resolve", "true")
+      .append_pair("limit", "1")
+      .finish();
+    let search = format!(
+      "{}://{}/api/v2/search?{}",
+      self.scheme, login.instance, query
+    );
+    let found: mastodon::Search = self.get_as(&search, login).await?;
+
+    found
+      .statuses
+      .into_iter()
+      .next()
+      .ok_or_else(|| NotFound(url.to_owned()).into())
+  }
+
+  /// Uploads media to attach to a post, giving back its id.
+  pub async fn upload_media(
+    &self,
+    media: &compose::Media,
+  ) -> Result<String, ApreadErrors> {
+    let login = self.writer()?;
+    let url = format!("{}://{}/api/v2/media", self.scheme, login.instance);
+    let uploaded: mastodon::Uploaded = self
+      .send_as(
+        Method::POST,
+        &url,
+        login,
+        &compose::Media::form_type(),
+        media.form(),
+      )
+      .await?;
+
+    Ok(uploaded.id)
+  }
+
+  /// Posts a draft as the logged in account, giving back the post made.
+  pub async fn post_status(
+    &self,
+    draft: &compose::Draft,
+  ) -> Result<mastodon::Status, ApreadErrors> {
+    self.send_draft(draft).await
+  }
+
+  /// Schedules a draft with a `scheduled_at` time, for the instance to post
+  /// then as the logged in account.
+  pub async fn schedule_status(
+    &self,
+    draft: &compose::Draft,
+  ) -> Result<mastodon::Scheduled, ApreadErrors> {
+    self.send_draft(draft).await
+  }
+
+  /// Sends a draft to be posted, now or when it's scheduled for.
+  async fn send_draft<T: DeserializeOwned>(
+    &self,
+    draft: &compose::Draft,
+  ) -> Result<T, ApreadErrors> {
+    let login = self.writer()?;
+    let url = format!("{}://{}/api/v1/statuses", self.scheme, login.instance);
+
+    self
+      .send_as(
+        Method::POST,
+        &url,
+        login,
+        "application/json",
+        serde_json::to_vec(draft)?,
+      )
+      .await
+  }
+
+  /// The posts the logged in account has scheduled, soonest first.
+  pub async fn scheduled_statuses(
+    &self,
+  ) -> Result<Vec<mastodon::Scheduled>, ApreadErrors> {
+    let login = self.require_login()?;
+    let url = format!(
+      "{}://{}/api/v1/scheduled_statuses?limit={}",
+      self.scheme,
+      login.instance,
+      mastodon::PAGE_SIZE
+    );
+
+    self.get_as(&url, login).await
+  }
+
+  /// Cancels a scheduled post, by its id.
+  pub async fn unschedule(&self, id: &str) -> Result<(), ApreadErrors> {
+    let login = self.writer()?;
+    let url = format!(
+      "{}://{}/api/v1/scheduled_statuses/{}",
+      self.scheme,
+      login.instance,
+      url::form_urlencoded::byte_serialize(id.as_bytes()).collect::<String>()
+    );
+    let _: serde_json::Value = self
+      .send_as(Method::DELETE, &url, login, "application/json", vec![])
+      .await?;
+
+    Ok(())
+  }
+
+  /// Favourites or boosts the post at a url, as the logged in account,
+  /// giving back the post as it now is.
+  pub async fn act(
+    &self,
+    url: &str,
+    action: compose::Action,
+  ) -> Result<mastodon::Status, ApreadErrors> {
+    let login = self.writer()?;
+    let status = self.find_status(url).await?;
+    let url = format!(
+      "{}://{}/api/v1/statuses/{}/{}",
+      self.scheme,
+      login.instance,
+      url::form_urlencoded::byte_serialize(status.id.as_bytes())
+        .collect::<String>(),
+      action.endpoint()
+    );
+
+    self
+      .send_as(
+        Method::POST,
+        &url,
+        login,
+        "application/x-www-form-urlencoded",
+        vec![],
+      )
+      .await
+  }
+
+  /// The login, if it's allowed to post.
+  fn writer(&self) -> Result<&Login, ApreadErrors> {
+    match self.require_login()? {
+      login if login.write => Ok(login),
+      _ => Err(ReadOnly.into()),
+    }
+  }
+
+  /// Fetches the newest posts of a Bluesky account.
+  #[cfg(feature = "bsky")]
+  pub async fn author_feed(
+    &self,
+    handle: &str,
+  ) -> Result<bsky::AuthorFeed, ApreadErrors> {
+    self
+      .get(
+        &bsky::author_feed_url(&self.appview, handle),
+        "application/json",
+      )
+      .await
+  }
+
+  /// Sends a message into a Matrix room, under the given transaction id.
+  #[cfg(feature = "matrix")]
+  pub async fn send_matrix(
+    &self,
+    room: &matrix::Room,
+    transaction: &str,
+    message: &matrix::Message,
+  ) -> Result<(), ApreadErrors> {
+    self
+      .http
+      .put(room.send_url(transaction))
+      .bearer_auth(&room.access_token)
+      .json(message)
+      .send()
+      .await?
+      .error_for_status()?;
+
+    Ok(())
+  }
+
+  /// Fetches the newest notes of a Nostr account, from the first of the
+  /// client's relays to answer.
+  #[cfg(feature = "nostr")]
+  pub async fn notes(
+    &self,
+    public_key: &str,
+  ) -> Result<Vec<nostr::Event>, ApreadErrors> {
+    let mut failure = None;
+
+    for relay in &self.relays {
+      match nostr::query(relay, public_key).await {
+        Ok(events) => return Ok(events),
+        Err(error) => failure = Some(error),
+      }
+    }
+
+    match failure {
+      Some(error) => Err(error),
+      None => Ok(vec![]),
+    }
+  }
+
+  /// POSTs a JSON body to a url, with any extra headers, and gives back
+  /// the status it was answered with.
+  pub async fn post_json(
+    &self,
+    url: &str,
+    body: Vec<u8>,
+    headers: &[(&'static str, String)],
+  ) -> Result<u16, ApreadErrors> {
+    let _permit = self.throttle.permit(url).await;
+    let mut request = self
+      .http
+      .post(url)
+      .header(CONTENT_TYPE, "application/json")
+      .body(body);
+
+    for (name, value) in headers {
+      request = request.header(*name, value);
+    }
+
+    Ok(request.send().await?.status().as_u16())
+  }
+
+  /// Checks a link still leads somewhere, with a HEAD request (or a GET,
+  /// for servers that won't answer HEAD).
+  pub async fn check(&self, url: &str) -> Health {
+    let _permit = self.throttle.permit(url).await;
+    let started = Instant::now();
+    let response = match self.http.head(url).send().await {
+      Ok(response)
+        if matches!(
+          response.status(),
+          StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        ) =>
+      {
+        self.http.get(url).send().await
+      }
+      response => response,
+    };
+
+    self.stats.request(started.elapsed());
+
+    match response {
+      Ok(response) => {
+        let code = response.status().as_u16();
+
+        if response.status().is_client_error()
+          || response.status().is_server_error()
+        {
+          Health::Dead { code }
+        } else {
+          Health::Alive { code }
+        }
+      }
+      Err(error) => Health::Unreachable {
+        reason: error.to_string(),
+      },
+    }
+  }
+
+  /// Follows a handle through WebFinger to its actor. If WebFinger fails,
+  /// as it will once an instance has moved, the actor the handle resolved
+  /// to last time is used instead.
+  pub async fn resolve(&self, handle: &Handle) -> Result<Actor, ApreadErrors> {
+    let url = match self.webfinger(handle).await {
+      Ok(webfinger) => webfinger.to_actor_url()?.as_str().to_owned(),
+      Err(error) => {
+        match self.identities().and_then(|known| known.actor(handle)) {
+          Some(url) => url,
+          None => return Err(error),
+        }
+      }
+    };
+    let actor = self.actor(&url).await?;
+
+    // As with the caches, failing to remember only means looking again.
+    if let Some(identities) = self.identities() {
+      self.kept("identities", identities.remember(handle, &url));
+    }
+
+    Ok(actor)
+  }
+
+  /// The handle of the actor at a url: the one it resolved from before, or
+  /// else the one the actor gives itself, on its own server.
+  pub async fn identify(&self, url: &str) -> Result<Handle, ApreadErrors> {
+    if let Some(handle) = self.identities().and_then(|known| known.handle(url))
+    {
+      return Ok(handle);
+    }
+
+    let actor = self.actor(url).await?;
+    let handle = match (&actor.preferred_username, &actor.id) {
+      // Unlike `Actor::handle`, this keeps any port, since it's to be
+      // looked up again.
+      (Some(id), Some(url)) => Handle {
+        id: id.clone(),
+        domain: url.authority(),
+      },
+      _ => return Err(BadHandleError.into()),
+    };
+
+    if let Some(identities) = self.identities() {
+      self.kept("identities", identities.remember(&handle, url));
+    }
+
+    Ok(handle)
+  }
+
+  /// Fetches the newest page of an actor's outbox.
+  pub async fn newest_page(&self, actor: &Actor) -> Result<Page, ApreadErrors> {
+    let outbox = self.outbox(actor.outbox.as_str()).await?;
+
+    self.first_page_of(outbox).await
+  }
+
+  /// Fetches the oldest page of an actor's outbox.
+  pub async fn oldest_page(&self, actor: &Actor) -> Result<Page, ApreadErrors> {
+    let outbox = self.outbox(actor.outbox.as_str()).await?;
+
+    self.last_page_of(outbox).await
+  }
+
+  /// Fetches the last page of an outbox, which holds its oldest posts.
+  pub async fn last_page_of(
+    &self,
+    outbox: OutboxIndex,
+  ) -> Result<Page, ApreadErrors> {
+    let url = outbox.last.ok_or(NoLastPage)?;
+
+    self.page(url.as_str()).await
+  }
+
+  /// Follows a handle all the way through to its newest outbox page.
+  pub async fn first_page(
+    &self,
+    handle: &Handle,
+  ) -> Result<Page, ApreadErrors> {
+    let actor = self.resolve(handle).await?;
+
+    self.newest_page(&actor).await
+  }
+}
+
+/// The HTTP client the settings ask for, with their timeouts, proxy and
+/// kind of address, to build on.
+fn builder(
+  settings: &Settings,
+) -> Result<reqwest::ClientBuilder, ApreadErrors> {
+  let mut http = reqwest::Client::builder();
+
+  if let Some(timeout) = settings.timeout {
+    http = http.timeout(Duration::from_secs(timeout));
+  }
+
+  if let Some(proxy) = proxy(settings) {
+    http = http.proxy(reqwest::Proxy::all(proxy)?);
+  }
+
+  if let Some(timeout) = settings.connect_timeout {
+    http = http.connect_timeout(Duration::from_secs(timeout));
+  }
+
+  // Binding to one kind of local address leaves only remote addresses of
+  // the same kind to connect to.
+  match settings.ip_version {
+    Some(IpVersion::Ipv4) => {
+      http = http.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    }
+    Some(IpVersion::Ipv6) => {
+      http = http.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED))
+    }
+    None => {}
+  }
+
+  Ok(http)
+}
+
+/// The proxy the settings ask for. Names are left for a SOCKS proxy to
+/// resolve, as Tor needs to reach onion services.
+fn proxy(settings: &Settings) -> Option<String> {
+  match &settings.socks5 {
+    Some(address) => Some(format!("socks5h://{}", address)),
+    None => settings.proxy.clone(),
+  }
+}
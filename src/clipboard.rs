@@ -0,0 +1,78 @@
+//! Copying to the system clipboard, for `apread copy`.
+//!
+//! There's no one clipboard to write to, so text is copied through
+//! whichever of the usual programs is installed: `pbcopy` on macOS, `clip`
+//! on Windows, and `wl-copy`, `xclip` or `xsel` elsewhere, Wayland's first.
+//! That's instead of a clipboard library such as [arboard], so reading a
+//! timeline never links one in, but it leaves copying to whichever of
+//! those programs is there.
+//!
+//! apread has no interactive reader to give `y` and `Y` keys for copying
+//! a post's url and markdown, so those are `apread copy N` and
+//! `apread copy --markdown N` instead, with the number the post was shown
+//! with.
+//!
+//! [arboard]: https://docs.rs/arboard
+
+use std::{
+  io::{self, Write},
+  process::{Command, Stdio},
+};
+
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, Localized},
+  ApreadErrors,
+};
+
+/// The programs which copy what they're given on stdin, with their flags,
+/// in the order they're tried.
+pub const PROGRAMS: &[(&str, &[&str])] = &[
+  ("pbcopy", &[]),
+  ("clip", &[]),
+  ("wl-copy", &[]),
+  ("xclip", &["-selection", "clipboard"]),
+  ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Raised when none of the [`PROGRAMS`] could copy.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoClipboard;
+
+impl Localized for NoClipboard {
+  fn id(&self) -> &'static str {
+    "error-no-clipboard"
+  }
+}
+
+/// Copies text to the clipboard with the first of the [`PROGRAMS`] which
+/// is installed and works: `xclip` is no use under Wayland without X, so
+/// one which fails makes way for the next.
+pub fn copy(text: &str) -> Result<(), ApreadErrors> {
+  for (program, args) in PROGRAMS {
+    let spawned = Command::new(program)
+      .args(*args)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .spawn();
+    let mut child = match spawned {
+      Ok(child) => child,
+      Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+      Err(error) => return Err(error.into()),
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+      // One which stops reading has failed, and says so by how it exits.
+      let _ = stdin.write_all(text.as_bytes());
+    }
+
+    if child.wait()?.success() {
+      return Ok(());
+    }
+  }
+
+  Err(NoClipboard.into())
+}
@@ -0,0 +1,121 @@
+//! ActivityStreams collections: outboxes, followers, featured posts, likes
+//! and replies all come as one of these.
+//!
+//! A collection either holds its items itself or, more often, points at
+//! pages of them, starting from `first`. Pages come linked by url or given
+//! inline, and ordered pages call their items `orderedItems` where
+//! unordered ones call them `items`; both are read the same way here.
+
+use serde::Deserialize;
+
+use crate::{ApUrl, BadUrl};
+
+/// The top level of a collection, holding or pointing at its items.
+///
+/// Not every server reports `last` or `totalItems` (GoToSocial and
+/// Pleroma both leave some of this out), so those are optional.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection<T> {
+  /// The first page of items. For an outbox, that's the newest.
+  pub first: Option<PageRef<T>>,
+  /// The url of the last page. For an outbox, that's the oldest.
+  pub last: Option<ApUrl>,
+  /// How many items the collection holds, all told.
+  pub total_items: Option<usize>,
+  /// The items, for small collections which hold them directly.
+  #[serde(default = "Vec::new", alias = "items")]
+  pub ordered_items: Vec<T>,
+}
+
+impl<T> Collection<T> {
+  /// Checks the collection's pages are on the same server as the
+  /// collection, which was fetched from `url`.
+  pub fn check_origin(&self, url: &ApUrl) -> Result<(), BadUrl> {
+    let first = match &self.first {
+      Some(PageRef::Url(first)) => Some(first),
+      Some(PageRef::Inline(page)) => return page.check_origin(url),
+      None => None,
+    };
+
+    check_links(url, [first, self.last.as_ref()])
+  }
+
+  /// The collection's own items as a page, for collections which aren't
+  /// split into pages.
+  pub fn into_page(self) -> CollectionPage<T> {
+    CollectionPage {
+      id: None,
+      ordered_items: self.ordered_items,
+      next: None,
+      prev: None,
+    }
+  }
+}
+
+/// A page of a collection, either linked by url or given inline.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PageRef<T> {
+  /// The page's url, which most servers give.
+  Url(ApUrl),
+  /// The page itself, as Bridgy Fed gives it.
+  Inline(CollectionPage<T>),
+}
+
+/// One page of a collection.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionPage<T> {
+  /// The page's own url, where it says.
+  pub id: Option<String>,
+  /// The items on this page. On an outbox page, that's newest first.
+  #[serde(default = "Vec::new", alias = "items")]
+  pub ordered_items: Vec<T>,
+  /// The url of the next page: for an outbox, of older posts.
+  pub next: Option<ApUrl>,
+  /// The url of the previous page: for an outbox, of newer posts.
+  pub prev: Option<ApUrl>,
+}
+
+impl<T> CollectionPage<T> {
+  /// Checks the pages either side of this one are on the same server as
+  /// it, which was fetched from `url`.
+  pub fn check_origin(&self, url: &ApUrl) -> Result<(), BadUrl> {
+    check_links(url, [self.next.as_ref(), self.prev.as_ref()])
+  }
+}
+
+fn check_links<'a>(
+  url: &ApUrl,
+  links: impl IntoIterator<Item = Option<&'a ApUrl>>,
+) -> Result<(), BadUrl> {
+  for link in links.into_iter().flatten() {
+    link.check_origin(url)?;
+  }
+
+  Ok(())
+}
+
+/// An item of a collection which only lists other documents, such as
+/// followers or likes, given either by url or inline.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ObjectRef {
+  /// The document's url.
+  Url(String),
+  /// The document itself, of which only its url is kept.
+  Inline {
+    /// The document's url.
+    id: String,
+  },
+}
+
+impl ObjectRef {
+  /// The document's url.
+  pub fn url(&self) -> &str {
+    match self {
+      Self::Url(url) | Self::Inline { id: url } => url,
+    }
+  }
+}
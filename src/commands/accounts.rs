@@ -0,0 +1,62 @@
+//! The accounts logged in to, and which of them apread uses.
+
+use apread::{login::Logins, ApreadErrors};
+
+use super::Globals;
+
+/// What to do with the accounts logged in to.
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+  /// Read and post as this account unless --as says otherwise
+  Default {
+    /// The account, as in @me@mastodon.social
+    account: String,
+  },
+  /// Log out of an account, forgetting its token
+  Remove {
+    /// The account, as in @me@mastodon.social
+    account: String,
+  },
+}
+
+/// Lists the accounts logged in to, marking the default, or changes them.
+pub fn run(
+  command: Option<Command>,
+  globals: &Globals,
+) -> Result<(), ApreadErrors> {
+  let mut logins = Logins::load(&globals.settings()?.logins_path()?)?;
+
+  match command {
+    None => {
+      for saved in logins.accounts() {
+        let name = saved.name();
+        let marker = match logins.default() == Some(name.as_str()) {
+          true => "*",
+          false => " ",
+        };
+        let access = match saved.write {
+          true => "read and write",
+          false => "read only",
+        };
+        let kept = match saved.in_keyring() {
+          true => "in the keyring",
+          false => "in the data directory",
+        };
+
+        println!("{} @{} ({}, token {})", marker, name, access, kept);
+      }
+    }
+    Some(Command::Default { account }) => {
+      logins.set_default(&account)?;
+      logins.save()?;
+      println!("Reading and posting as {}", account);
+    }
+    Some(Command::Remove { account }) => {
+      let saved = logins.remove(&account)?;
+      logins.save()?;
+      println!("Logged out of @{}", saved.name());
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,75 @@
+//! Syncing accounts into the archive, and reviewing what's been deleted.
+
+use std::io;
+
+use apread::{
+  archive, render,
+  timeline::{Author, Walk},
+  ApreadErrors, Handle,
+};
+use clap::ArgGroup;
+
+use super::Globals;
+
+/// Options for syncing the archive.
+#[derive(Debug, clap::Args)]
+#[command(group(
+  ArgGroup::new("what")
+    .required(true)
+    .multiple(true)
+    .args(["handles", "show_deleted"])
+))]
+pub struct Args {
+  /// Accounts to archive the posts of, as id@domain
+  handles: Vec<String>,
+  /// How many pages of each outbox to archive; posts missing from them are
+  /// marked deleted
+  #[arg(long, default_value_t = 1, value_name = "N")]
+  pages: usize,
+  /// How many of each account's posts older than the pages read to ask
+  /// their server about, to find the deleted ones
+  #[arg(long, default_value_t = archive::CHECKS, value_name = "N")]
+  checks: usize,
+  /// List the archived posts which have since been deleted
+  #[arg(long)]
+  show_deleted: bool,
+}
+
+/// Archives each account's newest posts, marking deleted the archived ones
+/// which have gone, then lists deleted posts if asked.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let archive = globals.archive()?;
+
+  if !args.handles.is_empty() {
+    let client = globals.client()?;
+    let walk = Walk {
+      pages: args.pages,
+      ..Walk::default()
+    };
+
+    for handle in &args.handles {
+      if client.interrupted() {
+        break;
+      }
+
+      let author = Author::new(Handle::parse_string(handle)?);
+      let synced =
+        archive::sync(&client, &archive, &author, walk, args.checks).await?;
+
+      println!(
+        "Archived {} posts from {}; {} newly deleted",
+        synced.archived,
+        handle,
+        synced.deleted.len()
+      );
+    }
+  }
+
+  if args.show_deleted {
+    let width = globals.settings()?.width;
+
+    render::deleted(&mut io::stdout().lock(), &archive.deleted()?, width)?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,64 @@
+//! Auditing an account's own posts.
+
+use std::io;
+
+use apread::{
+  audit::{self, Checks},
+  render,
+  settings::Format,
+  timeline::{self, Author, Walk},
+  ApreadErrors, Handle,
+};
+
+use super::Globals;
+
+/// Options for auditing an account.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to audit, as id@domain
+  handle: String,
+  /// A keyword which calls for a content warning, on top of the
+  /// cw_keywords setting; repeat for more than one
+  #[arg(long, value_name = "WORD")]
+  warn_on: Vec<String>,
+  /// Don't follow links to check they still work
+  #[arg(long)]
+  no_links: bool,
+  /// How many pages of the outbox to audit
+  #[arg(long, default_value_t = 1, value_name = "N")]
+  pages: usize,
+}
+
+/// Reports on an account's posts missing alt text, missing content
+/// warnings, or linking somewhere broken.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let client = globals.client()?;
+  let author = Author::new(Handle::parse_string(&args.handle)?);
+  let walk = Walk {
+    pages: args.pages,
+    ..Walk::default()
+  };
+  let entries = timeline::walk(&client, &author, walk).await?;
+
+  let mut warn_on = settings.cw_keywords.clone();
+  warn_on.extend(args.warn_on);
+
+  let checks = Checks {
+    warn_on,
+    links: !args.no_links,
+  };
+  let report = audit::audit(&client, &entries, &checks).await;
+  let out = &mut io::stdout().lock();
+
+  match settings
+    .format
+    .require("Audits", &[Format::Terminal, Format::Json, Format::Csv])?
+  {
+    Format::Json => render::audit_json(out, &report)?,
+    Format::Csv => render::audit_csv(out, &report)?,
+    _ => render::audit(out, &report, &settings.locale())?,
+  }
+
+  Ok(())
+}
@@ -0,0 +1,45 @@
+//! Archiving the whole of an account's outbox, a page at a time.
+
+use apread::{
+  archive,
+  timeline::{Author, Source},
+  ApreadErrors,
+};
+
+use super::Globals;
+
+/// Options for backfilling an account.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to archive every post of, as id@domain
+  handle: String,
+  /// Start again from the newest page, rather than where the last backfill
+  /// stopped
+  #[arg(long)]
+  restart: bool,
+}
+
+/// Archives every post in an account's outbox, carrying on from where an
+/// interrupted backfill stopped.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let author = Author::from_source(Source::parse(&args.handle)?);
+  let archive = globals.archive()?;
+
+  if args.restart {
+    archive.save_checkpoint(&author.source.to_string(), None)?;
+  }
+
+  let client = globals.client()?;
+  let backfilled = archive::backfill(&client, &archive, &author).await?;
+
+  println!(
+    "Archived {} posts from {} pages of {}",
+    backfilled.archived, backfilled.pages, author.source
+  );
+
+  if !backfilled.complete {
+    println!("Stopped before the oldest page; run again to carry on");
+  }
+
+  Ok(())
+}
@@ -0,0 +1,61 @@
+//! Saving posts worth keeping into the archive, and listing them again.
+
+use std::io;
+
+use apread::{render, ApreadErrors};
+
+use super::Globals;
+
+/// Options for bookmarking a post.
+#[derive(Debug, clap::Args)]
+pub struct AddArgs {
+  /// The url of the post to keep, or the number the last run showed it
+  /// with, as in 3 for [3]
+  url: String,
+  /// Tags to file the bookmark under; repeat for more than one
+  #[arg(long = "tag", value_name = "TAG")]
+  tags: Vec<String>,
+  /// A note to keep with the post
+  #[arg(long)]
+  note: Option<String>,
+}
+
+/// Options for listing bookmarks.
+#[derive(Debug, clap::Args)]
+pub struct ListArgs {
+  /// Only list bookmarks with this tag
+  #[arg(long)]
+  tag: Option<String>,
+}
+
+/// Fetches a post and bookmarks it, full content and all.
+pub async fn add(args: AddArgs, globals: &Globals) -> Result<(), ApreadErrors> {
+  let url = globals.post_url(args.url)?;
+  let client = globals.client()?;
+  let post = client.post(&url).await?;
+  let author = match &post.attributed_to {
+    Some(actor) => client.actor(actor).await?.handle(),
+    None => None,
+  };
+
+  globals.archive()?.bookmark(
+    &post,
+    author.as_deref(),
+    &args.tags,
+    args.note.as_deref(),
+  )?;
+
+  println!("Bookmarked {}", url);
+
+  Ok(())
+}
+
+/// Prints bookmarked posts, newest bookmark first.
+pub fn list(args: ListArgs, globals: &Globals) -> Result<(), ApreadErrors> {
+  let width = globals.settings()?.width;
+  let bookmarks = globals.archive()?.bookmarks(args.tag.as_deref())?;
+
+  render::bookmarks(&mut io::stdout().lock(), &bookmarks, width)?;
+
+  Ok(())
+}
@@ -0,0 +1,23 @@
+//! Boosting posts, as the account you've logged in as.
+
+use apread::{compose::Action, ApreadErrors};
+
+use super::Globals;
+
+/// Options for boosting a post.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The url of the post, or the number the last run showed it with, as in
+  /// 3 for [3]
+  url: String,
+}
+
+/// Boosts a post to your followers.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let url = globals.post_url(args.url)?;
+
+  globals.client()?.act(&url, Action::Boost).await?;
+  println!("Boosted {}", url);
+
+  Ok(())
+}
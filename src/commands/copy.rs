@@ -0,0 +1,34 @@
+//! Copying a post the last run showed to the clipboard.
+
+use apread::{clipboard, ApreadErrors, NoSuchPost};
+
+use super::Globals;
+
+/// Options for copying a post.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The number the post was shown with, as in 3 for [3]
+  number: usize,
+  /// Copy the post's content as markdown, rather than its url
+  #[arg(long)]
+  markdown: bool,
+}
+
+/// Copies the numbered post from the last run, its url or its content.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let last_run = globals.last_run()?;
+  let shown = last_run.post(args.number)?;
+  let text = match args.markdown {
+    true => {
+      let source = shown.source().ok_or(NoSuchPost(args.number))?;
+
+      globals.client()?.post(source).await?.markdown_content()
+    }
+    false => shown.page().ok_or(NoSuchPost(args.number))?.to_owned(),
+  };
+
+  clipboard::copy(&text)?;
+  println!("Copied post {}", args.number);
+
+  Ok(())
+}
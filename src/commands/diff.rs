@@ -0,0 +1,51 @@
+//! Comparing an account's profile against how it was last time.
+
+use apread::{profile::Profile, ApreadErrors, Handle};
+
+use super::Globals;
+
+/// Options for diffing a profile.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to compare, as id@domain
+  handle: String,
+}
+
+/// Reports how an account's name, bio and pinned posts have changed since
+/// the last snapshot, then keeps this one to compare against next time.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let handle = Handle::parse_string(&args.handle)?;
+  let client = globals.client()?;
+  let actor = client.resolve(&handle).await?;
+  let profile = Profile::fetch(&client, &actor).await?;
+  // Snapshots go by actor url, so they survive a handle being renamed.
+  let key = match &actor.id {
+    Some(id) => id.as_str().to_owned(),
+    None => handle.to_string(),
+  };
+  let archive = globals.archive()?;
+
+  match archive.profile(&key)? {
+    Some(snapshot) => {
+      let changes = snapshot.profile.changes(&client, &profile).await;
+
+      if changes.is_empty() {
+        println!("No changes to {} since {}", handle, snapshot.taken_at);
+      } else {
+        println!("Changes to {} since {}:", handle, snapshot.taken_at);
+
+        for change in changes {
+          println!("{}", change);
+        }
+      }
+    }
+    None => println!(
+      "No earlier snapshot of {}; keeping this one to compare against",
+      handle
+    ),
+  }
+
+  archive.store_profile(&key, &profile)?;
+
+  Ok(())
+}
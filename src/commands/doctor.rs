@@ -0,0 +1,73 @@
+//! Diagnosing problems, for bug reports.
+
+use std::io::{self, Write};
+
+use apread::{
+  doctor::{self, Check},
+  render, ApreadErrors, Handle,
+};
+
+use super::Globals;
+
+/// Options for diagnosing problems.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// An account to try finding, as id@domain, to check the network too
+  handle: Option<String>,
+}
+
+/// Prints a checklist of everything apread needs, and whether it works.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let mut checks = vec![doctor::config(globals.config_path().as_deref())];
+
+  // Bad settings fail the config check; the rest carry on with defaults.
+  let settings = globals.settings().unwrap_or_default();
+  checks.push(doctor::subscriptions(
+    settings.subscriptions_path().as_deref(),
+  ));
+  checks.push(doctor::cache_dir(settings.cache_dir().as_deref()));
+
+  let network = ["DNS", "Connection and TLS", "WebFinger"];
+
+  match args.handle.as_deref().map(Handle::parse_string) {
+    Some(Ok(handle)) => {
+      let client = globals.client().unwrap_or_default();
+      let dns = doctor::dns(&handle);
+      let resolved = dns.status == doctor::Status::Pass;
+
+      checks.push(dns);
+
+      if resolved {
+        checks.push(doctor::tls(&client, &handle).await);
+        checks.push(doctor::webfinger(&client, &handle).await);
+      } else {
+        checks.extend(
+          network[1..]
+            .iter()
+            .map(|name| Check::skipped(name, "the domain didn't resolve")),
+        );
+      }
+    }
+    Some(Err(error)) => return Err(error.into()),
+    None => checks.extend(
+      network
+        .iter()
+        .map(|name| Check::skipped(name, "no handle given")),
+    ),
+  }
+
+  let out = &mut io::stdout().lock();
+  let header = settings.messages().with(
+    "doctor-header",
+    &[
+      ("version", env!("CARGO_PKG_VERSION").into()),
+      ("os", std::env::consts::OS.into()),
+      ("arch", std::env::consts::ARCH.into()),
+    ],
+  );
+
+  writeln!(out, "{}\n", header)?;
+  render::doctor(out, &checks)?;
+
+  Ok(())
+}
@@ -0,0 +1,54 @@
+//! Reading everything archived from one instance.
+
+use std::io;
+
+use apread::{render, settings::Format, ApreadErrors};
+
+use super::Globals;
+
+/// Options for reading a domain.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The instance to read, as example.social
+  domain: String,
+  /// Show at most this many of its posts
+  #[arg(long, default_value_t = 20, value_name = "N")]
+  limit: usize,
+}
+
+/// Lists the archived accounts on a domain, then their posts together,
+/// newest first.
+pub fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let archive = globals.archive()?;
+  let domain = bare(&args.domain);
+  let accounts = archive.accounts_on(domain)?;
+  let posts = archive.posts_on(domain, Some(args.limit))?;
+  let out = &mut io::stdout().lock();
+
+  match settings.format.require_text("Domains")? {
+    Format::Json => render::domain_json(out, &accounts, &posts)?,
+    _ => render::domain(
+      out,
+      domain,
+      &accounts,
+      &posts,
+      settings.width,
+      &settings.locale(),
+    )?,
+  }
+
+  Ok(())
+}
+
+/// A domain however it's written, as `@example.social` or
+/// `https://example.social/`, as just `example.social`.
+fn bare(domain: &str) -> &str {
+  let domain = domain.trim().trim_start_matches('@');
+  let domain = domain
+    .strip_prefix("https://")
+    .or_else(|| domain.strip_prefix("http://"))
+    .unwrap_or(domain);
+
+  domain.trim_end_matches('/')
+}
@@ -0,0 +1,158 @@
+//! Drafts kept in the archive until they're sent, and posts scheduled on
+//! your instance.
+
+use apread::{
+  archive::SavedDraft,
+  compose::{self, Draft, EmptyDraft, Visibility},
+  timeline, ApreadErrors,
+};
+use chrono::{DateTime, FixedOffset};
+
+use super::{post, Globals};
+
+/// What to do with drafts.
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+  /// Write a new draft, in $VISUAL or $EDITOR unless the text is given
+  New {
+    /// The draft's text
+    text: Option<String>,
+    /// Hide the text behind this content warning
+    #[arg(long, value_name = "WARNING")]
+    cw: Option<String>,
+    /// Who to show the post to
+    #[arg(long, value_enum, default_value_t)]
+    visibility: Visibility,
+  },
+  /// Rewrite a draft in $VISUAL or $EDITOR
+  Edit {
+    /// The draft's number, as apread draft list shows it
+    id: i64,
+  },
+  /// List the drafts there are
+  List,
+  /// Post a draft, as the account you've logged in to with --write, and
+  /// throw it away
+  Send {
+    /// The draft's number, as apread draft list shows it
+    id: i64,
+    /// Schedule the post for this time, as 2023-06-01T09:00:00+01:00, for
+    /// the instance to post then
+    #[arg(long, value_name = "TIME", value_parser = timeline::parse_date)]
+    at: Option<DateTime<FixedOffset>>,
+  },
+  /// Throw a draft away without sending it
+  Delete {
+    /// The draft's number, as apread draft list shows it
+    id: i64,
+  },
+  /// List the posts scheduled on your instance
+  Scheduled,
+  /// Cancel a scheduled post
+  Unschedule {
+    /// The scheduled post's id, as apread draft scheduled shows it
+    id: String,
+  },
+}
+
+/// Runs a draft command.
+pub async fn run(
+  command: Command,
+  globals: &Globals,
+) -> Result<(), ApreadErrors> {
+  match command {
+    Command::New {
+      text,
+      cw,
+      visibility,
+    } => {
+      let text = match text {
+        Some(text) => text,
+        None => compose::edit("")?,
+      };
+
+      if text.is_empty() {
+        return Err(EmptyDraft.into());
+      }
+
+      let id =
+        globals
+          .archive()?
+          .save_draft(&text, cw.as_deref(), visibility)?;
+      println!("Saved draft {}", id);
+    }
+    Command::Edit { id } => {
+      let archive = globals.archive()?;
+      let text = compose::edit(&archive.draft(id)?.text)?;
+
+      if text.is_empty() {
+        return Err(EmptyDraft.into());
+      }
+
+      archive.update_draft(id, &text)?;
+      println!("Saved draft {}", id);
+    }
+    Command::List => {
+      for draft in globals.archive()?.drafts()? {
+        println!("{:>4}. {}", draft.id, describe(&draft));
+      }
+    }
+    Command::Send { id, at } => {
+      let archive = globals.archive()?;
+      let saved = archive.draft(id)?;
+      let draft = Draft {
+        status: saved.text,
+        spoiler_text: saved.spoiler_text,
+        visibility: saved.visibility,
+        scheduled_at: at.map(|at| at.to_rfc3339()),
+        ..Draft::default()
+      };
+
+      post::send(&globals.client()?, &draft, "Posted").await?;
+      archive.delete_draft(id)?;
+    }
+    Command::Delete { id } => {
+      globals.archive()?.delete_draft(id)?;
+      println!("Deleted draft {}", id);
+    }
+    Command::Scheduled => {
+      for scheduled in globals.client()?.scheduled_statuses().await? {
+        println!(
+          "{}  {}  {}",
+          scheduled.id,
+          scheduled.scheduled_at,
+          first_line(&scheduled.params.text)
+        );
+      }
+    }
+    Command::Unschedule { id } => {
+      globals.client()?.unschedule(&id).await?;
+      println!("Cancelled scheduled post {}", id);
+    }
+  }
+
+  Ok(())
+}
+
+/// A draft as one line: its warning, or the start of its text.
+fn describe(draft: &SavedDraft) -> String {
+  let shown = match &draft.spoiler_text {
+    Some(warning) => format!("CW: {}", warning),
+    None => first_line(&draft.text),
+  };
+
+  match draft.visibility {
+    Visibility::Public => shown,
+    visibility => format!("{} ({})", shown, visibility.name()),
+  }
+}
+
+/// The first line of some text, cut short if it's long.
+fn first_line(text: &str) -> String {
+  let line = text.lines().next().unwrap_or_default();
+
+  match line.char_indices().nth(60) {
+    Some((end, _)) => format!("{}…", &line[..end]),
+    None => line.to_owned(),
+  }
+}
@@ -0,0 +1,75 @@
+//! Exporting archived and freshly fetched posts for use elsewhere.
+
+use std::path::PathBuf;
+
+use apread::{
+  archive::ArchivedPost,
+  export,
+  timeline::{self, Author},
+  ApreadErrors, Handle,
+};
+use clap::ArgGroup;
+
+use super::Globals;
+
+/// Options for exporting posts.
+#[derive(Debug, clap::Args)]
+#[command(group(
+  ArgGroup::new("target")
+    .required(true)
+    .multiple(true)
+    .args(["sqlite", "ap_archive"])
+))]
+pub struct Args {
+  /// Accounts to fetch and export alongside the archive, as id@domain
+  handles: Vec<String>,
+  /// Write a standalone SQLite database to this (new) file
+  #[arg(long, value_name = "FILE")]
+  sqlite: Option<PathBuf>,
+  /// Write an ActivityPub account archive, as Mastodon exports, into this
+  /// directory
+  #[arg(long, value_name = "DIR")]
+  ap_archive: Option<PathBuf>,
+}
+
+/// Exports everything in the archive, plus the newest posts of any accounts
+/// given.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let archive = globals.archive()?;
+  let mut posts = archive.posts()?;
+  let bookmarks = archive.bookmarks(None)?;
+
+  if !args.handles.is_empty() {
+    let client = globals.client()?;
+
+    for handle in &args.handles {
+      if client.interrupted() {
+        break;
+      }
+
+      let author = Author::new(Handle::parse_string(handle)?);
+      let name = author.source.to_string();
+
+      posts.extend(
+        timeline::fetch(&client, &author)
+          .await?
+          .iter()
+          .filter_map(|entry| {
+            ArchivedPost::from_post(&entry.post, Some(&name))
+          }),
+      );
+    }
+  }
+
+  if let Some(path) = args.sqlite {
+    let written = export::sqlite(&path, &posts, &bookmarks)?;
+    println!("Exported {} posts to {}", written, path.display());
+  }
+
+  if let Some(dir) = args.ap_archive {
+    let written = export::ap_archive(&dir, &posts, &bookmarks)?;
+    println!("Exported {} posts to {}", written, dir.display());
+  }
+
+  Ok(())
+}
@@ -0,0 +1,23 @@
+//! Favouriting posts, as the account you've logged in as.
+
+use apread::{compose::Action, ApreadErrors};
+
+use super::Globals;
+
+/// Options for favouriting a post.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The url of the post, or the number the last run showed it with, as in
+  /// 3 for [3]
+  url: String,
+}
+
+/// Favourites a post.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let url = globals.post_url(args.url)?;
+
+  globals.client()?.act(&url, Action::Favourite).await?;
+  println!("Favourited {}", url);
+
+  Ok(())
+}
@@ -0,0 +1,52 @@
+//! Graphing who an account interacts with.
+
+use std::io;
+
+use apread::{graph, settings::Format, ApreadErrors, Handle, WrongFormat};
+
+use super::Globals;
+
+/// Options for graphing an account's interactions.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to start from, as id@domain
+  handle: String,
+  /// How far out to read: 1 reads only this account's posts, 2 also reads
+  /// the posts of everyone it mentions or boosts, and so on
+  #[arg(long, default_value_t = 1, value_name = "N")]
+  depth: usize,
+  /// How many pages of each outbox to read
+  #[arg(long, default_value_t = 1, value_name = "N")]
+  pages: usize,
+}
+
+/// Writes the graph of mentions and boosts around an account, as DOT
+/// unless `--format gexf` is asked for.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+
+  if !matches!(
+    settings.format,
+    Format::Terminal | Format::Dot | Format::Gexf
+  ) {
+    return Err(
+      WrongFormat {
+        what: "Graphs",
+        format: settings.format,
+      }
+      .into(),
+    );
+  }
+
+  let client = globals.client()?;
+  let handle = Handle::parse_string(&args.handle)?;
+  let graph = graph::build(&client, &handle, args.depth, args.pages).await?;
+  let out = &mut io::stdout().lock();
+
+  match settings.format {
+    Format::Gexf => graph.gexf(out)?,
+    _ => graph.dot(out)?,
+  }
+
+  Ok(())
+}
@@ -0,0 +1,31 @@
+//! Reading the revisions the archive has kept of an edited post.
+
+use std::io;
+
+use apread::{render, ApreadErrors};
+
+use super::Globals;
+
+/// Options for showing a post's history.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The url of the post, or the number the last run showed it with, as in
+  /// 3 for [3]
+  url: String,
+}
+
+/// Prints every archived version of a post, oldest first.
+pub fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let url = globals.post_url(args.url)?;
+  let settings = globals.settings()?;
+  let revisions = globals.archive()?.history(&url)?;
+
+  render::history(
+    &mut io::stdout().lock(),
+    &revisions,
+    settings.width,
+    &settings.locale(),
+  )?;
+
+  Ok(())
+}
@@ -0,0 +1,31 @@
+//! Importing a Mastodon account export into the archive.
+
+use std::path::PathBuf;
+
+use apread::{import, ApreadErrors};
+
+use super::Globals;
+
+/// Options for importing an account export.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The export: Mastodon's zip file, or a directory unzipped from one
+  path: PathBuf,
+}
+
+/// Archives every post in an account export, and keeps its media beside
+/// the archive.
+pub fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let archive = globals.archive()?;
+  let media = globals.settings()?.media_dir()?;
+  let imported = import::mastodon(&args.path, &archive, &media)?;
+
+  println!(
+    "Imported {} posts and {} media files from {}",
+    imported.posts,
+    imported.media,
+    args.path.display()
+  );
+
+  Ok(())
+}
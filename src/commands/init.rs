@@ -0,0 +1,69 @@
+//! Setting apread up for the first time, by answering a few questions.
+
+use std::io::{self, Write};
+
+use apread::{
+  init::{self, Setup},
+  ApreadErrors, NoConfigDir,
+};
+
+use super::{login, Globals};
+
+/// Options for setting up.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// Replace the config file if there's one already
+  #[arg(long)]
+  force: bool,
+}
+
+/// Asks which accounts to follow and how to show them, writes the config
+/// and subscriptions files, then offers to log in.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let config = globals.config_path().ok_or(NoConfigDir)?;
+  let settings = globals.settings()?;
+  let messages = settings.messages();
+  let subscriptions = settings.subscriptions_path().ok_or(NoConfigDir)?;
+
+  init::ready(&config, args.force)?;
+
+  let input = &mut io::stdin().lock();
+  let output = &mut io::stderr();
+  let setup = Setup::ask(input, output, &messages)?;
+
+  setup.write(&config, &subscriptions, args.force)?;
+  println!(
+    "{}",
+    messages.with(
+      "init-wrote",
+      &[("path", config.display().to_string().into())]
+    )
+  );
+
+  if !setup.handles.is_empty() {
+    println!(
+      "{}",
+      messages.with(
+        "init-added",
+        &[
+          ("handles", setup.handles.join(", ").into()),
+          ("path", subscriptions.display().to_string().into()),
+        ],
+      )
+    );
+  }
+
+  let skip = messages.get("init-skip");
+  let instance =
+    init::question(input, output, &messages.get("init-login"), &skip)?;
+  output.flush()?;
+
+  if instance.is_empty()
+    || instance.to_lowercase() == skip.to_lowercase()
+    || instance.eq_ignore_ascii_case("skip")
+  {
+    return Ok(());
+  }
+
+  login::run(login::Args::instance(instance), globals).await
+}
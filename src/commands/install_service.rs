@@ -0,0 +1,69 @@
+//! Installing `apread watch` as a systemd user service.
+
+use std::{fs, net::SocketAddr};
+
+use apread::{systemd, ApreadErrors};
+
+/// Options for installing the service.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// Seconds to wait between looking for new posts
+  #[arg(long, value_name = "SECS")]
+  every: Option<u64>,
+  /// Also install a socket unit serving Prometheus metrics at this
+  /// address, as 127.0.0.1:9185
+  #[arg(long, value_name = "ADDR")]
+  metrics: Option<SocketAddr>,
+  /// Print the units instead of installing them
+  #[arg(long)]
+  print: bool,
+}
+
+/// Writes a user unit running `apread watch --systemd`, and a socket unit
+/// for its metrics if asked, then says how to start them.
+pub fn run(args: Args) -> Result<(), ApreadErrors> {
+  let exe = std::env::current_exe()?;
+  let watch_args: Vec<String> = args
+    .every
+    .map(|every| vec!["--every".to_owned(), every.to_string()])
+    .unwrap_or_default();
+  let service = systemd::service(
+    &exe.to_string_lossy(),
+    &watch_args,
+    args.metrics.is_some(),
+  );
+  let mut units = vec![(systemd::SERVICE, service)];
+
+  if let Some(address) = args.metrics {
+    units.push((systemd::SOCKET, systemd::socket(address)));
+  }
+
+  if args.print {
+    for (name, unit) in &units {
+      println!("# {}\n{}", name, unit);
+    }
+
+    return Ok(());
+  }
+
+  let dir = systemd::unit_dir()?;
+
+  fs::create_dir_all(&dir)?;
+
+  for (name, unit) in &units {
+    let path = dir.join(name);
+
+    fs::write(&path, unit)?;
+    println!("Wrote {}", path.display());
+  }
+
+  let start: Vec<_> = units.iter().map(|(name, _)| *name).collect();
+
+  println!(
+    "Start it now and at every login with:\n\n    systemctl --user \
+     daemon-reload\n    systemctl --user enable --now {}",
+    start.join(" ")
+  );
+
+  Ok(())
+}
@@ -0,0 +1,56 @@
+//! Listing the links an account has posted.
+
+use std::io;
+
+use apread::{
+  links, render,
+  settings::Format,
+  timeline::{self, Author, Walk},
+  ApreadErrors, Handle,
+};
+
+use super::Globals;
+
+/// Options for listing an account's links.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account whose links to list, as id@domain
+  handle: String,
+  /// Follow each link, and report the ones which no longer work
+  #[arg(long)]
+  check: bool,
+  /// How many pages of the outbox to look through
+  #[arg(long, default_value_t = 1, value_name = "N")]
+  pages: usize,
+}
+
+/// Lists every link in an account's posts, with how often and since when
+/// it's been posted.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let client = globals.client()?;
+  let author = Author::new(Handle::parse_string(&args.handle)?);
+  let walk = Walk {
+    pages: args.pages,
+    ..Walk::default()
+  };
+  let entries = timeline::walk(&client, &author, walk).await?;
+  let mut links = links::tally(&entries);
+
+  if args.check {
+    links::check(&client, &mut links).await;
+  }
+
+  let out = &mut io::stdout().lock();
+
+  match settings
+    .format
+    .require("Links", &[Format::Terminal, Format::Json, Format::Csv])?
+  {
+    Format::Json => render::links_json(out, &links)?,
+    Format::Csv => render::links_csv(out, &links)?,
+    _ => render::links(out, &links, &settings.locale())?,
+  }
+
+  Ok(())
+}
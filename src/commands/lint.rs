@@ -0,0 +1,26 @@
+//! Checking an ActivityPub document for the problems apread would have
+//! reading it.
+
+use std::io;
+
+use apread::{lint, render, ApreadErrors};
+
+use super::Globals;
+
+/// Options for linting a document.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The url of the actor, collection, post or other document to check
+  url: String,
+}
+
+/// Fetches a document and reports how it falls short of what apread
+/// expects.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let response = globals.client()?.raw(&args.url).await?;
+  let issues = lint::lint(&args.url, &response);
+
+  render::lint(&mut io::stdout().lock(), &args.url, &issues)?;
+
+  Ok(())
+}
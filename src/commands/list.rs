@@ -0,0 +1,33 @@
+//! Reading your lists, from the instance you've logged in to.
+
+use apread::{timeline::Source, ApreadErrors};
+
+use super::{read, Globals};
+
+/// Options for reading a list.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The name of the list to read, ignoring case; leave this out to see
+  /// which lists there are
+  name: Option<String>,
+  #[command(flatten)]
+  options: read::Options,
+}
+
+/// Prints the newest posts on one of your lists, as a read of an account
+/// would, or the names of your lists.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let Some(name) = args.name else {
+    for list in globals.client()?.lists().await? {
+      println!("{}", list.title);
+    }
+
+    return Ok(());
+  };
+
+  read::run(
+    read::Args::source(Source::List(name), args.options),
+    globals,
+  )
+  .await
+}
@@ -0,0 +1,89 @@
+//! Logging in to your own instance, for reading your lists and
+//! notifications, and for posting. Each account logged in to is kept, and
+//! the newest is used until `--as` or `apread accounts default` says
+//! otherwise.
+
+use std::io::{self, Write};
+
+use apread::{
+  login::{self, Login, Logins},
+  ApreadErrors,
+};
+
+use super::Globals;
+
+/// Options for logging in.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The domain of your instance, as in mastodon.social
+  instance: String,
+  /// Don't open the authorization page in the browser, only print it
+  #[arg(long)]
+  no_browser: bool,
+  /// Ask to post and reply as well as read, for apread post and apread
+  /// reply
+  #[arg(long)]
+  write: bool,
+  /// Keep the token in the data directory, rather than the system keyring
+  #[arg(long)]
+  no_keyring: bool,
+}
+
+impl Args {
+  /// Logging in to read an instance, as asked for by default.
+  pub fn instance(instance: impl Into<String>) -> Self {
+    Self {
+      instance: instance.into(),
+      no_browser: false,
+      write: false,
+      no_keyring: false,
+    }
+  }
+}
+
+/// Authorizes apread to read an account, through the instance's OAuth, and
+/// keeps the token for later runs, beside any other accounts'.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let client = globals.client()?;
+  let instance = args.instance.trim_start_matches('@');
+  let scopes = login::scopes(args.write);
+  let app = client.register_app(instance, scopes).await?;
+  let page = client.authorize_url(instance, &app, scopes);
+
+  eprintln!(
+    "Authorize apread to read your account at:\n\n    {}\n",
+    page
+  );
+
+  // Failing to open a browser only means copying the link by hand.
+  if !args.no_browser && open::that(&page).is_err() {
+    eprintln!("(couldn't open a browser, so copy the link above)");
+  }
+
+  eprint!("Then paste the code it shows you here: ");
+  io::stderr().flush()?;
+
+  let mut code = String::new();
+  io::stdin().read_line(&mut code)?;
+
+  let access_token = client
+    .access_token(instance, &app, code.trim(), scopes)
+    .await?;
+  let account = client.verify_credentials(instance, &access_token).await?;
+  let login = Login {
+    instance: instance.to_owned(),
+    account: account.acct,
+    access_token,
+    write: args.write,
+  };
+
+  let mut logins = Logins::load(&settings.logins_path()?)?;
+  let name = login.name();
+
+  logins.add(login, !args.no_keyring);
+  logins.save()?;
+  println!("Logged in as @{}", name);
+
+  Ok(())
+}
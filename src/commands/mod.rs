@@ -0,0 +1,312 @@
+//! The subcommands apread runs, and the options they share.
+
+use std::{io, path::PathBuf};
+
+use apread::{
+  activity_log::ActivityLog,
+  archive::Archive,
+  capture::ErrorCapture,
+  diagnostics::Diagnostics,
+  fixtures::Recorder,
+  i18n::Messages,
+  interrupt::Interrupt,
+  last_run::LastRun,
+  links::Style,
+  login::Logins,
+  math::Math,
+  render,
+  settings::{Format, IpVersion, Overrides, Settings},
+  stats::Stats,
+  ApreadErrors, Client, NoSuchPost,
+};
+
+pub mod accounts;
+pub mod archive;
+pub mod audit;
+pub mod backfill;
+pub mod bookmark;
+pub mod boost;
+pub mod copy;
+pub mod diff;
+pub mod doctor;
+pub mod domain;
+pub mod draft;
+pub mod export;
+pub mod fav;
+pub mod graph;
+pub mod history;
+pub mod import;
+pub mod init;
+pub mod install_service;
+pub mod links;
+pub mod lint;
+pub mod list;
+pub mod login;
+pub mod notifications;
+pub mod open;
+pub mod plugin;
+pub mod post;
+pub mod profile;
+pub mod prune;
+pub mod queue;
+pub mod read;
+pub mod reply;
+pub mod search;
+pub mod similar;
+pub mod status;
+pub mod stream;
+pub mod suggest;
+pub mod tag;
+pub mod verify;
+pub mod watch;
+
+/// Options which apply whichever command runs.
+#[derive(Debug, clap::Args)]
+pub struct Globals {
+  /// Read settings from this file instead of the default one
+  #[arg(long, global = true, value_name = "FILE")]
+  config: Option<PathBuf>,
+  /// How many columns to wrap text at [env: APREAD_WIDTH]
+  #[arg(long, global = true)]
+  width: Option<usize>,
+  /// How to write timelines (or graphs) out [env: APREAD_FORMAT]
+  #[arg(long, global = true)]
+  format: Option<Format>,
+  /// Seconds to wait on a request before giving up [env: APREAD_TIMEOUT]
+  #[arg(long, global = true, value_name = "SECS")]
+  timeout: Option<u64>,
+  /// Send requests through this proxy [env: APREAD_PROXY]
+  #[arg(long, global = true, value_name = "URL")]
+  proxy: Option<String>,
+  /// Send requests through this SOCKS5 proxy, as Tor's 127.0.0.1:9050,
+  /// which is needed to read accounts on .onion instances [env:
+  /// APREAD_SOCKS5]
+  #[arg(long, global = true, value_name = "ADDRESS", conflicts_with = "proxy")]
+  socks5: Option<String>,
+  /// Look instances up with this DNS-over-HTTPS resolver, as in
+  /// https://1.1.1.1/dns-query, rather than the system's DNS [env:
+  /// APREAD_DOH]
+  #[arg(long, global = true, value_name = "URL")]
+  doh: Option<String>,
+  /// Connect to instances over IPv4 only [env: APREAD_IP_VERSION=ipv4]
+  #[arg(long, global = true, conflicts_with = "ipv6")]
+  ipv4: bool,
+  /// Connect to instances over IPv6 only [env: APREAD_IP_VERSION=ipv6]
+  #[arg(long, global = true)]
+  ipv6: bool,
+  /// Seconds to spend connecting to an instance, split between its
+  /// addresses so a broken one is given up on sooner [env:
+  /// APREAD_CONNECT_TIMEOUT]
+  #[arg(long, global = true, value_name = "SECS")]
+  connect_timeout: Option<u64>,
+  /// Requests to have in flight to any one instance at once, however many
+  /// accounts are being read, or 0 for no limit [env: APREAD_PER_INSTANCE]
+  #[arg(long, global = true, value_name = "N")]
+  per_instance: Option<usize>,
+  /// Keep cached documents here [env: APREAD_CACHE_DIR]
+  #[arg(long, global = true, value_name = "DIR")]
+  cache_dir: Option<PathBuf>,
+  /// Keep the archive, and media imported into it, here [env:
+  /// APREAD_DATA_DIR]
+  #[arg(long, global = true, value_name = "DIR")]
+  data_dir: Option<PathBuf>,
+  /// Seconds to remember how to find an account, or 0 to look it up every
+  /// time [env: APREAD_DISCOVERY_TTL]
+  #[arg(long, global = true, value_name = "SECS")]
+  discovery_ttl: Option<u64>,
+  /// Write dates and numbers for this locale, as in de_DE [env:
+  /// APREAD_LOCALE, or LC_ALL, LC_TIME or LANG]
+  #[arg(long, global = true, value_name = "LOCALE")]
+  locale: Option<String>,
+  /// Write apread's own messages in this language, as in de [env:
+  /// APREAD_LANG, or LANGUAGE, LC_ALL, LC_MESSAGES or LANG]
+  #[arg(long, global = true, value_name = "LANG")]
+  lang: Option<String>,
+  /// Write timelines for a screen reader: no color, indentation or markup,
+  /// with emoji, content warnings and attachments read out in words [env:
+  /// APREAD_SCREEN_READER]
+  #[arg(long, global = true)]
+  screen_reader: bool,
+  /// Write mathematics as readable text, or verbatim as the post has it
+  /// [env: APREAD_MATH]
+  #[arg(long, global = true)]
+  math: Option<Math>,
+  /// Write links inline, or as numbered footnotes listed under each post so
+  /// long urls don't break up the text [env: APREAD_LINKS]
+  #[arg(long, global = true, value_name = "STYLE")]
+  links: Option<Style>,
+  /// Append every activity fetched, with where and when it was fetched
+  /// from, to this JSON Lines file
+  #[arg(long, global = true, value_name = "FILE")]
+  log_activities: Option<PathBuf>,
+  /// Write any response apread can't read into this directory, with its
+  /// url and apread's version and with secrets taken out, to attach to a
+  /// bug report
+  #[arg(long, global = true, value_name = "DIR")]
+  capture_error: Option<PathBuf>,
+  /// Save every response fetched into this directory
+  #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+  record_fixtures: Option<PathBuf>,
+  /// Serve responses from a directory saved with --record-fixtures,
+  /// without touching the network
+  #[arg(long, global = true, value_name = "DIR")]
+  replay: Option<PathBuf>,
+  /// Read and post as this account, as in @me@mastodon.social, rather than
+  /// the one logged in to last [env: APREAD_ACCOUNT]
+  #[arg(long = "as", global = true, value_name = "ACCOUNT")]
+  account: Option<String>,
+  /// Say how many requests were made, how long they took and how many were
+  /// served from cache, once the command is done
+  #[arg(long, global = true)]
+  pub verbose: bool,
+  /// Counts every request the client makes.
+  #[arg(skip)]
+  pub stats: Stats,
+  /// Collects what goes wrong without stopping the command.
+  #[arg(skip)]
+  pub diagnostics: Diagnostics,
+  /// Set on Ctrl-C, to stop long crawls where they are.
+  #[arg(skip)]
+  pub interrupt: Interrupt,
+}
+
+impl Globals {
+  /// The format asked for on the command line, if one was.
+  pub fn format(&self) -> Option<Format> {
+    self.format
+  }
+
+  /// Settings from the config file and environment, with these options
+  /// applied over them.
+  pub fn settings(&self) -> Result<Settings, ApreadErrors> {
+    let path = self.config_path();
+
+    Settings::load(
+      path.as_deref(),
+      Overrides {
+        width: self.width,
+        format: self.format,
+        timeout: self.timeout,
+        proxy: self.proxy.clone(),
+        socks5: self.socks5.clone(),
+        doh: self.doh.clone(),
+        ip_version: self.ip_version(),
+        connect_timeout: self.connect_timeout,
+        per_instance: self.per_instance,
+        cache_dir: self.cache_dir.clone(),
+        data_dir: self.data_dir.clone(),
+        discovery_ttl: self.discovery_ttl,
+        locale: self.locale.clone(),
+        lang: self.lang.clone(),
+        screen_reader: self.screen_reader.then_some(true),
+        math: self.math,
+        links: self.links,
+        account: self.account.clone(),
+      },
+    )
+  }
+
+  /// Writes what's gone wrong without stopping the command so far to
+  /// stderr, in one block after whatever it wrote out, as JSON if that's
+  /// the format.
+  pub fn report(&self, format: Format) -> io::Result<()> {
+    let diagnostics = self.diagnostics.take();
+
+    if diagnostics.is_empty() {
+      return Ok(());
+    }
+
+    let out = &mut io::stderr().lock();
+
+    match format {
+      Format::Json => render::diagnostics_json(out, &diagnostics),
+      _ => render::diagnostics(out, &diagnostics, &self.messages()),
+    }
+  }
+
+  /// apread's own messages, in the language these options ask for; in
+  /// English if the settings can't be read.
+  pub fn messages(&self) -> Messages {
+    self.settings().unwrap_or_default().messages()
+  }
+
+  /// The IP version asked for on the command line, if one was.
+  fn ip_version(&self) -> Option<IpVersion> {
+    match (self.ipv4, self.ipv6) {
+      (true, _) => Some(IpVersion::Ipv4),
+      (_, true) => Some(IpVersion::Ipv6),
+      _ => None,
+    }
+  }
+
+  /// The config file these options point at, if there's anywhere for one.
+  pub fn config_path(&self) -> Option<PathBuf> {
+    self.config.clone().or_else(Settings::default_path)
+  }
+
+  /// The posts the last run showed, to pick out by number; none if there's
+  /// no cache directory to have kept them in.
+  pub fn last_run(&self) -> Result<LastRun, ApreadErrors> {
+    match self.settings()?.last_run() {
+      Some(path) => LastRun::load(&path),
+      None => Ok(LastRun::default()),
+    }
+  }
+
+  /// The url of a post given by its url, or by the number the last run
+  /// showed it with.
+  pub fn post_url(&self, url: String) -> Result<String, ApreadErrors> {
+    let Ok(number) = url.parse() else {
+      return Ok(url);
+    };
+    let last_run = self.last_run()?;
+    let source = last_run.post(number)?.source();
+
+    Ok(source.ok_or(NoSuchPost(number))?.to_owned())
+  }
+
+  /// The archive, in the data directory these options point at.
+  pub fn archive(&self) -> Result<Archive, ApreadErrors> {
+    Archive::open_in(&self.settings()?.data_dir()?)
+  }
+
+  /// A client set up as these options ask.
+  pub fn client(&self) -> Result<Client, ApreadErrors> {
+    let settings = self.settings()?;
+    let mut client = Client::from_settings(&settings)?;
+
+    if let Some(dir) = &self.record_fixtures {
+      client = client.recording(Recorder::new(dir)?);
+    }
+
+    if let Some(dir) = &self.replay {
+      client = client.replaying(dir);
+    }
+
+    if let Some(path) = &self.log_activities {
+      client = client.logging_activities(ActivityLog::open(path)?);
+    }
+
+    if let Some(dir) = &self.capture_error {
+      client = client.capturing_errors(ErrorCapture::new(dir));
+    }
+
+    let logins = Logins::load(&settings.logins_path()?)?;
+
+    if let Some(login) = logins.pending(settings.account.as_deref())? {
+      client = client.logging_in(login);
+    }
+
+    // Without an archive, boosted posts are simply fetched every time.
+    if let Ok(archive) = self.archive() {
+      client = client.caching_objects(archive);
+    }
+
+    Ok(
+      client
+        .counting(self.stats.clone())
+        .reporting(self.diagnostics.clone())
+        .interruptible(self.interrupt.clone()),
+    )
+  }
+}
@@ -0,0 +1,69 @@
+//! Reading the notifications of the account you've logged in as.
+
+use std::io;
+
+use apread::{
+  notifications::{LastRead, Notification},
+  render,
+  settings::Format,
+  ApreadErrors,
+};
+
+use super::Globals;
+
+/// Options for reading notifications.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// Only show mentions, leaving out boosts, favourites and follows
+  #[arg(long)]
+  mentions_only: bool,
+  /// Only show notifications newer than the newest one shown before
+  #[arg(long)]
+  unread: bool,
+  /// Leave the notifications shown unread, for showing again next time
+  #[arg(long)]
+  keep_unread: bool,
+}
+
+/// Prints the newest notifications, marking those not shown before as new,
+/// then marks them all read.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let client = globals.client()?;
+  let login = client.require_login()?;
+  let path = settings.notifications_path()?;
+  let last_read = LastRead::load(&path, login)?;
+  let mut notifications: Vec<_> = client
+    .notifications(args.mentions_only)
+    .await?
+    .into_iter()
+    .map(|raw| Notification::new(&login.instance, raw, last_read.as_deref()))
+    .collect();
+
+  if args.unread {
+    notifications.retain(|notification| notification.unread);
+  }
+
+  let out = &mut io::stdout().lock();
+
+  match settings.format.require_text("Notifications")? {
+    Format::Json => render::notifications_json(out, &notifications)?,
+    _ => render::notifications(
+      out,
+      &notifications,
+      settings.width,
+      &settings.locale(),
+    )?,
+  }
+
+  // Newest first, so the first unread one is the newest of all.
+  let newest = notifications
+    .iter()
+    .find(|notification| notification.unread);
+
+  if let (Some(newest), false) = (newest, args.keep_unread) {
+    LastRead::save(&path, login, &newest.id)?;
+  }
+
+  Ok(())
+}
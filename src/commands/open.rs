@@ -0,0 +1,29 @@
+//! Opening a post the last run showed in the browser.
+//!
+//! This and `apread read --open N` are the only ways to open a post: apread
+//! has no interactive mode, so there's no `o` key to open the focused post
+//! with, and one is out of scope until there is.
+
+use apread::{ApreadErrors, NoSuchPost};
+
+use super::Globals;
+
+/// Options for opening a post.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The number the post was shown with, as in 3 for [3]
+  number: usize,
+}
+
+/// Opens the numbered post from the last run in the browser.
+pub fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let last_run = globals.last_run()?;
+  let page = last_run
+    .post(args.number)?
+    .page()
+    .ok_or(NoSuchPost(args.number))?;
+
+  open::that(page)?;
+
+  Ok(())
+}
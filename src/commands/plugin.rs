@@ -0,0 +1,40 @@
+//! Running `apread-NAME` programs as apread's own commands.
+
+use std::env;
+
+use apread::{
+  plugin::{self, Context},
+  settings::{Overrides, Settings},
+  ApreadErrors,
+};
+
+/// Runs the plugin `apread NAME ...` asks for, when NAME isn't one of
+/// `own`, the names of apread's own commands, and there's an `apread-NAME`
+/// on the `PATH`, giving the exit code it finished with. Gives none if
+/// there's no such plugin, for apread to carry on as it would.
+///
+/// Plugins are looked for before apread's own flags are read, so they're
+/// set through the environment rather than on the command line.
+pub fn dispatch(own: &[String]) -> Result<Option<i32>, ApreadErrors> {
+  let mut args = env::args().skip(1);
+  let Some(name) = args.next() else {
+    return Ok(None);
+  };
+
+  if name == "help" || own.contains(&name) {
+    return Ok(None);
+  }
+
+  let Some(program) =
+    plugin::find(&name, &env::var_os("PATH").unwrap_or_default())
+  else {
+    return Ok(None);
+  };
+  let path = Settings::default_path();
+  let settings = Settings::load(path.as_deref(), Overrides::default())?;
+  let context = Context::new(&settings, path, args.collect())?;
+  let status = plugin::run(&program, &context)?;
+
+  // A plugin killed by a signal has no code of its own.
+  Ok(Some(status.code().unwrap_or(1)))
+}
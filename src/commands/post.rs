@@ -0,0 +1,108 @@
+//! Posting, as the account you've logged in as.
+
+use std::path::PathBuf;
+
+use apread::{
+  compose::{self, Draft, EmptyDraft, Media, Visibility},
+  timeline, ApreadErrors, Client,
+};
+use chrono::{DateTime, FixedOffset};
+
+use super::Globals;
+
+/// Options for posting.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  #[command(flatten)]
+  options: Options,
+}
+
+/// What to post, whether it's a post or a reply.
+#[derive(Debug, clap::Args)]
+pub struct Options {
+  /// The post's text; leave this out to write it in $VISUAL or $EDITOR
+  text: Option<String>,
+  /// Hide the text behind this content warning
+  #[arg(long, value_name = "WARNING")]
+  cw: Option<String>,
+  /// Who to show the post to
+  #[arg(long, value_enum, default_value_t)]
+  visibility: Visibility,
+  /// Attach this image, video or audio file; repeat for more than one
+  #[arg(long, value_name = "FILE")]
+  media: Vec<PathBuf>,
+  /// Alt text for the media, in the same order; repeat for each file
+  #[arg(long, value_name = "TEXT")]
+  alt: Vec<String>,
+  /// Schedule the post for this time, as 2023-06-01T09:00:00+01:00, for
+  /// the instance to post then
+  #[arg(long, value_name = "TIME", value_parser = timeline::parse_date)]
+  at: Option<DateTime<FixedOffset>>,
+}
+
+impl Options {
+  /// A draft of the post, with the media uploaded and attached.
+  pub async fn draft(self, client: &Client) -> Result<Draft, ApreadErrors> {
+    let text = match self.text {
+      Some(text) => text,
+      None => compose::edit("")?,
+    };
+
+    if text.is_empty() {
+      return Err(EmptyDraft.into());
+    }
+
+    let mut alts = self.alt.into_iter();
+    let mut media_ids = vec![];
+
+    for path in &self.media {
+      let media = Media::open(path, alts.next())?;
+
+      if media.description.is_none() {
+        eprintln!("warning: {} has no alt text", path.display());
+      }
+
+      media_ids.push(client.upload_media(&media).await?);
+    }
+
+    Ok(Draft {
+      status: text,
+      spoiler_text: self.cw,
+      visibility: self.visibility,
+      media_ids,
+      in_reply_to_id: None,
+      scheduled_at: self.at.map(|at| at.to_rfc3339()),
+    })
+  }
+}
+
+/// Posts a draft, or schedules it if it has a time, and says where it went.
+pub async fn send(
+  client: &Client,
+  draft: &Draft,
+  done: &str,
+) -> Result<(), ApreadErrors> {
+  if draft.scheduled_at.is_some() {
+    let scheduled = client.schedule_status(draft).await?;
+
+    println!(
+      "Scheduled for {}, as {}",
+      scheduled.scheduled_at, scheduled.id
+    );
+  } else {
+    let posted = client.post_status(draft).await?;
+
+    println!("{} {}", done, posted.url.unwrap_or(posted.uri));
+  }
+
+  Ok(())
+}
+
+/// Posts the text, and prints where the post can be read, or when it will
+/// be.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let client = globals.client()?;
+  let draft = args.options.draft(&client).await?;
+
+  send(&client, &draft, "Posted").await
+}
@@ -0,0 +1,32 @@
+//! Showing an account's profile, with its fields.
+
+use std::io;
+
+use apread::{profile::Card, render, settings::Format, ApreadErrors, Handle};
+
+use super::Globals;
+
+/// Options for showing a profile.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to show, as id@domain
+  handle: String,
+}
+
+/// Prints an account's name, bio and profile fields, checking the links
+/// among the fields for links back to the profile.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let handle = Handle::parse_string(&args.handle)?;
+  let client = globals.client()?;
+  let actor = client.resolve(&handle).await?;
+  let card = Card::fetch(&client, handle.to_string(), &actor).await;
+  let out = &mut io::stdout().lock();
+
+  match settings.format.require_text("Profiles")? {
+    Format::Json => render::profile_json(out, &card)?,
+    _ => render::profile(out, &card, settings.width)?,
+  }
+
+  Ok(())
+}
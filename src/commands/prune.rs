@@ -0,0 +1,53 @@
+//! Trimming the archive to a retention policy.
+
+use std::io;
+
+use apread::{
+  render,
+  retention::{self, Retention},
+  settings::Format,
+  ApreadErrors,
+};
+use chrono::Utc;
+
+use super::Globals;
+
+/// How much of the archive to keep, over the config file's `[retention]`.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// Prune posts published more than this many days ago
+  #[arg(long, value_name = "DAYS")]
+  max_age: Option<u64>,
+  /// Prune each account's oldest posts beyond this many
+  #[arg(long, value_name = "N")]
+  max_posts: Option<usize>,
+  /// Prune the oldest media, then the oldest posts, until the archive and
+  /// its media take up at most this many megabytes
+  #[arg(long, value_name = "MB")]
+  max_size: Option<u64>,
+}
+
+/// Prunes the archive and its media as the retention policy asks, and says
+/// what that reclaimed. Bookmarked posts are always kept.
+pub fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let retention = Retention {
+    max_age: args.max_age.or(settings.retention.max_age),
+    max_posts: args.max_posts.or(settings.retention.max_posts),
+    max_size: args.max_size.or(settings.retention.max_size),
+  };
+  let pruned = retention::prune(
+    &globals.archive()?,
+    &settings.media_dir()?,
+    &retention,
+    Utc::now(),
+  )?;
+  let out = &mut io::stdout().lock();
+
+  match settings.format.require_text("Prune reports")? {
+    Format::Json => render::pruned_json(out, &pruned)?,
+    _ => render::pruned(out, &pruned, &settings.locale())?,
+  }
+
+  Ok(())
+}
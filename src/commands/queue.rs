@@ -0,0 +1,121 @@
+//! The read-it-later queue, exported as plain text for text-to-speech.
+
+use std::{fs, path::PathBuf};
+
+use apread::{archive::QueuedItem, article::Article, render, ApreadErrors};
+
+use super::Globals;
+
+/// What to do with the queue.
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+  /// Queue a post, or any article on the web, to read later
+  Add {
+    /// The url of the post or article
+    url: String,
+  },
+  /// List what's queued, in order
+  List,
+  /// Take the first item off the queue and print it as plain text
+  Pop,
+  /// Print everything queued as plain text, leaving the queue as it is
+  Export {
+    /// Write to this file instead of printing
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+}
+
+/// Runs a queue command.
+pub async fn run(
+  command: Command,
+  globals: &Globals,
+) -> Result<(), ApreadErrors> {
+  let mut archive = globals.archive()?;
+
+  match command {
+    Command::Add { url } => {
+      let item = fetch(&url, globals).await?;
+      archive.enqueue(&item)?;
+      println!("Queued {}", describe(&item));
+    }
+    Command::List => {
+      for (index, item) in archive.queue()?.iter().enumerate() {
+        println!("{:>4}. {}", index + 1, describe(item));
+      }
+    }
+    Command::Pop => {
+      if let Some(item) = archive.pop()? {
+        println!("{}", plain_text(&item));
+      }
+    }
+    Command::Export { output } => {
+      let items: Vec<_> = archive.queue()?.iter().map(plain_text).collect();
+      let text = items.join("\n\n\n");
+
+      match output {
+        Some(path) => fs::write(path, text + "\n")?,
+        None => println!("{}", text),
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Fetches a url as a post if it is one, and as an article if not.
+async fn fetch(
+  url: &str,
+  globals: &Globals,
+) -> Result<QueuedItem, ApreadErrors> {
+  let client = globals.client()?;
+
+  if let Ok(post) = client.post(url).await {
+    let author = match &post.attributed_to {
+      Some(actor) => client.actor(actor).await?.handle(),
+      None => None,
+    };
+
+    return Ok(QueuedItem {
+      url: url.to_owned(),
+      title: None,
+      author,
+      content: post.content,
+    });
+  }
+
+  let article = Article::extract(&client.web_page(url).await?);
+
+  Ok(QueuedItem {
+    url: url.to_owned(),
+    title: article.title,
+    author: None,
+    content: article.content,
+  })
+}
+
+fn describe(item: &QueuedItem) -> String {
+  match (&item.title, &item.author) {
+    (Some(title), _) => format!("{} ({})", title, item.url),
+    (None, Some(author)) => format!("post by {} ({})", author, item.url),
+    (None, None) => item.url.clone(),
+  }
+}
+
+/// An item as text-to-speech tools like it: a spoken-style heading, then
+/// the content with all markup gone.
+fn plain_text(item: &QueuedItem) -> String {
+  let heading = match (&item.title, &item.author) {
+    (Some(title), Some(author)) => format!("{}. By {}.", title, author),
+    (Some(title), None) => format!("{}.", title),
+    (None, Some(author)) => format!("A post by {}.", author),
+    (None, None) => String::new(),
+  };
+  let body = render::plain_text(&item.content);
+
+  if heading.is_empty() {
+    body
+  } else {
+    format!("{}\n\n{}", heading, body)
+  }
+}
@@ -0,0 +1,567 @@
+//! Reading timelines, apread's default command.
+
+use std::{
+  fs,
+  io::{self, IsTerminal},
+  path::PathBuf,
+};
+
+use apread::{
+  breaker,
+  diagnostics::{Diagnostic, Diagnostics, Kind},
+  last_run::LastRun,
+  length::Length,
+  media::MediaCache,
+  player,
+  settings::{Format, Settings},
+  sink::{self, Layout, OutputSink, Terminal},
+  spam::{Action, Spam},
+  subscriptions::Subscriptions,
+  timeline::{self, Author, Period, Reading, Source, Start, Walk},
+  ApreadErrors, Handle, NoSuchPost, NothingToPlay, NothingToRead, WrongFormat,
+};
+use chrono::{DateTime, FixedOffset, Utc};
+
+use super::Globals;
+
+/// What to read, and how.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to read, as id@domain or its actor's url (or as a Bluesky
+  /// handle or Nostr npub, in builds with those features), a hashtag as
+  /// #tag@instance, or a view named in the config file; use - to read a
+  /// list of handles from stdin, or leave this out to read every
+  /// subscription together
+  handle: Option<String>,
+  /// Only read subscriptions with this nickname or handle; repeat for more
+  /// than one
+  #[arg(long, value_name = "NAME", conflicts_with = "handle")]
+  only: Vec<String>,
+  /// Read subscriptions from this file instead of the default one
+  #[arg(long, value_name = "FILE")]
+  subscriptions: Option<PathBuf>,
+  #[command(flatten)]
+  options: Options,
+  /// What to read, when another command knows already.
+  #[arg(skip)]
+  source: Option<Source>,
+}
+
+/// How to read and show a timeline, whatever's being read.
+#[derive(Debug, clap::Args)]
+pub struct Options {
+  /// Cluster posts by account, rather than interleaving them by date
+  #[arg(long)]
+  group_by_author: bool,
+  /// Write a digest for catching up: posts under a heading for each day or
+  /// week, clustered by account, after a line of totals
+  #[arg(
+    long,
+    value_enum,
+    value_name = "PERIOD",
+    conflicts_with_all = ["group_by_author", "columns"],
+  )]
+  digest: Option<Period>,
+  /// Lay posts out in this many newspaper-style columns, when the terminal
+  /// is wide enough for them
+  #[arg(long, value_name = "N")]
+  columns: Option<usize>,
+  /// Start from each account's newest posts, or from its very first ones
+  /// and read forward in the order they were written [default: newest]
+  #[arg(long, value_enum, value_name = "END")]
+  from: Option<Start>,
+  /// How many pages of each account's outbox to read [default: 1, or as
+  /// many as --limit needs]
+  #[arg(long, value_name = "N")]
+  pages: Option<usize>,
+  /// Show at most this many posts all told, reading only as many pages as
+  /// it takes to find them (up to 20 of each account's, unless --pages says)
+  #[arg(long, value_name = "N")]
+  limit: Option<usize>,
+  /// Dig back to the posts written around this date, as 2023-06-01 or a
+  /// full RFC 3339 time
+  #[arg(
+    long,
+    value_name = "DATE",
+    value_parser = timeline::parse_date,
+    conflicts_with_all = ["from", "pages"],
+  )]
+  around: Option<DateTime<FixedOffset>>,
+  /// Write each post through this minijinja template, in place of the
+  /// usual format
+  #[arg(long, value_name = "FILE", conflicts_with = "columns")]
+  template: Option<PathBuf>,
+  /// Warn, on stderr, about every image or other attachment without alt
+  /// text
+  #[arg(long)]
+  warn_missing_alt: bool,
+  /// Open the Nth post shown, counting from 1, in the browser
+  #[arg(long, value_name = "N")]
+  open: Option<usize>,
+  /// Play the first video or sound of the Nth post shown, counting from 1,
+  /// with the configured player
+  #[arg(long, value_name = "N", conflicts_with = "open")]
+  play: Option<usize>,
+  /// Only show posts of at least this many words, for reading long-form
+  /// writing
+  #[arg(long, value_name = "WORDS")]
+  min_length: Option<usize>,
+  /// Only show posts of at most this many words, for reading microblogging
+  #[arg(long, value_name = "WORDS")]
+  max_length: Option<usize>,
+  /// Only show posts about this topic, a keyword or hashtag, as in release
+  #[cfg(feature = "topics")]
+  #[arg(long, value_name = "TOPIC")]
+  topic: Option<String>,
+  /// Show attachments marked sensitive, rather than a blur of them
+  #[arg(long)]
+  reveal_sensitive: bool,
+  /// Show the posts the config file's [spam] heuristics would hide or move
+  /// down, where they are
+  #[arg(long)]
+  show_filtered: bool,
+  /// Write posts to this file instead of printing them
+  #[arg(long, short, value_name = "FILE")]
+  output: Option<PathBuf>,
+}
+
+impl Args {
+  /// Reading from just the one source.
+  pub fn source(source: Source, options: Options) -> Self {
+    Self {
+      handle: None,
+      only: vec![],
+      subscriptions: None,
+      options,
+      source: Some(source),
+    }
+  }
+}
+
+/// Prints the newest (or oldest) posts of an account, of every
+/// subscription, or of a view.
+pub async fn run(
+  mut args: Args,
+  globals: &Globals,
+) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let view = args
+    .handle
+    .as_deref()
+    .and_then(|name| settings.views.get(name))
+    .cloned();
+  let mut view_name = None;
+
+  // A view stands in for the flags it has settings for, where they weren't
+  // given.
+  if let Some(view) = &view {
+    view_name = args.handle.take();
+    args.options.from = args.options.from.or(view.from);
+    args.options.group_by_author |= view.group_by_author;
+  }
+
+  let path = args
+    .subscriptions
+    .clone()
+    .or_else(|| settings.subscriptions_path());
+  let subscriptions = match path {
+    Some(path) => Subscriptions::load(&path)?,
+    None => Subscriptions::default(),
+  };
+
+  let client = globals.client()?;
+  let authors = match (args.source.take(), args.handle.as_deref()) {
+    (Some(source), _) => vec![author(&subscriptions, source)?],
+    (None, Some("-")) => Handle::parse_list(&io::read_to_string(io::stdin())?)?
+      .into_iter()
+      .map(|handle| author(&subscriptions, Source::Account(handle)))
+      .collect::<Result<_, _>>()?,
+    (None, Some(url))
+      if url.starts_with("https://") || url.starts_with("http://") =>
+    {
+      let handle = client.identify(url).await?;
+      vec![author(&subscriptions, Source::Account(handle))?]
+    }
+    (None, Some(handle)) => {
+      vec![author(&subscriptions, Source::parse(handle)?)?]
+    }
+    (None, None) => match &view {
+      Some(view) => {
+        let accounts = view.accounts(&subscriptions)?;
+
+        for name in accounts.unknown {
+          client.diagnostics().push(Diagnostic::warning(
+            Kind::Skipped,
+            Some(name.clone()),
+            format!(
+              "the view {} names {}, which is neither a subscription nor a \
+               handle, so it was left out",
+              view_name.as_deref().unwrap_or_default(),
+              name
+            ),
+          ));
+        }
+
+        accounts.authors
+      }
+      None => subscriptions
+        .only(&args.only)
+        .into_iter()
+        .map(Author::from_subscription)
+        .collect::<Result<_, _>>()?,
+    },
+  };
+
+  if authors.is_empty() {
+    return Err(NothingToRead.into());
+  }
+
+  let template = match &args.options.template {
+    Some(path) => Some(fs::read_to_string(path)?),
+    None => None,
+  };
+  // The limit is on the posts shown, after the merge and every filter. A
+  // walk can only stop early at it when nothing's filtered out after; with
+  // filters it reads on as far as the pages go.
+  let filtering = view.as_ref().is_some_and(|view| !view.tags.is_empty())
+    || args.options.min_length.is_some()
+    || args.options.max_length.is_some()
+    || (settings.spam.is_some() && !args.options.show_filtered);
+  #[cfg(feature = "topics")]
+  let filtering = filtering || args.options.topic.is_some();
+  #[cfg(feature = "wasm")]
+  let mut wasm_filters = apread::wasm::Filters::load(&settings.wasm_filters)?;
+  #[cfg(feature = "wasm")]
+  let filtering = filtering || !wasm_filters.is_empty();
+  let walk = Walk {
+    start: args.options.from.unwrap_or_default(),
+    pages: match (args.options.pages, args.options.limit) {
+      (Some(pages), _) => pages,
+      (None, Some(_)) => timeline::LIMIT_PAGES,
+      (None, None) => 1,
+    },
+    limit: args.options.limit.filter(|_| !filtering),
+  };
+  let reading = match args.options.around {
+    Some(date) => Reading::Around(date),
+    None => Reading::Walk(walk),
+  };
+  let mut gathered = timeline::gather(&client, &authors, reading).await;
+
+  // An account that can't be read only costs its own posts, unless no
+  // account could be read at all.
+  if gathered.failed.len() == authors.len() {
+    return Err(gathered.failed.remove(0).1);
+  }
+
+  let mut entries = gathered.entries;
+
+  // Modules go first, so views can pick posts by the tags they add.
+  #[cfg(feature = "wasm")]
+  wasm_filters.apply(&mut entries, client.diagnostics());
+
+  if let Some(view) = &view {
+    entries.retain(|entry| view.shows(entry));
+  }
+
+  if args.options.min_length.is_some() || args.options.max_length.is_some() {
+    entries.retain(|entry| {
+      Length::of(&entry.post)
+        .within(args.options.min_length, args.options.max_length)
+    });
+  }
+
+  #[cfg(feature = "topics")]
+  if let Some(topic) = &args.options.topic {
+    // Without an archive, every post's keywords are found afresh.
+    let archive = globals.archive().ok();
+
+    entries.retain(|entry| {
+      apread::topics::matches(
+        &apread::topics::of(archive.as_ref(), &entry.post),
+        topic,
+      )
+    });
+  }
+
+  match args.options.from.unwrap_or_default() {
+    Start::Newest => timeline::merge(&mut entries),
+    Start::Oldest => timeline::chronological(&mut entries),
+  }
+
+  let filtered = match &settings.spam {
+    Some(spam) if !args.options.show_filtered => spam.apply(&mut entries),
+    _ => 0,
+  };
+
+  if let Some(limit) = args.options.limit {
+    entries.truncate(limit);
+  }
+
+  // Only the posts being shown are worth downloading the attachments of.
+  if let Some(dir) = settings.media_cache().filter(|_| settings.download_media)
+  {
+    let cache = MediaCache::new(dir);
+
+    cache.save(&client, &mut entries).await;
+
+    if settings.media_cache_size > 0 {
+      let max = settings.media_cache_size.saturating_mul(1_000_000);
+
+      if let Err(error) = cache.trim(max) {
+        client.diagnostics().push(Diagnostic::warning(
+          Kind::Cache,
+          None,
+          format!("couldn't trim the media cache: {}", error),
+        ));
+      }
+    }
+  }
+
+  let layout = Layout {
+    group_by_author: args.options.group_by_author,
+    digest: args.options.digest,
+    columns: args.options.columns,
+  };
+  let opening = match args.options.open {
+    Some(number) => Some(url_of(&entries, layout, number)?),
+    None => None,
+  };
+  let playing = match args.options.play {
+    Some(number) => Some(playable(&entries, layout, number)?),
+    None => None,
+  };
+
+  let format = globals
+    .format()
+    .or(view.and_then(|view| view.format))
+    .unwrap_or(settings.format);
+  let sink: Box<dyn OutputSink> = match (template, format) {
+    (Some(template), _) => Box::new(sink::Template::new(template)),
+    (None, Format::Json) => Box::new(sink::Json),
+    (None, Format::Csv) => Box::new(sink::Csv),
+    (None, Format::Html) => Box::new(sink::Html),
+    (None, Format::Rss) => Box::new(sink::Rss),
+    (None, Format::PodcastRss) => Box::new(sink::Podcast),
+    (None, Format::Ics) => Box::new(sink::Ics { now: Utc::now() }),
+    (None, Format::Terminal) => Box::new(Terminal {
+      width: settings.width,
+      colored: args.options.output.is_none()
+        && io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none(),
+      locale: settings.locale(),
+      messages: settings.messages(),
+      layout,
+      screen_reader: settings.screen_reader,
+      math: settings.math,
+      links: settings.links,
+      reveal: args.options.reveal_sensitive,
+      total: gathered.total,
+    }),
+    (None, format @ (Format::Dot | Format::Gexf)) => {
+      return Err(
+        WrongFormat {
+          what: "Timelines",
+          format,
+        }
+        .into(),
+      )
+    }
+  };
+  // Only the terminal numbers posts for picking out later.
+  let numbered = args.options.template.is_none() && format == Format::Terminal;
+  let mut sink = match &args.options.output {
+    Some(path) => Box::new(sink::File::new(path, sink)),
+    None => sink,
+  };
+
+  sink.write(&mut io::stdout().lock(), &entries)?;
+
+  let diagnostics = client.diagnostics();
+
+  if numbered {
+    remember(&layout.order(&entries), &settings, diagnostics);
+  }
+
+  if args.options.warn_missing_alt {
+    warn_missing_alt(&entries, diagnostics);
+  }
+
+  warn_failed(&gathered.failed, authors.len(), diagnostics);
+
+  if let Some(spam) = settings.spam.as_ref().filter(|_| filtered > 0) {
+    note_filtered(spam, filtered, diagnostics);
+  }
+
+  globals.report(format)?;
+
+  if let Some(url) = opening {
+    open::that(url)?;
+  }
+
+  if let Some(url) = playing {
+    player::play(&settings.player, &url)?;
+  }
+
+  Ok(())
+}
+
+/// Keeps the posts just shown, so later runs can pick them out by number.
+/// Reading a timeline shouldn't fail over this, so it only warns.
+fn remember(
+  entries: &[timeline::Entry],
+  settings: &Settings,
+  diagnostics: &Diagnostics,
+) {
+  let saved = match settings.last_run() {
+    Some(path) => LastRun::new(entries).save(&path),
+    None => return,
+  };
+
+  if let Err(error) = saved {
+    diagnostics.push(Diagnostic::warning(
+      Kind::Cache,
+      None,
+      format!("couldn't remember the posts shown: {}", error),
+    ));
+  }
+}
+
+/// Names every account that couldn't be read and why, and how many there
+/// were all told.
+fn warn_failed(
+  failed: &[(Author, ApreadErrors)],
+  of: usize,
+  diagnostics: &Diagnostics,
+) {
+  // Accounts skipped because their instance is down are counted by
+  // instance, rather than each repeating the same reason.
+  for (author, error) in failed {
+    if !matches!(error, ApreadErrors::InstanceDown(_)) {
+      diagnostics.push(Diagnostic::warning(
+        Kind::Unreadable,
+        Some(author.source.to_string()),
+        format!("couldn't read {}: {}", author.label(), error),
+      ));
+    }
+  }
+
+  let skipped = breaker::skipped(failed.iter().map(|(_, error)| error));
+  let message = match skipped.len() {
+    0 => None,
+    1 => Some(format!("skipped 1 unreachable instance: {}", skipped[0])),
+    n => Some(format!(
+      "skipped {} unreachable instances: {}",
+      n,
+      skipped.join(", ")
+    )),
+  };
+
+  if let Some(message) = message {
+    diagnostics.push(Diagnostic::warning(Kind::Unreachable, None, message));
+  }
+
+  if !failed.is_empty() {
+    diagnostics.push(Diagnostic::warning(
+      Kind::Unreadable,
+      None,
+      format!("{} of {} accounts couldn't be read", failed.len(), of),
+    ));
+  }
+}
+
+/// Says how many posts the spam heuristics filtered, and how to see them.
+fn note_filtered(spam: &Spam, filtered: usize, diagnostics: &Diagnostics) {
+  let posts = match filtered {
+    1 => "1 post".to_owned(),
+    n => format!("{} posts", n),
+  };
+  let done = match spam.action {
+    Action::Hide => "hidden",
+    Action::Downrank => "moved to the end",
+  };
+
+  diagnostics.push(Diagnostic::note(
+    Kind::Filtered,
+    None,
+    format!(
+      "{} {} as low quality; --show-filtered shows them in place",
+      posts, done
+    ),
+  ));
+}
+
+/// Names every post with an attachment lacking alt text, and how many there
+/// were all told.
+fn warn_missing_alt(entries: &[timeline::Entry], diagnostics: &Diagnostics) {
+  let mut missing = 0;
+
+  for entry in entries {
+    for attachment in &entry.post.attachment {
+      if attachment.alt_text().is_none() {
+        missing += 1;
+
+        let post = entry.post.url.as_deref().or(entry.post.id.as_deref());
+        diagnostics.push(Diagnostic::warning(
+          Kind::MissingAlt,
+          post.map(str::to_owned),
+          format!(
+            "{} attachment with no alt text, on {}'s post {}",
+            attachment.kind().to_lowercase(),
+            entry.author.label(),
+            post.unwrap_or("without a url")
+          ),
+        ));
+      }
+    }
+  }
+
+  if missing > 0 {
+    diagnostics.push(Diagnostic::warning(
+      Kind::MissingAlt,
+      None,
+      format!("{} attachments with no alt text", missing),
+    ));
+  }
+}
+
+/// The url of the Nth post, counting from 1 in the order they're shown.
+fn url_of(
+  entries: &[timeline::Entry],
+  layout: Layout,
+  number: usize,
+) -> Result<String, NoSuchPost> {
+  number
+    .checked_sub(1)
+    .and_then(|index| layout.order(entries).get(index)?.post.url.clone())
+    .ok_or(NoSuchPost(number))
+}
+
+/// The first video or sound of the Nth post in the order they're shown.
+fn playable(
+  entries: &[timeline::Entry],
+  layout: Layout,
+  number: usize,
+) -> Result<String, ApreadErrors> {
+  let shown = layout.order(entries);
+  let entry = number
+    .checked_sub(1)
+    .and_then(|index| shown.get(index))
+    .ok_or(NoSuchPost(number))?;
+
+  match entry.post.playable() {
+    Some(url) => Ok(url.to_owned()),
+    None => Err(NothingToPlay(number).into()),
+  }
+}
+
+/// The author for a handle, shown as its subscription asks if it has one.
+fn author(
+  subscriptions: &Subscriptions,
+  source: Source,
+) -> Result<Author, ApreadErrors> {
+  match subscriptions.find(&source.to_string()) {
+    Some(subscription) => Ok(Author::from_subscription(subscription)?),
+    None => Ok(Author::from_source(source)),
+  }
+}
@@ -0,0 +1,31 @@
+//! Replying to posts, as the account you've logged in as.
+
+use apread::{compose, ApreadErrors};
+
+use super::{post, Globals};
+
+/// Options for replying.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The url of the post to reply to, or the number the last run showed it
+  /// with, as in 3 for [3]
+  url: String,
+  #[command(flatten)]
+  options: post::Options,
+}
+
+/// Replies to a post, mentioning its author, and prints where the reply can
+/// be read.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let url = globals.post_url(args.url)?;
+  let client = globals.client()?;
+  let own = client.login().map_or("", |login| &login.account);
+  let replying_to = client.find_status(&url).await?;
+  let mut draft = args.options.draft(&client).await?;
+
+  draft.status =
+    compose::with_mention(&draft.status, &replying_to.account.acct, own);
+  draft.in_reply_to_id = Some(replying_to.id);
+
+  post::send(&client, &draft, "Replied").await
+}
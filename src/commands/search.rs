@@ -0,0 +1,67 @@
+//! Searching for posts on the instance you've logged in to.
+
+use apread::{
+  search::{self, NoSuchSearch, Search},
+  timeline::Source,
+  ApreadErrors,
+};
+use chrono::Utc;
+
+use super::{read, Globals};
+
+/// Options for searching.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// What to search for
+  #[arg(required_unless_present = "saved", conflicts_with = "saved")]
+  query: Option<String>,
+  /// Run the search with this name in the config file, showing only the
+  /// posts published since it last ran
+  #[arg(long, value_name = "NAME")]
+  saved: Option<String>,
+  /// Show everything a saved search finds, not only what's new
+  #[arg(long, requires = "saved")]
+  all: bool,
+  #[command(flatten)]
+  options: read::Options,
+}
+
+/// Prints the posts a search finds, as a read of an account would.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let Some(name) = args.saved else {
+    let search = Search {
+      query: args.query.unwrap_or_default(),
+      since: None,
+    };
+
+    return read::run(
+      read::Args::source(Source::Search(search), args.options),
+      globals,
+    )
+    .await;
+  };
+
+  let settings = globals.settings()?;
+  let saved = settings
+    .searches
+    .get(&name)
+    .ok_or_else(|| NoSuchSearch(name.clone()))?;
+  let path = settings.searches_path()?;
+  let search = Search {
+    query: saved.text(),
+    since: match args.all {
+      true => None,
+      false => search::last_run(&path, &name)?,
+    },
+  };
+  // Anything published while the search runs is new next time.
+  let started = Utc::now();
+
+  read::run(
+    read::Args::source(Source::Search(search), args.options),
+    globals,
+  )
+  .await?;
+
+  search::ran(&path, &name, started)
+}
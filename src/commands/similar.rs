@@ -0,0 +1,107 @@
+//! Finding accounts with interests like an account's.
+
+use std::io;
+
+use apread::{
+  mastodon, render,
+  settings::Format,
+  similar,
+  timeline::{self, Author, Walk},
+  ApreadErrors, Handle, WrongFormat,
+};
+
+use super::Globals;
+
+/// Options for finding similar accounts.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to compare against, as id@domain
+  handle: String,
+  /// Compare against the accounts in this instance's profile directory
+  /// too, rather than the account's own instance's; give it more than once
+  /// for several
+  #[arg(long, value_name = "DOMAIN")]
+  instance: Vec<String>,
+  /// How many pages of the account's outbox to read, when it isn't
+  /// archived
+  #[arg(long, default_value_t = 1, value_name = "N")]
+  pages: usize,
+  /// How many accounts to list
+  #[arg(long, default_value_t = 10, value_name = "N")]
+  limit: usize,
+}
+
+/// Lists the archived and directory accounts whose hashtags overlap an
+/// account's most.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let client = globals.client()?;
+  let handle = Handle::parse_string(&args.handle)?;
+  let name = handle.to_string().to_lowercase();
+  let archived = similar::archived(&globals.archive()?.hashtags_by_author()?);
+  let (own, mut candidates): (Vec<_>, Vec<_>) =
+    archived.into_iter().partition(|(author, _)| {
+      author.trim_start_matches('@').to_lowercase() == name
+    });
+  let interests = match own.into_iter().next() {
+    Some((_, interests)) => interests,
+    None => {
+      let walk = Walk {
+        pages: args.pages,
+        ..Walk::default()
+      };
+      let entries =
+        timeline::walk(&client, &Author::new(handle.clone()), walk).await?;
+
+      similar::interests(entries.iter().flat_map(|entry| entry.post.hashtags()))
+    }
+  };
+  let instances = match args.instance.is_empty() {
+    true => vec![handle.domain.clone()],
+    false => args.instance,
+  };
+
+  for instance in &instances {
+    match client.directory(instance).await {
+      Ok(accounts) => {
+        candidates.extend(accounts.into_iter().filter_map(|account| {
+          let found = mastodon::account_handle(instance, &account.acct)?;
+          let tags = similar::bio_hashtags(&account.note);
+
+          Some((
+            found.to_string(),
+            similar::interests(tags.iter().map(String::as_str)),
+          ))
+        }))
+      }
+      Err(error) => {
+        eprintln!("warning: couldn't read {}'s directory: {}", instance, error)
+      }
+    }
+  }
+
+  candidates.retain(|(candidate, _)| {
+    candidate.trim_start_matches('@').to_lowercase() != name
+  });
+
+  let mut found = similar::rank(&interests, candidates);
+  let out = &mut io::stdout().lock();
+
+  found.truncate(args.limit);
+
+  match settings.format {
+    Format::Json => render::similar_json(out, &found)?,
+    Format::Terminal => render::similar(out, &found)?,
+    format => {
+      return Err(
+        WrongFormat {
+          what: "Similar accounts",
+          format,
+        }
+        .into(),
+      )
+    }
+  }
+
+  Ok(())
+}
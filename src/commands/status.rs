@@ -0,0 +1,35 @@
+//! Reporting on a running watch, and what it has queued and cached.
+
+use std::io;
+
+use apread::{
+  render,
+  settings::Format,
+  status::{self, Report, Status},
+  ApreadErrors,
+};
+use chrono::Utc;
+
+use super::Globals;
+
+/// Reports how the running `apread watch` is doing, as text or, with
+/// `--format json`, as JSON.
+pub fn run(globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let watch = match settings.watch_status() {
+    Some(path) => Status::load(&path)?,
+    None => None,
+  };
+  let queue_depth = globals.archive()?.queue()?.len();
+  let cache_bytes =
+    settings.cache_dir().map_or(0, |dir| status::dir_size(&dir));
+  let report = Report::new(watch, queue_depth, cache_bytes, Utc::now());
+  let out = &mut io::stdout().lock();
+
+  match settings.format.require_text("Statuses")? {
+    Format::Json => render::status_json(out, &report)?,
+    _ => render::status(out, &report, &settings.locale())?,
+  }
+
+  Ok(())
+}
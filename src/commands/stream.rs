@@ -0,0 +1,112 @@
+//! Printing posts live, as the logged in instance streams them.
+
+use std::io::IsTerminal;
+
+use apread::{
+  login::NoSuchList,
+  mastodon,
+  streaming::{self, Event, Stream},
+  ApreadErrors,
+};
+
+use super::{watch, Globals};
+
+/// Which timeline to stream, when it isn't a hashtag or list.
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum Timeline {
+  /// Your home timeline
+  #[default]
+  Home,
+  /// Every public post your instance knows of
+  Public,
+  /// Public posts by your instance's own accounts
+  Local,
+}
+
+/// Options for streaming a timeline.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The timeline to stream
+  #[arg(default_value = "home", conflicts_with_all = ["tag", "list"])]
+  timeline: Timeline,
+  /// Stream public posts with this hashtag instead
+  #[arg(long, value_name = "TAG", conflicts_with = "list")]
+  tag: Option<String>,
+  /// Stream one of your lists instead, by name
+  #[arg(long, value_name = "NAME")]
+  list: Option<String>,
+}
+
+/// Prints posts as they arrive, oldest first as a log would have them,
+/// connecting again whenever the connection drops, until Ctrl-C.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let client = globals.client()?;
+  let interrupt = &globals.interrupt;
+  let stream = match (args.tag, args.list) {
+    (Some(tag), _) => Stream::Hashtag(tag.trim_start_matches('#').to_owned()),
+    (_, Some(name)) => {
+      let lists = client.lists().await?;
+      let list = mastodon::find_list(&lists, &name).ok_or(NoSuchList(name))?;
+
+      Stream::List(list.id.clone())
+    }
+    _ => match args.timeline {
+      Timeline::Home => Stream::Home,
+      Timeline::Public => Stream::Public,
+      Timeline::Local => Stream::Local,
+    },
+  };
+  let mut failures = 0;
+
+  while !interrupt.is_triggered() {
+    match client.open_stream(&stream).await {
+      Ok(mut events) => {
+        failures = 0;
+
+        if std::io::stderr().is_terminal() {
+          eprintln!("Streaming; Ctrl-C to stop");
+        }
+
+        loop {
+          let event = tokio::select! {
+            event = events.next() => event,
+            _ = interrupt.triggered() => break,
+          };
+
+          match event {
+            Ok(Some(Event::Update(entry) | Event::Edit(entry))) => {
+              watch::show(&[*entry], &settings)?;
+            }
+            Ok(Some(Event::Delete(_))) => {}
+            Ok(None) => {
+              eprintln!("warning: the stream closed");
+              break;
+            }
+            Err(error) => {
+              eprintln!("warning: the stream broke off: {}", error);
+              break;
+            }
+          }
+        }
+      }
+      // Without a login, trying again won't help.
+      Err(error @ ApreadErrors::NotLoggedIn(_)) => return Err(error),
+      Err(error) => {
+        eprintln!("warning: couldn't connect: {}", error);
+        failures += 1;
+      }
+    }
+
+    if interrupt.is_triggered() {
+      break;
+    }
+
+    let wait = streaming::backoff(failures);
+
+    eprintln!("Connecting again in {}s", wait.as_secs());
+    interrupt.sleep(wait).await;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,65 @@
+//! Suggesting accounts to follow, from the archive.
+
+use std::{io, path::PathBuf};
+
+use apread::{
+  render, settings::Format, subscriptions::Subscriptions, suggest,
+  ApreadErrors, WrongFormat,
+};
+
+use super::Globals;
+
+/// Options for suggesting accounts to follow.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// How many accounts to suggest
+  #[arg(long, default_value_t = 20, value_name = "N")]
+  limit: usize,
+  /// Leave out the accounts in this subscriptions file, rather than the
+  /// configured one
+  #[arg(long, value_name = "FILE")]
+  subscriptions: Option<PathBuf>,
+}
+
+/// Lists the accounts the archived accounts mention and boost most, which
+/// aren't already followed.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let subscriptions =
+    match args.subscriptions.or_else(|| settings.subscriptions_path()) {
+      Some(path) => Subscriptions::load(&path)?,
+      None => Subscriptions::default(),
+    };
+  let client = globals.client()?;
+  let mut following: Vec<_> = subscriptions
+    .accounts
+    .iter()
+    .filter_map(|subscription| subscription.handle.clone())
+    .collect();
+
+  following.extend(client.login().map(|login| login.name()));
+
+  let mut suggestions =
+    suggest::suggest(&client, &globals.archive()?, &following).await?;
+  let out = &mut io::stdout().lock();
+
+  suggestions.truncate(args.limit);
+
+  match settings.format {
+    Format::Json => render::suggestions_json(out, &suggestions)?,
+    Format::Terminal => {
+      render::suggestions(out, &suggestions, &settings.locale())?
+    }
+    format => {
+      return Err(
+        WrongFormat {
+          what: "Suggestions",
+          format,
+        }
+        .into(),
+      )
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,23 @@
+//! Reading a hashtag's timeline on an instance.
+
+use apread::{hashtag::Hashtag, timeline::Source, ApreadErrors};
+
+use super::{read, Globals};
+
+/// Options for reading a hashtag.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The hashtag and the instance to read it from, as #tag@instance; the #
+  /// can be left out, saving quoting it from the shell
+  tag: String,
+  #[command(flatten)]
+  options: read::Options,
+}
+
+/// Prints the newest posts an instance has with a hashtag, as a read of an
+/// account would.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let source = Source::Tag(Hashtag::parse(&args.tag)?);
+
+  read::run(read::Args::source(source, args.options), globals).await
+}
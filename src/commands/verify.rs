@@ -0,0 +1,32 @@
+//! Checking the links on an account's profile for links back to it.
+
+use std::io;
+
+use apread::{profile, render, settings::Format, ApreadErrors, Handle};
+
+use super::Globals;
+
+/// Options for verifying a profile's links.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to check, as id@domain
+  handle: String,
+}
+
+/// Fetches each link among an account's profile fields, and says whether
+/// it links back to the account with `rel="me"`.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let handle = Handle::parse_string(&args.handle)?;
+  let client = globals.client()?;
+  let actor = client.resolve(&handle).await?;
+  let checks = profile::link_checks(&client, &actor).await;
+  let out = &mut io::stdout().lock();
+
+  match settings.format.require_text("Link checks")? {
+    Format::Json => render::link_checks_json(out, &checks)?,
+    _ => render::link_checks(out, &checks)?,
+  }
+
+  Ok(())
+}
@@ -0,0 +1,276 @@
+//! Watching timelines for new posts, and running hooks on them.
+
+use std::{
+  fs,
+  io::{self, IsTerminal, Write},
+  net::SocketAddr,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use apread::{
+  alerts::{Rates, Spike},
+  breaker, hooks,
+  metrics::{self, Metrics},
+  render,
+  settings::{Format, Settings},
+  status::Status,
+  subscriptions::Subscriptions,
+  systemd,
+  timeline::{Author, Entry, Source},
+  watch::{self, Watch},
+  ApreadErrors, Client, NothingToRead,
+};
+use chrono::Utc;
+
+use super::Globals;
+
+/// Options for watching timelines.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+  /// The account to watch, as id@domain; leave this out to watch every
+  /// subscription
+  handle: Option<String>,
+  /// Only watch subscriptions with this nickname or handle; repeat for more
+  /// than one
+  #[arg(long, value_name = "NAME", conflicts_with = "handle")]
+  only: Vec<String>,
+  /// Read subscriptions from this file instead of the default one
+  #[arg(long, value_name = "FILE")]
+  subscriptions: Option<PathBuf>,
+  /// Seconds to wait between looking for new posts
+  #[arg(long, value_name = "SECS", default_value_t = watch::DEFAULT_EVERY.as_secs())]
+  every: u64,
+  /// Serve Prometheus metrics, and /healthz and /readyz probes, at this
+  /// address, as 0.0.0.0:9185 in a container
+  #[arg(long, value_name = "ADDR")]
+  metrics: Option<SocketAddr>,
+  /// Tell systemd when watching is ready, and serve metrics on the socket
+  /// it passes, if it passes one
+  #[arg(long)]
+  systemd: bool,
+}
+
+/// Prints the newest posts, then keeps looking for new ones until Ctrl-C,
+/// printing each new post and handing it to the `on_new_post` hook, every
+/// webhook, and the Matrix room if there is one, and alerting on accounts
+/// posting more than the `posting_alert` allows.
+pub async fn run(args: Args, globals: &Globals) -> Result<(), ApreadErrors> {
+  let settings = globals.settings()?;
+  let subscriptions =
+    match args.subscriptions.or_else(|| settings.subscriptions_path()) {
+      Some(path) => Subscriptions::load(&path)?,
+      None => Subscriptions::default(),
+    };
+
+  let authors: Vec<Author> = match args.handle.as_deref() {
+    Some(handle) => {
+      let source = Source::parse(handle)?;
+
+      vec![match subscriptions.find(&source.to_string()) {
+        Some(subscription) => Author::from_subscription(subscription)?,
+        None => Author::from_source(source),
+      }]
+    }
+    None => subscriptions
+      .only(&args.only)
+      .into_iter()
+      .map(Author::from_subscription)
+      .collect::<Result<_, _>>()?,
+  };
+
+  if authors.is_empty() {
+    return Err(NothingToRead.into());
+  }
+
+  let client = globals.client()?;
+  let mut status = Status::new(&authors, args.every, Utc::now());
+  let mut watch = Watch::new(authors);
+  let mut first = true;
+
+  settings.format.require_text("Watched timelines")?;
+
+  let activated = args.systemd.then(systemd::listener).flatten();
+  let metrics = match (activated, args.metrics) {
+    (Some(listener), _) => {
+      let metrics = Metrics::new(globals.stats.clone());
+
+      metrics::serve_from(listener, metrics.clone())?;
+      Some(metrics)
+    }
+    (None, Some(address)) => {
+      let metrics = Metrics::new(globals.stats.clone());
+
+      metrics::serve(address, metrics.clone()).await?;
+      Some(metrics)
+    }
+    (None, None) => None,
+  };
+
+  let status_path = settings.watch_status();
+  let mut rates = settings.posting_alert.as_ref().map(Rates::new);
+
+  save_status(&status, status_path.as_deref());
+
+  while !client.interrupted() {
+    let mut poll = watch.poll(&client).await;
+
+    if let Some(metrics) = &metrics {
+      metrics.poll(&poll);
+    }
+
+    status.polled(&poll, Utc::now());
+    save_status(&status, status_path.as_deref());
+
+    if args.systemd {
+      let state = format!(
+        "READY=1\nSTATUS=Watching {} accounts, {} failing",
+        status.accounts.len(),
+        poll.failed.len()
+      );
+
+      notify(&state);
+    }
+
+    for (author, error) in &poll.failed {
+      if !matches!(error, ApreadErrors::InstanceDown(_)) {
+        eprintln!("warning: couldn't fetch {}: {}", author.label(), error);
+      }
+    }
+
+    let skipped = breaker::skipped(poll.failed.iter().map(|(_, error)| error));
+
+    if !skipped.is_empty() {
+      eprintln!("warning: skipped unreachable {}", skipped.join(", "));
+    }
+
+    let spikes = match &mut rates {
+      Some(rates) => rates.observe(&poll.new, Utc::now()),
+      None => vec![],
+    };
+
+    // Bursts from before watching started aren't news.
+    if !first {
+      for spike in &spikes {
+        alert(&client, &settings, spike).await;
+      }
+    }
+
+    // Posts come out oldest first, as they would when tailing a log.
+    poll.new.reverse();
+    show(&poll.new, &settings)?;
+
+    if !first {
+      for entry in &poll.new {
+        if let Some(command) = &settings.on_new_post {
+          if let Err(error) = hooks::run(command, entry) {
+            eprintln!("warning: {}", error);
+          }
+        }
+
+        for webhook in &settings.webhooks {
+          if let Err(error) = hooks::deliver(&client, webhook, entry).await {
+            eprintln!("warning: {}", error);
+          }
+        }
+
+        #[cfg(feature = "matrix")]
+        if let Some(room) = &settings.matrix {
+          let message = apread::matrix::Message::new(entry);
+          let transaction = apread::matrix::transaction(room, entry);
+
+          if let Err(error) =
+            client.send_matrix(room, &transaction, &message).await
+          {
+            eprintln!("warning: couldn't relay to {}: {}", room.room, error);
+          }
+        }
+      }
+    }
+
+    first = false;
+    globals
+      .interrupt
+      .sleep(Duration::from_secs(args.every))
+      .await;
+  }
+
+  if let Some(path) = &status_path {
+    let _ = fs::remove_file(path);
+  }
+
+  if args.systemd {
+    notify("STOPPING=1");
+  }
+
+  Ok(())
+}
+
+/// Reports a spike in an account's posting, and hands it to the alert's
+/// command and webhook.
+async fn alert(client: &Client, settings: &Settings, spike: &Spike) {
+  eprintln!("alert: {}", spike.message);
+
+  let Some(alert) = &settings.posting_alert else {
+    return;
+  };
+  let body = serde_json::to_vec(spike).unwrap_or_default();
+
+  if let Some(command) = &alert.command {
+    if let Err(error) = hooks::run_with(command, &body) {
+      eprintln!("warning: {}", error);
+    }
+  }
+
+  if let Some(webhook) = &alert.webhook {
+    if let Err(error) = hooks::deliver_with(client, webhook, body).await {
+      eprintln!("warning: {}", error);
+    }
+  }
+}
+
+/// Tells systemd how watching is going, warning if it can't be told.
+fn notify(state: &str) {
+  if let Err(error) = systemd::notify(state) {
+    eprintln!("warning: couldn't notify systemd: {}", error);
+  }
+}
+
+/// Keeps the watch's status for `apread status`, warning rather than
+/// stopping if it can't.
+fn save_status(status: &Status, path: Option<&Path>) {
+  if let Some(Err(error)) = path.map(|path| status.save(path)) {
+    eprintln!("warning: couldn't save the watch's status: {}", error);
+  }
+}
+
+/// Prints new posts as the format asks: as text, or a line of JSON each.
+pub(super) fn show(entries: &[Entry], settings: &Settings) -> io::Result<()> {
+  let out = &mut io::stdout().lock();
+  let colored =
+    io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+  if entries.is_empty() {
+    return Ok(());
+  }
+
+  match settings.format {
+    Format::Json => {
+      for entry in entries {
+        render::json_entry(out, entry)?;
+      }
+    }
+    _ if settings.screen_reader => {
+      render::screen_reader(out, entries, &settings.locale())?
+    }
+    _ => render::terminal(
+      out,
+      entries,
+      settings.width,
+      colored,
+      &settings.messages(),
+    )?,
+  }
+
+  out.flush()
+}
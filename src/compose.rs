@@ -0,0 +1,268 @@
+//! Writing posts and replies, through the Mastodon API.
+//!
+//! apread is a reader first, but with a login made with `--write` it can
+//! post too: `apread post` and `apread reply` send a [`Draft`], after
+//! uploading any [`Media`] it carries. Replies are found on your own
+//! instance by their url, so posts from anywhere can be replied to, and
+//! start by mentioning whoever they reply to, as other clients' do. Posts
+//! are favourited and boosted the same way, as an [`Action`] on the post
+//! found there.
+//!
+//! Posts can wait, too: drafts are kept in the archive until they're sent,
+//! and a post sent with a time is scheduled, kept by the instance until
+//! then. Drafts are written in `$VISUAL` or `$EDITOR`, as git's commit
+//! messages are.
+
+use std::{env, fs, io, path::Path, process::Command};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, FluentValue, Localized},
+  ApreadErrors,
+};
+
+/// Raised when your instance can't find the post being replied to.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NotFound(pub String);
+
+impl Localized for NotFound {
+  fn id(&self) -> &'static str {
+    "error-not-found"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("url", self.0.as_str().into())]
+  }
+}
+
+/// Who a post is shown to.
+#[derive(
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  Deserialize,
+  Serialize,
+  PartialEq,
+  Eq,
+  clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+  /// Everyone, on public timelines too.
+  #[default]
+  Public,
+  /// Everyone, but left off public timelines.
+  Unlisted,
+  /// Only followers.
+  Private,
+  /// Only the accounts mentioned.
+  Direct,
+}
+
+/// Raised when a draft is left empty, so there's nothing to keep.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct EmptyDraft;
+
+impl Localized for EmptyDraft {
+  fn id(&self) -> &'static str {
+    "error-empty-draft"
+  }
+}
+
+/// Something to do to a post.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+  /// Favourite it.
+  Favourite,
+  /// Boost it to your followers.
+  Boost,
+}
+
+impl Action {
+  /// The API's name for the action, as its url ends.
+  pub fn endpoint(self) -> &'static str {
+    match self {
+      Self::Favourite => "favourite",
+      Self::Boost => "reblog",
+    }
+  }
+}
+
+impl Visibility {
+  /// The visibility's name, as the API and the archive have it.
+  pub fn name(self) -> &'static str {
+    match self {
+      Self::Public => "public",
+      Self::Unlisted => "unlisted",
+      Self::Private => "private",
+      Self::Direct => "direct",
+    }
+  }
+
+  /// The visibility with a name, or public for any other.
+  pub fn from_name(name: &str) -> Self {
+    match name {
+      "unlisted" => Self::Unlisted,
+      "private" => Self::Private,
+      "direct" => Self::Direct,
+      _ => Self::Public,
+    }
+  }
+}
+
+/// A post to send.
+#[derive(Clone, Debug, Default, Serialize, PartialEq, Eq)]
+pub struct Draft {
+  /// The post's text.
+  pub status: String,
+  /// A content warning, shown in place of the text until it's opened.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub spoiler_text: Option<String>,
+  /// Who the post is shown to.
+  pub visibility: Visibility,
+  /// The ids of media already uploaded to attach.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub media_ids: Vec<String>,
+  /// The id, on your own instance, of the post this replies to.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub in_reply_to_id: Option<String>,
+  /// When to post it, as an RFC 3339 time, if not straight away.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub scheduled_at: Option<String>,
+}
+
+/// A file to attach to a post, with its alt text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Media {
+  /// The file's name, without its directory.
+  pub name: String,
+  /// What kind of file it is, going by its extension.
+  pub content_type: &'static str,
+  /// The file itself.
+  pub bytes: Vec<u8>,
+  /// Alt text describing it, for those who can't see or hear it.
+  pub description: Option<String>,
+}
+
+/// The boundary between parts of an upload. Uploads are built by hand, so
+/// it only has to be unlikely to turn up in a file.
+const BOUNDARY: &str = "apread-media-5c1a1d0e9b7f4c2a8e3d6b0f7a9c4e21";
+
+impl Media {
+  /// Reads the file at a path to attach, with alt text if there is any.
+  pub fn open(
+    path: &Path,
+    description: Option<String>,
+  ) -> Result<Self, ApreadErrors> {
+    Ok(Self {
+      name: path.file_name().map_or_else(
+        || "media".to_owned(),
+        |name| name.to_string_lossy().into(),
+      ),
+      content_type: content_type(path),
+      bytes: fs::read(path)?,
+      description,
+    })
+  }
+
+  /// The Content-Type of an upload of this media.
+  pub fn form_type() -> String {
+    format!("multipart/form-data; boundary={}", BOUNDARY)
+  }
+
+  /// This media as a multipart form, with its file and any description.
+  pub fn form(&self) -> Vec<u8> {
+    let mut form = format!(
+      "--{}\r\nContent-Disposition: form-data; name=\"file\"; \
+       filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+      BOUNDARY,
+      self.name.replace(['"', '\r', '\n'], "_"),
+      self.content_type
+    )
+    .into_bytes();
+
+    form.extend(&self.bytes);
+    form.extend(b"\r\n");
+
+    if let Some(description) = &self.description {
+      form.extend(
+        format!(
+          "--{}\r\nContent-Disposition: form-data; \
+           name=\"description\"\r\n\r\n{}\r\n",
+          BOUNDARY, description
+        )
+        .as_bytes(),
+      );
+    }
+
+    form.extend(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    form
+  }
+}
+
+/// What kind of file a path is, going by its extension.
+pub fn content_type(path: &Path) -> &'static str {
+  let extension = path
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_lowercase());
+
+  match extension.as_deref() {
+    Some("png") => "image/png",
+    Some("jpg" | "jpeg") => "image/jpeg",
+    Some("gif") => "image/gif",
+    Some("webp") => "image/webp",
+    Some("avif") => "image/avif",
+    Some("mp4") => "video/mp4",
+    Some("webm") => "video/webm",
+    Some("mov") => "video/quicktime",
+    Some("mp3") => "audio/mpeg",
+    Some("ogg" | "oga") => "audio/ogg",
+    Some("wav") => "audio/wav",
+    Some("flac") => "audio/flac",
+    _ => "application/octet-stream",
+  }
+}
+
+/// A reply's text, starting with a mention of whoever it replies to unless
+/// it mentions them already. Your own posts are replied to without one.
+pub fn with_mention(text: &str, acct: &str, own: &str) -> String {
+  let mention = format!("@{}", acct);
+
+  if acct == own || text.split_whitespace().any(|word| word == mention) {
+    text.to_owned()
+  } else {
+    format!("{} {}", mention, text)
+  }
+}
+
+/// Opens text in the user's editor, giving back the text as they left it,
+/// trimmed. The editor is `$VISUAL`, `$EDITOR` or `vi`, as in `vim` or
+/// `code --wait`.
+pub fn edit(text: &str) -> Result<String, ApreadErrors> {
+  let editor = env::var("VISUAL")
+    .or_else(|_| env::var("EDITOR"))
+    .unwrap_or_else(|_| "vi".to_owned());
+  let mut words = editor.split_whitespace();
+  let program = words.next().unwrap_or("vi");
+  let path =
+    env::temp_dir().join(format!("apread-draft-{}.md", std::process::id()));
+
+  fs::write(&path, text)?;
+
+  let status = Command::new(program).args(words).arg(&path).status();
+  let edited = fs::read_to_string(&path);
+  let _ = fs::remove_file(&path);
+
+  if !status?.success() {
+    return Err(
+      io::Error::other(format!("{} didn't exit cleanly", program)).into(),
+    );
+  }
+
+  Ok(edited?.trim().to_owned())
+}
@@ -0,0 +1,155 @@
+//! Collecting what went wrong without stopping a run, to say once it's
+//! done.
+//!
+//! An account which couldn't be read, a boost which couldn't be fetched, an
+//! instance slow to answer, a cache which couldn't be written: none of
+//! these stop a timeline, and none should break it up either. They're
+//! collected as the run goes, in [`Diagnostics`] shared between a client's
+//! clones, and written after the timeline as one block on stderr, or one
+//! JSON array under `--format json`, so what's read from stdout is only
+//! ever posts.
+
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use serde::Serialize;
+
+/// How long an instance can take to answer before it's worth saying so.
+pub const SLOW: Duration = Duration::from_secs(5);
+
+/// How much a diagnostic matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+  /// Something was left out, or may be wrong.
+  Warning,
+  /// Worth knowing, but nothing went wrong.
+  Note,
+}
+
+/// What a diagnostic is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+  /// An account couldn't be read.
+  Unreadable,
+  /// Accounts were skipped because their instance was down.
+  Unreachable,
+  /// Something in a timeline, as a boost, couldn't be fetched.
+  Skipped,
+  /// An instance took longer than [`SLOW`] to answer.
+  Slow,
+  /// A cache couldn't be read or written, so the run was only slower.
+  Cache,
+  /// An attachment has no alt text.
+  MissingAlt,
+  /// Posts were filtered as low quality.
+  Filtered,
+  /// The posts around a date weren't all found, so those nearest it were
+  /// shown instead.
+  Around,
+  /// A filter module failed on a post, so it was left as it was.
+  Wasm,
+}
+
+/// Something that went wrong, or is worth knowing, without stopping the
+/// run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+  /// How much it matters.
+  pub level: Level,
+  /// What it's about.
+  pub kind: Kind,
+  /// The account, instance or url it's about, if it's about one.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source: Option<String>,
+  /// What happened, in a sentence.
+  pub message: String,
+}
+
+impl Diagnostic {
+  /// A warning, about a source if there is one.
+  pub fn warning(
+    kind: Kind,
+    source: Option<String>,
+    message: impl Into<String>,
+  ) -> Self {
+    Self {
+      level: Level::Warning,
+      kind,
+      source,
+      message: message.into(),
+    }
+  }
+
+  /// A note, about a source if there is one.
+  pub fn note(
+    kind: Kind,
+    source: Option<String>,
+    message: impl Into<String>,
+  ) -> Self {
+    Self {
+      level: Level::Note,
+      ..Self::warning(kind, source, message)
+    }
+  }
+}
+
+/// The diagnostics of a run, in the order they came, shared between a
+/// [`Client`](crate::Client)'s clones.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics(Arc<Mutex<Vec<Diagnostic>>>);
+
+impl Diagnostics {
+  /// Adds a diagnostic, unless it's already been given.
+  pub fn push(&self, diagnostic: Diagnostic) {
+    let mut all = self.0.lock().expect("diagnostics lock poisoned");
+
+    if !all.contains(&diagnostic) {
+      all.push(diagnostic);
+    }
+  }
+
+  /// Notes that a request to a url took `took`, if that's longer than
+  /// [`SLOW`]. Only an instance's first slow request is noted.
+  pub fn request(&self, url: &str, took: Duration) {
+    if took <= SLOW {
+      return;
+    }
+
+    let instance = reqwest::Url::parse(url)
+      .ok()
+      .and_then(|url| url.host_str().map(str::to_owned))
+      .unwrap_or_else(|| url.to_owned());
+    let mut all = self.0.lock().expect("diagnostics lock poisoned");
+
+    if all.iter().any(|diagnostic| {
+      diagnostic.kind == Kind::Slow
+        && diagnostic.source.as_ref() == Some(&instance)
+    }) {
+      return;
+    }
+
+    all.push(Diagnostic::warning(
+      Kind::Slow,
+      Some(instance.clone()),
+      format!(
+        "{} was slow to answer, taking {:.1}s",
+        instance,
+        took.as_secs_f64()
+      ),
+    ));
+  }
+
+  /// Every diagnostic so far, leaving none, so each is only said once.
+  pub fn take(&self) -> Vec<Diagnostic> {
+    std::mem::take(&mut *self.0.lock().expect("diagnostics lock poisoned"))
+  }
+
+  /// Whether there are none.
+  pub fn is_empty(&self) -> bool {
+    self.0.lock().expect("diagnostics lock poisoned").is_empty()
+  }
+}
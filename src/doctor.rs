@@ -0,0 +1,221 @@
+//! Diagnosing why apread can't read something, for bug reports.
+//!
+//! Each check looks at one thing that has to work for a timeline to be
+//! read: the config and subscriptions files parse, the cache directory can
+//! be written to, and, for a given handle, its domain resolves, answers
+//! over TLS, and serves a WebFinger document leading to an actor.
+
+use std::{fmt, fs, net::ToSocketAddrs, path::Path};
+
+use crate::{
+  links::Health,
+  settings::{Overrides, Settings},
+  subscriptions::Subscriptions,
+  Client, Handle,
+};
+
+/// How a check went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+  /// It works.
+  Pass,
+  /// It works, but something about it is worth knowing.
+  Warn,
+  /// It doesn't work.
+  Fail,
+  /// It wasn't checked, because there was nothing to check or an earlier
+  /// check failed.
+  Skip,
+}
+
+impl fmt::Display for Status {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Pass => "ok",
+      Self::Warn => "warn",
+      Self::Fail => "FAIL",
+      Self::Skip => "skip",
+    })
+  }
+}
+
+/// One line of the report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Check {
+  /// What was checked.
+  pub name: &'static str,
+  /// How it went.
+  pub status: Status,
+  /// What was found.
+  pub detail: String,
+}
+
+impl Check {
+  /// A check that wasn't made, and why.
+  pub fn skipped(name: &'static str, why: impl Into<String>) -> Self {
+    Self::new(name, Status::Skip, why)
+  }
+
+  fn new(
+    name: &'static str,
+    status: Status,
+    detail: impl Into<String>,
+  ) -> Self {
+    Self {
+      name,
+      status,
+      detail: detail.into(),
+    }
+  }
+}
+
+/// Whether the config file, if there is one, can be read.
+pub fn config(path: Option<&Path>) -> Check {
+  let name = "Config file";
+
+  let path = match path {
+    Some(path) if path.exists() => path,
+    Some(path) => {
+      return Check::new(
+        name,
+        Status::Pass,
+        format!("{} (not there; using defaults)", path.display()),
+      )
+    }
+    None => return Check::new(name, Status::Skip, "no config directory"),
+  };
+
+  match Settings::load(Some(path), Overrides::default()) {
+    Ok(_) => Check::new(name, Status::Pass, path.display().to_string()),
+    Err(error) => {
+      Check::new(name, Status::Fail, format!("{}: {}", path.display(), error))
+    }
+  }
+}
+
+/// Whether the subscriptions file, if there is one, can be read.
+pub fn subscriptions(path: Option<&Path>) -> Check {
+  let name = "Subscriptions file";
+
+  let path = match path {
+    Some(path) if path.exists() => path,
+    Some(path) => {
+      return Check::new(
+        name,
+        Status::Pass,
+        format!("{} (not there; no subscriptions)", path.display()),
+      )
+    }
+    None => return Check::new(name, Status::Skip, "no config directory"),
+  };
+
+  match Subscriptions::load(path) {
+    Ok(subscriptions) => Check::new(
+      name,
+      Status::Pass,
+      format!(
+        "{} ({} accounts)",
+        path.display(),
+        subscriptions.accounts.len()
+      ),
+    ),
+    Err(error) => {
+      Check::new(name, Status::Fail, format!("{}: {}", path.display(), error))
+    }
+  }
+}
+
+/// Whether the cache directory can be created and written to.
+pub fn cache_dir(dir: Option<&Path>) -> Check {
+  let name = "Cache directory";
+
+  let dir = match dir {
+    Some(dir) => dir,
+    None => return Check::new(name, Status::Warn, "none; nothing is cached"),
+  };
+
+  let probe = dir.join(".apread-doctor");
+  let written = fs::create_dir_all(dir)
+    .and_then(|_| fs::write(&probe, b"apread"))
+    .and_then(|_| fs::remove_file(&probe));
+
+  match written {
+    Ok(()) => Check::new(name, Status::Pass, dir.display().to_string()),
+    Err(error) => {
+      Check::new(name, Status::Fail, format!("{}: {}", dir.display(), error))
+    }
+  }
+}
+
+/// Whether a handle's domain resolves to an address.
+pub fn dns(handle: &Handle) -> Check {
+  let name = "DNS";
+  let address = match handle.domain.contains(':') {
+    true => handle.domain.clone(),
+    false => format!("{}:443", handle.domain),
+  };
+
+  match address.to_socket_addrs() {
+    Ok(mut addresses) => match addresses.next() {
+      Some(address) => Check::new(
+        name,
+        Status::Pass,
+        format!("{} is {}", handle.domain, address.ip()),
+      ),
+      None => Check::new(
+        name,
+        Status::Fail,
+        format!("{} has no addresses", handle.domain),
+      ),
+    },
+    Err(error) => {
+      Check::new(name, Status::Fail, format!("{}: {}", handle.domain, error))
+    }
+  }
+}
+
+/// Whether a handle's domain answers https requests, which means the
+/// connection and TLS handshake both work.
+pub async fn tls(client: &Client, handle: &Handle) -> Check {
+  let name = "Connection and TLS";
+  let url = format!("https://{}/", handle.domain);
+
+  match client.check(&url).await {
+    Health::Unreachable { reason } => {
+      Check::new(name, Status::Fail, format!("{}: {}", url, reason))
+    }
+    health => {
+      Check::new(name, Status::Pass, format!("{} answered ({})", url, health))
+    }
+  }
+}
+
+/// Whether a handle's WebFinger document can be fetched, and leads to an
+/// actor with an outbox.
+pub async fn webfinger(client: &Client, handle: &Handle) -> Check {
+  let name = "WebFinger";
+
+  let found = client.webfinger(handle).await;
+
+  let actor_url = match found.and_then(|webfinger| webfinger.to_actor_url()) {
+    Ok(url) => url,
+    Err(error) => {
+      return Check::new(
+        name,
+        Status::Fail,
+        format!("{}@{}: {}", handle.id, handle.domain, error),
+      )
+    }
+  };
+
+  match client.actor(actor_url.as_str()).await {
+    Ok(actor) => Check::new(
+      name,
+      Status::Pass,
+      format!("{} has its outbox at {}", actor_url, actor.outbox),
+    ),
+    Err(error) => {
+      Check::new(name, Status::Fail, format!("{}: {}", actor_url, error))
+    }
+  }
+}
@@ -0,0 +1,187 @@
+//! Looking instances up with DNS-over-HTTPS, for networks whose own DNS is
+//! broken or censored.
+//!
+//! Given a resolver's url, as `--doh https://1.1.1.1/dns-query`, hostnames
+//! are looked up by asking it for their A and AAAA records in the JSON form
+//! Cloudflare, Google and most other public resolvers answer in. Giving the
+//! resolver by its IP address, rather than its name, means apread need
+//! never ask the local DNS for anything.
+//!
+//! The resolver is asked through the same proxy, timeouts and kind of
+//! address as instances are, both record types at once, and its answers
+//! are kept for as long as their TTLs say.
+
+use std::{
+  collections::HashMap,
+  error::Error,
+  net::{IpAddr, SocketAddr},
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::i18n::{self, FluentValue, Localized};
+
+/// The record type for an IPv4 address.
+const A: u16 = 1;
+
+/// The record type for an IPv6 address.
+const AAAA: u16 = 28;
+
+/// Raised when a DoH resolver has no addresses for a hostname.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoAddress(pub String);
+
+impl Localized for NoAddress {
+  fn id(&self) -> &'static str {
+    "error-no-address"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("name", self.0.as_str().into())]
+  }
+}
+
+/// A DNS-over-HTTPS resolver, for a client to look hostnames up with.
+#[derive(Clone, Debug)]
+pub struct Doh {
+  url: String,
+  http: reqwest::Client,
+  known: Arc<Mutex<HashMap<String, Known>>>,
+}
+
+/// A hostname's addresses, as last looked up, and until when they hold.
+#[derive(Clone, Debug)]
+struct Known {
+  addresses: Vec<IpAddr>,
+  until: Instant,
+}
+
+#[derive(Deserialize)]
+struct Answers {
+  #[serde(rename = "Answer", default)]
+  answer: Vec<Answer>,
+}
+
+#[derive(Deserialize)]
+struct Answer {
+  #[serde(rename = "type")]
+  kind: u16,
+  #[serde(rename = "TTL", default)]
+  ttl: u64,
+  data: String,
+}
+
+type Failure = Box<dyn Error + Send + Sync>;
+
+impl Doh {
+  /// A resolver asking the DoH server at a url, as in
+  /// `https://1.1.1.1/dns-query`.
+  pub fn new(url: impl Into<String>) -> Self {
+    Self {
+      url: url.into(),
+      http: reqwest::Client::new(),
+      known: Arc::default(),
+    }
+  }
+
+  /// The resolver, asking its server with an HTTP client set up as
+  /// instances are asked, through the same proxy and with the same
+  /// timeouts.
+  pub fn through(self, http: reqwest::Client) -> Self {
+    Self { http, ..self }
+  }
+
+  /// The addresses a hostname has, IPv4 first.
+  pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, Failure> {
+    if let Some(known) = self.known(host) {
+      return Ok(known);
+    }
+
+    let (v4, v6) = tokio::join!(self.ask(host, A), self.ask(host, AAAA));
+    let (v4, v4_ttl) = v4?;
+    let (v6, v6_ttl) = v6?;
+    let addresses: Vec<_> = v4.into_iter().chain(v6).collect();
+
+    if addresses.is_empty() {
+      return Err(NoAddress(host.to_owned()).into());
+    }
+
+    let ttl = v4_ttl.into_iter().chain(v6_ttl).min().unwrap_or_default();
+
+    self.known.lock().expect("DoH cache lock poisoned").insert(
+      host.to_owned(),
+      Known {
+        addresses: addresses.clone(),
+        until: Instant::now() + Duration::from_secs(ttl),
+      },
+    );
+
+    Ok(addresses)
+  }
+
+  /// A hostname's addresses, if they were looked up and still hold.
+  fn known(&self, host: &str) -> Option<Vec<IpAddr>> {
+    let known = self.known.lock().expect("DoH cache lock poisoned");
+
+    known
+      .get(host)
+      .filter(|known| known.until > Instant::now())
+      .map(|known| known.addresses.clone())
+  }
+
+  /// The addresses in a hostname's records of one type, along with the
+  /// shortest time they're good for, if there are any.
+  async fn ask(
+    &self,
+    host: &str,
+    kind: u16,
+  ) -> Result<(Vec<IpAddr>, Option<u64>), Failure> {
+    let answers: Answers = self
+      .http
+      .get(&self.url)
+      .query(&[("name", host), ("type", &kind.to_string())])
+      .header(reqwest::header::ACCEPT, "application/dns-json")
+      .send()
+      .await?
+      .error_for_status()?
+      .json()
+      .await?;
+    // Answers can include the CNAMEs followed along the way, whose data
+    // is a name rather than an address.
+    let answers: Vec<_> = answers
+      .answer
+      .iter()
+      .filter(|answer| answer.kind == kind)
+      .filter_map(|answer| Some((answer.data.parse().ok()?, answer.ttl)))
+      .collect();
+    let ttl = answers.iter().map(|(_, ttl)| *ttl).min();
+
+    Ok((
+      answers.into_iter().map(|(address, _)| address).collect(),
+      ttl,
+    ))
+  }
+}
+
+impl Resolve for Doh {
+  fn resolve(&self, name: Name) -> Resolving {
+    let doh = self.clone();
+
+    Box::pin(async move {
+      let addresses = doh.lookup(name.as_str()).await?;
+      // The port is filled in from the url being fetched.
+      let addrs: Addrs = Box::new(
+        addresses
+          .into_iter()
+          .map(|address| SocketAddr::new(address, 0)),
+      );
+
+      Ok(addrs)
+    })
+  }
+}
@@ -0,0 +1,278 @@
+//! Exporting posts into a standalone SQLite database, or an ActivityPub
+//! account archive.
+//!
+//! The SQLite export has its own schema, separate from apread's archive,
+//! and it only changes with [`SCHEMA_VERSION`]. Version 1 is:
+//!
+//! ```sql
+//! -- Facts about the export itself: `schema_version`, `apread_version`,
+//! -- and `exported_at` (RFC 3339).
+//! CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+//!
+//! CREATE TABLE posts (
+//!   id TEXT PRIMARY KEY,  -- the post's ActivityPub id
+//!   url TEXT,             -- its web page, where it has one
+//!   handle TEXT,          -- its author, as id@domain, where known
+//!   published TEXT,       -- RFC 3339, as the server gave it
+//!   summary TEXT,         -- the content warning, if any
+//!   content_html TEXT NOT NULL,
+//!   content_plaintext TEXT NOT NULL
+//! );
+//!
+//! -- The post's hashtags, without their `#`.
+//! CREATE TABLE tags (
+//!   post_id TEXT NOT NULL REFERENCES posts (id),
+//!   tag TEXT NOT NULL,
+//!   PRIMARY KEY (post_id, tag)
+//! );
+//!
+//! -- Posts the reader bookmarked, with their own tags and note.
+//! CREATE TABLE bookmarks (
+//!   post_id TEXT PRIMARY KEY REFERENCES posts (id),
+//!   tags TEXT NOT NULL,   -- space-separated
+//!   note TEXT,
+//!   created_at TEXT NOT NULL
+//! );
+//! ```
+//!
+//! The account archive is a directory laid out as Mastodon's own account
+//! export is, for tools which read those: an `outbox.json` collection of
+//! `Create` activities, oldest first, and a `bookmarks.json` collection of
+//! bookmarked posts' ids. Each activity's `actor` is its post's author, and
+//! its post keeps the addressing and attachments it was archived with.
+//! Posts archived before apread kept those were read from public outboxes,
+//! so they're addressed as public.
+
+use std::{collections::HashSet, fs, io, path::Path};
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+
+use crate::{
+  activity::PUBLIC,
+  archive::{ArchivedPost, Bookmark},
+  compose::Visibility,
+  render, ApreadErrors,
+};
+
+/// The version of the schema exports are written with.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = "
+  CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+  CREATE TABLE posts (
+    id TEXT PRIMARY KEY,
+    url TEXT,
+    handle TEXT,
+    published TEXT,
+    summary TEXT,
+    content_html TEXT NOT NULL,
+    content_plaintext TEXT NOT NULL
+  );
+  CREATE TABLE tags (
+    post_id TEXT NOT NULL REFERENCES posts (id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (post_id, tag)
+  );
+  CREATE TABLE bookmarks (
+    post_id TEXT PRIMARY KEY REFERENCES posts (id),
+    tags TEXT NOT NULL,
+    note TEXT,
+    created_at TEXT NOT NULL
+  );
+";
+
+/// Writes posts and bookmarks into a new SQLite database at `path`, which
+/// mustn't already exist. Returns how many posts were written.
+pub fn sqlite(
+  path: &Path,
+  posts: &[ArchivedPost],
+  bookmarks: &[Bookmark],
+) -> Result<usize, ApreadErrors> {
+  if path.exists() {
+    return Err(
+      io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+      )
+      .into(),
+    );
+  }
+
+  let mut connection = Connection::open(path)?;
+  let transaction = connection.transaction()?;
+
+  transaction.execute_batch(SCHEMA)?;
+
+  for (key, value) in [
+    ("schema_version", SCHEMA_VERSION.to_string()),
+    ("apread_version", env!("CARGO_PKG_VERSION").to_owned()),
+    ("exported_at", chrono::Utc::now().to_rfc3339()),
+  ] {
+    transaction.execute(
+      "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+      params![key, value],
+    )?;
+  }
+
+  let mut written = 0;
+  let bookmarked = bookmarks.iter().map(|bookmark| &bookmark.post);
+
+  for post in posts.iter().chain(bookmarked) {
+    written += transaction.execute(
+      "INSERT OR IGNORE INTO posts
+         (id, url, handle, published, summary, content_html, content_plaintext)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+      params![
+        post.id,
+        post.url,
+        post.author,
+        post.published,
+        post.summary,
+        post.content,
+        render::plain_text(&post.content),
+      ],
+    )?;
+
+    for tag in &post.hashtags {
+      transaction.execute(
+        "INSERT OR IGNORE INTO tags (post_id, tag) VALUES (?1, ?2)",
+        params![post.id, tag],
+      )?;
+    }
+  }
+
+  for bookmark in bookmarks {
+    transaction.execute(
+      "INSERT INTO bookmarks (post_id, tags, note, created_at)
+       VALUES (?1, ?2, ?3, ?4)",
+      params![
+        bookmark.post.id,
+        bookmark.tags.join(" "),
+        bookmark.note,
+        bookmark.created_at,
+      ],
+    )?;
+  }
+
+  transaction.commit()?;
+
+  Ok(written)
+}
+
+/// Writes posts and bookmarks into `dir` as an ActivityPub account archive,
+/// creating the directory if need be. Returns how many posts were written.
+pub fn ap_archive(
+  dir: &Path,
+  posts: &[ArchivedPost],
+  bookmarks: &[Bookmark],
+) -> Result<usize, ApreadErrors> {
+  let mut seen = HashSet::new();
+  let bookmarked = bookmarks.iter().map(|bookmark| &bookmark.post);
+  let mut posts: Vec<_> = posts
+    .iter()
+    .chain(bookmarked)
+    .filter(|post| seen.insert(&post.id))
+    .collect();
+
+  posts.sort_by(|a, b| a.published.cmp(&b.published));
+
+  let activities: Vec<_> = posts.iter().map(|post| create(post)).collect();
+  let bookmarks: Vec<_> =
+    bookmarks.iter().map(|bookmark| &bookmark.post.id).collect();
+
+  fs::create_dir_all(dir)?;
+  fs::write(
+    dir.join("outbox.json"),
+    serde_json::to_string_pretty(&collection("outbox.json", &activities))?,
+  )?;
+  fs::write(
+    dir.join("bookmarks.json"),
+    serde_json::to_string_pretty(&collection("bookmarks.json", &bookmarks))?,
+  )?;
+
+  Ok(activities.len())
+}
+
+fn collection(id: &str, items: &[impl serde::Serialize]) -> Value {
+  json!({
+    "@context": "https://www.w3.org/ns/activitystreams",
+    "id": id,
+    "type": "OrderedCollection",
+    "totalItems": items.len(),
+    "orderedItems": items,
+  })
+}
+
+/// The `Create` activity for an archived post, as Mastodon's export has
+/// them.
+fn create(post: &ArchivedPost) -> Value {
+  let tags: Vec<_> = post
+    .hashtags
+    .iter()
+    .map(|tag| json!({ "type": "Hashtag", "name": format!("#{}", tag) }))
+    .collect();
+  let attachments: Vec<_> = post
+    .attachments
+    .iter()
+    .map(|attachment| {
+      json!({
+        "type": "Document",
+        "mediaType": attachment.media_type,
+        "url": attachment.url,
+        "name": attachment.name,
+        "blurhash": attachment.blurhash,
+        "width": attachment.width,
+        "height": attachment.height,
+      })
+    })
+    .collect();
+  let (to, cc) = audience(post);
+
+  json!({
+    "id": format!("{}/activity", post.id),
+    "type": "Create",
+    "actor": post.actor,
+    "published": post.published,
+    "to": to,
+    "cc": cc,
+    "object": {
+      "id": post.id,
+      "type": "Note",
+      "attributedTo": post.actor,
+      "summary": post.summary,
+      "published": post.published,
+      "url": post.url,
+      "to": to,
+      "cc": cc,
+      "sensitive": post.summary.as_deref().is_some_and(|cw| !cw.is_empty()),
+      "content": post.content,
+      "attachment": attachments,
+      "tag": tags,
+    },
+  })
+}
+
+/// Who an archived post is addressed and copied to: as it was archived, or
+/// as its visibility has it where only that was kept.
+fn audience(post: &ArchivedPost) -> (Vec<String>, Vec<String>) {
+  if !post.to.is_empty() || !post.cc.is_empty() {
+    return (post.to.clone(), post.cc.clone());
+  }
+
+  let public = || vec![PUBLIC.to_owned()];
+  let followers = || {
+    post
+      .actor
+      .iter()
+      .map(|actor| format!("{}/followers", actor))
+      .collect()
+  };
+
+  match post.visibility.unwrap_or_default() {
+    Visibility::Public => (public(), followers()),
+    Visibility::Unlisted => (followers(), public()),
+    Visibility::Private => (followers(), vec![]),
+    Visibility::Direct => (vec![], vec![]),
+  }
+}
@@ -0,0 +1,84 @@
+//! RSS and Atom feeds, read into the same posts as fediverse accounts.
+
+use feed_rs::model;
+
+use crate::{
+  activity::{escape, Post, Tag},
+  timeline::{Author, Entry},
+  ApreadErrors,
+};
+
+/// Reads the entries of an RSS or Atom feed as timeline entries. Feed
+/// categories become hashtags.
+pub fn entries(
+  author: &Author,
+  text: &str,
+) -> Result<Vec<Entry>, ApreadErrors> {
+  let feed = feed_rs::parser::parse(text.as_bytes())?;
+
+  Ok(
+    feed
+      .entries
+      .into_iter()
+      .map(|entry| to_entry(author, entry))
+      .collect(),
+  )
+}
+
+fn to_entry(author: &Author, entry: model::Entry) -> Entry {
+  let published = entry
+    .published
+    .or(entry.updated)
+    .map(|date| date.to_rfc3339())
+    .unwrap_or_default();
+  let body = entry
+    .content
+    .and_then(|content| content.body)
+    .or_else(|| entry.summary.map(|summary| summary.content))
+    .unwrap_or_default();
+
+  // A title is part of what a feed entry says, where a fediverse post has
+  // none, so it leads the content.
+  let content = match entry.title {
+    Some(title) => {
+      format!("<p><strong>{}</strong></p>{}", escape(&title.content), body)
+    }
+    None => body,
+  };
+
+  let tag = entry
+    .categories
+    .into_iter()
+    .map(|category| Tag::Hashtag {
+      name: category.term,
+      href: None,
+    })
+    .collect();
+
+  Entry {
+    author: author.clone(),
+    post: Post {
+      id: Some(entry.id),
+      kind: None,
+      url: entry.links.into_iter().next().map(|link| link.href),
+      attributed_to: None,
+      published: Some(published.clone()),
+      updated: None,
+      summary: None,
+      content,
+      tag,
+      attachment: vec![],
+      to: vec![],
+      cc: vec![],
+      sensitive: false,
+      duration: None,
+      name: None,
+      start_time: None,
+      end_time: None,
+      location: None,
+      in_reply_to: None,
+    },
+    published,
+    boosted: false,
+  }
+}
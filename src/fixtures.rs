@@ -0,0 +1,94 @@
+//! Recording responses to disk, and replaying them without the network.
+//!
+//! A fixture directory holds one file per response body, plus an
+//! `index.json` mapping each requested url to the file holding its body.
+//! Recording from a real run and replaying it later is the quickest way to
+//! reproduce a parsing bug against the exact documents that caused it.
+
+use std::{
+  collections::BTreeMap,
+  fs, io,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+};
+
+use thiserror::Error;
+
+use crate::i18n::{self, FluentValue, Localized};
+
+const INDEX: &str = "index.json";
+
+/// Raised when replaying a url that was never recorded.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NotRecorded(pub String);
+
+impl Localized for NotRecorded {
+  fn id(&self) -> &'static str {
+    "error-not-recorded"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("url", self.0.as_str().into())]
+  }
+}
+
+/// What a client does with fixtures, if anything.
+#[derive(Clone, Debug)]
+pub enum Fixtures {
+  /// Save every response into a directory as it is fetched.
+  Record(Recorder),
+  /// Serve responses from a directory instead of fetching them.
+  Replay(PathBuf),
+}
+
+/// Saves response bodies into a fixture directory.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+  dir: PathBuf,
+  index: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl Recorder {
+  /// Starts recording into a directory, creating it if need be.
+  ///
+  /// Anything already recorded there is kept, so several runs can share a
+  /// directory.
+  pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+    let dir = dir.into();
+    fs::create_dir_all(&dir)?;
+
+    Ok(Self {
+      index: Arc::new(Mutex::new(read_index(&dir)?)),
+      dir,
+    })
+  }
+
+  /// Saves the body fetched for a url.
+  pub fn record(&self, url: &str, body: &str) -> io::Result<()> {
+    let mut index = self.index.lock().expect("fixture index lock poisoned");
+    let next = format!("{:04}.json", index.len() + 1);
+    let file = index.entry(url.to_owned()).or_insert(next).clone();
+
+    fs::write(self.dir.join(file), body)?;
+    fs::write(self.dir.join(INDEX), serde_json::to_string_pretty(&*index)?)
+  }
+}
+
+/// Reads the body recorded for a url in a fixture directory.
+pub fn replay(dir: &Path, url: &str) -> Result<String, crate::ApreadErrors> {
+  let index = read_index(dir)?;
+  let file = index.get(url).ok_or_else(|| NotRecorded(url.to_owned()))?;
+
+  Ok(fs::read_to_string(dir.join(file))?)
+}
+
+fn read_index(dir: &Path) -> io::Result<BTreeMap<String, String>> {
+  match fs::read_to_string(dir.join(INDEX)) {
+    Ok(index) => Ok(serde_json::from_str(&index)?),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => {
+      Ok(BTreeMap::new())
+    }
+    Err(error) => Err(error),
+  }
+}
@@ -0,0 +1,335 @@
+//! Who an account talks to: a graph of mentions and boosts, for looking at
+//! in Graphviz or Gephi.
+//!
+//! Accounts are the graph's nodes, keyed by actor url, and each edge counts
+//! how often one account mentioned or boosted another. The graph starts
+//! from one account's outbox; with a depth of 2 or more, the outboxes of
+//! everyone it interacted with are read too, and so on outwards.
+
+use std::io::{self, Write};
+
+use crate::{
+  activity::{escape, Actor, Item, Page, Tag},
+  ApreadErrors, Client, Handle,
+};
+
+/// How one account interacted with another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interaction {
+  /// It mentioned the other account in a post.
+  Mention,
+  /// It boosted one of the other account's posts.
+  Boost,
+}
+
+impl Interaction {
+  fn name(self) -> &'static str {
+    match self {
+      Self::Mention => "mention",
+      Self::Boost => "boost",
+    }
+  }
+}
+
+/// An account in the graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+  /// The account's actor url.
+  pub id: String,
+  /// The account's handle, or its actor url when the handle isn't known.
+  pub label: String,
+}
+
+/// How often one account interacted with another, in one way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+  /// The actor url of the account which did the interacting.
+  pub from: String,
+  /// The actor url of the account it interacted with.
+  pub to: String,
+  /// What sort of interaction it was.
+  pub kind: Interaction,
+  /// How many times.
+  pub weight: usize,
+}
+
+/// Accounts, and how they've interacted.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+  /// Every account, in the order it was first seen.
+  pub nodes: Vec<Node>,
+  /// Every interaction, in the order it was first seen.
+  pub edges: Vec<Edge>,
+}
+
+impl Graph {
+  /// Adds an account, or gives one already in the graph a better label.
+  fn node(&mut self, id: &str, label: Option<&str>) {
+    match self.nodes.iter_mut().find(|node| node.id == id) {
+      Some(node) => {
+        if let Some(label) = label {
+          node.label = label.to_owned();
+        }
+      }
+      None => self.nodes.push(Node {
+        id: id.to_owned(),
+        label: label.unwrap_or(id).to_owned(),
+      }),
+    }
+  }
+
+  /// Counts one interaction. Accounts interacting with themselves aren't
+  /// counted.
+  fn interact(&mut self, from: &str, to: &str, kind: Interaction) {
+    if from == to {
+      return;
+    }
+
+    let edge = self
+      .edges
+      .iter_mut()
+      .find(|edge| edge.from == from && edge.to == to && edge.kind == kind);
+
+    match edge {
+      Some(edge) => edge.weight += 1,
+      None => self.edges.push(Edge {
+        from: from.to_owned(),
+        to: to.to_owned(),
+        kind,
+        weight: 1,
+      }),
+    }
+  }
+
+  /// Writes the graph as Graphviz DOT, with boosts drawn dashed.
+  pub fn dot(&self, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "digraph apread {{")?;
+
+    for node in &self.nodes {
+      writeln!(
+        out,
+        "  {} [label={}];",
+        quoted(&node.id),
+        quoted(&node.label)
+      )?;
+    }
+
+    for edge in &self.edges {
+      let style = match edge.kind {
+        Interaction::Mention => "solid",
+        Interaction::Boost => "dashed",
+      };
+
+      writeln!(
+        out,
+        "  {} -> {} [label=\"{}\", weight={}, style={}];",
+        quoted(&edge.from),
+        quoted(&edge.to),
+        edge.kind.name(),
+        edge.weight,
+        style
+      )?;
+    }
+
+    writeln!(out, "}}")
+  }
+
+  /// Writes the graph as GEXF, with each edge's kind as an attribute.
+  pub fn gexf(&self, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+    writeln!(out, r#"  <graph defaultedgetype="directed">"#)?;
+    writeln!(out, r#"    <attributes class="edge">"#)?;
+    writeln!(
+      out,
+      r#"      <attribute id="kind" title="kind" type="string"/>"#
+    )?;
+    writeln!(out, "    </attributes>")?;
+    writeln!(out, "    <nodes>")?;
+
+    for node in &self.nodes {
+      writeln!(
+        out,
+        r#"      <node id="{}" label="{}"/>"#,
+        attribute(&node.id),
+        attribute(&node.label)
+      )?;
+    }
+
+    writeln!(out, "    </nodes>")?;
+    writeln!(out, "    <edges>")?;
+
+    for (id, edge) in self.edges.iter().enumerate() {
+      writeln!(
+        out,
+        r#"      <edge id="{}" source="{}" target="{}" weight="{}">"#,
+        id,
+        attribute(&edge.from),
+        attribute(&edge.to),
+        edge.weight
+      )?;
+      writeln!(
+        out,
+        r#"        <attvalues><attvalue for="kind" value="{}"/></attvalues>"#,
+        edge.kind.name()
+      )?;
+      writeln!(out, "      </edge>")?;
+    }
+
+    writeln!(out, "    </edges>")?;
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")
+  }
+}
+
+/// Builds the graph around an account, reading `pages` pages of each
+/// outbox. A depth of 1 reads only the account's own outbox; each level
+/// beyond that reads the outboxes of the accounts the last level reached.
+/// Accounts beyond the first which can't be read are left as they are.
+pub async fn build(
+  client: &Client,
+  handle: &Handle,
+  depth: usize,
+  pages: usize,
+) -> Result<Graph, ApreadErrors> {
+  let mut graph = Graph::default();
+  let mut visited: Vec<String> = vec![];
+  let root = client.resolve(handle).await?;
+  let mut level = vec![root];
+
+  for step in 1..=depth {
+    let mut next = vec![];
+
+    for actor in level {
+      let id = match &actor.id {
+        Some(id) => id.to_string(),
+        None => continue,
+      };
+
+      if visited.contains(&id) || (step > 1 && client.interrupted()) {
+        continue;
+      }
+
+      visited.push(id.clone());
+      graph.node(&id, actor.handle().as_deref());
+
+      let reached = match read(client, &mut graph, &actor, &id, pages).await {
+        Ok(reached) => reached,
+        Err(error) if step == 1 => return Err(error),
+        Err(_) => continue,
+      };
+
+      if step < depth {
+        for url in reached {
+          if !visited.contains(&url) {
+            if let Ok(actor) = client.actor(&url).await {
+              next.push(actor);
+            }
+          }
+        }
+      }
+    }
+
+    level = next;
+  }
+
+  Ok(graph)
+}
+
+/// Adds an actor's interactions to the graph, returning the actor urls of
+/// everyone it interacted with.
+async fn read(
+  client: &Client,
+  graph: &mut Graph,
+  actor: &Actor,
+  id: &str,
+  pages: usize,
+) -> Result<Vec<String>, ApreadErrors> {
+  let mut reached = vec![];
+  let mut page = client.newest_page(actor).await?;
+
+  for taken in 1..=pages {
+    for (to, label, kind) in interactions(client, &page, id).await {
+      graph.node(&to, label.as_deref());
+      graph.interact(id, &to, kind);
+
+      if !reached.contains(&to) {
+        reached.push(to);
+      }
+    }
+
+    match page.next.take() {
+      Some(url) if taken < pages && !client.interrupted() => {
+        page = client.page(url.as_str()).await?
+      }
+      _ => break,
+    }
+  }
+
+  Ok(reached)
+}
+
+/// The interactions on a page, as the actor url and (when it's known) the
+/// handle of the account interacted with. A boost's author is found among
+/// the accounts it's addressed to, or failing that by fetching the post.
+async fn interactions(
+  client: &Client,
+  page: &Page,
+  id: &str,
+) -> Vec<(String, Option<String>, Interaction)> {
+  let mut found = vec![];
+
+  for item in &page.ordered_items {
+    match item {
+      Item::Post { object, .. } => {
+        for tag in &object.tag {
+          if let Tag::Mention {
+            name,
+            href: Some(href),
+          } = tag
+          {
+            let label = name
+              .as_deref()
+              .map(|name| name.trim_start_matches('@').to_owned());
+
+            found.push((href.clone(), label, Interaction::Mention));
+          }
+        }
+      }
+      Item::Boost { object, cc, .. } => {
+        let addressed = cc.iter().find(|url| {
+          *url != id
+            && !url.ends_with("#Public")
+            && !url.ends_with("/followers")
+        });
+
+        let author = match (addressed, object) {
+          (Some(url), _) => Some(url.clone()),
+          (None, Some(object)) => client
+            .boosted(object)
+            .await
+            .ok()
+            .and_then(|post| post.attributed_to),
+          (None, None) => None,
+        };
+
+        if let Some(author) = author {
+          found.push((author, None, Interaction::Boost));
+        }
+      }
+      Item::Edit { .. } | Item::Other => {}
+    }
+  }
+
+  found
+}
+
+/// A DOT string literal.
+fn quoted(text: &str) -> String {
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Text escaped for an XML attribute.
+fn attribute(text: &str) -> String {
+  escape(text).replace('"', "&quot;")
+}
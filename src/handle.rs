@@ -0,0 +1,96 @@
+//! Fediverse handles, in the `id@domain` form.
+
+use thiserror::Error;
+
+use crate::i18n::{self, FluentValue, Localized};
+
+/// An account handle, split into the account id and the domain hosting it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Handle {
+  /// The domain of the instance the account lives on.
+  pub domain: String,
+  /// The account's local id on its instance.
+  pub id: String,
+}
+
+impl std::fmt::Display for Handle {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}@{}", self.id, self.domain)
+  }
+}
+
+impl Handle {
+  /// Reads a handle from an `id@domain` string.
+  pub fn parse_string(given_string: &str) -> Result<Self, BadHandleError> {
+    let candidate: Vec<_> = given_string.split('@').collect();
+    let domain = candidate
+      .get(1)
+      .map(|str| (*str).to_owned())
+      .ok_or(BadHandleError)?;
+    let id = candidate
+      .first()
+      .map(|str| (*str).to_owned())
+      .ok_or(BadHandleError)?;
+
+    Ok(Self { domain, id })
+  }
+
+  /// Reads a newline-separated list of handles, skipping blank lines and
+  /// `#` comments.
+  pub fn parse_list(text: &str) -> Result<Vec<Self>, BadHandleError> {
+    text
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+      .map(Self::parse_string)
+      .collect()
+  }
+
+  /// Whether the account is on a Tor onion service, which can only be
+  /// reached through Tor.
+  pub fn is_onion(&self) -> bool {
+    onion(&self.domain)
+  }
+
+  /// The WebFinger lookup url for this handle, using the given scheme.
+  pub fn to_webfinger_url(&self, scheme: &str) -> String {
+    format!(
+      "{}://{}/.well-known/webfinger?resource=acct:{}@{}",
+      scheme, self.domain, self.id, self.domain
+    )
+  }
+}
+
+/// Whether a domain, perhaps with a port, is a Tor onion service's.
+pub(crate) fn onion(domain: &str) -> bool {
+  let host = domain.rsplit_once(':').map_or(domain, |(host, _)| host);
+
+  host.to_ascii_lowercase().ends_with(".onion")
+}
+
+/// Raised when reading an account on an onion service without Tor to reach
+/// it through.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NeedsTor(pub String);
+
+impl Localized for NeedsTor {
+  fn id(&self) -> &'static str {
+    "error-needs-tor"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("handle", self.0.as_str().into())]
+  }
+}
+
+/// Raised when a string can't be read as a handle.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct BadHandleError;
+
+impl Localized for BadHandleError {
+  fn id(&self) -> &'static str {
+    "error-bad-handle"
+  }
+}
@@ -0,0 +1,75 @@
+//! Hashtag timelines: the posts an instance knows with a tag, from across
+//! the fediverse.
+//!
+//! A tag is written `#tag@instance`, as in `#gardening@mastodon.social`, and
+//! reads wherever an account does: `apread tag`, `apread read`, views and
+//! subscriptions. ActivityPub has no standard hashtag collection, so posts
+//! are read through the [Mastodon API](crate::mastodon)'s tag timeline,
+//! which Mastodon, Pleroma, Akkoma and GoToSocial all serve. Each post keeps
+//! its own author, rather than the tag standing in as one.
+
+use url::Url;
+
+use crate::{mastodon::PAGE_SIZE, BadHandleError, BadUrl};
+
+/// A hashtag, on the instance to read it from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hashtag {
+  /// The tag, without its `#`.
+  pub name: String,
+  /// The domain of the instance whose tag timeline to read.
+  pub instance: String,
+}
+
+impl std::fmt::Display for Hashtag {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "#{}@{}", self.name, self.instance)
+  }
+}
+
+impl Hashtag {
+  /// Reads a tag from its `#tag@instance` form. The `#` can be left out.
+  pub fn parse(text: &str) -> Result<Self, BadHandleError> {
+    let (name, instance) = text
+      .trim_start_matches('#')
+      .split_once('@')
+      .ok_or(BadHandleError)?;
+
+    if name.is_empty() || instance.is_empty() || instance.contains('@') {
+      return Err(BadHandleError);
+    }
+
+    Ok(Self {
+      name: name.to_owned(),
+      instance: instance.to_owned(),
+    })
+  }
+
+  /// The instance's timeline for the tag, using the given scheme, from
+  /// before the post with id `max_id` if there is one.
+  pub fn timeline_url(
+    &self,
+    scheme: &str,
+    max_id: Option<&str>,
+  ) -> Result<String, BadUrl> {
+    let base = format!("{}://{}/api/v1/timelines/tag", scheme, self.instance);
+    let mut url = Url::parse(&base).map_err(|_| BadUrl {
+      url: base.clone(),
+      reason: "not an instance's domain",
+    })?;
+
+    if let Ok(mut segments) = url.path_segments_mut() {
+      segments.push(&self.name);
+    }
+
+    url
+      .query_pairs_mut()
+      .append_pair("limit", &PAGE_SIZE.to_string());
+
+    if let Some(id) = max_id {
+      url.query_pairs_mut().append_pair("max_id", id);
+    }
+
+    Ok(url.into())
+  }
+}
@@ -0,0 +1,19 @@
+//! Writing bytes as hex, and reading them back, as hashes, signatures and
+//! Nostr keys are passed around.
+
+/// Bytes as lowercase hex, two digits each.
+pub fn encode(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The bytes hex stands for, if it's only pairs of hex digits.
+pub fn decode(text: &str) -> Option<Vec<u8>> {
+  if !text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+    return None;
+  }
+
+  (0..text.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+    .collect()
+}
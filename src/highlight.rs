@@ -0,0 +1,57 @@
+//! Coloring code blocks for the terminal, with [syntect].
+//!
+//! A block's language comes from the `language-*` class servers give its
+//! `<code>` element, or failing that is guessed from its first line, as in
+//! a `#!` line. Blocks in languages syntect doesn't know are left plain.
+//!
+//! [syntect]: https://docs.rs/syntect
+
+use std::sync::OnceLock;
+
+use syntect::{
+  easy::HighlightLines,
+  highlighting::{Theme, ThemeSet},
+  parsing::{SyntaxReference, SyntaxSet},
+  util::{as_24_bit_terminal_escaped, LinesWithEndings},
+};
+
+const THEME: &str = "base16-ocean.dark";
+
+fn syntaxes() -> &'static SyntaxSet {
+  static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+
+  SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> &'static Theme {
+  static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+
+  &THEME_SET.get_or_init(ThemeSet::load_defaults).themes[THEME]
+}
+
+fn syntax(
+  code: &str,
+  language: Option<&str>,
+) -> Option<&'static SyntaxReference> {
+  let syntaxes = syntaxes();
+
+  language
+    .and_then(|language| syntaxes.find_syntax_by_token(language))
+    .or_else(|| syntaxes.find_syntax_by_first_line(code))
+}
+
+/// A code block's lines with terminal colors, or `None` if its language
+/// isn't one syntect knows.
+pub fn lines(code: &str, language: Option<&str>) -> Option<Vec<String>> {
+  let mut highlighter = HighlightLines::new(syntax(code, language)?, theme());
+  let mut lines = vec![];
+
+  for line in LinesWithEndings::from(code) {
+    let ranges = highlighter.highlight_line(line, syntaxes()).ok()?;
+    let colored = as_24_bit_terminal_escaped(&ranges, false);
+
+    lines.push(format!("{}\x1b[0m", colored.trim_end_matches('\n')));
+  }
+
+  Some(lines)
+}
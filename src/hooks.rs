@@ -0,0 +1,191 @@
+//! Running the user's own commands when something happens, for automation
+//! apread doesn't do itself.
+//!
+//! `apread watch` hands each post it hasn't seen before to two kinds of
+//! hook. The `on_new_post` command runs through the shell, so it can use
+//! pipes and quoting, and gets the post on stdin as a single line of JSON,
+//! in the same form as `--format json`:
+//!
+//! ```toml
+//! on_new_post = "jq -r .markdown | notify-send 'New post'"
+//! ```
+//!
+//! Webhooks get the same JSON POSTed to them, for feeding chat bridges or
+//! home automation. With a `secret`, each request is signed with it: the
+//! `X-Apread-Signature` header holds `sha256=` and the hex HMAC-SHA256 of
+//! the body, which the receiver can check to know the post came from here.
+//!
+//! ```toml
+//! [[webhooks]]
+//! url = "https://hooks.example/apread"
+//! secret = "correct horse battery staple"
+//! ```
+
+use std::{
+  io::Write,
+  process::{Command, Stdio},
+};
+
+use ring::hmac;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+  hex,
+  i18n::{self, FluentValue, Localized},
+  render,
+  timeline::Entry,
+  Client,
+};
+
+/// The header a signed webhook request carries its signature in.
+pub const SIGNATURE_HEADER: &str = "X-Apread-Signature";
+
+/// Raised when a hook couldn't be run, or exited unhappily.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct HookFailed {
+  /// The hook's command.
+  pub command: String,
+  /// What went wrong.
+  pub reason: String,
+}
+
+impl Localized for HookFailed {
+  fn id(&self) -> &'static str {
+    "error-hook-failed"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![
+      ("command", self.command.as_str().into()),
+      ("reason", self.reason.as_str().into()),
+    ]
+  }
+}
+
+/// Runs a hook's command for an entry, with the entry as JSON on its
+/// stdin, and waits for it to finish.
+pub fn run(command: &str, entry: &Entry) -> Result<(), HookFailed> {
+  let mut body = vec![];
+  // Writing to memory can't fail.
+  let _ = render::json_entry(&mut body, entry);
+
+  run_with(command, &body)
+}
+
+/// Runs a hook's command with `body` on its stdin, and waits for it to
+/// finish.
+pub fn run_with(command: &str, body: &[u8]) -> Result<(), HookFailed> {
+  let failed = |reason: String| HookFailed {
+    command: command.to_owned(),
+    reason,
+  };
+
+  let mut child = shell(command)
+    .stdin(Stdio::piped())
+    .spawn()
+    .map_err(|error| failed(error.to_string()))?;
+
+  if let Some(mut stdin) = child.stdin.take() {
+    // A hook which doesn't read its stdin closes it early, which is its
+    // business rather than a failure.
+    let _ = stdin.write_all(body).and_then(|_| stdin.flush());
+  }
+
+  let status = child.wait().map_err(|error| failed(error.to_string()))?;
+
+  match status.success() {
+    true => Ok(()),
+    false => Err(failed(format!("exited with {}", status))),
+  }
+}
+
+/// A url to POST each new post to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct Webhook {
+  /// Where to POST.
+  pub url: String,
+  /// What to sign each request with, if anything.
+  pub secret: Option<String>,
+}
+
+/// Raised when a webhook couldn't be reached, or didn't accept a post.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct WebhookFailed {
+  /// The webhook's url.
+  pub url: String,
+  /// What went wrong.
+  pub reason: String,
+}
+
+impl Localized for WebhookFailed {
+  fn id(&self) -> &'static str {
+    "error-webhook-failed"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![
+      ("url", self.url.as_str().into()),
+      ("reason", self.reason.as_str().into()),
+    ]
+  }
+}
+
+/// POSTs an entry to a webhook as JSON, signed if the webhook has a
+/// secret. Any 2xx answer counts as delivered.
+pub async fn deliver(
+  client: &Client,
+  webhook: &Webhook,
+  entry: &Entry,
+) -> Result<(), WebhookFailed> {
+  let mut body = vec![];
+  // Writing to memory can't fail.
+  let _ = render::json_entry(&mut body, entry);
+
+  deliver_with(client, webhook, body).await
+}
+
+/// POSTs a JSON body to a webhook, signed if the webhook has a secret.
+pub async fn deliver_with(
+  client: &Client,
+  webhook: &Webhook,
+  body: Vec<u8>,
+) -> Result<(), WebhookFailed> {
+  let failed = |reason: String| WebhookFailed {
+    url: webhook.url.clone(),
+    reason,
+  };
+  let headers = match &webhook.secret {
+    Some(secret) => vec![(SIGNATURE_HEADER, sign(secret, &body))],
+    None => vec![],
+  };
+
+  match client.post_json(&webhook.url, body, &headers).await {
+    Ok(code) if (200..300).contains(&code) => Ok(()),
+    Ok(code) => Err(failed(format!("answered {}", code))),
+    Err(error) => Err(failed(error.to_string())),
+  }
+}
+
+/// The signature for a webhook body: `sha256=` and its hex HMAC-SHA256.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+  let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+  let tag = hmac::sign(&key, body);
+  format!("sha256={}", hex::encode(tag.as_ref()))
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+  let mut shell = Command::new("cmd");
+  shell.args(["/C", command]);
+  shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+  let mut shell = Command::new("sh");
+  shell.args(["-c", command]);
+  shell
+}
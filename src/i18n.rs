@@ -0,0 +1,240 @@
+//! Writing apread's own messages in the reader's language.
+//!
+//! Messages are kept by id in a [Fluent] file for each language, in the
+//! `locales` directory and built into apread, so translating apread is
+//! writing one more file:
+//!
+//! ```ftl
+//! # locales/de.ftl
+//! init-wrote = { $path } geschrieben
+//! ```
+//!
+//! Messages are read with [fluent-bundle], so they're whole Fluent: a
+//! message can choose between forms with a selector, as in
+//! `{ $count -> [one] ... *[other] ... }`, and counts are matched to the
+//! language's own plural categories, however many it has. Counts are given
+//! twice, as `$count` to choose by and as `$number`, written for the
+//! reader's locale, to show. A message a language doesn't have is written
+//! in English instead.
+//!
+//! apread's own errors are kept here too, under `error-` ids, and what
+//! they write in English is their `Display`, so each is only written once.
+//!
+//! The language comes from the `lang` setting, or failing that from the
+//! usual `LANGUAGE`, `LC_ALL`, `LC_MESSAGES` and `LANG` environment
+//! variables. Only the language counts, so `de_AT.UTF-8` reads `de.ftl`.
+//!
+//! [Fluent]: https://projectfluent.org
+//! [fluent-bundle]: https://docs.rs/fluent-bundle
+
+use std::{
+  collections::BTreeSet,
+  fmt,
+  sync::{Arc, OnceLock},
+};
+
+pub use fluent_bundle::FluentValue;
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+/// The language written in when there's no other.
+pub const FALLBACK: &str = "en";
+
+/// The languages built in, each with its messages as Fluent.
+pub const LANGUAGES: &[(&str, &str)] = &[
+  ("de", include_str!("../locales/de.ftl")),
+  ("en", include_str!("../locales/en.ftl")),
+];
+
+/// The messages of a language, falling back on English.
+#[derive(Clone)]
+pub struct Messages {
+  language: String,
+  /// The language's messages, then English's, looked in in that order.
+  bundles: Arc<Vec<FluentBundle<FluentResource>>>,
+}
+
+/// Something with a message of its own, as an error: the id it's kept
+/// under, and what to fill in its variables with.
+pub trait Localized {
+  /// The id of its message.
+  fn id(&self) -> &'static str;
+
+  /// Its message's variables, by name.
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![]
+  }
+}
+
+impl Default for Messages {
+  fn default() -> Self {
+    Self::new(FALLBACK)
+  }
+}
+
+impl fmt::Debug for Messages {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Messages")
+      .field("language", &self.language)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Messages {
+  /// The messages of a language, by its code, as in `de`. Languages apread
+  /// hasn't been translated into are written in English.
+  pub fn new(language: &str) -> Self {
+    let language = language.to_lowercase();
+
+    match LANGUAGES.iter().find(|(code, _)| *code == language) {
+      Some((code, ftl)) => Self::from_ftl(code, ftl),
+      None => Self::from_ftl(FALLBACK, builtin(FALLBACK)),
+    }
+  }
+
+  /// The messages in a Fluent file, for a language by its code, falling
+  /// back on the English built in. Whatever in the file isn't Fluent is
+  /// left out, and the rest read.
+  pub fn from_ftl(language: &str, ftl: &str) -> Self {
+    let mut bundles = vec![bundle(language, ftl)];
+
+    if language != FALLBACK {
+      bundles.push(bundle(FALLBACK, builtin(FALLBACK)));
+    }
+
+    Self {
+      language: language.to_lowercase(),
+      bundles: Arc::new(bundles),
+    }
+  }
+
+  /// The messages of the language asked for, or the one the environment
+  /// gives.
+  pub fn resolve(asked: Option<&str>) -> Self {
+    Self::new(&language(asked))
+  }
+
+  /// The language the messages are in.
+  pub fn language(&self) -> &str {
+    &self.language
+  }
+
+  /// The message with an id, or the id itself if there's no such message.
+  pub fn get(&self, id: &str) -> String {
+    self.with(id, &[])
+  }
+
+  /// The message with an id, with its variables filled in from `args`.
+  /// A variable which isn't given is written as itself, as `{$name}`.
+  pub fn with(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent = FluentArgs::new();
+
+    for (name, value) in args {
+      fluent.set(*name, value.clone());
+    }
+
+    for bundle in self.bundles.iter() {
+      let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) else {
+        continue;
+      };
+      let mut errors = vec![];
+
+      return bundle
+        .format_pattern(pattern, Some(&fluent), &mut errors)
+        .into_owned();
+    }
+
+    id.to_owned()
+  }
+
+  /// What something with a message of its own says, in this language.
+  pub fn localize(&self, what: &dyn Localized) -> String {
+    self.with(what.id(), &what.args())
+  }
+}
+
+/// What something with a message of its own says in English, as its
+/// `Display` writes it.
+pub fn english(what: &dyn Localized) -> String {
+  static ENGLISH: OnceLock<Messages> = OnceLock::new();
+
+  ENGLISH.get_or_init(Messages::default).localize(what)
+}
+
+/// The ids of the messages in a Fluent file, leaving out terms.
+pub fn ids(ftl: &str) -> BTreeSet<String> {
+  let resource = resource(ftl);
+
+  resource
+    .entries()
+    .filter_map(|entry| match entry {
+      fluent_syntax::ast::Entry::Message(message) => {
+        Some(message.id.name.to_owned())
+      }
+      _ => None,
+    })
+    .collect()
+}
+
+/// The language asked for, or else the one the environment gives, as a
+/// lowercase code like `de`.
+pub fn language(asked: Option<&str>) -> String {
+  let from_env = || {
+    let language = std::env::var("LANGUAGE").ok().and_then(|languages| {
+      // LANGUAGE lists languages in order of preference.
+      languages
+        .split(':')
+        .find(|language| !language.is_empty())
+        .map(str::to_owned)
+    });
+
+    language.or_else(|| {
+      ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+    })
+  };
+  let name = asked
+    .map(str::to_owned)
+    .or_else(from_env)
+    .unwrap_or_default();
+  let code = name
+    .split(['.', '@', '_', '-'])
+    .next()
+    .unwrap_or_default()
+    .to_lowercase();
+
+  match code.as_str() {
+    "" | "c" | "posix" => FALLBACK.to_owned(),
+    _ => code,
+  }
+}
+
+/// The Fluent built in for a language, which has to be one of
+/// [`LANGUAGES`].
+fn builtin(language: &str) -> &'static str {
+  LANGUAGES
+    .iter()
+    .find(|(code, _)| *code == language)
+    .map(|(_, ftl)| *ftl)
+    .unwrap_or_default()
+}
+
+/// A Fluent file read, as much of it as is Fluent.
+fn resource(ftl: &str) -> FluentResource {
+  FluentResource::try_new(ftl.to_owned()).unwrap_or_else(|(read, _)| read)
+}
+
+fn bundle(language: &str, ftl: &str) -> FluentBundle<FluentResource> {
+  let id: LanguageIdentifier = language.parse().unwrap_or_default();
+  let mut bundle = FluentBundle::new_concurrent(vec![id]);
+
+  // Fluent marks off each variable as its own run of text, for languages
+  // written right to left, but a terminal shows the marks as they are.
+  bundle.set_use_isolating(false);
+  // A message given twice is kept as it was first given.
+  let _ = bundle.add_resource(resource(ftl));
+
+  bundle
+}
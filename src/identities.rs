@@ -0,0 +1,49 @@
+//! Remembering which actor each handle turned out to be, and back again.
+//!
+//! WebFinger turns a handle into an actor url, but only while the handle's
+//! domain still answers for it. Each account resolved is remembered both
+//! ways, by handle and by actor url, so an account can be read by either
+//! form, and one whose instance has since moved or changed address is
+//! still found at the actor it had last time. Unlike the discovery cache,
+//! identities don't expire: they're only used when nothing fresher is.
+
+use std::sync::Arc;
+
+use crate::{storage::Store, ApreadErrors, Handle};
+
+/// Handles and the actor urls they resolved to, kept in a [`Store`].
+#[derive(Clone, Debug)]
+pub struct Identities {
+  store: Arc<dyn Store>,
+}
+
+impl Identities {
+  /// Identities kept in the given store.
+  pub fn new(store: Arc<dyn Store>) -> Self {
+    Self { store }
+  }
+
+  /// The actor url a handle last resolved to.
+  pub fn actor(&self, handle: &Handle) -> Option<String> {
+    Some(self.store.get(&format!("handle:{}", handle))?.body)
+  }
+
+  /// The handle of the actor at a url, if it's been resolved before.
+  pub fn handle(&self, url: &str) -> Option<Handle> {
+    let stored = self.store.get(&format!("actor:{}", url))?;
+
+    Handle::parse_string(&stored.body).ok()
+  }
+
+  /// Remembers that a handle is the actor at a url, both ways round.
+  pub fn remember(
+    &self,
+    handle: &Handle,
+    url: &str,
+  ) -> Result<(), ApreadErrors> {
+    self.store.put(&format!("handle:{}", handle), url)?;
+    self
+      .store
+      .put(&format!("actor:{}", url), &handle.to_string())
+  }
+}
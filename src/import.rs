@@ -0,0 +1,188 @@
+//! Importing a Mastodon account export into the archive, to browse and
+//! search one's own posts offline.
+//!
+//! Mastodon exports an account as a zip file holding an `outbox.json` of
+//! everything the account posted, an `actor.json` describing the account,
+//! and a `media_attachments` directory of everything it uploaded. The
+//! account's own posts go into the archive under its handle, and the media
+//! is copied into the archive's `media` directory, keeping the paths the
+//! posts link to it by. An unzipped export, or one `apread export
+//! --ap-archive` wrote, imports the same way.
+
+use std::{
+  fs::{self, File},
+  io::{self, Read},
+  path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use thiserror::Error;
+use zip::ZipArchive;
+
+use crate::{
+  activity::{Item, OutboxIndex},
+  archive::Archive,
+  i18n::{self, FluentValue, Localized},
+  ApUrl, ApreadErrors,
+};
+
+/// Where media is kept in an export.
+const MEDIA: &str = "media_attachments";
+
+/// Raised when a file to import isn't an account export.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NotAnExport(pub PathBuf);
+
+impl Localized for NotAnExport {
+  fn id(&self) -> &'static str {
+    "error-not-an-export"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("path", self.0.display().to_string().into())]
+  }
+}
+
+/// What an import brought in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Imported {
+  /// How many posts were archived.
+  pub posts: usize,
+  /// How many media files were copied.
+  pub media: usize,
+}
+
+/// An account export, zipped or not.
+enum Export {
+  Zip(ZipArchive<File>),
+  Dir(PathBuf),
+}
+
+impl Export {
+  fn open(path: &Path) -> Result<Self, ApreadErrors> {
+    match path.is_dir() {
+      true => Ok(Self::Dir(path.to_owned())),
+      false => Ok(Self::Zip(ZipArchive::new(File::open(path)?)?)),
+    }
+  }
+
+  /// One of the export's files, as text, if it has it.
+  fn read(&mut self, name: &str) -> Result<Option<String>, ApreadErrors> {
+    let mut text = String::new();
+
+    match self {
+      Self::Zip(zip) => match zip.by_name(name) {
+        Ok(mut file) => {
+          file.read_to_string(&mut text)?;
+        }
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(error) => return Err(error.into()),
+      },
+      Self::Dir(dir) => match fs::read_to_string(dir.join(name)) {
+        Ok(read) => text = read,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+          return Ok(None)
+        }
+        Err(error) => return Err(error.into()),
+      },
+    };
+
+    Ok(Some(text))
+  }
+
+  /// Copies the export's media into `to`, giving how many files there were.
+  fn copy_media(&mut self, to: &Path) -> Result<usize, ApreadErrors> {
+    match self {
+      Self::Zip(zip) => {
+        let mut copied = 0;
+
+        for index in 0..zip.len() {
+          let mut file = zip.by_index(index)?;
+          // Names which would climb out of the directory are left behind.
+          let name = match file.enclosed_name() {
+            Some(name) if name.starts_with(MEDIA) && file.is_file() => name,
+            _ => continue,
+          };
+          let path = to.join(name);
+
+          if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+          }
+
+          io::copy(&mut file, &mut File::create(path)?)?;
+          copied += 1;
+        }
+
+        Ok(copied)
+      }
+      Self::Dir(dir) => copy_dir(&dir.join(MEDIA), &to.join(MEDIA)),
+    }
+  }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<usize, ApreadErrors> {
+  if !from.is_dir() {
+    return Ok(0);
+  }
+
+  let mut copied = 0;
+  fs::create_dir_all(to)?;
+
+  for entry in fs::read_dir(from)? {
+    let entry = entry?;
+    let path = entry.path();
+
+    if entry.file_type()?.is_dir() {
+      copied += copy_dir(&path, &to.join(entry.file_name()))?;
+    } else {
+      fs::copy(&path, to.join(entry.file_name()))?;
+      copied += 1;
+    }
+  }
+
+  Ok(copied)
+}
+
+/// Imports the account export at `path`, a zip file or a directory, into
+/// the archive, copying its media into `media`.
+pub fn mastodon(
+  path: &Path,
+  archive: &Archive,
+  media: &Path,
+) -> Result<Imported, ApreadErrors> {
+  let mut export = Export::open(path)?;
+  let outbox = export
+    .read("outbox.json")?
+    .ok_or_else(|| NotAnExport(path.to_owned()))?;
+  let outbox: OutboxIndex = serde_json::from_str(&outbox)?;
+  let author = match export.read("actor.json")? {
+    Some(actor) => handle(&serde_json::from_str(&actor)?),
+    None => None,
+  };
+  let mut imported = Imported::default();
+
+  for item in &outbox.ordered_items {
+    if let Item::Post { object, .. } = item {
+      if archive.import(object, author.as_deref()).is_ok() {
+        imported.posts += 1;
+      }
+    }
+  }
+
+  imported.media = export.copy_media(media)?;
+
+  Ok(imported)
+}
+
+/// The handle of an exported actor, whose own links are relative to the
+/// export but whose id is where it really lives.
+fn handle(actor: &Value) -> Option<String> {
+  let id = ApUrl::parse(actor["id"].as_str()?).ok()?;
+
+  Some(format!(
+    "{}@{}",
+    actor["preferredUsername"].as_str()?,
+    id.host()
+  ))
+}
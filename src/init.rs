@@ -0,0 +1,331 @@
+//! Setting apread up for the first time.
+//!
+//! `apread init` asks a new reader which accounts to follow and how they'd
+//! like timelines written, then writes the config file and subscriptions
+//! file to say so, each as it'd be written by hand. Every question has a
+//! default, shown in brackets, which a blank answer takes.
+
+use std::{
+  fs,
+  io::{self, BufRead, Write},
+  path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, FluentValue, Localized, Messages},
+  links,
+  settings::Format,
+  timeline::Source,
+  ApreadErrors,
+};
+
+/// Raised when `apread init` would write over a config file that's already
+/// there.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct AlreadySetUp(pub PathBuf);
+
+impl Localized for AlreadySetUp {
+  fn id(&self) -> &'static str {
+    "error-already-set-up"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("path", self.0.display().to_string().into())]
+  }
+}
+
+/// Raised when there's no platform config directory to write the config
+/// file in, and none was given.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoConfigDir;
+
+impl Localized for NoConfigDir {
+  fn id(&self) -> &'static str {
+    "error-no-config-dir"
+  }
+}
+
+/// What a new reader asked for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Setup {
+  /// The accounts to follow, as `id@domain` or anything else
+  /// [`Source::parse`] reads.
+  pub handles: Vec<String>,
+  /// How to write timelines out.
+  pub format: Format,
+  /// How wide to wrap posts.
+  pub width: usize,
+  /// Where in a post to write its links.
+  pub links: links::Style,
+  /// Whether to write timelines for a screen reader.
+  pub screen_reader: bool,
+}
+
+impl Default for Setup {
+  fn default() -> Self {
+    Self {
+      handles: vec![],
+      format: Format::default(),
+      width: 80,
+      links: links::Style::default(),
+      screen_reader: false,
+    }
+  }
+}
+
+impl Setup {
+  /// Asks each question on `output`, in the language of `messages`,
+  /// reading the answers from `input`. An answer which doesn't make sense
+  /// is asked for again.
+  pub fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    messages: &Messages,
+  ) -> io::Result<Self> {
+    let defaults = Self::default();
+
+    let handles = loop {
+      let answer = question(
+        input,
+        output,
+        &messages.get("init-handles"),
+        &messages.get("init-no-handles"),
+      )?;
+      let handles: Vec<String> =
+        answer.split_whitespace().map(str::to_owned).collect();
+
+      match handles.iter().find(|handle| Source::parse(handle).is_err()) {
+        Some(bad) => writeln!(
+          output,
+          "{}",
+          messages.with("init-bad-handle", &[("handle", bad.into())])
+        )?,
+        None => break handles,
+      }
+    };
+    let format = choice(
+      input,
+      output,
+      messages,
+      &messages.get("init-format"),
+      defaults.format,
+    )?;
+    let width = loop {
+      let answer = question(
+        input,
+        output,
+        &messages.get("init-width"),
+        &defaults.width.to_string(),
+      )?;
+
+      if answer.is_empty() {
+        break defaults.width;
+      }
+
+      match answer.parse() {
+        Ok(width) if width > 0 => break width,
+        _ => writeln!(output, "{}", messages.get("init-bad-width"))?,
+      }
+    };
+    let links = choice(
+      input,
+      output,
+      messages,
+      &messages.get("init-links"),
+      defaults.links,
+    )?;
+    let screen_reader = yes(
+      input,
+      output,
+      messages,
+      &messages.get("init-screen-reader"),
+      false,
+    )?;
+
+    Ok(Self {
+      handles,
+      format,
+      width,
+      links,
+      screen_reader,
+    })
+  }
+
+  /// The config file, as TOML.
+  pub fn config(&self) -> String {
+    format!(
+      "# Written by apread init, to change as you like.\n\
+       format = {}\nwidth = {}\nlinks = {}\nscreen_reader = {}\n",
+      quoted(&name(&self.format)),
+      self.width,
+      quoted(&name(&self.links)),
+      self.screen_reader
+    )
+  }
+
+  /// The accounts to follow as `[[account]]` tables of a subscriptions
+  /// file.
+  pub fn subscriptions(&self) -> String {
+    self
+      .handles
+      .iter()
+      .map(|handle| format!("[[account]]\nhandle = {}\n", quoted(handle)))
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+
+  /// Writes the config file at `config`, unless there's one already and
+  /// `force` isn't given, and adds the accounts to follow to the end of
+  /// the subscriptions file at `subscriptions`.
+  pub fn write(
+    &self,
+    config: &Path,
+    subscriptions: &Path,
+    force: bool,
+  ) -> Result<(), ApreadErrors> {
+    ready(config, force)?;
+    create_parent(config)?;
+    fs::write(config, self.config())?;
+
+    if self.handles.is_empty() {
+      return Ok(());
+    }
+
+    let existing = match fs::read_to_string(subscriptions) {
+      Ok(existing) => existing,
+      Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+      Err(error) => return Err(error.into()),
+    };
+    let separator = match existing.trim_end().is_empty() {
+      true => "",
+      false => "\n",
+    };
+
+    create_parent(subscriptions)?;
+    fs::write(
+      subscriptions,
+      format!(
+        "{}{}{}",
+        existing.trim_end(),
+        separator,
+        self.subscriptions()
+      ),
+    )?;
+
+    Ok(())
+  }
+}
+
+/// Whether a config file can be written at `config`: there's none there
+/// yet, or `force` says to replace it.
+pub fn ready(config: &Path, force: bool) -> Result<(), AlreadySetUp> {
+  match config.exists() && !force {
+    true => Err(AlreadySetUp(config.to_owned())),
+    false => Ok(()),
+  }
+}
+
+/// Asks a question, giving the answer, or `default` for a blank one.
+pub fn question(
+  input: &mut impl BufRead,
+  output: &mut impl Write,
+  text: &str,
+  default: &str,
+) -> io::Result<String> {
+  write!(output, "{} [{}]: ", text, default)?;
+  output.flush()?;
+
+  let mut answer = String::new();
+  input.read_line(&mut answer)?;
+
+  Ok(answer.trim().to_owned())
+}
+
+/// Asks a yes-or-no question. Yes and no are understood in English as
+/// well as in the language of `messages`, whole or by their first letter.
+pub fn yes(
+  input: &mut impl BufRead,
+  output: &mut impl Write,
+  messages: &Messages,
+  text: &str,
+  default: bool,
+) -> io::Result<bool> {
+  let (yes, no) = (messages.get("yes"), messages.get("no"));
+  let initial = |word: &str| word.chars().take(1).collect::<String>();
+  let hint = match default {
+    true => format!("{}/{}", initial(&yes).to_uppercase(), initial(&no)),
+    false => format!("{}/{}", initial(&yes), initial(&no).to_uppercase()),
+  };
+  let means = |answer: &str, id: &str, word: &str| {
+    [word, id].iter().any(|word| {
+      let word = word.to_lowercase();
+
+      answer == word || answer == initial(&word)
+    })
+  };
+
+  loop {
+    let answer = question(input, output, text, &hint)?.to_lowercase();
+
+    match answer.as_str() {
+      "" => return Ok(default),
+      answer if means(answer, "yes", &yes) => return Ok(true),
+      answer if means(answer, "no", &no) => return Ok(false),
+      _ => writeln!(output, "{}", messages.get("answer-yes-or-no"))?,
+    }
+  }
+}
+
+/// Asks which of a flag's values to take.
+fn choice<T: ValueEnum>(
+  input: &mut impl BufRead,
+  output: &mut impl Write,
+  messages: &Messages,
+  text: &str,
+  default: T,
+) -> io::Result<T> {
+  let names: Vec<String> = T::value_variants().iter().map(name).collect();
+  let text = format!("{} ({})", text, names.join(", "));
+
+  loop {
+    let answer = question(input, output, &text, &name(&default))?;
+
+    if answer.is_empty() {
+      return Ok(default);
+    }
+
+    match T::from_str(&answer, true) {
+      Ok(value) => return Ok(value),
+      Err(_) => writeln!(
+        output,
+        "{}",
+        messages.with("pick-one-of", &[("choices", names.join(", ").into())])
+      )?,
+    }
+  }
+}
+
+/// A flag's value as it's written.
+fn name<T: ValueEnum>(value: &T) -> String {
+  value
+    .to_possible_value()
+    .map(|value| value.get_name().to_owned())
+    .unwrap_or_default()
+}
+
+/// Text as a TOML string. TOML's basic strings escape as JSON's do.
+fn quoted(text: &str) -> String {
+  serde_json::to_string(text).unwrap_or_default()
+}
+
+fn create_parent(path: &Path) -> io::Result<()> {
+  match path.parent() {
+    Some(dir) => fs::create_dir_all(dir),
+    None => Ok(()),
+  }
+}
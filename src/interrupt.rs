@@ -0,0 +1,72 @@
+//! Stopping a long crawl early, on Ctrl-C, without losing what it read.
+//!
+//! The first Ctrl-C asks for the crawl to stop: whatever's fetching
+//! finishes the page it's on, then everything read so far is written out
+//! as usual, and apread exits with [`EXIT_CODE`] so scripts can tell the
+//! output is partial. A second Ctrl-C gives up straight away.
+
+use std::{
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
+
+/// What apread exits with after being interrupted, as shells expect of a
+/// program stopped by SIGINT.
+pub const EXIT_CODE: i32 = 130;
+
+/// A flag for asking crawls to stop, shared between the signal handler and
+/// the [`Client`](crate::Client) doing the crawling.
+#[derive(Clone, Debug, Default)]
+pub struct Interrupt(Arc<AtomicBool>);
+
+impl Interrupt {
+  /// Asks crawls to stop.
+  pub fn trigger(&self) {
+    self.0.store(true, Ordering::SeqCst);
+  }
+
+  /// Whether crawls have been asked to stop.
+  pub fn is_triggered(&self) -> bool {
+    self.0.load(Ordering::SeqCst)
+  }
+
+  /// Waits out a duration, or until crawls are asked to stop if that comes
+  /// first.
+  pub async fn sleep(&self, duration: Duration) {
+    let step = Duration::from_millis(100);
+    let mut left = duration;
+
+    while !left.is_zero() && !self.is_triggered() {
+      let nap = left.min(step);
+
+      tokio::time::sleep(nap).await;
+      left -= nap;
+    }
+  }
+
+  /// Waits until crawls are asked to stop.
+  pub async fn triggered(&self) {
+    while !self.is_triggered() {
+      tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+  }
+
+  /// Triggers on Ctrl-C, in the background, and exits on a second one.
+  pub fn on_ctrl_c(&self) {
+    let interrupt = self.clone();
+
+    tokio::spawn(async move {
+      while tokio::signal::ctrl_c().await.is_ok() {
+        if interrupt.is_triggered() {
+          std::process::exit(EXIT_CODE);
+        }
+
+        eprintln!("Stopping after this page (Ctrl-C again to quit now)");
+        interrupt.trigger();
+      }
+    });
+  }
+}
@@ -0,0 +1,143 @@
+//! Keeping access tokens in the system's keyring, rather than in a file.
+//!
+//! Tokens are kept under the service `apread`, by account, through the
+//! keyring's own command line tool: `security` for the macOS keychain, and
+//! `secret-tool` for the Secret Service GNOME Keyring and KWallet provide
+//! elsewhere. Where there's neither, or the keyring is locked and stays
+//! that way, logins keep their tokens in the data directory instead, in a
+//! file only its owner can read.
+
+#[cfg(unix)]
+use std::io::Write;
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+
+/// The service tokens are kept under.
+pub const SERVICE: &str = "apread";
+
+/// Keeps an account's token in the keyring, replacing any kept before.
+/// Whether it could be.
+#[cfg(target_os = "macos")]
+pub fn store(account: &str, token: &str) -> bool {
+  // `security` only takes the password as an argument, so the command goes
+  // in on stdin to its interactive mode, keeping the token out of the
+  // process list. -U updates the one there is rather than failing on it.
+  let command = format!(
+    "add-generic-password -U -s {} -a {} -w {}\n",
+    quoted(SERVICE),
+    quoted(account),
+    quoted(token)
+  );
+
+  write_to(Command::new("security").arg("-i"), &command)
+}
+
+/// A word quoted for `security -i`, which splits its commands as a shell
+/// does.
+#[cfg(target_os = "macos")]
+fn quoted(word: &str) -> String {
+  format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Keeps an account's token in the keyring, replacing any kept before.
+/// Whether it could be.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn store(account: &str, token: &str) -> bool {
+  let label = format!("{}: {}", SERVICE, account);
+
+  write_to(
+    Command::new("secret-tool")
+      .args(["store", "--label", &label, "service", SERVICE])
+      .args(["account", account]),
+    token,
+  )
+}
+
+/// Runs a command with `input` on its stdin, so it's never in the process
+/// list. Whether it succeeded.
+#[cfg(unix)]
+fn write_to(command: &mut Command, input: &str) -> bool {
+  let child = command
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn();
+  let Ok(mut child) = child else {
+    return false;
+  };
+  let written = child
+    .stdin
+    .take()
+    .is_some_and(|mut stdin| stdin.write_all(input.as_bytes()).is_ok());
+
+  child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// Keeps an account's token in the keyring, which there isn't one of here.
+#[cfg(not(unix))]
+pub fn store(_: &str, _: &str) -> bool {
+  false
+}
+
+/// The token kept in the keyring for an account, if there is one.
+#[cfg(unix)]
+pub fn lookup(account: &str) -> Option<String> {
+  let output = lookup_command(account)
+    .stderr(Stdio::null())
+    .output()
+    .ok()?;
+  let token = String::from_utf8(output.stdout).ok()?;
+  let token = token.trim();
+
+  (output.status.success() && !token.is_empty()).then(|| token.to_owned())
+}
+
+/// The token kept in the keyring for an account, which there isn't one of
+/// here.
+#[cfg(not(unix))]
+pub fn lookup(_: &str) -> Option<String> {
+  None
+}
+
+/// Forgets the token kept in the keyring for an account, if there is one.
+#[cfg(unix)]
+pub fn delete(account: &str) {
+  // Nothing being there to forget is as good as forgetting it.
+  let _ = delete_command(account)
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .status();
+}
+
+/// Forgets the token kept in the keyring for an account, which there isn't
+/// one of here.
+#[cfg(not(unix))]
+pub fn delete(_: &str) {}
+
+#[cfg(target_os = "macos")]
+fn lookup_command(account: &str) -> Command {
+  let mut command = Command::new("security");
+  command.args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"]);
+  command
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn lookup_command(account: &str) -> Command {
+  let mut command = Command::new("secret-tool");
+  command.args(["lookup", "service", SERVICE, "account", account]);
+  command
+}
+
+#[cfg(target_os = "macos")]
+fn delete_command(account: &str) -> Command {
+  let mut command = Command::new("security");
+  command.args(["delete-generic-password", "-s", SERVICE, "-a", account]);
+  command
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn delete_command(account: &str) -> Command {
+  let mut command = Command::new("secret-tool");
+  command.args(["clear", "service", SERVICE, "account", account]);
+  command
+}
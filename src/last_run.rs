@@ -0,0 +1,143 @@
+//! Remembering which posts the last run showed, so later runs can pick
+//! them out by number.
+//!
+//! Posts are numbered as they're printed, `[1]` first, and kept in the
+//! cache directory, so `apread open 3` or `apread bookmark 3` act on the
+//! third post shown. Each run that prints posts replaces the last.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{timeline::Entry, ApreadErrors, NoSuchPost};
+
+/// The name of the file the last run is kept in, in the cache directory.
+pub const FILE: &str = "last-run.json";
+
+/// The posts a run showed, in the order they were numbered.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LastRun {
+  /// Each post shown, the first numbered 1.
+  pub posts: Vec<Shown>,
+}
+
+/// A post as it was shown: whichever of its id and url it has, and what
+/// was shown of it. Runs kept before the rest was have only the id and
+/// url.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Shown {
+  /// The post's ActivityPub id, which it can be fetched by.
+  pub id: Option<String>,
+  /// The address of the post's web page.
+  pub url: Option<String>,
+  /// Who posted or boosted it, as their handle or feed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub author: Option<String>,
+  /// The name they go by in subscriptions, if they've been given one.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub nickname: Option<String>,
+  /// Whether the author boosted the post, rather than wrote it.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub boosted: bool,
+  /// When it was published.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub published: Option<String>,
+  /// When it was last edited, if it has been.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub updated: Option<String>,
+  /// Its content warning, if it has one.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub summary: Option<String>,
+  /// Its content, as html.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content: Option<String>,
+  /// The files attached to it.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub attachments: Vec<Attached>,
+}
+
+/// A file attached to a post as it was shown.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Attached {
+  /// Where the file is.
+  pub url: Option<String>,
+  /// The file's media type, as in `image/png`.
+  pub media_type: Option<String>,
+  /// The file's alt text, if it was given any.
+  pub alt: Option<String>,
+}
+
+impl Shown {
+  /// The address to open the post at: its web page if it has one.
+  pub fn page(&self) -> Option<&str> {
+    self.url.as_deref().or(self.id.as_deref())
+  }
+
+  /// The address to fetch the post from: its id if it has one.
+  pub fn source(&self) -> Option<&str> {
+    self.id.as_deref().or(self.url.as_deref())
+  }
+}
+
+impl LastRun {
+  /// A run which showed these entries, in this order.
+  pub fn new(entries: &[Entry]) -> Self {
+    let posts = entries
+      .iter()
+      .map(|entry| Shown {
+        id: entry.post.id.clone(),
+        url: entry.post.url.clone(),
+        author: Some(entry.author.source.to_string()),
+        nickname: entry.author.nickname.clone(),
+        boosted: entry.boosted,
+        published: Some(entry.published.clone()),
+        updated: entry.post.updated.clone(),
+        summary: entry.post.summary.clone().filter(|cw| !cw.is_empty()),
+        content: Some(entry.post.content.clone()),
+        attachments: entry
+          .post
+          .attachment
+          .iter()
+          .map(|attachment| Attached {
+            url: attachment.url.clone(),
+            media_type: attachment.media_type.clone(),
+            alt: attachment.alt_text().map(str::to_owned),
+          })
+          .collect(),
+      })
+      .collect();
+
+    Self { posts }
+  }
+
+  /// The run kept at a path, or one which showed nothing if there isn't
+  /// one there yet.
+  pub fn load(path: &Path) -> Result<Self, ApreadErrors> {
+    match fs::read_to_string(path) {
+      Ok(json) => Ok(serde_json::from_str(&json)?),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => {
+        Ok(Self::default())
+      }
+      Err(error) => Err(error.into()),
+    }
+  }
+
+  /// Keeps the run at a path, replacing whichever was kept there.
+  pub fn save(&self, path: &Path) -> Result<(), ApreadErrors> {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, serde_json::to_string(self)?)?;
+
+    Ok(())
+  }
+
+  /// The post shown as `[number]`.
+  pub fn post(&self, number: usize) -> Result<&Shown, NoSuchPost> {
+    number
+      .checked_sub(1)
+      .and_then(|index| self.posts.get(index))
+      .ok_or(NoSuchPost(number))
+  }
+}
@@ -0,0 +1,69 @@
+//! How long posts are, and how long they take to read.
+//!
+//! A post's length is the words in its text, leaving out its html and its
+//! attachments. Timelines mark the posts which take more than a minute to
+//! read with how many minutes they take at [`WORDS_PER_MINUTE`], and those
+//! of [`LONG`] words or more with a `[long]` badge, so long-form writing
+//! stands out among the microblogging. `apread read --min-length` and
+//! `--max-length` pick out one or the other.
+
+use crate::{activity::Post, render};
+
+/// How many words a minute a post is read at.
+pub const WORDS_PER_MINUTE: usize = 230;
+
+/// How many words make a post long-form.
+pub const LONG: usize = 400;
+
+/// How long a post is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Length {
+  /// The words in its text.
+  pub words: usize,
+}
+
+impl Length {
+  /// The length of some html's text.
+  pub fn of_html(html: &str) -> Self {
+    Self {
+      words: render::plain_text(html)
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count(),
+    }
+  }
+
+  /// The length of a post's text.
+  pub fn of(post: &Post) -> Self {
+    Self::of_html(&post.content)
+  }
+
+  /// How many minutes it takes to read, rounded up.
+  pub fn minutes(&self) -> usize {
+    self.words.div_ceil(WORDS_PER_MINUTE)
+  }
+
+  /// Whether it's long-form.
+  pub fn is_long(&self) -> bool {
+    self.words >= LONG
+  }
+
+  /// What to mark a post of this length with, as in `[long] 4 min read`,
+  /// if it takes more than a minute to read.
+  pub fn badge(&self) -> Option<String> {
+    let read = format!("{} min read", self.minutes());
+
+    match self.minutes() {
+      0 | 1 => None,
+      _ if self.is_long() => Some(format!("[long] {}", read)),
+      _ => Some(read),
+    }
+  }
+
+  /// Whether it's within the given bounds, in words, either of which can be
+  /// left out.
+  pub fn within(&self, min: Option<usize>, max: Option<usize>) -> bool {
+    min.is_none_or(|min| self.words >= min)
+      && max.is_none_or(|max| self.words <= max)
+  }
+}
@@ -0,0 +1,324 @@
+//! Apread is a command-line feed reader for ActivityPub urls
+#![deny(missing_docs)]
+
+use i18n::{FluentValue, Localized};
+use thiserror::Error;
+
+pub mod activity;
+pub mod activity_log;
+pub mod alerts;
+pub mod ap_url;
+pub mod archive;
+pub mod article;
+pub mod audit;
+pub mod bidi;
+pub mod blurhash;
+pub mod breaker;
+pub mod bridge;
+#[cfg(feature = "bsky")]
+pub mod bsky;
+pub mod cache;
+pub mod capture;
+pub mod client;
+pub mod clipboard;
+pub mod collection;
+pub mod compose;
+pub mod diagnostics;
+pub mod doctor;
+pub mod doh;
+pub mod export;
+pub mod feed;
+pub mod fixtures;
+pub mod graph;
+pub mod handle;
+pub mod hashtag;
+pub mod hex;
+#[cfg(feature = "highlight")]
+pub mod highlight;
+pub mod hooks;
+pub mod i18n;
+pub mod identities;
+pub mod import;
+pub mod init;
+pub mod interrupt;
+pub mod keyring;
+pub mod last_run;
+pub mod length;
+pub mod links;
+pub mod lint;
+pub mod locale;
+pub mod login;
+pub mod mastodon;
+pub mod math;
+#[cfg(feature = "matrix")]
+pub mod matrix;
+pub mod media;
+pub mod mention;
+pub mod metrics;
+#[cfg(feature = "nostr")]
+pub mod nostr;
+pub mod notifications;
+pub mod player;
+pub mod plugin;
+pub mod profile;
+pub mod render;
+pub mod retention;
+pub mod search;
+pub mod settings;
+pub mod similar;
+pub mod sink;
+pub mod spam;
+pub mod stats;
+pub mod status;
+pub mod storage;
+pub mod streaming;
+pub mod subscriptions;
+pub mod suggest;
+pub mod systemd;
+pub mod throttle;
+pub mod timeline;
+#[cfg(feature = "topics")]
+pub mod topics;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod watch;
+pub mod webfinger;
+
+pub use activity::NoLastPage;
+pub use ap_url::{ApUrl, BadUrl};
+pub use archive::{
+  NoDataDir, NoPostId, NoSuchDraft, NotAnAccount, NotArchived,
+};
+pub use breaker::InstanceDown;
+pub use capture::Captured;
+pub use client::Client;
+pub use clipboard::NoClipboard;
+pub use compose::{EmptyDraft, NotFound};
+pub use fixtures::NotRecorded;
+pub use handle::{BadHandleError, Handle, NeedsTor};
+pub use import::NotAnExport;
+pub use init::{AlreadySetUp, NoConfigDir};
+pub use login::{NoSuchAccount, NoSuchList, NoToken, NotLoggedIn, ReadOnly};
+pub use player::NothingToPlay;
+pub use retention::NoRetention;
+pub use search::NoSuchSearch;
+pub use settings::WrongFormat;
+pub use systemd::NoUnitDir;
+#[cfg(feature = "wasm")]
+pub use wasm::BadModule;
+pub use webfinger::NoFeedLink;
+
+/// Raised when there's no handle to read and no subscriptions either.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NothingToRead;
+
+impl Localized for NothingToRead {
+  fn id(&self) -> &'static str {
+    "error-nothing-to-read"
+  }
+}
+
+/// Raised when asked to open a post that wasn't shown, or that has no url.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoSuchPost(pub usize);
+
+impl Localized for NoSuchPost {
+  fn id(&self) -> &'static str {
+    "error-no-such-post"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("number", self.0.into())]
+  }
+}
+
+/// Everything that can go wrong while reading a feed.
+#[derive(Debug, Error)]
+pub enum ApreadErrors {
+  /// The handle given couldn't be read.
+  #[error(transparent)]
+  BadHandleError(#[from] BadHandleError),
+  /// The account's WebFinger document didn't lead to an actor.
+  #[error(transparent)]
+  NoFeedLink(#[from] NoFeedLink),
+  /// A request to an instance failed.
+  #[error("{0}")]
+  RequestError(#[from] reqwest::Error),
+  /// The outbox can't be read from its oldest post.
+  #[error(transparent)]
+  NoLastPage(#[from] NoLastPage),
+  /// A document linked to a url it shouldn't have.
+  #[error(transparent)]
+  BadUrl(#[from] BadUrl),
+  /// A response wasn't the document we expected.
+  #[error("{0}")]
+  ParseError(#[from] serde_json::Error),
+  /// A response wasn't the document we expected, and was captured to
+  /// report.
+  #[error(transparent)]
+  Captured(#[from] Captured),
+  /// Replay was asked for a response that was never recorded.
+  #[error(transparent)]
+  NotRecorded(#[from] NotRecorded),
+  /// Reading or writing the archive failed.
+  #[error("{0}")]
+  ArchiveError(#[from] rusqlite::Error),
+  /// A post couldn't be archived because it has no id.
+  #[error(transparent)]
+  NoPostId(#[from] NoPostId),
+  /// A post was asked about which isn't in the archive.
+  #[error(transparent)]
+  NotArchived(#[from] NotArchived),
+  /// A feed was asked for something only accounts have.
+  #[error(transparent)]
+  NotAnAccount(#[from] NotAnAccount),
+  /// A file to import isn't an account export.
+  #[error(transparent)]
+  NotAnExport(#[from] NotAnExport),
+  /// An account export's zip file couldn't be read.
+  #[error("{0}")]
+  Zip(#[from] zip::result::ZipError),
+  /// There's nowhere to keep the archive.
+  #[error(transparent)]
+  NoDataDir(#[from] NoDataDir),
+  /// A subscriptions or configuration file couldn't be read.
+  #[error("{0}")]
+  ConfigError(#[from] config::ConfigError),
+  /// A subscription named nothing to read.
+  #[error(transparent)]
+  EmptySubscription(#[from] subscriptions::EmptySubscription),
+  /// An RSS or Atom feed couldn't be read.
+  #[error("{0}")]
+  FeedError(#[from] feed_rs::parser::ParseFeedError),
+  /// There was nothing to read: no handle, and no subscriptions.
+  #[error(transparent)]
+  NothingToRead(#[from] NothingToRead),
+  /// A Nostr relay couldn't be talked to.
+  #[cfg(feature = "nostr")]
+  #[error("{0}")]
+  RelayError(Box<tokio_tungstenite::tungstenite::Error>),
+  /// The post asked to be opened isn't there.
+  #[error(transparent)]
+  NoSuchPost(#[from] NoSuchPost),
+  /// A post template couldn't be read or rendered.
+  #[error("{0}")]
+  TemplateError(#[from] minijinja::Error),
+  /// Output was asked for in a format that doesn't suit it.
+  #[error(transparent)]
+  WrongFormat(#[from] WrongFormat),
+  /// An onion service's account was read without Tor.
+  #[error(transparent)]
+  NeedsTor(#[from] NeedsTor),
+  /// An instance kept failing, so was left alone for a while.
+  #[error(transparent)]
+  InstanceDown(#[from] InstanceDown),
+  /// There's nowhere to install systemd units.
+  #[error(transparent)]
+  NoUnitDir(#[from] NoUnitDir),
+  /// Something needing a login was read without one.
+  #[error(transparent)]
+  NotLoggedIn(#[from] NotLoggedIn),
+  /// The logged in account has no list by the name asked for.
+  #[error(transparent)]
+  NoSuchList(#[from] NoSuchList),
+  /// `--as` named an account which isn't logged in.
+  #[error(transparent)]
+  NoSuchAccount(#[from] NoSuchAccount),
+  /// `--saved` named a search the config file doesn't have.
+  #[error(transparent)]
+  NoSuchSearch(#[from] NoSuchSearch),
+  /// There was no program to copy to the clipboard with.
+  #[error(transparent)]
+  NoClipboard(#[from] NoClipboard),
+  /// `--play` picked a post with no video or audio.
+  #[error(transparent)]
+  NothingToPlay(#[from] NothingToPlay),
+  /// `apread init` would have written over a config file.
+  #[error(transparent)]
+  AlreadySetUp(#[from] AlreadySetUp),
+  /// There's no platform config directory to write a config file in.
+  #[error(transparent)]
+  NoConfigDir(#[from] NoConfigDir),
+  /// `apread prune` was given no retention policy to prune by.
+  #[error(transparent)]
+  NoRetention(#[from] NoRetention),
+  /// An account's token wasn't in the keyring it was kept in.
+  #[error(transparent)]
+  NoToken(#[from] NoToken),
+  /// Posting was asked of a login which can only read.
+  #[error(transparent)]
+  ReadOnly(#[from] ReadOnly),
+  /// A post to reply to couldn't be found.
+  #[error(transparent)]
+  NotFound(#[from] NotFound),
+  /// A draft was left with nothing in it.
+  #[error(transparent)]
+  EmptyDraft(#[from] EmptyDraft),
+  /// There's no draft with the number asked for.
+  #[error(transparent)]
+  NoSuchDraft(#[from] NoSuchDraft),
+  /// A filter module couldn't be loaded.
+  #[cfg(feature = "wasm")]
+  #[error(transparent)]
+  BadModule(#[from] BadModule),
+  /// Writing output failed.
+  #[error("{0}")]
+  IoError(#[from] std::io::Error),
+}
+
+impl ApreadErrors {
+  /// What went wrong, in the language of `messages`. Errors from the
+  /// libraries apread uses, as a failed request, are written as they give
+  /// them.
+  pub fn message(&self, messages: &i18n::Messages) -> String {
+    match self.localized() {
+      Some(error) => messages.localize(error),
+      None => self.to_string(),
+    }
+  }
+
+  /// The error as one with a message of its own, unless it's from a
+  /// library.
+  fn localized(&self) -> Option<&dyn Localized> {
+    Some(match self {
+      Self::BadHandleError(error) => error,
+      Self::NoFeedLink(error) => error,
+      Self::NoLastPage(error) => error,
+      Self::BadUrl(error) => error,
+      Self::Captured(error) => error,
+      Self::NotRecorded(error) => error,
+      Self::NoPostId(error) => error,
+      Self::NotArchived(error) => error,
+      Self::NotAnAccount(error) => error,
+      Self::NotAnExport(error) => error,
+      Self::NoDataDir(error) => error,
+      Self::EmptySubscription(error) => error,
+      Self::NothingToRead(error) => error,
+      Self::NoSuchPost(error) => error,
+      Self::WrongFormat(error) => error,
+      Self::NeedsTor(error) => error,
+      Self::InstanceDown(error) => error,
+      Self::NoUnitDir(error) => error,
+      Self::NotLoggedIn(error) => error,
+      Self::NoSuchList(error) => error,
+      Self::NoSuchAccount(error) => error,
+      Self::NoSuchSearch(error) => error,
+      Self::NoClipboard(error) => error,
+      Self::NothingToPlay(error) => error,
+      Self::AlreadySetUp(error) => error,
+      Self::NoConfigDir(error) => error,
+      Self::NoRetention(error) => error,
+      Self::NoToken(error) => error,
+      Self::ReadOnly(error) => error,
+      Self::NotFound(error) => error,
+      Self::EmptyDraft(error) => error,
+      Self::NoSuchDraft(error) => error,
+      #[cfg(feature = "wasm")]
+      Self::BadModule(error) => error,
+      _ => return None,
+    })
+  }
+}
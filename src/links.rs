@@ -0,0 +1,240 @@
+//! Links in posts: finding them, and checking they still lead somewhere.
+
+use std::{cmp::Ordering, fmt};
+
+use chrono::DateTime;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{activity::escape, timeline::Entry, Client};
+
+/// The web links in a post's html, in order and without repeats. Mentions
+/// and hashtags are left out, since they point back into the fediverse
+/// rather than out of it.
+pub fn extract(html: &str) -> Vec<String> {
+  let document = Html::parse_fragment(html);
+  let anchors = Selector::parse("a[href]").expect("the selector is valid");
+  let mut links: Vec<String> = vec![];
+
+  for anchor in document.select(&anchors) {
+    let element = anchor.value();
+    let href = element.attr("href").unwrap_or_default();
+
+    if !is_internal(element)
+      && web(href)
+      && !links.iter().any(|link| link == href)
+    {
+      links.push(href.to_owned());
+    }
+  }
+
+  links
+}
+
+/// Where in a post to write its links.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+  /// Where they are in the text.
+  #[default]
+  Inline,
+  /// As numbered references in the text, listed under the post, as
+  /// [`footnoted`] writes them.
+  Footnotes,
+}
+
+/// Html with each web link followed by a numbered reference, as in
+/// `the docs[1]`, and the links listed by number at the end, so long urls
+/// don't break up the text. Bare links, whose text is just their url,
+/// become the reference alone. Mentions and hashtags are left as they are.
+pub fn footnoted(html: &str) -> String {
+  let anchors = Selector::parse("a[href]").expect("the selector is valid");
+  let mut out = String::with_capacity(html.len());
+  let mut notes: Vec<String> = vec![];
+  let mut rest = html;
+
+  while let Some((start, end)) = next_anchor(rest) {
+    out.push_str(&rest[..start]);
+
+    let anchor = &rest[start..end];
+    let fragment = Html::parse_fragment(anchor);
+    let link = fragment
+      .select(&anchors)
+      .next()
+      .filter(|element| !is_internal(element.value()))
+      .and_then(|element| {
+        let href = element.value().attr("href")?;
+        let text: String = element.text().collect();
+
+        web(href).then(|| (href.to_owned(), text))
+      });
+
+    match link {
+      Some((href, text)) => {
+        let number = match notes.iter().position(|note| *note == href) {
+          Some(index) => index + 1,
+          None => {
+            notes.push(href.clone());
+            notes.len()
+          }
+        };
+
+        if text.trim() != href && text.trim() != href.trim_end_matches('/') {
+          out.push_str(&escape(text.trim()));
+        }
+
+        out.push_str(&format!("[{}]", number));
+      }
+      None => out.push_str(anchor),
+    }
+
+    rest = &rest[end..];
+  }
+
+  out.push_str(rest);
+
+  if !notes.is_empty() {
+    let notes: Vec<_> = notes
+      .iter()
+      .enumerate()
+      .map(|(index, url)| format!("[{}] {}", index + 1, escape(url)))
+      .collect();
+
+    out.push_str(&format!("<p>{}</p>", notes.join("<br>")));
+  }
+
+  out
+}
+
+/// Where the next `<a>` element in some html starts and ends.
+pub(crate) fn next_anchor(html: &str) -> Option<(usize, usize)> {
+  let start = html.match_indices("<a").map(|(at, _)| at).find(|at| {
+    html[at + 2..].starts_with(|next: char| next.is_whitespace() || next == '>')
+  })?;
+  let end = html[start..].find("</a>")? + start + "</a>".len();
+
+  Some((start, end))
+}
+
+/// Whether a link points back into the fediverse, as mentions and hashtags
+/// do.
+fn is_internal(element: &scraper::node::Element) -> bool {
+  element
+    .classes()
+    .any(|class| class == "mention" || class == "hashtag")
+    || element
+      .attr("rel")
+      .is_some_and(|rel| rel.split_whitespace().any(|rel| rel == "tag"))
+}
+
+fn web(href: &str) -> bool {
+  href.starts_with("https://") || href.starts_with("http://")
+}
+
+/// A link an account has posted, and how often.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Link {
+  /// Where the link goes.
+  pub url: String,
+  /// How many posts it's in.
+  pub count: usize,
+  /// When the earliest of those posts was published.
+  pub first_seen: String,
+  /// Whether the link still works, once it's been checked.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub health: Option<Health>,
+}
+
+/// Every link in the entries' posts, once each, most often posted first
+/// and then oldest first.
+pub fn tally(entries: &[Entry]) -> Vec<Link> {
+  let mut links: Vec<Link> = vec![];
+
+  for entry in entries {
+    for url in extract(&entry.post.content) {
+      match links.iter_mut().find(|link| link.url == url) {
+        Some(link) => {
+          link.count += 1;
+
+          if by_date(&entry.published, &link.first_seen).is_lt() {
+            link.first_seen = entry.published.clone();
+          }
+        }
+        None => links.push(Link {
+          url,
+          count: 1,
+          first_seen: entry.published.clone(),
+          health: None,
+        }),
+      }
+    }
+  }
+
+  links.sort_by(|a, b| {
+    b.count
+      .cmp(&a.count)
+      .then_with(|| by_date(&a.first_seen, &b.first_seen))
+  });
+
+  links
+}
+
+/// Follows each link, recording whether it still works.
+pub async fn check(client: &Client, links: &mut [Link]) {
+  for link in links {
+    if client.interrupted() {
+      break;
+    }
+
+    link.health = Some(client.check(&link.url).await);
+  }
+}
+
+fn by_date(a: &str, b: &str) -> Ordering {
+  match (
+    DateTime::parse_from_rfc3339(a),
+    DateTime::parse_from_rfc3339(b),
+  ) {
+    (Ok(a), Ok(b)) => a.cmp(&b),
+    _ => a.cmp(b),
+  }
+}
+
+/// Whether a link still leads somewhere.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Health {
+  /// The link answered, with this status code.
+  Alive {
+    /// The HTTP status code.
+    code: u16,
+  },
+  /// The link answered, but with an error status code.
+  Dead {
+    /// The HTTP status code.
+    code: u16,
+  },
+  /// The link didn't answer at all.
+  Unreachable {
+    /// What went wrong.
+    reason: String,
+  },
+}
+
+impl Health {
+  /// Whether the link is broken, one way or another.
+  pub fn is_broken(&self) -> bool {
+    !matches!(self, Self::Alive { .. })
+  }
+}
+
+impl fmt::Display for Health {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Alive { code } | Self::Dead { code } => write!(f, "HTTP {}", code),
+      Self::Unreachable { reason } => f.write_str(reason),
+    }
+  }
+}
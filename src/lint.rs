@@ -0,0 +1,287 @@
+//! Checking an ActivityPub document against what apread expects of one,
+//! for server admins working out why their posts don't federate.
+//!
+//! apread reads the fediverse as a strict consumer: what it trips over,
+//! other software is likely to as well. `apread lint` fetches a document as
+//! apread would and reports against its own rules:
+//!
+//! - the response is a success, served as ActivityStreams JSON
+//! - the document has an ActivityStreams `@context`, a `type` and an `id`
+//!   matching where it was fetched from
+//! - actors point at an inbox and an outbox on their own server
+//! - collections and their pages keep their items where their type says
+//! - posts say who wrote them, and when in RFC 3339
+
+use std::fmt;
+
+use chrono::DateTime;
+use serde_json::{Map, Value};
+
+use crate::{client::Raw, ApUrl};
+
+/// The `@context` every ActivityStreams document should have.
+pub const CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+
+/// The ActivityStreams actor types.
+const ACTORS: &[&str] =
+  &["Application", "Group", "Organization", "Person", "Service"];
+
+/// The types of object which are posts, as far as apread is concerned.
+const POSTS: &[&str] = &["Article", "Note", "Page", "Question"];
+
+/// How much an issue matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+  /// Breaks reading the document, in apread and likely elsewhere.
+  Error,
+  /// Works in apread, but goes against the spec or common practice.
+  Warning,
+}
+
+impl fmt::Display for Level {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Error => "error",
+      Self::Warning => "warning",
+    })
+  }
+}
+
+/// Something wrong with a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+  /// How much it matters.
+  pub level: Level,
+  /// What's wrong.
+  pub message: String,
+}
+
+impl Issue {
+  fn error(message: impl Into<String>) -> Self {
+    Self {
+      level: Level::Error,
+      message: message.into(),
+    }
+  }
+
+  fn warning(message: impl Into<String>) -> Self {
+    Self {
+      level: Level::Warning,
+      message: message.into(),
+    }
+  }
+}
+
+impl fmt::Display for Issue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.level, self.message)
+  }
+}
+
+/// Everything wrong with the document fetched from `url`, errors first.
+pub fn lint(url: &str, response: &Raw) -> Vec<Issue> {
+  let mut issues = vec![];
+
+  if !(200..300).contains(&response.status) {
+    issues.push(Issue::error(format!(
+      "the server answered {}",
+      response.status
+    )));
+  }
+
+  match response.content_type.as_deref() {
+    Some(content_type) if is_activity_streams(content_type) => {}
+    Some(content_type) => issues.push(Issue::error(format!(
+      "served as {}, not application/activity+json",
+      content_type
+    ))),
+    None => issues.push(Issue::error("served without a Content-Type")),
+  }
+
+  match serde_json::from_str::<Value>(&response.body) {
+    Ok(Value::Object(document)) => issues.extend(object(url, &document)),
+    Ok(_) => issues.push(Issue::error("the document isn't a JSON object")),
+    Err(error) => {
+      issues.push(Issue::error(format!("the document isn't JSON: {}", error)))
+    }
+  }
+
+  issues.sort_by_key(|issue| issue.level);
+  issues
+}
+
+/// Whether a Content-Type is one of the two ActivityPub allows.
+fn is_activity_streams(content_type: &str) -> bool {
+  let mut parts = content_type.split(';').map(str::trim);
+  let essence = parts.next().unwrap_or_default().to_ascii_lowercase();
+
+  match essence.as_str() {
+    "application/activity+json" => true,
+    "application/ld+json" => parts.any(|part| {
+      part
+        .strip_prefix("profile=")
+        .map(|profile| profile.trim_matches('"'))
+        .is_some_and(|profile| profile.split(' ').any(|url| url == CONTEXT))
+    }),
+    _ => false,
+  }
+}
+
+fn object(url: &str, document: &Map<String, Value>) -> Vec<Issue> {
+  let mut issues = vec![];
+
+  match document.get("@context") {
+    None => issues.push(Issue::error("there's no @context")),
+    Some(context) if !has_context(context) => issues.push(Issue::error(
+      format!("the @context doesn't include {}", CONTEXT),
+    )),
+    Some(_) => {}
+  }
+
+  match document.get("id").and_then(Value::as_str) {
+    None => issues.push(Issue::error("there's no id")),
+    Some(id) if id != url => issues.push(Issue::warning(format!(
+      "the id is {}, but it was fetched from {}",
+      id, url
+    ))),
+    Some(_) => {}
+  }
+
+  let kind = match document.get("type").and_then(Value::as_str) {
+    Some(kind) => kind,
+    None => {
+      issues.push(Issue::error("there's no type"));
+      return issues;
+    }
+  };
+
+  if ACTORS.contains(&kind) {
+    issues.extend(actor(url, document));
+  } else if kind.ends_with("Collection") || kind.ends_with("CollectionPage") {
+    issues.extend(collection(kind, document));
+  } else if POSTS.contains(&kind) {
+    issues.extend(post(document));
+  }
+
+  issues
+}
+
+fn has_context(context: &Value) -> bool {
+  match context {
+    Value::String(context) => context == CONTEXT,
+    Value::Array(contexts) => contexts.iter().any(has_context),
+    _ => false,
+  }
+}
+
+fn actor(url: &str, document: &Map<String, Value>) -> Vec<Issue> {
+  let mut issues = vec![];
+  let origin = ApUrl::parse(url).ok();
+
+  for field in ["inbox", "outbox"] {
+    let link = match document.get(field).and_then(Value::as_str) {
+      Some(link) => link,
+      None => {
+        issues.push(Issue::error(format!("the actor has no {}", field)));
+        continue;
+      }
+    };
+
+    let checked = match (ApUrl::parse(link), &origin) {
+      (Ok(link), Some(origin)) => link.check_origin(origin).err(),
+      (Ok(_), None) => None,
+      (Err(error), _) => Some(error),
+    };
+
+    if let Some(error) = checked {
+      issues.push(Issue::error(format!("the actor's {}: {}", field, error)));
+    }
+  }
+
+  if document.get("preferredUsername").is_none() {
+    issues.push(Issue::warning(
+      "the actor has no preferredUsername, so no handle to be found by",
+    ));
+  }
+
+  issues
+}
+
+fn collection(kind: &str, document: &Map<String, Value>) -> Vec<Issue> {
+  let mut issues = vec![];
+  let ordered = kind.starts_with("Ordered");
+  let (right, wrong) = match ordered {
+    true => ("orderedItems", "items"),
+    false => ("items", "orderedItems"),
+  };
+
+  if document.contains_key(wrong) {
+    issues.push(Issue::warning(format!(
+      "a{} {} keeps its items in {}, not {}",
+      if ordered { "n" } else { "" },
+      kind,
+      wrong,
+      right
+    )));
+  }
+
+  if let Some(items) = document.get(right).or_else(|| document.get(wrong)) {
+    if !items.is_array() {
+      issues.push(Issue::error(format!("{} isn't an array", right)));
+    }
+  }
+
+  if let Some(total) = document.get("totalItems") {
+    if total.as_u64().is_none() {
+      issues.push(Issue::error("totalItems isn't a whole number"));
+    }
+  }
+
+  let paged = kind.ends_with("Page");
+
+  if !paged
+    && !document.contains_key("first")
+    && !document.contains_key(right)
+    && !document.contains_key(wrong)
+  {
+    issues.push(Issue::error(
+      "the collection has neither items nor a first page",
+    ));
+  }
+
+  if paged && !document.contains_key("partOf") {
+    issues.push(Issue::warning(
+      "the page doesn't say which collection it's part of",
+    ));
+  }
+
+  issues
+}
+
+fn post(document: &Map<String, Value>) -> Vec<Issue> {
+  let mut issues = vec![];
+
+  if document.get("attributedTo").is_none() {
+    issues.push(Issue::error("the post has no attributedTo"));
+  }
+
+  for field in ["published", "updated"] {
+    match document.get(field).map(Value::as_str) {
+      Some(Some(date)) if DateTime::parse_from_rfc3339(date).is_err() => issues
+        .push(Issue::error(format!(
+          "{} isn't an RFC 3339 date: {}",
+          field, date
+        ))),
+      Some(None) => {
+        issues.push(Issue::error(format!("{} isn't a string", field)))
+      }
+      Some(Some(_)) => {}
+      None if field == "published" => {
+        issues.push(Issue::warning("the post has no published date"))
+      }
+      None => {}
+    }
+  }
+
+  issues
+}
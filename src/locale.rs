@@ -0,0 +1,78 @@
+//! Showing dates and numbers the way the reader's locale writes them.
+//!
+//! The locale comes from the `locale` setting, or failing that from the
+//! usual `LC_ALL`, `LC_TIME` and `LANG` environment variables, and is
+//! written the POSIX way, as in `de_DE` or `en_US.UTF-8`. Without one (or
+//! with the `C` locale) dates are shown as `2023-02-14 18:03`.
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use num_format::ToFormattedString;
+
+/// How to write dates and numbers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Locale {
+  time: Option<chrono::Locale>,
+  numbers: Option<num_format::Locale>,
+}
+
+impl Locale {
+  /// A locale by its POSIX name, such as `de_DE` or `en_US.UTF-8`. Names
+  /// that aren't recognised fall back to the default.
+  pub fn new(name: &str) -> Self {
+    let name = name.split(['.', '@']).next().unwrap_or_default();
+
+    if name.is_empty() || name == "C" || name == "POSIX" {
+      return Self::default();
+    }
+
+    let language = name.split('_').next().unwrap_or(name);
+    let numbers = num_format::Locale::from_name(name.replace('_', "-"))
+      .or_else(|_| num_format::Locale::from_name(language))
+      .ok();
+
+    Self {
+      time: chrono::Locale::try_from(name).ok(),
+      numbers,
+    }
+  }
+
+  /// The locale asked for, or the one the environment gives.
+  pub fn resolve(name: Option<&str>) -> Self {
+    let from_env = || {
+      ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+    };
+
+    match name.map(str::to_owned).or_else(from_env) {
+      Some(name) => Self::new(&name),
+      None => Self::default(),
+    }
+  }
+
+  /// A date and time, with the locale's day and month names and its 12 or
+  /// 24 hour clock.
+  pub fn date(&self, date: &DateTime<FixedOffset>) -> String {
+    match self.time {
+      Some(locale) => date.format_localized("%c", locale).to_string(),
+      None => date.format("%Y-%m-%d %H:%M").to_string(),
+    }
+  }
+
+  /// A day, with the locale's day and month names.
+  pub fn day(&self, date: &NaiveDate) -> String {
+    match self.time {
+      Some(locale) => date.format_localized("%A %e %B %Y", locale).to_string(),
+      None => date.format("%Y-%m-%d").to_string(),
+    }
+  }
+
+  /// A count, with the locale's digit grouping.
+  pub fn number(&self, number: usize) -> String {
+    match &self.numbers {
+      Some(locale) => number.to_formatted_string(locale),
+      None => number.to_string(),
+    }
+  }
+}
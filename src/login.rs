@@ -0,0 +1,432 @@
+//! Logging in to your own instance, for what only it can show you.
+//!
+//! Most of what apread reads is public, but some of it, like your lists, is
+//! only kept by your own instance and only shown to you. `apread login
+//! mastodon.social` registers apread with the instance as an OAuth app,
+//! sends you to authorize it, and trades the code the instance shows for an
+//! access token. The token is only for `read`, so apread can't post or
+//! change anything with it, unless you log in with `--write` to post and
+//! reply from apread too.
+//!
+//! Logging in again, to another account, keeps both: the accounts are
+//! listed in the data directory, the newest login being the one used unless
+//! `--as @me@instance` picks another, and their tokens are kept in the
+//! system's [keyring](crate::keyring) where there is one.
+
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+  sync::OnceLock,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, FluentValue, Localized},
+  ApreadErrors,
+};
+
+/// The file the accounts logged in to are kept in, in the data directory.
+pub const FILE: &str = "logins.json";
+
+/// The file a single login was kept in before there could be several, read
+/// when there's no [`FILE`] yet.
+pub const OLD_FILE: &str = "login.json";
+
+/// The redirect for apps without a web page to come back to: the instance
+/// shows the code to paste instead.
+pub const REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+/// What apread asks to be allowed to do, to read.
+pub const READ_SCOPES: &str = "read";
+
+/// What apread asks to be allowed to do, to post as well: write, boost and
+/// favourite posts and upload their media, but nothing else.
+pub const WRITE_SCOPES: &str =
+  "read write:statuses write:media write:favourites";
+
+/// The scopes to ask for, to read or to post as well.
+pub fn scopes(write: bool) -> &'static str {
+  match write {
+    true => WRITE_SCOPES,
+    false => READ_SCOPES,
+  }
+}
+
+/// Raised when reading something which needs a login without one.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NotLoggedIn;
+
+impl Localized for NotLoggedIn {
+  fn id(&self) -> &'static str {
+    "error-not-logged-in"
+  }
+}
+
+/// Raised when posting with a login that can only read.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct ReadOnly;
+
+impl Localized for ReadOnly {
+  fn id(&self) -> &'static str {
+    "error-read-only"
+  }
+}
+
+/// Raised when the logged in account has no list with a name.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoSuchList(pub String);
+
+impl Localized for NoSuchList {
+  fn id(&self) -> &'static str {
+    "error-no-such-list"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("name", self.0.as_str().into())]
+  }
+}
+
+/// Raised when `--as` names an account which isn't logged in.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoSuchAccount(pub String);
+
+impl Localized for NoSuchAccount {
+  fn id(&self) -> &'static str {
+    "error-no-such-account"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("name", self.0.as_str().into())]
+  }
+}
+
+/// Raised when an account's token should be in the keyring, but isn't.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoToken(pub String);
+
+impl Localized for NoToken {
+  fn id(&self) -> &'static str {
+    "error-no-token"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("name", self.0.as_str().into())]
+  }
+}
+
+/// An access token for an account on an instance.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Login {
+  /// The instance's domain.
+  pub instance: String,
+  /// The account logged in as, as the instance names it.
+  pub account: String,
+  /// The token to send with requests.
+  pub access_token: String,
+  /// Whether the token can post, as well as read.
+  #[serde(default)]
+  pub write: bool,
+}
+
+impl Login {
+  /// The account's full name, as in `alice@example.social`.
+  pub fn name(&self) -> String {
+    format!("{}@{}", self.account, self.instance)
+  }
+}
+
+/// A login whose token, if it's in the keyring, is only looked up the first
+/// time something needs it, so reading what's public never waits on the
+/// keyring, or fails with it.
+#[derive(Debug)]
+pub struct Pending {
+  saved: Saved,
+  login: OnceLock<Option<Login>>,
+}
+
+impl Pending {
+  /// The login, looking its token up in the keyring if it hasn't been yet.
+  pub fn get(&self) -> Result<&Login, NoToken> {
+    self
+      .login
+      .get_or_init(|| {
+        let access_token = match &self.saved.access_token {
+          Some(token) => token.clone(),
+          None => crate::keyring::lookup(&self.saved.name())?,
+        };
+
+        Some(Login {
+          instance: self.saved.instance.clone(),
+          account: self.saved.account.clone(),
+          access_token,
+          write: self.saved.write,
+        })
+      })
+      .as_ref()
+      .ok_or_else(|| NoToken(self.saved.name()))
+  }
+}
+
+impl From<Login> for Pending {
+  fn from(login: Login) -> Self {
+    let token = Some(login.access_token.clone());
+
+    Self {
+      saved: saved(login.clone(), token),
+      login: OnceLock::from(Some(login)),
+    }
+  }
+}
+
+/// An account logged in to, as it's listed in the logins file.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Saved {
+  /// The instance's domain.
+  pub instance: String,
+  /// The account logged in as, as the instance names it.
+  pub account: String,
+  /// Whether the token can post, as well as read.
+  #[serde(default)]
+  pub write: bool,
+  /// The token, if it's kept here rather than in the keyring.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  access_token: Option<String>,
+}
+
+impl Saved {
+  /// The account's full name, as in `alice@example.social`.
+  pub fn name(&self) -> String {
+    format!("{}@{}", self.account, self.instance)
+  }
+
+  /// Whether the account's token is kept in the keyring.
+  pub fn in_keyring(&self) -> bool {
+    self.access_token.is_none()
+  }
+
+  /// Whether this is the account a name, with or without its leading @,
+  /// stands for.
+  fn is(&self, name: &str) -> bool {
+    self
+      .name()
+      .eq_ignore_ascii_case(name.trim_start_matches('@'))
+  }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct File {
+  default: Option<String>,
+  accounts: Vec<Saved>,
+}
+
+/// Every account logged in to, and which of them is used unless another is
+/// asked for.
+#[derive(Debug)]
+pub struct Logins {
+  path: PathBuf,
+  file: File,
+}
+
+impl Logins {
+  /// Reads the accounts listed at a path, or the one login kept beside it
+  /// from before there could be several.
+  pub fn load(path: &Path) -> Result<Self, ApreadErrors> {
+    let file = match read(path)? {
+      Some(json) => serde_json::from_str(&json)?,
+      None => match read(&path.with_file_name(OLD_FILE))? {
+        Some(json) => {
+          let login: Login = serde_json::from_str(&json)?;
+          let token = Some(login.access_token.clone());
+
+          File {
+            default: Some(login.name()),
+            accounts: vec![saved(login, token)],
+          }
+        }
+        None => File::default(),
+      },
+    };
+
+    Ok(Self {
+      path: path.to_owned(),
+      file,
+    })
+  }
+
+  /// The accounts logged in to, in the order they were first logged in.
+  pub fn accounts(&self) -> &[Saved] {
+    &self.file.accounts
+  }
+
+  /// The name of the account used unless another is asked for.
+  pub fn default(&self) -> Option<&str> {
+    self.file.default.as_deref()
+  }
+
+  /// The login for an account by name, as in `@alice@example.social`, or
+  /// for the default account if none is named. With nothing logged in,
+  /// that's none.
+  pub fn get(&self, name: Option<&str>) -> Result<Option<Login>, ApreadErrors> {
+    match self.pending(name)? {
+      Some(pending) => Ok(Some(pending.get()?.clone())),
+      None => Ok(None),
+    }
+  }
+
+  /// The login [`Logins::get`] gives, without looking up its token yet.
+  pub fn pending(
+    &self,
+    name: Option<&str>,
+  ) -> Result<Option<Pending>, NoSuchAccount> {
+    let saved = match (name, self.default()) {
+      (Some(name), _) => Some(self.find(name)?),
+      (None, Some(name)) => self.find(name).ok(),
+      (None, None) => None,
+    };
+
+    Ok(saved.map(|saved| Pending {
+      saved: saved.clone(),
+      login: OnceLock::new(),
+    }))
+  }
+
+  /// Adds a login, replacing any for the same account, and makes it the
+  /// default. Its token goes in the keyring if `keyring` asks and there is
+  /// one, or in the logins file if not.
+  pub fn add(&mut self, login: Login, keyring: bool) {
+    let name = login.name();
+    let in_keyring =
+      keyring && crate::keyring::store(&name, &login.access_token);
+    let token = (!in_keyring).then(|| login.access_token.clone());
+
+    // A token left behind in the keyring would outlive the login.
+    if !in_keyring && keyring {
+      crate::keyring::delete(&name);
+    }
+
+    self.file.accounts.retain(|saved| !saved.is(&name));
+    self.file.accounts.push(saved(login, token));
+    self.file.default = Some(name);
+  }
+
+  /// Makes the named account the default.
+  pub fn set_default(&mut self, name: &str) -> Result<(), ApreadErrors> {
+    self.file.default = Some(self.find(name)?.name());
+
+    Ok(())
+  }
+
+  /// Forgets the named account, and its token in the keyring. The first
+  /// account left becomes the default, if it was.
+  pub fn remove(&mut self, name: &str) -> Result<Saved, ApreadErrors> {
+    let saved = self.find(name)?.clone();
+
+    if saved.in_keyring() {
+      crate::keyring::delete(&saved.name());
+    }
+    self.file.accounts.retain(|other| *other != saved);
+
+    if self.default() == Some(saved.name().as_str()) {
+      self.file.default = self.file.accounts.first().map(Saved::name);
+    }
+
+    Ok(saved)
+  }
+
+  /// Keeps the accounts at the path they were read from. The file is only
+  /// readable by its owner, since the tokens not in the keyring are as good
+  /// as passwords for reading the accounts.
+  pub fn save(&self) -> Result<(), ApreadErrors> {
+    if let Some(dir) = self.path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    io::Write::write_all(
+      &mut options.open(&self.path)?,
+      serde_json::to_string(&self.file)?.as_bytes(),
+    )?;
+
+    // The old file's token is in the new one now, so needn't be kept twice.
+    match fs::remove_file(self.path.with_file_name(OLD_FILE)) {
+      Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error)?,
+      _ => Ok(()),
+    }
+  }
+
+  fn find(&self, name: &str) -> Result<&Saved, NoSuchAccount> {
+    self
+      .file
+      .accounts
+      .iter()
+      .find(|saved| saved.is(name))
+      .ok_or_else(|| NoSuchAccount(name.to_owned()))
+  }
+}
+
+/// A login as it's listed, keeping its token only if it's given.
+fn saved(login: Login, access_token: Option<String>) -> Saved {
+  Saved {
+    instance: login.instance,
+    account: login.account,
+    write: login.write,
+    access_token,
+  }
+}
+
+/// A file's contents, if it's there.
+fn read(path: &Path) -> Result<Option<String>, ApreadErrors> {
+  match fs::read_to_string(path) {
+    Ok(json) => Ok(Some(json)),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(error) => Err(error.into()),
+  }
+}
+
+/// apread as an app registered with an instance.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct App {
+  /// The app's id.
+  pub client_id: String,
+  /// The app's secret, for trading codes for tokens.
+  pub client_secret: String,
+}
+
+impl App {
+  /// The page on an instance, using the given scheme, where its user
+  /// authorizes the app to do what the scopes say.
+  pub fn authorize_url(
+    &self,
+    scheme: &str,
+    instance: &str,
+    scopes: &str,
+  ) -> String {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+      .append_pair("client_id", &self.client_id)
+      .append_pair("scope", scopes)
+      .append_pair("redirect_uri", REDIRECT_URI)
+      .append_pair("response_type", "code")
+      .finish();
+
+    format!("{}://{}/oauth/authorize?{}", scheme, instance, query)
+  }
+}
+
+/// The instance's answer when a code is traded for a token.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Token {
+  /// The token itself.
+  pub access_token: String,
+}
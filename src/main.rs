@@ -1,250 +1,276 @@
 //! Apread is a command-line feed reader for ActivityPub urls
 #![deny(missing_docs)]
 
-use clap::Parser;
-use reqwest::header::ACCEPT;
-use serde::Deserialize;
-use thiserror::Error;
-use tokio;
+use std::io::{self, Write};
 
+use apread::{interrupt, ApreadErrors};
+use clap::{CommandFactory, Parser, Subcommand};
+
+mod commands;
+
+/// An activity pub feed reader for the command line
 #[derive(Debug, Parser)]
+#[command(
+  args_conflicts_with_subcommands = true,
+  subcommand_negates_reqs = true,
+  after_help = "Settings can also come from APREAD_* environment variables, \
+    or the config file. Flags win over the environment, which wins over the \
+    config file.\n\nAny apread-NAME program on the PATH runs as apread NAME, \
+    given the config and the last timeline as JSON on its stdin."
+)]
 struct Cli {
-  handle: String,
+  #[command(subcommand)]
+  command: Option<Command>,
+  #[command(flatten)]
+  read: commands::read::Args,
+  #[command(flatten)]
+  globals: commands::Globals,
 }
 
-#[derive(Clone, Debug)]
-struct Handle {
-  domain: String,
-  id: String,
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// List the accounts you've logged in to, and pick which one to use
+  Accounts {
+    #[command(subcommand)]
+    command: Option<commands::accounts::Command>,
+  },
+  /// Archive accounts' posts, noting the ones since deleted, and list
+  /// those
+  Archive(commands::archive::Args),
+  /// Check an account's own posts for missing alt text, missing content
+  /// warnings and broken links
+  Audit(commands::audit::Args),
+  /// Archive every post an account has made, carrying on where an
+  /// interrupted backfill stopped
+  Backfill(commands::backfill::Args),
+  /// Save a post, with tags and a note, to read again later
+  Bookmark(commands::bookmark::AddArgs),
+  /// List bookmarked posts
+  Bookmarks(commands::bookmark::ListArgs),
+  /// Boost a post, as the account you've logged in to with --write
+  Boost(commands::boost::Args),
+  /// Copy a post the last run showed, by its number, to the clipboard: its
+  /// url, or its content with --markdown
+  Copy(commands::copy::Args),
+  /// Compare an account's name, bio and pinned posts against the last time
+  /// it was diffed
+  Diff(commands::diff::Args),
+  /// Check what apread needs to work, for including in bug reports
+  Doctor(commands::doctor::Args),
+  /// List the archived accounts on an instance, and read their posts
+  /// together
+  Domain(commands::domain::Args),
+  /// Write drafts to post later, and manage posts scheduled on your
+  /// instance
+  Draft {
+    #[command(subcommand)]
+    command: commands::draft::Command,
+  },
+  /// Export archived (and freshly fetched) posts to other formats
+  Export(commands::export::Args),
+  /// Favourite a post, as the account you've logged in to with --write
+  Fav(commands::fav::Args),
+  /// Graph who an account mentions and boosts, for Graphviz or Gephi
+  Graph(commands::graph::Args),
+  /// Show every version the archive has kept of an edited post
+  History(commands::history::Args),
+  /// Install apread watch as a systemd user service
+  InstallService(commands::install_service::Args),
+  /// Import a Mastodon account export into the archive, media and all
+  Import(commands::import::Args),
+  /// Set apread up for the first time: which accounts to follow, how to
+  /// show them, and logging in
+  Init(commands::init::Args),
+  /// List the links an account has posted, and check they still work
+  Links(commands::links::Args),
+  /// Check an ActivityPub document for the problems apread (and likely
+  /// other software) would have reading it
+  Lint(commands::lint::Args),
+  /// Read one of your lists, from the instance you've logged in to
+  List(commands::list::Args),
+  /// Log in to your own instance, for reading your lists, adding to the
+  /// accounts logged in to
+  Login(commands::login::Args),
+  /// Read the mentions, boosts, favourites and follows of the account
+  /// you've logged in as
+  Notifications(commands::notifications::Args),
+  /// Open a post the last run showed, by its number, in the browser
+  Open(commands::open::Args),
+  /// Post, as the account you've logged in to with --write
+  Post(commands::post::Args),
+  /// Show an account's profile, with its fields and which of their links
+  /// are verified
+  Profile(commands::profile::Args),
+  /// Trim the archive and its media to the retention policy, saying how
+  /// much space that reclaimed
+  Prune(commands::prune::Args),
+  /// Keep a queue of posts and articles to read (or listen to) later
+  Queue {
+    #[command(subcommand)]
+    command: commands::queue::Command,
+  },
+  /// Read timelines, as apread does when given no command: one account's,
+  /// every subscription's, or a view's
+  Read(commands::read::Args),
+  /// Reply to a post, as the account you've logged in to with --write
+  Reply(commands::reply::Args),
+  /// Search for posts on the instance you've logged in to, or run a search
+  /// saved in the config file
+  Search(commands::search::Args),
+  /// Find accounts with interests like an account's, by the hashtags they
+  /// use
+  Similar(commands::similar::Args),
+  /// Report how the running watch is doing, and what's queued and cached
+  Status,
+  /// Print posts live as your instance streams them: your home timeline,
+  /// the public ones, a hashtag or a list
+  Stream(commands::stream::Args),
+  /// Suggest accounts to follow: the ones the accounts you've archived
+  /// mention and boost most
+  Suggest(commands::suggest::Args),
+  /// Read the newest posts an instance has with a hashtag
+  Tag(commands::tag::Args),
+  /// Check each link on an account's profile for a rel="me" link back, as
+  /// Mastodon does before marking it verified
+  Verify(commands::verify::Args),
+  /// Keep watching timelines, printing new posts and running hooks on them
+  Watch(commands::watch::Args),
 }
 
-impl Handle {
-  fn parse_string(given_string: &str) -> Result<Self, BadHandleError> {
-    let candidate: Vec<_> = given_string.split("@").collect();
-    let domain = candidate
-      .get(1)
-      .map(|str| (*str).to_owned())
-      .ok_or_else(|| BadHandleError)?;
-    let id = candidate
-      .get(0)
-      .map(|str| (*str).to_owned())
-      .ok_or_else(|| BadHandleError)?;
-
-    Ok(Self { domain, id })
-  }
-
-  fn to_webfinger_url(&self) -> String {
-    format!(
-      "https://{}/.well-known/webfinger?resource=acct:{}@{}",
-      self.domain, self.id, self.domain
-    )
+#[tokio::main]
+async fn main() -> Result<(), ApreadErrors> {
+  let own: Vec<String> = Cli::command()
+    .get_subcommands()
+    .flat_map(|command| {
+      std::iter::once(command.get_name()).chain(command.get_all_aliases())
+    })
+    .map(str::to_owned)
+    .collect();
+
+  if let Some(code) = commands::plugin::dispatch(&own)? {
+    std::process::exit(code);
   }
-}
 
-#[derive(Debug, Error)]
-enum ApreadErrors {
-  #[error(transparent)]
-  BadHandleError(#[from] BadHandleError),
-  #[error(transparent)]
-  NoFeedLink(#[from] NoFeedLink),
-  #[error("{0}")]
-  RequestError(#[from] reqwest::Error),
-}
-
-#[derive(Debug, Error)]
-#[error("Unable to read handle")]
-struct BadHandleError;
-
-#[derive(Debug, Error)]
-#[error("No feed link")]
-struct NoFeedLink;
-
-#[derive(Debug, Deserialize)]
-struct Webfinger {
-  //   aliases: Vec<String>,
-  links: Vec<Link>,
-  //   subject: String,
-}
+  let cli = Cli::parse();
+  let interrupt = cli.globals.interrupt.clone();
+  let stats = cli.globals.verbose.then(|| cli.globals.stats.clone());
 
-impl Webfinger {
-  fn to_actor_url(&self) -> Result<String, ApreadErrors> {
-    let mut feed = Err(ApreadErrors::NoFeedLink(NoFeedLink));
+  interrupt.on_ctrl_c();
 
-    for link in &self.links {
-      match link {
-        Link::Feed { href, .. } => {
-          feed = Ok(href.clone());
-        }
-        _ => (),
-      }
+  let result = match cli.command {
+    None => commands::read::run(cli.read, &cli.globals).await,
+    Some(Command::Accounts { command }) => {
+      commands::accounts::run(command, &cli.globals)
     }
+    Some(Command::Archive(args)) => {
+      commands::archive::run(args, &cli.globals).await
+    }
+    Some(Command::Audit(args)) => {
+      commands::audit::run(args, &cli.globals).await
+    }
+    Some(Command::Backfill(args)) => {
+      commands::backfill::run(args, &cli.globals).await
+    }
+    Some(Command::Bookmark(args)) => {
+      commands::bookmark::add(args, &cli.globals).await
+    }
+    Some(Command::Bookmarks(args)) => {
+      commands::bookmark::list(args, &cli.globals)
+    }
+    Some(Command::Boost(args)) => {
+      commands::boost::run(args, &cli.globals).await
+    }
+    Some(Command::Copy(args)) => commands::copy::run(args, &cli.globals).await,
+    Some(Command::Diff(args)) => commands::diff::run(args, &cli.globals).await,
+    Some(Command::Doctor(args)) => {
+      commands::doctor::run(args, &cli.globals).await
+    }
+    Some(Command::Domain(args)) => commands::domain::run(args, &cli.globals),
+    Some(Command::Draft { command }) => {
+      commands::draft::run(command, &cli.globals).await
+    }
+    Some(Command::Export(args)) => {
+      commands::export::run(args, &cli.globals).await
+    }
+    Some(Command::Fav(args)) => commands::fav::run(args, &cli.globals).await,
+    Some(Command::Graph(args)) => {
+      commands::graph::run(args, &cli.globals).await
+    }
+    Some(Command::History(args)) => commands::history::run(args, &cli.globals),
+    Some(Command::Import(args)) => commands::import::run(args, &cli.globals),
+    Some(Command::Init(args)) => commands::init::run(args, &cli.globals).await,
+    Some(Command::InstallService(args)) => commands::install_service::run(args),
+    Some(Command::Links(args)) => {
+      commands::links::run(args, &cli.globals).await
+    }
+    Some(Command::Lint(args)) => commands::lint::run(args, &cli.globals).await,
+    Some(Command::List(args)) => commands::list::run(args, &cli.globals).await,
+    Some(Command::Login(args)) => {
+      commands::login::run(args, &cli.globals).await
+    }
+    Some(Command::Notifications(args)) => {
+      commands::notifications::run(args, &cli.globals).await
+    }
+    Some(Command::Open(args)) => commands::open::run(args, &cli.globals),
+    Some(Command::Post(args)) => commands::post::run(args, &cli.globals).await,
+    Some(Command::Profile(args)) => {
+      commands::profile::run(args, &cli.globals).await
+    }
+    Some(Command::Prune(args)) => commands::prune::run(args, &cli.globals),
+    Some(Command::Queue { command }) => {
+      commands::queue::run(command, &cli.globals).await
+    }
+    Some(Command::Read(args)) => commands::read::run(args, &cli.globals).await,
+    Some(Command::Reply(args)) => {
+      commands::reply::run(args, &cli.globals).await
+    }
+    Some(Command::Search(args)) => {
+      commands::search::run(args, &cli.globals).await
+    }
+    Some(Command::Similar(args)) => {
+      commands::similar::run(args, &cli.globals).await
+    }
+    Some(Command::Status) => commands::status::run(&cli.globals),
+    Some(Command::Stream(args)) => {
+      commands::stream::run(args, &cli.globals).await
+    }
+    Some(Command::Suggest(args)) => {
+      commands::suggest::run(args, &cli.globals).await
+    }
+    Some(Command::Tag(args)) => commands::tag::run(args, &cli.globals).await,
+    Some(Command::Verify(args)) => {
+      commands::verify::run(args, &cli.globals).await
+    }
+    Some(Command::Watch(args)) => {
+      commands::watch::run(args, &cli.globals).await
+    }
+  };
 
-    feed
-  }
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(tag = "rel")]
-enum Link {
-  /// This represents a link to the profile page for a user.
-  ///
-  /// This is left deliberately blank.
-  /// The actual structure here is closer to this:
-  ///
-  /// ```
-  ///   Profile {
-  ///     href: String,
-  ///     #[serde(rename = "type")]
-  ///     kind: String,
-  ///   }
-  /// ```
-  #[serde(rename = "http://webfinger.net/rel/profile-page")]
-  Profile,
-  /// As with profile, this represents a link, this time to the
-  /// actor's feed.
-  ///
-  /// We omit some fields.
-  /// The actual structure here is closer to this:
-  ///
-  /// ```
-  ///   Feed {
-  ///     href: String,
-  ///     #[serde(rename = "type")]
-  ///     kind: String,
-  ///   }
-  /// ```
-  #[serde(rename = "self")]
-  Feed { href: String },
-  /// This represents a subscription template for the domain hosting
-  /// our actor.
-  ///
-  /// The actual structure here is closer to this:
-  ///
-  /// ```
-  ///   Subscribe {
-  ///     template: String,
-  ///   }
-  /// ```
-  #[serde(rename = "http://ostatus.org/schema/1.0/subscribe")]
-  Subscribe,
-}
-
-#[derive(Debug, Deserialize)]
-struct Actor {
-  outbox: String,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct OutboxIndex {
-  first: String,
-  last: String,
-  total_items: usize,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Page {
-  ordered_items: Vec<Item>,
-}
-
-impl Page {
-  fn posts(&self) -> Vec<Item> {
-    let mut posts = vec![];
+  // Reading a timeline says what went wrong once it's written out; any
+  // other command says so once it's done.
+  let format = cli
+    .globals
+    .format()
+    .or_else(|| Some(cli.globals.settings().ok()?.format));
 
-    for candidate in &self.ordered_items {
-      match candidate {
-        Item::Post { .. } => {
-          posts.push(candidate.clone());
-        }
-        _ => (),
-      }
-    }
+  cli.globals.report(format.unwrap_or_default())?;
 
-    posts
+  if let Some(stats) = stats {
+    eprintln!("{}", stats);
   }
-}
-
-#[derive(Clone, Debug, Deserialize)]
-#[serde(tag = "type")]
-enum Item {
-  #[serde(rename = "Create")]
-  Post { object: Post, published: String },
-  #[serde(other)]
-  Boost,
-}
 
-impl Item {
-  fn markdown_content(&self) -> String {
-    match self {
-      Self::Boost => String::new(),
-      Self::Post { object, .. } => html2md::parse_html(&object.content),
-    }
+  // Whatever was read before the interrupt has been written out; say so
+  // in the exit code, so scripts don't mistake it for everything.
+  if interrupt.is_triggered() && result.is_ok() {
+    io::stdout().flush()?;
+    std::process::exit(interrupt::EXIT_CODE);
   }
-}
 
-#[derive(Clone, Debug, Deserialize)]
-struct Post {
-  content: String,
-}
+  if let Err(error) = result {
+    let messages = cli.globals.messages();
 
-#[tokio::main]
-async fn main() -> Result<(), ApreadErrors> {
-  let cli = Cli::parse();
-  let handle = Handle::parse_string(&cli.handle)?;
-  let client = reqwest::Client::new();
-
-  let webfinger = client
-    .get(handle.to_webfinger_url())
-    .header(ACCEPT, "application/activity+json")
-    .send()
-    .await?
-    .json::<Webfinger>()
-    .await?;
-
-  let actor = client
-    .get(webfinger.to_actor_url()?)
-    .header(
-      ACCEPT,
-      "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"",
-    )
-    .send()
-    .await?
-    .json::<Actor>()
-    .await?;
-
-  let index = client
-    .get(actor.outbox)
-    .header(
-      ACCEPT,
-      "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"",
-    )
-    .send()
-    .await?
-    .json::<OutboxIndex>()
-    .await?;
-
-  let page = client
-    .get(index.first)
-    .header(
-      ACCEPT,
-      "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"",
-    )
-    .send()
-    .await?
-    .json::<Page>()
-    .await?;
-
-  let options = textwrap::Options::new(80);
-
-  for post in page.posts() {
-    println!("{:>15}\n", handle.id);
-
-    for line in textwrap::wrap(&post.markdown_content(), &options) {
-      println!("     {}", line);
-    }
-
-    println!();
+    eprintln!("{}: {}", messages.get("error"), error.message(&messages));
+    std::process::exit(1);
   }
 
   Ok(())
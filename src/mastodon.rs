@@ -0,0 +1,239 @@
+//! Reading the Mastodon API, for what an instance only serves through it.
+//!
+//! ActivityPub gives each account's posts, but not an instance's views
+//! across them: hashtag timelines and lists are only served through the
+//! client API Mastodon started and Pleroma, Akkoma and GoToSocial copied.
+//! Posts read there come back as the API's statuses, which are turned into
+//! the same entries as posts read from an outbox.
+
+use serde::Deserialize;
+
+use crate::{
+  activity::{Attachment, Post, Tag},
+  timeline::{Author, Entry},
+  Handle,
+};
+
+/// How many posts to ask an instance for at once, the most Mastodon gives.
+pub const PAGE_SIZE: usize = 40;
+
+/// One of the lists of accounts the logged in user keeps.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct List {
+  /// The list's id, for reading its timeline.
+  pub id: String,
+  /// The list's name.
+  pub title: String,
+}
+
+/// The answer to a search, of the posts found.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Search {
+  /// The posts found.
+  #[serde(default)]
+  pub statuses: Vec<Status>,
+}
+
+/// A post the instance is keeping to post later.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scheduled {
+  /// The scheduled post's id, for cancelling it.
+  pub id: String,
+  /// When it's to be posted.
+  pub scheduled_at: String,
+  /// What's to be posted.
+  pub params: ScheduledParams,
+}
+
+/// What a scheduled post will say.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScheduledParams {
+  /// The post's text.
+  pub text: String,
+  /// Its content warning, if it has one.
+  pub spoiler_text: Option<String>,
+}
+
+/// The answer to uploading media.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Uploaded {
+  /// The media's id, for attaching it to a post.
+  pub id: String,
+}
+
+/// The list with a title, ignoring case.
+pub fn find_list<'a>(lists: &'a [List], title: &str) -> Option<&'a List> {
+  lists
+    .iter()
+    .find(|list| list.title.to_lowercase() == title.to_lowercase())
+}
+
+/// A post, as the Mastodon API has it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Status {
+  /// The post's id on the instance, for paging.
+  pub id: String,
+  /// The post's ActivityPub id.
+  pub uri: String,
+  /// The post's page, for reading it in a browser.
+  pub url: Option<String>,
+  created_at: String,
+  edited_at: Option<String>,
+  #[serde(default)]
+  spoiler_text: String,
+  /// The post, as html.
+  #[serde(default)]
+  pub content: String,
+  /// Who wrote the post.
+  pub account: Account,
+  #[serde(default)]
+  sensitive: bool,
+  #[serde(default)]
+  media_attachments: Vec<Media>,
+  #[serde(default)]
+  tags: Vec<StatusTag>,
+  #[serde(default)]
+  mentions: Vec<Mention>,
+}
+
+/// An account, as the Mastodon API has it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Account {
+  /// The account's handle: bare for the instance's own accounts, and
+  /// `id@domain` for everyone else's.
+  pub acct: String,
+  /// The account's profile page.
+  pub url: Option<String>,
+  /// The account's bio, as html.
+  #[serde(default)]
+  pub note: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Media {
+  #[serde(rename = "type")]
+  kind: String,
+  url: Option<String>,
+  description: Option<String>,
+  blurhash: Option<String>,
+  meta: Option<Meta>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Meta {
+  original: Option<Original>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct Original {
+  width: Option<u64>,
+  height: Option<u64>,
+  duration: Option<f64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct StatusTag {
+  name: String,
+  url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Mention {
+  acct: String,
+  url: Option<String>,
+}
+
+/// The entries for posts read from an instance's API, each by its own
+/// author.
+pub fn entries(instance: &str, statuses: Vec<Status>) -> Vec<Entry> {
+  statuses
+    .into_iter()
+    .filter_map(|status| to_entry(instance, status))
+    .collect()
+}
+
+fn to_entry(instance: &str, status: Status) -> Option<Entry> {
+  let handle = account_handle(instance, &status.account.acct)?;
+  let tag = status
+    .tags
+    .into_iter()
+    .map(|tag| Tag::Hashtag {
+      name: format!("#{}", tag.name),
+      href: tag.url,
+    })
+    .chain(status.mentions.into_iter().map(|mention| {
+      Tag::Mention {
+        name: account_handle(instance, &mention.acct)
+          .map(|handle| format!("@{}", handle)),
+        href: mention.url,
+      }
+    }))
+    .collect();
+  let attachment = status
+    .media_attachments
+    .into_iter()
+    .map(|media| {
+      let original = media
+        .meta
+        .and_then(|meta| meta.original)
+        .unwrap_or_default();
+
+      Attachment {
+        media_type: match media.kind.as_str() {
+          "image" => Some("image/*".to_owned()),
+          "gifv" | "video" => Some("video/*".to_owned()),
+          "audio" => Some("audio/*".to_owned()),
+          _ => None,
+        },
+        url: media.url,
+        name: media.description,
+        blurhash: media.blurhash,
+        duration: original.duration,
+        width: original.width,
+        height: original.height,
+        hidden: false,
+        saved: None,
+        thumbnail: None,
+      }
+    })
+    .collect();
+
+  Some(Entry {
+    author: Author::new(handle),
+    post: Post {
+      id: Some(status.uri),
+      kind: None,
+      url: status.url,
+      attributed_to: status.account.url,
+      published: Some(status.created_at.clone()),
+      updated: status.edited_at,
+      summary: Some(status.spoiler_text).filter(|cw| !cw.is_empty()),
+      in_reply_to: None,
+      content: status.content,
+      tag,
+      attachment,
+      to: vec![],
+      cc: vec![],
+      sensitive: status.sensitive,
+      duration: None,
+      name: None,
+      start_time: None,
+      end_time: None,
+      location: None,
+    },
+    published: status.created_at,
+    boosted: false,
+  })
+}
+
+/// The handle of an account as the API writes it: bare for the instance's
+/// own accounts, and `id@domain` for everyone else's.
+pub fn account_handle(instance: &str, acct: &str) -> Option<Handle> {
+  match acct.contains('@') {
+    true => Handle::parse_string(acct).ok(),
+    false => Some(Handle {
+      id: acct.to_owned(),
+      domain: instance.to_owned(),
+    }),
+  }
+}
@@ -0,0 +1,294 @@
+//! Mathematics in posts, as MathML or LaTeX, made readable in a terminal.
+//!
+//! Converting a post to markdown flattens MathML into its symbols run
+//! together, and escapes the `_`s and `*`s LaTeX is full of. By default
+//! MathML is written out as readable text instead, as in `x^2 + 1`. Asking
+//! for [`Math::Verbatim`] keeps both MathML and LaTeX (between `$`s, `$$`s,
+//! `\(` and `\)`, or `\[` and `\]`) exactly as the post has them.
+
+use scraper::{ElementRef, Html};
+use serde::Deserialize;
+
+use crate::{activity::escape, timeline::Entry};
+
+/// How mathematics in posts is written out.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Math {
+  /// MathML as readable text, and LaTeX as any other text.
+  #[default]
+  Text,
+  /// MathML and LaTeX as they're written, untouched.
+  Verbatim,
+}
+
+/// Entries as they should be written out for a way of writing mathematics.
+/// Verbatim mathematics is marked as code, which markdown leaves alone.
+pub fn prepare(entries: &[Entry], math: Math) -> Vec<Entry> {
+  let mut entries = entries.to_vec();
+
+  if math == Math::Verbatim {
+    for entry in &mut entries {
+      entry.post.content = verbatim(&entry.post.content);
+    }
+  }
+
+  entries
+}
+
+/// Html with its MathML written out as readable text.
+pub fn readable(html: &str) -> String {
+  replace_math(html, |source| {
+    let fragment = Html::parse_fragment(source);
+    let text = fragment
+      .root_element()
+      .children()
+      .filter_map(ElementRef::wrap)
+      .map(text)
+      .collect::<String>();
+
+    escape(&squeeze(&text))
+  })
+}
+
+/// Html with its MathML and LaTeX marked as code, outside of code it's
+/// already in.
+pub fn verbatim(html: &str) -> String {
+  let html =
+    replace_math(html, |source| format!("<code>{}</code>", escape(source)));
+  let mut out = String::with_capacity(html.len());
+  let mut code = 0usize;
+  let mut rest = html.as_str();
+
+  while let Some(start) = rest.find('<') {
+    let end = rest[start..]
+      .find('>')
+      .map_or(rest.len(), |end| start + end + 1);
+    let tag = rest[start..end].to_ascii_lowercase();
+
+    match code {
+      0 => out.push_str(&latex(&rest[..start])),
+      _ => out.push_str(&rest[..start]),
+    }
+
+    if tag.starts_with("<code") || tag.starts_with("<pre") {
+      code += 1;
+    } else if tag.starts_with("</code") || tag.starts_with("</pre") {
+      code = code.saturating_sub(1);
+    }
+
+    out.push_str(&rest[start..end]);
+    rest = &rest[end..];
+  }
+
+  match code {
+    0 => out.push_str(&latex(rest)),
+    _ => out.push_str(rest),
+  }
+
+  out
+}
+
+/// Html with each `<math>` element swapped for what `replace` makes of its
+/// source.
+fn replace_math(html: &str, replace: impl Fn(&str) -> String) -> String {
+  let mut out = String::with_capacity(html.len());
+  let mut rest = html;
+
+  while let Some(start) = rest.find("<math") {
+    let end = match rest[start..].find("</math>") {
+      Some(end) => start + end + "</math>".len(),
+      None => break,
+    };
+
+    out.push_str(&rest[..start]);
+    out.push_str(&replace(&rest[start..end]));
+    rest = &rest[end..];
+  }
+
+  out.push_str(rest);
+  out
+}
+
+/// Text with its LaTeX marked as code. A lone `$` only opens LaTeX when
+/// it's followed by something other than a space, and only closes it when
+/// it follows something other than a space and isn't followed by a digit,
+/// so prices like "$5 or $10" are left as they are.
+fn latex(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut at = 0;
+
+  while at < text.len() {
+    let rest = &text[at..];
+    let span = [("$$", "$$"), ("\\[", "\\]"), ("\\(", "\\)")]
+      .iter()
+      .find_map(|(open, close)| {
+        let inner = rest.strip_prefix(open)?;
+        let end = inner.find(close)?;
+
+        (end > 0).then_some(open.len() + end + close.len())
+      })
+      .or_else(|| inline_dollars(rest));
+
+    match span {
+      Some(len) => {
+        out.push_str("<code>");
+        out.push_str(&rest[..len]);
+        out.push_str("</code>");
+        at += len;
+      }
+      None => {
+        let next = rest.chars().next().map_or(1, char::len_utf8);
+        out.push_str(&rest[..next]);
+        at += next;
+      }
+    }
+  }
+
+  out
+}
+
+/// How long the `$...$` span `text` starts with is, if it starts with one.
+fn inline_dollars(text: &str) -> Option<usize> {
+  let inner = text.strip_prefix('$')?;
+
+  if inner.starts_with(char::is_whitespace) || inner.starts_with('$') {
+    return None;
+  }
+
+  inner
+    .match_indices('$')
+    .map(|(end, _)| end)
+    .find_map(|end| {
+      let before = inner[..end].chars().next_back()?;
+      let after = inner[end + 1..].chars().next();
+
+      (!before.is_whitespace() && !after.is_some_and(|c| c.is_ascii_digit()))
+        .then_some(end + 2)
+    })
+}
+
+/// MathML as text, as in `x^2`, `(a + b)/2` or `√(x)`.
+fn text(element: ElementRef) -> String {
+  let children: Vec<_> =
+    element.children().filter_map(ElementRef::wrap).collect();
+  let part = |index: usize| children.get(index).map(|child| grouped(*child));
+
+  match element.value().name() {
+    "mi" | "mn" | "mtext" | "ms" => element.text().collect::<String>(),
+    "mo" => {
+      let operator: String = element.text().collect();
+
+      match operator.trim() {
+        "" | "(" | ")" | "[" | "]" | "{" | "}" | "," | "|" => operator,
+        operator => format!(" {} ", operator),
+      }
+    }
+    "msup" | "mover" => {
+      format!(
+        "{}^{}",
+        part(0).unwrap_or_default(),
+        part(1).unwrap_or_default()
+      )
+    }
+    "msub" | "munder" => {
+      format!(
+        "{}_{}",
+        part(0).unwrap_or_default(),
+        part(1).unwrap_or_default()
+      )
+    }
+    "msubsup" | "munderover" => format!(
+      "{}_{}^{}",
+      part(0).unwrap_or_default(),
+      part(1).unwrap_or_default(),
+      part(2).unwrap_or_default()
+    ),
+    "mfrac" => {
+      format!(
+        "{}/{}",
+        part(0).unwrap_or_default(),
+        part(1).unwrap_or_default()
+      )
+    }
+    "msqrt" => format!("√({})", squeeze(&all(&children))),
+    "mroot" => format!(
+      "{}√({})",
+      part(1).unwrap_or_default(),
+      children
+        .first()
+        .map(|child| squeeze(&text(*child)))
+        .unwrap_or_default()
+    ),
+    "mfenced" => format!("({})", squeeze(&all(&children))),
+    "mtr" => children
+      .iter()
+      .map(|cell| squeeze(&text(*cell)))
+      .collect::<Vec<_>>()
+      .join(", "),
+    "mtable" => children
+      .iter()
+      .map(|row| format!("[{}]", text(*row)))
+      .collect::<Vec<_>>()
+      .join(" "),
+    // A `<semantics>` element's first child is what's shown; the rest are
+    // other notations for the same thing.
+    "semantics" => children
+      .first()
+      .map(|first| text(*first))
+      .unwrap_or_default(),
+    "annotation" | "annotation-xml" => String::new(),
+    _ => all(&children),
+  }
+}
+
+fn all(children: &[ElementRef]) -> String {
+  children.iter().map(|child| text(*child)).collect()
+}
+
+/// A part of a superscript, fraction and so on, bracketed unless it's a
+/// number or a single symbol.
+fn grouped(element: ElementRef) -> String {
+  let text = squeeze(&text(element));
+  let simple = text.chars().all(|c| c.is_ascii_digit())
+    || text.chars().count() == 1
+    || bracketed(&text);
+
+  match simple {
+    true => text,
+    false => format!("({})", text),
+  }
+}
+
+/// Whether text is all in one pair of brackets, as `(a + b)` is but
+/// `(a)/(b)` isn't.
+fn bracketed(text: &str) -> bool {
+  let mut depth = 0usize;
+
+  for (at, c) in text.char_indices() {
+    match c {
+      '(' => depth += 1,
+      ')' => depth = depth.saturating_sub(1),
+      _ => {}
+    }
+
+    if depth == 0 {
+      return at + 1 == text.len() && at > 0;
+    }
+  }
+
+  false
+}
+
+/// Text with runs of whitespace made single spaces, and none at the ends.
+fn squeeze(text: &str) -> String {
+  text
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+    .chars()
+    .filter(|c| !matches!(c, '\u{2061}'..='\u{2064}'))
+    .collect()
+}
@@ -0,0 +1,94 @@
+//! Relaying posts into a Matrix room, as `apread watch` does in builds with
+//! the `matrix` feature.
+//!
+//! Posts are sent as `m.room.message` events through the homeserver's
+//! client-server API, as whichever account the access token belongs to
+//! (a bot account made for the purpose is best). The room is given by its
+//! id, as in `!abcdefg:matrix.org`, rather than an alias:
+//!
+//! ```toml
+//! [matrix]
+//! homeserver = "https://matrix.org"
+//! access_token = "syt_..."
+//! room = "!abcdefg:matrix.org"
+//! ```
+
+use ring::digest;
+use serde::{Deserialize, Serialize};
+
+use crate::{activity::escape, hex, timeline::Entry};
+
+/// A room to relay posts into, and how to get there.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct Room {
+  /// The homeserver's url.
+  pub homeserver: String,
+  /// The access token of the account posting into the room.
+  pub access_token: String,
+  /// The room's id.
+  pub room: String,
+}
+
+impl Room {
+  /// The url to send a message into the room at. The transaction id makes
+  /// sending the same message twice harmless, since the homeserver keeps
+  /// only the first.
+  pub fn send_url(&self, transaction: &str) -> String {
+    format!(
+      "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+      self.homeserver.trim_end_matches('/'),
+      url::form_urlencoded::byte_serialize(self.room.as_bytes())
+        .collect::<String>(),
+      transaction
+    )
+  }
+}
+
+/// A post as a Matrix message: plain text for clients which want it, and
+/// html for the rest.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct Message {
+  /// Always `m.text`.
+  pub msgtype: &'static str,
+  /// The post as plain text, under its author's name.
+  pub body: String,
+  /// Always `org.matrix.custom.html`.
+  pub format: &'static str,
+  /// The post as html, under its author's name.
+  pub formatted_body: String,
+}
+
+impl Message {
+  /// The message for a post, with its author and a link back to it.
+  pub fn new(entry: &Entry) -> Self {
+    let label = entry.author.label();
+    let mut body = format!("{}:\n{}", label, entry.post.markdown_content());
+    let mut formatted_body = format!(
+      "<p><strong>{}</strong></p>{}",
+      escape(&label),
+      entry.post.content
+    );
+
+    if let Some(url) = &entry.post.url {
+      body.push_str(&format!("\n\n{}", url));
+      formatted_body
+        .push_str(&format!("<p><a href=\"{0}\">{0}</a></p>", escape(url)));
+    }
+
+    Self {
+      msgtype: "m.text",
+      body,
+      format: "org.matrix.custom.html",
+      formatted_body,
+    }
+  }
+}
+
+/// The transaction id to relay a post into a room with, which is the same
+/// every time that post goes to that room.
+pub fn transaction(room: &Room, entry: &Entry) -> String {
+  let key = format!("{} {}", room.room, entry.key());
+  let hash = digest::digest(&digest::SHA256, key.as_bytes());
+
+  hex::encode(hash.as_ref())
+}
@@ -0,0 +1,269 @@
+//! Keeping posts' attachments on disk, so each is downloaded once however
+//! often it's viewed.
+//!
+//! With `download_media` set, `apread read` saves the attachments of the
+//! posts it shows into the `media` directory of the cache, and shows where
+//! each was saved beside its url. Files are kept by the SHA-256 of what's
+//! in them, so the same image attached to several posts, or served from
+//! several urls, is only kept once; a small file for each url says which
+//! it is.
+//!
+//! The cache is kept to `media_cache_size` megabytes, 500 unless set, the
+//! files kept longest ago going first once it's bigger; they're downloaded
+//! again if they're shown again.
+//!
+//! Built with the `thumbnails` feature, images saved are also drawn under
+//! their urls, [`THUMBNAIL_COLUMNS`] cells wide at most, in the half blocks
+//! [blurhashes](crate::blurhash) are drawn in. Each image is shrunk once,
+//! the first time it's shown, and kept in the cache's `thumbnails`
+//! directory after that. Without the feature, only the whole files are
+//! kept, and nothing is drawn.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use ring::digest::{digest, SHA256};
+
+#[cfg(feature = "thumbnails")]
+use crate::blurhash::{self, Rgb};
+use crate::{
+  hex, retention::oldest_first, timeline::Entry, ApreadErrors, Client,
+};
+
+/// The directory attachments are kept in, in the cache directory.
+pub const DIR: &str = "media";
+
+/// How many megabytes of attachments are kept, unless set otherwise.
+pub const DEFAULT_CACHE_SIZE: u64 = 500;
+
+/// How many character cells wide a thumbnail is drawn, at most.
+pub const THUMBNAIL_COLUMNS: u32 = 40;
+
+/// How many character cells tall a thumbnail is drawn, at most.
+pub const THUMBNAIL_ROWS: u32 = 20;
+
+/// Attachments kept by their content, in a directory.
+#[derive(Clone, Debug)]
+pub struct MediaCache {
+  dir: PathBuf,
+}
+
+impl MediaCache {
+  /// A cache in the given directory, created when it's first written to.
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self { dir: dir.into() }
+  }
+
+  /// Where the file downloaded from a url is kept, if it's been kept.
+  pub fn get(&self, url: &str) -> Option<PathBuf> {
+    let name = fs::read_to_string(self.index(url)).ok()?;
+    let path = self.blob(name.trim());
+
+    path.is_file().then_some(path)
+  }
+
+  /// Keeps a file downloaded from a url, giving where it's kept.
+  pub fn put(&self, url: &str, bytes: &[u8]) -> Result<PathBuf, ApreadErrors> {
+    let hash = hex::encode(digest(&SHA256, bytes).as_ref());
+    let name = match extension(url) {
+      Some(extension) => format!("{}.{}", hash, extension),
+      None => hash,
+    };
+    let path = self.blob(&name);
+
+    if !path.is_file() {
+      write(&path, bytes)?;
+    }
+
+    write(&self.index(url), name.as_bytes())?;
+
+    Ok(path)
+  }
+
+  /// Where the file at a url is kept, downloading it first if it hasn't
+  /// been.
+  pub async fn fetch(
+    &self,
+    client: &Client,
+    url: &str,
+  ) -> Result<PathBuf, ApreadErrors> {
+    match self.get(url) {
+      Some(path) => Ok(path),
+      None => self.put(url, &client.media(url).await?),
+    }
+  }
+
+  /// Downloads the attachments of entries which haven't been, noting where
+  /// each is kept on it, and its thumbnail if it's an image and they're
+  /// built in. Attachments which can't be downloaded are left for the next
+  /// time.
+  pub async fn save(&self, client: &Client, entries: &mut [Entry]) {
+    for entry in entries {
+      for attachment in &mut entry.post.attachment {
+        if client.interrupted() {
+          return;
+        }
+
+        if let Some(url) = &attachment.url {
+          attachment.saved = self.fetch(client, url).await.ok();
+          attachment.thumbnail = attachment
+            .saved
+            .as_deref()
+            .and_then(|file| self.thumbnail(file));
+        }
+      }
+    }
+  }
+
+  /// A kept image drawn small, shrinking it the first time it's asked for
+  /// and keeping what it shrank to. Files which aren't images have none.
+  #[cfg(feature = "thumbnails")]
+  pub fn thumbnail(&self, file: &Path) -> Option<Vec<String>> {
+    let kept = self
+      .dir
+      .join("thumbnails")
+      .join(format!("{}.png", file.file_name()?.to_string_lossy()));
+    let image = match image::open(&kept) {
+      Ok(image) => image.into_rgb8(),
+      Err(_) => {
+        let image = image::ImageReader::open(file)
+          .ok()?
+          .with_guessed_format()
+          .ok()?
+          .decode()
+          .ok()?;
+        let (width, height) = fit(image.width(), image.height())?;
+        let image = image.thumbnail_exact(width, height).into_rgb8();
+
+        // Without somewhere to keep it, it's only shrunk again next time.
+        if kept
+          .parent()
+          .is_some_and(|dir| fs::create_dir_all(dir).is_ok())
+        {
+          let _ = image.save(&kept);
+        }
+
+        image
+      }
+    };
+    let pixels: Vec<Rgb> = image.pixels().map(|pixel| pixel.0).collect();
+
+    Some(blurhash::draw(&pixels, image.width() as usize))
+  }
+
+  /// Thumbnails aren't built in, so there are none.
+  #[cfg(not(feature = "thumbnails"))]
+  pub fn thumbnail(&self, _file: &Path) -> Option<Vec<String>> {
+    None
+  }
+
+  /// Removes the files kept longest ago, and the urls naming them, until
+  /// those left take up at most `max` bytes. Gives how many were removed.
+  pub fn trim(&self, max: u64) -> Result<usize, ApreadErrors> {
+    let files = oldest_first(&self.dir.join("files"));
+    let mut size: u64 = files.iter().map(|(_, length)| length).sum();
+    let mut removed = 0;
+
+    for (file, length) in files {
+      if size <= max {
+        break;
+      }
+
+      fs::remove_file(&file)?;
+      size = size.saturating_sub(length);
+
+      if let Some(name) = file.file_name() {
+        let mut thumbnail = name.to_owned();
+        thumbnail.push(".png");
+        let _ = fs::remove_file(self.dir.join("thumbnails").join(thumbnail));
+      }
+
+      removed += 1;
+    }
+
+    if removed > 0 {
+      for (index, _) in oldest_first(&self.dir.join("urls")) {
+        let kept = fs::read_to_string(&index)
+          .is_ok_and(|name| self.blob(name.trim()).is_file());
+
+        if !kept {
+          fs::remove_file(&index)?;
+        }
+      }
+    }
+
+    Ok(removed)
+  }
+
+  /// The file saying which file a url's is, named for the url's hash.
+  fn index(&self, url: &str) -> PathBuf {
+    let hash = hex::encode(digest(&SHA256, url.as_bytes()).as_ref());
+
+    self.dir.join("urls").join(hash)
+  }
+
+  /// Where a file's kept, in a directory for the first two characters of
+  /// its name, so no one directory holds too many.
+  fn blob(&self, name: &str) -> PathBuf {
+    self
+      .dir
+      .join("files")
+      .join(name.get(..2).unwrap_or(name))
+      .join(name)
+  }
+}
+
+/// Writes a file whole, beside where it's going then moved into place, so
+/// another process never reads half of one.
+fn write(path: &Path, bytes: &[u8]) -> Result<(), ApreadErrors> {
+  let partial = path.with_extension(format!("{}.partial", std::process::id()));
+
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+
+  fs::write(&partial, bytes)?;
+  fs::rename(&partial, path)?;
+
+  Ok(())
+}
+
+/// The size in pixels to shrink an image to, keeping its shape, so it's
+/// drawn in at most [`THUMBNAIL_COLUMNS`] by [`THUMBNAIL_ROWS`] cells, two
+/// rows of pixels to a cell.
+#[cfg(feature = "thumbnails")]
+fn fit(width: u32, height: u32) -> Option<(u32, u32)> {
+  if width == 0 || height == 0 {
+    return None;
+  }
+
+  let (most_wide, most_tall) = (THUMBNAIL_COLUMNS, THUMBNAIL_ROWS * 2);
+  let wide = width.min(most_wide);
+  let tall = (wide as u64 * height as u64 / width as u64) as u32;
+  let (wide, tall) = match tall > most_tall {
+    true => {
+      let wide = (most_tall as u64 * width as u64 / height as u64) as u32;
+      (wide.max(1), most_tall)
+    }
+    false => (wide, tall),
+  };
+
+  // Rows of pixels are drawn two at a time, so there have to be an even
+  // number of them.
+  Some((wide, (tall + 1).max(2) / 2 * 2))
+}
+
+/// The extension of the file a url names, as in `png`, if it has a short
+/// one, so what opens the file kept knows what it is.
+fn extension(url: &str) -> Option<String> {
+  let path = reqwest::Url::parse(url).ok()?.path().to_owned();
+  let name = path.rsplit('/').next()?;
+  let (_, extension) = name.rsplit_once('.')?;
+
+  (!extension.is_empty()
+    && extension.len() <= 5
+    && extension.chars().all(|c| c.is_ascii_alphanumeric()))
+  .then(|| extension.to_ascii_lowercase())
+}
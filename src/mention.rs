@@ -0,0 +1,130 @@
+//! Mentions of other accounts, written out as their full handles.
+//!
+//! Servers link a mention to the account's profile with only the first half
+//! of its handle as the link text, so `@bob@bob.example` arrives as a link
+//! reading `@bob`. Which bob that is comes from the post's `Mention` tags,
+//! or failing those from the server the link goes to.
+
+use scraper::{Html, Selector};
+use url::Url;
+
+use crate::{
+  activity::{escape, Post, Tag},
+  links,
+};
+
+/// The post's html with each mention's link text replaced by the full
+/// handle, so a link reading `@bob` reads `@bob@bob.example`.
+pub fn linked(post: &Post) -> String {
+  rewrite(&post.content, &post.tag, |href, handle| {
+    format!(
+      "<a href=\"{}\" class=\"u-url mention\">@{}</a>",
+      escape(href).replace('"', "&quot;"),
+      escape(handle)
+    )
+  })
+}
+
+/// The post's html with each mention replaced by the full handle as plain
+/// text, for output that can't follow links anyway.
+pub fn unlinked(post: &Post) -> String {
+  rewrite(&post.content, &post.tag, |_, handle| {
+    format!("@{}", escape(handle))
+  })
+}
+
+/// The full handles of the accounts a post's html mentions, in the order
+/// it mentions them, going by its tags where it has them.
+pub fn handles(html: &str, tags: &[Tag]) -> Vec<String> {
+  let mut handles = vec![];
+
+  rewrite(html, tags, |_, handle| {
+    handles.push(handle.to_owned());
+    String::new()
+  });
+
+  handles
+}
+
+/// The full handle of the account a mention links to, without its leading
+/// `@`. `text` is the link's text, and `href` where it goes.
+pub fn handle(href: &str, text: &str, tags: &[Tag]) -> Option<String> {
+  let name = text.trim().strip_prefix('@')?;
+
+  if name.contains('@') {
+    return Some(name.to_owned());
+  }
+
+  let host = Url::parse(href)
+    .ok()
+    .and_then(|url| url.host_str().map(str::to_owned));
+  let mentioned: Vec<_> = tags
+    .iter()
+    .filter_map(|tag| match tag {
+      Tag::Mention {
+        name: Some(name),
+        href,
+      } => Some((name.trim_start_matches('@'), href.as_deref())),
+      _ => None,
+    })
+    .collect();
+
+  // Some servers link mentions to the actor itself, which the tag names;
+  // most link to a profile page, which only the handle's halves can match.
+  let by_href = mentioned.iter().find(|(_, tagged)| *tagged == Some(href));
+  let by_name = || {
+    let named: Vec<_> = mentioned
+      .iter()
+      .filter(|(handle, _)| handle.split('@').next() == Some(name))
+      .collect();
+
+    named
+      .iter()
+      .find(|(handle, _)| handle.split('@').nth(1) == host.as_deref())
+      .or_else(|| named.first())
+      .copied()
+  };
+
+  match by_href.or_else(by_name) {
+    Some((handle, _)) => Some((*handle).to_owned()),
+    None => host.map(|host| format!("{}@{}", name, host)),
+  }
+}
+
+/// The post's html, with each mention link swapped for what `replace`
+/// makes of its href and handle. Hashtags, which some servers also class
+/// as mentions, are left alone.
+fn rewrite(
+  html: &str,
+  tags: &[Tag],
+  mut replace: impl FnMut(&str, &str) -> String,
+) -> String {
+  let anchors = Selector::parse("a[href]").expect("the selector is valid");
+  let mut out = String::with_capacity(html.len());
+  let mut rest = html;
+
+  while let Some((start, end)) = links::next_anchor(rest) {
+    out.push_str(&rest[..start]);
+
+    let anchor = &rest[start..end];
+    let fragment = Html::parse_fragment(anchor);
+    let found = fragment.select(&anchors).next().and_then(|element| {
+      let classes: Vec<_> = element.value().classes().collect();
+
+      if !classes.contains(&"mention") || classes.contains(&"hashtag") {
+        return None;
+      }
+
+      let href = element.value().attr("href")?;
+      let text: String = element.text().collect();
+
+      handle(href, &text, tags).map(|handle| replace(href, &handle))
+    });
+
+    out.push_str(found.as_deref().unwrap_or(anchor));
+    rest = &rest[end..];
+  }
+
+  out.push_str(rest);
+  out
+}
@@ -0,0 +1,269 @@
+//! Prometheus metrics for a long-running `apread watch`.
+//!
+//! Given `--metrics 127.0.0.1:9185`, watching also answers `GET /metrics`
+//! at that address in Prometheus's text format, so whoever runs apread as a
+//! daemon can see it's keeping up: how many requests it's made and how long
+//! they took, how many documents came from a cache instead, how many new
+//! posts it's found, and which instances have been failing.
+//!
+//! The same address answers `GET /healthz` whenever watching is running,
+//! and `GET /readyz` once the first poll is done, for container runtimes
+//! and Kubernetes to probe.
+
+use std::{
+  collections::BTreeMap,
+  fmt::Write as _,
+  net::SocketAddr,
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+};
+
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpListener,
+};
+
+use crate::{
+  stats::Stats,
+  timeline::{Author, Source},
+  watch::Poll,
+  ApreadErrors,
+};
+
+/// What a watch has done so far, shared with the server reporting it.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+  stats: Stats,
+  polls: Arc<AtomicU64>,
+  posts: Arc<AtomicU64>,
+  ready: Arc<AtomicBool>,
+  errors: Arc<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl Metrics {
+  /// Metrics including the requests counted in a client's stats.
+  pub fn new(stats: Stats) -> Self {
+    Self {
+      stats,
+      ..Self::default()
+    }
+  }
+
+  /// Counts what a poll found, and the instances of each author it
+  /// couldn't fetch.
+  pub fn poll(&self, poll: &Poll) {
+    self.polls.fetch_add(1, Ordering::Relaxed);
+    self.ready.store(true, Ordering::Relaxed);
+    self
+      .posts
+      .fetch_add(poll.new.len() as u64, Ordering::Relaxed);
+
+    if let Ok(mut errors) = self.errors.lock() {
+      for (author, _) in &poll.failed {
+        *errors.entry(instance(author)).or_default() += 1;
+      }
+    }
+  }
+
+  /// Whether the watch has polled at least once, so has posts to show.
+  pub fn ready(&self) -> bool {
+    self.ready.load(Ordering::Relaxed)
+  }
+
+  /// The metrics in Prometheus's text exposition format.
+  pub fn render(&self) -> String {
+    let mut out = String::new();
+    let requests = self.stats.requests();
+    let cache_hits = self.stats.cache_hits();
+    let fetched = requests + cache_hits;
+    let hit_rate = match fetched {
+      0 => 0.0,
+      _ => cache_hits as f64 / fetched as f64,
+    };
+
+    metric(
+      &mut out,
+      "apread_polls_total",
+      "counter",
+      "Times every watched timeline was polled.",
+      self.polls.load(Ordering::Relaxed),
+    );
+    metric(
+      &mut out,
+      "apread_posts_found_total",
+      "counter",
+      "New posts found by polls.",
+      self.posts.load(Ordering::Relaxed),
+    );
+    metric(
+      &mut out,
+      "apread_requests_total",
+      "counter",
+      "Requests made to instances.",
+      requests,
+    );
+
+    let _ = writeln!(
+      out,
+      "# HELP apread_request_duration_seconds How long requests to \
+       instances took.\n# TYPE apread_request_duration_seconds histogram"
+    );
+
+    for (bound, count) in self.stats.buckets() {
+      let _ = writeln!(
+        out,
+        "apread_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+        bound, count
+      );
+    }
+
+    let _ = writeln!(
+      out,
+      "apread_request_duration_seconds_bucket{{le=\"+Inf\"}} {0}\n\
+       apread_request_duration_seconds_sum {1}\n\
+       apread_request_duration_seconds_count {0}",
+      requests,
+      self.stats.time().as_secs_f64()
+    );
+    metric(
+      &mut out,
+      "apread_cache_hits_total",
+      "counter",
+      "Documents served from a cache rather than fetched.",
+      cache_hits,
+    );
+    metric(
+      &mut out,
+      "apread_cache_hit_ratio",
+      "gauge",
+      "The share of documents served from a cache.",
+      hit_rate,
+    );
+
+    let _ = writeln!(
+      out,
+      "# HELP apread_errors_total Polls which couldn't fetch an account, \
+       by instance.\n# TYPE apread_errors_total counter"
+    );
+
+    if let Ok(errors) = self.errors.lock() {
+      for (instance, count) in errors.iter() {
+        let _ = writeln!(
+          out,
+          "apread_errors_total{{instance=\"{}\"}} {}",
+          escaped(instance),
+          count
+        );
+      }
+    }
+
+    out
+  }
+}
+
+/// Answers `GET /metrics`, and the probes, at an address until the process
+/// ends, giving back the address it's listening at (which has a port, if
+/// port `0` was asked for).
+pub async fn serve(
+  address: SocketAddr,
+  metrics: Metrics,
+) -> Result<SocketAddr, ApreadErrors> {
+  serve_on(TcpListener::bind(address).await?, metrics)
+}
+
+/// Answers `GET /metrics` on a listener already bound, as one handed over
+/// by [systemd](crate::systemd::listener), until the process ends.
+pub fn serve_from(
+  listener: std::net::TcpListener,
+  metrics: Metrics,
+) -> Result<SocketAddr, ApreadErrors> {
+  listener.set_nonblocking(true)?;
+  serve_on(TcpListener::from_std(listener)?, metrics)
+}
+
+fn serve_on(
+  listener: TcpListener,
+  metrics: Metrics,
+) -> Result<SocketAddr, ApreadErrors> {
+  let address = listener.local_addr()?;
+
+  tokio::spawn(async move {
+    while let Ok((mut stream, _)) = listener.accept().await {
+      let metrics = metrics.clone();
+
+      tokio::spawn(async move {
+        // Only the request line matters, and it fits in the first read.
+        let mut request = [0; 1024];
+        let read = stream.read(&mut request).await.unwrap_or(0);
+        let request = String::from_utf8_lossy(&request[..read]);
+        let (status, body) =
+          match request.split(' ').take(2).collect::<Vec<_>>()[..] {
+            ["GET", "/metrics"] => ("200 OK", metrics.render()),
+            ["GET", "/healthz"] => ("200 OK", "ok\n".to_owned()),
+            ["GET", "/readyz"] if metrics.ready() => {
+              ("200 OK", "ready\n".to_owned())
+            }
+            ["GET", "/readyz"] => {
+              ("503 Service Unavailable", "not polled yet\n".to_owned())
+            }
+            _ => ("404 Not Found", String::new()),
+          };
+        let response = format!(
+          "HTTP/1.1 {}\r\n\
+           Content-Type: text/plain; version=0.0.4\r\n\
+           Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+          status,
+          body.len(),
+          body
+        );
+
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+      });
+    }
+  });
+
+  Ok(address)
+}
+
+fn metric(
+  out: &mut String,
+  name: &str,
+  kind: &str,
+  help: &str,
+  value: impl std::fmt::Display,
+) {
+  let _ = writeln!(
+    out,
+    "# HELP {0} {1}\n# TYPE {0} {2}\n{0} {3}",
+    name, help, kind, value
+  );
+}
+
+/// The instance an author's posts are fetched from.
+fn instance(author: &Author) -> String {
+  match &author.source {
+    Source::Account(handle) => handle.domain.clone(),
+    Source::Feed(url) => reqwest::Url::parse(url)
+      .ok()
+      .and_then(|url| url.host_str().map(str::to_owned))
+      .unwrap_or_else(|| url.clone()),
+    Source::Tag(tag) => tag.instance.clone(),
+    Source::List(_) => "lists".to_owned(),
+    Source::Search(_) => "searches".to_owned(),
+    #[cfg(feature = "bsky")]
+    Source::Bluesky(_) => "bsky".to_owned(),
+    #[cfg(feature = "nostr")]
+    Source::Nostr(_) => "nostr".to_owned(),
+  }
+}
+
+/// A label value with its backslashes, quotes and newlines escaped.
+fn escaped(value: &str) -> String {
+  value
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+}
@@ -0,0 +1,227 @@
+//! Nostr accounts, read from their relays.
+//!
+//! Nostr accounts go by their public key, written as an `npub1...` string.
+//! Their notes are asked for from each relay in turn, over a websocket, and
+//! the first relay to answer wins. Notes are plain text, and become posts
+//! like any other; their `t` tags become hashtags. Relays pass on whatever
+//! they're sent, so a note is only read if its id is its hash and it's
+//! signed by the account it's said to be by.
+
+use std::time::Duration;
+
+use bech32::{FromBase32, ToBase32, Variant};
+use futures_util::{SinkExt, StreamExt};
+use k256::schnorr::{Signature, VerifyingKey};
+use ring::digest::{digest, SHA256};
+use serde::Deserialize;
+use serde_json::json;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{
+  activity::{text_to_html, Post, Tag},
+  hex,
+  timeline::{Author, Entry},
+  ApreadErrors,
+};
+
+/// The relays asked for notes, unless the client is given others.
+pub const DEFAULT_RELAYS: &[&str] = &["wss://relay.damus.io", "wss://nos.lol"];
+
+/// How many notes to ask a relay for.
+const LIMIT: usize = 20;
+
+/// How long to wait on a relay before making do with what it's sent.
+const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The hex public key an `npub1...` string stands for, if it is one.
+pub fn public_key(npub: &str) -> Option<String> {
+  let (hrp, data, _) = bech32::decode(npub).ok()?;
+  let key = Vec::<u8>::from_base32(&data).ok()?;
+
+  (hrp == "npub" && key.len() == 32).then(|| hex::encode(&key))
+}
+
+/// A note, signed by its author.
+#[derive(Debug, Deserialize)]
+pub struct Event {
+  /// The note's id, as hex: the hash of the rest of it.
+  pub id: String,
+  /// The public key of the note's author, as hex.
+  #[serde(default)]
+  pub pubkey: String,
+  /// When the note was written, in seconds since the epoch.
+  pub created_at: i64,
+  /// What kind of event this is; notes are kind 1.
+  pub kind: u32,
+  /// The note's tags, each a name followed by its values.
+  #[serde(default)]
+  pub tags: Vec<Vec<String>>,
+  /// The note's plain text.
+  #[serde(default)]
+  pub content: String,
+  /// The author's signature of the note's id, as hex.
+  #[serde(default)]
+  pub sig: String,
+}
+
+impl Event {
+  /// Whether the event is by the account with a hex public key: it says
+  /// it's by them, its id is its hash, and they signed it.
+  pub fn is_signed_by(&self, public_key: &str) -> bool {
+    let serialized = json!([
+      0,
+      self.pubkey,
+      self.created_at,
+      self.kind,
+      self.tags,
+      self.content
+    ])
+    .to_string();
+    let hash = digest(&SHA256, serialized.as_bytes());
+
+    if self.pubkey != public_key || self.id != hex::encode(hash.as_ref()) {
+      return false;
+    }
+
+    match (hex::decode(&self.pubkey), hex::decode(&self.sig)) {
+      (Some(key), Some(sig)) => verify(&key, hash.as_ref(), &sig),
+      _ => false,
+    }
+  }
+}
+
+/// Whether `signature` is the [BIP-340] signature of `message` by the
+/// x-only `public_key`, all as bytes, as Nostr events are signed.
+///
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+  match (
+    VerifyingKey::from_bytes(public_key),
+    Signature::try_from(signature),
+  ) {
+    (Ok(key), Ok(signature)) => key.verify_raw(message, &signature).is_ok(),
+    _ => false,
+  }
+}
+
+/// The entries for an author's notes, newest first. Events which aren't
+/// signed by the author, at the hex `public_key`, are left out.
+pub fn entries(
+  author: &Author,
+  public_key: &str,
+  mut events: Vec<Event>,
+) -> Vec<Entry> {
+  events.retain(|event| event.kind == 1 && event.is_signed_by(public_key));
+  events.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+  events.dedup_by(|a, b| a.id == b.id);
+
+  events
+    .into_iter()
+    .map(|event| to_entry(author, event))
+    .collect()
+}
+
+fn to_entry(author: &Author, event: Event) -> Entry {
+  let published = chrono::DateTime::from_timestamp(event.created_at, 0)
+    .map(|date| date.to_rfc3339())
+    .unwrap_or_default();
+  let note = note_id(&event.id);
+  let tag = event
+    .tags
+    .iter()
+    .filter(|tag| tag.first().map(String::as_str) == Some("t"))
+    .filter_map(|tag| tag.get(1))
+    .map(|name| Tag::Hashtag {
+      name: name.clone(),
+      href: None,
+    })
+    .collect();
+
+  Entry {
+    author: author.clone(),
+    post: Post {
+      url: note
+        .as_ref()
+        .map(|note| format!("https://njump.me/{}", note)),
+      id: Some(note.unwrap_or(event.id)),
+      kind: None,
+      attributed_to: None,
+      published: Some(published.clone()),
+      updated: None,
+      summary: None,
+      content: text_to_html(&event.content),
+      tag,
+      attachment: vec![],
+      to: vec![],
+      cc: vec![],
+      sensitive: false,
+      duration: None,
+      name: None,
+      start_time: None,
+      end_time: None,
+      location: None,
+      in_reply_to: None,
+    },
+    published,
+    boosted: false,
+  }
+}
+
+/// Asks one relay for an author's newest notes. A relay that goes quiet
+/// without saying it's done is given up on, keeping what it sent.
+pub(crate) async fn query(
+  relay: &str,
+  public_key: &str,
+) -> Result<Vec<Event>, ApreadErrors> {
+  let (mut socket, _) = tokio_tungstenite::connect_async(relay).await?;
+  let request = json!(
+    ["REQ", "apread", { "authors": [public_key], "kinds": [1], "limit": LIMIT }]
+  );
+
+  socket.send(Message::Text(request.to_string())).await?;
+
+  let mut events = vec![];
+  let read = async {
+    while let Some(message) = socket.next().await {
+      let Message::Text(text) = message? else {
+        continue;
+      };
+
+      let message: Vec<serde_json::Value> = match serde_json::from_str(&text) {
+        Ok(message) => message,
+        Err(_) => continue,
+      };
+
+      match message.first().and_then(serde_json::Value::as_str) {
+        Some("EVENT") => {
+          if let Some(event) = message.get(2) {
+            events.push(serde_json::from_value(event.clone())?);
+          }
+        }
+        Some("EOSE") => break,
+        // Notices and the like say nothing about the notes asked for.
+        _ => {}
+      }
+    }
+
+    Ok::<_, ApreadErrors>(())
+  };
+
+  if let Ok(result) = tokio::time::timeout(RELAY_TIMEOUT, read).await {
+    result?;
+  }
+
+  Ok(events)
+}
+
+// Websocket errors are large, and rare enough to be worth boxing.
+impl From<tokio_tungstenite::tungstenite::Error> for ApreadErrors {
+  fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+    Self::RelayError(Box::new(error))
+  }
+}
+
+/// A note's id as a `note1...` string, the way Nostr clients link to it.
+fn note_id(id: &str) -> Option<String> {
+  bech32::encode("note", hex::decode(id)?.to_base32(), Variant::Bech32).ok()
+}
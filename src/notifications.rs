@@ -0,0 +1,169 @@
+//! Notifications: the mentions, boosts, favourites and follows aimed at the
+//! account you've logged in as.
+//!
+//! Like lists, notifications are only served through the Mastodon API, to
+//! the account they're for. Which have been read is kept by apread rather
+//! than the instance, as the id of the newest notification shown, so any
+//! newer one is unread until a later run shows it in turn.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  login::Login,
+  mastodon::{Account, Status},
+  render, ApreadErrors,
+};
+
+/// The file the newest read notification is kept in, in the data
+/// directory.
+pub const FILE: &str = "notifications.json";
+
+/// What kind of thing happened.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+  /// Someone mentioned you.
+  Mention,
+  /// Someone boosted one of your posts.
+  Reblog,
+  /// Someone favourited one of your posts.
+  Favourite,
+  /// Someone followed you.
+  Follow,
+  /// Anything else: polls ending, follow requests, and whatever instances
+  /// add next.
+  #[serde(other)]
+  Other,
+}
+
+impl Kind {
+  /// What someone did, as it follows their name.
+  pub fn done(self) -> &'static str {
+    match self {
+      Self::Mention => "mentioned you",
+      Self::Reblog => "boosted your post",
+      Self::Favourite => "favourited your post",
+      Self::Follow => "followed you",
+      Self::Other => "did something",
+    }
+  }
+}
+
+/// A notification, as the Mastodon API has it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Raw {
+  /// The notification's id, for paging and for telling which are read.
+  pub id: String,
+  /// What happened.
+  #[serde(rename = "type")]
+  pub kind: Kind,
+  /// When it happened.
+  pub created_at: String,
+  /// Who did it.
+  pub account: Account,
+  /// The post it happened to, or that mentioned you.
+  pub status: Option<Status>,
+}
+
+/// A notification, ready to show.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct Notification {
+  /// The notification's id.
+  pub id: String,
+  /// What happened.
+  #[serde(rename = "type")]
+  pub kind: Kind,
+  /// When it happened.
+  pub created_at: String,
+  /// Who did it, as `id@domain`.
+  pub account: String,
+  /// The post's url, if there's a post.
+  pub url: Option<String>,
+  /// The post as plain text, if there's a post.
+  pub text: Option<String>,
+  /// Whether this is newer than the last notification shown.
+  pub unread: bool,
+}
+
+impl Notification {
+  /// A notification from the API, for an account on an instance.
+  pub fn new(instance: &str, raw: Raw, last_read: Option<&str>) -> Self {
+    let account = match raw.account.acct.contains('@') {
+      true => raw.account.acct,
+      false => format!("{}@{}", raw.account.acct, instance),
+    };
+
+    Self {
+      unread: last_read.is_none_or(|read| newer(&raw.id, read)),
+      id: raw.id,
+      kind: raw.kind,
+      created_at: raw.created_at,
+      account,
+      url: raw
+        .status
+        .as_ref()
+        .and_then(|status| status.url.clone().or(Some(status.uri.clone()))),
+      text: raw.status.map(|status| render::plain_text(&status.content)),
+    }
+  }
+}
+
+/// Whether one notification id is newer than another. Ids are numbers, or
+/// on some instances other strings which sort the same way, so longer ids
+/// are newer and those of a length go by their characters.
+pub fn newer(id: &str, than: &str) -> bool {
+  (id.len(), id) > (than.len(), than)
+}
+
+/// The newest notification shown to an account.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LastRead {
+  /// The account, as `id@instance`.
+  pub account: String,
+  /// The newest notification's id.
+  pub id: String,
+}
+
+impl LastRead {
+  /// The newest notification read by the logged in account, from the file
+  /// at a path. Another account's is the same as none.
+  pub fn load(
+    path: &Path,
+    login: &Login,
+  ) -> Result<Option<String>, ApreadErrors> {
+    let last_read: Self = match fs::read_to_string(path) {
+      Ok(json) => serde_json::from_str(&json)?,
+      Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+      Err(error) => return Err(error.into()),
+    };
+
+    Ok((last_read.account == account(login)).then_some(last_read.id))
+  }
+
+  /// Marks every notification up to `id` read for the logged in account,
+  /// in the file at a path.
+  pub fn save(
+    path: &Path,
+    login: &Login,
+    id: &str,
+  ) -> Result<(), ApreadErrors> {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    let last_read = Self {
+      account: account(login),
+      id: id.to_owned(),
+    };
+
+    fs::write(path, serde_json::to_string(&last_read)?)?;
+
+    Ok(())
+  }
+}
+
+fn account(login: &Login) -> String {
+  format!("{}@{}", login.account, login.instance)
+}
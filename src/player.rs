@@ -0,0 +1,45 @@
+//! Handing posts' videos and sounds to a media player.
+//!
+//! `apread read --play N` plays the first video or sound of the Nth post
+//! shown, or the post itself when it's a PeerTube video or a podcast
+//! episode, with the `player` setting: `mpv` unless it's set to another.
+//! The player is run as the setting says with the url after it, as in
+//! `mpv --no-video https://…`, so it can be given flags of its own.
+
+use std::process::Command;
+
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, FluentValue, Localized},
+  ApreadErrors,
+};
+
+/// The player to play with, unless another's set.
+pub const DEFAULT: &str = "mpv";
+
+/// Raised when asked to play a post with nothing to play.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NothingToPlay(pub usize);
+
+impl Localized for NothingToPlay {
+  fn id(&self) -> &'static str {
+    "error-nothing-to-play"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("number", self.0.into())]
+  }
+}
+
+/// Plays the file at a url with a player, as a command and its flags,
+/// waiting until it's closed.
+pub fn play(player: &str, url: &str) -> Result<(), ApreadErrors> {
+  let mut words = player.split_whitespace();
+  let program = words.next().unwrap_or(DEFAULT);
+
+  Command::new(program).args(words).arg(url).status()?;
+
+  Ok(())
+}
@@ -0,0 +1,207 @@
+//! Extending apread with programs named for it, as git is extended.
+//!
+//! `apread NAME ARGS...`, where NAME isn't one of apread's own commands,
+//! runs the first `apread-NAME` program on the `PATH` with ARGS, the way
+//! `git NAME` runs `git-NAME`. It's given what apread knows as one JSON
+//! document on its stdin:
+//!
+//! ```json
+//! {
+//!   "apread": "0.2.1",
+//!   "args": ["--since", "yesterday"],
+//!   "config": {
+//!     "path": "/home/me/.config/apread/config.toml",
+//!     "subscriptions": "/home/me/.config/apread/subscriptions.toml",
+//!     "cache_dir": "/home/me/.cache/apread",
+//!     "data_dir": "/home/me/.local/share/apread",
+//!     "format": "terminal",
+//!     "width": 80,
+//!     "lang": "en",
+//!     "file": { "width": 80, "views": { ... } }
+//!   },
+//!   "timeline": [{
+//!     "id": "https://example.social/users/alice/statuses/1",
+//!     "url": "https://example.social/@alice/1",
+//!     "author": "alice@example.social",
+//!     "published": "2023-02-14T18:03:11Z",
+//!     "updated": "2023-02-14T18:10:00Z",
+//!     "content": "<p>Hello!</p>",
+//!     "attachments": [
+//!       { "url": "...", "media_type": "image/png", "alt": "..." }
+//!     ]
+//!   }]
+//! }
+//! ```
+//!
+//! `file` is the config file as it's written, except that secrets are
+//! withheld: anything kept under a name like `secret`, `access_token` or
+//! `password`, as a webhook's secret or the Matrix access token, is given
+//! as `"[redacted]"`, the way [capture] bundles give it, so a plugin can't
+//! pass them on. `timeline` is the posts the last run showed, in the order
+//! they were numbered. Posts keep what was shown of them, as `nickname`,
+//! `boosted` and `summary`, only where there's something to keep, and a
+//! run kept by an older apread gives only ids and urls. The program's exit code is apread's.
+
+use std::{
+  ffi::OsStr,
+  io::{self, Write},
+  path::{Path, PathBuf},
+  process::{Command, ExitStatus, Stdio},
+};
+
+use clap::ValueEnum;
+use config::{Config, File, FileFormat};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+  capture,
+  last_run::{LastRun, Shown},
+  settings::Settings,
+  ApreadErrors,
+};
+
+/// What a plugin's program name starts with.
+pub const PREFIX: &str = "apread-";
+
+/// What a plugin is given on its stdin.
+#[derive(Clone, Debug, Serialize)]
+pub struct Context {
+  /// The version of apread running it.
+  pub apread: &'static str,
+  /// The arguments after its name.
+  pub args: Vec<String>,
+  /// Where apread keeps things, and how it's set up.
+  pub config: Setup,
+  /// The posts the last run showed, the first numbered 1.
+  pub timeline: Vec<Shown>,
+}
+
+/// Where apread keeps things, and the settings plugins most often need.
+#[derive(Clone, Debug, Serialize)]
+pub struct Setup {
+  /// The config file, if there's anywhere for one.
+  pub path: Option<PathBuf>,
+  /// The subscriptions file.
+  pub subscriptions: Option<PathBuf>,
+  /// The cache directory.
+  pub cache_dir: Option<PathBuf>,
+  /// The data directory, which the archive is kept in.
+  pub data_dir: Option<PathBuf>,
+  /// How to write timelines out.
+  pub format: String,
+  /// How many columns to wrap text at.
+  pub width: usize,
+  /// The language to write messages in.
+  pub lang: String,
+  /// The config file as it's written, with its secrets redacted, or null
+  /// if there isn't one.
+  pub file: Value,
+}
+
+impl Context {
+  /// What a plugin given `args` is told, with the settings read from the
+  /// config file at `path`, less its secrets.
+  pub fn new(
+    settings: &Settings,
+    path: Option<PathBuf>,
+    args: Vec<String>,
+  ) -> Result<Self, ApreadErrors> {
+    let file = match path.as_deref().filter(|path| path.is_file()) {
+      Some(path) => Config::builder()
+        .add_source(File::from(path).format(FileFormat::Toml))
+        .build()?
+        .try_deserialize()
+        .map(capture::sanitize)?,
+      None => Value::Null,
+    };
+    let timeline = match settings.last_run() {
+      Some(path) => LastRun::load(&path)?.posts,
+      None => vec![],
+    };
+
+    Ok(Self {
+      apread: env!("CARGO_PKG_VERSION"),
+      args,
+      config: Setup {
+        path,
+        subscriptions: settings.subscriptions_path(),
+        cache_dir: settings.cache_dir(),
+        data_dir: settings.data_dir().ok(),
+        format: settings
+          .format
+          .to_possible_value()
+          .map(|value| value.get_name().to_owned())
+          .unwrap_or_default(),
+        width: settings.width,
+        lang: settings.messages().language().to_owned(),
+        file,
+      },
+      timeline,
+    })
+  }
+}
+
+/// The program for the plugin with a name, from the directories of a
+/// `PATH`, if one's installed. Names which couldn't be a command's, as
+/// handles and urls, are never looked for.
+pub fn find(name: &str, path: &OsStr) -> Option<PathBuf> {
+  let plausible = !name.is_empty()
+    && !name.starts_with('-')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+  if !plausible {
+    return None;
+  }
+
+  let program = format!("{}{}{}", PREFIX, name, std::env::consts::EXE_SUFFIX);
+
+  std::env::split_paths(path)
+    .map(|dir| dir.join(&program))
+    .find(|candidate| is_executable(candidate))
+}
+
+/// Runs a plugin's program with its arguments, writing it the context on
+/// its stdin, and waits for it to finish.
+pub fn run(
+  program: &Path,
+  context: &Context,
+) -> Result<ExitStatus, ApreadErrors> {
+  let mut child = Command::new(program)
+    .args(&context.args)
+    .stdin(Stdio::piped())
+    .spawn()?;
+  let json = serde_json::to_vec(context)?;
+
+  if let Some(mut stdin) = child.stdin.take() {
+    // A plugin which has no use for the context needn't read it.
+    match stdin.write_all(&json) {
+      Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+        return Err(error.into())
+      }
+      _ => {}
+    }
+  }
+
+  Ok(child.wait()?)
+}
+
+fn is_executable(path: &Path) -> bool {
+  let Ok(metadata) = path.metadata() else {
+    return false;
+  };
+
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+  }
+
+  #[cfg(not(unix))]
+  {
+    metadata.is_file()
+  }
+}
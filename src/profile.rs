@@ -0,0 +1,385 @@
+//! How an account's profile has changed since it was last looked at: its
+//! display name, its bio, and which posts it has pinned.
+//!
+//! `apread diff` keeps a [`Profile`] for each account in the archive every
+//! time it runs, and compares the account as it is now against the one it
+//! kept last. A pinned post which has gone missing is checked, to tell one
+//! the account unpinned from one which was deleted outright.
+//!
+//! `apread profile` shows an account's profile as it is, with its fields:
+//! the website, pronouns, keys and the like it lists under its bio. A link
+//! in a field is verified, as Mastodon verifies them, when the page it
+//! links to links back to the profile with `rel="me"`; `apread verify`
+//! checks just the links, saying why each that isn't verified isn't.
+
+use std::fmt;
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  activity::{Actor, ActorAttachment, Post},
+  links::Health,
+  render::plain_text,
+  ApreadErrors, Client,
+};
+
+/// How many characters of a pinned post to keep, to say which it was.
+const EXCERPT_LENGTH: usize = 60;
+
+/// An account's profile, as it stood when it was fetched.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Profile {
+  /// The account's display name.
+  pub name: Option<String>,
+  /// The account's bio, as plain text.
+  pub bio: Option<String>,
+  /// The account's pinned posts, in the order it pins them.
+  pub pinned: Vec<Pinned>,
+}
+
+/// A pinned post, as much of it as it takes to recognize.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Pinned {
+  /// The post's ActivityPub id.
+  pub id: String,
+  /// The start of the post, as plain text.
+  pub excerpt: String,
+}
+
+/// An account's profile as `apread profile` shows it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Card {
+  /// The account's handle, as `id@domain`.
+  pub handle: String,
+  /// The account's display name.
+  pub name: Option<String>,
+  /// The account's bio, as plain text.
+  pub bio: Option<String>,
+  /// The account's profile page.
+  pub url: Option<String>,
+  /// The fields under its bio.
+  pub fields: Vec<Field>,
+}
+
+impl Card {
+  /// The profile of the actor with a handle, its links checked for links
+  /// back.
+  pub async fn fetch(client: &Client, handle: String, actor: &Actor) -> Self {
+    Self {
+      handle,
+      name: actor.name.clone().filter(|name| !name.is_empty()),
+      bio: actor
+        .summary
+        .as_deref()
+        .map(plain_text)
+        .map(|bio| bio.trim().to_owned())
+        .filter(|bio| !bio.is_empty()),
+      url: actor.url.clone(),
+      fields: fields(client, actor).await,
+    }
+  }
+}
+
+/// One of the fields on a profile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Field {
+  /// What the field is, as in `Website`.
+  pub name: String,
+  /// What it says, as plain text.
+  pub value: String,
+  /// Where it links to, if it's a link.
+  pub link: Option<String>,
+  /// Whether the page it links to links back to the profile.
+  pub verified: bool,
+}
+
+/// A link on a profile, checked for a link back.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LinkCheck {
+  /// The name of the field the link is in.
+  pub name: String,
+  /// Where it links to.
+  pub link: String,
+  /// Whether the page links back.
+  #[serde(flatten)]
+  pub verification: Verification,
+}
+
+/// Whether a page links back to a profile which links to it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Verification {
+  /// It links back with `rel="me"`.
+  Verified,
+  /// It doesn't link back.
+  Unverified,
+  /// It couldn't be fetched to tell.
+  Unreachable {
+    /// What went wrong fetching it.
+    error: String,
+  },
+}
+
+impl fmt::Display for Verification {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Verified => f.write_str("verified"),
+      Self::Unverified => f.write_str("unverified: no rel=\"me\" link back"),
+      Self::Unreachable { error } => {
+        write!(f, "unverified: couldn't fetch it ({})", error)
+      }
+    }
+  }
+}
+
+/// A profile kept in the archive, and when.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+  /// The profile as it was.
+  pub profile: Profile,
+  /// When it was kept, as RFC 3339.
+  pub taken_at: String,
+}
+
+/// One way a profile has changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+  /// The display name is different.
+  Name {
+    /// What it was.
+    before: Option<String>,
+    /// What it is now.
+    after: Option<String>,
+  },
+  /// The bio is different.
+  Bio {
+    /// What it was.
+    before: Option<String>,
+    /// What it is now.
+    after: Option<String>,
+  },
+  /// A post has been pinned.
+  Pinned(Pinned),
+  /// A post is no longer pinned, but is still there.
+  Unpinned(Pinned),
+  /// A pinned post has been deleted.
+  Deleted(Pinned),
+}
+
+impl fmt::Display for Change {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Name { before, after } => write!(
+        f,
+        "Display name: {} -> {}",
+        quoted(before.as_deref()),
+        quoted(after.as_deref())
+      ),
+      Self::Bio { before, after } => write!(
+        f,
+        "Bio:\n  - {}\n  + {}",
+        quoted(before.as_deref()),
+        quoted(after.as_deref())
+      ),
+      Self::Pinned(post) => write!(f, "Pinned: {}", post),
+      Self::Unpinned(post) => write!(f, "Unpinned: {}", post),
+      Self::Deleted(post) => write!(f, "Deleted pinned post: {}", post),
+    }
+  }
+}
+
+impl fmt::Display for Pinned {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} ({:?})", self.id, self.excerpt)
+  }
+}
+
+fn quoted(text: Option<&str>) -> String {
+  match text {
+    Some(text) => format!("{:?}", text),
+    None => "none".to_owned(),
+  }
+}
+
+impl Profile {
+  /// Fetches an actor's pinned posts to go with its name and bio.
+  pub async fn fetch(
+    client: &Client,
+    actor: &Actor,
+  ) -> Result<Self, ApreadErrors> {
+    let pinned = match &actor.featured {
+      Some(url) => {
+        let featured = client.collection::<Post>(url).await?;
+        client.first_page_of(featured).await?.ordered_items
+      }
+      None => vec![],
+    };
+
+    Ok(Self {
+      name: actor.name.clone().filter(|name| !name.is_empty()),
+      bio: actor
+        .summary
+        .as_deref()
+        .map(plain_text)
+        .filter(|bio| !bio.is_empty()),
+      pinned: pinned.iter().filter_map(Pinned::from_post).collect(),
+    })
+  }
+
+  /// What's changed from this profile to a newer one, with the pinned posts
+  /// it's lost told apart by whether they're still there.
+  pub async fn changes(&self, client: &Client, now: &Profile) -> Vec<Change> {
+    let mut changes = vec![];
+
+    if self.name != now.name {
+      changes.push(Change::Name {
+        before: self.name.clone(),
+        after: now.name.clone(),
+      });
+    }
+
+    if self.bio != now.bio {
+      changes.push(Change::Bio {
+        before: self.bio.clone(),
+        after: now.bio.clone(),
+      });
+    }
+
+    for post in &self.pinned {
+      if now.pinned.iter().any(|pinned| pinned.id == post.id) {
+        continue;
+      }
+
+      changes.push(match client.check(&post.id).await {
+        Health::Dead { code: 404 | 410 } => Change::Deleted(post.clone()),
+        _ => Change::Unpinned(post.clone()),
+      });
+    }
+
+    for post in &now.pinned {
+      if !self.pinned.iter().any(|pinned| pinned.id == post.id) {
+        changes.push(Change::Pinned(post.clone()));
+      }
+    }
+
+    changes
+  }
+}
+
+impl Pinned {
+  /// A pinned post, if it has an id to know it by.
+  pub fn from_post(post: &Post) -> Option<Self> {
+    let text = plain_text(&post.content);
+    let mut excerpt: String = text.chars().take(EXCERPT_LENGTH).collect();
+
+    if excerpt.len() < text.len() {
+      excerpt.push('…');
+    }
+
+    Some(Self {
+      id: post.id.clone()?,
+      excerpt,
+    })
+  }
+}
+
+/// The fields on an actor's profile, each link among them checked for a
+/// link back. A page which can't be fetched leaves its link unverified.
+pub async fn fields(client: &Client, actor: &Actor) -> Vec<Field> {
+  let mut fields = vec![];
+
+  for attachment in &actor.attachment {
+    let ActorAttachment::PropertyValue { name, value } = attachment else {
+      continue;
+    };
+    let link = first_link(value);
+    let verified = match &link {
+      Some(link) => verify(client, link, actor).await == Verification::Verified,
+      None => false,
+    };
+
+    fields.push(Field {
+      name: name.clone(),
+      value: plain_text(value).trim().to_owned(),
+      link,
+      verified,
+    });
+  }
+
+  fields
+}
+
+/// Checks each link among the fields on an actor's profile for a link
+/// back, as Mastodon does before marking one verified.
+pub async fn link_checks(client: &Client, actor: &Actor) -> Vec<LinkCheck> {
+  let mut checks = vec![];
+
+  for attachment in &actor.attachment {
+    let ActorAttachment::PropertyValue { name, value } = attachment else {
+      continue;
+    };
+    let Some(link) = first_link(value) else {
+      continue;
+    };
+    let verification = verify(client, &link, actor).await;
+
+    checks.push(LinkCheck {
+      name: name.clone(),
+      link,
+      verification,
+    });
+  }
+
+  checks
+}
+
+/// Whether the page at a link links back to an actor.
+pub async fn verify(
+  client: &Client,
+  link: &str,
+  actor: &Actor,
+) -> Verification {
+  match client.web_page(link).await {
+    Ok(page) if links_back(&page, actor) => Verification::Verified,
+    Ok(_) => Verification::Unverified,
+    Err(error) => Verification::Unreachable {
+      error: error.to_string(),
+    },
+  }
+}
+
+/// Whether a page links to an actor's profile, or to the actor itself, as
+/// `rel="me"`.
+pub fn links_back(page: &str, actor: &Actor) -> bool {
+  let selector = Selector::parse("a[rel][href], link[rel][href]")
+    .expect("the selector is valid");
+  let profiles: Vec<&str> = actor
+    .url
+    .as_deref()
+    .into_iter()
+    .chain(actor.id.as_ref().map(|id| id.as_str()))
+    .map(|url| url.trim_end_matches('/'))
+    .collect();
+
+  Html::parse_document(page).select(&selector).any(|link| {
+    let element = link.value();
+    let is_me = element
+      .attr("rel")
+      .is_some_and(|rel| rel.split_whitespace().any(|rel| rel == "me"));
+    let href = element.attr("href").unwrap_or_default();
+
+    is_me && profiles.contains(&href.trim_end_matches('/'))
+  })
+}
+
+/// The first web link in some html.
+fn first_link(html: &str) -> Option<String> {
+  let selector = Selector::parse("a[href]").expect("the selector is valid");
+
+  Html::parse_fragment(html)
+    .select(&selector)
+    .filter_map(|link| link.value().attr("href"))
+    .find(|href| href.starts_with("https://") || href.starts_with("http://"))
+    .map(str::to_owned)
+}
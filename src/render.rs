@@ -0,0 +1,2292 @@
+//! Rendering posts for the terminal.
+
+use std::{
+  borrow::Cow,
+  collections::BTreeSet,
+  io::{self, Write},
+};
+
+use chrono::{DateTime, TimeZone, Utc};
+use html2text::render::text_renderer::TrivialDecorator;
+use minijinja::Environment;
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use textwrap::core::Word;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+  activity::{clock, escape, Attachment},
+  archive::{ArchivedPost, Bookmark, Deleted, Revision},
+  audit::{Problem, Report},
+  bidi, blurhash,
+  bridge::Bridge,
+  diagnostics::{Diagnostic, Level},
+  doctor::Check,
+  i18n::Messages,
+  length::Length,
+  links::{Health, Link},
+  lint::Issue,
+  locale::Locale,
+  mention,
+  notifications::Notification,
+  profile::{Card, LinkCheck, Verification},
+  retention::Pruned,
+  similar::Similar,
+  status,
+  suggest::Suggestion,
+  timeline::{self, Author, Entry, Period},
+  ApreadErrors,
+};
+
+/// A terminal color to show an account's name in.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+  /// ANSI red.
+  Red,
+  /// ANSI green.
+  Green,
+  /// ANSI yellow.
+  Yellow,
+  /// ANSI blue.
+  Blue,
+  /// ANSI magenta.
+  Magenta,
+  /// ANSI cyan.
+  Cyan,
+  /// ANSI white.
+  White,
+}
+
+impl Color {
+  /// Wraps text in the escape codes to show it in this color.
+  pub fn paint(&self, text: &str) -> String {
+    let code = match self {
+      Self::Red => 31,
+      Self::Green => 32,
+      Self::Yellow => 33,
+      Self::Blue => 34,
+      Self::Magenta => 35,
+      Self::Cyan => 36,
+      Self::White => 37,
+    };
+
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+  }
+}
+
+/// Writes timeline entries as wrapped, indented text, each numbered and
+/// under the name of its author, with what's noted of it in the language
+/// of `messages`. Names are colored as their subscriptions ask, but only
+/// when `colored` is set.
+pub fn terminal(
+  out: &mut impl Write,
+  entries: &[Entry],
+  width: usize,
+  colored: bool,
+  messages: &Messages,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+
+  for (index, entry) in entries.iter().enumerate() {
+    titled(out, entry, index + 1, &options, colored, messages)?;
+  }
+
+  Ok(())
+}
+
+/// Renders entries as separate blocks for laying out in [`columns`]: one
+/// per post, or one per author when `grouped`, numbered as they would be
+/// in a single column.
+pub fn blocks(
+  entries: &[Entry],
+  grouped: bool,
+  width: usize,
+  colored: bool,
+  locale: &Locale,
+  messages: &Messages,
+) -> io::Result<Vec<String>> {
+  // Each column's text is indented like the single-column layout, so wrap
+  // it that much narrower than the column.
+  let options = wrap_options(width.saturating_sub(5));
+  let mut blocks = vec![];
+  let mut number = 1;
+
+  let mut push = |write: &mut dyn FnMut(&mut Vec<u8>) -> io::Result<()>| {
+    let mut block = vec![];
+    write(&mut block)?;
+    blocks.push(String::from_utf8_lossy(&block).into_owned());
+    io::Result::Ok(())
+  };
+
+  if grouped {
+    for group in timeline::group_by_author(entries) {
+      push(&mut |block| {
+        let style = (colored, locale, messages);
+        author_group(block, &group, number, &options, style)
+      })?;
+      number += group.len();
+    }
+  } else {
+    for entry in entries {
+      push(&mut |block| {
+        titled(block, entry, number, &options, colored, messages)
+      })?;
+      number += 1;
+    }
+  }
+
+  Ok(blocks)
+}
+
+/// Writes an entry under its number and the name of its author.
+fn titled(
+  out: &mut impl Write,
+  entry: &Entry,
+  number: usize,
+  options: &textwrap::Options,
+  colored: bool,
+  messages: &Messages,
+) -> io::Result<()> {
+  let label = paint(&entry.author, &entry.author.label(), colored);
+  let mut note = match entry.author.bridge {
+    Some(bridge) => {
+      format!(
+        "  ({})",
+        messages.with("post-bridged", &[("bridge", bridge.to_string().into())])
+      )
+    }
+    None => String::new(),
+  };
+
+  if entry.boosted {
+    note.push_str(&format!("  ({})", messages.get("post-boosted")));
+  }
+
+  if entry.post.is_edited() {
+    note.push_str(&format!("  ({})", messages.get("post-edited")));
+  }
+
+  if let Some(badge) = Length::of(&entry.post).badge() {
+    note.push_str(&format!("  ({})", badge));
+  }
+
+  writeln!(out, "{}{}\n", numbered(number, &label, 15), note)?;
+  body(out, entry, options, colored)
+}
+
+/// A post's number, as in `[3]`, then text right-aligned after it in
+/// `width` terminal columns, or a space after it if there isn't room.
+fn numbered(number: usize, text: &str, width: usize) -> String {
+  let tag = format!("[{}]", number);
+  let padding = width
+    .saturating_sub(display_width(&tag) + display_width(text))
+    .max(1);
+
+  format!("{}{}{}", tag, " ".repeat(padding), text)
+}
+
+/// Writes timeline entries clustered by author: a heading with the author's
+/// name and post count, then each of their posts under its date, both
+/// written as the locale has them, in the language of `messages`.
+pub fn grouped(
+  out: &mut impl Write,
+  entries: &[Entry],
+  width: usize,
+  colored: bool,
+  locale: &Locale,
+  messages: &Messages,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+  let mut number = 1;
+
+  for group in timeline::group_by_author(entries) {
+    let style = (colored, locale, messages);
+    author_group(out, &group, number, &options, style)?;
+    number += group.len();
+  }
+
+  Ok(())
+}
+
+/// Writes timeline entries as a digest for catching up: a line of totals,
+/// then a section for each day or week with each author's posts under their
+/// own heading, as [`grouped`] writes them.
+pub fn digest(
+  out: &mut impl Write,
+  entries: &[Entry],
+  period: Period,
+  width: usize,
+  colored: bool,
+  locale: &Locale,
+  messages: &Messages,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+  let sections = timeline::digest(entries, period);
+  let authors = timeline::group_by_author(entries).len();
+  let spans = sections.iter().filter(|section| section.start.is_some());
+  let span = match period {
+    Period::Daily => count("days", spans.count(), locale, messages),
+    Period::Weekly => count("weeks", spans.count(), locale, messages),
+  };
+  let summary = messages.with(
+    "digest-summary",
+    &[
+      (
+        "posts",
+        count("posts", entries.len(), locale, messages).into(),
+      ),
+      (
+        "accounts",
+        count("accounts", authors, locale, messages).into(),
+      ),
+      ("span", span.into()),
+    ],
+  );
+
+  writeln!(out, "{}\n", summary)?;
+
+  let mut number = 1;
+
+  for section in sections {
+    let heading = match (section.start, period) {
+      (Some(day), Period::Daily) => locale.day(&day),
+      (Some(day), Period::Weekly) => {
+        messages.with("digest-week", &[("day", locale.day(&day).into())])
+      }
+      (None, _) => messages.get("digest-undated"),
+    };
+
+    writeln!(out, "== {} ==\n", heading)?;
+
+    for group in &section.authors {
+      let style = (colored, locale, messages);
+      author_group(out, group, number, &options, style)?;
+      number += group.len();
+    }
+  }
+
+  Ok(())
+}
+
+/// Writes one author's entries under a heading with their name and post
+/// count, and each entry under its number and date, counting from `first`.
+/// `style` is whether to color, and the locale and messages to write in.
+fn author_group(
+  out: &mut impl Write,
+  group: &[&Entry],
+  first: usize,
+  options: &textwrap::Options,
+  (colored, locale, messages): (bool, &Locale, &Messages),
+) -> io::Result<()> {
+  let author = &group[0].author;
+  let posts = count("posts", group.len(), locale, messages);
+  let mut heading = match &author.nickname {
+    Some(nickname) => format!("{} ({}), {}", nickname, author.source, posts),
+    None => format!("{}, {}", author.source, posts),
+  };
+
+  if let Some(bridge) = author.bridge {
+    let bridged =
+      messages.with("post-bridged", &[("bridge", bridge.to_string().into())]);
+    heading.push_str(&format!(", {}", bridged));
+  }
+
+  writeln!(out, "{}\n", paint(author, &heading, colored))?;
+
+  for (index, entry) in group.iter().enumerate() {
+    let date = match entry.published_at() {
+      Some(date) => locale.date(&date),
+      None => entry.published.clone(),
+    };
+    let mut date = numbered(first + index, &date, 20);
+
+    if entry.boosted {
+      date.push_str(&format!("  ({})", messages.get("post-boosted")));
+    }
+
+    if entry.post.is_edited() {
+      date.push_str(&format!("  ({})", messages.get("post-edited")));
+    }
+
+    if let Some(badge) = Length::of(&entry.post).badge() {
+      date.push_str(&format!("  ({})", badge));
+    }
+
+    writeln!(out, "{}\n", date)?;
+    body(out, entry, options, colored)?;
+  }
+
+  Ok(())
+}
+
+/// A count of posts, as in "1 post" or "12 posts".
+fn posts(count: usize, locale: &Locale) -> String {
+  match count {
+    1 => "1 post".to_owned(),
+    count => format!("{} posts", locale.number(count)),
+  }
+}
+
+/// A count of something in the language of `messages`, from its `what`
+/// message, which chooses its form by `$count` and shows `$number`.
+fn count(
+  what: &str,
+  count: usize,
+  locale: &Locale,
+  messages: &Messages,
+) -> String {
+  messages.with(
+    what,
+    &[
+      ("count", count.into()),
+      ("number", locale.number(count).into()),
+    ],
+  )
+}
+
+/// Writes a line saying how many posts were shown, when there are more
+/// than that to see, in the language of `messages`. `total` is how many
+/// there are all told, where that's known.
+pub fn footer(
+  out: &mut impl Write,
+  shown: usize,
+  total: Option<usize>,
+  locale: &Locale,
+  messages: &Messages,
+) -> io::Result<()> {
+  match total {
+    Some(total) if shown < total => {
+      let footer = messages.with(
+        "footer-showing",
+        &[
+          ("shown", locale.number(shown).into()),
+          ("total", count("posts", total, locale, messages).into()),
+        ],
+      );
+
+      writeln!(out, "\n{}", footer)
+    }
+    _ => Ok(()),
+  }
+}
+
+/// Writes timeline entries for reading with a screen reader: each post is
+/// announced with its number, author and date, content warnings are read
+/// out before the post, and attachments are described after it. There's no
+/// indentation, color or markdown to wade through, and emoji are spelled
+/// out by name.
+pub fn screen_reader(
+  out: &mut impl Write,
+  entries: &[Entry],
+  locale: &Locale,
+) -> io::Result<()> {
+  for (index, entry) in entries.iter().enumerate() {
+    let mut heading = format!(
+      "Post {} of {}, {} {}",
+      locale.number(index + 1),
+      locale.number(entries.len()),
+      if entry.boosted { "boosted by" } else { "by" },
+      entry.author.label()
+    );
+
+    if let Some(bridge) = entry.author.bridge {
+      heading.push_str(&format!(", bridged from {}", bridge));
+    }
+
+    if let Some(date) = entry.published_at() {
+      heading.push_str(&format!(", {}", locale.date(&date)));
+    }
+
+    if entry.post.is_edited() {
+      heading.push_str(", edited");
+    }
+
+    let length = Length::of(&entry.post);
+
+    if length.badge().is_some() {
+      heading.push_str(&format!(
+        ", {}{} minute read",
+        if length.is_long() { "long, " } else { "" },
+        locale.number(length.minutes())
+      ));
+    }
+
+    writeln!(out, "{}.", heading)?;
+
+    if let Some(warning) = entry.post.summary.as_deref().map(str::trim) {
+      if !warning.is_empty() {
+        writeln!(out, "Content warning: {}.", spell_out_emoji(warning))?;
+      }
+    }
+
+    writeln!(
+      out,
+      "{}",
+      spell_out_emoji(&plain_text(&entry.post.readable_content()))
+    )?;
+
+    if let Some(kind) =
+      entry.post.kind.as_deref().filter(|_| entry.post.is_media())
+    {
+      match entry.post.duration {
+        Some(duration) => writeln!(out, "{}, {} long.", kind, clock(duration))?,
+        None => writeln!(out, "{}.", kind)?,
+      }
+    }
+
+    for attachment in &entry.post.attachment {
+      if attachment.hidden {
+        writeln!(out, "{} attached, marked sensitive.", attachment.kind())?;
+        continue;
+      }
+
+      let kind = match attachment.details() {
+        Some(details) => format!("{} ({})", attachment.kind(), details),
+        None => attachment.kind().to_owned(),
+      };
+
+      match attachment.alt_text() {
+        Some(alt) => {
+          writeln!(out, "{} attached: {}", kind, spell_out_emoji(alt))?
+        }
+        None => writeln!(out, "{} attached, with no description.", kind)?,
+      }
+    }
+
+    writeln!(out)?;
+  }
+
+  Ok(())
+}
+
+/// Replaces each emoji with its name, in parentheses.
+fn spell_out_emoji(text: &str) -> String {
+  text
+    .graphemes(true)
+    .map(|grapheme| match emojis::get(grapheme) {
+      Some(emoji) => format!("({})", emoji.name()),
+      None => grapheme.to_owned(),
+    })
+    .collect()
+}
+
+/// Columns narrower than this aren't worth reading, so layouts fall back to
+/// a single column rather than use them.
+pub const MIN_COLUMN_WIDTH: usize = 30;
+
+const GUTTER: &str = "   ";
+
+/// How wide each of `count` columns can be across `width` characters, or
+/// `None` if that would leave them too narrow to read.
+pub fn column_width(width: usize, count: usize) -> Option<usize> {
+  let gutters = GUTTER.len() * count.checked_sub(1)?;
+  let column = width.checked_sub(gutters)? / count;
+
+  (column >= MIN_COLUMN_WIDTH).then_some(column)
+}
+
+/// Lays already-rendered blocks of text out in newspaper-style columns: they
+/// run down the first column, then carry on at the top of the next. Blocks
+/// are kept whole, so a post never starts at the foot of one column and
+/// finishes in the next.
+pub fn columns(
+  out: &mut impl Write,
+  blocks: &[String],
+  width: usize,
+  count: usize,
+) -> io::Result<()> {
+  let total: usize = blocks.iter().map(|block| block.lines().count()).sum();
+  let target = total.div_ceil(count.max(1));
+  let mut columns: Vec<Vec<&str>> = vec![vec![]];
+
+  for block in blocks {
+    let lines: Vec<_> = block.lines().collect();
+    let room = columns.len() < count;
+    let column = columns.last_mut().expect("there's always a column");
+
+    if room && !column.is_empty() && column.len() + lines.len() > target {
+      columns.push(lines);
+    } else {
+      column.extend(lines);
+    }
+  }
+
+  let height = columns.iter().map(Vec::len).max().unwrap_or_default();
+
+  for row in 0..height {
+    let mut line = String::new();
+
+    for (index, column) in columns.iter().enumerate() {
+      let cell = column.get(row).copied().unwrap_or_default();
+      // Lines too wide for their column would push the next one out of
+      // line; the last column has nothing after it to push.
+      let cell = match index + 1 < columns.len() {
+        true => truncate(cell, width),
+        false => Cow::Borrowed(cell),
+      };
+
+      if index > 0 {
+        line.push_str(GUTTER);
+      }
+
+      line.push_str(&cell);
+
+      let padding = width.saturating_sub(display_width(&cell));
+      line.extend(std::iter::repeat_n(' ', padding));
+    }
+
+    writeln!(out, "{}", line.trim_end())?;
+  }
+
+  Ok(())
+}
+
+/// How post text is wrapped: only at spaces, so never inside a word (or a
+/// url) even one too long for the line, which overflows instead. Left to
+/// itself textwrap also breaks at hyphens and slashes, which urls are full
+/// of. Chinese and Japanese, which are written without spaces, can break
+/// between any two characters.
+fn wrap_options(width: usize) -> textwrap::Options<'static> {
+  textwrap::Options::new(width)
+    .break_words(false)
+    .word_separator(textwrap::WordSeparator::Custom(words))
+    .word_splitter(textwrap::WordSplitter::NoHyphenation)
+}
+
+/// A line's words, split at spaces and either side of Chinese and Japanese
+/// characters.
+fn words(line: &str) -> Box<dyn Iterator<Item = Word<'_>> + '_> {
+  let mut words = vec![];
+
+  for word in textwrap::WordSeparator::AsciiSpace.find_words(line) {
+    let from = offset(line, word.word);
+    let text = &line[from..from + word.word.len() + word.whitespace.len()];
+    let mut start = 0;
+
+    for (at, c) in text.char_indices() {
+      if at < start || !is_cjk(c) {
+        continue;
+      }
+
+      if start < at {
+        words.push(Word::from(&text[start..at]));
+      }
+
+      // Any spaces after the character go with it.
+      let end = at + c.len_utf8();
+      start = text.len() - text[end..].trim_start_matches(' ').len();
+      words.push(Word::from(&text[at..start]));
+    }
+
+    if start < text.len() {
+      words.push(Word::from(&text[start..]));
+    }
+  }
+
+  Box::new(words.into_iter())
+}
+
+/// Where a slice of `line` starts in it.
+fn offset(line: &str, part: &str) -> usize {
+  part.as_ptr() as usize - line.as_ptr() as usize
+}
+
+/// Whether a character is Chinese or Japanese, which lines can break either
+/// side of. Korean is written with spaces, so it breaks at those.
+fn is_cjk(c: char) -> bool {
+  matches!(
+    c,
+    '\u{2E80}'..='\u{A4CF}'
+      | '\u{F900}'..='\u{FAFF}'
+      | '\u{FE30}'..='\u{FE4F}'
+      | '\u{FF00}'..='\u{FF60}'
+      | '\u{FFE0}'..='\u{FFE6}'
+      | '\u{20000}'..='\u{3FFFD}'
+  )
+}
+
+/// How many terminal columns text takes up, counting wide characters such
+/// as CJK and most emoji as two.
+fn display_width(text: &str) -> usize {
+  textwrap::core::display_width(text)
+}
+
+/// Text right-aligned in `width` terminal columns, as `{:>width}` would
+/// align it if every character were one column wide.
+fn align_right(text: &str, width: usize) -> String {
+  let padding = width.saturating_sub(display_width(text));
+
+  format!("{}{}", " ".repeat(padding), text)
+}
+
+/// Text cut down to `width` terminal columns, ending in `…` where it was
+/// cut.
+fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+  if display_width(text) <= width {
+    return Cow::Borrowed(text);
+  }
+
+  let mut cut = String::new();
+  let mut used = 0;
+  let mut chars = text.chars();
+
+  while let Some(c) = chars.next() {
+    // Colors take up no room, and mustn't be cut in half.
+    if c == '\x1b' {
+      cut.push(c);
+      cut.extend(chars.by_ref().take_while(|&c| c != 'm'));
+      cut.push('m');
+      continue;
+    }
+
+    let size = display_width(c.encode_utf8(&mut [0; 4]));
+
+    if used + size + 1 > width {
+      break;
+    }
+
+    cut.push(c);
+    used += size;
+  }
+
+  if cut.contains('\x1b') {
+    cut.push_str("\x1b[0m");
+  }
+
+  cut.push('…');
+  Cow::Owned(cut)
+}
+
+fn paint(author: &Author, text: &str, colored: bool) -> String {
+  match author.color {
+    Some(color) if colored => color.paint(text),
+    _ => text.to_owned(),
+  }
+}
+
+fn body(
+  out: &mut impl Write,
+  entry: &Entry,
+  options: &textwrap::Options,
+  colored: bool,
+) -> io::Result<()> {
+  let mut languages = code_languages(&entry.post.content).into_iter();
+
+  for segment in segments(&entry.post.markdown_content()) {
+    let lines = match segment {
+      Segment::Prose(text) => prose_lines(&text, options),
+      Segment::Code(code) => {
+        code_lines(&code, languages.next().flatten().as_deref(), colored)
+      }
+    };
+
+    for line in lines {
+      writeln!(out, "     {}", line)?;
+    }
+  }
+
+  writeln!(out)?;
+
+  // A PeerTube video or a podcast episode is the post itself, rather than
+  // an attachment, but reads best described as one.
+  if let Some(kind) =
+    entry.post.kind.as_deref().filter(|_| entry.post.is_media())
+  {
+    let reference = match entry.post.duration {
+      Some(duration) => format!("{} ({})", kind, clock(duration)),
+      None => kind.to_owned(),
+    };
+
+    for line in textwrap::wrap(&reference, options) {
+      writeln!(out, "     {}", line)?;
+    }
+
+    if entry.post.attachment.is_empty() {
+      writeln!(out)?;
+    }
+  }
+
+  if entry.post.attachment.is_empty() {
+    return Ok(());
+  }
+
+  // Alt text sits under the attachment it describes, indented a little
+  // further so it reads as belonging to it.
+  let alt_options = options
+    .clone()
+    .initial_indent("       ")
+    .subsequent_indent("       ");
+
+  for attachment in &entry.post.attachment {
+    if attachment.hidden {
+      let blur = attachment.blurhash.as_deref().and_then(blurhash::blocks);
+
+      for line in blur.filter(|_| colored).into_iter().flatten() {
+        writeln!(out, "       {}", line)?;
+      }
+
+      writeln!(
+        out,
+        "       {} marked sensitive; --reveal-sensitive shows it",
+        attachment.kind()
+      )?;
+      continue;
+    }
+
+    let url = attachment.url.as_deref().unwrap_or_default();
+    let reference = match attachment.details() {
+      Some(details) => format!("{} ({}): {}", attachment.kind(), details, url),
+      None => format!("{}: {}", attachment.kind(), url),
+    };
+
+    for line in textwrap::wrap(&reference, options) {
+      writeln!(out, "     {}", line)?;
+    }
+
+    for line in attachment.thumbnail.iter().flatten().filter(|_| colored) {
+      writeln!(out, "       {}", line)?;
+    }
+
+    if let Some(saved) = &attachment.saved {
+      writeln!(out, "       saved as {}", saved.display())?;
+    }
+
+    match attachment.alt_text() {
+      Some(alt) => {
+        for line in textwrap::wrap(alt, &alt_options) {
+          writeln!(out, "{}", line)?;
+        }
+      }
+      None => writeln!(out, "       (no alt text)")?,
+    }
+  }
+
+  writeln!(out)
+}
+
+/// A stretch of a post's markdown: either prose to wrap, or a fenced code
+/// block to leave as it is.
+enum Segment {
+  Prose(String),
+  Code(String),
+}
+
+/// Splits markdown into prose and code blocks. Each code block keeps its
+/// fences, which are all that mark it out when it isn't colored.
+fn segments(markdown: &str) -> Vec<Segment> {
+  let mut segments = vec![];
+  let mut prose: Vec<&str> = vec![];
+  let mut code: Option<Vec<&str>> = None;
+
+  for line in markdown.lines() {
+    let fence = line.trim_start().starts_with("```");
+
+    match (&mut code, fence) {
+      (Some(lines), true) => {
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+          lines.pop();
+        }
+
+        lines.push(line);
+        segments.push(Segment::Code(lines.join("\n")));
+        code = None;
+      }
+      (Some(lines), false) => lines.push(line),
+      (None, true) => {
+        if !prose.is_empty() {
+          segments.push(Segment::Prose(prose.join("\n")));
+          prose.clear();
+        }
+
+        code = Some(vec![line]);
+      }
+      (None, false) => prose.push(line),
+    }
+  }
+
+  // An unclosed fence is just prose.
+  prose.extend(code.into_iter().flatten());
+
+  if !prose.is_empty() {
+    segments.push(Segment::Prose(prose.join("\n")));
+  }
+
+  segments
+}
+
+/// Bullets for each level of a nested list, from the outside in.
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
+/// Wraps markdown prose. Blockquotes keep their `>` gutter on every line
+/// they wrap onto, and list items hang their wrapped lines under the text
+/// rather than the bullet, with bullets changing at each level of nesting.
+/// Lines of paragraphs with right-to-left text start with a mark saying
+/// which way they run.
+fn prose_lines(text: &str, options: &textwrap::Options) -> Vec<String> {
+  let mut lines = vec![];
+
+  for line in tidy_quotes(text) {
+    let (depth, rest) = quoted(line);
+    let gutter = "> ".repeat(depth);
+    let indent = rest.len() - rest.trim_start().len();
+    let rest = rest.trim_start();
+
+    if rest.is_empty() {
+      lines.push(gutter.trim_end().to_owned());
+      continue;
+    }
+
+    let rest = unescape_urls(rest);
+    let (marker, content) = match list_marker(&rest) {
+      Some((marker, content)) if marker.ends_with('.') => {
+        (format!("{} ", marker), content)
+      }
+      Some((_, content)) => (
+        format!("{} ", BULLETS[(indent / 2) % BULLETS.len()]),
+        content,
+      ),
+      None => (String::new(), rest.as_str()),
+    };
+    let initial = format!("{}{}{}", gutter, " ".repeat(indent), marker);
+    let hanging =
+      format!("{}{}", gutter, " ".repeat(indent + display_width(&marker)));
+    let options = options
+      .clone()
+      .initial_indent(&initial)
+      .subsequent_indent(&hanging);
+
+    let mark = bidi::mark(content);
+
+    lines.extend(textwrap::wrap(content, &options).into_iter().map(|line| {
+      match mark {
+        Some(mark) => format!("{}{}", mark, line),
+        None => line.into_owned(),
+      }
+    }));
+  }
+
+  lines
+}
+
+/// Markdown with the backslashes it escapes `_`s and `*`s with taken back
+/// out of bare urls, so they can be copied as they are.
+fn unescape_urls(text: &str) -> String {
+  text
+    .split(' ')
+    .map(|word| match word.contains("://") {
+      true => {
+        let mut unescaped = String::with_capacity(word.len());
+        let mut chars = word.chars().peekable();
+
+        while let Some(c) = chars.next() {
+          let escaping = chars.peek().is_some_and(char::is_ascii_punctuation);
+
+          if !(c == '\\' && escaping) {
+            unescaped.push(c);
+          }
+        }
+
+        unescaped
+      }
+      false => word.to_owned(),
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Markdown's lines with the empty `>` lines html2md leaves around and
+/// between quoted paragraphs cut down to one between paragraphs, and none
+/// at either end of a quote.
+fn tidy_quotes(text: &str) -> Vec<&str> {
+  let lines: Vec<_> = text.split('\n').collect();
+  let content = |line: &&&str| !quoted(line).1.trim().is_empty();
+  let mut tidied = vec![];
+
+  for (index, line) in lines.iter().enumerate() {
+    let (depth, rest) = quoted(line);
+
+    if depth == 0 || !rest.trim().is_empty() {
+      tidied.push(*line);
+      continue;
+    }
+
+    let before = lines[..index].iter().rev().find(content);
+    let after = lines[index + 1..].iter().find(content);
+    let inside = [before, after]
+      .iter()
+      .all(|line| line.is_some_and(|line| quoted(line).0 > 0));
+    let repeated = tidied.last().is_some_and(|last: &&str| {
+      let (depth, rest) = quoted(last);
+      depth > 0 && rest.trim().is_empty()
+    });
+
+    if inside && !repeated {
+      tidied.push(*line);
+    }
+  }
+
+  tidied
+}
+
+/// How deeply a markdown line is quoted, and the rest of it.
+fn quoted(line: &str) -> (usize, &str) {
+  let mut depth = 0;
+  let mut rest = line;
+
+  while let Some(inner) = rest.strip_prefix('>') {
+    depth += 1;
+    rest = inner.strip_prefix(' ').unwrap_or(inner);
+  }
+
+  (depth, rest)
+}
+
+/// A markdown list item's marker, as in `*` or `2.`, and its text.
+fn list_marker(line: &str) -> Option<(&str, &str)> {
+  let (marker, content) = line.split_once(' ')?;
+  let bullet = matches!(marker, "*" | "-" | "+");
+  let number = marker
+    .strip_suffix('.')
+    .is_some_and(|number| number.chars().all(|c| c.is_ascii_digit()))
+    && marker.len() > 1;
+
+  (bullet || number).then_some((marker, content.trim_start()))
+}
+
+/// The language of each of a post's code blocks, in order, from the
+/// `language-*` class servers put on the `<code>` inside a `<pre>`.
+fn code_languages(html: &str) -> Vec<Option<String>> {
+  let document = Html::parse_fragment(html);
+  let blocks = Selector::parse("pre").expect("the selector is valid");
+  let code = Selector::parse("code").expect("the selector is valid");
+
+  document
+    .select(&blocks)
+    .map(|block| {
+      block.select(&code).next().and_then(|code| {
+        code
+          .value()
+          .classes()
+          .find_map(|class| class.strip_prefix("language-"))
+          .map(str::to_owned)
+      })
+    })
+    .collect()
+}
+
+/// The lines of a fenced code block, colored by language when `colored` is
+/// set and apread was built with highlighting.
+#[cfg_attr(not(feature = "highlight"), allow(unused_variables))]
+fn code_lines(
+  code: &str,
+  language: Option<&str>,
+  colored: bool,
+) -> Vec<String> {
+  let (fence, rest) = code.split_once('\n').unwrap_or((code, ""));
+  let (inner, closing) = rest.rsplit_once('\n').unwrap_or(("", rest));
+
+  #[cfg(feature = "highlight")]
+  if colored {
+    if let Some(lines) = crate::highlight::lines(inner, language) {
+      return std::iter::once(fence.to_owned())
+        .chain(lines)
+        .chain(std::iter::once(closing.to_owned()))
+        .collect();
+    }
+  }
+
+  code.lines().map(str::to_owned).collect()
+}
+
+/// A timeline entry as it appears in JSON output.
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+  author: String,
+  nickname: Option<&'a str>,
+  bridge: Option<Bridge>,
+  id: Option<&'a str>,
+  url: Option<&'a str>,
+  published: &'a str,
+  summary: Option<&'a str>,
+  content: &'a str,
+  markdown: String,
+  words: usize,
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  boosted: bool,
+}
+
+impl<'a> JsonEntry<'a> {
+  fn new(entry: &'a Entry) -> Self {
+    Self {
+      author: entry.author.source.to_string(),
+      nickname: entry.author.nickname.as_deref(),
+      bridge: entry.author.bridge,
+      id: entry.post.id.as_deref(),
+      url: entry.post.url.as_deref(),
+      published: &entry.published,
+      summary: entry.post.summary.as_deref().filter(|cw| !cw.is_empty()),
+      content: &entry.post.content,
+      markdown: entry.post.markdown_content(),
+      words: Length::of(&entry.post).words,
+      boosted: entry.boosted,
+    }
+  }
+}
+
+/// Writes timeline entries as a JSON array, one object per post, with the
+/// content both as the original html and as markdown.
+pub fn json(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+  let entries: Vec<_> = entries.iter().map(JsonEntry::new).collect();
+
+  serde_json::to_writer_pretty(&mut *out, &entries)?;
+  writeln!(out)
+}
+
+/// Writes one timeline entry as a JSON object on a single line, as it
+/// appears in [`json`]'s array.
+pub fn json_entry(out: &mut impl Write, entry: &Entry) -> io::Result<()> {
+  serde_json::to_writer(&mut *out, &JsonEntry::new(entry))?;
+  writeln!(out)
+}
+
+/// A timeline entry as templates see it: everything in its JSON form, along
+/// with its author's label, its plain text and its tags.
+#[derive(Serialize)]
+struct TemplateEntry<'a> {
+  #[serde(flatten)]
+  entry: JsonEntry<'a>,
+  label: String,
+  text: String,
+  html: String,
+  tags: Vec<&'a str>,
+}
+
+/// Writes each timeline entry through a [minijinja] template, one after
+/// another. Templates see the fields of the JSON output, along with
+/// `label` (the author's name as the terminal shows it), `text` (the
+/// content as plain text), `html` (the content with mentions linked as
+/// full handles, and right-to-left paragraphs marked `dir="rtl"`) and
+/// `tags` (hashtags, without their `#`).
+///
+/// [minijinja]: https://docs.rs/minijinja
+pub fn template(
+  out: &mut impl Write,
+  entries: &[Entry],
+  source: &str,
+) -> Result<(), ApreadErrors> {
+  let mut environment = Environment::new();
+  environment.set_keep_trailing_newline(true);
+  environment.add_template("post", source)?;
+
+  let template = environment.get_template("post")?;
+
+  for entry in entries {
+    let context = TemplateEntry {
+      entry: JsonEntry::new(entry),
+      label: entry.author.label(),
+      text: plain_text(&entry.post.readable_content()),
+      html: bidi::directed(&mention::linked(&entry.post)),
+      tags: entry.post.hashtags(),
+    };
+
+    write!(out, "{}", template.render(context)?)?;
+  }
+
+  Ok(())
+}
+
+/// Writes timeline entries as CSV, with a header row and the columns `id`,
+/// `handle`, `published`, `url`, `content_plaintext` and `tags`. Tags are
+/// space-separated, without their `#`.
+pub fn csv(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+  let mut writer = csv::Writer::from_writer(out);
+
+  writer.write_record([
+    "id",
+    "handle",
+    "published",
+    "url",
+    "content_plaintext",
+    "tags",
+  ])?;
+
+  for entry in entries {
+    writer.write_record([
+      entry.post.id.as_deref().unwrap_or_default(),
+      &entry.author.source.to_string(),
+      &entry.published,
+      entry.post.url.as_deref().unwrap_or_default(),
+      &plain_text(&entry.post.readable_content()),
+      &entry.post.hashtags().join(" "),
+    ])?;
+  }
+
+  writer.flush()
+}
+
+/// Writes timeline entries as a standalone html page, each post an
+/// `<article>` under its author and date, and any content warning a
+/// `<details>` to open. Posts' html is sanitized, so a post can't run
+/// scripts in the page.
+pub fn html(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+  writeln!(
+    out,
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+     <title>apread</title>\n</head>\n<body>"
+  )?;
+
+  for entry in entries {
+    let content =
+      ammonia::clean(&bidi::directed(&mention::linked(&entry.post)));
+    let date = match &entry.post.url {
+      Some(url) => format!(
+        "<a href=\"{}\"><time datetime=\"{}\">{}</time></a>",
+        attribute(url),
+        attribute(&entry.published),
+        escape(&entry.published)
+      ),
+      None => format!(
+        "<time datetime=\"{}\">{}</time>",
+        attribute(&entry.published),
+        escape(&entry.published)
+      ),
+    };
+
+    writeln!(out, "<article>")?;
+    writeln!(
+      out,
+      "<header><strong>{}</strong> {}{}</header>",
+      escape(&entry.author.label()),
+      date,
+      if entry.boosted { " (boosted)" } else { "" }
+    )?;
+
+    match entry.post.summary.as_deref().filter(|cw| !cw.is_empty()) {
+      Some(warning) => writeln!(
+        out,
+        "<details><summary>{}</summary>\n{}\n</details>",
+        escape(warning),
+        content
+      )?,
+      None => writeln!(out, "{}", content)?,
+    }
+
+    writeln!(out, "</article>")?;
+  }
+
+  writeln!(out, "</body>\n</html>")
+}
+
+/// Writes timeline entries as an RSS 2.0 feed, an `<item>` per post with
+/// its html as the description, for reading in a feed reader.
+pub fn rss(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+  writeln!(
+    out,
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n\
+     <channel>\n<title>apread</title>\n<link>{}</link>\n\
+     <description>Posts read with apread</description>",
+    env!("CARGO_PKG_HOMEPAGE")
+  )?;
+
+  for entry in entries {
+    writeln!(out, "<item>")?;
+    writeln!(out, "<title>{}</title>", escape(&item_title(entry)))?;
+
+    if let Some(url) = &entry.post.url {
+      writeln!(out, "<link>{}</link>", escape(url))?;
+    }
+
+    writeln!(
+      out,
+      "<guid isPermaLink=\"false\">{}</guid>",
+      escape(&entry.key())
+    )?;
+
+    if let Some(date) = entry.published_at() {
+      writeln!(out, "<pubDate>{}</pubDate>", date.to_rfc2822())?;
+    }
+
+    writeln!(
+      out,
+      "<description>{}</description>",
+      escape(&mention::linked(&entry.post))
+    )?;
+    writeln!(out, "</item>")?;
+  }
+
+  writeln!(out, "</channel>\n</rss>")
+}
+
+/// Writes an RSS 2.0 feed of the posts with audio attached, each audio
+/// file an enclosure, as podcast apps read them. A feed of one account's
+/// posts is titled for the account.
+pub fn podcast(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+  let episodes: Vec<(&Entry, &Attachment)> = entries
+    .iter()
+    .filter_map(|entry| {
+      let audio = entry.post.attachment.iter().find(|attachment| {
+        attachment.kind() == "Audio" && attachment.url.is_some()
+      })?;
+
+      Some((entry, audio))
+    })
+    .collect();
+  let authors: BTreeSet<String> = episodes
+    .iter()
+    .map(|(entry, _)| entry.author.label())
+    .collect();
+  let title = match authors.len() {
+    1 => authors.into_iter().next().unwrap_or_default(),
+    _ => "apread".to_owned(),
+  };
+
+  writeln!(
+    out,
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\" \
+     xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">\n\
+     <channel>\n<title>{title}</title>\n<link>{}</link>\n\
+     <description>Audio posted by {title}, read with apread</description>\n\
+     <itunes:author>{title}</itunes:author>",
+    env!("CARGO_PKG_HOMEPAGE"),
+    title = escape(&title),
+  )?;
+
+  for (entry, audio) in episodes {
+    let url = audio.url.as_deref().unwrap_or_default();
+    // Podcast apps want the file's size up front, which is only known of
+    // files that have been downloaded.
+    let length = audio
+      .saved
+      .as_ref()
+      .and_then(|saved| saved.metadata().ok())
+      .map_or(0, |metadata| metadata.len());
+
+    writeln!(out, "<item>")?;
+    writeln!(out, "<title>{}</title>", escape(&item_title(entry)))?;
+
+    if let Some(page) = &entry.post.url {
+      writeln!(out, "<link>{}</link>", escape(page))?;
+    }
+
+    writeln!(
+      out,
+      "<guid isPermaLink=\"false\">{}</guid>",
+      escape(&entry.key())
+    )?;
+
+    if let Some(date) = entry.published_at() {
+      writeln!(out, "<pubDate>{}</pubDate>", date.to_rfc2822())?;
+    }
+
+    writeln!(
+      out,
+      "<enclosure url=\"{}\" length=\"{}\" type=\"{}\"/>",
+      attribute(url),
+      length,
+      attribute(&audio_type(audio))
+    )?;
+
+    if let Some(duration) = audio.duration {
+      writeln!(
+        out,
+        "<itunes:duration>{}</itunes:duration>",
+        clock(duration)
+      )?;
+    }
+
+    writeln!(
+      out,
+      "<description>{}</description>",
+      escape(&mention::linked(&entry.post))
+    )?;
+    writeln!(out, "</item>")?;
+  }
+
+  writeln!(out, "</channel>\n</rss>")
+}
+
+/// Writes an iCalendar file of the events among posts which haven't ended
+/// by `now`, as calendar apps import them. An event which doesn't say when
+/// it ends is taken to end when it starts.
+pub fn ics(
+  out: &mut impl Write,
+  entries: &[Entry],
+  now: DateTime<Utc>,
+) -> io::Result<()> {
+  let stamp = ics_time(&now);
+
+  write!(
+    out,
+    "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//apread//apread {}//EN\r\n",
+    env!("CARGO_PKG_VERSION")
+  )?;
+
+  for entry in entries.iter().filter(|entry| entry.post.is_event()) {
+    let (Some(start), end) = (entry.post.starts_at(), entry.post.ends_at())
+    else {
+      continue;
+    };
+
+    if end.unwrap_or(start) < now {
+      continue;
+    }
+
+    let mut lines = vec![
+      "BEGIN:VEVENT".to_owned(),
+      format!("UID:{}", ics_text(&entry.key())),
+      format!("DTSTAMP:{}", stamp),
+      format!("DTSTART:{}", ics_time(&start)),
+    ];
+
+    if let Some(end) = end {
+      lines.push(format!("DTEND:{}", ics_time(&end)));
+    }
+
+    let summary = match entry.post.name.as_deref() {
+      Some(name) => name.to_owned(),
+      None => item_title(entry),
+    };
+
+    lines.push(format!("SUMMARY:{}", ics_text(&summary)));
+
+    let description = plain_text(&entry.post.readable_content());
+
+    if !description.trim().is_empty() {
+      lines.push(format!("DESCRIPTION:{}", ics_text(description.trim())));
+    }
+
+    if let Some(place) = entry
+      .post
+      .location
+      .as_ref()
+      .and_then(|place| place.name.as_deref())
+    {
+      lines.push(format!("LOCATION:{}", ics_text(place)));
+    }
+
+    if let Some(url) = &entry.post.url {
+      lines.push(format!("URL:{}", url));
+    }
+
+    lines.push("END:VEVENT".to_owned());
+
+    for line in lines {
+      write!(out, "{}\r\n", ics_fold(&line))?;
+    }
+  }
+
+  write!(out, "END:VCALENDAR\r\n")
+}
+
+/// A time as iCalendar writes it, in UTC: `20240601T180000Z`.
+fn ics_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+  time
+    .with_timezone(&Utc)
+    .format("%Y%m%dT%H%M%SZ")
+    .to_string()
+}
+
+/// Escapes text for an iCalendar property's value.
+fn ics_text(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace(';', "\\;")
+    .replace(',', "\\,")
+    .replace("\r\n", "\\n")
+    .replace('\n', "\\n")
+}
+
+/// Folds an iCalendar line into lines of at most 75 bytes, each after the
+/// first starting with a space, never splitting a character.
+fn ics_fold(line: &str) -> String {
+  let mut folded = String::with_capacity(line.len());
+  let mut width = 0;
+
+  for c in line.chars() {
+    if width + c.len_utf8() > 75 {
+      folded.push_str("\r\n ");
+      width = 1;
+    }
+
+    folded.push(c);
+    width += c.len_utf8();
+  }
+
+  folded
+}
+
+/// A feed item's title: its author, and its content warning or the start
+/// of its text.
+fn item_title(entry: &Entry) -> String {
+  let text = plain_text(&entry.post.readable_content());
+
+  match entry.post.summary.as_deref().filter(|cw| !cw.is_empty()) {
+    Some(warning) => format!("{}: {}", entry.author.label(), warning),
+    None => format!("{}: {}", entry.author.label(), truncate(text.trim(), 60)),
+  }
+}
+
+/// The media type of an audio file, guessed from its url's extension when
+/// all that's known is that it's audio, as the Mastodon API has it.
+fn audio_type(audio: &Attachment) -> String {
+  if let Some(media_type) = audio
+    .media_type
+    .as_deref()
+    .filter(|mime| !mime.contains('*'))
+  {
+    return media_type.to_owned();
+  }
+
+  let url = audio.url.as_deref().unwrap_or_default();
+  let extension = url
+    .split(['?', '#'])
+    .next()
+    .and_then(|path| path.rsplit_once('.'))
+    .map(|(_, extension)| extension.to_ascii_lowercase());
+
+  match extension.as_deref() {
+    Some("ogg" | "oga" | "opus") => "audio/ogg",
+    Some("m4a" | "aac") => "audio/mp4",
+    Some("wav") => "audio/wav",
+    Some("flac") => "audio/flac",
+    _ => "audio/mpeg",
+  }
+  .to_owned()
+}
+
+/// Escapes text for a quoted html attribute.
+fn attribute(text: &str) -> String {
+  escape(text).replace('"', "&quot;")
+}
+
+/// Writes an audit report: each post with problems, under its url and
+/// date, then how many posts were checked.
+pub fn audit(
+  out: &mut impl Write,
+  report: &Report,
+  locale: &Locale,
+) -> io::Result<()> {
+  for finding in &report.findings {
+    let date = DateTime::parse_from_rfc3339(&finding.published)
+      .map(|date| locale.date(&date))
+      .unwrap_or_else(|_| finding.published.clone());
+
+    writeln!(out, "{}  ({})", finding.post, date)?;
+
+    for problem in &finding.problems {
+      writeln!(out, "     {}", problem)?;
+    }
+
+    writeln!(out)?;
+  }
+
+  let posts = match report.checked {
+    1 => "1 post".to_owned(),
+    count => format!("{} posts", locale.number(count)),
+  };
+
+  writeln!(
+    out,
+    "Checked {}: {} with problems.",
+    posts,
+    locale.number(report.findings.len())
+  )
+}
+
+/// Writes an audit report as JSON.
+pub fn audit_json(out: &mut impl Write, report: &Report) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, report)?;
+  writeln!(out)
+}
+
+/// Writes an audit report as CSV, one row per problem, with the columns
+/// `post`, `published`, `problem` and `detail`.
+pub fn audit_csv(out: &mut impl Write, report: &Report) -> io::Result<()> {
+  let mut writer = csv::Writer::from_writer(out);
+
+  writer.write_record(["post", "published", "problem", "detail"])?;
+
+  for finding in &report.findings {
+    for problem in &finding.problems {
+      let (name, detail) = match problem {
+        Problem::MissingAlt { url, .. } => {
+          ("missing_alt", url.clone().unwrap_or_default())
+        }
+        Problem::MissingWarning { keyword } => {
+          ("missing_warning", keyword.clone())
+        }
+        Problem::BrokenLink { url, .. } => ("broken_link", url.clone()),
+      };
+
+      writer.write_record([
+        &finding.post,
+        &finding.published,
+        name,
+        &detail,
+      ])?;
+    }
+  }
+
+  writer.flush()
+}
+
+/// Writes the links an account has posted, each with how many posts it's
+/// in and when it was first posted. Checked links which are broken say so,
+/// and a count of them follows.
+pub fn links(
+  out: &mut impl Write,
+  links: &[Link],
+  locale: &Locale,
+) -> io::Result<()> {
+  for link in links {
+    let date = DateTime::parse_from_rfc3339(&link.first_seen)
+      .map(|date| locale.date(&date))
+      .unwrap_or_else(|_| link.first_seen.clone());
+
+    write!(
+      out,
+      "{:>5}  {}  {}",
+      locale.number(link.count),
+      date,
+      link.url
+    )?;
+
+    match &link.health {
+      Some(health) if health.is_broken() => {
+        writeln!(out, "  (dead: {})", health)?
+      }
+      _ => writeln!(out)?,
+    }
+  }
+
+  if links.iter().any(|link| link.health.is_some()) {
+    let dead = links
+      .iter()
+      .filter(|link| link.health.as_ref().is_some_and(Health::is_broken))
+      .count();
+
+    writeln!(
+      out,
+      "\nChecked {} links: {} dead.",
+      locale.number(links.len()),
+      locale.number(dead)
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes links as JSON.
+pub fn links_json(out: &mut impl Write, links: &[Link]) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, links)?;
+  writeln!(out)
+}
+
+/// Writes links as CSV, with the columns `url`, `count`, `first_seen` and
+/// `status`. The status is empty for links which weren't checked.
+pub fn links_csv(out: &mut impl Write, links: &[Link]) -> io::Result<()> {
+  let mut writer = csv::Writer::from_writer(out);
+
+  writer.write_record(["url", "count", "first_seen", "status"])?;
+
+  for link in links {
+    let status = match &link.health {
+      Some(Health::Alive { code }) | Some(Health::Dead { code }) => {
+        code.to_string()
+      }
+      Some(Health::Unreachable { reason }) => reason.clone(),
+      None => String::new(),
+    };
+
+    writer.write_record([
+      &link.url,
+      &link.count.to_string(),
+      &link.first_seen,
+      &status,
+    ])?;
+  }
+
+  writer.flush()
+}
+
+/// Writes a diagnostics report, one check to a line.
+pub fn doctor(out: &mut impl Write, checks: &[Check]) -> io::Result<()> {
+  let width = checks.iter().map(|check| check.name.len()).max();
+
+  for check in checks {
+    writeln!(
+      out,
+      "[{:<4}] {:<width$}  {}",
+      check.status.to_string(),
+      check.name,
+      check.detail,
+      width = width.unwrap_or_default()
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes what linting the document at `url` found, one issue a line.
+pub fn lint(
+  out: &mut impl Write,
+  url: &str,
+  issues: &[Issue],
+) -> io::Result<()> {
+  if issues.is_empty() {
+    return writeln!(out, "No problems found with {}", url);
+  }
+
+  writeln!(out, "{}\n", url)?;
+
+  for issue in issues {
+    writeln!(out, "  {}", issue)?;
+  }
+
+  Ok(())
+}
+
+/// Writes bookmarked posts, each under its author and followed by its tags
+/// and note.
+pub fn bookmarks(
+  out: &mut impl Write,
+  bookmarks: &[Bookmark],
+  width: usize,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+
+  for bookmark in bookmarks {
+    let post = &bookmark.post;
+    let author = post.author.as_deref().unwrap_or("unknown");
+
+    writeln!(out, "{}\n", align_right(author, 15))?;
+
+    for line in textwrap::wrap(&post.markdown_content(), &options) {
+      writeln!(out, "     {}", line)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "     {}", post.url.as_deref().unwrap_or(&post.id))?;
+
+    if !bookmark.tags.is_empty() {
+      let tags: Vec<_> = bookmark
+        .tags
+        .iter()
+        .map(|tag| format!("#{}", tag))
+        .collect();
+      writeln!(out, "     tags: {}", tags.join(" "))?;
+    }
+
+    if let Some(note) = &bookmark.note {
+      writeln!(out, "     note: {}", note)?;
+    }
+
+    writeln!(out)?;
+  }
+
+  Ok(())
+}
+
+/// Writes each revision of a post, oldest first, under when it was written.
+pub fn history(
+  out: &mut impl Write,
+  revisions: &[Revision],
+  width: usize,
+  locale: &Locale,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+
+  for (index, revision) in revisions.iter().enumerate() {
+    let date = revision.edited_at.as_deref().map(|edited_at| {
+      match DateTime::parse_from_rfc3339(edited_at) {
+        Ok(date) => locale.date(&date),
+        Err(_) => edited_at.to_owned(),
+      }
+    });
+    let heading = format!(
+      "Revision {} of {}",
+      locale.number(index + 1),
+      locale.number(revisions.len())
+    );
+
+    match date {
+      Some(date) => writeln!(out, "{}, {}\n", heading, date)?,
+      None => writeln!(out, "{}\n", heading)?,
+    }
+
+    if let Some(warning) =
+      revision.summary.as_deref().filter(|cw| !cw.is_empty())
+    {
+      writeln!(out, "     Content warning: {}\n", warning)?;
+    }
+
+    for line in textwrap::wrap(&revision.markdown_content(), &options) {
+      writeln!(out, "     {}", line)?;
+    }
+
+    writeln!(out)?;
+  }
+
+  Ok(())
+}
+
+/// Writes deleted posts out as they were archived, each with when it was
+/// found to be gone.
+pub fn deleted(
+  out: &mut impl Write,
+  deleted: &[Deleted],
+  width: usize,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+
+  for found in deleted {
+    let post = &found.post;
+    let author = post.author.as_deref().unwrap_or("unknown");
+
+    writeln!(out, "{}\n", align_right(author, 15))?;
+
+    for line in textwrap::wrap(&post.markdown_content(), &options) {
+      writeln!(out, "     {}", line)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "     {}", post.url.as_deref().unwrap_or(&post.id))?;
+
+    if let Some(published) = &post.published {
+      writeln!(out, "     published: {}", published)?;
+    }
+
+    writeln!(out, "     deleted: {}", found.deleted_at)?;
+    writeln!(out)?;
+  }
+
+  Ok(())
+}
+
+/// Writes what's archived from a domain: its accounts, each with how many
+/// of its posts are archived, then their posts, newest first.
+pub fn domain(
+  out: &mut impl Write,
+  domain: &str,
+  accounts: &[(String, usize)],
+  posts: &[ArchivedPost],
+  width: usize,
+  locale: &Locale,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+
+  if accounts.is_empty() {
+    return writeln!(
+      out,
+      "Nothing archived from {}; apread archive keeps accounts' posts",
+      domain
+    );
+  }
+
+  for (handle, count) in accounts {
+    writeln!(out, "@{}  ({})", handle, self::posts(*count, locale))?;
+  }
+
+  writeln!(out)?;
+
+  for post in posts {
+    let author = post.author.as_deref().unwrap_or("unknown");
+    let date = post
+      .published
+      .as_deref()
+      .and_then(|published| DateTime::parse_from_rfc3339(published).ok())
+      .map(|published| locale.date(&published))
+      .unwrap_or_default();
+
+    writeln!(out, "{}  {}\n", align_right(author, 15), date)?;
+
+    for line in textwrap::wrap(&post.markdown_content(), &options) {
+      writeln!(out, "     {}", line)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "     {}", post.url.as_deref().unwrap_or(&post.id))?;
+    writeln!(out)?;
+  }
+
+  Ok(())
+}
+
+/// Writes what's archived from a domain as JSON: its accounts and their
+/// posts.
+pub fn domain_json(
+  out: &mut impl Write,
+  accounts: &[(String, usize)],
+  posts: &[ArchivedPost],
+) -> io::Result<()> {
+  let accounts: Vec<_> = accounts
+    .iter()
+    .map(
+      |(handle, count)| serde_json::json!({"handle": handle, "posts": count}),
+    )
+    .collect();
+  let posts: Vec<_> = posts
+    .iter()
+    .map(|post| {
+      serde_json::json!({
+        "id": post.id,
+        "url": post.url,
+        "author": post.author,
+        "published": post.published,
+        "summary": post.summary,
+        "content": post.content,
+        "hashtags": post.hashtags,
+      })
+    })
+    .collect();
+
+  serde_json::to_writer_pretty(
+    &mut *out,
+    &serde_json::json!({"accounts": accounts, "posts": posts}),
+  )?;
+  writeln!(out)
+}
+
+/// Converts html to plain text: no markup, no link targets, paragraphs kept
+/// apart by blank lines. This is what text-to-speech tools read best.
+pub fn plain_text(html: &str) -> String {
+  let text = html2text::from_read_with_decorator(
+    html.as_bytes(),
+    usize::MAX,
+    TrivialDecorator::new(),
+  );
+  let mut paragraphs = vec![];
+
+  for paragraph in text.split("\n\n") {
+    let words: Vec<_> = paragraph.split_whitespace().collect();
+
+    if !words.is_empty() {
+      paragraphs.push(words.join(" "));
+    }
+  }
+
+  paragraphs.join("\n\n")
+}
+
+/// Writes notifications out, newest first, each under who did what and
+/// when, with any post they're about.
+pub fn notifications(
+  out: &mut impl Write,
+  notifications: &[Notification],
+  width: usize,
+  locale: &Locale,
+) -> io::Result<()> {
+  let options = wrap_options(width);
+
+  if notifications.is_empty() {
+    return writeln!(out, "No notifications");
+  }
+
+  for notification in notifications {
+    let date = match DateTime::parse_from_rfc3339(&notification.created_at) {
+      Ok(date) => locale.date(&date),
+      Err(_) => notification.created_at.clone(),
+    };
+    let new = if notification.unread { "  (new)" } else { "" };
+
+    writeln!(
+      out,
+      "@{} {}, {}{}",
+      notification.account,
+      notification.kind.done(),
+      date,
+      new
+    )?;
+
+    if let Some(text) = &notification.text {
+      for line in textwrap::wrap(text, &options) {
+        writeln!(out, "     {}", line)?;
+      }
+    }
+
+    if let Some(url) = &notification.url {
+      writeln!(out, "     {}", url)?;
+    }
+
+    writeln!(out)?;
+  }
+
+  Ok(())
+}
+
+/// Writes notifications as a JSON array.
+pub fn notifications_json(
+  out: &mut impl Write,
+  notifications: &[Notification],
+) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, notifications)?;
+  writeln!(out)
+}
+
+/// Writes an account's profile: its name and handle, its bio, then its
+/// fields as a table, with the links which link back marked verified.
+pub fn profile(
+  out: &mut impl Write,
+  card: &Card,
+  width: usize,
+) -> io::Result<()> {
+  match &card.name {
+    Some(name) => writeln!(out, "{}  @{}", name, card.handle)?,
+    None => writeln!(out, "@{}", card.handle)?,
+  }
+
+  if let Some(url) = &card.url {
+    writeln!(out, "{}", url)?;
+  }
+
+  if let Some(bio) = &card.bio {
+    writeln!(out)?;
+
+    for line in textwrap::wrap(bio, width) {
+      writeln!(out, "{}", line)?;
+    }
+  }
+
+  if card.fields.is_empty() {
+    return Ok(());
+  }
+
+  let names = card
+    .fields
+    .iter()
+    .map(|field| field.name.chars().count())
+    .max()
+    .unwrap_or_default();
+
+  writeln!(out)?;
+
+  for field in &card.fields {
+    let mark = if field.verified { "  ✓ verified" } else { "" };
+
+    writeln!(
+      out,
+      "{:<names$}  {}{}",
+      field.name,
+      field.value,
+      mark,
+      names = names
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes how checking a profile's links went, one a line: a mark, the
+/// field's name, its link, and whether it links back.
+pub fn link_checks(
+  out: &mut impl Write,
+  checks: &[LinkCheck],
+) -> io::Result<()> {
+  if checks.is_empty() {
+    return writeln!(out, "No links to verify on this profile");
+  }
+
+  let names = checks
+    .iter()
+    .map(|check| check.name.chars().count())
+    .max()
+    .unwrap_or_default();
+
+  for check in checks {
+    let mark = match check.verification {
+      Verification::Verified => "✓",
+      _ => "✗",
+    };
+
+    writeln!(
+      out,
+      "{} {:<names$}  {}  {}",
+      mark,
+      check.name,
+      check.link,
+      check.verification,
+      names = names
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes how checking a profile's links went as a JSON array.
+pub fn link_checks_json(
+  out: &mut impl Write,
+  checks: &[LinkCheck],
+) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, checks)?;
+  writeln!(out)
+}
+
+/// Writes what went wrong without stopping a run, a line each, as
+/// `warning: ...` or `note: ...` in the language of `messages`.
+pub fn diagnostics(
+  out: &mut impl Write,
+  diagnostics: &[Diagnostic],
+  messages: &Messages,
+) -> io::Result<()> {
+  for diagnostic in diagnostics {
+    let level = match diagnostic.level {
+      Level::Warning => messages.get("warning"),
+      Level::Note => messages.get("note"),
+    };
+
+    writeln!(out, "{}: {}", level, diagnostic.message)?;
+  }
+
+  Ok(())
+}
+
+/// Writes what went wrong without stopping a run as a JSON array.
+pub fn diagnostics_json(
+  out: &mut impl Write,
+  diagnostics: &[Diagnostic],
+) -> io::Result<()> {
+  serde_json::to_writer(&mut *out, diagnostics)?;
+  writeln!(out)
+}
+
+/// Writes an account's profile as JSON.
+pub fn profile_json(out: &mut impl Write, card: &Card) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, card)?;
+  writeln!(out)
+}
+
+/// Writes similar accounts, one a line: how alike they are, then the
+/// handle and the hashtags in common.
+pub fn similar(out: &mut impl Write, similar: &[Similar]) -> io::Result<()> {
+  for account in similar {
+    let shared: Vec<_> = account
+      .shared
+      .iter()
+      .map(|tag| format!("#{}", tag))
+      .collect();
+
+    writeln!(
+      out,
+      "{:.2}  @{}  ({})",
+      account.score,
+      account.handle,
+      shared.join(", ")
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes similar accounts as a JSON array.
+pub fn similar_json(
+  out: &mut impl Write,
+  similar: &[Similar],
+) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, similar)?;
+  writeln!(out)
+}
+
+/// Writes accounts to follow, one a line: how often they were interacted
+/// with, then the handle, and how.
+pub fn suggestions(
+  out: &mut impl Write,
+  suggestions: &[Suggestion],
+  locale: &Locale,
+) -> io::Result<()> {
+  let count = |count: usize, one: &str, many: &str| match count {
+    1 => format!("1 {}", one),
+    _ => format!("{} {}", locale.number(count), many),
+  };
+
+  for suggestion in suggestions {
+    writeln!(
+      out,
+      "{:>5}  @{}  ({}, {}, by {})",
+      locale.number(suggestion.interactions()),
+      suggestion.handle,
+      count(suggestion.mentions, "mention", "mentions"),
+      count(suggestion.boosts, "boost", "boosts"),
+      count(suggestion.by.len(), "account", "accounts"),
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes accounts to follow as a JSON array.
+pub fn suggestions_json(
+  out: &mut impl Write,
+  suggestions: &[Suggestion],
+) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, suggestions)?;
+  writeln!(out)
+}
+
+/// Writes how a watch is doing: how long it's run, when it polls, and when
+/// each account it watches was last refreshed.
+pub fn status(
+  out: &mut impl Write,
+  report: &status::Report,
+  locale: &Locale,
+) -> io::Result<()> {
+  let date = |date: &str| match DateTime::parse_from_rfc3339(date) {
+    Ok(date) => locale.date(&date),
+    Err(_) => date.to_owned(),
+  };
+
+  match (&report.watch, report.running) {
+    (None, _) => writeln!(out, "apread watch isn't running")?,
+    (Some(watch), true) => {
+      let uptime = report.uptime_secs.unwrap_or(0).max(0).unsigned_abs();
+
+      writeln!(
+        out,
+        "Watching since {} (up {}), as process {}",
+        date(&watch.started_at),
+        span(uptime),
+        watch.pid
+      )?;
+    }
+    (Some(watch), false) => writeln!(
+      out,
+      "apread watch isn't running; the last one, started {}, stopped \
+       without tidying up",
+      date(&watch.started_at)
+    )?,
+  }
+
+  if let Some(watch) = &report.watch {
+    let polled = watch.polled_at.as_deref().map_or_else(
+      || "not yet".to_owned(),
+      |polled_at| format!("last at {}", date(polled_at)),
+    );
+
+    write!(out, "Polling every {}, {}", span(watch.every), polled)?;
+
+    match watch.next_poll_at.as_deref() {
+      Some(next) if report.running => {
+        writeln!(out, ", next at {}", date(next))?
+      }
+      _ => writeln!(out)?,
+    }
+  }
+
+  writeln!(
+    out,
+    "{} queued to read later, and {} cached",
+    match report.queue_depth {
+      1 => "1 item".to_owned(),
+      count => format!("{} items", locale.number(count)),
+    },
+    bytes(report.cache_bytes)
+  )?;
+
+  let Some(watch) = &report.watch else {
+    return Ok(());
+  };
+  let widest = watch
+    .accounts
+    .iter()
+    .map(|refresh| refresh.account.chars().count())
+    .max()
+    .unwrap_or(0);
+
+  writeln!(out)?;
+
+  for refresh in &watch.accounts {
+    let refreshed = match refresh.refreshed_at.as_deref() {
+      Some(refreshed_at) => format!("refreshed {}", date(refreshed_at)),
+      None => "not refreshed yet".to_owned(),
+    };
+
+    write!(out, "  {:<widest$}  {}", refresh.account, refreshed)?;
+
+    match &refresh.error {
+      Some(error) => writeln!(out, ", failing: {}", error)?,
+      None => writeln!(out)?,
+    }
+  }
+
+  Ok(())
+}
+
+/// Writes a watch's status as a JSON object.
+pub fn status_json(
+  out: &mut impl Write,
+  report: &status::Report,
+) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, report)?;
+  writeln!(out)
+}
+
+/// Writes what pruning the archive took out of it, and the space that gave
+/// back.
+pub fn pruned(
+  out: &mut impl Write,
+  pruned: &Pruned,
+  locale: &Locale,
+) -> io::Result<()> {
+  let media = match pruned.media {
+    1 => "1 media file".to_owned(),
+    count => format!("{} media files", locale.number(count)),
+  };
+
+  writeln!(
+    out,
+    "Pruned {} and {}, reclaiming {}",
+    posts(pruned.posts, locale),
+    media,
+    bytes(pruned.bytes)
+  )
+}
+
+/// Writes what pruning the archive took out of it as JSON.
+pub fn pruned_json(out: &mut impl Write, pruned: &Pruned) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, pruned)?;
+  writeln!(out)
+}
+
+/// A number of seconds as the two largest units in it, as in `3h 12m`.
+fn span(secs: u64) -> String {
+  let units = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")];
+  let parts: Vec<_> = units
+    .iter()
+    .scan(secs, |left, (size, unit)| {
+      let count = *left / size;
+      *left %= size;
+      Some((count, unit))
+    })
+    .skip_while(|(count, _)| *count == 0)
+    .take(2)
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, unit)| format!("{}{}", count, unit))
+    .collect();
+
+  match parts.is_empty() {
+    true => "0s".to_owned(),
+    false => parts.join(" "),
+  }
+}
+
+/// A number of bytes in the largest unit it makes at least one of.
+fn bytes(count: u64) -> String {
+  let units = ["KB", "MB", "GB", "TB"];
+  let mut size = count as f64;
+  let mut unit = None;
+
+  for next in units {
+    if size < 1000.0 {
+      break;
+    }
+
+    size /= 1000.0;
+    unit = Some(next);
+  }
+
+  match unit {
+    Some(unit) => format!("{:.1} {}", size, unit),
+    None => format!("{} bytes", count),
+  }
+}
@@ -0,0 +1,174 @@
+//! Keeping the archive from growing without end.
+//!
+//! A `[retention]` table in the config file says how much of the archive to
+//! keep, and `apread prune` trims it to that:
+//!
+//! ```toml
+//! [retention]
+//! max_age = 365     # days
+//! max_posts = 1000  # per account
+//! max_size = 500    # megabytes, database and media together
+//! ```
+//!
+//! Posts older than `max_age` go first, then each account's oldest beyond
+//! its `max_posts`. While the archive's still bigger than `max_size`, the
+//! oldest media goes, then the oldest posts of all. Bookmarked posts are
+//! always kept.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  time::SystemTime,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+  archive::Archive,
+  i18n::{self, Localized},
+  ApreadErrors,
+};
+
+/// How many of the oldest posts are pruned at a time, while the archive's
+/// too big.
+pub const BATCH: usize = 100;
+
+/// Raised when asked to prune with no retention policy to prune by.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoRetention;
+
+impl Localized for NoRetention {
+  fn id(&self) -> &'static str {
+    "error-no-retention"
+  }
+}
+
+/// How much of the archive to keep. Anything left out is kept however much
+/// there is of it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct Retention {
+  /// Keep posts for this many days after they were published.
+  pub max_age: Option<u64>,
+  /// Keep at most this many posts of each account, the newest.
+  pub max_posts: Option<usize>,
+  /// Keep the archive and its media to this many megabytes.
+  pub max_size: Option<u64>,
+}
+
+impl Retention {
+  /// Whether the policy keeps everything.
+  pub fn is_empty(&self) -> bool {
+    self.max_age.is_none()
+      && self.max_posts.is_none()
+      && self.max_size.is_none()
+  }
+}
+
+/// What pruning took out of the archive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Pruned {
+  /// How many posts were removed.
+  pub posts: usize,
+  /// How many media files were removed.
+  pub media: usize,
+  /// How many bytes that gave back.
+  pub bytes: u64,
+}
+
+/// Trims an archive, and the media kept in `media` beside it, to a
+/// retention policy, as of `now`.
+pub fn prune(
+  archive: &Archive,
+  media: &Path,
+  retention: &Retention,
+  now: DateTime<Utc>,
+) -> Result<Pruned, ApreadErrors> {
+  if retention.is_empty() {
+    return Err(NoRetention.into());
+  }
+
+  // The media's walked once, its total kept as files are removed.
+  let files = oldest_first(media);
+  let mut media_size: u64 = files.iter().map(|(_, length)| length).sum();
+  let before = archive.size()? + media_size;
+  let mut pruned = Pruned::default();
+
+  // An age too great to count back to keeps everything.
+  let cutoff = retention
+    .max_age
+    .and_then(|days| Duration::try_days(i64::try_from(days).ok()?))
+    .and_then(|age| now.checked_sub_signed(age));
+
+  if let Some(cutoff) = cutoff {
+    pruned.posts += archive.prune_before(&cutoff.to_rfc3339())?;
+  }
+
+  if let Some(keep) = retention.max_posts {
+    pruned.posts += archive.prune_beyond(keep)?;
+  }
+
+  archive.vacuum()?;
+
+  if let Some(megabytes) = retention.max_size {
+    let max = megabytes.saturating_mul(1_000_000);
+
+    for (file, length) in files {
+      if archive.size()? + media_size <= max {
+        break;
+      }
+
+      if fs::remove_file(&file).is_ok() {
+        pruned.media += 1;
+        media_size = media_size.saturating_sub(length);
+      }
+    }
+
+    while archive.size()? + media_size > max {
+      match archive.prune_oldest(BATCH)? {
+        0 => break,
+        count => pruned.posts += count,
+      }
+
+      archive.vacuum()?;
+    }
+  }
+
+  pruned.bytes = before.saturating_sub(archive.size()? + media_size);
+
+  Ok(pruned)
+}
+
+/// The files in a directory and those inside it, with their lengths, the
+/// least recently modified first.
+pub(crate) fn oldest_first(dir: &Path) -> Vec<(PathBuf, u64)> {
+  let mut files = vec![];
+  let mut dirs = vec![dir.to_owned()];
+
+  while let Some(dir) = dirs.pop() {
+    for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+      match entry.file_type() {
+        Ok(kind) if kind.is_dir() => dirs.push(entry.path()),
+        Ok(_) => {
+          let metadata = entry.metadata().ok();
+          let modified = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+          let length = metadata.map_or(0, |metadata| metadata.len());
+
+          files.push((modified, entry.path(), length));
+        }
+        Err(_) => {}
+      }
+    }
+  }
+
+  files.sort();
+  files
+    .into_iter()
+    .map(|(_, path, length)| (path, length))
+    .collect()
+}
@@ -0,0 +1,137 @@
+//! Searching posts through the logged in account's instance, and searches
+//! saved in the config file to run again.
+//!
+//! Saved searches are `[searches.NAME]` tables, of a query and the filters
+//! to narrow it with:
+//!
+//! ```toml
+//! [searches.rust-releases]
+//! query = "rust release"
+//! from = "rust@social.rust-lang.org"
+//! language = "en"
+//! ```
+//!
+//! The filters are written into the query as the operators Mastodon's
+//! search understands. `apread search --saved NAME` only shows the posts
+//! published since it last ran the search, as `apread notifications`
+//! only shows the notifications since it last ran; when each search last
+//! ran is kept in the data directory.
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, FluentValue, Localized},
+  ApreadErrors,
+};
+
+/// The file when each saved search last ran is kept in, in the data
+/// directory.
+pub const FILE: &str = "searches.json";
+
+/// Raised when no saved search has the name asked for.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoSuchSearch(pub String);
+
+impl Localized for NoSuchSearch {
+  fn id(&self) -> &'static str {
+    "error-no-such-search"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![("name", self.0.as_str().into())]
+  }
+}
+
+/// A search saved in the config file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct SavedSearch {
+  /// What to search for.
+  pub query: String,
+  /// Only posts by this account, as `id@domain`.
+  pub from: Option<String>,
+  /// Only posts in this language, as a code like `en`.
+  pub language: Option<String>,
+  /// Only posts with media attached.
+  #[serde(default)]
+  pub media: bool,
+}
+
+impl SavedSearch {
+  /// The query to search for, with the filters written in as operators.
+  pub fn text(&self) -> String {
+    let mut text = self.query.clone();
+
+    if let Some(from) = &self.from {
+      text.push_str(&format!(" from:{}", from.trim_start_matches('@')));
+    }
+
+    if let Some(language) = &self.language {
+      text.push_str(&format!(" language:{}", language));
+    }
+
+    if self.media {
+      text.push_str(" has:media");
+    }
+
+    text
+  }
+}
+
+/// A search to read the posts of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Search {
+  /// What to search for.
+  pub query: String,
+  /// Only the posts published after this.
+  pub since: Option<DateTime<FixedOffset>>,
+}
+
+/// When the saved search with a name last ran, from the file at a path, if
+/// it has run before.
+pub fn last_run(
+  path: &Path,
+  name: &str,
+) -> Result<Option<DateTime<FixedOffset>>, ApreadErrors> {
+  let runs = runs(path)?;
+
+  Ok(
+    runs
+      .get(name)
+      .and_then(|at| DateTime::parse_from_rfc3339(at).ok()),
+  )
+}
+
+/// Keeps that a saved search ran at a time, in the file at a path, so the
+/// next run only shows what's been posted since.
+pub fn ran(
+  path: &Path,
+  name: &str,
+  at: DateTime<Utc>,
+) -> Result<(), ApreadErrors> {
+  let mut runs = runs(path)?;
+
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+
+  runs.insert(name.to_owned(), at.to_rfc3339());
+  fs::write(path, serde_json::to_string_pretty(&runs)?)?;
+
+  Ok(())
+}
+
+/// When each saved search last ran, as RFC 3339 times by name.
+fn runs(path: &Path) -> Result<BTreeMap<String, String>, ApreadErrors> {
+  match fs::read_to_string(path) {
+    Ok(json) => Ok(serde_json::from_str(&json)?),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => {
+      Ok(BTreeMap::new())
+    }
+    Err(error) => Err(error.into()),
+  }
+}
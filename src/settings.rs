@@ -0,0 +1,489 @@
+//! Settings, layered from a config file, the environment, and flags.
+//!
+//! Each setting is read from, in order of precedence:
+//!
+//! 1. its command-line flag, such as `--width 100`
+//! 2. its `APREAD_*` environment variable, such as `APREAD_WIDTH=100`
+//! 3. the config file, `apread/config.toml` in the platform config directory
+//! 4. its default
+//!
+//! The settings, and their environment variables, are:
+//!
+//! | setting            | variable                  | default                                      |
+//! |--------------------|---------------------------|----------------------------------------------|
+//! | `width`            | `APREAD_WIDTH`            | `80`                                         |
+//! | `format`           | `APREAD_FORMAT`           | `terminal`                                   |
+//! | `timeout`          | `APREAD_TIMEOUT`          | none; seconds                                |
+//! | `proxy`            | `APREAD_PROXY`            | none; a proxy url                            |
+//! | `socks5`           | `APREAD_SOCKS5`           | none; as `127.0.0.1:9050`, for Tor           |
+//! | `doh`              | `APREAD_DOH`              | none; a DNS-over-HTTPS resolver's url        |
+//! | `ip_version`       | `APREAD_IP_VERSION`       | either; or `ipv4` or `ipv6`                  |
+//! | `connect_timeout`  | `APREAD_CONNECT_TIMEOUT`  | none; seconds, split between addresses       |
+//! | `per_instance`     | `APREAD_PER_INSTANCE`     | `2`; requests in flight, or `0` for any      |
+//! | `cache_dir`        | `APREAD_CACHE_DIR`        | the platform cache dir                       |
+//! | `data_dir`         | `APREAD_DATA_DIR`         | the platform data dir; for the archive       |
+//! | `subscriptions`    | `APREAD_SUBSCRIPTIONS`    | `subscriptions.toml` beside the config file  |
+//! | `discovery_ttl`    | `APREAD_DISCOVERY_TTL`    | `86400`; seconds                             |
+//! | `storage`          | `APREAD_STORAGE`          | `disk`; or `sqlite` or `memory`              |
+//! | `locale`           | `APREAD_LOCALE`           | `LC_ALL`, `LC_TIME` or `LANG`                |
+//! | `lang`             | `APREAD_LANG`             | `LANGUAGE`, `LC_ALL`, `LC_MESSAGES`, `LANG`  |
+//! | `screen_reader`    | `APREAD_SCREEN_READER`    | `false`                                      |
+//! | `math`             | `APREAD_MATH`             | `text`; or `verbatim`                        |
+//! | `links`            | `APREAD_LINKS`            | `inline`; or `footnotes`                     |
+//! | `download_media`   | `APREAD_DOWNLOAD_MEDIA`   | `false`; thumbnails need `thumbnails` built  |
+//! | `media_cache_size` | `APREAD_MEDIA_CACHE_SIZE` | `500`; megabytes, or `0` for no limit        |
+//! | `player`           | `APREAD_PLAYER`           | `mpv`; a command, given the url to play      |
+//! | `account`          | `APREAD_ACCOUNT`          | the account logged in to last                |
+//! | `cw_keywords`      | `APREAD_CW_KEYWORDS`      | none; comma-separated in the environment     |
+//! | `on_new_post`      | `APREAD_ON_NEW_POST`      | none; a shell command                        |
+//! | `webhooks`         | none                      | none; `[[webhooks]]` tables                  |
+//! | `posting_alert`    | none                      | none; a `[posting_alert]` table              |
+//! | `spam`             | none                      | none; a `[spam]` table                       |
+//! | `matrix`           | none                      | none; a `[matrix]` table, in `matrix` builds |
+//! | `views`            | none                      | none; `[views.NAME]` tables                  |
+//! | `searches`         | none                      | none; `[searches.NAME]` tables               |
+//! | `retention`        | none                      | keep everything; a `[retention]` table       |
+//! | `wasm_filters`     | none                      | none; module paths, in `wasm` builds         |
+
+use std::{
+  collections::BTreeMap,
+  fmt,
+  path::{Path, PathBuf},
+};
+
+use config::{Config, Environment, File, FileFormat};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+  alerts::PostingAlert,
+  cache::DEFAULT_DISCOVERY_TTL,
+  hooks::Webhook,
+  i18n::{self, FluentValue, Localized, Messages},
+  links,
+  locale::Locale,
+  math::Math,
+  player,
+  retention::Retention,
+  search::SavedSearch,
+  spam::Spam,
+  storage,
+  throttle::DEFAULT_PER_INSTANCE,
+  view::View,
+  ApreadErrors,
+};
+
+/// How timelines (and graphs) are written out.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+  /// Wrapped text for reading in a terminal.
+  #[default]
+  Terminal,
+  /// A JSON array of posts.
+  Json,
+  /// CSV, one row per post, for spreadsheets and data pipelines.
+  Csv,
+  /// A standalone html page of posts.
+  Html,
+  /// An RSS feed, for reading in a feed reader.
+  Rss,
+  /// An RSS feed of the posts with audio attached, as enclosures, for
+  /// listening to in a podcast app.
+  #[serde(rename = "podcast-rss")]
+  PodcastRss,
+  /// An iCalendar file of the upcoming events among the posts, for
+  /// importing into a calendar app.
+  Ics,
+  /// Graphviz DOT, for `apread graph`.
+  Dot,
+  /// GEXF, as Gephi reads, for `apread graph`.
+  Gexf,
+}
+
+impl fmt::Display for Format {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Terminal => "terminal",
+      Self::Json => "json",
+      Self::Csv => "csv",
+      Self::Html => "html",
+      Self::Rss => "rss",
+      Self::PodcastRss => "podcast-rss",
+      Self::Ics => "ics",
+      Self::Dot => "dot",
+      Self::Gexf => "gexf",
+    })
+  }
+}
+
+impl Format {
+  /// The format, if it's one of those `what` can be written in, as in
+  /// `"Profiles"`.
+  pub fn require(
+    self,
+    what: &'static str,
+    supported: &[Format],
+  ) -> Result<Self, WrongFormat> {
+    match supported.contains(&self) {
+      true => Ok(self),
+      false => Err(WrongFormat { what, format: self }),
+    }
+  }
+
+  /// The format, if it's the terminal's or JSON, which are all most
+  /// reports can be written in.
+  pub fn require_text(self, what: &'static str) -> Result<Self, WrongFormat> {
+    self.require(what, &[Format::Terminal, Format::Json])
+  }
+}
+
+/// Which kind of address to connect to instances over.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+  /// IPv4 only.
+  Ipv4,
+  /// IPv6 only.
+  Ipv6,
+}
+
+/// Raised when asked to write something in a format it can't be written
+/// in, such as a timeline as DOT.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct WrongFormat {
+  /// What was being written, as in `Timelines`.
+  pub what: &'static str,
+  /// The format asked for.
+  pub format: Format,
+}
+
+impl Localized for WrongFormat {
+  fn id(&self) -> &'static str {
+    "error-wrong-format"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![
+      ("what", self.what.into()),
+      ("format", self.format.to_string().into()),
+    ]
+  }
+}
+
+/// Everything apread can be configured with.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+  /// How many columns to wrap text at.
+  pub width: usize,
+  /// How timelines (and graphs) are written out.
+  pub format: Format,
+  /// How many seconds to wait on a request before giving up.
+  pub timeout: Option<u64>,
+  /// A proxy to send requests through.
+  pub proxy: Option<String>,
+  /// A SOCKS5 proxy, such as Tor's, to send requests through in place of
+  /// [`proxy`](Self::proxy), leaving it to look names up.
+  pub socks5: Option<String>,
+  /// A DNS-over-HTTPS resolver to look instances up with, in place of the
+  /// system's DNS.
+  pub doh: Option<String>,
+  /// Connect to instances over only IPv4 or only IPv6, rather than trying
+  /// both, for hosts whose addresses of one kind are broken.
+  pub ip_version: Option<IpVersion>,
+  /// How many seconds to spend connecting to an instance, split between its
+  /// addresses, so a broken one is given up on for the next sooner.
+  pub connect_timeout: Option<u64>,
+  /// How many requests to have in flight to any one instance at once, or
+  /// `0` for no limit.
+  pub per_instance: usize,
+  /// Where to keep cached documents.
+  pub cache_dir: Option<PathBuf>,
+  /// Where to keep the archive, and the media imported into it.
+  pub data_dir: Option<PathBuf>,
+  /// The subscriptions file to read, unless a command is given another.
+  pub subscriptions: Option<PathBuf>,
+  /// How many seconds to keep WebFinger and actor documents for, or `0` to
+  /// look accounts up afresh every time.
+  pub discovery_ttl: u64,
+  /// What to keep cached documents in, in the cache directory.
+  pub storage: storage::Backend,
+  /// The locale to write dates and numbers for, as in `de_DE`.
+  pub locale: Option<String>,
+  /// The language to write apread's own messages in, as in `de`.
+  pub lang: Option<String>,
+  /// Whether to write timelines for a screen reader rather than the eye.
+  pub screen_reader: bool,
+  /// How to write out mathematics in posts.
+  pub math: Math,
+  /// Where in a post to write its links.
+  pub links: links::Style,
+  /// Whether to download posts' attachments as they're read, keeping them in
+  /// the cache directory. Only the whole files are kept, unless apread's
+  /// built with `thumbnails`, when images are drawn small too.
+  pub download_media: bool,
+  /// How many megabytes of downloaded attachments to keep, or `0` for no
+  /// limit.
+  pub media_cache_size: u64,
+  /// The media player `--play` hands videos and sounds to, as a command
+  /// and its flags.
+  pub player: String,
+  /// The account to read and post as, as in `me@mastodon.social`, rather
+  /// than the one logged in to last.
+  pub account: Option<String>,
+  /// Keywords which call for a content warning, for `apread audit`.
+  pub cw_keywords: Vec<String>,
+  /// A command to run for each new post `apread watch` sees.
+  pub on_new_post: Option<String>,
+  /// Urls to POST each new post `apread watch` sees to.
+  pub webhooks: Vec<Webhook>,
+  /// How much posting `apread watch` alerts on, and where it sends alerts.
+  pub posting_alert: Option<PostingAlert>,
+  /// Which low-quality posts `apread read` filters out of timelines.
+  pub spam: Option<Spam>,
+  /// Named ways of reading timelines, for `apread read NAME`.
+  pub views: BTreeMap<String, View>,
+  /// Named searches, for `apread search --saved NAME`.
+  pub searches: BTreeMap<String, SavedSearch>,
+  /// How much of the archive `apread prune` keeps.
+  pub retention: Retention,
+  /// A Matrix room to relay each new post `apread watch` sees into.
+  #[cfg(feature = "matrix")]
+  pub matrix: Option<crate::matrix::Room>,
+  /// WebAssembly modules `apread read` hands each post to, to hide, tag or
+  /// rewrite it.
+  #[cfg(feature = "wasm")]
+  pub wasm_filters: Vec<PathBuf>,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      width: 80,
+      format: Format::default(),
+      timeout: None,
+      proxy: None,
+      socks5: None,
+      doh: None,
+      ip_version: None,
+      connect_timeout: None,
+      per_instance: DEFAULT_PER_INSTANCE,
+      cache_dir: None,
+      data_dir: None,
+      subscriptions: None,
+      discovery_ttl: DEFAULT_DISCOVERY_TTL.as_secs(),
+      storage: storage::Backend::default(),
+      locale: None,
+      lang: None,
+      screen_reader: false,
+      math: Math::default(),
+      links: links::Style::default(),
+      download_media: false,
+      media_cache_size: crate::media::DEFAULT_CACHE_SIZE,
+      player: player::DEFAULT.to_owned(),
+      account: None,
+      cw_keywords: vec![],
+      on_new_post: None,
+      webhooks: vec![],
+      posting_alert: None,
+      spam: None,
+      views: BTreeMap::new(),
+      searches: BTreeMap::new(),
+      retention: Retention::default(),
+      #[cfg(feature = "matrix")]
+      matrix: None,
+      #[cfg(feature = "wasm")]
+      wasm_filters: vec![],
+    }
+  }
+}
+
+/// Settings given on the command line, which win over everything else.
+#[derive(Clone, Debug, Default)]
+pub struct Overrides {
+  /// Overrides [`Settings::width`].
+  pub width: Option<usize>,
+  /// Overrides [`Settings::format`].
+  pub format: Option<Format>,
+  /// Overrides [`Settings::timeout`].
+  pub timeout: Option<u64>,
+  /// Overrides [`Settings::proxy`].
+  pub proxy: Option<String>,
+  /// Overrides [`Settings::socks5`].
+  pub socks5: Option<String>,
+  /// Overrides [`Settings::doh`].
+  pub doh: Option<String>,
+  /// Overrides [`Settings::ip_version`].
+  pub ip_version: Option<IpVersion>,
+  /// Overrides [`Settings::connect_timeout`].
+  pub connect_timeout: Option<u64>,
+  /// Overrides [`Settings::per_instance`].
+  pub per_instance: Option<usize>,
+  /// Overrides [`Settings::cache_dir`].
+  pub cache_dir: Option<PathBuf>,
+  /// Overrides [`Settings::data_dir`].
+  pub data_dir: Option<PathBuf>,
+  /// Overrides [`Settings::discovery_ttl`].
+  pub discovery_ttl: Option<u64>,
+  /// Overrides [`Settings::locale`].
+  pub locale: Option<String>,
+  /// Overrides [`Settings::lang`].
+  pub lang: Option<String>,
+  /// Overrides [`Settings::screen_reader`].
+  pub screen_reader: Option<bool>,
+  /// Overrides [`Settings::math`].
+  pub math: Option<Math>,
+  /// Overrides [`Settings::links`].
+  pub links: Option<links::Style>,
+  /// Overrides [`Settings::account`].
+  pub account: Option<String>,
+}
+
+impl Settings {
+  /// Where the config file lives unless told otherwise.
+  pub fn default_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("apread").join("config.toml"))
+  }
+
+  /// Reads settings from a config file (which needn't exist) and the
+  /// environment, then applies the overrides on top.
+  pub fn load(
+    path: Option<&Path>,
+    overrides: Overrides,
+  ) -> Result<Self, ApreadErrors> {
+    let mut builder = Config::builder();
+
+    if let Some(path) = path {
+      builder = builder
+        .add_source(File::from(path).format(FileFormat::Toml).required(false));
+    }
+
+    let mut settings: Self = builder
+      .add_source(
+        Environment::with_prefix("APREAD")
+          .try_parsing(true)
+          .list_separator(",")
+          .with_list_parse_key("cw_keywords"),
+      )
+      .build()?
+      .try_deserialize()?;
+
+    if let Some(width) = overrides.width {
+      settings.width = width;
+    }
+
+    if let Some(format) = overrides.format {
+      settings.format = format;
+    }
+
+    settings.timeout = overrides.timeout.or(settings.timeout);
+    settings.proxy = overrides.proxy.or(settings.proxy);
+    settings.socks5 = overrides.socks5.or(settings.socks5);
+    settings.doh = overrides.doh.or(settings.doh);
+    settings.ip_version = overrides.ip_version.or(settings.ip_version);
+    settings.connect_timeout =
+      overrides.connect_timeout.or(settings.connect_timeout);
+    settings.per_instance =
+      overrides.per_instance.unwrap_or(settings.per_instance);
+    settings.cache_dir = overrides.cache_dir.or(settings.cache_dir);
+    settings.data_dir = overrides.data_dir.or(settings.data_dir);
+
+    settings.locale = overrides.locale.or(settings.locale);
+    settings.lang = overrides.lang.or(settings.lang);
+    settings.account = overrides.account.or(settings.account);
+
+    if let Some(screen_reader) = overrides.screen_reader {
+      settings.screen_reader = screen_reader;
+    }
+
+    if let Some(math) = overrides.math {
+      settings.math = math;
+    }
+
+    if let Some(links) = overrides.links {
+      settings.links = links;
+    }
+
+    if let Some(ttl) = overrides.discovery_ttl {
+      settings.discovery_ttl = ttl;
+    }
+
+    Ok(settings)
+  }
+
+  /// The locale to write dates and numbers for: the configured one, or
+  /// the environment's.
+  pub fn locale(&self) -> Locale {
+    Locale::resolve(self.locale.as_deref())
+  }
+
+  /// apread's own messages, in the configured language or the
+  /// environment's.
+  pub fn messages(&self) -> Messages {
+    Messages::resolve(self.lang.as_deref())
+  }
+
+  /// The cache directory to use: the configured one, or the platform's.
+  pub fn cache_dir(&self) -> Option<PathBuf> {
+    self
+      .cache_dir
+      .clone()
+      .or_else(|| Some(dirs::cache_dir()?.join("apread")))
+  }
+
+  /// The directory to keep the archive in: the configured one, or the
+  /// platform's.
+  pub fn data_dir(&self) -> Result<PathBuf, ApreadErrors> {
+    match &self.data_dir {
+      Some(dir) => Ok(dir.clone()),
+      None => crate::archive::data_dir(),
+    }
+  }
+
+  /// The subscriptions file to read: the configured one, or the default.
+  pub fn subscriptions_path(&self) -> Option<PathBuf> {
+    self
+      .subscriptions
+      .clone()
+      .or_else(crate::subscriptions::Subscriptions::default_path)
+  }
+
+  /// Where the posts the last run showed are kept, in the cache directory.
+  pub fn last_run(&self) -> Option<PathBuf> {
+    Some(self.cache_dir()?.join(crate::last_run::FILE))
+  }
+
+  /// Where the accounts logged in to are kept, in the data directory.
+  pub fn logins_path(&self) -> Result<PathBuf, ApreadErrors> {
+    Ok(self.data_dir()?.join(crate::login::FILE))
+  }
+
+  /// Where the newest notification read is kept, in the data directory.
+  pub fn notifications_path(&self) -> Result<PathBuf, ApreadErrors> {
+    Ok(self.data_dir()?.join(crate::notifications::FILE))
+  }
+
+  /// Where media imported into the archive is kept, in the data directory.
+  pub fn media_dir(&self) -> Result<PathBuf, ApreadErrors> {
+    Ok(self.data_dir()?.join("media"))
+  }
+
+  /// Where when each saved search last ran is kept, in the data directory.
+  pub fn searches_path(&self) -> Result<PathBuf, ApreadErrors> {
+    Ok(self.data_dir()?.join(crate::search::FILE))
+  }
+
+  /// Where downloaded attachments are kept, in the cache directory.
+  pub fn media_cache(&self) -> Option<PathBuf> {
+    Some(self.cache_dir()?.join(crate::media::DIR))
+  }
+
+  /// Where a running watch keeps its status, in the cache directory.
+  pub fn watch_status(&self) -> Option<PathBuf> {
+    Some(self.cache_dir()?.join(crate::status::FILE))
+  }
+}
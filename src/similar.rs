@@ -0,0 +1,146 @@
+//! Finding accounts whose interests overlap an account's, by the hashtags
+//! they use.
+//!
+//! An account's interests are the hashtags it uses, weighted by how many
+//! of its posts use each, and two accounts are as similar as the cosine of
+//! their weights: 1 for accounts tagging in the same proportions, 0 for
+//! accounts with no hashtag in common. The accounts compared against are
+//! the archived ones, by their archived posts, and those in instances'
+//! profile directories, by the hashtags in their bios, since a directory
+//! doesn't say what its accounts post.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::render;
+
+/// How many shared hashtags to say a similar account has in common.
+pub const SHARED_SHOWN: usize = 5;
+
+/// An account's interests: each hashtag it uses, lowercase, and how often.
+pub type Interests = BTreeMap<String, usize>;
+
+/// An account with interests like the one compared against.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Similar {
+  /// The account's handle, as in `bob@example.social`.
+  pub handle: String,
+  /// How alike their interests are, from 0 to 1.
+  pub score: f64,
+  /// The hashtags they share, the ones both use most first.
+  pub shared: Vec<String>,
+}
+
+/// The interests in a run of hashtags, with or without their `#`.
+pub fn interests<'a>(hashtags: impl IntoIterator<Item = &'a str>) -> Interests {
+  let mut interests = Interests::new();
+
+  for tag in hashtags {
+    let tag = tag.trim_start_matches('#').to_lowercase();
+
+    if !tag.is_empty() {
+      *interests.entry(tag).or_default() += 1;
+    }
+  }
+
+  interests
+}
+
+/// The hashtags written in a bio, given as html.
+pub fn bio_hashtags(note: &str) -> Vec<String> {
+  render::plain_text(note)
+    .split(|c: char| c.is_whitespace() || c == ',')
+    .filter_map(|word| word.strip_prefix('#'))
+    .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric()))
+    .filter(|tag| !tag.is_empty())
+    .map(str::to_owned)
+    .collect()
+}
+
+/// How alike two accounts' interests are: the cosine of their weights.
+pub fn similarity(a: &Interests, b: &Interests) -> f64 {
+  let dot: f64 = a
+    .iter()
+    .filter_map(|(tag, count)| Some((*count * *b.get(tag)?) as f64))
+    .sum();
+  let length = |interests: &Interests| {
+    interests
+      .values()
+      .map(|count| (*count as f64).powi(2))
+      .sum::<f64>()
+      .sqrt()
+  };
+
+  let lengths = length(a) * length(b);
+
+  if lengths == 0.0 {
+    0.0
+  } else {
+    dot / lengths
+  }
+}
+
+/// The candidates sharing any interests with `of`, the most alike first.
+/// Candidates given more than once, as an archived account also in a
+/// directory, are compared by their interests put together.
+pub fn rank(
+  of: &Interests,
+  candidates: impl IntoIterator<Item = (String, Interests)>,
+) -> Vec<Similar> {
+  let mut merged: BTreeMap<String, Interests> = BTreeMap::new();
+
+  for (handle, interests) in candidates {
+    let handle = handle.trim_start_matches('@').to_lowercase();
+    let into = merged.entry(handle).or_default();
+
+    for (tag, count) in interests {
+      *into.entry(tag).or_default() += count;
+    }
+  }
+
+  let mut similar: Vec<_> = merged
+    .into_iter()
+    .filter_map(|(handle, interests)| {
+      let score = similarity(of, &interests);
+      let mut shared: Vec<_> = of
+        .iter()
+        .filter_map(|(tag, count)| Some((tag, count.min(interests.get(tag)?))))
+        .collect();
+
+      shared.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+      (score > 0.0).then(|| Similar {
+        handle,
+        score,
+        shared: shared
+          .into_iter()
+          .take(SHARED_SHOWN)
+          .map(|(tag, _)| tag.clone())
+          .collect(),
+      })
+    })
+    .collect();
+
+  similar.sort_by(|a, b| {
+    b.score
+      .total_cmp(&a.score)
+      .then_with(|| a.handle.cmp(&b.handle))
+  });
+
+  similar
+}
+
+/// Each archived author's interests, from the hashtags on their posts.
+pub fn archived(hashtags: &[(String, String)]) -> Vec<(String, Interests)> {
+  let mut by_author: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+  for (author, tag) in hashtags {
+    by_author.entry(author).or_default().push(tag);
+  }
+
+  by_author
+    .into_iter()
+    .map(|(author, tags)| (author.to_owned(), interests(tags)))
+    .collect()
+}
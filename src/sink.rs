@@ -0,0 +1,343 @@
+//! Where the posts a run reads are written, and in what format.
+//!
+//! Reading a timeline ends by handing its entries to an [`OutputSink`],
+//! picked by `--format`. Each format is a sink, so a new one only needs a
+//! sink of its own rather than changes to how posts are fetched, and
+//! programs using apread as a library can write posts anywhere by
+//! implementing the trait themselves.
+
+use std::{
+  fs,
+  io::{self, Write},
+  path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+  i18n::Messages,
+  links,
+  locale::Locale,
+  math::{self, Math},
+  render,
+  timeline::{self, Entry, Period},
+  ApreadErrors,
+};
+
+/// Something timeline entries can be written out to.
+pub trait OutputSink {
+  /// Writes the entries, newest first or as they've been put in order, to
+  /// `out` or wherever else the sink writes to.
+  fn write(
+    &mut self,
+    out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors>;
+}
+
+/// How the terminal lays posts out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+  /// Cluster posts by account, rather than interleaving them by date.
+  pub group_by_author: bool,
+  /// Write a digest, sectioned by day or week.
+  pub digest: Option<Period>,
+  /// Lay posts out in this many columns, if the terminal is wide enough.
+  pub columns: Option<usize>,
+}
+
+impl Layout {
+  /// The entries in the order they're shown, and numbered: in a digest or
+  /// grouped by author if asked, or as they come.
+  pub fn order(&self, entries: &[Entry]) -> Vec<Entry> {
+    if let Some(period) = self.digest {
+      timeline::digest(entries, period)
+        .into_iter()
+        .flat_map(|section| section.authors.concat())
+        .cloned()
+        .collect()
+    } else if self.group_by_author {
+      timeline::group_by_author(entries)
+        .concat()
+        .into_iter()
+        .cloned()
+        .collect()
+    } else {
+      entries.to_vec()
+    }
+  }
+}
+
+/// Wrapped text for reading in a terminal, or for a screen reader.
+#[derive(Clone, Debug, Default)]
+pub struct Terminal {
+  /// How many columns to wrap text at.
+  pub width: usize,
+  /// Whether to color names as subscriptions ask.
+  pub colored: bool,
+  /// The locale to write dates and numbers for.
+  pub locale: Locale,
+  /// The messages to write headings and notes in.
+  pub messages: Messages,
+  /// How posts are laid out.
+  pub layout: Layout,
+  /// Whether to write for a screen reader rather than the eye.
+  pub screen_reader: bool,
+  /// How to write out mathematics.
+  pub math: Math,
+  /// Where in a post to write its links.
+  pub links: links::Style,
+  /// Whether to show attachments marked sensitive, rather than a blur of
+  /// them.
+  pub reveal: bool,
+  /// How many posts there were to show, for saying how many weren't.
+  pub total: Option<usize>,
+}
+
+impl Terminal {
+  /// Entries as the terminal shows them, with mathematics and links
+  /// written out as asked.
+  fn displayed(&self, entries: &[Entry]) -> Vec<Entry> {
+    let mut entries = math::prepare(entries, self.math);
+
+    if self.links == links::Style::Footnotes {
+      for entry in &mut entries {
+        entry.post.content = links::footnoted(&entry.post.content);
+      }
+    }
+
+    for entry in &mut entries {
+      let hide = entry.post.sensitive && !self.reveal;
+
+      for attachment in &mut entry.post.attachment {
+        attachment.hidden = hide;
+      }
+    }
+
+    entries
+  }
+
+  fn posts(
+    &self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> io::Result<()> {
+    let layout = self.layout;
+    let columns = layout.columns.and_then(|count| {
+      let width = render::column_width(textwrap::termwidth(), count)?;
+      Some((width, count))
+    });
+
+    if let Some((width, count)) = columns {
+      let blocks = render::blocks(
+        entries,
+        layout.group_by_author,
+        width,
+        self.colored,
+        &self.locale,
+        &self.messages,
+      )?;
+
+      render::columns(&mut out, &blocks, width, count)
+    } else if let Some(period) = layout.digest {
+      render::digest(
+        &mut out,
+        entries,
+        period,
+        self.width,
+        self.colored,
+        &self.locale,
+        &self.messages,
+      )
+    } else if layout.group_by_author {
+      render::grouped(
+        &mut out,
+        entries,
+        self.width,
+        self.colored,
+        &self.locale,
+        &self.messages,
+      )
+    } else {
+      render::terminal(
+        &mut out,
+        entries,
+        self.width,
+        self.colored,
+        &self.messages,
+      )
+    }
+  }
+}
+
+impl OutputSink for Terminal {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    if self.screen_reader {
+      let shown = self.displayed(&self.layout.order(entries));
+      render::screen_reader(&mut out, &shown, &self.locale)?;
+    } else {
+      self.posts(&mut out, &self.displayed(entries))?;
+    }
+
+    render::footer(
+      &mut out,
+      entries.len(),
+      self.total,
+      &self.locale,
+      &self.messages,
+    )?;
+
+    Ok(())
+  }
+}
+
+/// A JSON array of posts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+impl OutputSink for Json {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    Ok(render::json(&mut out, entries)?)
+  }
+}
+
+/// CSV, one row per post.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Csv;
+
+impl OutputSink for Csv {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    Ok(render::csv(&mut out, entries)?)
+  }
+}
+
+/// A standalone html page of posts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Html;
+
+impl OutputSink for Html {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    Ok(render::html(&mut out, entries)?)
+  }
+}
+
+/// An RSS 2.0 feed of posts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rss;
+
+impl OutputSink for Rss {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    Ok(render::rss(&mut out, entries)?)
+  }
+}
+
+/// An RSS 2.0 feed of the posts with audio attached, for podcast apps.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Podcast;
+
+impl OutputSink for Podcast {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    Ok(render::podcast(&mut out, entries)?)
+  }
+}
+
+/// An iCalendar file of the events among posts which haven't ended yet.
+#[derive(Clone, Copy, Debug)]
+pub struct Ics {
+  /// The time events have to end after to be written.
+  pub now: DateTime<Utc>,
+}
+
+impl OutputSink for Ics {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    Ok(render::ics(&mut out, entries, self.now)?)
+  }
+}
+
+/// Each post written through a [minijinja] template, as
+/// [`render::template`] writes them.
+///
+/// [minijinja]: https://docs.rs/minijinja
+#[derive(Clone, Debug)]
+pub struct Template {
+  source: String,
+}
+
+impl Template {
+  /// A sink writing posts through the template with this source.
+  pub fn new(source: impl Into<String>) -> Self {
+    Self {
+      source: source.into(),
+    }
+  }
+}
+
+impl OutputSink for Template {
+  fn write(
+    &mut self,
+    mut out: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    render::template(&mut out, entries, &self.source)
+  }
+}
+
+/// Another sink's output, written to a file instead. The file is replaced
+/// if it's already there.
+pub struct File {
+  path: PathBuf,
+  sink: Box<dyn OutputSink>,
+}
+
+impl File {
+  /// A sink writing what `sink` would to the file at `path`.
+  pub fn new(path: impl Into<PathBuf>, sink: Box<dyn OutputSink>) -> Self {
+    Self {
+      path: path.into(),
+      sink,
+    }
+  }
+}
+
+impl OutputSink for File {
+  fn write(
+    &mut self,
+    _: &mut dyn Write,
+    entries: &[Entry],
+  ) -> Result<(), ApreadErrors> {
+    let mut file = io::BufWriter::new(fs::File::create(&self.path)?);
+
+    self.sink.write(&mut file, entries)?;
+    file.flush()?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,191 @@
+//! Hiding low-quality posts from the combined timeline.
+//!
+//! With a `[spam]` table in the config file, `apread read` leaves out posts
+//! that are nothing but links, that carry more hashtags than anyone reading
+//! them wants, or that say the same thing as a post before them:
+//!
+//! ```toml
+//! [spam]
+//! link_only = true
+//! max_hashtags = 6
+//! repeated = true
+//! action = "downrank"
+//! ```
+//!
+//! Each heuristic is on by default once the table's there, and can be
+//! turned off on its own, `max_hashtags` by setting it to `0`. Posts are
+//! hidden unless the `action` is `downrank`, which moves them below the
+//! rest instead. `--show-filtered` reads everything regardless.
+
+use std::collections::HashMap;
+
+use scraper::Html;
+use serde::Deserialize;
+
+use crate::{links, render, timeline::Entry};
+
+/// The most hashtags a post can carry before it's filtered, unless the
+/// config file says otherwise.
+pub const DEFAULT_MAX_HASHTAGS: usize = 6;
+
+/// What to do with the posts which are filtered.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+  /// Leave them out.
+  #[default]
+  Hide,
+  /// Show them after everything else.
+  Downrank,
+}
+
+/// Why a post was filtered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+  /// It's only links, with nothing said about them.
+  LinkOnly,
+  /// It carries this many hashtags, more than it's allowed.
+  Hashtags(usize),
+  /// It says just what an earlier post said.
+  Repeated,
+}
+
+/// Which heuristics to filter posts by, and what to do with them.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Spam {
+  /// Filter posts which are only links, without attachments.
+  pub link_only: bool,
+  /// Filter posts with more hashtags than this, unless it's 0.
+  pub max_hashtags: usize,
+  /// Filter posts saying the same as an earlier one, keeping the first.
+  pub repeated: bool,
+  /// What to do with the posts filtered.
+  pub action: Action,
+}
+
+impl Default for Spam {
+  fn default() -> Self {
+    Self {
+      link_only: true,
+      max_hashtags: DEFAULT_MAX_HASHTAGS,
+      repeated: true,
+      action: Action::default(),
+    }
+  }
+}
+
+impl Spam {
+  /// Why each entry would be filtered, if it would be, in the same order.
+  /// Of the copies of a repeated post, the one published first is kept,
+  /// wherever it is among the entries.
+  pub fn reasons(&self, entries: &[Entry]) -> Vec<Option<Reason>> {
+    let texts: Vec<String> = entries
+      .iter()
+      .map(|entry| normal(&entry.post.content))
+      .collect();
+    let mut first: HashMap<&str, usize> = HashMap::new();
+
+    if self.repeated {
+      for (index, text) in texts.iter().enumerate() {
+        if text.is_empty() {
+          continue;
+        }
+
+        let kept = first.entry(text).or_insert(index);
+
+        if entries[index].published_at() < entries[*kept].published_at() {
+          *kept = index;
+        }
+      }
+    }
+
+    entries
+      .iter()
+      .enumerate()
+      .map(|(index, entry)| {
+        let hashtags = entry.post.hashtags().len();
+
+        if self.link_only && link_only(entry) {
+          Some(Reason::LinkOnly)
+        } else if self.max_hashtags > 0 && hashtags > self.max_hashtags {
+          Some(Reason::Hashtags(hashtags))
+        } else {
+          let kept = *first.get(texts[index].as_str())?;
+          let copy = |index: usize| entries[index].post.id.as_deref();
+
+          // The same post boosted twice isn't repeating itself.
+          (kept != index && (copy(kept).is_none() || copy(kept) != copy(index)))
+            .then_some(Reason::Repeated)
+        }
+      })
+      .collect()
+  }
+
+  /// Hides the entries the heuristics filter, or moves them after the rest,
+  /// keeping the order they were in otherwise. How many were filtered.
+  pub fn apply(&self, entries: &mut Vec<Entry>) -> usize {
+    let reasons = self.reasons(entries);
+    let mut kept = vec![];
+    let mut filtered = vec![];
+
+    for (entry, reason) in entries.drain(..).zip(reasons) {
+      match reason {
+        Some(_) => filtered.push(entry),
+        None => kept.push(entry),
+      }
+    }
+
+    let count = filtered.len();
+
+    if self.action == Action::Downrank {
+      kept.extend(filtered);
+    }
+
+    *entries = kept;
+    count
+  }
+}
+
+/// Whether a post is nothing but links: it links somewhere, has nothing
+/// attached, and says nothing outside its links, mentions and hashtags.
+fn link_only(entry: &Entry) -> bool {
+  let post = &entry.post;
+
+  if links::extract(&post.content).is_empty() || !post.attachment.is_empty() {
+    return false;
+  }
+
+  let document = Html::parse_fragment(&post.content);
+  let text = document
+    .root_element()
+    .descendants()
+    .filter(|node| {
+      !node.ancestors().any(|ancestor| {
+        ancestor
+          .value()
+          .as_element()
+          .is_some_and(|element| element.name() == "a")
+      })
+    })
+    .filter_map(|node| node.value().as_text().map(|text| text.to_string()))
+    .collect::<Vec<_>>()
+    .join(" ");
+
+  !text
+    .split_whitespace()
+    .filter(|word| {
+      !word.contains("://") && !word.starts_with('@') && !word.starts_with('#')
+    })
+    .any(|word| word.chars().any(char::is_alphanumeric))
+}
+
+/// A post's text as it's compared for repeats: lowercase, with its spacing
+/// evened out.
+fn normal(html: &str) -> String {
+  render::plain_text(html)
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+    .to_lowercase()
+}
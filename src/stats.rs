@@ -0,0 +1,96 @@
+//! Counting what a run fetched, for `--verbose`.
+
+use std::{
+  fmt,
+  sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
+
+/// The bounds of the buckets requests are counted into by how long they
+/// took, in seconds, as a Prometheus histogram has them.
+pub const BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Counts of what a [`Client`](crate::Client) fetched, shared between its
+/// clones.
+#[derive(Clone, Debug, Default)]
+pub struct Stats(Arc<Counts>);
+
+#[derive(Debug, Default)]
+struct Counts {
+  requests: AtomicUsize,
+  cache_hits: AtomicUsize,
+  micros: AtomicU64,
+  buckets: [AtomicUsize; BUCKETS.len()],
+}
+
+impl Stats {
+  /// Counts a request, and how long it took.
+  pub fn request(&self, took: Duration) {
+    let micros = u64::try_from(took.as_micros()).unwrap_or(u64::MAX);
+
+    self.0.requests.fetch_add(1, Ordering::Relaxed);
+    self.0.micros.fetch_add(micros, Ordering::Relaxed);
+
+    if let Some(bucket) = BUCKETS
+      .iter()
+      .position(|bound| took.as_secs_f64() <= *bound)
+    {
+      self.0.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  /// Counts a document served from a cache rather than fetched.
+  pub fn cache_hit(&self) {
+    self.0.cache_hits.fetch_add(1, Ordering::Relaxed);
+  }
+
+  /// How many requests were made.
+  pub fn requests(&self) -> usize {
+    self.0.requests.load(Ordering::Relaxed)
+  }
+
+  /// How many documents were served from a cache.
+  pub fn cache_hits(&self) -> usize {
+    self.0.cache_hits.load(Ordering::Relaxed)
+  }
+
+  /// How many requests took no longer than each of [`BUCKETS`], in order.
+  pub fn buckets(&self) -> Vec<(f64, usize)> {
+    let mut total = 0;
+
+    BUCKETS
+      .iter()
+      .zip(&self.0.buckets)
+      .map(|(bound, count)| {
+        total += count.load(Ordering::Relaxed);
+        (*bound, total)
+      })
+      .collect()
+  }
+
+  /// How long requests took, added together. Requests made at once each
+  /// count in full, so this can be longer than the run.
+  pub fn time(&self) -> Duration {
+    Duration::from_micros(self.0.micros.load(Ordering::Relaxed))
+  }
+}
+
+impl fmt::Display for Stats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let requests = match self.requests() {
+      1 => "1 request".to_owned(),
+      count => format!("{} requests", count),
+    };
+
+    write!(
+      f,
+      "{} taking {:.2}s, and {} served from cache",
+      requests,
+      self.time().as_secs_f64(),
+      self.cache_hits()
+    )
+  }
+}
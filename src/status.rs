@@ -0,0 +1,194 @@
+//! What a running `apread watch` is up to, for `apread status` to report.
+//!
+//! Watching keeps a small JSON file in the cache directory, rewritten after
+//! every poll: when it started, when it last polled and will poll next,
+//! and when each account it watches was last fetched, or why it couldn't
+//! be. The file is removed when watching stops, so a file whose next poll
+//! is long overdue was left by a watch which didn't stop cleanly.
+
+use std::{fs, io, path::Path};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{timeline::Author, watch::Poll, ApreadErrors};
+
+/// The name of the file a watch's status is kept in, in the cache
+/// directory.
+pub const FILE: &str = "watch-status.json";
+
+/// A watch as it last described itself.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Status {
+  /// The watching process's id.
+  pub pid: u32,
+  /// When watching started.
+  pub started_at: String,
+  /// Seconds between polls.
+  pub every: u64,
+  /// When the timelines were last polled.
+  pub polled_at: Option<String>,
+  /// When they'll next be polled.
+  pub next_poll_at: Option<String>,
+  /// Each account watched, in the order they're watched.
+  pub accounts: Vec<Refresh>,
+}
+
+/// When one watched account was last fetched.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Refresh {
+  /// The account, as it's written in subscriptions.
+  pub account: String,
+  /// When its posts were last fetched.
+  pub refreshed_at: Option<String>,
+  /// Why the latest poll couldn't fetch it, if it couldn't.
+  pub error: Option<String>,
+}
+
+impl Status {
+  /// A watch of these authors, polling every `every` seconds, starting
+  /// now.
+  pub fn new(authors: &[Author], every: u64, now: DateTime<Utc>) -> Self {
+    Self {
+      pid: std::process::id(),
+      started_at: now.to_rfc3339(),
+      every,
+      polled_at: None,
+      next_poll_at: None,
+      accounts: authors
+        .iter()
+        .map(|author| Refresh {
+          account: author.source.to_string(),
+          refreshed_at: None,
+          error: None,
+        })
+        .collect(),
+    }
+  }
+
+  /// Notes a poll finished at `now`: every account it didn't fail on was
+  /// refreshed, and the next poll is `every` seconds away.
+  pub fn polled(&mut self, poll: &Poll, now: DateTime<Utc>) {
+    for refresh in &mut self.accounts {
+      let failed = poll
+        .failed
+        .iter()
+        .find(|(author, _)| author.source.to_string() == refresh.account);
+
+      match failed {
+        Some((_, error)) => refresh.error = Some(error.to_string()),
+        None => {
+          refresh.refreshed_at = Some(now.to_rfc3339());
+          refresh.error = None;
+        }
+      }
+    }
+
+    self.polled_at = Some(now.to_rfc3339());
+    self.next_poll_at = Some((now + self.every()).to_rfc3339());
+  }
+
+  /// How long the watch has been running, as of `now`.
+  pub fn uptime(&self, now: DateTime<Utc>) -> Option<Duration> {
+    let started = DateTime::parse_from_rfc3339(&self.started_at).ok()?;
+
+    Some(now.signed_duration_since(started))
+  }
+
+  /// Whether the watch seems to still be running: its next poll isn't
+  /// overdue by more than the time between polls.
+  pub fn running(&self, now: DateTime<Utc>) -> bool {
+    let Some(next) = self
+      .next_poll_at
+      .as_deref()
+      .and_then(|next| DateTime::parse_from_rfc3339(next).ok())
+    else {
+      // Still on its first poll.
+      return true;
+    };
+    now.signed_duration_since(next) <= self.every()
+  }
+
+  /// The time between polls.
+  fn every(&self) -> Duration {
+    Duration::seconds(i64::from(u32::try_from(self.every).unwrap_or(u32::MAX)))
+  }
+
+  /// The status kept at a path, if there's a watch to have kept one.
+  pub fn load(path: &Path) -> Result<Option<Self>, ApreadErrors> {
+    match fs::read_to_string(path) {
+      Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(error) => Err(error.into()),
+    }
+  }
+
+  /// Keeps the status at a path, replacing whichever was kept there.
+  pub fn save(&self, path: &Path) -> Result<(), ApreadErrors> {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, serde_json::to_string(self)?)?;
+
+    Ok(())
+  }
+}
+
+/// Everything `apread status` reports.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct Report {
+  /// Whether a watch is running.
+  pub running: bool,
+  /// How long it's been running, in seconds.
+  pub uptime_secs: Option<i64>,
+  /// The running watch's status, or the last one's if it stopped without
+  /// tidying up.
+  pub watch: Option<Status>,
+  /// How many items are in the read-it-later queue.
+  pub queue_depth: usize,
+  /// How much the cache directory holds, in bytes.
+  pub cache_bytes: u64,
+}
+
+impl Report {
+  /// A report on a watch, as of `now`.
+  pub fn new(
+    watch: Option<Status>,
+    queue_depth: usize,
+    cache_bytes: u64,
+    now: DateTime<Utc>,
+  ) -> Self {
+    let running = watch.as_ref().is_some_and(|watch| watch.running(now));
+    let uptime_secs = watch
+      .as_ref()
+      .filter(|_| running)
+      .and_then(|watch| watch.uptime(now))
+      .map(|uptime| uptime.num_seconds());
+
+    Self {
+      running,
+      uptime_secs,
+      watch,
+      queue_depth,
+      cache_bytes,
+    }
+  }
+}
+
+/// How many bytes the files in a directory hold, counting those in the
+/// directories inside it. A directory which isn't there holds none.
+pub fn dir_size(dir: &Path) -> u64 {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return 0;
+  };
+
+  entries
+    .flatten()
+    .map(|entry| match entry.file_type() {
+      Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+      Ok(_) => entry.metadata().map_or(0, |metadata| metadata.len()),
+      Err(_) => 0,
+    })
+    .sum()
+}
@@ -0,0 +1,255 @@
+//! Where cached responses are kept, behind one [`Store`] trait.
+//!
+//! Every cache apread keeps, from WebFinger and actor documents to boosted
+//! posts, is a store of response bodies by url. Which store is used is a
+//! setting, `storage`:
+//!
+//! - [`Disk`], the default: a JSON file per response in the cache directory
+//! - [`Sqlite`]: one database file in the cache directory
+//! - [`Memory`]: nothing kept between runs, for a long `apread watch` on a
+//!   read-only disk
+//!
+//! The disk and SQLite stores are shared by every apread process using the
+//! same cache directory, so a `watch` left running and one-off reads in
+//! another terminal fill the cache for each other. Neither leaves a half
+//! written response for another process to trip over.
+
+use std::{
+  collections::HashMap,
+  fmt, fs,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::ApreadErrors;
+
+/// A response body kept in a store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stored {
+  /// The body, as it was fetched.
+  pub body: String,
+  /// When it was kept, in seconds since the Unix epoch.
+  pub kept_at: u64,
+}
+
+impl Stored {
+  /// Whether the body was kept less than `ttl` ago.
+  pub fn fresh(&self, ttl: Duration) -> bool {
+    now().saturating_sub(self.kept_at) < ttl.as_secs()
+  }
+}
+
+/// Somewhere to keep response bodies by url.
+///
+/// Stores are only ever caches: a body which can't be read back is fetched
+/// again, so reading errors are `None` rather than errors.
+pub trait Store: fmt::Debug + Send + Sync {
+  /// The body kept for a url, if there is one.
+  fn get(&self, url: &str) -> Option<Stored>;
+
+  /// Keeps a body for a url, replacing whatever was kept for it.
+  fn put(&self, url: &str, body: &str) -> Result<(), ApreadErrors>;
+}
+
+/// Which kind of [`Store`] to keep cached responses in.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+  /// A JSON file per response.
+  #[default]
+  Disk,
+  /// One SQLite database.
+  Sqlite,
+  /// Memory, forgotten when apread exits.
+  Memory,
+}
+
+/// Opens a store of a kind in a cache directory, keeping what it stores
+/// under `name`, as in `discovery`.
+pub fn open(
+  backend: Backend,
+  dir: &Path,
+  name: &str,
+) -> Result<Arc<dyn Store>, ApreadErrors> {
+  Ok(match backend {
+    Backend::Disk => Arc::new(Disk::new(dir.join(name))),
+    Backend::Sqlite => {
+      fs::create_dir_all(dir)?;
+      Arc::new(Sqlite::open(dir.join("cache.sqlite3"), name)?)
+    }
+    Backend::Memory => Arc::new(Memory::default()),
+  })
+}
+
+/// Responses kept in memory, for as long as the process runs.
+#[derive(Debug, Default)]
+pub struct Memory(Mutex<HashMap<String, Stored>>);
+
+impl Store for Memory {
+  fn get(&self, url: &str) -> Option<Stored> {
+    self.0.lock().ok()?.get(url).cloned()
+  }
+
+  fn put(&self, url: &str, body: &str) -> Result<(), ApreadErrors> {
+    let stored = Stored {
+      body: body.to_owned(),
+      kept_at: now(),
+    };
+
+    // A lock poisoned by a panicking thread only costs a fetch next time.
+    if let Ok(mut responses) = self.0.lock() {
+      responses.insert(url.to_owned(), stored);
+    }
+
+    Ok(())
+  }
+}
+
+/// Responses kept as a JSON file each in a directory.
+#[derive(Clone, Debug)]
+pub struct Disk {
+  dir: PathBuf,
+}
+
+#[derive(Deserialize, Serialize)]
+struct File {
+  url: String,
+  fetched_at: u64,
+  body: String,
+}
+
+impl Disk {
+  /// A store in the given directory, created when it's first written to.
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self { dir: dir.into() }
+  }
+
+  /// Where a url's response is kept. Names are only readable, not unique,
+  /// so files remember their url to tell any collisions apart.
+  fn path(&self, url: &str) -> PathBuf {
+    let name: String = url
+      .chars()
+      .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+      .collect();
+
+    self.dir.join(format!("{}.json", name))
+  }
+}
+
+impl Store for Disk {
+  fn get(&self, url: &str) -> Option<Stored> {
+    let file: File =
+      serde_json::from_str(&fs::read_to_string(self.path(url)).ok()?).ok()?;
+
+    (file.url == url).then_some(Stored {
+      body: file.body,
+      kept_at: file.fetched_at,
+    })
+  }
+
+  fn put(&self, url: &str, body: &str) -> Result<(), ApreadErrors> {
+    let file = File {
+      url: url.to_owned(),
+      fetched_at: now(),
+      body: body.to_owned(),
+    };
+    let path = self.path(url);
+    // Written beside the real file then moved over it, so another process
+    // reading at the same time sees the old response or the new, never
+    // half of one.
+    let partial =
+      path.with_extension(format!("json.{}.partial", std::process::id()));
+
+    fs::create_dir_all(&self.dir)?;
+    fs::write(&partial, serde_json::to_string(&file)?)?;
+    fs::rename(&partial, &path)?;
+
+    Ok(())
+  }
+}
+
+/// Responses kept in a table of a SQLite database, which several stores
+/// (and processes) can share.
+#[derive(Debug)]
+pub struct Sqlite {
+  connection: Mutex<Connection>,
+  name: String,
+}
+
+impl Sqlite {
+  /// Opens (creating as needed) the database at a path, keeping responses
+  /// under `name` so stores sharing it don't see each other's.
+  pub fn open(
+    path: impl AsRef<Path>,
+    name: &str,
+  ) -> Result<Self, ApreadErrors> {
+    let connection = Connection::open(path)?;
+
+    // Other processes may be writing; wait for them rather than failing.
+    connection.busy_timeout(Duration::from_secs(5))?;
+    connection.execute_batch(
+      "CREATE TABLE IF NOT EXISTS responses (
+        store TEXT NOT NULL,
+        url TEXT NOT NULL,
+        body TEXT NOT NULL,
+        kept_at INTEGER NOT NULL,
+        PRIMARY KEY (store, url)
+      );",
+    )?;
+
+    Ok(Self {
+      connection: Mutex::new(connection),
+      name: name.to_owned(),
+    })
+  }
+}
+
+impl Store for Sqlite {
+  fn get(&self, url: &str) -> Option<Stored> {
+    self
+      .connection
+      .lock()
+      .ok()?
+      .query_row(
+        "SELECT body, kept_at FROM responses WHERE store = ?1 AND url = ?2",
+        params![self.name, url],
+        |row| {
+          Ok(Stored {
+            body: row.get(0)?,
+            kept_at: row.get(1)?,
+          })
+        },
+      )
+      .ok()
+  }
+
+  fn put(&self, url: &str, body: &str) -> Result<(), ApreadErrors> {
+    let connection = match self.connection.lock() {
+      Ok(connection) => connection,
+      Err(_) => return Ok(()),
+    };
+
+    connection.execute(
+      "INSERT INTO responses (store, url, body, kept_at) VALUES (?1, ?2, ?3, ?4)
+       ON CONFLICT (store, url) DO UPDATE SET
+         body = excluded.body,
+         kept_at = excluded.kept_at",
+      params![self.name, url, body, now()],
+    )?;
+
+    Ok(())
+  }
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |since| since.as_secs())
+}
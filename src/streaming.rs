@@ -0,0 +1,221 @@
+//! Reading timelines live, from an instance's streaming API.
+//!
+//! Rather than polling as `apread watch` does, `apread stream` holds a
+//! connection open to your instance, which sends each post down it as
+//! Server-Sent Events the moment it's posted. Connections drop, so they're
+//! made again after [`backoff`], waiting longer the more attempts in a row
+//! have failed.
+
+use std::time::Duration;
+
+use crate::{
+  mastodon::{self, Status},
+  timeline::Entry,
+  ApreadErrors,
+};
+
+/// The longest to wait before connecting again.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// One of the streams an instance sends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stream {
+  /// The logged in account's home timeline.
+  Home,
+  /// Every public post the instance knows of.
+  Public,
+  /// Public posts by the instance's own accounts.
+  Local,
+  /// Public posts with a hashtag, named without its `#`.
+  Hashtag(String),
+  /// One of the logged in account's lists, by id.
+  List(String),
+}
+
+impl Stream {
+  /// The stream's path and query, under the streaming API's base url.
+  pub fn path(&self) -> String {
+    let query = |name: &str, value: &str| {
+      url::form_urlencoded::Serializer::new(String::new())
+        .append_pair(name, value)
+        .finish()
+    };
+
+    match self {
+      Self::Home => "/api/v1/streaming/user".to_owned(),
+      Self::Public => "/api/v1/streaming/public".to_owned(),
+      Self::Local => "/api/v1/streaming/public/local".to_owned(),
+      Self::Hashtag(tag) => {
+        format!("/api/v1/streaming/hashtag?{}", query("tag", tag))
+      }
+      Self::List(id) => {
+        format!("/api/v1/streaming/list?{}", query("list", id))
+      }
+    }
+  }
+}
+
+/// Something a stream sent worth acting on.
+#[derive(Clone, Debug)]
+pub enum Event {
+  /// A new post.
+  Update(Box<Entry>),
+  /// A post edited since it was sent.
+  Edit(Box<Entry>),
+  /// The id of a post since deleted.
+  Delete(String),
+}
+
+impl Event {
+  /// The event a message from an instance's stream stands for, if it's one
+  /// apread acts on.
+  pub fn parse(instance: &str, message: &Message) -> Option<Self> {
+    let entry = || {
+      let status: Status = serde_json::from_str(&message.data).ok()?;
+      mastodon::entries(instance, vec![status])
+        .pop()
+        .map(Box::new)
+    };
+
+    match message.event.as_str() {
+      "update" => entry().map(Self::Update),
+      "status.update" => entry().map(Self::Edit),
+      "delete" => Some(Self::Delete(message.data.clone())),
+      _ => None,
+    }
+  }
+}
+
+/// One Server-Sent Event, as it came.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Message {
+  /// The event's name, as in `update`, or `message` if it wasn't given one.
+  pub event: String,
+  /// The event's data, its lines joined back together.
+  pub data: String,
+}
+
+/// Reads Server-Sent Events out of a body as it arrives, however it's split
+/// into chunks.
+#[derive(Debug, Default)]
+pub struct Parser {
+  pending: Vec<u8>,
+  event: Option<String>,
+  data: Option<String>,
+}
+
+impl Parser {
+  /// Takes in the next chunk of the body, giving back the messages it
+  /// finishes.
+  pub fn push(&mut self, chunk: &[u8]) -> Vec<Message> {
+    let mut messages = vec![];
+
+    self.pending.extend_from_slice(chunk);
+
+    // Lines are only read once they're whole, so a character split between
+    // chunks is never cut in half.
+    while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+      let line: Vec<u8> = self.pending.drain(..=end).collect();
+      let line = String::from_utf8_lossy(&line);
+      let line = line.trim_end_matches(['\n', '\r']);
+
+      if line.is_empty() {
+        messages.extend(self.dispatch());
+        continue;
+      }
+
+      // Lines starting with a colon are comments, which instances send as
+      // heartbeats.
+      if line.starts_with(':') {
+        continue;
+      }
+
+      let (field, value) = line.split_once(':').unwrap_or((line, ""));
+      let value = value.strip_prefix(' ').unwrap_or(value);
+
+      match field {
+        "event" => self.event = Some(value.to_owned()),
+        "data" => match &mut self.data {
+          Some(data) => {
+            data.push('\n');
+            data.push_str(value);
+          }
+          None => self.data = Some(value.to_owned()),
+        },
+        _ => {}
+      }
+    }
+
+    messages
+  }
+
+  /// The message the fields read so far make up, if they make one up.
+  fn dispatch(&mut self) -> Option<Message> {
+    let event = self.event.take();
+    let data = self.data.take()?;
+
+    Some(Message {
+      event: event.unwrap_or_else(|| "message".to_owned()),
+      data,
+    })
+  }
+}
+
+/// A stream being read, from a connection the [`Client`](crate::Client)
+/// opened.
+#[derive(Debug)]
+pub struct Events {
+  response: reqwest::Response,
+  instance: String,
+  parser: Parser,
+  ready: Vec<Event>,
+}
+
+impl Events {
+  pub(crate) fn new(response: reqwest::Response, instance: &str) -> Self {
+    Self {
+      response,
+      instance: instance.to_owned(),
+      parser: Parser::default(),
+      ready: vec![],
+    }
+  }
+
+  /// The next event the stream sends, waiting for it, or none once the
+  /// connection's closed.
+  pub async fn next(&mut self) -> Result<Option<Event>, ApreadErrors> {
+    while self.ready.is_empty() {
+      let Some(chunk) = self.response.chunk().await? else {
+        return Ok(None);
+      };
+      let messages = self.parser.push(&chunk);
+      let instance = &self.instance;
+
+      self.ready = messages
+        .iter()
+        .filter_map(|message| Event::parse(instance, message))
+        .rev()
+        .collect();
+    }
+
+    Ok(self.ready.pop())
+  }
+}
+
+/// How long to wait before connecting again, after `failures` attempts in a
+/// row have failed: a second, doubling each time, up to [`MAX_BACKOFF`].
+pub fn backoff(failures: u32) -> Duration {
+  Duration::from_secs(1u64 << failures.min(6)).min(MAX_BACKOFF)
+}
+
+/// The base url of an instance's streaming API, from the `wss://` url its
+/// `/api/v2/instance` gives for it.
+pub fn base_url(streaming: &str) -> String {
+  let url = streaming.trim_end_matches('/');
+
+  match url.split_once("://") {
+    Some(("wss", rest)) => format!("https://{}", rest),
+    Some(("ws", rest)) => format!("http://{}", rest),
+    _ => url.to_owned(),
+  }
+}
@@ -0,0 +1,134 @@
+//! The accounts a reader follows, kept in a subscriptions file.
+//!
+//! The file is TOML, with one `[[account]]` table per subscription. Each
+//! names either a fediverse `handle` or the url of an RSS or Atom `feed`:
+//!
+//! ```toml
+//! [[account]]
+//! handle = "alice@example.social"
+//! nickname = "alice"
+//! color = "green"
+//!
+//! [[account]]
+//! feed = "https://blog.example/atom.xml"
+//! nickname = "blog"
+//! ```
+//!
+//! Busy accounts can be kept from drowning out the rest of the timeline:
+//! `replies = false` leaves their replies out, `max_per_refresh = 10` shows
+//! no more than ten of their posts at a time, and `boosts = true` shows
+//! what they boost as well as what they write (boosts are left out
+//! otherwise).
+//!
+//! Builds with the `bsky` or `nostr` features also take Bluesky handles,
+//! like `alice.bsky.social`, and Nostr `npub1...` keys as a `handle`.
+
+use std::path::{Path, PathBuf};
+
+use config::{Config, File, FileFormat};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, Localized},
+  render::Color,
+  ApreadErrors,
+};
+
+/// Raised when a subscription has neither a handle nor a feed.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct EmptySubscription;
+
+impl Localized for EmptySubscription {
+  fn id(&self) -> &'static str {
+    "error-empty-subscription"
+  }
+}
+
+/// Everything in a subscriptions file.
+#[derive(Debug, Default, Deserialize)]
+pub struct Subscriptions {
+  /// The accounts subscribed to, in the order they're listed.
+  #[serde(default, rename = "account")]
+  pub accounts: Vec<Subscription>,
+}
+
+/// One followed account, and how to show it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Subscription {
+  /// The account's handle, as `id@domain`, or whatever else
+  /// [`Source::parse`](crate::timeline::Source::parse) reads.
+  pub handle: Option<String>,
+  /// The url of an RSS or Atom feed, for subscriptions outside the
+  /// fediverse.
+  pub feed: Option<String>,
+  /// A local name to show in place of the account's id.
+  pub nickname: Option<String>,
+  /// The color to show the account's name in.
+  pub color: Option<Color>,
+  /// Whether to show the account's boosts. They're left out unless asked
+  /// for, since showing each one means fetching the boosted post.
+  #[serde(default)]
+  pub boosts: bool,
+  /// Whether to show the account's replies.
+  #[serde(default = "shown")]
+  pub replies: bool,
+  /// The most posts to show from the account each time it's read.
+  pub max_per_refresh: Option<usize>,
+}
+
+fn shown() -> bool {
+  true
+}
+
+impl Subscription {
+  /// Whether this subscription goes by a name, either as its nickname, its
+  /// handle or its feed url.
+  pub fn matches(&self, name: &str) -> bool {
+    [&self.nickname, &self.handle, &self.feed]
+      .iter()
+      .any(|candidate| candidate.as_deref() == Some(name))
+  }
+}
+
+impl Subscriptions {
+  /// Where the subscriptions file lives unless told otherwise.
+  pub fn default_path() -> Option<PathBuf> {
+    Some(
+      dirs::config_dir()?
+        .join("apread")
+        .join("subscriptions.toml"),
+    )
+  }
+
+  /// Reads a subscriptions file. A missing file means no subscriptions.
+  pub fn load(path: &Path) -> Result<Self, ApreadErrors> {
+    Ok(
+      Config::builder()
+        .add_source(File::from(path).format(FileFormat::Toml).required(false))
+        .build()?
+        .try_deserialize()?,
+    )
+  }
+
+  /// The subscription for a handle, if there is one.
+  pub fn find(&self, handle: &str) -> Option<&Subscription> {
+    self
+      .accounts
+      .iter()
+      .find(|subscription| subscription.handle.as_deref() == Some(handle))
+  }
+
+  /// The subscriptions going by any of the given names, or all of them if
+  /// there are no names.
+  pub fn only(&self, names: &[String]) -> Vec<&Subscription> {
+    self
+      .accounts
+      .iter()
+      .filter(|subscription| {
+        names.is_empty() || names.iter().any(|name| subscription.matches(name))
+      })
+      .collect()
+  }
+}
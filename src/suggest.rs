@@ -0,0 +1,135 @@
+//! Suggesting accounts to follow, from who the accounts you read talk to.
+//!
+//! Every account mentioned in the archive's posts, and every account whose
+//! posts the archived accounts have boosted, is a suggestion, ranked by how
+//! often it was mentioned or boosted, then by how many of the accounts you
+//! read did so. Accounts you already follow, and the archived accounts
+//! themselves, aren't suggested. Boosts are only known of where `apread
+//! archive` read an account with its boosts shown.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{archive::Archive, mention, ApreadErrors, Client};
+
+/// An account worth following, and why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+  /// The account's handle, as in `bob@example.social`.
+  pub handle: String,
+  /// How many times the accounts read mentioned it.
+  pub mentions: usize,
+  /// How many of its posts the accounts read boosted.
+  pub boosts: usize,
+  /// Which of the accounts read mentioned or boosted it.
+  pub by: BTreeSet<String>,
+}
+
+impl Suggestion {
+  /// How often the account was interacted with, all told.
+  pub fn interactions(&self) -> usize {
+    self.mentions + self.boosts
+  }
+}
+
+/// Ranks the accounts interacted with, each given as who interacted and the
+/// handle of who they interacted with, leaving out `excluded` handles and
+/// anyone interacting with themselves. Handles are matched ignoring case
+/// and any leading `@`.
+pub fn rank(
+  mentions: &[(String, String)],
+  boosts: &[(String, String)],
+  excluded: &[String],
+) -> Vec<Suggestion> {
+  let excluded: BTreeSet<_> =
+    excluded.iter().map(|handle| normal(handle)).collect();
+  let mut found: BTreeMap<String, Suggestion> = BTreeMap::new();
+  let counted = mentions
+    .iter()
+    .map(|pair| (pair, true))
+    .chain(boosts.iter().map(|pair| (pair, false)));
+
+  for ((by, handle), mention) in counted {
+    let key = normal(handle);
+
+    if excluded.contains(&key) || key == normal(by) {
+      continue;
+    }
+
+    let suggestion = found.entry(key.clone()).or_insert_with(|| Suggestion {
+      handle: key,
+      mentions: 0,
+      boosts: 0,
+      by: BTreeSet::new(),
+    });
+
+    match mention {
+      true => suggestion.mentions += 1,
+      false => suggestion.boosts += 1,
+    }
+    suggestion.by.insert(normal(by));
+  }
+
+  let mut suggestions: Vec<_> = found.into_values().collect();
+
+  suggestions.sort_by(|a, b| {
+    b.interactions()
+      .cmp(&a.interactions())
+      .then(b.by.len().cmp(&a.by.len()))
+      .then(a.handle.cmp(&b.handle))
+  });
+
+  suggestions
+}
+
+/// Suggestions from everything in the archive, leaving out the handles
+/// `following` gives. The accounts boosted are looked up by their actor
+/// urls to find their handles; the ones which can't be are left out.
+pub async fn suggest(
+  client: &Client,
+  archive: &Archive,
+  following: &[String],
+) -> Result<Vec<Suggestion>, ApreadErrors> {
+  let posts = archive.posts()?;
+  let mut excluded = following.to_vec();
+  let mut mentions = vec![];
+  let mut boosts = vec![];
+
+  for post in &posts {
+    let Some(author) = &post.author else {
+      continue;
+    };
+
+    excluded.push(author.clone());
+    mentions.extend(
+      mention::handles(&post.content, &[])
+        .into_iter()
+        .map(|handle| (author.clone(), handle)),
+    );
+  }
+
+  let mut handles: BTreeMap<String, Option<String>> = BTreeMap::new();
+
+  for (author, actor) in archive.boosts()? {
+    if !handles.contains_key(&actor) {
+      let handle = match client.interrupted() {
+        true => None,
+        false => client.actor(&actor).await.ok().and_then(|a| a.handle()),
+      };
+
+      handles.insert(actor.clone(), handle);
+    }
+
+    if let Some(Some(handle)) = handles.get(&actor) {
+      boosts.push((author, handle.clone()));
+    }
+  }
+
+  Ok(rank(&mentions, &boosts, &excluded))
+}
+
+/// A handle as it's compared: lowercase, without its leading `@`.
+fn normal(handle: &str) -> String {
+  handle.trim_start_matches('@').to_lowercase()
+}
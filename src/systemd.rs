@@ -0,0 +1,176 @@
+//! Running `apread watch` as a systemd user service.
+//!
+//! With `--systemd`, watching tells systemd when it's ready (once the first
+//! poll is done) and what it's up to, over the socket systemd names in
+//! `NOTIFY_SOCKET`, so the unit can be `Type=notify`. When systemd starts
+//! it from a socket unit, the metrics listener is the socket it was handed
+//! rather than one apread binds itself, so the port can be privileged or
+//! shared between restarts without apread ever holding it.
+//!
+//! `apread install-service` writes the units for all this. Outside systemd
+//! (or off Unix) the notifications and listeners just aren't there.
+
+use std::{
+  io,
+  net::{SocketAddr, TcpListener},
+  path::PathBuf,
+};
+
+use thiserror::Error;
+
+use crate::i18n::{self, Localized};
+
+/// The name of the service unit `apread install-service` writes.
+pub const SERVICE: &str = "apread.service";
+
+/// The name of the socket unit it writes, when metrics are asked for.
+pub const SOCKET: &str = "apread.socket";
+
+/// Raised when there's no config directory for systemd's user units.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoUnitDir;
+
+impl Localized for NoUnitDir {
+  fn id(&self) -> &'static str {
+    "error-no-unit-dir"
+  }
+}
+
+/// The first file descriptor systemd passes sockets from.
+#[cfg(unix)]
+const LISTEN_FDS_START: i32 = 3;
+
+/// Tells systemd something about the service, as in `READY=1` or
+/// `STATUS=...`, if it's listening. Gives back whether it was.
+pub fn notify(state: &str) -> io::Result<bool> {
+  #[cfg(unix)]
+  {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+      return Ok(false);
+    };
+    let socket = UnixDatagram::unbound()?;
+    let path = path.to_string_lossy();
+
+    // A leading `@` names a socket in Linux's abstract namespace.
+    match path.strip_prefix('@') {
+      #[cfg(target_os = "linux")]
+      Some(name) => {
+        use std::os::{linux::net::SocketAddrExt, unix::net};
+
+        let address = net::SocketAddr::from_abstract_name(name)?;
+        socket.send_to_addr(state.as_bytes(), &address)?;
+      }
+      _ => {
+        socket.send_to(state.as_bytes(), path.as_ref())?;
+      }
+    }
+
+    Ok(true)
+  }
+
+  #[cfg(not(unix))]
+  {
+    let _ = state;
+    Ok(false)
+  }
+}
+
+/// The listening socket systemd passed this process, if it passed one.
+pub fn listener() -> Option<TcpListener> {
+  #[cfg(unix)]
+  {
+    use std::os::fd::FromRawFd;
+
+    let ours = std::env::var("LISTEN_PID").ok()?.parse::<u32>().ok()?;
+    let count = std::env::var("LISTEN_FDS").ok()?.parse::<i32>().ok()?;
+
+    if ours != std::process::id() || count < 1 {
+      return None;
+    }
+
+    // SAFETY: systemd hands the process its sockets from descriptor 3 on,
+    // and LISTEN_PID says they were handed to this process, which hasn't
+    // opened anything of its own in their place.
+    Some(unsafe { TcpListener::from_raw_fd(LISTEN_FDS_START) })
+  }
+
+  #[cfg(not(unix))]
+  None
+}
+
+/// Where systemd looks for a user's units.
+pub fn unit_dir() -> Result<PathBuf, NoUnitDir> {
+  Ok(
+    dirs::config_dir()
+      .ok_or(NoUnitDir)?
+      .join("systemd")
+      .join("user"),
+  )
+}
+
+/// A service unit running `apread watch` with these arguments, as the
+/// program at `exe`.
+pub fn service(exe: &str, args: &[String], socket: bool) -> String {
+  let mut command =
+    vec![quoted(exe), "watch".to_owned(), "--systemd".to_owned()];
+  let requires = match socket {
+    true => format!("Requires={0}\nAfter={0}\n", SOCKET),
+    false => String::new(),
+  };
+
+  command.extend(args.iter().map(|arg| quoted(arg)));
+
+  format!(
+    "[Unit]\n\
+     Description=apread, watching timelines for new posts\n\
+     Wants=network-online.target\n\
+     After=network-online.target\n\
+     {}\n\
+     [Service]\n\
+     Type=notify\n\
+     ExecStart={}\n\
+     Restart=on-failure\n\
+     RestartSec=30\n\
+     \n\
+     [Install]\n\
+     WantedBy=default.target\n",
+    requires,
+    command.join(" ")
+  )
+}
+
+/// A socket unit listening for metrics requests at an address, for
+/// systemd to hand to the service.
+pub fn socket(address: SocketAddr) -> String {
+  format!(
+    "[Unit]\n\
+     Description=apread's metrics listener\n\
+     \n\
+     [Socket]\n\
+     ListenStream={}\n\
+     \n\
+     [Install]\n\
+     WantedBy=sockets.target\n",
+    address
+  )
+}
+
+/// An argument quoted for an `ExecStart=` line, if it needs to be.
+fn quoted(arg: &str) -> String {
+  if !arg.is_empty()
+    && !arg.contains(|c: char| c.is_whitespace() || "\"'\\$%;".contains(c))
+  {
+    return arg.to_owned();
+  }
+
+  let escaped = arg
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('$', "$$")
+    .replace('%', "%%");
+
+  format!("\"{}\"", escaped)
+}
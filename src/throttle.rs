@@ -0,0 +1,65 @@
+//! Keeping apread from hammering any one server.
+//!
+//! Reading hundreds of subscriptions means reading them all at once, but a
+//! follow list tends to bunch up on a few big instances. Every request the
+//! client makes first takes a permit for the host it's going to, and each
+//! host only has so many, set by the `per_instance` setting: however many
+//! authors are being read, no instance has more than that many requests in
+//! flight from apread, and the rest wait their turn.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// How many requests to have in flight to one instance unless told
+/// otherwise.
+pub const DEFAULT_PER_INSTANCE: usize = 2;
+
+/// Permits for requests, so many per host.
+#[derive(Clone, Debug)]
+pub struct Throttle {
+  per_host: usize,
+  hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl Default for Throttle {
+  fn default() -> Self {
+    Self::new(DEFAULT_PER_INSTANCE)
+  }
+}
+
+impl Throttle {
+  /// A throttle allowing `per_host` requests in flight to each host at
+  /// once, or any number if it's `0`.
+  pub fn new(per_host: usize) -> Self {
+    Self {
+      per_host,
+      hosts: Arc::default(),
+    }
+  }
+
+  /// Waits for a permit to send a request to a url's host, which is given
+  /// back when it's dropped. Unlimited throttles, and urls without a host,
+  /// don't need one.
+  pub async fn permit(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+    if self.per_host == 0 {
+      return None;
+    }
+
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = format!("{}:{}", url.host_str()?, url.port_or_known_default()?);
+    let semaphore = self
+      .hosts
+      .lock()
+      .ok()?
+      .entry(host)
+      .or_insert_with(|| Arc::new(Semaphore::new(self.per_host)))
+      .clone();
+
+    // Semaphores here are never closed, so this always gives a permit.
+    semaphore.acquire_owned().await.ok()
+  }
+}
@@ -0,0 +1,900 @@
+//! Timelines: posts from one or more accounts, ready to render.
+
+use std::{cmp::Reverse, collections::HashSet, future::Future};
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime};
+use serde::Deserialize;
+
+use crate::{
+  activity::{Actor, Item, Page, Post},
+  bridge::Bridge,
+  diagnostics::{Diagnostic, Kind},
+  feed,
+  handle::{BadHandleError, Handle},
+  hashtag::Hashtag,
+  login::NoSuchList,
+  mastodon,
+  render::Color,
+  search::Search,
+  subscriptions::{EmptySubscription, Subscription},
+  ApreadErrors, Client,
+};
+
+/// Where an author's posts come from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+  /// A fediverse account, read through its outbox.
+  Account(Handle),
+  /// An RSS or Atom feed, by its url.
+  Feed(String),
+  /// A hashtag's timeline on an instance.
+  Tag(Hashtag),
+  /// One of the logged in account's lists, by its name.
+  List(String),
+  /// The posts a search of the logged in account's instance finds.
+  Search(Search),
+  /// A Bluesky account, by its handle.
+  #[cfg(feature = "bsky")]
+  Bluesky(String),
+  /// A Nostr account, by its `npub1...` public key.
+  #[cfg(feature = "nostr")]
+  Nostr(String),
+}
+
+impl Source {
+  /// Reads an account from however it's written: `id@domain` for the
+  /// fediverse, `#tag@instance` for a hashtag, and, when apread is built
+  /// for them, a bare domain for Bluesky or an `npub1...` key for Nostr.
+  pub fn parse(text: &str) -> Result<Self, BadHandleError> {
+    if text.starts_with('#') {
+      return Ok(Self::Tag(Hashtag::parse(text)?));
+    }
+
+    #[cfg(feature = "nostr")]
+    if text.starts_with("npub1") {
+      return match crate::nostr::public_key(text) {
+        Some(_) => Ok(Self::Nostr(text.to_owned())),
+        None => Err(BadHandleError),
+      };
+    }
+
+    #[cfg(feature = "bsky")]
+    if crate::bsky::is_handle(text) {
+      return Ok(Self::Bluesky(text.trim_start_matches('@').to_owned()));
+    }
+
+    Ok(Self::Account(Handle::parse_string(text)?))
+  }
+}
+
+impl std::fmt::Display for Source {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Account(handle) => handle.fmt(f),
+      Self::Feed(url) => f.write_str(url),
+      Self::Tag(tag) => tag.fmt(f),
+      Self::List(title) => write!(f, "list:{}", title),
+      Self::Search(search) => write!(f, "search:{}", search.query),
+      #[cfg(feature = "bsky")]
+      Self::Bluesky(handle) => f.write_str(handle),
+      #[cfg(feature = "nostr")]
+      Self::Nostr(npub) => f.write_str(npub),
+    }
+  }
+}
+
+/// Who a timeline entry is from, and how to show them.
+#[derive(Clone, Debug)]
+pub struct Author {
+  /// Where the author's posts come from.
+  pub source: Source,
+  /// A local name to show in place of the account's id.
+  pub nickname: Option<String>,
+  /// The color to show the account's name in.
+  pub color: Option<Color>,
+  /// The bridge the account comes through, if it's bridged from elsewhere.
+  pub bridge: Option<Bridge>,
+  /// How much of the author's timeline to show.
+  pub mix: Mix,
+}
+
+/// How much of an author's timeline to show, as their subscription asks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mix {
+  /// Whether to show what they boost.
+  pub boosts: bool,
+  /// Whether to show their replies.
+  pub replies: bool,
+  /// The most of their posts to show each time they're read.
+  pub max_per_refresh: Option<usize>,
+}
+
+impl Default for Mix {
+  fn default() -> Self {
+    Self {
+      boosts: false,
+      replies: true,
+      max_per_refresh: None,
+    }
+  }
+}
+
+impl Author {
+  /// An author known only by their handle.
+  pub fn new(handle: Handle) -> Self {
+    Self::from_source(Source::Account(handle))
+  }
+
+  /// An author known only by their feed's url.
+  pub fn feed(url: impl Into<String>) -> Self {
+    Self::from_source(Source::Feed(url.into()))
+  }
+
+  /// An author known only by where their posts come from.
+  pub fn from_source(source: Source) -> Self {
+    let bridge = match &source {
+      Source::Account(handle) => Bridge::from_domain(&handle.domain),
+      _ => None,
+    };
+
+    Self {
+      source,
+      nickname: None,
+      color: None,
+      bridge,
+      mix: Mix::default(),
+    }
+  }
+
+  /// The author of a subscription, shown the way it asks.
+  pub fn from_subscription(
+    subscription: &Subscription,
+  ) -> Result<Self, ApreadErrors> {
+    let source = match (&subscription.handle, &subscription.feed) {
+      (Some(handle), _) => Source::parse(handle)?,
+      (None, Some(feed)) => Source::Feed(feed.clone()),
+      (None, None) => return Err(EmptySubscription.into()),
+    };
+
+    Ok(Self {
+      nickname: subscription.nickname.clone(),
+      color: subscription.color,
+      mix: Mix {
+        boosts: subscription.boosts,
+        replies: subscription.replies,
+        max_per_refresh: subscription.max_per_refresh,
+      },
+      ..Self::from_source(source)
+    })
+  }
+
+  /// The author's handle, if they're a fediverse account.
+  pub fn handle(&self) -> Option<&Handle> {
+    match &self.source {
+      Source::Account(handle) => Some(handle),
+      _ => None,
+    }
+  }
+
+  /// The name to show for this author: their nickname, or failing that
+  /// their account id, their feed's domain, or the start of their Nostr key.
+  pub fn label(&self) -> String {
+    if let Some(nickname) = &self.nickname {
+      return nickname.clone();
+    }
+
+    match &self.source {
+      Source::Account(handle) => handle.id.clone(),
+      Source::Feed(url) => reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_else(|| url.clone()),
+      Source::Tag(tag) => format!("#{}", tag.name),
+      Source::List(title) => title.clone(),
+      Source::Search(search) => format!("\"{}\"", search.query),
+      #[cfg(feature = "bsky")]
+      Source::Bluesky(handle) => handle.clone(),
+      #[cfg(feature = "nostr")]
+      Source::Nostr(npub) => format!("{}…", npub.get(..12).unwrap_or(npub)),
+    }
+  }
+}
+
+/// Which end of an account's posts reading starts from.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Start {
+  /// The newest posts, walking back through older ones.
+  #[default]
+  Newest,
+  /// The account's very first posts, walking forward through newer ones.
+  Oldest,
+}
+
+/// How much of an account's outbox to read, and from which end.
+#[derive(Clone, Copy, Debug)]
+pub struct Walk {
+  /// The end to start from.
+  pub start: Start,
+  /// How many pages to read before stopping.
+  pub pages: usize,
+  /// How many posts to read before stopping, however many pages that
+  /// takes (up to `pages`).
+  pub limit: Option<usize>,
+}
+
+impl Default for Walk {
+  fn default() -> Self {
+    Self {
+      start: Start::Newest,
+      pages: 1,
+      limit: None,
+    }
+  }
+}
+
+/// How many pages of each author's posts a walk with a limit reads at most,
+/// unless it's told how many.
+pub const LIMIT_PAGES: usize = 20;
+
+/// How far into each author's posts to read.
+#[derive(Clone, Copy, Debug)]
+pub enum Reading {
+  /// Walk their posts from one end, as [`walk`] does.
+  Walk(Walk),
+  /// Dig back to the posts around a date, as [`around`] does.
+  Around(DateTime<FixedOffset>),
+}
+
+/// What reading several authors found: every post that could be read, and
+/// who couldn't be.
+#[derive(Debug, Default)]
+pub struct Gathered {
+  /// Every author's entries, in the order the authors came, each author's
+  /// as they were read.
+  pub entries: Vec<Entry>,
+  /// The authors whose posts couldn't be read, and why, in the order the
+  /// authors came.
+  pub failed: Vec<(Author, ApreadErrors)>,
+  /// How many posts the authors that were read have all told, if every
+  /// one of their outboxes said.
+  pub total: Option<usize>,
+}
+
+/// Reads every author at once, rather than one after another. One author
+/// failing doesn't stop the rest being read.
+pub async fn gather(
+  client: &Client,
+  authors: &[Author],
+  reading: Reading,
+) -> Gathered {
+  let mut tasks = tokio::task::JoinSet::new();
+
+  for (index, author) in authors.iter().enumerate() {
+    let client = client.clone();
+    let author = author.clone();
+
+    tasks.spawn(async move {
+      let found = match reading {
+        Reading::Walk(walk) => walk_counted(&client, &author, walk).await,
+        Reading::Around(date) => around(&client, &author, date, AROUND_WINDOW)
+          .await
+          .map(|entries| Walked {
+            entries,
+            total: None,
+          }),
+      };
+
+      (index, author, found)
+    });
+  }
+
+  let mut results = vec![];
+
+  while let Some(joined) = tasks.join_next().await {
+    match joined {
+      Ok(result) => results.push(result),
+      // Tasks are never cancelled, so they only end early by panicking,
+      // which carries on here.
+      Err(error) => std::panic::resume_unwind(error.into_panic()),
+    }
+  }
+
+  results.sort_by_key(|(index, _, _)| *index);
+
+  let mut gathered = Gathered {
+    total: Some(0),
+    ..Gathered::default()
+  };
+
+  for (_, author, found) in results {
+    match found {
+      Ok(walked) => {
+        gathered.entries.extend(walked.entries);
+        gathered.total = gathered.total.zip(walked.total).map(|(a, b)| a + b);
+      }
+      Err(error) => gathered.failed.push((author, error)),
+    }
+  }
+
+  gathered
+}
+
+/// Fetches the newest posts from wherever an author posts. Accounts which
+/// turn out to be bridged are marked as such along the way.
+pub async fn fetch(
+  client: &Client,
+  author: &Author,
+) -> Result<Vec<Entry>, ApreadErrors> {
+  walk(client, author, Walk::default()).await
+}
+
+/// Fetches an author's posts as far as the walk goes. Outboxes are walked
+/// page by page, along `next` links from the newest page or `prev` links
+/// from the oldest; other sources only have the one page to read. Walks with
+/// a `limit`, and authors with a `max_per_refresh`, stop there.
+pub async fn walk(
+  client: &Client,
+  author: &Author,
+  walk: Walk,
+) -> Result<Vec<Entry>, ApreadErrors> {
+  Ok(walk_counted(client, author, walk).await?.entries)
+}
+
+/// What a walk found.
+#[derive(Clone, Debug, Default)]
+pub struct Walked {
+  /// The entries, as [`walk`] gives them.
+  pub entries: Vec<Entry>,
+  /// How many posts the author has all told, where their outbox says.
+  pub total: Option<usize>,
+}
+
+/// Walks an author's posts as [`walk`] does, also keeping how many posts
+/// they have all told.
+pub async fn walk_counted(
+  client: &Client,
+  author: &Author,
+  walk: Walk,
+) -> Result<Walked, ApreadErrors> {
+  let mut total = None;
+  let mut found = match &author.source {
+    Source::Account(handle) => {
+      let actor = client.resolve(handle).await?;
+      let author = bridged(author, &actor);
+      let outbox = client.outbox(actor.outbox.as_str()).await?;
+      total = outbox.total_items;
+      let mut page = match walk.start {
+        Start::Newest => client.first_page_of(outbox).await?,
+        Start::Oldest => client.last_page_of(outbox).await?,
+      };
+      let mut found = page_entries(client, &author, &page).await;
+      // A page seen before is as good as the end, as in walking to a date.
+      let mut seen: HashSet<_> = page.id.clone().into_iter().collect();
+
+      for _ in 1..walk.pages {
+        let enough = walk.limit.is_some_and(|limit| found.len() >= limit);
+
+        if enough || client.interrupted() {
+          break;
+        }
+
+        let link = match walk.start {
+          Start::Newest => page.next.take(),
+          Start::Oldest => page.prev.take(),
+        }
+        .filter(|url| seen.insert(url.as_str().to_owned()));
+
+        match link {
+          Some(url) => page = client.page(url.as_str()).await?,
+          None => break,
+        }
+
+        found.extend(page_entries(client, &author, &page).await);
+      }
+
+      found
+    }
+    Source::Feed(url) => feed::entries(author, &client.feed(url).await?)?,
+    Source::Tag(tag) => {
+      api_timeline(client, walk, &tag.instance, |max_id| async move {
+        client.tag_timeline(tag, max_id.as_deref()).await
+      })
+      .await?
+    }
+    Source::List(title) => {
+      let lists = client.lists().await?;
+      let list = mastodon::find_list(&lists, title)
+        .ok_or_else(|| NoSuchList(title.clone()))?;
+      // Having the lists means there's a login to have fetched them with.
+      let instance = client.login().map_or("", |login| &login.instance);
+
+      api_timeline(client, walk, instance, |max_id| async move {
+        client.list_timeline(list, max_id.as_deref()).await
+      })
+      .await?
+    }
+    Source::Search(search) => {
+      // Search results come by relevance rather than by date, so there are
+      // no further pages to walk.
+      let statuses = client.search(&search.query).await?;
+      let instance = client.login().map_or("", |login| &login.instance);
+      let mut found = mastodon::entries(instance, statuses);
+
+      if let Some(since) = search.since {
+        found.retain(|entry| entry.published_at().is_some_and(|at| at > since));
+      }
+
+      found
+    }
+    #[cfg(feature = "bsky")]
+    Source::Bluesky(handle) => {
+      crate::bsky::entries(author, client.author_feed(handle).await?)
+    }
+    #[cfg(feature = "nostr")]
+    Source::Nostr(npub) => {
+      let public_key = crate::nostr::public_key(npub).ok_or(BadHandleError)?;
+
+      crate::nostr::entries(
+        author,
+        &public_key,
+        client.notes(&public_key).await?,
+      )
+    }
+  };
+
+  let most = match (author.mix.max_per_refresh, walk.limit) {
+    (Some(max), Some(limit)) => Some(max.min(limit)),
+    (max, limit) => max.or(limit),
+  };
+
+  if let Some(most) = most {
+    found.truncate(most);
+  }
+
+  Ok(Walked {
+    entries: found,
+    total,
+  })
+}
+
+/// Pages back through a timeline from the Mastodon API as far as a walk
+/// asks, fetching each page with the id of the last post before it.
+async fn api_timeline<F, Fut>(
+  client: &Client,
+  walk: Walk,
+  instance: &str,
+  page: F,
+) -> Result<Vec<Entry>, ApreadErrors>
+where
+  F: Fn(Option<String>) -> Fut,
+  Fut: Future<Output = Result<Vec<mastodon::Status>, ApreadErrors>>,
+{
+  let mut found = vec![];
+  let mut max_id = None;
+
+  for _ in 0..walk.pages {
+    let enough = walk.limit.is_some_and(|limit| found.len() >= limit);
+
+    if enough || client.interrupted() {
+      break;
+    }
+
+    let statuses = page(max_id.take()).await?;
+    let Some(last) = statuses.last() else {
+      break;
+    };
+
+    max_id = Some(last.id.clone());
+    found.extend(mastodon::entries(instance, statuses));
+  }
+
+  Ok(found)
+}
+
+/// How many posts either side of a date to show, unless asked otherwise.
+pub const AROUND_WINDOW: usize = 5;
+
+/// How many pages to walk in from either end of an outbox looking for a
+/// date, at most.
+pub const AROUND_PAGES: usize = 50;
+
+/// Fetches the posts an author wrote around a date: up to `size` from
+/// before it and as many from after.
+///
+/// Outbox pages only link to their neighbours, so there's no jumping into
+/// the middle of one. Instead, the newest and oldest pages give a rough idea
+/// of which end the date is nearer, and pages are walked in from that end
+/// until there are enough posts on the far side of the date. Giving up
+/// after [`AROUND_PAGES`] without getting there, or finding nothing from
+/// before the date at all, is said in a diagnostic.
+pub async fn around(
+  client: &Client,
+  author: &Author,
+  date: DateTime<FixedOffset>,
+  size: usize,
+) -> Result<Vec<Entry>, ApreadErrors> {
+  let mut found = match &author.source {
+    Source::Account(handle) => {
+      walk_to(client, author, handle, date, size).await?
+    }
+    _ => fetch(client, author).await?,
+  };
+
+  merge(&mut found);
+
+  let before = count(&found, |published| published <= date);
+
+  if before == 0 && !found.is_empty() {
+    client.diagnostics().push(Diagnostic::note(
+      Kind::Around,
+      Some(author.source.to_string()),
+      format!(
+        "found nothing {} posted before {}, so the oldest posts found are \
+         shown",
+        author.label(),
+        date.to_rfc3339()
+      ),
+    ));
+  }
+
+  Ok(window(&found, date, size))
+}
+
+async fn walk_to(
+  client: &Client,
+  author: &Author,
+  handle: &Handle,
+  date: DateTime<FixedOffset>,
+  size: usize,
+) -> Result<Vec<Entry>, ApreadErrors> {
+  let actor = client.resolve(handle).await?;
+  let author = bridged(author, &actor);
+  let mut page = client.newest_page(&actor).await?;
+  let mut found = page_entries(client, &author, &page).await;
+  let mut start = Start::Newest;
+
+  if count(&found, |published| published <= date) == 0 {
+    let oldest = match client.oldest_page(&actor).await {
+      Err(ApreadErrors::NoLastPage(_)) => None,
+      oldest => Some(oldest?),
+    };
+
+    if let Some(oldest) = oldest {
+      let oldest_found = page_entries(client, &author, &oldest).await;
+      let newest_date = found.iter().filter_map(Entry::published_at).max();
+      let oldest_date =
+        oldest_found.iter().filter_map(Entry::published_at).min();
+
+      if let (Some(newest_date), Some(oldest_date)) = (newest_date, oldest_date)
+      {
+        if newest_date - date > date - oldest_date {
+          page = oldest;
+          found = oldest_found;
+          start = Start::Oldest;
+        }
+      }
+    }
+  }
+
+  // Some servers link a page back to itself, or round in a loop; a page
+  // seen before is as good as the end.
+  let mut seen: HashSet<_> = page.id.clone().into_iter().collect();
+
+  for pages in 1.. {
+    let far_side = match start {
+      Start::Newest => count(&found, |published| published <= date),
+      Start::Oldest => count(&found, |published| published > date),
+    };
+
+    if far_side >= size || client.interrupted() {
+      break;
+    }
+
+    if pages == AROUND_PAGES {
+      client.diagnostics().push(Diagnostic::warning(
+        Kind::Around,
+        Some(handle.to_string()),
+        format!(
+          "stopped after {} pages of {}'s outbox without reaching {}, so \
+           the posts shown are only the nearest found",
+          AROUND_PAGES,
+          handle,
+          date.to_rfc3339()
+        ),
+      ));
+      break;
+    }
+
+    let link = match start {
+      Start::Newest => page.next.take(),
+      Start::Oldest => page.prev.take(),
+    }
+    .filter(|url| seen.insert(url.as_str().to_owned()));
+
+    match link {
+      Some(url) => page = client.page(url.as_str()).await?,
+      None => break,
+    }
+
+    found.extend(page_entries(client, &author, &page).await);
+  }
+
+  Ok(found)
+}
+
+fn count(
+  entries: &[Entry],
+  matches: impl Fn(DateTime<FixedOffset>) -> bool,
+) -> usize {
+  entries
+    .iter()
+    .filter(|entry| entry.published_at().is_some_and(&matches))
+    .count()
+}
+
+/// The entries either side of a date, up to `size` on each, from entries
+/// sorted newest first.
+pub fn window(
+  entries: &[Entry],
+  date: DateTime<FixedOffset>,
+  size: usize,
+) -> Vec<Entry> {
+  let at = entries
+    .iter()
+    .position(|entry| entry.published_at().is_some_and(|at| at <= date))
+    .unwrap_or(entries.len());
+
+  entries[at.saturating_sub(size)..(at + size).min(entries.len())].to_vec()
+}
+
+/// Reads a date given on the command line, either as a day such as
+/// `2023-06-01` (taken as its start, in UTC) or as a full RFC 3339 time.
+pub fn parse_date(
+  text: &str,
+) -> Result<DateTime<FixedOffset>, chrono::ParseError> {
+  match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+    Ok(day) => Ok(day.and_time(NaiveTime::MIN).and_utc().fixed_offset()),
+    Err(_) => DateTime::parse_from_rfc3339(text),
+  }
+}
+
+/// The author, marked as bridged if their actor turns out to be.
+fn bridged(author: &Author, actor: &Actor) -> Author {
+  Author {
+    bridge: author
+      .bridge
+      .or_else(|| Bridge::from_url(actor.id.as_ref()?.as_str())),
+    ..author.clone()
+  }
+}
+
+/// A single post in a timeline.
+#[derive(Clone, Debug)]
+pub struct Entry {
+  /// Who posted it.
+  pub author: Author,
+  /// The post itself.
+  pub post: Post,
+  /// When it was published.
+  pub published: String,
+  /// Whether the author boosted the post, rather than wrote it.
+  pub boosted: bool,
+}
+
+impl Entry {
+  /// When the post was published, if the server gave a readable date.
+  pub fn published_at(&self) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(&self.published).ok()
+  }
+
+  /// What tells the post from any other: its id, or failing that its url,
+  /// or failing both who posted it when.
+  pub fn key(&self) -> String {
+    match self.post.id.as_ref().or(self.post.url.as_ref()) {
+      Some(id) => id.clone(),
+      None => format!("{} {}", self.author.source, self.published),
+    }
+  }
+}
+
+/// The entries for an author's own posts on an outbox page, leaving out
+/// their replies if the author's mix asks.
+pub fn entries(author: &Author, page: &Page) -> Vec<Entry> {
+  let mut entries = vec![];
+
+  for item in &page.ordered_items {
+    if let Item::Post { object, published } = item {
+      if !author.mix.replies && object.in_reply_to.is_some() {
+        continue;
+      }
+
+      entries.push(Entry {
+        author: author.clone(),
+        post: (**object).clone(),
+        published: published
+          .clone()
+          .or_else(|| object.published.clone())
+          .unwrap_or_default(),
+        boosted: false,
+      });
+    }
+  }
+
+  // Edits come as Update activities of their own, which stand for the post
+  // as it is now.
+  for item in &page.ordered_items {
+    if let Item::Edit { object, .. } = item {
+      let edited = entries
+        .iter_mut()
+        .find(|entry| entry.post.id.is_some() && entry.post.id == object.id);
+
+      if let Some(entry) = edited {
+        entry.post = (**object).clone();
+      }
+    }
+  }
+
+  entries
+}
+
+/// The entries for the posts an author boosted on an outbox page, each
+/// fetched from its own server. Boosts which can't be fetched are skipped.
+pub async fn boosts(
+  client: &Client,
+  author: &Author,
+  page: &Page,
+) -> Vec<Entry> {
+  let mut entries = vec![];
+
+  for item in &page.ordered_items {
+    if let Item::Boost {
+      object: Some(url),
+      published,
+      ..
+    } = item
+    {
+      if client.interrupted() {
+        break;
+      }
+
+      match client.boosted(url).await {
+        Ok(post) => entries.push(Entry {
+          author: author.clone(),
+          published: published
+            .clone()
+            .or_else(|| post.published.clone())
+            .unwrap_or_default(),
+          post,
+          boosted: true,
+        }),
+        Err(error) => client.diagnostics().push(Diagnostic::warning(
+          Kind::Skipped,
+          Some(url.clone()),
+          format!(
+            "skipped a post {} boosted, {}: {}",
+            author.label(),
+            url,
+            error
+          ),
+        )),
+      }
+    }
+  }
+
+  entries
+}
+
+/// Everything on an outbox page the author's mix asks for: their own
+/// posts, and their boosts if they're shown, newest first.
+async fn page_entries(
+  client: &Client,
+  author: &Author,
+  page: &Page,
+) -> Vec<Entry> {
+  let mut found = entries(author, page);
+
+  if author.mix.boosts {
+    found.extend(boosts(client, author, page).await);
+    merge(&mut found);
+  }
+
+  found
+}
+
+/// Interleaves entries from several accounts, newest first.
+pub fn merge(entries: &mut [Entry]) {
+  entries.sort_by_key(|entry| Reverse(entry.published_at()));
+}
+
+/// Interleaves entries from several accounts, oldest first, for reading
+/// in the order they were written.
+pub fn chronological(entries: &mut [Entry]) {
+  entries.sort_by_key(|entry| entry.published_at());
+}
+
+/// Clusters entries by author, keeping each author's entries in order. Authors
+/// come in the order they first appear, so after [`merge`] the most recently
+/// active account comes first.
+pub fn group_by_author(entries: &[Entry]) -> Vec<Vec<&Entry>> {
+  cluster(entries)
+}
+
+fn cluster<'a>(
+  entries: impl IntoIterator<Item = &'a Entry>,
+) -> Vec<Vec<&'a Entry>> {
+  let mut groups: Vec<Vec<&Entry>> = vec![];
+
+  for entry in entries {
+    match groups
+      .iter_mut()
+      .find(|group| group[0].author.source == entry.author.source)
+    {
+      Some(group) => group.push(entry),
+      None => groups.push(vec![entry]),
+    }
+  }
+
+  groups
+}
+
+/// How long each section of a digest covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Period {
+  /// A section per day.
+  Daily,
+  /// A section per week, starting on Monday.
+  Weekly,
+}
+
+impl Period {
+  /// The first day of the period a date falls in.
+  pub fn start(self, date: NaiveDate) -> NaiveDate {
+    match self {
+      Self::Daily => date,
+      Self::Weekly => {
+        let since_monday = date.weekday().num_days_from_monday();
+        date - chrono::Duration::days(since_monday.into())
+      }
+    }
+  }
+}
+
+/// The entries posted in one period, by author.
+#[derive(Clone, Debug)]
+pub struct Section<'a> {
+  /// The first day of the period, or none for entries without a date.
+  pub start: Option<NaiveDate>,
+  /// Each author's entries, as [`group_by_author`] clusters them.
+  pub authors: Vec<Vec<&'a Entry>>,
+}
+
+/// Buckets entries into days or weeks, as they were dated where they were
+/// posted. Sections and the entries within them keep the entries' order,
+/// and entries without a date come in a section of their own, at the end.
+pub fn digest(entries: &[Entry], period: Period) -> Vec<Section<'_>> {
+  let mut buckets: Vec<(Option<NaiveDate>, Vec<&Entry>)> = vec![];
+
+  for entry in entries {
+    let start = entry
+      .published_at()
+      .map(|date| period.start(date.date_naive()));
+
+    match buckets.iter_mut().find(|(found, _)| *found == start) {
+      Some((_, bucket)) => bucket.push(entry),
+      None => buckets.push((start, vec![entry])),
+    }
+  }
+
+  buckets.sort_by_key(|(start, _)| start.is_none());
+
+  buckets
+    .into_iter()
+    .map(|(start, bucket)| Section {
+      start,
+      authors: cluster(bucket),
+    })
+    .collect()
+}
@@ -0,0 +1,128 @@
+//! Tagging posts with the keywords they're about, for `apread read
+//! --topic`.
+//!
+//! Everything happens locally: a post's keywords are its hashtags, then the
+//! words it uses most once common English words, links and mentions are
+//! left out. Plurals are folded into their singulars, so `--topic
+//! release` finds posts about releases. Posts are tagged as they're
+//! archived, and posts which aren't archived are tagged as they're read.
+//! Only builds with the `topics` feature tag posts.
+
+use crate::{activity::Post, archive::Archive, render};
+
+/// How many keywords a post is tagged with, at most.
+pub const KEYWORDS: usize = 8;
+
+/// Words too common to say what a post is about.
+#[rustfmt::skip]
+const STOPWORDS: &[&str] = &[
+  "about", "above", "after", "again", "against", "all", "also", "and", "any",
+  "are", "aren't", "because", "been", "before", "being", "below", "between",
+  "both", "but", "can", "can't", "could", "couldn't", "did", "didn't", "does",
+  "doesn't", "doing", "don't", "down", "during", "each", "even", "few", "for",
+  "from", "further", "get", "got", "had", "hadn't", "has", "hasn't", "have",
+  "haven't", "having", "her", "here", "hers", "herself", "him", "himself",
+  "his", "how", "i'd", "i'll", "i'm", "i've", "into", "isn't", "it's", "its",
+  "itself", "just", "let's", "like", "more", "most", "much", "must", "myself",
+  "nor", "not", "now", "off", "once", "one", "only", "other", "our", "ours",
+  "ourselves", "out", "over", "own", "really", "same", "she", "should",
+  "shouldn't", "some", "still", "such", "than", "that", "that's", "the",
+  "their", "theirs", "them", "themselves", "then", "there", "there's", "these",
+  "they", "they're", "thing", "things", "this", "those", "through", "too",
+  "under", "until", "very", "was", "wasn't", "way", "we'd", "we'll", "we're",
+  "we've", "well", "were", "weren't", "what", "when", "where", "which", "while",
+  "who", "whom", "why", "will", "with", "won't", "would", "wouldn't", "yes",
+  "yet", "you", "you'd", "you'll", "you're", "you've", "your", "yours",
+  "yourself", "yourselves",
+];
+
+/// A keyword as it's compared: lowercase, without a `#`, and singular.
+pub fn normal(word: &str) -> String {
+  let word = word.trim_start_matches('#').to_lowercase();
+
+  match word.strip_suffix('s') {
+    Some(singular)
+      if singular.chars().count() > 2
+        && !singular.ends_with('s')
+        && !singular.ends_with('u')
+        && !singular.ends_with('i') =>
+    {
+      singular.to_owned()
+    }
+    _ => word,
+  }
+}
+
+/// The keywords for a post's html and hashtags: the hashtags first, then
+/// the words used most, each only once.
+pub fn keywords(html: &str, hashtags: &[&str]) -> Vec<String> {
+  let mut keywords: Vec<String> = vec![];
+  let mut counted: Vec<(String, usize)> = vec![];
+
+  for tag in hashtags {
+    let tag = normal(tag);
+
+    if !tag.is_empty() && !keywords.contains(&tag) {
+      keywords.push(tag);
+    }
+  }
+
+  // Curly apostrophes would split words like "don't" in two.
+  let text = render::plain_text(html).replace('\u{2019}', "'");
+  let words = text
+    .split_whitespace()
+    .filter(|word| {
+      !word.contains("://") && !word.starts_with('@') && !word.starts_with('#')
+    })
+    .flat_map(|word| word.split(|c: char| !c.is_alphanumeric() && c != '\''));
+
+  for word in words {
+    let word = word.trim_matches('\'').to_lowercase();
+    let word = word.strip_suffix("'s").unwrap_or(&word);
+
+    if word.chars().count() < 3
+      || word.chars().all(|c| c.is_numeric())
+      || STOPWORDS.contains(&word)
+    {
+      continue;
+    }
+
+    let word = normal(word);
+
+    match counted.iter_mut().find(|(seen, _)| *seen == word) {
+      Some((_, count)) => *count += 1,
+      None => counted.push((word, 1)),
+    }
+  }
+
+  // The sort is stable, so words used as often keep the order they came in.
+  counted.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+  for (word, _) in counted {
+    if keywords.len() >= KEYWORDS {
+      break;
+    }
+
+    if !keywords.contains(&word) {
+      keywords.push(word);
+    }
+  }
+
+  keywords.truncate(KEYWORDS);
+  keywords
+}
+
+/// A post's keywords: as it was tagged when archived, if it was, or found
+/// afresh.
+pub fn of(archive: Option<&Archive>, post: &Post) -> Vec<String> {
+  let archived = archive
+    .zip(post.id.as_deref())
+    .and_then(|(archive, id)| archive.keywords(id).ok().flatten());
+
+  archived.unwrap_or_else(|| keywords(&post.content, &post.hashtags()))
+}
+
+/// Whether a post with these keywords is about a topic.
+pub fn matches(keywords: &[String], topic: &str) -> bool {
+  keywords.contains(&normal(topic))
+}
@@ -0,0 +1,125 @@
+//! Named views: saved ways of reading a timeline, picked by name.
+//!
+//! Each view, defined in the config file, says which subscriptions to read
+//! and which of their posts to keep, along with how to show them. Every
+//! part is optional; a view with nothing set reads every subscription as
+//! `apread` would with no handle. Accounts in a view needn't be subscribed
+//! to: a handle which isn't a subscription is read as it is.
+//!
+//! ```toml
+//! [views.work]
+//! accounts = ["alice@example.social", "ops"]
+//! tags = ["rust", "incident"]
+//!
+//! [views.news]
+//! accounts = ["bbc", "ap", "guardian"]
+//! from = "oldest"
+//! group_by_author = true
+//! format = "json"
+//! ```
+//!
+//! Flags given alongside a view win over what it says.
+
+use serde::Deserialize;
+
+use crate::{
+  activity::Tag,
+  settings::Format,
+  subscriptions::Subscriptions,
+  timeline::{Author, Entry, Source, Start},
+  ApreadErrors,
+};
+
+/// A saved way of reading a timeline.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct View {
+  /// The accounts to read, by a subscription's nickname or handle or by
+  /// any other handle, or every subscription if there are none.
+  pub accounts: Vec<String>,
+  /// Hashtags, with or without their `#`, of which posts need at least
+  /// one to be shown. With none, every post is.
+  pub tags: Vec<String>,
+  /// Which end of each account's posts to read from.
+  pub from: Option<Start>,
+  /// Whether to cluster posts by account.
+  pub group_by_author: bool,
+  /// How to write the timeline out.
+  pub format: Option<Format>,
+}
+
+/// The authors a view reads.
+#[derive(Debug, Default)]
+pub struct Accounts {
+  /// The authors, each once, in the order the view names them.
+  pub authors: Vec<Author>,
+  /// The names which are neither a subscription nor a handle, and so were
+  /// left out.
+  pub unknown: Vec<String>,
+}
+
+impl View {
+  /// The authors the view reads: every subscription if it names none, and
+  /// otherwise the subscriptions it names, by nickname or handle, and the
+  /// handles it names which aren't subscribed to.
+  pub fn accounts(
+    &self,
+    subscriptions: &Subscriptions,
+  ) -> Result<Accounts, ApreadErrors> {
+    let mut accounts = Accounts::default();
+
+    if self.accounts.is_empty() {
+      for subscription in subscriptions.only(&[]) {
+        accounts
+          .authors
+          .push(Author::from_subscription(subscription)?);
+      }
+
+      return Ok(accounts);
+    }
+
+    for name in &self.accounts {
+      let subscribed = subscriptions.only(std::slice::from_ref(name));
+      let found = match (subscribed.is_empty(), Source::parse(name)) {
+        (false, _) => subscribed
+          .into_iter()
+          .map(Author::from_subscription)
+          .collect::<Result<_, _>>()?,
+        (true, Ok(source)) => vec![Author::from_source(source)],
+        (true, Err(_)) => {
+          accounts.unknown.push(name.clone());
+          continue;
+        }
+      };
+
+      for author in found {
+        if !accounts
+          .authors
+          .iter()
+          .any(|known| known.source == author.source)
+        {
+          accounts.authors.push(author);
+        }
+      }
+    }
+
+    Ok(accounts)
+  }
+
+  /// Whether the view shows an entry: whether it carries one of the view's
+  /// tags, ignoring case, if the view has any.
+  pub fn shows(&self, entry: &Entry) -> bool {
+    if self.tags.is_empty() {
+      return true;
+    }
+
+    entry.post.tag.iter().any(|tag| match tag {
+      Tag::Hashtag { name, .. } => self.tags.iter().any(|wanted| {
+        wanted
+          .trim_start_matches('#')
+          .eq_ignore_ascii_case(name.trim_start_matches('#'))
+      }),
+      _ => false,
+    })
+  }
+}
@@ -0,0 +1,276 @@
+//! Hiding, tagging or rewriting posts with WebAssembly modules, in builds
+//! with the `wasm` feature, for moderation and curation apread doesn't do
+//! itself.
+//!
+//! `apread read` hands each post it reads to each module in the
+//! `wasm_filters` list in turn, before views and the rest of its filters
+//! see it:
+//!
+//! ```toml
+//! wasm_filters = ["/home/me/.config/apread/no-spoilers.wasm"]
+//! ```
+//!
+//! A module, binary or in the text format, exports its `memory` and two
+//! functions:
+//!
+//! ```wat
+//! (func (export "alloc") (param $len i32) (result i32) ...)
+//! (func (export "filter") (param $ptr i32) (param $len i32) (result i64) ...)
+//! ```
+//!
+//! apread asks `alloc` for somewhere to put a post, writes the post there
+//! as a line of JSON, in the same form as `--format json`, and calls
+//! `filter` with where it is. `filter` answers with where its verdict is,
+//! the pointer in the high 32 bits and the length in the low, or `0` to
+//! leave the post as it is. The verdict is a JSON object, any of whose
+//! fields can be left out:
+//!
+//! ```json
+//! { "hide": true, "tags": ["spoilers"], "content": "<p>...</p>" }
+//! ```
+//!
+//! `hide` leaves the post out, `tags` adds hashtags to it, which views can
+//! pick posts by, and `content` and `summary` replace its own. An instance
+//! of each module lasts the run, so a module can remember the posts it's
+//! seen.
+//!
+//! Modules import nothing, so they can't reach files or the network, and
+//! each has [`FUEL`] to spend on a post and [`MEMORY`] bytes to spend it
+//! in. A module which can't be loaded stops the run; one which fails on a
+//! post leaves the post as it was, with a warning.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+use wasmtime::{
+  Config, Engine, Instance, Memory, Module, Store, StoreLimits,
+  StoreLimitsBuilder, TypedFunc,
+};
+
+use crate::{
+  activity::Tag,
+  diagnostics::{Diagnostic, Diagnostics, Kind},
+  i18n::{self, FluentValue, Localized},
+  render,
+  timeline::Entry,
+};
+
+/// How much fuel, in roughly one unit an instruction, a module can spend
+/// on a post before it's stopped.
+pub const FUEL: u64 = 100_000_000;
+
+/// How many bytes of memory a module can grow to.
+pub const MEMORY: usize = 64 << 20;
+
+/// Raised when a filter module can't be read, compiled or instantiated, or
+/// doesn't export what a filter needs to.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct BadModule {
+  /// Where the module was looked for.
+  pub path: PathBuf,
+  /// What's wrong with it.
+  pub reason: String,
+}
+
+impl Localized for BadModule {
+  fn id(&self) -> &'static str {
+    "error-bad-module"
+  }
+
+  fn args(&self) -> Vec<(&'static str, FluentValue<'_>)> {
+    vec![
+      ("path", self.path.display().to_string().into()),
+      ("reason", self.reason.as_str().into()),
+    ]
+  }
+}
+
+/// What a module makes of a post.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Verdict {
+  /// Whether to leave the post out.
+  pub hide: bool,
+  /// Hashtags to add to the post, with or without their `#`.
+  pub tags: Vec<String>,
+  /// What to replace the post's content with, as HTML.
+  pub content: Option<String>,
+  /// What to replace the post's content warning with.
+  pub summary: Option<String>,
+}
+
+impl Verdict {
+  /// Applies the verdict to an entry, adding the tags it hasn't got and
+  /// replacing what's rewritten. Whether the entry is kept.
+  pub fn apply(self, entry: &mut Entry) -> bool {
+    if self.hide {
+      return false;
+    }
+
+    for tag in self.tags {
+      let name = tag.trim_start_matches('#');
+
+      if !name.is_empty() && !entry.post.hashtags().contains(&name) {
+        entry.post.tag.push(Tag::Hashtag {
+          name: format!("#{}", name),
+          href: None,
+        });
+      }
+    }
+
+    if let Some(content) = self.content {
+      entry.post.content = content;
+    }
+
+    if let Some(summary) = self.summary {
+      entry.post.summary = Some(summary);
+    }
+
+    true
+  }
+}
+
+/// A filter module, instantiated to be handed posts.
+pub struct Filter {
+  path: PathBuf,
+  store: Store<StoreLimits>,
+  memory: Memory,
+  alloc: TypedFunc<i32, i32>,
+  filter: TypedFunc<(i32, i32), i64>,
+}
+
+impl Filter {
+  /// Loads the module at a path with an engine which consumes fuel.
+  pub fn load(engine: &Engine, path: &Path) -> Result<Self, BadModule> {
+    let bad = |reason: String| BadModule {
+      path: path.to_owned(),
+      reason,
+    };
+    let module = Module::from_file(engine, path)
+      .map_err(|error| bad(error.to_string()))?;
+    let limits = StoreLimitsBuilder::new().memory_size(MEMORY).build();
+    let mut store = Store::new(engine, limits);
+
+    store.limiter(|limits| limits);
+    // Starting the module can spend fuel too.
+    store
+      .set_fuel(FUEL)
+      .map_err(|error| bad(error.to_string()))?;
+
+    let instance = Instance::new(&mut store, &module, &[])
+      .map_err(|error| bad(error.to_string()))?;
+    let memory = instance
+      .get_memory(&mut store, "memory")
+      .ok_or_else(|| bad("it doesn't export its memory".to_owned()))?;
+    let alloc = instance
+      .get_typed_func(&mut store, "alloc")
+      .map_err(|error| bad(error.to_string()))?;
+    let filter = instance
+      .get_typed_func(&mut store, "filter")
+      .map_err(|error| bad(error.to_string()))?;
+
+    Ok(Self {
+      path: path.to_owned(),
+      store,
+      memory,
+      alloc,
+      filter,
+    })
+  }
+
+  /// Where the module was loaded from.
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// What the module makes of an entry.
+  pub fn verdict(&mut self, entry: &Entry) -> Result<Verdict, wasmtime::Error> {
+    let mut post = vec![];
+    // Writing to memory can't fail.
+    let _ = render::json_entry(&mut post, entry);
+
+    self.store.set_fuel(FUEL)?;
+
+    let len = i32::try_from(post.len())?;
+    let ptr = self.alloc.call(&mut self.store, len)?;
+
+    self
+      .memory
+      .write(&mut self.store, ptr as u32 as usize, &post)?;
+
+    let answer = self.filter.call(&mut self.store, (ptr, len))? as u64;
+
+    if answer == 0 {
+      return Ok(Verdict::default());
+    }
+
+    let mut verdict = vec![0; (answer & 0xffff_ffff) as usize];
+
+    self
+      .memory
+      .read(&self.store, (answer >> 32) as usize, &mut verdict)?;
+
+    Ok(serde_json::from_slice(&verdict)?)
+  }
+}
+
+/// The filter modules a run hands its posts to, in order.
+#[derive(Default)]
+pub struct Filters(Vec<Filter>);
+
+impl Filters {
+  /// Loads the modules at each of `paths`, sharing one engine.
+  pub fn load(paths: &[PathBuf]) -> Result<Self, BadModule> {
+    if paths.is_empty() {
+      return Ok(Self::default());
+    }
+
+    let mut config = Config::new();
+
+    // A backtrace through a module's own functions means nothing to
+    // whoever's reading.
+    config.consume_fuel(true).wasm_backtrace_max_frames(None);
+
+    let engine = Engine::new(&config).map_err(|error| BadModule {
+      path: paths[0].clone(),
+      reason: error.to_string(),
+    })?;
+    let filters = paths
+      .iter()
+      .map(|path| Filter::load(&engine, path))
+      .collect::<Result<_, _>>()?;
+
+    Ok(Self(filters))
+  }
+
+  /// Whether there are no modules to filter with.
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Hands each entry to each module in turn, leaving out the entries one
+  /// hides and keeping the rest in order. A module which fails on an entry
+  /// leaves it as it was, and says so to `diagnostics`.
+  pub fn apply(&mut self, entries: &mut Vec<Entry>, diagnostics: &Diagnostics) {
+    if self.is_empty() {
+      return;
+    }
+
+    entries.retain_mut(|entry| {
+      self.0.iter_mut().all(|filter| match filter.verdict(entry) {
+        Ok(verdict) => verdict.apply(entry),
+        Err(error) => {
+          diagnostics.push(Diagnostic::warning(
+            Kind::Wasm,
+            Some(filter.path().display().to_string()),
+            format!("the filter failed on {}: {}", entry.key(), error),
+          ));
+
+          true
+        }
+      })
+    });
+  }
+}
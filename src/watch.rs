@@ -0,0 +1,64 @@
+//! Watching timelines for new posts, as `apread watch` does.
+//!
+//! Each poll fetches the newest posts of every author being watched and
+//! keeps only the ones no earlier poll has seen, so whatever acts on new
+//! posts (printing them, or running hooks on them) sees each post once.
+//! Every author is polled at once, but the client's
+//! [`Throttle`](crate::throttle::Throttle) keeps any one instance to a few
+//! requests at a time, however many of the authors it hosts.
+
+use std::{collections::HashSet, time::Duration};
+
+use crate::{
+  timeline::{self, Author, Entry, Reading, Walk},
+  ApreadErrors, Client,
+};
+
+/// How often to poll unless told otherwise.
+pub const DEFAULT_EVERY: Duration = Duration::from_secs(300);
+
+/// What one poll found.
+#[derive(Debug, Default)]
+pub struct Poll {
+  /// The posts no earlier poll saw, newest first.
+  pub new: Vec<Entry>,
+  /// The authors whose posts couldn't be fetched, and why. Watching goes
+  /// on without them until they can be.
+  pub failed: Vec<(Author, ApreadErrors)>,
+}
+
+/// Timelines being watched, and the posts already seen on them.
+#[derive(Debug)]
+pub struct Watch {
+  authors: Vec<Author>,
+  seen: HashSet<String>,
+}
+
+impl Watch {
+  /// Starts watching authors, having seen nothing yet.
+  pub fn new(authors: Vec<Author>) -> Self {
+    Self {
+      authors,
+      seen: HashSet::new(),
+    }
+  }
+
+  /// Fetches every author's newest posts, all at once, keeping the ones
+  /// not seen before. The first poll finds everything.
+  pub async fn poll(&mut self, client: &Client) -> Poll {
+    let reading = Reading::Walk(Walk::default());
+    let gathered = timeline::gather(client, &self.authors, reading).await;
+    let mut poll = Poll {
+      new: gathered
+        .entries
+        .into_iter()
+        .filter(|entry| self.seen.insert(entry.key()))
+        .collect(),
+      failed: gathered.failed,
+    };
+
+    timeline::merge(&mut poll.new);
+
+    poll
+  }
+}
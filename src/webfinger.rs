@@ -0,0 +1,99 @@
+//! WebFinger documents, used to discover an account's actor.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+  i18n::{self, Localized},
+  ApUrl, ApreadErrors,
+};
+
+/// Raised when a WebFinger document has no link to the actor's feed.
+#[derive(Debug, Error)]
+#[error("{}", i18n::english(self))]
+pub struct NoFeedLink;
+
+impl Localized for NoFeedLink {
+  fn id(&self) -> &'static str {
+    "error-no-feed-link"
+  }
+}
+
+/// The WebFinger response for a handle.
+#[derive(Debug, Deserialize)]
+pub struct Webfinger {
+  //   aliases: Vec<String>,
+  /// Links describing the account, one of which points at the actor.
+  pub links: Vec<Link>,
+  //   subject: String,
+}
+
+impl Webfinger {
+  /// Finds the url of the actor document among the links.
+  pub fn to_actor_url(&self) -> Result<ApUrl, ApreadErrors> {
+    let mut feed = Err(ApreadErrors::NoFeedLink(NoFeedLink));
+
+    for link in &self.links {
+      if let Link::Feed { href, .. } = link {
+        feed = Ok(href.clone());
+      }
+    }
+
+    feed
+  }
+}
+
+/// A single link in a WebFinger document, told apart by its `rel`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "rel")]
+pub enum Link {
+  /// This represents a link to the profile page for a user.
+  ///
+  /// This is left deliberately blank.
+  /// The actual structure here is closer to this:
+  ///
+  /// ```text
+  ///   Profile {
+  ///     href: String,
+  ///     #[serde(rename = "type")]
+  ///     kind: String,
+  ///   }
+  /// ```
+  #[serde(rename = "http://webfinger.net/rel/profile-page")]
+  Profile,
+  /// As with profile, this represents a link, this time to the
+  /// actor's feed.
+  ///
+  /// We omit some fields.
+  /// The actual structure here is closer to this:
+  ///
+  /// ```text
+  ///   Feed {
+  ///     href: String,
+  ///     #[serde(rename = "type")]
+  ///     kind: String,
+  ///   }
+  /// ```
+  #[serde(rename = "self")]
+  Feed {
+    /// The actor document's url.
+    href: ApUrl,
+  },
+  /// This represents a subscription template for the domain hosting
+  /// our actor.
+  ///
+  /// The actual structure here is closer to this:
+  ///
+  /// ```text
+  ///   Subscribe {
+  ///     template: String,
+  ///   }
+  /// ```
+  #[serde(rename = "http://ostatus.org/schema/1.0/subscribe")]
+  Subscribe,
+  /// Any other kind of link, such as avatars or Salmon endpoints.
+  ///
+  /// Servers add these freely, so we accept and ignore them.
+  #[serde(other)]
+  Other,
+}
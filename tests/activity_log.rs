@@ -0,0 +1,76 @@
+use apread::{activity_log::ActivityLog, Client};
+use serde_json::{json, Value};
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+fn lines(path: &std::path::Path) -> Vec<Value> {
+  std::fs::read_to_string(path)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str(line).unwrap())
+    .collect()
+}
+
+#[tokio::test]
+async fn activities_are_appended_as_they_came() {
+  let server = MockServer::start().await;
+  let page = format!("{}/outbox?page=1", server.uri());
+  let create = json!({
+    "type": "Create",
+    "id": "https://example.social/activities/1",
+    "object": { "type": "Note", "content": "hi", "x-extension": true },
+  });
+
+  Mock::given(matchers::path("/outbox"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "type": "OrderedCollectionPage",
+      "orderedItems": [create],
+    })))
+    .mount(&server)
+    .await;
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("activities.jsonl");
+  let client =
+    Client::insecure().logging_activities(ActivityLog::open(&path).unwrap());
+
+  client.page(&page).await.unwrap();
+  client.page(&page).await.unwrap();
+
+  let logged = lines(&path);
+
+  assert_eq!(logged.len(), 2);
+  assert_eq!(logged[0]["source"], page);
+  assert_eq!(logged[0]["activity"], create);
+  assert!(logged[0]["fetched_at"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn inline_first_pages_are_logged_and_actors_are_not() {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/outbox"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "type": "OrderedCollection",
+      "first": {
+        "type": "OrderedCollectionPage",
+        "orderedItems": [{ "type": "Announce" }],
+      },
+    })))
+    .mount(&server)
+    .await;
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("nested").join("activities.jsonl");
+  let client =
+    Client::insecure().logging_activities(ActivityLog::open(&path).unwrap());
+
+  client
+    .outbox(&format!("{}/outbox", server.uri()))
+    .await
+    .unwrap();
+
+  let logged = lines(&path);
+
+  assert_eq!(logged.len(), 1);
+  assert_eq!(logged[0]["activity"]["type"], "Announce");
+}
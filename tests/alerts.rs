@@ -0,0 +1,75 @@
+use apread::{
+  alerts::{PostingAlert, Rates, DEFAULT_WITHIN},
+  timeline::{Author, Entry},
+  Handle,
+};
+use chrono::{DateTime, Utc};
+
+fn entry(handle: &str, published: &str) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string(handle).unwrap()),
+    post: serde_json::from_value(serde_json::json!({ "content": "down" }))
+      .unwrap(),
+    published: published.to_owned(),
+    boosted: false,
+  }
+}
+
+fn at(date: &str) -> DateTime<Utc> {
+  DateTime::parse_from_rfc3339(date).unwrap().into()
+}
+
+fn alert(posts: usize) -> PostingAlert {
+  serde_json::from_value(serde_json::json!({ "posts": posts })).unwrap()
+}
+
+#[test]
+fn alerts_count_posts_over_an_hour_by_default() {
+  assert_eq!(alert(3).within, DEFAULT_WITHIN);
+}
+
+#[test]
+fn accounts_posting_past_the_limit_alert_once() {
+  let mut rates = Rates::new(&alert(2));
+  let burst = [
+    entry("status@example.social", "2023-02-14T18:00:00Z"),
+    entry("status@example.social", "2023-02-14T18:10:00Z"),
+    entry("alice@example.social", "2023-02-14T18:15:00Z"),
+  ];
+
+  assert!(rates.observe(&burst, at("2023-02-14T18:20:00Z")).is_empty());
+
+  let more = [entry("status@example.social", "2023-02-14T18:20:00Z")];
+  let spikes = rates.observe(&more, at("2023-02-14T18:21:00Z"));
+
+  assert_eq!(spikes.len(), 1);
+  assert_eq!(spikes[0].account, "status@example.social");
+  assert_eq!(spikes[0].posts, 3);
+  assert_eq!(
+    spikes[0].message,
+    "status@example.social posted 3 times in the last 60 minutes"
+  );
+
+  let still = [entry("status@example.social", "2023-02-14T18:25:00Z")];
+
+  assert!(rates.observe(&still, at("2023-02-14T18:26:00Z")).is_empty());
+}
+
+#[test]
+fn accounts_alert_again_once_they_have_calmed_down() {
+  let mut rates = Rates::new(&alert(1));
+  let first = [
+    entry("status@example.social", "2023-02-14T18:00:00Z"),
+    entry("status@example.social", "2023-02-14T18:01:00Z"),
+  ];
+
+  assert_eq!(rates.observe(&first, at("2023-02-14T18:02:00Z")).len(), 1);
+  assert!(rates.observe(&[], at("2023-02-14T20:00:00Z")).is_empty());
+
+  let second = [
+    entry("status@example.social", "2023-02-14T20:01:00Z"),
+    entry("status@example.social", "2023-02-14T20:02:00Z"),
+  ];
+
+  assert_eq!(rates.observe(&second, at("2023-02-14T20:03:00Z")).len(), 1);
+}
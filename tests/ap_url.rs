@@ -0,0 +1,52 @@
+use apread::{activity::OutboxIndex, ApUrl, ApreadErrors, BadUrl, Client};
+
+#[test]
+fn only_absolute_web_urls_are_read() {
+  assert!(ApUrl::parse("https://social.example/users/alice").is_ok());
+  assert!(ApUrl::parse("http://127.0.0.1:8080/users/alice").is_ok());
+
+  for bad in [
+    "/users/alice/outbox",
+    "",
+    "mailto:alice@example",
+    "https://",
+  ] {
+    assert!(ApUrl::parse(bad).is_err(), "{:?} should be refused", bad);
+  }
+}
+
+#[test]
+fn malformed_links_fail_as_the_document_is_read() {
+  let outbox = r#"{ "first": "?page=true", "totalItems": 3 }"#;
+
+  assert!(serde_json::from_str::<OutboxIndex>(outbox).is_err());
+}
+
+#[test]
+fn pages_on_other_servers_are_refused() {
+  let url = ApUrl::parse("https://social.example/users/alice/outbox").unwrap();
+  let outbox: OutboxIndex = serde_json::from_str(
+    r#"{
+      "first": "https://social.example/users/alice/outbox?page=true",
+      "last": "https://elsewhere.example/outbox?min_id=0"
+    }"#,
+  )
+  .unwrap();
+
+  assert_eq!(
+    outbox.check_origin(&url),
+    Err(BadUrl {
+      url: "https://elsewhere.example/outbox?min_id=0".into(),
+      reason: "on a different server from what links to it",
+    })
+  );
+}
+
+#[tokio::test]
+async fn secure_clients_refuse_plain_http() {
+  let result = Client::new()
+    .actor("http://social.example/users/alice")
+    .await;
+
+  assert!(matches!(result, Err(ApreadErrors::BadUrl(_))));
+}
@@ -0,0 +1,431 @@
+mod common;
+
+use apread::{
+  activity::Post,
+  archive::{self, Archive, QueuedItem, Revision},
+  article::Article,
+  compose::Visibility,
+  render,
+  timeline::{Author, Walk},
+  ApreadErrors, Client,
+};
+use common::FakeInstance;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn bookmarks_keep_the_full_post_with_tags_and_note() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+
+  let url =
+    format!("{}/users/alice/statuses/109000000000000001", instance.uri());
+  let post = client.post(&url).await.unwrap();
+  let author = client
+    .actor(post.attributed_to.as_deref().unwrap())
+    .await
+    .unwrap()
+    .handle();
+
+  let mut archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  archive
+    .bookmark(
+      &post,
+      author.as_deref(),
+      &["#compilers".to_owned(), "reading".to_owned()],
+      Some("come back to this"),
+    )
+    .unwrap();
+
+  let bookmarks = archive.bookmarks(None).unwrap();
+
+  assert_eq!(bookmarks.len(), 1);
+  assert_eq!(bookmarks[0].post.id, url);
+  assert_eq!(bookmarks[0].post.author.as_deref(), Some("alice@127.0.0.1"));
+  assert!(bookmarks[0].post.content.contains("register allocation"));
+  assert_eq!(bookmarks[0].tags, ["compilers", "reading"]);
+  assert_eq!(bookmarks[0].note.as_deref(), Some("come back to this"));
+
+  assert_eq!(archive.bookmarks(Some("#reading")).unwrap().len(), 1);
+  assert!(archive.bookmarks(Some("gardening")).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn archives_survive_being_reopened() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("archive.sqlite3");
+  let instance = FakeInstance::start("mastodon").await;
+
+  let url =
+    format!("{}/users/alice/statuses/109000000000000001", instance.uri());
+  let post = Client::insecure().post(&url).await.unwrap();
+
+  Archive::open(&path)
+    .unwrap()
+    .bookmark(&post, None, &[], None)
+    .unwrap();
+
+  assert_eq!(
+    Archive::open(&path).unwrap().bookmarks(None).unwrap().len(),
+    1
+  );
+}
+
+#[tokio::test]
+async fn boosted_posts_are_only_fetched_once() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("archive.sqlite3");
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::method("GET"))
+    .and(matchers::path("/notes/viral"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(
+      r#"{"id": "https://elsewhere.example/notes/viral", "content": "<p>wow</p>"}"#,
+    ))
+    .expect(1)
+    .mount(&server)
+    .await;
+
+  let url = format!("{}/notes/viral", server.uri());
+  let client =
+    Client::insecure().caching_objects(Archive::open(&path).unwrap());
+
+  for _ in 0..3 {
+    assert_eq!(client.boosted(&url).await.unwrap().content, "<p>wow</p>");
+  }
+
+  assert!(Archive::open(&path)
+    .unwrap()
+    .object(&url)
+    .unwrap()
+    .is_some());
+}
+
+#[test]
+fn the_queue_is_first_in_first_out() {
+  let dir = tempfile::tempdir().unwrap();
+  let mut archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  for url in ["https://one.example", "https://two.example"] {
+    archive
+      .enqueue(&QueuedItem {
+        url: url.to_owned(),
+        title: None,
+        author: None,
+        content: "<p>hello</p>".to_owned(),
+      })
+      .unwrap();
+  }
+
+  assert_eq!(archive.queue().unwrap().len(), 2);
+  assert_eq!(archive.pop().unwrap().unwrap().url, "https://one.example");
+  assert_eq!(archive.pop().unwrap().unwrap().url, "https://two.example");
+  assert!(archive.pop().unwrap().is_none());
+}
+
+#[test]
+fn drafts_are_kept_until_thrown_away() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  let first = archive
+    .save_draft("hello", Some("greetings"), Visibility::Unlisted)
+    .unwrap();
+  let second = archive.save_draft("hi", None, Visibility::Public).unwrap();
+  archive.update_draft(second, "hi again").unwrap();
+
+  let drafts = archive.drafts().unwrap();
+  assert_eq!(drafts.len(), 2);
+  assert_eq!(drafts[0].spoiler_text.as_deref(), Some("greetings"));
+  assert_eq!(drafts[0].visibility, Visibility::Unlisted);
+  assert_eq!(archive.draft(second).unwrap().text, "hi again");
+
+  archive.delete_draft(first).unwrap();
+
+  assert_eq!(archive.drafts().unwrap().len(), 1);
+  assert!(matches!(
+    archive.draft(first),
+    Err(ApreadErrors::NoSuchDraft(_))
+  ));
+  assert!(archive.update_draft(first, "gone").is_err());
+}
+
+#[tokio::test]
+async fn articles_export_as_plain_text() {
+  let instance = FakeInstance::start("mastodon").await;
+  let url = format!("{}/notes/regalloc", instance.uri());
+  let page = Client::insecure().web_page(&url).await.unwrap();
+  let article = Article::extract(&page);
+
+  assert_eq!(article.title.as_deref(), Some("Register allocation notes"));
+  insta::assert_snapshot!(render::plain_text(&article.content));
+}
+
+fn post(id: &str, published: &str) -> Post {
+  serde_json::from_value(serde_json::json!({
+    "id": id,
+    "published": published,
+    "content": "<p>Something to think better of</p>",
+  }))
+  .unwrap()
+}
+
+#[tokio::test]
+async fn syncing_marks_posts_gone_from_the_outbox_deleted() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let elsewhere = MockServer::start().await;
+  let handle = instance.handle("alice");
+  let name = handle.to_string();
+
+  Mock::given(matchers::path("/statuses/gone"))
+    .respond_with(ResponseTemplate::new(410))
+    .mount(&elsewhere)
+    .await;
+
+  Mock::given(matchers::path("/statuses/old"))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&elsewhere)
+    .await;
+
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  // Between the outbox's two posts, so it should have been there.
+  let missing = format!("{}/users/alice/statuses/2000", instance.uri());
+  let gone = format!("{}/statuses/gone", elsewhere.uri());
+  let old = format!("{}/statuses/old", elsewhere.uri());
+
+  for (id, published) in [
+    (&missing, "2023-02-13T12:00:00Z"),
+    (&gone, "2022-12-01T08:00:00Z"),
+    (&old, "2022-11-01T08:00:00Z"),
+  ] {
+    archive.store(&post(id, published), Some(&name)).unwrap();
+  }
+
+  let synced = archive::sync(
+    &Client::insecure(),
+    &archive,
+    &Author::new(handle),
+    Walk::default(),
+    archive::CHECKS,
+  )
+  .await
+  .unwrap();
+
+  assert_eq!(synced.archived, 2);
+  assert_eq!(synced.deleted, [missing.clone(), gone.clone()]);
+
+  let mut deleted: Vec<_> = archive
+    .deleted()
+    .unwrap()
+    .into_iter()
+    .map(|found| found.post.id)
+    .collect();
+  let mut expected = vec![gone, missing.clone()];
+  deleted.sort();
+  expected.sort();
+  assert_eq!(deleted, expected);
+
+  // A deleted post turning up again is no longer deleted.
+  archive
+    .store(&post(&missing, "2023-02-13T12:00:00Z"), Some(&name))
+    .unwrap();
+  assert_eq!(archive.deleted().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn syncing_checks_a_few_older_posts_each_time() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let elsewhere = MockServer::start().await;
+  let handle = instance.handle("alice");
+  let name = handle.to_string();
+
+  // Mastodon answers 404 for a deleted status, rather than 410.
+  Mock::given(matchers::path("/statuses/removed"))
+    .respond_with(ResponseTemplate::new(404))
+    .expect(1)
+    .mount(&elsewhere)
+    .await;
+
+  Mock::given(matchers::path("/statuses/old"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&elsewhere)
+    .await;
+
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let removed = format!("{}/statuses/removed", elsewhere.uri());
+  let old = format!("{}/statuses/old", elsewhere.uri());
+
+  for (id, published) in [
+    (&old, "2022-12-01T08:00:00Z"),
+    (&removed, "2022-11-01T08:00:00Z"),
+  ] {
+    archive.store(&post(id, published), Some(&name)).unwrap();
+  }
+
+  let client = Client::insecure();
+  let author = Author::new(handle);
+  let sync = || archive::sync(&client, &archive, &author, Walk::default(), 1);
+
+  // The newer is checked first, then the one not checked yet.
+  assert!(sync().await.unwrap().deleted.is_empty());
+  assert_eq!(sync().await.unwrap().deleted, [removed]);
+}
+
+#[tokio::test]
+async fn syncing_leaves_imported_private_posts_standing() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+  let name = handle.to_string();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let id = |n| format!("{}/users/alice/statuses/{}", instance.uri(), n);
+  let addressed = |id: &str, to: &str| -> Post {
+    serde_json::from_value(serde_json::json!({
+      "id": id,
+      "published": "2023-02-13T12:00:00Z",
+      "content": "<p>Between the outbox's two posts</p>",
+      "to": to,
+    }))
+    .unwrap()
+  };
+  let followers = format!("{}/users/alice/followers", instance.uri());
+
+  archive
+    .import(&addressed(&id(2001), &followers), Some(&name))
+    .unwrap();
+  archive
+    .import(&addressed(&id(2002), apread::activity::PUBLIC), Some(&name))
+    .unwrap();
+
+  let synced = archive::sync(
+    &Client::insecure(),
+    &archive,
+    &Author::new(handle),
+    Walk::default(),
+    archive::CHECKS,
+  )
+  .await
+  .unwrap();
+
+  assert_eq!(synced.deleted, [id(2002)]);
+}
+
+#[test]
+fn attachments_and_addressing_are_archived() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let post: Post = serde_json::from_value(serde_json::json!({
+    "id": "https://example.social/users/alice/statuses/1",
+    "content": "<p>For followers</p>",
+    "to": "https://example.social/users/alice/followers",
+    "cc": ["https://other.example/users/bob"],
+    "attachment": [{
+      "type": "Document",
+      "mediaType": "image/png",
+      "url": "https://example.social/media/1.png",
+      "name": "A lighthouse",
+      "width": 640,
+      "height": 480,
+    }],
+  }))
+  .unwrap();
+
+  archive.store(&post, Some("alice@example.social")).unwrap();
+
+  let archived = archive.posts().unwrap().remove(0);
+  assert_eq!(archived.visibility, Some(Visibility::Private));
+  assert_eq!(
+    archived.to,
+    ["https://example.social/users/alice/followers"]
+  );
+  assert_eq!(archived.cc, ["https://other.example/users/bob"]);
+  assert_eq!(archived.attachments.len(), 1);
+  assert_eq!(archived.attachments[0].alt_text(), Some("A lighthouse"));
+  assert_eq!(archived.attachments[0].width, Some(640));
+}
+
+#[test]
+fn edits_are_kept_as_revisions() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let post = |content: &str, updated: Option<&str>| -> Post {
+    serde_json::from_value(serde_json::json!({
+      "id": "https://example.social/notes/1",
+      "url": "https://example.social/@alice/1",
+      "published": "2023-02-14T18:03:11Z",
+      "updated": updated,
+      "content": content,
+    }))
+    .unwrap()
+  };
+
+  archive.store(&post("<p>Potatoes</p>", None), None).unwrap();
+  archive.store(&post("<p>Potatoes</p>", None), None).unwrap();
+  archive
+    .store(&post("<p>Tomatoes</p>", Some("2023-02-14T19:00:00Z")), None)
+    .unwrap();
+
+  let revisions = archive.history("https://example.social/@alice/1").unwrap();
+
+  assert_eq!(
+    revisions,
+    [
+      Revision {
+        summary: None,
+        content: "<p>Potatoes</p>".to_owned(),
+        edited_at: Some("2023-02-14T18:03:11Z".to_owned()),
+      },
+      Revision {
+        summary: None,
+        content: "<p>Tomatoes</p>".to_owned(),
+        edited_at: Some("2023-02-14T19:00:00Z".to_owned()),
+      },
+    ]
+  );
+  assert!(archive.history("https://example.social/notes/2").is_err());
+}
+
+#[test]
+fn domains_gather_their_accounts_posts() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  for (id, published, author) in [
+    ("1", "2024-01-01T00:00:00Z", "alice@small.town"),
+    ("2", "2024-01-03T00:00:00Z", "bob@Small.Town"),
+    ("3", "2024-01-02T00:00:00Z", "alice@small.town"),
+    ("4", "2024-01-04T00:00:00Z", "carol@notsmall.town"),
+  ] {
+    let id = format!("https://example.social/notes/{}", id);
+
+    archive.store(&post(&id, published), Some(author)).unwrap();
+  }
+
+  assert_eq!(
+    archive.accounts_on("small.town").unwrap(),
+    [
+      ("alice@small.town".to_owned(), 2),
+      ("bob@Small.Town".to_owned(), 1)
+    ]
+  );
+
+  let ids: Vec<_> = archive
+    .posts_on("small.town", Some(2))
+    .unwrap()
+    .into_iter()
+    .map(|post| post.id)
+    .collect();
+
+  assert_eq!(
+    ids,
+    [
+      "https://example.social/notes/2",
+      "https://example.social/notes/3"
+    ]
+  );
+  assert_eq!(archive.posts_on("small.town", None).unwrap().len(), 3);
+  assert!(archive.accounts_on("town").unwrap().is_empty());
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use apread::{
+  audit::{self, Checks, Problem},
+  links::Health,
+  timeline::{self, Author},
+  Client,
+};
+use common::FakeInstance;
+
+#[tokio::test]
+async fn attachments_without_alt_text_are_found() {
+  let instance = FakeInstance::start("gotosocial").await;
+  let author = Author::new(instance.handle("alice"));
+  let client = Client::insecure();
+  let entries = timeline::fetch(&client, &author).await.unwrap();
+
+  let report = audit::audit(&client, &entries, &Checks::default()).await;
+
+  assert_eq!(report.checked, 2);
+  assert_eq!(report.findings.len(), 1);
+  assert_eq!(
+    report.findings[0].problems,
+    [Problem::MissingAlt {
+      kind: "Image",
+      url: Some(format!(
+        "{}/fileserver/01GS/attachment/original/01GS2A.jpeg",
+        instance.uri()
+      )),
+    }]
+  );
+}
+
+#[tokio::test]
+async fn keywords_are_only_flagged_without_a_content_warning() {
+  let instance = FakeInstance::start("pleroma").await;
+  let author = Author::new(instance.handle("alice"));
+  let client = Client::insecure();
+  let entries = timeline::fetch(&client, &author).await.unwrap();
+  let checks = Checks {
+    warn_on: vec!["Uptime".into(), "config".into(), "box".into(), "yel".into()],
+    links: false,
+  };
+
+  let report = audit::audit(&client, &entries, &checks).await;
+
+  // The post about config formats has a warning, so only the other counts.
+  assert_eq!(report.findings.len(), 1);
+  assert_eq!(
+    report.findings[0].problems,
+    [
+      Problem::MissingWarning {
+        keyword: "Uptime".into()
+      },
+      Problem::MissingWarning {
+        keyword: "box".into()
+      },
+    ]
+  );
+}
+
+#[tokio::test]
+async fn links_are_checked() {
+  let instance = FakeInstance::start("gotosocial").await;
+  let client = Client::insecure();
+
+  assert_eq!(
+    client
+      .check(&format!("{}/users/alice", instance.uri()))
+      .await,
+    Health::Alive { code: 200 }
+  );
+  assert_eq!(
+    client.check(&format!("{}/gone", instance.uri())).await,
+    Health::Dead { code: 404 }
+  );
+  assert!(client.check("http://127.0.0.1:1/").await.is_broken());
+}
@@ -0,0 +1,109 @@
+use apread::{
+  archive::{self, Archive},
+  timeline::Author,
+  Client, Handle,
+};
+use serde_json::json;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+/// An account with an outbox three pages long, whose second page fails the
+/// first time it's asked for.
+async fn instance() -> MockServer {
+  let server = MockServer::start().await;
+  let base = server.uri();
+  let actor = format!("{}/users/alice", base);
+
+  Mock::given(matchers::path("/.well-known/webfinger"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "subject": "acct:alice@example.social",
+      "links": [
+        { "rel": "self", "type": "application/activity+json", "href": actor },
+      ],
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/users/alice"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "id": actor,
+      "preferredUsername": "alice",
+      "outbox": format!("{}/outbox", actor),
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/users/alice/outbox"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "type": "OrderedCollection",
+      "first": format!("{}/outbox/1", actor),
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/users/alice/outbox/2"))
+    .respond_with(ResponseTemplate::new(503))
+    .up_to_n_times(1)
+    .with_priority(1)
+    .mount(&server)
+    .await;
+
+  for number in 1..=3 {
+    let next = (number < 3).then(|| format!("{}/outbox/{}", actor, number + 1));
+
+    Mock::given(matchers::path(format!("/users/alice/outbox/{}", number)))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+        "type": "OrderedCollectionPage",
+        "next": next,
+        "orderedItems": (0..2).map(|post| json!({
+          "type": "Create",
+          "object": {
+            "id": format!("{}/statuses/{}{}", actor, number, post),
+            "published": "2023-02-14T18:03:11Z",
+            "content": "<p>Hello</p>",
+          },
+        })).collect::<Vec<_>>(),
+      })))
+      .mount(&server)
+      .await;
+  }
+
+  server
+}
+
+#[tokio::test]
+async fn backfills_carry_on_from_where_they_stopped() {
+  let dir = tempfile::tempdir().unwrap();
+  let server = instance().await;
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let handle = Handle {
+    id: "alice".to_owned(),
+    domain: server.address().to_string(),
+  };
+  let author = Author::new(handle.clone());
+  let client = Client::insecure();
+
+  assert!(archive::backfill(&client, &archive, &author).await.is_err());
+  assert_eq!(archive.posts().unwrap().len(), 2);
+  assert_eq!(
+    archive.checkpoint(&handle.to_string()).unwrap(),
+    Some(format!("{}/users/alice/outbox/2", server.uri()))
+  );
+
+  let before = server.received_requests().await.unwrap().len();
+  let backfilled = archive::backfill(&client, &archive, &author).await.unwrap();
+  let requests: Vec<_> = server
+    .received_requests()
+    .await
+    .unwrap()
+    .into_iter()
+    .skip(before)
+    .map(|request| request.url.path().to_owned())
+    .collect();
+
+  assert_eq!(requests, ["/users/alice/outbox/2", "/users/alice/outbox/3"]);
+  assert_eq!(backfilled.pages, 2);
+  assert_eq!(backfilled.archived, 4);
+  assert!(backfilled.complete);
+  assert_eq!(archive.posts().unwrap().len(), 6);
+  assert_eq!(archive.checkpoint(&handle.to_string()).unwrap(), None);
+}
@@ -0,0 +1,74 @@
+use apread::{
+  bidi::{self, Direction},
+  i18n::Messages,
+  render,
+  timeline::{Author, Entry},
+  Handle,
+};
+
+fn entry(content: &str) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(serde_json::json!({ "content": content }))
+      .unwrap(),
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  }
+}
+
+#[test]
+fn direction_goes_by_the_first_letter() {
+  assert_eq!(bidi::direction("שלום world"), Some(Direction::Rtl));
+  assert_eq!(bidi::direction("123 مرحبا"), Some(Direction::Rtl));
+  assert_eq!(bidi::direction("hello עולם"), Some(Direction::Ltr));
+  assert_eq!(bidi::direction("1, 2, 3"), None);
+}
+
+#[test]
+fn every_wrapped_line_of_a_right_to_left_paragraph_is_marked() {
+  let entry = entry(
+    "<p>زرعت الطماطم اليوم في الحديقة https://garden.example/tomatoes وأتمنى ألا يأتي الصقيع</p><p>Only English here</p>",
+  );
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 30, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+  let lines: Vec<_> = out
+    .lines()
+    .skip(2)
+    .filter(|line| !line.trim().is_empty())
+    .collect();
+  let (english, arabic) = lines.split_last().unwrap();
+
+  assert!(arabic.len() > 2);
+  for line in arabic {
+    assert!(line.starts_with("     \u{200F}"), "{:?}", line);
+  }
+  assert_eq!(*english, "     Only English here");
+}
+
+#[test]
+fn left_to_right_paragraphs_with_hebrew_in_are_marked_left_to_right() {
+  let entry =
+    entry("<p>The word for peace is שלום, and very nice it is too</p>");
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 20, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+
+  for line in out.lines().skip(2).filter(|line| !line.trim().is_empty()) {
+    assert!(line.starts_with("     \u{200E}"), "{:?}", line);
+  }
+}
+
+#[test]
+fn right_to_left_html_gets_a_dir_attribute() {
+  assert_eq!(
+    bidi::directed(
+      r#"<p>שלום</p><p class="x">hello</p><ul><li>مرحبا</li></ul><p dir="ltr">שלום</p>"#
+    ),
+    r#"<p dir="rtl">שלום</p><p class="x">hello</p><ul><li dir="rtl">مرحبا</li></ul><p dir="ltr">שלום</p>"#
+  );
+}
@@ -0,0 +1,99 @@
+use apread::{
+  blurhash::{self, COLUMNS, ROWS},
+  i18n::Messages,
+  locale::Locale,
+  sink::{OutputSink, Terminal},
+  timeline::{Author, Entry},
+  Handle,
+};
+use serde_json::json;
+
+/// One component, the average color: pure red.
+const RED: &str = "00TI:j";
+
+fn sensitive() -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(json!({
+      "content": "<p>Spoilers</p>",
+      "sensitive": true,
+      "attachment": [{
+        "type": "Document",
+        "mediaType": "image/png",
+        "url": "https://example.social/ending.png",
+        "name": "The ending",
+        "blurhash": RED,
+      }],
+    }))
+    .unwrap(),
+    published: "2024-01-01T00:00:00Z".to_owned(),
+    boosted: false,
+  }
+}
+
+fn shown(entries: &[Entry], colored: bool, reveal: bool) -> String {
+  let mut terminal = Terminal {
+    width: 80,
+    colored,
+    locale: Locale::default(),
+    messages: Messages::default(),
+    layout: Default::default(),
+    screen_reader: false,
+    math: Default::default(),
+    links: Default::default(),
+    reveal,
+    total: None,
+  };
+  let mut out = vec![];
+
+  terminal.write(&mut out, entries).unwrap();
+  String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn hashes_decode_to_their_colors() {
+  let pixels = blurhash::decode(RED, 4, 3).unwrap();
+
+  assert_eq!(pixels.len(), 12);
+  assert!(pixels.iter().all(|pixel| *pixel == [255, 0, 0]));
+
+  let varied = blurhash::decode("LEHV6nWB2yk8pyo0adR*.7kCMdnj", 8, 8).unwrap();
+  assert_eq!(varied.len(), 64);
+  assert!(varied.iter().any(|pixel| *pixel != varied[0]));
+}
+
+#[test]
+fn malformed_hashes_decode_to_nothing() {
+  assert_eq!(blurhash::decode("", 4, 4), None);
+  assert_eq!(blurhash::decode("LEHV6nWB2yk8", 4, 4), None);
+  assert_eq!(blurhash::decode("00TI:ü", 4, 4), None);
+}
+
+#[test]
+fn blocks_fill_the_cells_with_color() {
+  let lines = blurhash::blocks(RED).unwrap();
+
+  assert_eq!(lines.len(), ROWS);
+  assert_eq!(lines[0].matches('▀').count(), COLUMNS);
+  assert!(lines[0].contains("38;2;255;0;0;48;2;255;0;0m"));
+}
+
+#[test]
+fn sensitive_media_is_blurred_until_revealed() {
+  let entries = vec![sensitive()];
+
+  let hidden = shown(&entries, true, false);
+  assert!(hidden.contains('▀'), "{}", hidden);
+  assert!(hidden.contains("marked sensitive"), "{}", hidden);
+  assert!(!hidden.contains("ending.png"), "{}", hidden);
+  assert!(!hidden.contains("The ending"), "{}", hidden);
+
+  let plain = shown(&entries, false, false);
+  assert!(!plain.contains('▀'), "{}", plain);
+  assert!(plain.contains("marked sensitive"), "{}", plain);
+
+  let revealed = shown(&entries, true, true);
+  assert!(revealed.contains("ending.png"), "{}", revealed);
+  assert!(revealed.contains("The ending"), "{}", revealed);
+  assert!(!revealed.contains('▀'), "{}", revealed);
+}
@@ -0,0 +1,112 @@
+use apread::{breaker::skipped, ApreadErrors, Client, InstanceDown};
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+async fn unavailable() -> MockServer {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(503))
+    .mount(&server)
+    .await;
+
+  server
+}
+
+#[tokio::test]
+async fn instances_failing_twice_are_skipped() {
+  let server = unavailable().await;
+  let client = Client::insecure();
+  let url = format!("{}/about", server.uri());
+
+  client.web_page(&url).await.ok();
+  client.web_page(&url).await.ok();
+
+  let skipped = client.web_page(&url).await;
+
+  assert!(
+    matches!(skipped, Err(ApreadErrors::InstanceDown(_))),
+    "{:?}",
+    skipped
+  );
+  assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn other_instances_are_still_read() {
+  let down = unavailable().await;
+  let up = MockServer::start().await;
+
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+    .mount(&up)
+    .await;
+
+  let client = Client::insecure();
+  let down = format!("{}/about", down.uri());
+
+  for _ in 0..3 {
+    client.web_page(&down).await.ok();
+  }
+
+  assert!(client
+    .web_page(&format!("{}/about", up.uri()))
+    .await
+    .is_ok());
+}
+
+#[tokio::test]
+async fn refused_connections_count_as_failures() {
+  let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+  let url = format!("http://{}/about", listener.local_addr().unwrap());
+  let client = Client::insecure();
+
+  drop(listener);
+  client.web_page(&url).await.ok();
+  client.web_page(&url).await.ok();
+
+  assert!(matches!(
+    client.web_page(&url).await,
+    Err(ApreadErrors::InstanceDown(_))
+  ));
+}
+
+#[tokio::test]
+async fn answering_in_between_resets_the_count() {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(503))
+    .up_to_n_times(1)
+    .mount(&server)
+    .await;
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(200))
+    .up_to_n_times(1)
+    .mount(&server)
+    .await;
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(503))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure();
+  let url = format!("{}/about", server.uri());
+
+  for _ in 0..4 {
+    client.web_page(&url).await.ok();
+  }
+
+  assert_eq!(server.received_requests().await.unwrap().len(), 4);
+}
+
+#[test]
+fn skipped_instances_are_each_named_once() {
+  let errors = [
+    InstanceDown("down.example".to_owned()).into(),
+    ApreadErrors::NothingToRead(apread::NothingToRead),
+    InstanceDown("down.example".to_owned()).into(),
+    InstanceDown("gone.example".to_owned()).into(),
+  ];
+
+  assert_eq!(skipped(&errors), ["down.example", "gone.example"]);
+}
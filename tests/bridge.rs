@@ -0,0 +1,42 @@
+mod common;
+
+use apread::{
+  bridge::Bridge,
+  i18n::Messages,
+  render,
+  timeline::{self, Author},
+  Client, Handle,
+};
+use common::FakeInstance;
+
+#[test]
+fn bridges_are_known_by_their_domains() {
+  let bluesky = Handle::parse_string("alice.bsky.social@bsky.brid.gy").unwrap();
+
+  assert_eq!(Author::new(bluesky).bridge, Some(Bridge::Bluesky));
+  assert_eq!(
+    Bridge::from_url("https://web.brid.gy/alice.example"),
+    Some(Bridge::Web)
+  );
+  assert_eq!(Bridge::from_url("https://example.social/users/alice"), None);
+}
+
+#[tokio::test]
+async fn bridged_outboxes_read_like_any_other() {
+  let instance = FakeInstance::start("bridgy").await;
+  let author = Author {
+    bridge: Some(Bridge::Bluesky),
+    ..Author::new(instance.handle("alice.bsky.social"))
+  };
+
+  let entries = timeline::fetch(&Client::insecure(), &author).await.unwrap();
+
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].published, "2023-09-01T08:15:00.000Z");
+
+  let mut out = vec![];
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "bsky")]
+
+mod common;
+
+use apread::{
+  i18n::Messages,
+  render,
+  timeline::{self, Author, Source},
+  Client,
+};
+use common::FakeInstance;
+
+#[test]
+fn bare_domains_are_bluesky_handles() {
+  assert_eq!(
+    Source::parse("alice.bsky.social").unwrap(),
+    Source::Bluesky("alice.bsky.social".to_owned())
+  );
+  assert!(matches!(
+    Source::parse("alice@example.social").unwrap(),
+    Source::Account(_)
+  ));
+}
+
+#[tokio::test]
+async fn bluesky_posts_read_like_any_other() {
+  let appview = FakeInstance::start("bsky").await;
+  let client = Client::insecure().appview(appview.uri());
+  let author =
+    Author::from_source(Source::Bluesky("alice.bsky.social".to_owned()));
+
+  let entries = timeline::fetch(&client, &author).await.unwrap();
+
+  assert_eq!(entries.len(), 1, "reposts are skipped");
+  assert_eq!(
+    entries[0].post.url.as_deref(),
+    Some("https://bsky.app/profile/alice.bsky.social/post/3kbs2xvj4np2b")
+  );
+  assert_eq!(entries[0].post.hashtags(), ["gardening"]);
+
+  let mut out = vec![];
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
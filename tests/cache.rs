@@ -0,0 +1,49 @@
+mod common;
+
+use std::time::Duration;
+
+use apread::{cache::DiscoveryCache, Client};
+use common::FakeInstance;
+
+async fn lookups(instance: &FakeInstance) -> usize {
+  instance
+    .requests()
+    .await
+    .iter()
+    .filter(|path| {
+      path.starts_with("/.well-known/webfinger") || *path == "/users/alice"
+    })
+    .count()
+}
+
+#[tokio::test]
+async fn recent_lookups_skip_discovery() {
+  let instance = FakeInstance::start("mastodon").await;
+  let dir = tempfile::tempdir().unwrap();
+  let client = Client::insecure().caching_discovery(DiscoveryCache::new(
+    dir.path(),
+    Duration::from_secs(60),
+  ));
+
+  client.first_page(&instance.handle("alice")).await.unwrap();
+  client.first_page(&instance.handle("alice")).await.unwrap();
+
+  assert_eq!(
+    lookups(&instance).await,
+    2,
+    "webfinger and actor, once each"
+  );
+}
+
+#[tokio::test]
+async fn stale_lookups_are_made_again() {
+  let instance = FakeInstance::start("mastodon").await;
+  let dir = tempfile::tempdir().unwrap();
+  let client = Client::insecure()
+    .caching_discovery(DiscoveryCache::new(dir.path(), Duration::ZERO));
+
+  client.first_page(&instance.handle("alice")).await.unwrap();
+  client.first_page(&instance.handle("alice")).await.unwrap();
+
+  assert_eq!(lookups(&instance).await, 4);
+}
@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::PermissionsExt};
+
+use apread::{clipboard, ApreadErrors};
+
+// The only test here, since it sets the PATH for the whole binary.
+#[test]
+fn text_is_copied_by_the_first_program_which_works() {
+  let dir = tempfile::tempdir().unwrap();
+  let copied = dir.path().join("copied");
+
+  for (name, script) in [
+    // As xclip fails without an X display to reach.
+    ("xclip", "#!/bin/sh\nexit 1\n".to_owned()),
+    (
+      "xsel",
+      format!("#!/bin/sh\n/bin/cat > {}\n", copied.display()),
+    ),
+  ] {
+    let path = dir.path().join(name);
+
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+  }
+
+  std::env::set_var("PATH", dir.path());
+
+  clipboard::copy("https://example.social/@alice/1").unwrap();
+  assert_eq!(
+    fs::read_to_string(&copied).unwrap(),
+    "https://example.social/@alice/1"
+  );
+
+  fs::remove_file(dir.path().join("xsel")).unwrap();
+  assert!(matches!(
+    clipboard::copy("anything"),
+    Err(ApreadErrors::NoClipboard(_))
+  ));
+}
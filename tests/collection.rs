@@ -0,0 +1,52 @@
+mod common;
+
+use apread::{
+  activity::Post,
+  collection::{Collection, ObjectRef},
+  Client,
+};
+use common::FakeInstance;
+
+#[tokio::test]
+async fn followers_are_read_from_a_linked_page() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let actor = client.resolve(&instance.handle("alice")).await.unwrap();
+
+  let followers: Collection<ObjectRef> = client
+    .collection(actor.followers.as_deref().unwrap())
+    .await
+    .unwrap();
+  assert_eq!(followers.total_items, Some(2));
+
+  let page = client.first_page_of(followers).await.unwrap();
+  let urls: Vec<_> = page.ordered_items.iter().map(ObjectRef::url).collect();
+
+  assert_eq!(
+    urls,
+    [
+      "https://bob.example/users/bob",
+      "https://carol.example/users/carol"
+    ]
+  );
+}
+
+#[tokio::test]
+async fn featured_posts_are_read_from_the_collection_itself() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let actor = client.resolve(&instance.handle("alice")).await.unwrap();
+
+  let featured: Collection<Post> = client
+    .collection(actor.featured.as_deref().unwrap())
+    .await
+    .unwrap();
+  let page = client.first_page_of(featured).await.unwrap();
+
+  assert_eq!(page.ordered_items.len(), 1);
+  assert_eq!(
+    page.ordered_items[0].content,
+    "<p>Pinned: notes on register allocation.</p>"
+  );
+  assert_eq!(page.next, None);
+}
@@ -0,0 +1,115 @@
+//! A fake ActivityPub instance, serving canned documents from
+//! `tests/fixtures/<software>`.
+//!
+//! Each fixture directory has a `routes.json` mapping request paths (with
+//! their query, where it matters) to the file served for them. Fixture files
+//! can use `{{base}}` and `{{host}}`, which are filled in with the fake
+//! instance's own address so links between documents resolve back to it.
+
+// Each test binary compiles this module separately, using only some of it.
+#![allow(dead_code)]
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use apread::Handle;
+use wiremock::{
+  matchers::any, Mock, MockServer, Request, Respond, ResponseTemplate,
+};
+
+pub struct FakeInstance {
+  server: MockServer,
+}
+
+impl FakeInstance {
+  pub async fn start(software: &str) -> Self {
+    let server = MockServer::start().await;
+    let fixtures = Fixtures::load(software, &server.uri());
+
+    Mock::given(any())
+      .respond_with(fixtures)
+      .mount(&server)
+      .await;
+
+    Self { server }
+  }
+
+  /// The instance's base url, which stands in for `{{base}}` in fixtures.
+  pub fn uri(&self) -> String {
+    self.server.uri()
+  }
+
+  /// The paths (and queries) of every request the instance has been sent,
+  /// in order.
+  pub async fn requests(&self) -> Vec<String> {
+    self
+      .server
+      .received_requests()
+      .await
+      .unwrap_or_default()
+      .into_iter()
+      .map(|request| match request.url.query() {
+        Some(query) => format!("{}?{}", request.url.path(), query),
+        None => request.url.path().to_owned(),
+      })
+      .collect()
+  }
+
+  pub fn handle(&self, id: &str) -> Handle {
+    Handle {
+      domain: self.server.address().to_string(),
+      id: id.to_owned(),
+    }
+  }
+}
+
+struct Fixtures {
+  dir: PathBuf,
+  routes: HashMap<String, String>,
+  base: String,
+  host: String,
+}
+
+impl Fixtures {
+  fn load(software: &str, base: &str) -> Self {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/fixtures")
+      .join(software);
+    let routes = fs::read_to_string(dir.join("routes.json"))
+      .expect("fixture directory should have a routes.json");
+
+    Self {
+      routes: serde_json::from_str(&routes).expect("routes.json should parse"),
+      host: base.trim_start_matches("http://").to_owned(),
+      base: base.to_owned(),
+      dir,
+    }
+  }
+}
+
+impl Respond for Fixtures {
+  fn respond(&self, request: &Request) -> ResponseTemplate {
+    let path = request.url.path();
+    let full = match request.url.query() {
+      Some(query) => format!("{}?{}", path, query),
+      None => path.to_owned(),
+    };
+
+    let file = match self.routes.get(&full).or_else(|| self.routes.get(path)) {
+      Some(file) => file,
+      None => return ResponseTemplate::new(404),
+    };
+
+    let body = fs::read_to_string(self.dir.join(file))
+      .expect("routed fixture should exist")
+      .replace("{{base}}", &self.base)
+      .replace("{{host}}", &self.host);
+
+    let mime = match file.rsplit('.').next() {
+      Some("html") => "text/html",
+      Some("xml") => "application/xml",
+      _ => "application/activity+json",
+    };
+
+    ResponseTemplate::new(200).set_body_raw(body, mime)
+  }
+}
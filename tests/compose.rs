@@ -0,0 +1,230 @@
+use std::path::Path;
+
+use apread::{
+  compose::{self, Action, Draft, Media, Visibility},
+  login::Login,
+  ApreadErrors, Client,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{body_partial_json, header, method, path, query_param},
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn login(instance: &str, write: bool) -> Login {
+  Login {
+    instance: instance.to_owned(),
+    account: "alice".to_owned(),
+    access_token: "sekrit".to_owned(),
+    write,
+  }
+}
+
+fn status(id: &str, acct: &str) -> serde_json::Value {
+  json!({
+    "id": id,
+    "uri": format!("https://example.social/statuses/{}", id),
+    "url": format!("https://example.social/@alice/{}", id),
+    "created_at": "2023-06-01T10:00:00.000Z",
+    "content": "<p>hello</p>",
+    "account": { "acct": acct },
+  })
+}
+
+#[test]
+fn replies_mention_whoever_they_reply_to_once() {
+  assert_eq!(
+    compose::with_mention("lovely!", "bob@elsewhere.example", "alice"),
+    "@bob@elsewhere.example lovely!"
+  );
+  assert_eq!(
+    compose::with_mention(
+      "@bob@elsewhere.example lovely!",
+      "bob@elsewhere.example",
+      "alice"
+    ),
+    "@bob@elsewhere.example lovely!"
+  );
+  assert_eq!(
+    compose::with_mention("and another thing", "alice", "alice"),
+    "and another thing"
+  );
+}
+
+#[test]
+fn media_is_typed_by_its_extension() {
+  assert_eq!(compose::content_type(Path::new("a.PNG")), "image/png");
+  assert_eq!(compose::content_type(Path::new("b.jpeg")), "image/jpeg");
+  assert_eq!(compose::content_type(Path::new("c.ogg")), "audio/ogg");
+  assert_eq!(
+    compose::content_type(Path::new("d")),
+    "application/octet-stream"
+  );
+}
+
+#[test]
+fn uploads_carry_the_file_and_its_alt_text() {
+  let media = Media {
+    name: "tomato.png".to_owned(),
+    content_type: "image/png",
+    bytes: b"PNG".to_vec(),
+    description: Some("A red tomato".to_owned()),
+  };
+  let form = String::from_utf8(media.form()).unwrap();
+  let boundary = Media::form_type()
+    .split_once("boundary=")
+    .unwrap()
+    .1
+    .to_owned();
+
+  assert!(form.starts_with(&format!("--{}\r\n", boundary)));
+  assert!(form.contains(
+    "name=\"file\"; filename=\"tomato.png\"\r\nContent-Type: image/png\r\n\r\nPNG\r\n"
+  ));
+  assert!(form.contains("name=\"description\"\r\n\r\nA red tomato\r\n"));
+  assert!(form.ends_with(&format!("--{}--\r\n", boundary)));
+}
+
+#[tokio::test]
+async fn replies_are_found_on_your_instance_and_posted_there() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v2/search"))
+    .and(query_param("q", "https://elsewhere.example/notes/300"))
+    .and(query_param("resolve", "true"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "statuses": [status("300", "bob@elsewhere.example")],
+    })))
+    .mount(&server)
+    .await;
+  Mock::given(method("POST"))
+    .and(path("/api/v1/statuses"))
+    .and(header("authorization", "Bearer sekrit"))
+    .and(body_partial_json(json!({
+      "status": "@bob@elsewhere.example lovely!",
+      "visibility": "unlisted",
+      "spoiler_text": "food",
+      "in_reply_to_id": "300",
+    })))
+    .respond_with(
+      ResponseTemplate::new(200).set_body_json(status("301", "alice")),
+    )
+    .expect(1)
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance, true));
+  let found = client
+    .find_status("https://elsewhere.example/notes/300")
+    .await
+    .unwrap();
+  let draft = Draft {
+    status: compose::with_mention("lovely!", &found.account.acct, "alice"),
+    spoiler_text: Some("food".to_owned()),
+    visibility: Visibility::Unlisted,
+    in_reply_to_id: Some(found.id),
+    ..Draft::default()
+  };
+  let posted = client.post_status(&draft).await.unwrap();
+
+  assert_eq!(
+    posted.url.as_deref(),
+    Some("https://example.social/@alice/301")
+  );
+}
+
+#[tokio::test]
+async fn posting_needs_a_login_made_to_write() {
+  let draft = Draft {
+    status: "hello".to_owned(),
+    ..Draft::default()
+  };
+
+  assert!(matches!(
+    Client::insecure().post_status(&draft).await,
+    Err(ApreadErrors::NotLoggedIn(_))
+  ));
+  assert!(matches!(
+    Client::insecure()
+      .logged_in(login("example.social", false))
+      .post_status(&draft)
+      .await,
+    Err(ApreadErrors::ReadOnly(_))
+  ));
+}
+
+#[tokio::test]
+async fn posts_are_favourited_and_boosted_by_their_id_on_your_instance() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v2/search"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "statuses": [status("300", "bob@elsewhere.example")],
+    })))
+    .mount(&server)
+    .await;
+
+  for endpoint in ["favourite", "reblog"] {
+    Mock::given(method("POST"))
+      .and(path(format!("/api/v1/statuses/300/{}", endpoint)))
+      .and(header("authorization", "Bearer sekrit"))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_json(status("300", "bob@elsewhere.example")),
+      )
+      .expect(1)
+      .mount(&server)
+      .await;
+  }
+
+  let client = Client::insecure().logged_in(login(&instance, true));
+  let url = "https://elsewhere.example/notes/300";
+
+  client.act(url, Action::Favourite).await.unwrap();
+  client.act(url, Action::Boost).await.unwrap();
+}
+
+#[tokio::test]
+async fn drafts_with_a_time_are_scheduled_and_can_be_cancelled() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("POST"))
+    .and(path("/api/v1/statuses"))
+    .and(body_partial_json(json!({
+      "status": "good morning",
+      "scheduled_at": "2030-06-01T09:00:00+01:00",
+    })))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "id": "5",
+      "scheduled_at": "2030-06-01T08:00:00.000Z",
+      "params": { "text": "good morning", "spoiler_text": null },
+    })))
+    .mount(&server)
+    .await;
+  Mock::given(method("DELETE"))
+    .and(path("/api/v1/scheduled_statuses/5"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+    .expect(1)
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance, true));
+  let draft = Draft {
+    status: "good morning".to_owned(),
+    scheduled_at: Some("2030-06-01T09:00:00+01:00".to_owned()),
+    ..Draft::default()
+  };
+  let scheduled = client.schedule_status(&draft).await.unwrap();
+
+  assert_eq!(scheduled.id, "5");
+  assert_eq!(scheduled.params.text, "good morning");
+
+  client.unschedule(&scheduled.id).await.unwrap();
+}
@@ -0,0 +1,96 @@
+use std::io::Write;
+
+use apread::Client;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+const POST: &str =
+  r#"{"id": "https://example.social/notes/1", "content": "<p>squeezed</p>"}"#;
+
+const ENCODINGS: [&str; 4] = ["gzip", "deflate", "br", "zstd"];
+
+fn compress(encoding: &str, body: &[u8]) -> Vec<u8> {
+  match encoding {
+    "gzip" => gzip(body),
+    "deflate" => deflate(body),
+    "br" => brotli(body),
+    _ => zstd(body),
+  }
+}
+
+fn gzip(body: &[u8]) -> Vec<u8> {
+  let mut encoder =
+    flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+  encoder.write_all(body).unwrap();
+  encoder.finish().unwrap()
+}
+
+fn deflate(body: &[u8]) -> Vec<u8> {
+  let mut encoder =
+    flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+  encoder.write_all(body).unwrap();
+  encoder.finish().unwrap()
+}
+
+fn brotli(body: &[u8]) -> Vec<u8> {
+  let mut out = vec![];
+  brotli::BrotliCompress(
+    &mut &body[..],
+    &mut out,
+    &brotli::enc::BrotliEncoderParams::default(),
+  )
+  .unwrap();
+  out
+}
+
+fn zstd(body: &[u8]) -> Vec<u8> {
+  zstd::encode_all(body, 0).unwrap()
+}
+
+#[tokio::test]
+async fn compressed_responses_are_decoded() {
+  let server = MockServer::start().await;
+
+  for encoding in ENCODINGS {
+    Mock::given(matchers::path(format!("/notes/{}", encoding)))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .insert_header("content-encoding", encoding)
+          .set_body_bytes(compress(encoding, POST.as_bytes())),
+      )
+      .mount(&server)
+      .await;
+  }
+
+  for encoding in ENCODINGS {
+    let url = format!("{}/notes/{}", server.uri(), encoding);
+    let post = Client::insecure().post(&url).await.unwrap();
+
+    assert_eq!(post.content, "<p>squeezed</p>", "{}", encoding);
+  }
+}
+
+#[tokio::test]
+async fn every_encoding_is_asked_for() {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/notes/1"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(POST))
+    .mount(&server)
+    .await;
+
+  Client::insecure()
+    .post(&format!("{}/notes/1", server.uri()))
+    .await
+    .unwrap();
+
+  let requests = server.received_requests().await.unwrap();
+  let accepted = requests[0]
+    .headers
+    .get(&"accept-encoding".into())
+    .unwrap()
+    .to_string();
+
+  for encoding in ENCODINGS {
+    assert!(accepted.contains(encoding), "{}", accepted);
+  }
+}
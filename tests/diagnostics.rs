@@ -0,0 +1,85 @@
+mod common;
+
+use apread::{
+  diagnostics::{Diagnostic, Diagnostics, Kind, SLOW},
+  i18n::Messages,
+  render,
+  timeline::{self, Author},
+  Client,
+};
+use common::FakeInstance;
+
+#[test]
+fn diagnostics_are_shared_and_said_once() {
+  let diagnostics = Diagnostics::default();
+  let clone = diagnostics.clone();
+  let full = Diagnostic::warning(
+    Kind::Cache,
+    None,
+    "couldn't write to the archive: disk full",
+  );
+
+  clone.push(full.clone());
+  clone.push(full.clone());
+  diagnostics.request("https://fast.example/users/alice", SLOW / 2);
+  diagnostics.request("https://slow.example/users/alice", SLOW * 2);
+  diagnostics.request("https://slow.example/users/bob", SLOW * 3);
+  clone.push(Diagnostic::note(Kind::Filtered, None, "1 post hidden"));
+
+  let all = diagnostics.take();
+
+  assert_eq!(all.len(), 3);
+  assert_eq!(all[0], full);
+  assert_eq!(all[1].kind, Kind::Slow);
+  assert_eq!(all[1].source.as_deref(), Some("slow.example"));
+  assert_eq!(
+    all[1].message,
+    "slow.example was slow to answer, taking 10.0s"
+  );
+  assert!(clone.is_empty());
+
+  let mut out = vec![];
+  render::diagnostics(&mut out, &all, &Messages::default()).unwrap();
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "warning: couldn't write to the archive: disk full\n\
+     warning: slow.example was slow to answer, taking 10.0s\n\
+     note: 1 post hidden\n"
+  );
+
+  let mut out = vec![];
+  render::diagnostics_json(&mut out, &all[2..]).unwrap();
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "[{\"level\":\"note\",\"kind\":\"filtered\",\"message\":\"1 post hidden\"}]\n"
+  );
+}
+
+#[tokio::test]
+async fn boosts_which_cant_be_fetched_are_reported() {
+  let instance = FakeInstance::start("mastodon").await;
+  let mut author = Author::new(instance.handle("alice"));
+  let client = Client::insecure();
+  let missing = format!("{}/users/alice/statuses/404", instance.uri());
+
+  author.mix.boosts = true;
+  let page = serde_json::from_value(serde_json::json!({
+    "orderedItems": [{
+      "type": "Announce",
+      "published": "2023-02-15T08:00:00Z",
+      "object": missing,
+    }]
+  }))
+  .unwrap();
+
+  assert!(timeline::boosts(&client, &author, &page).await.is_empty());
+
+  let diagnostics = client.diagnostics().take();
+
+  assert_eq!(diagnostics.len(), 1);
+  assert_eq!(diagnostics[0].kind, Kind::Skipped);
+  assert_eq!(diagnostics[0].source.as_deref(), Some(missing.as_str()));
+  assert!(diagnostics[0]
+    .message
+    .starts_with("skipped a post alice boosted"));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use std::fs;
+
+use apread::{
+  doctor::{self, Status},
+  Client,
+};
+use common::FakeInstance;
+
+#[test]
+fn config_files_are_checked_when_there_is_one() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("config.toml");
+
+  assert_eq!(doctor::config(Some(&path)).status, Status::Pass);
+
+  fs::write(&path, "width = 100\n").unwrap();
+  assert_eq!(doctor::config(Some(&path)).status, Status::Pass);
+
+  fs::write(&path, "width = \"wide\"\n").unwrap();
+  assert_eq!(doctor::config(Some(&path)).status, Status::Fail);
+}
+
+#[test]
+fn cache_directories_have_to_be_writable() {
+  let dir = tempfile::tempdir().unwrap();
+  let file = dir.path().join("in-the-way");
+  fs::write(&file, "").unwrap();
+
+  assert_eq!(
+    doctor::cache_dir(Some(&dir.path().join("cache"))).status,
+    Status::Pass
+  );
+  assert_eq!(
+    doctor::cache_dir(Some(&file.join("cache"))).status,
+    Status::Fail
+  );
+}
+
+#[tokio::test]
+async fn handles_are_followed_to_their_actor() {
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+
+  assert_eq!(doctor::dns(&handle).status, Status::Pass);
+
+  let check = doctor::webfinger(&Client::insecure(), &handle).await;
+  assert_eq!(check.status, Status::Pass);
+  assert_eq!(
+    check.detail,
+    format!(
+      "{base}/users/alice has its outbox at {base}/users/alice/outbox",
+      base = instance.uri()
+    )
+  );
+}
+
+#[tokio::test]
+async fn missing_webfinger_documents_fail() {
+  let instance = FakeInstance::start("feeds").await;
+  let check =
+    doctor::webfinger(&Client::insecure(), &instance.handle("alice")).await;
+
+  assert_eq!(check.status, Status::Fail);
+}
@@ -0,0 +1,85 @@
+use std::net::IpAddr;
+
+use apread::{doh::Doh, settings::Settings, Client};
+use serde_json::json;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+/// A DoH server which knows one name, by way of a CNAME.
+async fn resolver() -> MockServer {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/dns-query"))
+    .and(matchers::query_param("name", "fediverse.test"))
+    .and(matchers::query_param("type", "1"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "Status": 0,
+      "Answer": [
+        { "name": "fediverse.test", "type": 5, "data": "lb.fediverse.test." },
+        {
+          "name": "lb.fediverse.test",
+          "type": 1,
+          "TTL": 300,
+          "data": "127.0.0.1",
+        },
+      ],
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/dns-query"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "Status": 0,
+    })))
+    .mount(&server)
+    .await;
+
+  server
+}
+
+#[tokio::test]
+async fn names_are_looked_up_through_the_resolver() {
+  let server = resolver().await;
+  let doh = Doh::new(format!("{}/dns-query", server.uri()));
+
+  assert_eq!(
+    doh.lookup("fediverse.test").await.unwrap(),
+    ["127.0.0.1".parse::<IpAddr>().unwrap()]
+  );
+  assert!(doh.lookup("unknown.test").await.is_err());
+}
+
+#[tokio::test]
+async fn answers_are_kept_for_their_ttl() {
+  let server = resolver().await;
+  let doh = Doh::new(format!("{}/dns-query", server.uri()));
+
+  doh.lookup("fediverse.test").await.unwrap();
+  doh.clone().lookup("fediverse.test").await.unwrap();
+
+  let asked = server.received_requests().await.unwrap();
+  assert_eq!(asked.len(), 2, "A and AAAA are each asked for once");
+}
+
+#[tokio::test]
+async fn clients_fetch_from_wherever_the_resolver_says() {
+  let resolver = resolver().await;
+  let instance = MockServer::start().await;
+
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+    .mount(&instance)
+    .await;
+
+  let settings = Settings {
+    doh: Some(format!("{}/dns-query", resolver.uri())),
+    ..Settings::default()
+  };
+  let port = instance.address().port();
+  let page = Client::from_settings(&settings)
+    .unwrap()
+    .web_page(&format!("http://fediverse.test:{}/about", port))
+    .await
+    .unwrap();
+
+  assert_eq!(page, "hello");
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use apread::{
+  activity::{Item, OutboxIndex, Post},
+  archive::Archive,
+  compose::Visibility,
+  export, Client,
+};
+use common::FakeInstance;
+use rusqlite::Connection;
+
+#[tokio::test]
+async fn sqlite_exports_posts_tags_and_bookmarks() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let mut archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  let page = client.first_page(&instance.handle("alice")).await.unwrap();
+  let newest = match &page.ordered_items[0] {
+    apread::activity::Item::Post { object, .. } => object.clone(),
+    _ => panic!("the fixture starts with a post"),
+  };
+  archive
+    .store(&newest, Some("alice@example.social"))
+    .unwrap();
+
+  let url =
+    format!("{}/users/alice/statuses/109000000000000001", instance.uri());
+  let bookmarked = client.post(&url).await.unwrap();
+  archive
+    .bookmark(&bookmarked, None, &["later".to_owned()], Some("good"))
+    .unwrap();
+
+  let path = dir.path().join("export.db");
+  let written = export::sqlite(
+    &path,
+    &archive.posts().unwrap(),
+    &archive.bookmarks(None).unwrap(),
+  )
+  .unwrap();
+
+  assert_eq!(written, 2);
+
+  let export = Connection::open(&path).unwrap();
+  let version: String = export
+    .query_row(
+      "SELECT value FROM meta WHERE key = 'schema_version'",
+      [],
+      |row| row.get(0),
+    )
+    .unwrap();
+  let tag: String = export
+    .query_row("SELECT tag FROM tags", [], |row| row.get(0))
+    .unwrap();
+  let plaintext: String = export
+    .query_row(
+      "SELECT content_plaintext FROM posts WHERE handle IS NOT NULL",
+      [],
+      |row| row.get(0),
+    )
+    .unwrap();
+  let note: String = export
+    .query_row("SELECT note FROM bookmarks", [], |row| row.get(0))
+    .unwrap();
+
+  assert_eq!(version, export::SCHEMA_VERSION.to_string());
+  assert_eq!(tag, "gardening");
+  assert!(plaintext.starts_with("The tomatoes survived the frost!"));
+  assert_eq!(note, "good");
+}
+
+#[test]
+fn sqlite_exports_never_overwrite() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("export.db");
+
+  std::fs::write(&path, "precious").unwrap();
+
+  assert!(export::sqlite(&path, &[], &[]).is_err());
+  assert_eq!(std::fs::read_to_string(&path).unwrap(), "precious");
+}
+
+#[tokio::test]
+async fn ap_archives_read_back_as_outboxes() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let mut archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  let page = client.first_page(&instance.handle("alice")).await.unwrap();
+  for item in &page.ordered_items {
+    if let Item::Post { object, .. } = item {
+      archive.store(object, Some("alice@example.social")).unwrap();
+    }
+  }
+
+  let url =
+    format!("{}/users/alice/statuses/109000000000000001", instance.uri());
+  let bookmarked = client.post(&url).await.unwrap();
+  archive.bookmark(&bookmarked, None, &[], None).unwrap();
+
+  let export = dir.path().join("export");
+  let written = export::ap_archive(
+    &export,
+    &archive.posts().unwrap(),
+    &archive.bookmarks(None).unwrap(),
+  )
+  .unwrap();
+
+  assert_eq!(written, 2);
+
+  let outbox: OutboxIndex = serde_json::from_str(
+    &std::fs::read_to_string(export.join("outbox.json")).unwrap(),
+  )
+  .unwrap();
+  let published: Vec<_> = outbox
+    .ordered_items
+    .iter()
+    .map(|item| match item {
+      Item::Post { object, .. } => object.published.clone().unwrap(),
+      _ => panic!("outboxes only hold posts"),
+    })
+    .collect();
+
+  assert_eq!(outbox.total_items, Some(2));
+  assert_eq!(published, ["2023-02-12T21:15:42Z", "2023-02-14T18:03:11Z"]);
+
+  let bookmarks: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(export.join("bookmarks.json")).unwrap(),
+  )
+  .unwrap();
+  assert_eq!(bookmarks["orderedItems"], serde_json::json!([url]));
+}
+
+#[test]
+fn ap_archives_keep_addressing_actors_and_attachments() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let post: Post = serde_json::from_value(serde_json::json!({
+    "id": "https://example.social/users/alice/statuses/1",
+    "attributedTo": "https://example.social/users/alice",
+    "published": "2023-02-14T18:03:11Z",
+    "content": "<p>For followers</p>",
+    "to": "https://example.social/users/alice/followers",
+    "attachment": {
+      "type": "Document",
+      "mediaType": "image/png",
+      "url": "https://example.social/media/1.png",
+      "name": "A diagram",
+    },
+  }))
+  .unwrap();
+
+  archive.store(&post, Some("alice@example.social")).unwrap();
+
+  let export = dir.path().join("export");
+  export::ap_archive(&export, &archive.posts().unwrap(), &[]).unwrap();
+
+  let outbox: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(export.join("outbox.json")).unwrap(),
+  )
+  .unwrap();
+  let activity = &outbox["orderedItems"][0];
+  let object: Post =
+    serde_json::from_value(activity["object"].clone()).unwrap();
+
+  assert_eq!(activity["actor"], "https://example.social/users/alice");
+  assert_eq!(object.visibility(), Some(Visibility::Private));
+  assert_eq!(object.attachment.len(), 1);
+  assert_eq!(object.attachment[0].alt_text(), Some("A diagram"));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use apread::{
+  i18n::Messages,
+  render,
+  timeline::{self, Author},
+  Client,
+};
+use common::FakeInstance;
+
+#[tokio::test]
+async fn feeds_and_accounts_share_a_timeline() {
+  let feeds = FakeInstance::start("feeds").await;
+  let mastodon = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let authors = [
+    Author::feed(format!("{}/atom.xml", feeds.uri())),
+    Author {
+      nickname: Some("garden".to_owned()),
+      ..Author::feed(format!("{}/rss.xml", feeds.uri()))
+    },
+    Author::new(mastodon.handle("alice")),
+  ];
+  let mut entries = vec![];
+
+  for author in &authors {
+    entries.extend(timeline::fetch(&client, author).await.unwrap());
+  }
+
+  timeline::merge(&mut entries);
+
+  assert_eq!(
+    entries[0].post.url,
+    Some(format!("{}/garden/frost", feeds.uri()))
+  );
+  assert_eq!(entries[0].post.hashtags(), ["gardening"]);
+
+  let mut out = vec![];
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out)
+    .unwrap()
+    .replace(&mastodon.uri(), "{{base}}"));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use apread::{
+  fixtures::Recorder,
+  i18n::Messages,
+  render,
+  timeline::{self, Author},
+  ApreadErrors, Client,
+};
+use common::FakeInstance;
+
+#[tokio::test]
+async fn replays_a_recorded_run_without_the_network() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+
+  let recorded = Client::insecure()
+    .recording(Recorder::new(dir.path()).unwrap())
+    .first_page(&handle)
+    .await
+    .unwrap();
+
+  drop(instance);
+
+  let replayed = Client::insecure()
+    .replaying(dir.path())
+    .first_page(&handle)
+    .await
+    .unwrap();
+
+  let author = Author::new(handle);
+  let (mut expected, mut actual) = (vec![], vec![]);
+  render::terminal(
+    &mut expected,
+    &timeline::entries(&author, &recorded),
+    80,
+    false,
+    &Messages::default(),
+  )
+  .unwrap();
+  render::terminal(
+    &mut actual,
+    &timeline::entries(&author, &replayed),
+    80,
+    false,
+    &Messages::default(),
+  )
+  .unwrap();
+
+  assert_eq!(expected, actual);
+  assert!(dir.path().join("index.json").exists());
+}
+
+#[tokio::test]
+async fn replay_reports_urls_missing_from_the_recording() {
+  let dir = tempfile::tempdir().unwrap();
+  let instance = FakeInstance::start("mastodon").await;
+
+  let result = Client::insecure()
+    .replaying(dir.path())
+    .first_page(&instance.handle("alice"))
+    .await;
+
+  assert!(matches!(result, Err(ApreadErrors::NotRecorded(_))));
+}
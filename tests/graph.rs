@@ -0,0 +1,50 @@
+mod common;
+
+use apread::{graph, Client};
+use common::FakeInstance;
+
+async fn mastodon_graph(write: fn(&graph::Graph, &mut Vec<u8>)) -> String {
+  let instance = FakeInstance::start("mastodon").await;
+  let graph =
+    graph::build(&Client::insecure(), &instance.handle("alice"), 1, 1)
+      .await
+      .unwrap();
+
+  let mut out = vec![];
+  write(&graph, &mut out);
+
+  String::from_utf8(out)
+    .unwrap()
+    .replace(&instance.uri(), "{{base}}")
+}
+
+#[tokio::test]
+async fn mentions_and_boosts_are_graphed_as_dot() {
+  let dot = mastodon_graph(|graph, out| graph.dot(out).unwrap()).await;
+
+  assert_eq!(
+    dot,
+    r#"digraph apread {
+  "{{base}}/users/alice" [label="alice@127.0.0.1"];
+  "https://bob.example/users/bob" [label="bob@bob.example"];
+  "https://carol.example/users/carol" [label="https://carol.example/users/carol"];
+  "{{base}}/users/alice" -> "https://bob.example/users/bob" [label="mention", weight=1, style=solid];
+  "{{base}}/users/alice" -> "https://carol.example/users/carol" [label="boost", weight=1, style=dashed];
+}
+"#
+  );
+}
+
+#[tokio::test]
+async fn graphs_can_be_written_as_gexf() {
+  let gexf = mastodon_graph(|graph, out| graph.gexf(out).unwrap()).await;
+
+  assert!(gexf.starts_with("<?xml"));
+  assert!(gexf.contains(
+    r#"<node id="https://bob.example/users/bob" label="bob@bob.example"/>"#
+  ));
+  assert!(gexf.contains(
+    r#"<edge id="1" source="{{base}}/users/alice" target="https://carol.example/users/carol" weight="1">"#
+  ));
+  assert!(gexf.contains(r#"<attvalue for="kind" value="boost"/>"#));
+}
@@ -0,0 +1,33 @@
+use apread::Handle;
+
+#[test]
+fn handle_lists_skip_blanks_and_comments() {
+  let handles = Handle::parse_list(
+    "alice@example.social\n\n# old account\nbob@example.net\n  carol@example.org  \n",
+  )
+  .unwrap();
+  let handles: Vec<_> = handles.iter().map(Handle::to_string).collect();
+
+  assert_eq!(
+    handles,
+    [
+      "alice@example.social",
+      "bob@example.net",
+      "carol@example.org"
+    ]
+  );
+}
+
+#[test]
+fn handle_lists_reject_bad_handles() {
+  assert!(Handle::parse_list("alice@example.social\nnot-a-handle\n").is_err());
+}
+
+#[test]
+fn onion_services_are_recognized() {
+  let onion = |text| Handle::parse_string(text).unwrap().is_onion();
+
+  assert!(onion("alice@abcdefghijklmnop.onion"));
+  assert!(onion("alice@ABCDEFGHIJKLMNOP.ONION:8080"));
+  assert!(!onion("alice@onion.example"));
+}
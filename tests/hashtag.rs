@@ -0,0 +1,126 @@
+use apread::{
+  hashtag::Hashtag,
+  timeline::{self, Author, Source, Walk},
+  Client, Handle,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{method, path, query_param, query_param_is_missing},
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn status(id: &str, acct: &str, content: &str) -> serde_json::Value {
+  json!({
+    "id": id,
+    "uri": format!("https://example.social/statuses/{}", id),
+    "url": format!("https://example.social/@alice/{}", id),
+    "created_at": format!("2023-06-0{}T10:00:00.000Z", id),
+    "content": content,
+    "account": { "acct": acct, "url": "https://example.social/@alice" },
+    "media_attachments": [
+      { "type": "image", "url": "https://example.social/a.png", "description": null }
+    ],
+    "tags": [{ "name": "gardening", "url": "https://example.social/tags/gardening" }],
+  })
+}
+
+#[test]
+fn tags_are_read_with_or_without_their_hash() {
+  let tag = Hashtag::parse("#gardening@example.social").unwrap();
+
+  assert_eq!(tag.name, "gardening");
+  assert_eq!(tag.instance, "example.social");
+  assert_eq!(Hashtag::parse("gardening@example.social").unwrap(), tag);
+  assert_eq!(tag.to_string(), "#gardening@example.social");
+  assert!(Hashtag::parse("#gardening").is_err());
+  assert!(Hashtag::parse("#@example.social").is_err());
+  assert_eq!(
+    Source::parse("#gardening@example.social").unwrap(),
+    Source::Tag(tag)
+  );
+}
+
+#[test]
+fn timeline_urls_page_back_by_max_id() {
+  let tag = Hashtag::parse("#café@example.social").unwrap();
+
+  assert_eq!(
+    tag.timeline_url("https", None).unwrap(),
+    "https://example.social/api/v1/timelines/tag/caf%C3%A9?limit=40"
+  );
+  assert_eq!(
+    tag.timeline_url("https", Some("109")).unwrap(),
+    "https://example.social/api/v1/timelines/tag/caf%C3%A9?limit=40&max_id=109"
+  );
+}
+
+#[tokio::test]
+async fn tag_timelines_are_paged_and_keep_each_posts_author() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v1/timelines/tag/gardening"))
+    .and(query_param_is_missing("max_id"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+      status("3", "alice", "<p>first tomato</p>"),
+      status("2", "bob@elsewhere.example", "<p>slugs again</p>"),
+    ])))
+    .mount(&server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/api/v1/timelines/tag/gardening"))
+    .and(query_param("max_id", "2"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([status(
+      "1",
+      "alice",
+      "<p>seedlings up</p>"
+    )])))
+    .mount(&server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/api/v1/timelines/tag/gardening"))
+    .and(query_param("max_id", "1"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+    .mount(&server)
+    .await;
+
+  let tag = Hashtag::parse(&format!("#gardening@{}", instance)).unwrap();
+  let author = Author::from_source(Source::Tag(tag));
+  let walk = Walk {
+    pages: 5,
+    ..Walk::default()
+  };
+  let entries = timeline::walk(&Client::insecure(), &author, walk)
+    .await
+    .unwrap();
+
+  let contents: Vec<_> = entries
+    .iter()
+    .map(|entry| entry.post.content.as_str())
+    .collect();
+  assert_eq!(
+    contents,
+    [
+      "<p>first tomato</p>",
+      "<p>slugs again</p>",
+      "<p>seedlings up</p>"
+    ]
+  );
+  assert_eq!(
+    entries[0].author.source,
+    Source::Account(Handle {
+      id: "alice".to_owned(),
+      domain: instance,
+    })
+  );
+  assert_eq!(
+    entries[1].author.source,
+    Source::Account(Handle::parse_string("bob@elsewhere.example").unwrap())
+  );
+  assert_eq!(
+    entries[0].post.attachment[0].media_type.as_deref(),
+    Some("image/*")
+  );
+  assert_eq!(author.label(), "#gardening");
+}
@@ -0,0 +1,203 @@
+use std::io::Cursor;
+
+use apread::{
+  i18n::{self, Messages, FALLBACK, LANGUAGES},
+  init::{self, Setup},
+  locale::Locale,
+  render,
+  settings::Format,
+  ApreadErrors, NoSuchList, NoSuchPost, NotLoggedIn, WrongFormat,
+};
+
+#[test]
+fn messages_are_read_from_fluent() {
+  let messages = Messages::from_ftl(
+    "en",
+    r#"# A comment
+-brand = apread
+hello = Hello from { -brand }, { $name }!
+
+long =
+    One line,
+    then another
+braced = { "{" }literally{ "}" }
+with-title = Titled
+    .title = ignored
+unread = { $count ->
+    [one] One unread post
+   *[other] { $count } unread posts
+}
+"#,
+  );
+
+  assert_eq!(
+    messages.with("hello", &[("name", "Ada".into())]),
+    "Hello from apread, Ada!"
+  );
+  assert_eq!(messages.get("long"), "One line,\nthen another");
+  assert_eq!(messages.get("braced"), "{literally}");
+  assert_eq!(messages.get("with-title"), "Titled");
+  assert_eq!(
+    messages.with("unread", &[("count", 1.into())]),
+    "One unread post"
+  );
+  assert_eq!(
+    messages.with("unread", &[("count", 4.into())]),
+    "4 unread posts"
+  );
+  assert_eq!(messages.get("-brand"), "-brand", "terms aren't messages");
+}
+
+#[test]
+fn counts_take_the_languages_own_plural_forms() {
+  let polish = Messages::from_ftl(
+    "pl",
+    r#"posts = { $count ->
+    [one] { $number } wpis
+    [few] { $number } wpisy
+   *[many] { $number } wpisów
+}
+"#,
+  );
+  let posts = |count: usize| {
+    polish.with(
+      "posts",
+      &[
+        ("count", count.into()),
+        ("number", count.to_string().into()),
+      ],
+    )
+  };
+
+  assert_eq!(posts(1), "1 wpis");
+  assert_eq!(posts(3), "3 wpisy");
+  assert_eq!(posts(5), "5 wpisów");
+  assert_eq!(posts(22), "22 wpisy");
+  assert_eq!(polish.get("warning"), "warning", "the rest is English");
+}
+
+#[test]
+fn messages_fall_back_on_english() {
+  let german = Messages::new("de");
+
+  assert_eq!(german.language(), "de");
+  assert_eq!(german.get("warning"), "Warnung");
+  assert_eq!(
+    german.with("init-wrote", &[("path", "/tmp/config.toml".into())]),
+    "/tmp/config.toml geschrieben"
+  );
+  assert_eq!(german.get("no-such-message"), "no-such-message");
+
+  let klingon = Messages::new("tlh");
+
+  assert_eq!(klingon.language(), FALLBACK);
+  assert_eq!(klingon.get("warning"), "warning");
+  assert_eq!(
+    klingon.with("pick-one-of", &[]),
+    "Pick one of {$choices}",
+    "a variable that isn't given is left for someone to see"
+  );
+}
+
+#[test]
+fn every_translation_is_of_english_messages() {
+  let english = i18n::ids(
+    LANGUAGES
+      .iter()
+      .find(|(code, _)| *code == FALLBACK)
+      .unwrap()
+      .1,
+  );
+
+  for (code, ftl) in LANGUAGES {
+    for id in i18n::ids(ftl) {
+      assert!(
+        english.contains(&id),
+        "{} has {}, which English hasn't",
+        code,
+        id
+      );
+    }
+  }
+}
+
+#[test]
+fn languages_come_from_the_setting_or_the_environment() {
+  assert_eq!(i18n::language(Some("de_AT.UTF-8")), "de");
+  assert_eq!(i18n::language(Some("pt-BR")), "pt");
+  assert_eq!(i18n::language(Some("C")), "en");
+
+  std::env::set_var("LANGUAGE", ":fr_FR:de");
+  assert_eq!(i18n::language(None), "fr");
+
+  std::env::remove_var("LANGUAGE");
+  std::env::remove_var("LC_ALL");
+  std::env::remove_var("LC_MESSAGES");
+  std::env::set_var("LANG", "de_DE.UTF-8");
+  assert_eq!(i18n::language(None), "de");
+  assert_eq!(Messages::resolve(Some("en")).get("warning"), "warning");
+}
+
+#[test]
+fn setup_asks_in_the_readers_language() {
+  let german = Messages::new("de");
+  let mut input = Cursor::new("\n\n\n\nvielleicht\nj\n");
+  let mut output = vec![];
+  let setup = Setup::ask(&mut input, &mut output, &german).unwrap();
+  let output = String::from_utf8(output).unwrap();
+
+  assert!(setup.screen_reader);
+  assert!(output.starts_with("Konten zum Folgen"));
+  assert!(output.contains("[j/N]"));
+  assert!(output.contains("Bitte mit ja oder nein antworten"));
+
+  // English answers are understood whatever the language.
+  let mut input = Cursor::new("yes\n");
+
+  assert!(init::yes(&mut input, &mut vec![], &german, "?", false).unwrap());
+}
+
+#[test]
+fn errors_are_written_in_the_readers_language() {
+  let errors: Vec<ApreadErrors> = vec![
+    NoSuchPost(3).into(),
+    NoSuchList("friends".to_owned()).into(),
+    NotLoggedIn.into(),
+    WrongFormat {
+      what: "Timelines",
+      format: Format::Dot,
+    }
+    .into(),
+  ];
+  let english = Messages::new("en");
+  let german = Messages::new("de");
+
+  for error in &errors {
+    assert_eq!(error.message(&english), error.to_string());
+    assert_ne!(error.message(&german), error.to_string());
+  }
+
+  assert_eq!(
+    errors[0].message(&german),
+    "Es gibt keinen Beitrag 3 mit einer URL zum Öffnen"
+  );
+}
+
+#[test]
+fn timeline_headings_are_written_in_the_readers_language() {
+  let mut out = vec![];
+
+  render::footer(
+    &mut out,
+    2,
+    Some(6),
+    &Locale::default(),
+    &Messages::new("de"),
+  )
+  .unwrap();
+
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "\n2 gezeigt, insgesamt 6 Beiträge (mit --pages mehr sehen).\n"
+  );
+}
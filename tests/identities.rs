@@ -0,0 +1,62 @@
+mod common;
+
+use std::sync::Arc;
+
+use apread::{identities::Identities, storage::Memory, Client, Handle};
+use common::FakeInstance;
+
+fn client(identities: &Identities) -> Client {
+  Client::insecure().remembering_identities(identities.clone())
+}
+
+#[test]
+fn identities_are_remembered_both_ways_round() {
+  let identities = Identities::new(Arc::new(Memory::default()));
+  let handle = Handle::parse_string("alice@example.social").unwrap();
+
+  identities
+    .remember(&handle, "https://example.social/users/alice")
+    .unwrap();
+
+  assert_eq!(
+    identities.actor(&handle).as_deref(),
+    Some("https://example.social/users/alice")
+  );
+  assert_eq!(
+    identities.handle("https://example.social/users/alice"),
+    Some(handle)
+  );
+}
+
+#[tokio::test]
+async fn actor_urls_are_read_as_handles() {
+  let instance = FakeInstance::start("mastodon").await;
+  let identities = Identities::new(Arc::new(Memory::default()));
+  let url = format!("{}/users/alice", instance.uri());
+
+  for _ in 0..2 {
+    assert_eq!(
+      client(&identities).identify(&url).await.unwrap(),
+      instance.handle("alice")
+    );
+  }
+
+  assert_eq!(instance.requests().await, ["/users/alice"]);
+}
+
+#[tokio::test]
+async fn handles_resolved_before_survive_their_instance_moving() {
+  let instance = FakeInstance::start("mastodon").await;
+  let identities = Identities::new(Arc::new(Memory::default()));
+  // Nothing answers WebFinger on port 1, as if the old domain had gone.
+  let moved = Handle::parse_string("alice@127.0.0.1:1").unwrap();
+
+  assert!(client(&identities).resolve(&moved).await.is_err());
+
+  identities
+    .remember(&moved, &format!("{}/users/alice", instance.uri()))
+    .unwrap();
+  let actor = client(&identities).resolve(&moved).await.unwrap();
+
+  assert_eq!(actor.preferred_username.as_deref(), Some("alice"));
+}
@@ -0,0 +1,136 @@
+use std::{fs::File, io::Write, path::Path};
+
+use apread::{archive::Archive, export, import, ApreadErrors};
+use serde_json::json;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+/// Writes a Mastodon export, as a zip, with two posts, a boost and an
+/// image.
+fn mastodon_export(path: &Path) {
+  let mut zip = ZipWriter::new(File::create(path).unwrap());
+  let options = SimpleFileOptions::default();
+  let files = [
+    (
+      "actor.json",
+      json!({
+        "id": "https://example.social/users/alice",
+        "type": "Person",
+        "preferredUsername": "alice",
+        "outbox": "outbox.json",
+      }),
+    ),
+    (
+      "outbox.json",
+      json!({
+        "id": "outbox.json",
+        "type": "OrderedCollection",
+        "totalItems": 3,
+        "orderedItems": [
+          {
+            "type": "Create",
+            "object": {
+              "id": "https://example.social/users/alice/statuses/1",
+              "published": "2022-11-01T08:00:00Z",
+              "content": "<p>My first post</p>",
+              "attachment": [{
+                "type": "Document",
+                "url": "/media_attachments/files/000/001/original/tomato.png",
+              }],
+            },
+          },
+          {
+            "type": "Announce",
+            "object": "https://elsewhere.example/notes/1",
+          },
+          {
+            "type": "Create",
+            "object": {
+              "id": "https://example.social/users/alice/statuses/2",
+              "published": "2022-11-02T08:00:00Z",
+              "content": "<p>My second post <a href=\"https://example.social/tags/tomatoes\" rel=\"tag\">#tomatoes</a></p>",
+              "tag": [{ "type": "Hashtag", "name": "#tomatoes" }],
+            },
+          },
+        ],
+      }),
+    ),
+  ];
+
+  for (name, document) in files {
+    zip.start_file(name, options).unwrap();
+    zip.write_all(document.to_string().as_bytes()).unwrap();
+  }
+
+  zip
+    .start_file(
+      "media_attachments/files/000/001/original/tomato.png",
+      options,
+    )
+    .unwrap();
+  zip.write_all(b"not really a png").unwrap();
+  zip.start_file("../escaped.txt", options).unwrap();
+  zip.write_all(b"should stay in the zip").unwrap();
+  zip.finish().unwrap();
+}
+
+#[test]
+fn mastodon_exports_import_posts_and_media() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("archive.zip");
+  let media = dir.path().join("data").join("media");
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  mastodon_export(&path);
+
+  let imported = import::mastodon(&path, &archive, &media).unwrap();
+  let posts = archive.posts().unwrap();
+
+  assert_eq!(imported, import::Imported { posts: 2, media: 1 });
+  assert_eq!(posts.len(), 2);
+  assert_eq!(posts[0].author.as_deref(), Some("alice@example.social"));
+  assert_eq!(posts[0].hashtags, ["tomatoes"]);
+  assert_eq!(
+    std::fs::read_to_string(
+      media.join("media_attachments/files/000/001/original/tomato.png")
+    )
+    .unwrap(),
+    "not really a png"
+  );
+  assert!(!dir.path().join("data").join("escaped.txt").exists());
+}
+
+#[test]
+fn apread_exports_import_back() {
+  let dir = tempfile::tempdir().unwrap();
+  let zip = dir.path().join("archive.zip");
+  let from = Archive::open(dir.path().join("from.sqlite3")).unwrap();
+  let to = Archive::open(dir.path().join("to.sqlite3")).unwrap();
+  let media = dir.path().join("media");
+
+  mastodon_export(&zip);
+  import::mastodon(&zip, &from, &media).unwrap();
+  export::ap_archive(&dir.path().join("export"), &from.posts().unwrap(), &[])
+    .unwrap();
+
+  let imported =
+    import::mastodon(&dir.path().join("export"), &to, &media).unwrap();
+
+  assert_eq!(imported.posts, 2);
+  assert_eq!(to.posts().unwrap()[0].hashtags, ["tomatoes"]);
+}
+
+#[test]
+fn other_zips_are_not_exports() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("photos.zip");
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let mut zip = ZipWriter::new(File::create(&path).unwrap());
+
+  zip
+    .start_file("photo.jpg", SimpleFileOptions::default())
+    .unwrap();
+  zip.finish().unwrap();
+
+  let error = import::mastodon(&path, &archive, dir.path()).unwrap_err();
+  assert!(matches!(error, ApreadErrors::NotAnExport(_)), "{}", error);
+}
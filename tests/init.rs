@@ -0,0 +1,102 @@
+use std::{fs, io::Cursor};
+
+use apread::{
+  i18n::Messages,
+  init::Setup,
+  links,
+  settings::{Format, Overrides, Settings},
+  subscriptions::Subscriptions,
+  ApreadErrors,
+};
+
+#[test]
+fn setup_takes_defaults_and_asks_again_after_nonsense() {
+  let mut input = Cursor::new("\n\n\n\n\n");
+  let mut output = vec![];
+
+  assert_eq!(
+    Setup::ask(&mut input, &mut output, &Messages::default()).unwrap(),
+    Setup::default()
+  );
+
+  let mut input = Cursor::new(
+    "alice@example.social alice\n\
+     alice@example.social #rust@fosstodon.org\n\
+     yaml\nJSON\n\
+     wide\n0\n100\n\
+     footnotes\nmaybe\ny\n",
+  );
+  let mut output = vec![];
+  let setup =
+    Setup::ask(&mut input, &mut output, &Messages::default()).unwrap();
+  let output = String::from_utf8(output).unwrap();
+
+  assert_eq!(
+    setup,
+    Setup {
+      handles: vec![
+        "alice@example.social".to_owned(),
+        "#rust@fosstodon.org".to_owned()
+      ],
+      format: Format::Json,
+      width: 100,
+      links: links::Style::Footnotes,
+      screen_reader: true,
+    }
+  );
+  assert!(output.contains("alice isn't a handle apread can read"));
+  assert!(output.contains("Pick one of terminal, json,"));
+  assert_eq!(output.matches("The width is a number").count(), 2);
+  assert!(output.contains("Answer yes or no"));
+}
+
+#[test]
+fn setup_writes_files_apread_reads_back() {
+  let dir = tempfile::tempdir().unwrap();
+  let config = dir.path().join("apread").join("config.toml");
+  let subscriptions = dir.path().join("subscriptions.toml");
+  let setup = Setup {
+    handles: vec!["alice@example.social".to_owned()],
+    format: Format::Json,
+    width: 100,
+    links: links::Style::Footnotes,
+    screen_reader: true,
+  };
+
+  fs::write(
+    &subscriptions,
+    "[[account]]\nhandle = \"bob@example.social\"\n",
+  )
+  .unwrap();
+  setup.write(&config, &subscriptions, false).unwrap();
+
+  let settings = Settings::load(Some(&config), Overrides::default()).unwrap();
+
+  assert_eq!(settings.format, Format::Json);
+  assert_eq!(settings.width, 100);
+  assert_eq!(settings.links, links::Style::Footnotes);
+  assert!(settings.screen_reader);
+
+  let handles: Vec<String> = Subscriptions::load(&subscriptions)
+    .unwrap()
+    .accounts
+    .into_iter()
+    .filter_map(|account| account.handle)
+    .collect();
+
+  assert_eq!(handles, ["bob@example.social", "alice@example.social"]);
+
+  // A config file that's there already is only replaced when asked to.
+  assert!(matches!(
+    Setup::default().write(&config, &subscriptions, false),
+    Err(ApreadErrors::AlreadySetUp(_))
+  ));
+
+  Setup::default()
+    .write(&config, &subscriptions, true)
+    .unwrap();
+
+  let settings = Settings::load(Some(&config), Overrides::default()).unwrap();
+
+  assert_eq!(settings.format, Format::Terminal);
+}
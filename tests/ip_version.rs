@@ -0,0 +1,44 @@
+use apread::{
+  settings::{IpVersion, Settings},
+  Client,
+};
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+/// A page on an instance listening on 127.0.0.1 alone.
+async fn instance() -> (MockServer, String) {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+    .mount(&server)
+    .await;
+
+  let url = format!("{}/about", server.uri());
+  (server, url)
+}
+
+fn client(ip_version: IpVersion) -> Client {
+  Client::from_settings(&Settings {
+    ip_version: Some(ip_version),
+    connect_timeout: Some(5),
+    ..Settings::default()
+  })
+  .unwrap()
+}
+
+#[tokio::test]
+async fn ipv4_only_clients_reach_ipv4_addresses() {
+  let (_server, url) = instance().await;
+
+  assert_eq!(
+    client(IpVersion::Ipv4).web_page(&url).await.unwrap(),
+    "hello"
+  );
+}
+
+#[tokio::test]
+async fn ipv6_only_clients_skip_ipv4_addresses() {
+  let (_server, url) = instance().await;
+
+  assert!(client(IpVersion::Ipv6).web_page(&url).await.is_err());
+}
@@ -0,0 +1,80 @@
+use apread::{
+  last_run::{LastRun, Shown},
+  timeline::{Author, Entry},
+  Handle, NoSuchPost,
+};
+use serde_json::json;
+
+fn entry(id: &str, url: Option<&str>) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(json!({ "id": id, "url": url })).unwrap(),
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  }
+}
+
+#[test]
+fn posts_are_picked_out_by_the_number_they_were_shown_with() {
+  let run = LastRun::new(&[
+    entry(
+      "https://example.social/notes/1",
+      Some("https://example.social/@alice/1"),
+    ),
+    entry("https://example.social/notes/2", None),
+  ]);
+
+  assert_eq!(
+    run.post(1).unwrap().page(),
+    Some("https://example.social/@alice/1")
+  );
+  assert_eq!(
+    run.post(1).unwrap().source(),
+    Some("https://example.social/notes/1")
+  );
+  assert_eq!(
+    run.post(2).unwrap().page(),
+    Some("https://example.social/notes/2")
+  );
+  assert!(matches!(run.post(0), Err(NoSuchPost(0))));
+  assert!(matches!(run.post(3), Err(NoSuchPost(3))));
+}
+
+#[test]
+fn runs_are_kept_between_invocations() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("apread").join("last-run.json");
+  let run = LastRun::new(&[entry("https://example.social/notes/1", None)]);
+
+  run.save(&path).unwrap();
+
+  assert_eq!(LastRun::load(&path).unwrap(), run);
+}
+
+#[test]
+fn nothing_was_shown_before_the_first_run() {
+  let dir = tempfile::tempdir().unwrap();
+  let run = LastRun::load(&dir.path().join("last-run.json")).unwrap();
+
+  assert_eq!(run.posts, Vec::<Shown>::new());
+}
+
+#[test]
+fn runs_kept_with_only_ids_still_load() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("last-run.json");
+
+  std::fs::write(
+    &path,
+    r#"{"posts":[{"id":"https://example.social/notes/1","url":null}]}"#,
+  )
+  .unwrap();
+
+  let run = LastRun::load(&path).unwrap();
+
+  assert_eq!(
+    run.post(1).unwrap().source(),
+    Some("https://example.social/notes/1")
+  );
+  assert_eq!(run.post(1).unwrap().content, None);
+}
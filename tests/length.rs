@@ -0,0 +1,74 @@
+use apread::{
+  i18n::Messages,
+  length::{Length, LONG, WORDS_PER_MINUTE},
+  render,
+  timeline::{Author, Entry},
+  Handle,
+};
+use serde_json::json;
+
+fn words(count: usize) -> String {
+  format!("<p>{}</p>", vec!["word"; count].join(" "))
+}
+
+fn entry(content: &str) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(json!({ "content": content })).unwrap(),
+    published: "2024-01-01T00:00:00Z".to_owned(),
+    boosted: false,
+  }
+}
+
+#[test]
+fn length_counts_the_words_in_the_text_not_the_markup() {
+  let html =
+    r#"<p>Read <a href="https://example.com">my post</a> &mdash; it's new</p>"#;
+
+  assert_eq!(Length::of_html(html).words, 5);
+}
+
+#[test]
+fn reading_time_rounds_up_to_the_minute() {
+  assert_eq!(Length::of_html(&words(1)).minutes(), 1);
+  assert_eq!(Length::of_html(&words(WORDS_PER_MINUTE)).minutes(), 1);
+  assert_eq!(Length::of_html(&words(WORDS_PER_MINUTE + 1)).minutes(), 2);
+}
+
+#[test]
+fn only_posts_past_a_minute_get_badges() {
+  assert_eq!(Length::of_html(&words(40)).badge(), None);
+  assert_eq!(
+    Length::of_html(&words(WORDS_PER_MINUTE * 3 / 2))
+      .badge()
+      .as_deref(),
+    Some("2 min read")
+  );
+  assert_eq!(
+    Length::of_html(&words(LONG * 2)).badge().as_deref(),
+    Some("[long] 4 min read")
+  );
+}
+
+#[test]
+fn lengths_are_within_bounds_either_can_be_left_out() {
+  let length = Length::of_html(&words(100));
+
+  assert!(length.within(None, None));
+  assert!(length.within(Some(100), Some(100)));
+  assert!(!length.within(Some(101), None));
+  assert!(!length.within(None, Some(99)));
+}
+
+#[test]
+fn long_posts_are_badged_in_the_terminal() {
+  let entries = vec![entry(&words(LONG * 2)), entry("<p>Short</p>")];
+  let mut out = vec![];
+
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+
+  assert_eq!(out.matches("([long] 4 min read)").count(), 1, "{}", out);
+  assert_eq!(out.matches("min read").count(), 1, "{}", out);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use apread::{
+  i18n::Messages,
+  links::{self, Health},
+  locale::Locale,
+  render,
+  settings::{Overrides, Settings},
+  timeline::{Author, Entry},
+  Client, Handle,
+};
+use common::FakeInstance;
+
+fn entry(published: &str, content: &str) -> Entry {
+  let post = serde_json::json!({
+    "id": format!("https://alice.example/{}", published),
+    "published": published,
+    "content": content,
+  });
+
+  Entry {
+    author: Author::new(Handle::parse_string("alice@alice.example").unwrap()),
+    post: serde_json::from_value(post).unwrap(),
+    published: published.to_owned(),
+    boosted: false,
+  }
+}
+
+fn link(url: &str) -> String {
+  format!("<a href=\"{}\">{}</a>", url, url)
+}
+
+#[test]
+fn mentions_and_hashtags_are_not_links() {
+  let page = include_str!("fixtures/mastodon/page.json");
+  let page: serde_json::Value = serde_json::from_str(page).unwrap();
+  let content = page["orderedItems"][0]["object"]["content"]
+    .as_str()
+    .unwrap();
+
+  assert!(links::extract(content).is_empty());
+  assert_eq!(
+    links::extract(
+      "<a href=\"https://a.example/\">a</a> <a href=\"https://a.example/\">\
+       again</a> <a href=\"mailto:me@a.example\">mail</a>"
+    ),
+    ["https://a.example/"]
+  );
+}
+
+#[test]
+fn links_are_counted_once_per_post_and_dated_from_their_first() {
+  let often = "https://often.example/";
+  let once = "https://once.example/";
+  let entries = [
+    entry("2023-03-01T00:00:00Z", &link(often)),
+    entry(
+      "2023-02-01T00:00:00Z",
+      &format!("{} {}", link(once), link(often)),
+    ),
+    entry("2023-01-01T00:00:00+02:00", &(link(often) + &link(often))),
+  ];
+
+  let links = links::tally(&entries);
+
+  assert_eq!(links.len(), 2);
+  assert_eq!(links[0].url, often);
+  assert_eq!(links[0].count, 3);
+  assert_eq!(links[0].first_seen, "2023-01-01T00:00:00+02:00");
+  assert_eq!(links[1].url, once);
+  assert_eq!(links[1].count, 1);
+}
+
+#[tokio::test]
+async fn checked_links_report_the_dead_ones() {
+  let instance = FakeInstance::start("gotosocial").await;
+  let alive = format!("{}/users/alice", instance.uri());
+  let dead = format!("{}/gone", instance.uri());
+  let entries = [entry(
+    "2023-01-01T00:00:00Z",
+    &(link(&alive) + &link(&dead)),
+  )];
+
+  let mut links = links::tally(&entries);
+  links::check(&Client::insecure(), &mut links).await;
+
+  assert_eq!(links[0].health, Some(Health::Alive { code: 200 }));
+  assert_eq!(links[1].health, Some(Health::Dead { code: 404 }));
+
+  let mut out = vec![];
+  render::links(&mut out, &links, &Locale::default()).unwrap();
+  let out = String::from_utf8(out)
+    .unwrap()
+    .replace(&instance.uri(), "{{base}}");
+
+  assert_eq!(
+    out,
+    "    1  2023-01-01 00:00  {{base}}/users/alice\n    \
+     1  2023-01-01 00:00  {{base}}/gone  (dead: HTTP 404)\n\n\
+     Checked 2 links: 1 dead.\n"
+  );
+}
+
+#[test]
+fn links_become_footnotes() {
+  let html = r#"<p>Read <a href="https://docs.example/guide_one">the guide</a>, then <a href="https://example.social/tags/rust" class="mention hashtag" rel="tag">#<span>rust</span></a> <a href="https://docs.example/guide_one"><span class="invisible">https://</span>docs.example/guide_one</a> and <a href="https://blog.example/">https://blog.example/</a></p>"#;
+
+  assert_eq!(
+    links::footnoted(html),
+    r#"<p>Read the guide[1], then <a href="https://example.social/tags/rust" class="mention hashtag" rel="tag">#<span>rust</span></a> [1] and [2]</p><p>[1] https://docs.example/guide_one<br>[2] https://blog.example/</p>"#
+  );
+}
+
+#[test]
+fn posts_without_links_have_no_footnotes() {
+  let html = "<p>nothing to see</p>";
+
+  assert_eq!(links::footnoted(html), html);
+}
+
+#[test]
+fn footnoted_urls_are_written_to_be_copied() {
+  let post = serde_json::from_value(serde_json::json!({
+    "content": links::footnoted(
+      r#"<p><a href="https://docs.example/a_long_name">docs</a></p>"#
+    ),
+  }))
+  .unwrap();
+  let entry = Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post,
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  };
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 80, false, &Messages::default())
+    .unwrap();
+
+  assert!(String::from_utf8(out).unwrap().contains(
+    "     docs[1]\n     \n     [1] https://docs.example/a_long_name\n"
+  ));
+}
+
+#[test]
+fn footnotes_can_be_asked_for_in_the_config_file() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("config.toml");
+  std::fs::write(&path, "links = \"footnotes\"\n").unwrap();
+
+  let settings = Settings::load(Some(&path), Overrides::default()).unwrap();
+
+  assert_eq!(settings.links, links::Style::Footnotes);
+}
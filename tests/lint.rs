@@ -0,0 +1,137 @@
+mod common;
+
+use apread::{
+  client::Raw,
+  lint::{self, Level},
+  Client,
+};
+use common::FakeInstance;
+use serde_json::json;
+
+const URL: &str = "https://example.social/users/alice";
+
+fn raw(content_type: &str, document: serde_json::Value) -> Raw {
+  Raw {
+    status: 200,
+    content_type: Some(content_type.to_owned()),
+    body: document.to_string(),
+  }
+}
+
+fn messages(url: &str, response: &Raw) -> Vec<String> {
+  lint::lint(url, response)
+    .iter()
+    .map(ToString::to_string)
+    .collect()
+}
+
+#[tokio::test]
+async fn the_fake_instances_documents_are_clean() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+
+  for path in [
+    "/users/alice",
+    "/users/alice/outbox",
+    "/users/alice/collections/featured",
+  ] {
+    let url = format!("{}{}", instance.uri(), path);
+    let response = client.raw(&url).await.unwrap();
+    let errors: Vec<_> = lint::lint(&url, &response)
+      .into_iter()
+      .filter(|issue| issue.level == Level::Error)
+      .collect();
+
+    assert!(errors.is_empty(), "{}: {:?}", url, errors);
+  }
+}
+
+#[test]
+fn actors_need_a_context_and_boxes_on_their_own_server() {
+  let response = raw(
+    "application/json",
+    json!({
+      "id": URL,
+      "type": "Person",
+      "outbox": "https://elsewhere.example/users/alice/outbox",
+    }),
+  );
+
+  assert_eq!(
+    messages(URL, &response),
+    [
+      "error: served as application/json, not application/activity+json",
+      "error: there's no @context",
+      "error: the actor has no inbox",
+      "error: the actor's outbox: Bad url \
+       https://elsewhere.example/users/alice/outbox: on a different server \
+       from what links to it",
+      "warning: the actor has no preferredUsername, so no handle to be found \
+       by",
+    ]
+  );
+}
+
+#[test]
+fn collections_keep_items_where_their_type_says() {
+  let response = raw(
+    r#"application/ld+json; profile="https://www.w3.org/ns/activitystreams""#,
+    json!({
+      "@context": ["https://www.w3.org/ns/activitystreams", { "toot": "x" }],
+      "id": "https://example.social/users/alice/outbox?page=true",
+      "type": "OrderedCollectionPage",
+      "totalItems": "three",
+      "items": [],
+    }),
+  );
+
+  assert_eq!(
+    messages(
+      "https://example.social/users/alice/outbox?page=true",
+      &response
+    ),
+    [
+      "error: totalItems isn't a whole number",
+      "warning: an OrderedCollectionPage keeps its items in items, not \
+       orderedItems",
+      "warning: the page doesn't say which collection it's part of",
+    ]
+  );
+}
+
+#[test]
+fn posts_need_an_author_and_readable_dates() {
+  let response = raw(
+    "application/activity+json",
+    json!({
+      "@context": "https://www.w3.org/ns/activitystreams",
+      "id": "https://example.social/notes/1",
+      "type": "Note",
+      "published": "14 Feb 2023",
+    }),
+  );
+
+  assert_eq!(
+    messages("https://example.social/@alice/1", &response),
+    [
+      "error: the post has no attributedTo",
+      "error: published isn't an RFC 3339 date: 14 Feb 2023",
+      "warning: the id is https://example.social/notes/1, but it was fetched \
+       from https://example.social/@alice/1",
+    ]
+  );
+}
+
+#[test]
+fn failures_and_non_json_are_reported() {
+  let response = Raw {
+    status: 404,
+    content_type: Some("text/html".to_owned()),
+    body: "<h1>Not found</h1>".to_owned(),
+  };
+  let issues = lint::lint(URL, &response);
+
+  assert_eq!(issues.len(), 3);
+  assert!(issues.iter().all(|issue| issue.level == Level::Error));
+  assert_eq!(issues[0].message, "the server answered 404");
+}
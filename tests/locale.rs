@@ -0,0 +1,25 @@
+use apread::locale::Locale;
+use chrono::DateTime;
+
+#[test]
+fn dates_and_numbers_follow_the_locale() {
+  let date = DateTime::parse_from_rfc3339("2023-02-14T18:03:11Z").unwrap();
+
+  let german = Locale::new("de_DE.UTF-8");
+  assert_eq!(german.date(&date), "Di 14 Feb 2023 18:03:11 +00:00");
+  assert_eq!(german.number(4812), "4.812");
+
+  let american = Locale::new("en_US");
+  assert_eq!(american.date(&date), "Tue 14 Feb 2023 06:03:11 PM +00:00");
+  assert_eq!(american.number(4812), "4,812");
+}
+
+#[test]
+fn without_a_locale_dates_are_iso_like() {
+  let date = DateTime::parse_from_rfc3339("2023-02-14T18:03:11Z").unwrap();
+
+  for locale in [Locale::default(), Locale::new("C"), Locale::new("xx_YY")] {
+    assert_eq!(locale.date(&date), "2023-02-14 18:03");
+    assert_eq!(locale.number(4812), "4812");
+  }
+}
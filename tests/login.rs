@@ -0,0 +1,261 @@
+use apread::{
+  login::{self, App, Login, Logins},
+  timeline::{self, Author, Source, Walk},
+  ApreadErrors, Client, NoSuchAccount,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{
+    body_partial_json, header, method, path, query_param,
+    query_param_is_missing,
+  },
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn login(instance: &str) -> Login {
+  Login {
+    instance: instance.to_owned(),
+    account: "alice".to_owned(),
+    access_token: "sekrit".to_owned(),
+    write: false,
+  }
+}
+
+#[tokio::test]
+async fn logging_in_registers_an_app_and_trades_a_code_for_a_token() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("POST"))
+    .and(path("/api/v1/apps"))
+    .and(body_partial_json(
+      json!({ "client_name": "apread", "scopes": "read" }),
+    ))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "client_id": "id",
+      "client_secret": "secret",
+    })))
+    .mount(&server)
+    .await;
+  Mock::given(method("POST"))
+    .and(path("/oauth/token"))
+    .and(body_partial_json(
+      json!({ "code": "abc", "client_secret": "secret" }),
+    ))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_json(json!({ "access_token": "sekrit" })),
+    )
+    .mount(&server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/api/v1/accounts/verify_credentials"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "acct": "alice",
+      "url": "https://example.social/@alice",
+    })))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure();
+  let app = client.register_app(&instance, "read").await.unwrap();
+  let token = client
+    .access_token(&instance, &app, "abc", "read")
+    .await
+    .unwrap();
+  let account = client.verify_credentials(&instance, &token).await.unwrap();
+
+  assert_eq!(token, "sekrit");
+  assert_eq!(account.acct, "alice");
+}
+
+#[test]
+fn the_authorize_page_asks_for_a_code_to_paste() {
+  let app = App {
+    client_id: "id".to_owned(),
+    client_secret: "secret".to_owned(),
+  };
+
+  assert_eq!(
+    app.authorize_url("https", "example.social", login::READ_SCOPES),
+    "https://example.social/oauth/authorize?client_id=id&scope=read\
+     &redirect_uri=urn%3Aietf%3Awg%3Aoauth%3A2.0%3Aoob&response_type=code"
+  );
+}
+
+#[test]
+fn logins_are_kept_for_their_owner_only() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("apread").join("logins.json");
+  let mut logins = Logins::load(&path).unwrap();
+
+  assert_eq!(logins.get(None).unwrap(), None);
+
+  logins.add(login("example.social"), false);
+  logins.save().unwrap();
+
+  let logins = Logins::load(&path).unwrap();
+  assert_eq!(logins.get(None).unwrap(), Some(login("example.social")));
+  assert!(!logins.accounts()[0].in_keyring());
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+  }
+}
+
+#[test]
+fn a_token_missing_from_the_keyring_only_fails_what_needs_it() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("logins.json");
+
+  std::fs::write(
+    &path,
+    json!({
+      "default": "alice@apread.invalid",
+      "accounts": [{ "instance": "apread.invalid", "account": "alice" }],
+    })
+    .to_string(),
+  )
+  .unwrap();
+
+  let logins = Logins::load(&path).unwrap();
+  let client = Client::new().logging_in(logins.pending(None).unwrap().unwrap());
+
+  assert_eq!(client.login(), None);
+  assert!(matches!(
+    client.require_login(),
+    Err(ApreadErrors::NoToken(_))
+  ));
+}
+
+#[test]
+fn each_account_logged_in_to_is_kept_and_the_newest_is_the_default() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("logins.json");
+  let mut logins = Logins::load(&path).unwrap();
+  let other = Login {
+    account: "bob".to_owned(),
+    access_token: "hush".to_owned(),
+    write: true,
+    ..login("other.example")
+  };
+
+  logins.add(login("example.social"), false);
+  logins.add(other.clone(), false);
+  logins.add(login("example.social"), false);
+  logins.save().unwrap();
+
+  let mut logins = Logins::load(&path).unwrap();
+  let names: Vec<_> =
+    logins.accounts().iter().map(|saved| saved.name()).collect();
+  assert_eq!(names, ["bob@other.example", "alice@example.social"]);
+  assert_eq!(logins.default(), Some("alice@example.social"));
+  assert_eq!(
+    logins.get(Some("@Bob@other.example")).unwrap(),
+    Some(other.clone())
+  );
+  assert!(matches!(
+    logins.get(Some("carol@example.social")),
+    Err(ApreadErrors::NoSuchAccount(NoSuchAccount(_)))
+  ));
+
+  logins.set_default("bob@other.example").unwrap();
+  assert_eq!(logins.get(None).unwrap(), Some(other));
+
+  logins.remove("@bob@other.example").unwrap();
+  assert_eq!(logins.default(), Some("alice@example.social"));
+  assert_eq!(logins.accounts().len(), 1);
+}
+
+#[test]
+fn a_login_kept_before_there_could_be_several_is_still_read() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("logins.json");
+  let old = dir.path().join(login::OLD_FILE);
+
+  std::fs::write(
+    &old,
+    serde_json::to_string(&login("example.social")).unwrap(),
+  )
+  .unwrap();
+
+  let logins = Logins::load(&path).unwrap();
+  assert_eq!(logins.get(None).unwrap(), Some(login("example.social")));
+
+  logins.save().unwrap();
+  assert!(!old.exists());
+  assert_eq!(
+    Logins::load(&path).unwrap().get(None).unwrap(),
+    Some(login("example.social"))
+  );
+}
+
+#[tokio::test]
+async fn lists_are_read_by_name_as_the_logged_in_account() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v1/lists"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+      { "id": "7", "title": "Gardeners" },
+      { "id": "8", "title": "Cooks" },
+    ])))
+    .mount(&server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/api/v1/timelines/list/7"))
+    .and(query_param_is_missing("max_id"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+      "id": "100",
+      "uri": "https://example.social/statuses/100",
+      "created_at": "2023-06-01T10:00:00.000Z",
+      "content": "<p>first tomato</p>",
+      "account": { "acct": "bob@elsewhere.example" },
+    }])))
+    .mount(&server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/api/v1/timelines/list/7"))
+    .and(query_param("max_id", "100"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance));
+  let walk = Walk {
+    pages: 3,
+    ..Walk::default()
+  };
+  let author = Author::from_source(Source::List("gardeners".to_owned()));
+  let entries = timeline::walk(&client, &author, walk).await.unwrap();
+
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].post.content, "<p>first tomato</p>");
+  assert_eq!(
+    entries[0].author.source.to_string(),
+    "bob@elsewhere.example"
+  );
+
+  let missing = Author::from_source(Source::List("Knitters".to_owned()));
+  assert!(matches!(
+    timeline::walk(&client, &missing, walk).await,
+    Err(ApreadErrors::NoSuchList(_))
+  ));
+}
+
+#[tokio::test]
+async fn lists_need_a_login() {
+  let author = Author::from_source(Source::List("Gardeners".to_owned()));
+
+  assert!(matches!(
+    timeline::walk(&Client::insecure(), &author, Walk::default()).await,
+    Err(ApreadErrors::NotLoggedIn(_))
+  ));
+}
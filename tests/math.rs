@@ -0,0 +1,57 @@
+use apread::{activity::Post, math};
+use serde_json::json;
+
+fn post(content: &str) -> Post {
+  serde_json::from_value(json!({ "content": content })).unwrap()
+}
+
+const QUADRATIC: &str = r#"<p>Roots: <math><mi>x</mi><mo>=</mo><mfrac><mrow><mo>−</mo><mi>b</mi><mo>±</mo><msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>−</mo><mn>4</mn><mi>a</mi><mi>c</mi></msqrt></mrow><mrow><mn>2</mn><mi>a</mi></mrow></mfrac></math></p>"#;
+
+#[test]
+fn mathml_is_written_as_readable_text() {
+  assert_eq!(
+    post(QUADRATIC).markdown_content(),
+    "Roots: x = (− b ± √(b^2 − 4ac))/(2a)"
+  );
+}
+
+#[test]
+fn mathml_annotations_are_left_out() {
+  let content = r#"<math><semantics><msub><mi>x</mi><mi>i</mi></msub><annotation encoding="application/x-tex">x_i</annotation></semantics></math>"#;
+
+  assert_eq!(post(content).markdown_content(), "x\\_i");
+}
+
+#[test]
+fn verbatim_mathml_is_kept_as_it_is() {
+  let content = "<p><math><mi>x</mi></math></p>";
+
+  assert_eq!(
+    post(&math::verbatim(content)).markdown_content(),
+    "`<math><mi>x</mi></math>`"
+  );
+}
+
+#[test]
+fn verbatim_latex_is_not_escaped() {
+  let content = r"<p>Euler: $e^{i\pi} + 1 = 0$, or \(a_1 * b_1\) at scale</p><p>$$\sum_{n=1}^\infty 2^{-n} = 1$$</p>";
+
+  assert_eq!(
+    post(&math::verbatim(content)).markdown_content(),
+    "Euler: `$e^{i\\pi} + 1 = 0$`, or `\\(a_1 * b_1\\)` at scale\n\n`$$\\sum_{n=1}^\\infty 2^{-n} = 1$$`"
+  );
+}
+
+#[test]
+fn prices_are_not_latex() {
+  let content = "<p>It was $5 or $10, and $ 20 after that</p>";
+
+  assert_eq!(math::verbatim(content), content);
+}
+
+#[test]
+fn latex_in_code_is_left_alone() {
+  let content = "<p><code>echo $HOME$PATH</code></p>";
+
+  assert_eq!(math::verbatim(content), content);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "matrix")]
+
+mod common;
+
+use apread::{
+  matrix::{self, Message, Room},
+  timeline::{self, Author},
+  Client,
+};
+use common::FakeInstance;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+fn room(homeserver: &str) -> Room {
+  Room {
+    homeserver: format!("{}/", homeserver),
+    access_token: "syt_secret".to_owned(),
+    room: "!garden:matrix.example".to_owned(),
+  }
+}
+
+#[test]
+fn room_ids_are_escaped_in_the_send_url() {
+  assert_eq!(
+    room("https://matrix.example").send_url("abc"),
+    "https://matrix.example/_matrix/client/v3/rooms/%21garden%3Amatrix.example/send/m.room.message/abc"
+  );
+}
+
+#[tokio::test]
+async fn posts_are_relayed_as_html_messages() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let author = Author::new(instance.handle("alice"));
+  let entries = timeline::fetch(&client, &author).await.unwrap();
+  let entry = &entries[0];
+
+  let homeserver = MockServer::start().await;
+  let room = room(&homeserver.uri());
+  let transaction = matrix::transaction(&room, entry);
+  let message = Message::new(entry);
+
+  assert_eq!(transaction, matrix::transaction(&room, entry));
+  assert_ne!(transaction, matrix::transaction(&room, &entries[1]));
+  assert!(message.body.starts_with("alice:\n"));
+  assert!(message.formatted_body.contains(&entry.post.content));
+
+  Mock::given(matchers::method("PUT"))
+    .and(matchers::path(format!(
+      "/_matrix/client/v3/rooms/%21garden%3Amatrix.example/send/m.room.message/{}",
+      transaction
+    )))
+    .and(matchers::header("Authorization", "Bearer syt_secret"))
+    .and(matchers::body_json(&message))
+    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+      "event_id": "$relayed"
+    })))
+    .expect(1)
+    .mount(&homeserver)
+    .await;
+
+  client
+    .send_matrix(&room, &transaction, &message)
+    .await
+    .unwrap();
+
+  let refused = Room {
+    access_token: "wrong".to_owned(),
+    ..room
+  };
+  assert!(client
+    .send_matrix(&refused, &transaction, &message)
+    .await
+    .is_err());
+}
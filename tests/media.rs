@@ -0,0 +1,151 @@
+use apread::{
+  i18n::Messages,
+  media::MediaCache,
+  render,
+  timeline::{Author, Entry},
+  Client, Handle,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{method, path},
+  Mock, MockServer, ResponseTemplate,
+};
+
+async fn serve(server: &MockServer, at: &str, body: &[u8], times: u64) {
+  Mock::given(method("GET"))
+    .and(path(at))
+    .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+    .expect(times)
+    .mount(server)
+    .await;
+}
+
+#[tokio::test]
+async fn attachments_are_downloaded_once_and_kept_by_content() {
+  let server = MockServer::start().await;
+  let dir = tempfile::tempdir().unwrap();
+  let cache = MediaCache::new(dir.path());
+  let client = Client::insecure();
+
+  serve(&server, "/cat.PNG", b"a cat", 1).await;
+  serve(&server, "/mirror/cat.png", b"a cat", 1).await;
+  serve(&server, "/dog.png", b"a dog", 1).await;
+
+  let cat = format!("{}/cat.PNG", server.uri());
+  let first = cache.fetch(&client, &cat).await.unwrap();
+  let again = cache.fetch(&client, &cat).await.unwrap();
+  let mirrored = cache
+    .fetch(&client, &format!("{}/mirror/cat.png", server.uri()))
+    .await
+    .unwrap();
+  let dog = cache
+    .fetch(&client, &format!("{}/dog.png", server.uri()))
+    .await
+    .unwrap();
+
+  assert_eq!(first, again);
+  assert_eq!(first, mirrored);
+  assert_ne!(first, dog);
+  assert_eq!(first.extension().unwrap(), "png");
+  assert_eq!(std::fs::read(&first).unwrap(), b"a cat");
+  assert_eq!(cache.get(&cat), Some(first));
+}
+
+#[tokio::test]
+async fn saved_attachments_are_shown_where_they_were_kept() {
+  let server = MockServer::start().await;
+  let dir = tempfile::tempdir().unwrap();
+  let cache = MediaCache::new(dir.path());
+
+  serve(&server, "/cat.png", b"a cat", 1).await;
+
+  let mut entries = vec![Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(json!({
+      "content": "<p>Look</p>",
+      "attachment": [{
+        "type": "Image",
+        "mediaType": "image/png",
+        "url": format!("{}/cat.png", server.uri()),
+        "name": "A cat",
+      }],
+    }))
+    .unwrap(),
+    published: "2024-01-01T00:00:00Z".to_owned(),
+    boosted: false,
+  }];
+
+  cache.save(&Client::insecure(), &mut entries).await;
+
+  let saved = entries[0].post.attachment[0].saved.clone().unwrap();
+  let mut out = vec![];
+  render::terminal(&mut out, &entries, 200, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+
+  assert!(saved.starts_with(dir.path()));
+  assert!(
+    out.contains(&format!("saved as {}", saved.display())),
+    "{}",
+    out
+  );
+}
+
+#[test]
+fn the_cache_is_trimmed_oldest_first() {
+  let dir = tempfile::tempdir().unwrap();
+  let cache = MediaCache::new(dir.path());
+  let old = cache.put("https://example.social/old.png", b"old").unwrap();
+  let new = cache.put("https://example.social/new.png", b"new").unwrap();
+
+  std::fs::File::options()
+    .write(true)
+    .open(&old)
+    .unwrap()
+    .set_modified(std::time::SystemTime::UNIX_EPOCH)
+    .unwrap();
+
+  assert_eq!(cache.trim(6).unwrap(), 0);
+  assert_eq!(cache.trim(3).unwrap(), 1);
+  assert_eq!(cache.get("https://example.social/old.png"), None);
+  assert_eq!(cache.get("https://example.social/new.png"), Some(new));
+  assert_eq!(
+    std::fs::read_dir(dir.path().join("urls")).unwrap().count(),
+    1
+  );
+}
+
+#[cfg(feature = "thumbnails")]
+#[test]
+fn kept_images_are_drawn_as_thumbnails() {
+  let dir = tempfile::tempdir().unwrap();
+  let cache = MediaCache::new(dir.path());
+  let mut png = std::io::Cursor::new(vec![]);
+
+  image::RgbImage::from_pixel(80, 40, image::Rgb([255, 0, 0]))
+    .write_to(&mut png, image::ImageFormat::Png)
+    .unwrap();
+
+  let red = cache
+    .put("https://example.social/red.png", png.get_ref())
+    .unwrap();
+  let thumbnail = cache.thumbnail(&red).unwrap();
+
+  // 80 by 40 pixels shrinks to 40 by 20, two rows of pixels to a line.
+  assert_eq!(thumbnail.len(), 10);
+  assert_eq!(thumbnail[0].matches('▀').count(), 40);
+  assert!(thumbnail[0].starts_with("\x1b[38;2;255;0;0;48;2;255;0;0m▀"));
+  assert_eq!(
+    std::fs::read_dir(dir.path().join("thumbnails"))
+      .unwrap()
+      .count(),
+    1
+  );
+  assert_eq!(cache.thumbnail(&red), Some(thumbnail));
+
+  let text = cache
+    .put("https://example.social/notes.txt", b"not an image")
+    .unwrap();
+
+  assert_eq!(cache.thumbnail(&text), None);
+}
@@ -0,0 +1,80 @@
+use apread::{activity::Post, mention};
+use serde_json::json;
+
+fn post(content: &str, tags: serde_json::Value) -> Post {
+  serde_json::from_value(json!({ "content": content, "tag": tags })).unwrap()
+}
+
+const MASTODON: &str = r#"<p>Thanks <span class="h-card"><a href="https://bob.example/@bob" class="u-url mention">@<span>bob</span></a></span>!</p>"#;
+
+#[test]
+fn mentions_are_written_as_full_handles() {
+  let post = post(
+    MASTODON,
+    json!([{
+      "type": "Mention",
+      "href": "https://bob.example/users/bob",
+      "name": "@bob@bob.example"
+    }]),
+  );
+
+  assert_eq!(post.markdown_content(), "Thanks @bob@bob.example!");
+  assert_eq!(
+    mention::linked(&post),
+    r#"<p>Thanks <span class="h-card"><a href="https://bob.example/@bob" class="u-url mention">@bob@bob.example</a></span>!</p>"#
+  );
+}
+
+#[test]
+fn mentions_of_the_same_name_are_told_apart_by_server() {
+  let tags = json!([
+    { "type": "Mention", "name": "@bob@other.example" },
+    { "type": "Mention", "name": "@bob@bob.example" },
+  ]);
+  let post = post(MASTODON, tags);
+
+  assert_eq!(post.markdown_content(), "Thanks @bob@bob.example!");
+  assert_eq!(
+    mention::handle("https://other.example/@bob", "@bob", &post.tag).as_deref(),
+    Some("bob@other.example")
+  );
+}
+
+#[test]
+fn mentions_linked_to_the_actor_match_their_tag() {
+  let post = post(
+    r#"<a href="https://pleroma.example/users/bob" class="u-url mention">@bob</a>"#,
+    json!([{
+      "type": "Mention",
+      "href": "https://pleroma.example/users/bob",
+      "name": "bob@pleroma.example"
+    }]),
+  );
+
+  assert_eq!(post.markdown_content(), "@bob@pleroma.example");
+}
+
+#[test]
+fn untagged_mentions_take_the_server_they_link_to() {
+  let post = post(MASTODON, json!([]));
+
+  assert_eq!(post.markdown_content(), "Thanks @bob@bob.example!");
+}
+
+#[test]
+fn hashtags_and_other_links_are_left_alone() {
+  let content = r#"<p><a href="https://example.social/tags/garden" class="mention hashtag" rel="tag">#<span>garden</span></a> <a href="https://example.com/">a link</a></p>"#;
+  let post = post(content, json!([]));
+
+  assert_eq!(mention::linked(&post), content);
+}
+
+#[test]
+fn the_handles_a_post_mentions_are_listed_without_hashtags() {
+  let html = format!(
+    r##"{}<a href="https://bob.example/tags/tomatoes" class="mention hashtag">#tomatoes</a>"##,
+    MASTODON
+  );
+
+  assert_eq!(mention::handles(&html, &[]), ["bob@bob.example"]);
+}
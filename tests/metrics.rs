@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use apread::{
+  metrics::{self, Metrics},
+  stats::Stats,
+  timeline::{Author, Entry},
+  watch::Poll,
+  Handle, NothingToRead,
+};
+
+fn author(handle: &str) -> Author {
+  Author::new(Handle::parse_string(handle).unwrap())
+}
+
+fn poll() -> Poll {
+  Poll {
+    new: vec![Entry {
+      author: author("alice@example.social"),
+      post: serde_json::from_value(serde_json::json!({ "content": "hi" }))
+        .unwrap(),
+      published: "2023-02-14T18:03:11Z".to_owned(),
+      boosted: false,
+    }],
+    failed: vec![
+      (author("bob@down.example"), NothingToRead.into()),
+      (author("carol@down.example"), NothingToRead.into()),
+    ],
+  }
+}
+
+#[test]
+fn metrics_count_requests_posts_and_errors_by_instance() {
+  let stats = Stats::default();
+  let metrics = Metrics::new(stats.clone());
+
+  stats.request(Duration::from_millis(250));
+  stats.request(Duration::from_millis(250));
+  stats.cache_hit();
+  stats.cache_hit();
+  metrics.poll(&poll());
+  metrics.poll(&poll());
+
+  let out = metrics.render();
+
+  for line in [
+    "# TYPE apread_requests_total counter",
+    "apread_polls_total 2",
+    "apread_posts_found_total 2",
+    "apread_requests_total 2",
+    "# TYPE apread_request_duration_seconds histogram",
+    "apread_request_duration_seconds_bucket{le=\"0.1\"} 0",
+    "apread_request_duration_seconds_bucket{le=\"0.25\"} 2",
+    "apread_request_duration_seconds_bucket{le=\"+Inf\"} 2",
+    "apread_request_duration_seconds_sum 0.5",
+    "apread_request_duration_seconds_count 2",
+    "apread_cache_hits_total 2",
+    "apread_cache_hit_ratio 0.5",
+    "apread_errors_total{instance=\"down.example\"} 4",
+  ] {
+    assert!(out.lines().any(|got| got == line), "{}\n{}", line, out);
+  }
+}
+
+#[tokio::test]
+async fn metrics_are_served_over_http() {
+  let metrics = Metrics::default();
+
+  metrics.poll(&poll());
+
+  let address = metrics::serve("127.0.0.1:0".parse().unwrap(), metrics)
+    .await
+    .unwrap();
+  let response = reqwest::get(format!("http://{}/metrics", address))
+    .await
+    .unwrap();
+
+  assert_eq!(response.status(), 200);
+  assert!(response
+    .text()
+    .await
+    .unwrap()
+    .contains("apread_posts_found_total 1\n"));
+
+  let missing = reqwest::get(format!("http://{}/", address)).await.unwrap();
+
+  assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn watches_are_ready_once_they_have_polled() {
+  let metrics = Metrics::default();
+  let address = metrics::serve("127.0.0.1:0".parse().unwrap(), metrics.clone())
+    .await
+    .unwrap();
+  let status = |path: &'static str| async move {
+    reqwest::get(format!("http://{}{}", address, path))
+      .await
+      .unwrap()
+      .status()
+  };
+
+  assert_eq!(status("/healthz").await, 200);
+  assert_eq!(status("/readyz").await, 503);
+
+  metrics.poll(&poll());
+
+  assert_eq!(status("/healthz").await, 200);
+  assert_eq!(status("/readyz").await, 200);
+}
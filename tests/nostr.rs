@@ -0,0 +1,134 @@
+#![cfg(feature = "nostr")]
+
+use std::{fs, path::PathBuf};
+
+use apread::{
+  i18n::Messages,
+  locale::Locale,
+  nostr, render,
+  timeline::{self, Author, Source},
+  Client,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+const NPUB: &str =
+  "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+
+/// The author of the notes in `tests/fixtures/nostr/events.json`.
+const AUTHOR: &str =
+  "npub1szk3hxdtnjagkavyzm8t4revay97ttaptqfge2uhgz5aew75plmsst3c99";
+
+/// A relay which answers one request with the events in
+/// `tests/fixtures/nostr/events.json`.
+async fn fake_relay() -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let address = listener.local_addr().unwrap();
+  let events: Vec<Value> = serde_json::from_str(
+    &fs::read_to_string(
+      PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/nostr/events.json"),
+    )
+    .unwrap(),
+  )
+  .unwrap();
+
+  tokio::spawn(async move {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+    let Some(Ok(Message::Text(request))) = socket.next().await else {
+      return;
+    };
+    let request: Value = serde_json::from_str(&request).unwrap();
+    let subscription = &request[1];
+
+    socket
+      .send(Message::Text(json!(["NOTICE", "welcome"]).to_string()))
+      .await
+      .unwrap();
+
+    for event in events {
+      let message = json!(["EVENT", subscription, event]);
+
+      socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .unwrap();
+    }
+
+    let done = json!(["EOSE", subscription]);
+
+    socket.send(Message::Text(done.to_string())).await.unwrap();
+  });
+
+  format!("ws://{}", address)
+}
+
+#[test]
+fn npubs_decode_to_public_keys() {
+  assert_eq!(
+    nostr::public_key(NPUB).as_deref(),
+    Some("7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e")
+  );
+  assert_eq!(nostr::public_key("npub1notakey"), None);
+  assert!(Source::parse("npub1notakey").is_err());
+}
+
+#[tokio::test]
+async fn nostr_notes_read_like_any_other() {
+  let client = Client::insecure().relays(vec![fake_relay().await]);
+  let author = Author::from_source(Source::parse(AUTHOR).unwrap());
+
+  let entries = timeline::fetch(&client, &author).await.unwrap();
+
+  assert_eq!(entries.len(), 2, "only notes the author signed are read");
+  assert_eq!(entries[0].post.hashtags(), ["gardening"]);
+  assert!(entries[0]
+    .post
+    .url
+    .as_deref()
+    .unwrap()
+    .starts_with("https://njump.me/note1"));
+
+  let mut out = vec![];
+  render::grouped(
+    &mut out,
+    &entries,
+    80,
+    false,
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn signatures_are_checked_as_bip_340_has_them() {
+  let bytes = |hex: &str| -> Vec<u8> {
+    (0..hex.len())
+      .step_by(2)
+      .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+      .collect()
+  };
+  // BIP-340's own test vectors, from its test-vectors.csv.
+  let mut vectors =
+    csv::Reader::from_path("tests/fixtures/bip340.csv").unwrap();
+
+  for vector in vectors.records() {
+    let vector = vector.unwrap();
+    let valid =
+      nostr::verify(&bytes(&vector[2]), &bytes(&vector[4]), &bytes(&vector[5]));
+
+    assert_eq!(
+      valid,
+      &vector[6] == "TRUE",
+      "vector {}: {}",
+      &vector[0],
+      &vector[7]
+    );
+  }
+}
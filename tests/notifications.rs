@@ -0,0 +1,129 @@
+use apread::{
+  locale::Locale,
+  login::Login,
+  notifications::{self, Kind, LastRead, Notification},
+  render, Client,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{header, method, path, query_param},
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn login(instance: &str) -> Login {
+  Login {
+    instance: instance.to_owned(),
+    account: "alice".to_owned(),
+    access_token: "sekrit".to_owned(),
+    write: false,
+  }
+}
+
+fn answers() -> serde_json::Value {
+  json!([
+    {
+      "id": "12",
+      "type": "mention",
+      "created_at": "2023-06-02T10:00:00.000Z",
+      "account": { "acct": "bob@elsewhere.example" },
+      "status": {
+        "id": "300",
+        "uri": "https://elsewhere.example/notes/300",
+        "url": "https://elsewhere.example/@bob/300",
+        "created_at": "2023-06-02T10:00:00.000Z",
+        "content": "<p>@alice how are the tomatoes?</p>",
+        "account": { "acct": "bob@elsewhere.example" },
+      },
+    },
+    {
+      "id": "9",
+      "type": "follow",
+      "created_at": "2023-06-01T10:00:00.000Z",
+      "account": { "acct": "carol" },
+    },
+    {
+      "id": "8",
+      "type": "admin.sign_up",
+      "created_at": "2023-06-01T09:00:00.000Z",
+      "account": { "acct": "dave" },
+    },
+  ])
+}
+
+#[tokio::test]
+async fn notifications_are_read_as_the_logged_in_account() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v1/notifications"))
+    .and(query_param("types[]", "mention"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(answers()))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance));
+  let raw = client.notifications(true).await.unwrap();
+  let found: Vec<_> = raw
+    .into_iter()
+    .map(|raw| Notification::new(&instance, raw, Some("9")))
+    .collect();
+
+  assert_eq!(
+    found.iter().map(|found| found.kind).collect::<Vec<_>>(),
+    [Kind::Mention, Kind::Follow, Kind::Other]
+  );
+  assert_eq!(
+    found.iter().map(|found| found.unread).collect::<Vec<_>>(),
+    [true, false, false]
+  );
+  assert_eq!(found[1].account, format!("carol@{}", instance));
+  assert_eq!(
+    found[0].text.as_deref(),
+    Some("@alice how are the tomatoes?")
+  );
+
+  let mut out = vec![];
+  render::notifications(&mut out, &found[..2], 80, &Locale::default()).unwrap();
+
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    format!(
+      "@bob@elsewhere.example mentioned you, 2023-06-02 10:00  (new)\n     \
+       @alice how are the tomatoes?\n     \
+       https://elsewhere.example/@bob/300\n\n\
+       @carol@{} followed you, 2023-06-01 10:00\n\n",
+      instance
+    )
+  );
+}
+
+#[test]
+fn longer_ids_are_newer() {
+  assert!(notifications::newer("10", "9"));
+  assert!(notifications::newer("AbC2", "AbC1"));
+  assert!(!notifications::newer("9", "9"));
+  assert!(!notifications::newer("100", "1000"));
+}
+
+#[test]
+fn what_was_read_is_kept_per_account() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("notifications.json");
+  let alice = login("example.social");
+  let bob = Login {
+    account: "bob".to_owned(),
+    ..alice.clone()
+  };
+
+  assert_eq!(LastRead::load(&path, &alice).unwrap(), None);
+
+  LastRead::save(&path, &alice, "12").unwrap();
+
+  assert_eq!(
+    LastRead::load(&path, &alice).unwrap().as_deref(),
+    Some("12")
+  );
+  assert_eq!(LastRead::load(&path, &bob).unwrap(), None);
+}
@@ -0,0 +1,382 @@
+mod common;
+
+use apread::{
+  diagnostics::{Kind, Level},
+  i18n::Messages,
+  interrupt::Interrupt,
+  locale::Locale,
+  render,
+  stats::Stats,
+  timeline::{self, Author, Reading, Start, Walk},
+  Client, Handle,
+};
+use common::FakeInstance;
+use serde_json::json;
+use wiremock::{
+  matchers::any, Mock, MockServer, Request, Respond, ResponseTemplate,
+};
+
+fn contents(entries: &[timeline::Entry]) -> Vec<&str> {
+  entries
+    .iter()
+    .map(|entry| entry.post.content.as_str())
+    .collect()
+}
+
+#[tokio::test]
+async fn pages_are_walked_back_from_the_newest() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let walk = Walk {
+    pages: 2,
+    ..Walk::default()
+  };
+
+  let entries = timeline::walk(&Client::insecure(), &author, walk)
+    .await
+    .unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    [
+      "<p>put the beds to sleep for winter</p>",
+      "<p>more courgettes than anyone could want</p>",
+      "<p>the courgettes have opinions about slugs</p>",
+      "<p>planted out the courgettes</p>",
+    ]
+  );
+}
+
+#[tokio::test]
+async fn reading_from_the_oldest_walks_forward_to_the_end() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let walk = Walk {
+    start: Start::Oldest,
+    pages: 10,
+    ..Walk::default()
+  };
+
+  let mut entries = timeline::walk(&Client::insecure(), &author, walk)
+    .await
+    .unwrap();
+  timeline::chronological(&mut entries);
+
+  assert_eq!(entries.len(), 6);
+  assert_eq!(entries[0].post.content, "<p>new year, new allotment</p>");
+  assert_eq!(
+    entries[5].post.content,
+    "<p>put the beds to sleep for winter</p>"
+  );
+}
+
+#[tokio::test]
+async fn dates_near_the_start_are_walked_to_from_the_oldest_page() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let date = timeline::parse_date("2023-06-01").unwrap();
+
+  let entries = timeline::around(&Client::insecure(), &author, date, 1)
+    .await
+    .unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    [
+      "<p>the courgettes have opinions about slugs</p>",
+      "<p>planted out the courgettes</p>",
+    ]
+  );
+  assert!(!instance
+    .requests()
+    .await
+    .contains(&"/users/bob/outbox?max_id=5&page=true".to_owned()));
+}
+
+#[tokio::test]
+async fn recent_dates_are_found_on_the_newest_page() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let date = timeline::parse_date("2023-10-01T00:00:00Z").unwrap();
+
+  let entries = timeline::around(&Client::insecure(), &author, date, 1)
+    .await
+    .unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    [
+      "<p>put the beds to sleep for winter</p>",
+      "<p>more courgettes than anyone could want</p>",
+    ]
+  );
+  assert_eq!(
+    instance
+      .requests()
+      .await
+      .iter()
+      .filter(|path| path.starts_with("/users/bob/outbox?"))
+      .count(),
+    1
+  );
+}
+
+#[tokio::test]
+async fn walking_to_a_date_stops_at_a_page_linking_to_itself() {
+  let instance = FakeInstance::start("looped").await;
+  let author = Author::new(instance.handle("bob"));
+  let date = timeline::parse_date("2020-01-01").unwrap();
+
+  let entries = timeline::around(&Client::insecure(), &author, date, 5)
+    .await
+    .unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    ["<p>the last page goes round again</p>"]
+  );
+  assert!(
+    instance.requests().await.len() < timeline::AROUND_PAGES,
+    "the loop is only gone round once"
+  );
+}
+
+/// An outbox which goes on for ever, a day's post a page going back from
+/// the end of 2023, with no last page to jump to.
+struct Endless(String);
+
+impl Respond for Endless {
+  fn respond(&self, request: &Request) -> ResponseTemplate {
+    let base = &self.0;
+    let actor = format!("{}/users/bob", base);
+    let outbox = format!("{}/outbox", actor);
+    let page = |number: u32| format!("{}?page={}", outbox, number);
+    let body = match (request.url.path(), request.url.query()) {
+      ("/.well-known/webfinger", _) => json!({
+        "subject": "acct:bob@example.social",
+        "links": [{
+          "rel": "self",
+          "type": "application/activity+json",
+          "href": actor,
+        }],
+      }),
+      ("/users/bob", _) => json!({
+        "id": actor,
+        "type": "Person",
+        "outbox": outbox,
+      }),
+      ("/users/bob/outbox", None) => json!({
+        "id": outbox,
+        "type": "OrderedCollection",
+        "first": page(1),
+      }),
+      ("/users/bob/outbox", Some(query)) => {
+        let number: u32 = query.trim_start_matches("page=").parse().unwrap();
+        let published = chrono::NaiveDate::from_ymd_opt(2023, 12, 31)
+          .unwrap()
+          .checked_sub_days(chrono::Days::new(number.into()))
+          .unwrap()
+          .format("%Y-%m-%dT12:00:00Z")
+          .to_string();
+        let id = format!("{}/statuses/{}", actor, number);
+
+        json!({
+          "id": page(number),
+          "type": "OrderedCollectionPage",
+          "next": page(number + 1),
+          "orderedItems": [{
+            "id": format!("{}/activity", id),
+            "type": "Create",
+            "actor": actor,
+            "published": published,
+            "object": {
+              "id": id,
+              "type": "Note",
+              "published": published,
+              "attributedTo": actor,
+              "content": format!("<p>day {}</p>", number),
+            },
+          }],
+        })
+      }
+      _ => return ResponseTemplate::new(404),
+    };
+
+    ResponseTemplate::new(200).set_body_json(body)
+  }
+}
+
+#[tokio::test]
+async fn walking_to_a_date_past_the_cap_says_it_gave_up() {
+  let server = MockServer::start().await;
+  Mock::given(any())
+    .respond_with(Endless(server.uri()))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure();
+  let handle = Handle {
+    domain: server.address().to_string(),
+    id: "bob".to_owned(),
+  };
+  let author = Author::new(handle);
+  let date = timeline::parse_date("2020-01-01").unwrap();
+
+  let entries = timeline::around(&client, &author, date, 5).await.unwrap();
+  let pages = server
+    .received_requests()
+    .await
+    .unwrap()
+    .iter()
+    .filter(|request| request.url.query().is_some_and(|q| q.contains("page")))
+    .count();
+  let diagnostics = client.diagnostics().take();
+
+  assert_eq!(pages, timeline::AROUND_PAGES);
+  assert_eq!(
+    contents(&entries),
+    [
+      "<p>day 46</p>",
+      "<p>day 47</p>",
+      "<p>day 48</p>",
+      "<p>day 49</p>",
+      "<p>day 50</p>",
+    ]
+  );
+  assert!(diagnostics.iter().any(|diagnostic| {
+    diagnostic.kind == Kind::Around && diagnostic.level == Level::Warning
+  }));
+  assert!(diagnostics.iter().any(|diagnostic| {
+    diagnostic.kind == Kind::Around && diagnostic.level == Level::Note
+  }));
+}
+
+#[tokio::test]
+async fn an_interrupted_walk_keeps_the_pages_it_has() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let interrupt = Interrupt::default();
+  let client = Client::insecure().interruptible(interrupt.clone());
+  let walk = Walk {
+    pages: 3,
+    ..Walk::default()
+  };
+
+  interrupt.trigger();
+  let entries = timeline::walk(&client, &author, walk).await.unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    [
+      "<p>put the beds to sleep for winter</p>",
+      "<p>more courgettes than anyone could want</p>",
+    ]
+  );
+}
+
+#[tokio::test]
+async fn a_limited_walk_reads_only_the_pages_it_needs() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let walk = Walk {
+    pages: usize::MAX,
+    limit: Some(3),
+    ..Walk::default()
+  };
+
+  let entries = timeline::walk(&Client::insecure(), &author, walk)
+    .await
+    .unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    [
+      "<p>put the beds to sleep for winter</p>",
+      "<p>more courgettes than anyone could want</p>",
+      "<p>the courgettes have opinions about slugs</p>",
+    ]
+  );
+  assert!(!instance
+    .requests()
+    .await
+    .contains(&"/users/bob/outbox?max_id=3&page=true".to_owned()));
+}
+
+#[tokio::test]
+async fn a_walk_stops_at_a_page_linking_to_itself() {
+  let instance = FakeInstance::start("looped").await;
+  let author = Author::new(instance.handle("bob"));
+  let walk = Walk {
+    pages: usize::MAX,
+    limit: Some(5),
+    ..Walk::default()
+  };
+
+  let entries = timeline::walk(&Client::insecure(), &author, walk)
+    .await
+    .unwrap();
+
+  assert_eq!(
+    contents(&entries),
+    ["<p>the last page goes round again</p>"]
+  );
+}
+
+#[tokio::test]
+async fn the_footer_says_how_many_posts_there_are_all_told() {
+  let instance = FakeInstance::start("paged").await;
+  let authors = [Author::new(instance.handle("bob"))];
+  let reading = Reading::Walk(Walk::default());
+
+  let gathered = timeline::gather(&Client::insecure(), &authors, reading).await;
+  let mut out = vec![];
+  render::footer(
+    &mut out,
+    gathered.entries.len(),
+    gathered.total,
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap();
+
+  assert_eq!(gathered.total, Some(6));
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "\nShowing 2 of 6 posts (use --pages to see more).\n"
+  );
+}
+
+#[test]
+fn there_is_no_footer_when_everything_was_shown() {
+  let mut out = vec![];
+
+  render::footer(
+    &mut out,
+    6,
+    Some(6),
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap();
+  render::footer(&mut out, 6, None, &Locale::default(), &Messages::default())
+    .unwrap();
+
+  assert!(out.is_empty());
+}
+
+#[tokio::test]
+async fn every_request_is_counted() {
+  let instance = FakeInstance::start("paged").await;
+  let author = Author::new(instance.handle("bob"));
+  let stats = Stats::default();
+  let client = Client::insecure().counting(stats.clone());
+  let walk = Walk {
+    pages: 2,
+    ..Walk::default()
+  };
+
+  timeline::walk(&client, &author, walk).await.unwrap();
+
+  assert_eq!(stats.requests(), instance.requests().await.len());
+  assert_eq!(stats.cache_hits(), 0);
+}
@@ -0,0 +1,144 @@
+use apread::{
+  activity::{self, Post},
+  i18n::Messages,
+  locale::Locale,
+  player,
+  sink::{OutputSink, Terminal},
+  timeline::{Author, Entry},
+  Handle,
+};
+use serde_json::json;
+
+fn entry(post: serde_json::Value) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(post).unwrap(),
+    published: "2024-01-01T00:00:00Z".to_owned(),
+    boosted: false,
+  }
+}
+
+fn shown(entries: &[Entry]) -> String {
+  let mut terminal = Terminal {
+    width: 80,
+    colored: false,
+    locale: Locale::default(),
+    messages: Messages::default(),
+    layout: Default::default(),
+    screen_reader: false,
+    math: Default::default(),
+    links: Default::default(),
+    reveal: false,
+    total: None,
+  };
+  let mut out = vec![];
+
+  terminal.write(&mut out, entries).unwrap();
+  String::from_utf8(out).unwrap()
+}
+
+/// A PeerTube video, which links to its page and to each of its files.
+fn peertube() -> serde_json::Value {
+  json!({
+    "type": "Video",
+    "name": "Building a birdhouse",
+    "content": "<p>Start to finish</p>",
+    "duration": "PT12M34S",
+    "url": [
+      {
+        "type": "Link",
+        "mediaType": "application/x-mpegURL",
+        "href": "https://tube.example/playlist.m3u8",
+      },
+      {
+        "type": "Link",
+        "mediaType": "text/html",
+        "href": "https://tube.example/w/birdhouse",
+      },
+    ],
+  })
+}
+
+#[test]
+fn durations_parse_from_iso_8601() {
+  assert_eq!(activity::parse_duration("PT5M30S"), Some(330.0));
+  assert_eq!(activity::parse_duration("PT1H2M3.5S"), Some(3723.5));
+  assert_eq!(activity::parse_duration("P1DT1S"), Some(86_401.0));
+  assert_eq!(activity::parse_duration("PT"), Some(0.0));
+  assert_eq!(activity::parse_duration("5:30"), None);
+  assert_eq!(activity::parse_duration("PT5X"), None);
+}
+
+#[test]
+fn durations_read_like_a_clock() {
+  assert_eq!(activity::clock(5.0), "0:05");
+  assert_eq!(activity::clock(330.4), "5:30");
+  assert_eq!(activity::clock(3723.0), "1:02:03");
+}
+
+#[test]
+fn videos_are_shown_with_their_length_and_size() {
+  let video = entry(json!({
+    "content": "<p>Look at this</p>",
+    "attachment": [{
+      "type": "Document",
+      "mediaType": "video/mp4",
+      "url": "https://example.social/clip.mp4",
+      "name": "A cat",
+      "duration": "PT5M30S",
+      "width": 1920,
+      "height": 1080,
+    }],
+  }));
+
+  assert!(shown(&[video])
+    .contains("Video (5:30, 1920×1080): https://example.social/clip.mp4"));
+}
+
+#[test]
+fn peertube_videos_are_pages_to_play() {
+  let post: Post = serde_json::from_value(peertube()).unwrap();
+
+  assert!(post.is_media());
+  assert_eq!(post.duration, Some(754.0));
+  assert_eq!(
+    post.url.as_deref(),
+    Some("https://tube.example/w/birdhouse")
+  );
+  assert_eq!(post.playable(), Some("https://tube.example/w/birdhouse"));
+  assert!(shown(&[entry(peertube())]).contains("Video (12:34)"));
+}
+
+#[test]
+fn attached_media_plays_before_the_page() {
+  let post: Post = serde_json::from_value(json!({
+    "type": "Note",
+    "url": "https://example.social/@alice/1",
+    "attachment": [
+      {"mediaType": "image/png", "url": "https://example.social/a.png"},
+      {"mediaType": "audio/ogg", "url": "https://example.social/a.ogg"},
+    ],
+  }))
+  .unwrap();
+  let note: Post = serde_json::from_value(json!({
+    "type": "Note",
+    "url": "https://example.social/@alice/2",
+  }))
+  .unwrap();
+
+  assert_eq!(post.playable(), Some("https://example.social/a.ogg"));
+  assert_eq!(note.playable(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn players_are_given_the_url_after_their_flags() {
+  let dir = tempfile::tempdir().unwrap();
+  let played = dir.path().join("played");
+
+  player::play("touch -c", played.to_str().unwrap()).unwrap();
+  assert!(!played.exists());
+
+  player::play("touch", played.to_str().unwrap()).unwrap();
+  assert!(played.exists());
+}
@@ -0,0 +1,151 @@
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use apread::{
+  last_run::LastRun,
+  plugin::{self, Context},
+  settings::{Overrides, Settings},
+  timeline::{Author, Entry},
+  Handle,
+};
+use serde_json::{json, Value};
+
+fn install(dir: &Path, name: &str, script: &str, mode: u32) {
+  let path = dir.join(name);
+
+  fs::write(&path, script).unwrap();
+  fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn plugins_are_found_on_the_path() {
+  let first = tempfile::tempdir().unwrap();
+  let second = tempfile::tempdir().unwrap();
+  let path = std::env::join_paths([first.path(), second.path()]).unwrap();
+
+  install(first.path(), "apread-notes", "#!/bin/sh\n", 0o644);
+  install(second.path(), "apread-notes", "#!/bin/sh\n", 0o755);
+  install(
+    second.path(),
+    "apread-alice@example.social",
+    "#!/bin/sh\n",
+    0o755,
+  );
+
+  assert_eq!(
+    plugin::find("notes", &path),
+    Some(second.path().join("apread-notes")),
+    "a file which can't be run isn't a plugin"
+  );
+  assert_eq!(plugin::find("missing", &path), None);
+  assert_eq!(plugin::find("alice@example.social", &path), None);
+}
+
+#[test]
+fn plugins_are_given_the_config_and_timeline() {
+  let dir = tempfile::tempdir().unwrap();
+  let config = dir.path().join("config.toml");
+  let cache = dir.path().join("cache");
+  let dumped = dir.path().join("context.json");
+
+  install(
+    dir.path(),
+    "apread-dump",
+    &format!("#!/bin/sh\ncat > {}\nexit \"$1\"\n", dumped.display()),
+    0o755,
+  );
+  fs::write(
+    &config,
+    format!(
+      "width = 100\ncache_dir = {:?}\n",
+      cache.display().to_string()
+    ),
+  )
+  .unwrap();
+
+  let settings = Settings::load(Some(&config), Overrides::default()).unwrap();
+  let entry = Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(json!({
+      "id": "https://example.social/users/alice/statuses/1",
+      "updated": "2023-02-14T18:10:00Z",
+      "content": "<p>Hello!</p>",
+      "attachment": {
+        "type": "Document",
+        "mediaType": "image/png",
+        "url": "https://example.social/media/1.png",
+        "name": "A wave",
+      },
+    }))
+    .unwrap(),
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  };
+
+  LastRun::new(&[entry])
+    .save(&settings.last_run().unwrap())
+    .unwrap();
+
+  let context =
+    Context::new(&settings, Some(config), vec!["7".to_owned()]).unwrap();
+  let status = plugin::run(&dir.path().join("apread-dump"), &context).unwrap();
+
+  assert_eq!(status.code(), Some(7));
+
+  let given: Value =
+    serde_json::from_str(&fs::read_to_string(&dumped).unwrap()).unwrap();
+
+  assert_eq!(given["apread"], env!("CARGO_PKG_VERSION"));
+  assert_eq!(given["args"], serde_json::json!(["7"]));
+  assert_eq!(given["config"]["width"], 100);
+  assert_eq!(given["config"]["format"], "terminal");
+  assert_eq!(given["config"]["file"]["width"], 100);
+  assert_eq!(
+    given["timeline"][0],
+    json!({
+      "id": "https://example.social/users/alice/statuses/1",
+      "url": null,
+      "author": "alice@example.social",
+      "published": "2023-02-14T18:03:11Z",
+      "updated": "2023-02-14T18:10:00Z",
+      "content": "<p>Hello!</p>",
+      "attachments": [{
+        "url": "https://example.social/media/1.png",
+        "media_type": "image/png",
+        "alt": "A wave",
+      }],
+    })
+  );
+}
+
+#[test]
+fn plugins_arent_given_secrets() {
+  let dir = tempfile::tempdir().unwrap();
+  let config = dir.path().join("config.toml");
+
+  fs::write(
+    &config,
+    "width = 100\n\
+     \n\
+     [[webhooks]]\n\
+     url = \"https://hooks.example/apread\"\n\
+     secret = \"correct horse battery staple\"\n\
+     \n\
+     [matrix]\n\
+     homeserver = \"https://matrix.org\"\n\
+     access_token = \"syt_abc\"\n\
+     room = \"!abcdefg:matrix.org\"\n",
+  )
+  .unwrap();
+
+  let settings = Settings::load(Some(&config), Overrides::default()).unwrap();
+  let context = Context::new(&settings, Some(config), vec![]).unwrap();
+  let file = &context.config.file;
+
+  assert_eq!(file["width"], 100);
+  assert_eq!(file["webhooks"][0]["url"], "https://hooks.example/apread");
+  assert_eq!(file["webhooks"][0]["secret"], "[redacted]");
+  assert_eq!(file["matrix"]["access_token"], "[redacted]");
+  assert_eq!(file["matrix"]["room"], "!abcdefg:matrix.org");
+}
@@ -0,0 +1,291 @@
+use apread::{
+  archive::Archive,
+  profile::{self, Card, Change, Field, Pinned, Profile, Verification},
+  render, Client,
+};
+use serde_json::json;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+/// An account with a name, a bio and one post pinned, besides a post which
+/// is still up and one which has been deleted.
+async fn instance() -> MockServer {
+  let server = MockServer::start().await;
+  let base = server.uri();
+
+  Mock::given(matchers::path("/users/alice"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "id": format!("{}/users/alice", base),
+      "preferredUsername": "alice",
+      "name": "Alice 🌱",
+      "summary": "<p>Growing <b>tomatoes</b> now</p>",
+      "outbox": format!("{}/users/alice/outbox", base),
+      "featured": format!("{}/users/alice/featured", base),
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/users/alice/featured"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "type": "OrderedCollection",
+      "orderedItems": [{
+        "type": "Note",
+        "id": format!("{}/statuses/3", base),
+        "content": "<p>Read this first</p>",
+      }],
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/statuses/1"))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/statuses/2"))
+    .respond_with(ResponseTemplate::new(404))
+    .mount(&server)
+    .await;
+
+  server
+}
+
+fn pinned(server: &MockServer, id: u32, excerpt: &str) -> Pinned {
+  Pinned {
+    id: format!("{}/statuses/{}", server.uri(), id),
+    excerpt: excerpt.to_owned(),
+  }
+}
+
+#[tokio::test]
+async fn profiles_have_the_name_bio_and_pinned_posts() {
+  let server = instance().await;
+  let client = Client::insecure();
+  let actor = client
+    .actor(&format!("{}/users/alice", server.uri()))
+    .await
+    .unwrap();
+
+  assert_eq!(
+    Profile::fetch(&client, &actor).await.unwrap(),
+    Profile {
+      name: Some("Alice 🌱".to_owned()),
+      bio: Some("Growing tomatoes now".to_owned()),
+      pinned: vec![pinned(&server, 3, "Read this first")],
+    }
+  );
+}
+
+#[tokio::test]
+async fn changes_tell_unpinned_posts_from_deleted_ones() {
+  let server = instance().await;
+  let client = Client::insecure();
+  let before = Profile {
+    name: Some("Alice".to_owned()),
+    bio: Some("Growing tomatoes now".to_owned()),
+    pinned: vec![pinned(&server, 1, "Still here"), pinned(&server, 2, "Gone")],
+  };
+  let now = Profile {
+    name: Some("Alice 🌱".to_owned()),
+    pinned: vec![pinned(&server, 3, "Read this first")],
+    ..before.clone()
+  };
+
+  assert_eq!(
+    before.changes(&client, &now).await,
+    [
+      Change::Name {
+        before: Some("Alice".to_owned()),
+        after: Some("Alice 🌱".to_owned()),
+      },
+      Change::Unpinned(pinned(&server, 1, "Still here")),
+      Change::Deleted(pinned(&server, 2, "Gone")),
+      Change::Pinned(pinned(&server, 3, "Read this first")),
+    ]
+  );
+  assert!(now.changes(&client, &now).await.is_empty());
+}
+
+#[test]
+fn the_archive_keeps_the_latest_snapshot_of_each_profile() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let first = Profile {
+    name: Some("Alice".to_owned()),
+    ..Profile::default()
+  };
+  let second = Profile {
+    bio: Some("Hello".to_owned()),
+    ..first.clone()
+  };
+
+  assert_eq!(
+    archive.profile("https://example.social/alice").unwrap(),
+    None
+  );
+
+  archive
+    .store_profile("https://example.social/alice", &first)
+    .unwrap();
+  archive
+    .store_profile("https://example.social/alice", &second)
+    .unwrap();
+  archive
+    .store_profile("https://example.social/bob", &Profile::default())
+    .unwrap();
+
+  let snapshot = archive
+    .profile("https://example.social/alice")
+    .unwrap()
+    .unwrap();
+  assert_eq!(snapshot.profile, second);
+}
+
+/// An account with a field linking to a page which links back, one linking
+/// to a page which doesn't, one linking to a server which isn't there, and
+/// one which isn't a link.
+async fn fielded() -> MockServer {
+  let server = MockServer::start().await;
+  let base = server.uri();
+  let link = |path: &str| {
+    format!(
+      r#"<a href="{}{}" rel="me nofollow">{}</a>"#,
+      base, path, path
+    )
+  };
+
+  Mock::given(matchers::path("/users/alice"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "id": format!("{}/users/alice", base),
+      "url": format!("{}/@alice", base),
+      "preferredUsername": "alice",
+      "outbox": format!("{}/users/alice/outbox", base),
+      "attachment": [
+        {"type": "PropertyValue", "name": "Website", "value": link("/home")},
+        {"type": "PropertyValue", "name": "Blog", "value": link("/blog")},
+        {
+          "type": "PropertyValue",
+          "name": "Shop",
+          "value": r#"<a href="http://127.0.0.1:1/shop">shop</a>"#,
+        },
+        {"type": "PropertyValue", "name": "Pronouns", "value": "she/her"},
+        {"type": "IdentityProof", "name": "keybase"},
+      ],
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/home"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      r#"<html><head><link rel="me" href="{}/@alice/"></head></html>"#,
+      base
+    )))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/blog"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_string(format!(r#"<a href="{}/@alice">me</a>"#, base)),
+    )
+    .mount(&server)
+    .await;
+
+  server
+}
+
+#[tokio::test]
+async fn fields_are_verified_by_links_back() {
+  let server = fielded().await;
+  let client = Client::insecure();
+  let actor = client
+    .actor(&format!("{}/users/alice", server.uri()))
+    .await
+    .unwrap();
+  let card =
+    Card::fetch(&client, "alice@example.social".to_owned(), &actor).await;
+  let names: Vec<_> = card
+    .fields
+    .iter()
+    .map(|field| field.name.as_str())
+    .collect();
+
+  assert_eq!(names, ["Website", "Blog", "Shop", "Pronouns"]);
+  assert!(card.fields[0].verified);
+  assert!(!card.fields[1].verified);
+  assert!(!card.fields[2].verified);
+  assert_eq!(
+    card.fields[3],
+    Field {
+      name: "Pronouns".to_owned(),
+      value: "she/her".to_owned(),
+      link: None,
+      verified: false,
+    }
+  );
+}
+
+#[tokio::test]
+async fn link_checks_say_why_links_are_unverified() {
+  let server = fielded().await;
+  let client = Client::insecure();
+  let actor = client
+    .actor(&format!("{}/users/alice", server.uri()))
+    .await
+    .unwrap();
+  let checks = profile::link_checks(&client, &actor).await;
+  let verifications: Vec<_> = checks
+    .iter()
+    .map(|check| (check.name.as_str(), &check.verification))
+    .collect();
+
+  assert_eq!(verifications.len(), 3);
+  assert_eq!(verifications[0], ("Website", &Verification::Verified));
+  assert_eq!(verifications[1], ("Blog", &Verification::Unverified));
+  assert!(matches!(
+    verifications[2],
+    ("Shop", Verification::Unreachable { .. })
+  ));
+  assert_eq!(checks[0].link, format!("{}/home", server.uri()));
+
+  let mut out = vec![];
+  render::link_checks(&mut out, &checks).unwrap();
+  let out = String::from_utf8(out).unwrap();
+
+  assert!(out.contains("✓ Website"), "{}", out);
+  assert!(out.contains("✗ Blog"), "{}", out);
+  assert!(out.contains("no rel=\"me\" link back"), "{}", out);
+}
+
+#[test]
+fn fields_are_shown_as_a_table() {
+  let field = |name: &str, value: &str, verified| Field {
+    name: name.to_owned(),
+    value: value.to_owned(),
+    link: None,
+    verified,
+  };
+  let card = Card {
+    handle: "alice@example.social".to_owned(),
+    name: Some("Alice".to_owned()),
+    bio: Some("Growing tomatoes".to_owned()),
+    url: Some("https://example.social/@alice".to_owned()),
+    fields: vec![
+      field("Website", "alice.example", true),
+      field("Pronouns", "she/her", false),
+    ],
+  };
+  let mut out = vec![];
+
+  render::profile(&mut out, &card, 80).unwrap();
+
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "Alice  @alice@example.social\n\
+     https://example.social/@alice\n\
+     \n\
+     Growing tomatoes\n\
+     \n\
+     Website   alice.example  ✓ verified\n\
+     Pronouns  she/her\n"
+  );
+}
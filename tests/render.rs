@@ -0,0 +1,367 @@
+mod common;
+
+use apread::{
+  activity::Page,
+  i18n::Messages,
+  locale::Locale,
+  render,
+  timeline::{self, Author},
+  Client, Handle,
+};
+use common::FakeInstance;
+
+async fn render_timeline(software: &str) -> String {
+  let instance = FakeInstance::start(software).await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure()
+    .first_page(&handle)
+    .await
+    .expect("the fake instance should serve a timeline");
+
+  let mut out = vec![];
+  let entries = timeline::entries(&Author::new(handle), &page);
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+
+  // Put the placeholder back, so snapshots don't depend on the port.
+  String::from_utf8(out)
+    .unwrap()
+    .replace(&instance.uri(), "{{base}}")
+}
+
+#[tokio::test]
+async fn mastodon_timeline() {
+  insta::assert_snapshot!(render_timeline("mastodon").await);
+}
+
+#[tokio::test]
+async fn pleroma_timeline() {
+  insta::assert_snapshot!(render_timeline("pleroma").await);
+}
+
+#[tokio::test]
+async fn gotosocial_timeline() {
+  insta::assert_snapshot!(render_timeline("gotosocial").await);
+}
+
+#[tokio::test]
+async fn mastodon_timeline_in_columns() {
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+  let width = render::column_width(100, 2).unwrap();
+
+  let blocks: Vec<_> = render::blocks(
+    &entries,
+    false,
+    width,
+    false,
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap()
+  .into_iter()
+  .map(|block| block.replace(&instance.uri(), "{{base}}"))
+  .collect();
+
+  let mut out = vec![];
+  render::columns(&mut out, &blocks, width, 2).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn narrow_terminals_get_one_column() {
+  assert_eq!(render::column_width(100, 2), Some(48));
+  assert_eq!(render::column_width(80, 3), None);
+  assert_eq!(render::column_width(80, 0), None);
+}
+
+#[tokio::test]
+async fn gotosocial_timeline_as_json() {
+  let instance = FakeInstance::start("gotosocial").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+
+  let mut out = vec![];
+  render::json(&mut out, &entries).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out)
+    .unwrap()
+    .replace(&instance.uri(), "{{base}}")
+    .replace(&instance.handle("alice").to_string(), "alice@{{host}}"));
+}
+
+#[tokio::test]
+async fn mastodon_timeline_as_csv() {
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+
+  let mut out = vec![];
+  render::csv(&mut out, &entries).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out)
+    .unwrap()
+    .replace(&instance.uri(), "{{base}}")
+    .replace(&instance.handle("alice").to_string(), "alice@{{host}}"));
+}
+
+#[tokio::test]
+async fn mastodon_timeline_through_a_template() {
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+  let template = "{{ published[:10] }} {{ label }}: {{ text }}\
+    {% if tags %} [{{ tags | join(\", \") }}]{% endif %}\n";
+
+  let mut out = vec![];
+  render::template(&mut out, &entries, template).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn broken_templates_are_reported() {
+  let mut out = vec![];
+
+  assert!(render::template(&mut out, &[], "{{ unclosed").is_err());
+}
+
+#[tokio::test]
+async fn pleroma_timeline_for_a_screen_reader() {
+  let instance = FakeInstance::start("pleroma").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+
+  let mut out = vec![];
+  render::screen_reader(&mut out, &entries, &Locale::default()).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[tokio::test]
+async fn attachments_are_described_for_a_screen_reader() {
+  let instance = FakeInstance::start("gotosocial").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+
+  let mut out = vec![];
+  render::screen_reader(&mut out, &entries, &Locale::default()).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn emoji_are_spelled_out_for_a_screen_reader() {
+  let post = serde_json::from_str(
+    r#"{
+      "id": "https://example.social/notes/1",
+      "summary": "cold 🥶",
+      "content": "<p>frost again ❄️ and the leeks 👍🏽 can take it</p>"
+    }"#,
+  )
+  .unwrap();
+  let entry = timeline::Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post,
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  };
+
+  let mut out = vec![];
+  render::screen_reader(&mut out, &[entry], &Locale::default()).unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+fn entry_with(content: &str) -> timeline::Entry {
+  let post = serde_json::from_value(serde_json::json!({
+    "id": "https://example.social/notes/2",
+    "content": content,
+  }))
+  .unwrap();
+
+  timeline::Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post,
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  }
+}
+
+fn code_entry() -> timeline::Entry {
+  entry_with(
+    "<p>The fix, for anyone else who hits this:</p><pre><code class=\"language-rust\">fn main() {\n    let fixed = registers.iter().filter(|register| register.is_free()).count();\n}\n</code></pre><p>Which took far longer to find than it should have done, honestly.</p>",
+  )
+}
+
+#[test]
+fn code_blocks_are_not_wrapped() {
+  let mut out = vec![];
+  render::terminal(&mut out, &[code_entry()], 40, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[cfg(feature = "highlight")]
+#[test]
+fn code_blocks_are_colored_by_language() {
+  let mut out = vec![];
+  render::terminal(&mut out, &[code_entry()], 40, true, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+  let line = out.lines().find(|line| line.contains("registers")).unwrap();
+
+  assert!(line.contains("\x1b[38;2;"));
+  assert!(out.contains("     ```\n"));
+}
+
+// As Mastodon serves posts written in markdown on other servers, quotes
+// and all.
+#[test]
+fn quotes_keep_their_gutter_when_wrapped() {
+  let entry = entry_with(
+    "<p>From the seed catalogue:</p><blockquote><p>Sow under glass in early spring, then plant out once all danger of frost has passed.</p><p>Hardy to -5°C.</p><blockquote><p>Not in our garden it isn&#39;t.</p></blockquote></blockquote><p>We&#39;ll see.</p>",
+  );
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 50, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn nested_lists_keep_their_shape() {
+  let entry = entry_with(
+    "<p>Jobs for the weekend:</p><ul><li>Beds<ul><li>Dig over the bed by the shed, which has gone to weeds again</li><li>Mulch</li></ul></li><li>Greenhouse</li></ul><ol><li>Order seeds</li><li>Sow<ol><li>Tomatoes</li><li>Chillies, which want far more heat than anything else</li></ol></li></ol>",
+  );
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 50, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn urls_overflow_rather_than_break() {
+  let entry = entry_with(
+    "<p>Notes from the allotment society meeting: <a href=\"https://allotments.example/minutes/2023-02-14_annual-general-meeting.html\"><span class=\"invisible\">https://</span><span class=\"ellipsis\">allotments.example/minutes/202</span><span class=\"invisible\">3-02-14_annual-general-meeting.html</span></a></p>",
+  );
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 40, false, &Messages::default())
+    .unwrap();
+
+  insta::assert_snapshot!(String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn cjk_text_wraps_by_how_wide_it_is() {
+  let mut entry = entry_with(
+    "<p>今日は畑でトマトとナスを植えました。霜が降りないといいのですが、天気予報によると来週は寒くなるそうです。</p><p>한국어 문장도 공백에서 줄바꿈됩니다 🍅🍆</p>",
+  );
+  entry.author.nickname = Some("畑の人".to_owned());
+
+  let mut out = vec![];
+  render::terminal(&mut out, &[entry], 30, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+
+  for line in out.lines().skip(2) {
+    assert!(textwrap::core::display_width(line) <= 5 + 30, "{}", line);
+  }
+
+  insta::assert_snapshot!(out);
+}
+
+#[test]
+fn columns_stay_lined_up_around_wide_text() {
+  let blocks = [
+    "トマトとナスを植えました。霜が降りないといいのですが。\nshort".to_owned(),
+    "right".to_owned(),
+  ];
+
+  let mut out = vec![];
+  render::columns(&mut out, &blocks, 20, 2).unwrap();
+
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "トマトとナスを植え…    right\nshort\n"
+  );
+}
+
+#[test]
+fn posts_are_numbered_across_authors() {
+  let mut bob = entry_with("<p>Second</p>");
+  bob.author = Author::new(Handle::parse_string("bob@example.social").unwrap());
+  let entries = [entry_with("<p>First</p>"), bob, entry_with("<p>Third</p>")];
+
+  let mut out = vec![];
+  render::grouped(
+    &mut out,
+    &entries,
+    40,
+    false,
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap();
+  let out = String::from_utf8(out).unwrap();
+  let numbers: Vec<_> = out
+    .lines()
+    .filter_map(|line| line.strip_prefix('[')?.split_once(']'))
+    .map(|(number, _)| number)
+    .collect();
+
+  assert_eq!(numbers, ["1", "2", "3"]);
+}
+
+#[test]
+fn edits_replace_the_post_and_are_marked() {
+  let page: Page = serde_json::from_value(serde_json::json!({
+    "orderedItems": [
+      {
+        "type": "Update",
+        "published": "2023-02-14T19:00:00Z",
+        "object": {
+          "id": "https://example.social/notes/1",
+          "published": "2023-02-14T18:03:11Z",
+          "updated": "2023-02-14T19:00:00Z",
+          "content": "<p>Tomatoes, not potatoes</p>",
+        },
+      },
+      {
+        "type": "Create",
+        "published": "2023-02-14T18:03:11Z",
+        "object": {
+          "id": "https://example.social/notes/1",
+          "published": "2023-02-14T18:03:11Z",
+          "content": "<p>Potatoes</p>",
+        },
+      },
+    ],
+  }))
+  .unwrap();
+  let author =
+    Author::new(Handle::parse_string("alice@example.social").unwrap());
+  let entries = timeline::entries(&author, &page);
+
+  let mut out = vec![];
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out).unwrap();
+
+  assert_eq!(entries.len(), 1);
+  assert!(out.contains("(edited)"), "{}", out);
+  assert!(out.contains("Tomatoes, not potatoes"), "{}", out);
+}
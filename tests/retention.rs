@@ -0,0 +1,162 @@
+use apread::{
+  activity::Post,
+  archive::Archive,
+  retention::{self, Retention},
+  ApreadErrors,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+fn post(id: &str, published: &str) -> Post {
+  serde_json::from_value(json!({
+    "id": id,
+    "published": published,
+    "content": format!("<p>{} #garden</p>", "words ".repeat(200)),
+    "tag": [{ "type": "Hashtag", "name": "#garden" }],
+  }))
+  .unwrap()
+}
+
+fn now() -> DateTime<Utc> {
+  DateTime::parse_from_rfc3339("2024-06-01T00:00:00Z")
+    .unwrap()
+    .into()
+}
+
+fn ids(archive: &Archive) -> Vec<String> {
+  let mut ids: Vec<_> = archive
+    .posts()
+    .unwrap()
+    .into_iter()
+    .map(|post| post.id)
+    .collect();
+  ids.sort();
+  ids
+}
+
+#[test]
+fn posts_past_the_max_age_are_pruned_but_bookmarks_kept() {
+  let dir = tempfile::tempdir().unwrap();
+  let mut archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  archive
+    .store(&post("old", "2023-01-01T00:00:00Z"), Some("a"))
+    .unwrap();
+  archive
+    .store(&post("new", "2024-05-30T00:00:00Z"), Some("a"))
+    .unwrap();
+  archive
+    .bookmark(&post("saved", "2022-01-01T00:00:00Z"), Some("a"), &[], None)
+    .unwrap();
+
+  let retention = Retention {
+    max_age: Some(30),
+    ..Retention::default()
+  };
+  let pruned =
+    retention::prune(&archive, &dir.path().join("media"), &retention, now())
+      .unwrap();
+
+  assert_eq!(pruned.posts, 1);
+  assert_eq!(ids(&archive), vec!["new", "saved"]);
+  assert_eq!(archive.hashtags_by_author().unwrap().len(), 2);
+}
+
+#[test]
+fn each_account_keeps_only_its_newest_posts() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  for (id, day, author) in [
+    ("a1", "01", "alice"),
+    ("a2", "02", "alice"),
+    ("a3", "03", "alice"),
+    ("b1", "01", "bob"),
+  ] {
+    let published = format!("2024-05-{}T00:00:00Z", day);
+    archive.store(&post(id, &published), Some(author)).unwrap();
+  }
+
+  let retention = Retention {
+    max_posts: Some(2),
+    ..Retention::default()
+  };
+  let pruned =
+    retention::prune(&archive, &dir.path().join("media"), &retention, now())
+      .unwrap();
+
+  assert_eq!(pruned.posts, 1);
+  assert_eq!(ids(&archive), vec!["a2", "a3", "b1"]);
+}
+
+#[test]
+fn an_archive_too_big_loses_its_oldest_media_first() {
+  let dir = tempfile::tempdir().unwrap();
+  let media = dir.path().join("media");
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  std::fs::create_dir_all(media.join("photos")).unwrap();
+  std::fs::write(media.join("photos/cat.png"), vec![0; 2_000_000]).unwrap();
+
+  for day in 1..=28 {
+    let published = format!("2024-05-{:02}T00:00:00Z", day);
+    archive
+      .store(&post(&day.to_string(), &published), Some("alice"))
+      .unwrap();
+  }
+
+  let retention = Retention {
+    max_size: Some(1),
+    ..Retention::default()
+  };
+  let pruned = retention::prune(&archive, &media, &retention, now()).unwrap();
+
+  assert_eq!(pruned.media, 1);
+  assert!(!media.join("photos/cat.png").exists());
+  assert!(pruned.bytes >= 2_000_000, "{:?}", pruned);
+  assert_eq!(pruned.posts, 0);
+}
+
+#[test]
+fn an_archive_still_too_big_loses_its_oldest_posts() {
+  let dir = tempfile::tempdir().unwrap();
+  let mut archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  for day in 1..=28 {
+    let published = format!("2024-05-{:02}T00:00:00Z", day);
+    archive
+      .store(&post(&day.to_string(), &published), Some("alice"))
+      .unwrap();
+  }
+  archive
+    .bookmark(&post("saved", "2022-01-01T00:00:00Z"), Some("a"), &[], None)
+    .unwrap();
+
+  // No archive is ever this small, so everything that can go does.
+  let retention = Retention {
+    max_size: Some(0),
+    ..Retention::default()
+  };
+  let pruned =
+    retention::prune(&archive, &dir.path().join("media"), &retention, now())
+      .unwrap();
+
+  assert_eq!(pruned.posts, 28);
+  assert_eq!(ids(&archive), vec!["saved"]);
+}
+
+#[test]
+fn pruning_needs_a_policy() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  assert!(matches!(
+    retention::prune(
+      &archive,
+      &dir.path().join("media"),
+      &Retention::default(),
+      now()
+    ),
+    Err(ApreadErrors::NoRetention(_))
+  ));
+}
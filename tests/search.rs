@@ -0,0 +1,115 @@
+use apread::{
+  login::Login,
+  search::{self, SavedSearch, Search},
+  timeline::{self, Author, Source, Walk},
+  Client,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use wiremock::{
+  matchers::{header, method, path, query_param},
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn login(instance: &str) -> Login {
+  Login {
+    instance: instance.to_owned(),
+    account: "alice".to_owned(),
+    access_token: "sekrit".to_owned(),
+    write: false,
+  }
+}
+
+fn status(id: &str, created_at: &str) -> serde_json::Value {
+  json!({
+    "id": id,
+    "uri": format!("https://example.social/statuses/{}", id),
+    "created_at": created_at,
+    "content": format!("<p>rust release {}</p>", id),
+    "account": { "acct": "bob@elsewhere.example" },
+  })
+}
+
+#[test]
+fn saved_searches_write_their_filters_as_operators() {
+  let saved: SavedSearch = serde_json::from_value(json!({
+    "query": "rust release",
+    "from": "@rust@social.rust-lang.org",
+    "language": "en",
+    "media": true,
+  }))
+  .unwrap();
+
+  assert_eq!(
+    saved.text(),
+    "rust release from:rust@social.rust-lang.org language:en has:media"
+  );
+
+  let bare: SavedSearch =
+    serde_json::from_value(json!({ "query": "rust" })).unwrap();
+  assert_eq!(bare.text(), "rust");
+}
+
+#[tokio::test]
+async fn searches_only_show_what_was_published_since() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v2/search"))
+    .and(query_param("q", "rust release"))
+    .and(query_param("type", "statuses"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "statuses": [
+        status("2", "2024-03-02T00:00:00.000Z"),
+        status("1", "2024-03-01T00:00:00.000Z"),
+      ],
+    })))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance));
+  let search = |since: Option<&str>| {
+    Author::from_source(Source::Search(Search {
+      query: "rust release".to_owned(),
+      since: since.map(|at| DateTime::parse_from_rfc3339(at).unwrap()),
+    }))
+  };
+
+  let all = timeline::walk(&client, &search(None), Walk::default())
+    .await
+    .unwrap();
+  assert_eq!(all.len(), 2);
+
+  let since = search(Some("2024-03-01T12:00:00Z"));
+  let new = timeline::walk(&client, &since, Walk::default())
+    .await
+    .unwrap();
+  assert_eq!(new.len(), 1);
+  assert_eq!(new[0].post.content, "<p>rust release 2</p>");
+}
+
+#[test]
+fn saved_searches_remember_when_they_last_ran() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join(search::FILE);
+  let at = |date: &str| -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(date).unwrap().into()
+  };
+
+  assert_eq!(search::last_run(&path, "rust").unwrap(), None);
+
+  search::ran(&path, "rust", at("2024-03-01T00:00:00Z")).unwrap();
+  search::ran(&path, "gardening", at("2024-03-02T00:00:00Z")).unwrap();
+  search::ran(&path, "rust", at("2024-03-03T00:00:00Z")).unwrap();
+
+  assert_eq!(
+    search::last_run(&path, "rust").unwrap(),
+    Some(at("2024-03-03T00:00:00Z").fixed_offset())
+  );
+  assert_eq!(
+    search::last_run(&path, "gardening").unwrap(),
+    Some(at("2024-03-02T00:00:00Z").fixed_offset())
+  );
+}
@@ -0,0 +1,70 @@
+use std::{fs, path::Path};
+
+use apread::settings::{Format, Overrides, Settings};
+
+// Everything touching the environment lives in this one test, so nothing
+// running alongside it sees the variables change.
+#[test]
+fn flags_beat_the_environment_which_beats_the_config_file() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("config.toml");
+
+  fs::write(&path, "width = 100\nformat = \"json\"\ntimeout = 5\n").unwrap();
+
+  let settings = Settings::load(Some(&path), Overrides::default()).unwrap();
+  assert_eq!(settings.width, 100);
+  assert_eq!(settings.format, Format::Json);
+  assert_eq!(settings.timeout, Some(5));
+
+  std::env::set_var("APREAD_WIDTH", "120");
+  std::env::set_var("APREAD_PROXY", "socks5://127.0.0.1:9050");
+
+  let settings = Settings::load(Some(&path), Overrides::default()).unwrap();
+  assert_eq!(settings.width, 120);
+  assert_eq!(settings.proxy.as_deref(), Some("socks5://127.0.0.1:9050"));
+
+  let settings = Settings::load(
+    Some(&path),
+    Overrides {
+      width: Some(140),
+      format: Some(Format::Terminal),
+      ..Overrides::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(settings.width, 140);
+  assert_eq!(settings.format, Format::Terminal);
+  assert_eq!(settings.timeout, Some(5));
+
+  std::env::remove_var("APREAD_WIDTH");
+  std::env::remove_var("APREAD_PROXY");
+
+  let settings = Settings::load(None, Overrides::default()).unwrap();
+  assert_eq!(settings.width, 80);
+  assert_eq!(settings.format, Format::Terminal);
+  assert_eq!(settings.discovery_ttl, 24 * 60 * 60);
+
+  // Containers can do without a config file and its directory altogether.
+  std::env::set_var("APREAD_DATA_DIR", "/data");
+  std::env::set_var("APREAD_SUBSCRIPTIONS", "/config/subscriptions.toml");
+
+  let settings = Settings::load(None, Overrides::default()).unwrap();
+  assert_eq!(settings.data_dir().unwrap(), Path::new("/data"));
+  assert_eq!(
+    settings.subscriptions_path().as_deref(),
+    Some(Path::new("/config/subscriptions.toml"))
+  );
+
+  let settings = Settings::load(
+    None,
+    Overrides {
+      data_dir: Some("/elsewhere".into()),
+      ..Overrides::default()
+    },
+  )
+  .unwrap();
+  assert_eq!(settings.data_dir().unwrap(), Path::new("/elsewhere"));
+
+  std::env::remove_var("APREAD_DATA_DIR");
+  std::env::remove_var("APREAD_SUBSCRIPTIONS");
+}
@@ -0,0 +1,117 @@
+use apread::{activity::Post, archive::Archive, similar, Client};
+use serde_json::json;
+use wiremock::{
+  matchers::{method, path, query_param},
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn interests(tags: &[&str]) -> similar::Interests {
+  similar::interests(tags.iter().copied())
+}
+
+fn tagged(id: &str, tags: &[&str]) -> Post {
+  let tag: Vec<_> = tags
+    .iter()
+    .map(|name| json!({ "type": "Hashtag", "name": format!("#{}", name) }))
+    .collect();
+
+  serde_json::from_value(json!({ "id": id, "content": "", "tag": tag }))
+    .unwrap()
+}
+
+#[test]
+fn accounts_tagging_in_the_same_proportions_are_the_most_alike() {
+  let mine = interests(&["Gardening", "#gardening", "tomatoes"]);
+
+  assert!((similar::similarity(&mine, &mine) - 1.0).abs() < 1e-9);
+  assert_eq!(similar::similarity(&mine, &interests(&["rust"])), 0.0);
+  assert_eq!(similar::similarity(&mine, &interests(&[])), 0.0);
+
+  let ranked = similar::rank(
+    &mine,
+    [
+      (
+        "@Bob@bob.example".to_owned(),
+        interests(&["tomatoes", "rust"]),
+      ),
+      ("carol@carol.example".to_owned(), interests(&["gardening"])),
+      ("dan@dan.example".to_owned(), interests(&["rust"])),
+      ("bob@bob.example".to_owned(), interests(&["tomatoes"])),
+    ],
+  );
+  let handles: Vec<_> = ranked.iter().map(|found| &found.handle).collect();
+
+  assert_eq!(handles, ["carol@carol.example", "bob@bob.example"]);
+  assert_eq!(ranked[0].shared, ["gardening"]);
+  assert_eq!(ranked[1].shared, ["tomatoes"]);
+}
+
+#[test]
+fn hashtags_are_read_out_of_a_bio() {
+  assert_eq!(
+    similar::bio_hashtags(
+      r#"<p>Growing things. <a href="https://example.social/tags/Gardening" class="mention hashtag">#<span>Gardening</span></a>, #compost!</p>"#
+    ),
+    ["Gardening", "compost"]
+  );
+}
+
+#[test]
+fn archived_accounts_are_compared_by_their_standing_posts_hashtags() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+
+  archive
+    .store(
+      &tagged("https://a/1", &["gardening", "tomatoes"]),
+      Some("alice@a"),
+    )
+    .unwrap();
+  archive
+    .store(&tagged("https://a/2", &["gardening"]), Some("alice@a"))
+    .unwrap();
+  archive
+    .store(&tagged("https://b/1", &["rust"]), Some("bob@b"))
+    .unwrap();
+  archive
+    .store(&tagged("https://b/2", &["gone"]), Some("bob@b"))
+    .unwrap();
+  archive.mark_deleted("https://b/2").unwrap();
+
+  let archived = similar::archived(&archive.hashtags_by_author().unwrap());
+
+  assert_eq!(
+    archived,
+    [
+      (
+        "alice@a".to_owned(),
+        interests(&["gardening", "gardening", "tomatoes"])
+      ),
+      ("bob@b".to_owned(), interests(&["rust"])),
+    ]
+  );
+}
+
+#[tokio::test]
+async fn an_instances_directory_lists_its_active_accounts() {
+  let server = MockServer::start().await;
+
+  Mock::given(method("GET"))
+    .and(path("/api/v1/directory"))
+    .and(query_param("order", "active"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+      { "acct": "bob", "note": "<p>#tomatoes</p>" },
+      { "acct": "carol@carol.example" },
+    ])))
+    .mount(&server)
+    .await;
+
+  let accounts = Client::insecure()
+    .directory(&server.address().to_string())
+    .await
+    .unwrap();
+
+  assert_eq!(accounts.len(), 2);
+  assert_eq!(accounts[0].note, "<p>#tomatoes</p>");
+  assert_eq!(accounts[1].note, "");
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use std::{fs, io::Write};
+
+use apread::{
+  sink::{self, OutputSink},
+  timeline::{self, Author, Entry},
+  ApreadErrors, Client, Handle,
+};
+use common::FakeInstance;
+
+fn entry(content: &str) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(serde_json::json!({
+      "id": "https://example.social/notes/1",
+      "url": "https://example.social/@alice/1",
+      "content": content,
+    }))
+    .unwrap(),
+    published: "2023-02-14T18:03:11Z".to_owned(),
+    boosted: false,
+  }
+}
+
+fn written(mut sink: impl OutputSink, entries: &[Entry]) -> String {
+  let mut out = vec![];
+  sink.write(&mut out, entries).unwrap();
+  String::from_utf8(out).unwrap()
+}
+
+#[tokio::test]
+async fn mastodon_timeline_as_rss() {
+  let instance = FakeInstance::start("mastodon").await;
+  let handle = instance.handle("alice");
+  let page = Client::insecure().first_page(&handle).await.unwrap();
+  let entries = timeline::entries(&Author::new(handle), &page);
+
+  insta::assert_snapshot!(written(sink::Rss, &entries)
+    .replace(&instance.uri(), "{{base}}")
+    .replace(&instance.handle("alice").to_string(), "alice@{{host}}"));
+}
+
+#[test]
+fn podcast_feeds_enclose_the_audio_posts() {
+  let episode = Entry {
+    post: serde_json::from_value(serde_json::json!({
+      "id": "https://example.social/notes/2",
+      "url": "https://example.social/@alice/2",
+      "content": "<p>Episode one</p>",
+      "attachment": [
+        {"mediaType": "image/png", "url": "https://example.social/cover.png"},
+        {
+          "mediaType": "audio/*",
+          "url": "https://example.social/one.ogg?v=1",
+          "duration": "PT31M5S",
+        },
+      ],
+    }))
+    .unwrap(),
+    ..entry("<p>unused</p>")
+  };
+  let feed = written(sink::Podcast, &[entry("<p>No audio</p>"), episode]);
+
+  assert!(feed.contains("xmlns:itunes="), "{}", feed);
+  assert!(feed.contains("<title>alice</title>"), "{}", feed);
+  assert_eq!(feed.matches("<item>").count(), 1, "{}", feed);
+  assert!(feed.contains(
+    r#"<enclosure url="https://example.social/one.ogg?v=1" length="0" type="audio/ogg"/>"#
+  ));
+  assert!(feed.contains("<itunes:duration>31:05</itunes:duration>"));
+  assert!(!feed.contains("No audio"));
+}
+
+fn event(id: &str, start: &str, end: Option<&str>) -> Entry {
+  Entry {
+    post: serde_json::from_value(serde_json::json!({
+      "type": "Event",
+      "id": id,
+      "url": id,
+      "name": "Repair café; bring things, broken or not",
+      "content": "<p>Tools provided</p>",
+      "startTime": start,
+      "endTime": end,
+      "location": {"type": "Place", "name": "The library"},
+    }))
+    .unwrap(),
+    ..entry("<p>unused</p>")
+  }
+}
+
+#[test]
+fn calendars_have_the_events_yet_to_end() {
+  let now = chrono::DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+    .unwrap()
+    .to_utc();
+  let entries = [
+    entry("<p>Not an event</p>"),
+    event("https://events.example/e/1", "2024-05-01T18:00:00Z", None),
+    event(
+      "https://events.example/e/2",
+      "2024-06-01T10:00:00+02:00",
+      Some("2024-06-01T16:00:00+02:00"),
+    ),
+    event("https://events.example/e/3", "2024-07-04T18:00:00Z", None),
+  ];
+  let calendar = written(sink::Ics { now }, &entries);
+
+  assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+  assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+  assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2, "{}", calendar);
+  assert!(!calendar.contains("e/1"));
+  assert!(calendar.contains("DTSTART:20240601T080000Z\r\n"));
+  assert!(calendar.contains("DTEND:20240601T140000Z\r\n"));
+  assert!(calendar.contains("DTSTART:20240704T180000Z\r\n"));
+  assert!(calendar
+    .contains("SUMMARY:Repair café\\; bring things\\, broken or not\r\n"));
+  assert!(calendar.contains("LOCATION:The library\r\n"));
+  assert!(calendar.contains("DESCRIPTION:Tools provided\r\n"));
+  assert!(calendar.lines().all(|line| line.len() <= 76));
+}
+
+#[test]
+fn html_pages_leave_scripts_out() {
+  let page = written(
+    sink::Html,
+    &[entry(
+      r#"<p onclick="steal()">hi</p><script>steal()</script><a href="javascript:steal()">x</a>"#,
+    )],
+  );
+
+  assert!(page.starts_with("<!DOCTYPE html>"));
+  assert!(page.contains("<p>hi</p>"), "{}", page);
+  assert!(!page.contains("steal"), "{}", page);
+  assert!(page.contains(r#"<a href="https://example.social/@alice/1">"#));
+}
+
+#[test]
+fn files_get_what_the_sink_would_have_printed() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("posts.json");
+  let entries = [entry("<p>hi</p>")];
+
+  let printed = written(sink::File::new(&path, Box::new(sink::Json)), &entries);
+
+  assert_eq!(printed, "");
+  assert_eq!(
+    fs::read_to_string(&path).unwrap(),
+    written(sink::Json, &entries)
+  );
+}
+
+#[test]
+fn sinks_can_be_written_outside_apread() {
+  struct Ids;
+
+  impl OutputSink for Ids {
+    fn write(
+      &mut self,
+      out: &mut dyn Write,
+      entries: &[Entry],
+    ) -> Result<(), ApreadErrors> {
+      for entry in entries {
+        writeln!(out, "{}", entry.key())?;
+      }
+
+      Ok(())
+    }
+  }
+
+  assert_eq!(
+    written(Ids, &[entry("<p>hi</p>")]),
+    "https://example.social/notes/1\n"
+  );
+}
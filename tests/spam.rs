@@ -0,0 +1,161 @@
+use apread::{
+  spam::{Action, Reason, Spam},
+  timeline::{Author, Entry},
+  Handle,
+};
+use serde_json::json;
+
+fn entry(handle: &str, published: &str, post: serde_json::Value) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string(handle).unwrap()),
+    post: serde_json::from_value(post).unwrap(),
+    published: published.to_owned(),
+    boosted: false,
+  }
+}
+
+fn said(handle: &str, published: &str, content: &str) -> Entry {
+  entry(handle, published, json!({ "content": content }))
+}
+
+fn hashtags(count: usize) -> Entry {
+  let tags: Vec<_> = (0..count)
+    .map(|n| json!({ "type": "Hashtag", "name": format!("#tag{}", n) }))
+    .collect();
+
+  entry(
+    "alice@example.social",
+    "2024-01-01T00:00:00Z",
+    json!({ "content": format!("<p>{} tags</p>", count), "tag": tags }),
+  )
+}
+
+#[test]
+fn the_spam_table_turns_every_heuristic_on() {
+  let spam: Spam = serde_json::from_value(json!({})).unwrap();
+
+  assert!(spam.link_only && spam.repeated);
+  assert_eq!(spam.max_hashtags, 6);
+  assert_eq!(spam.action, Action::Hide);
+}
+
+#[test]
+fn posts_that_are_only_links_are_filtered() {
+  let entries = vec![
+    said(
+      "alice@example.social",
+      "2024-01-01T00:00:00Z",
+      r#"<p><a href="https://shop.example/deal">shop.example/deal</a></p>"#,
+    ),
+    said(
+      "bob@example.social",
+      "2024-01-01T00:00:00Z",
+      r#"<p>I wrote about parsers <a href="https://blog.example/p">blog.example/p</a></p>"#,
+    ),
+    said(
+      "carol@example.social",
+      "2024-01-01T00:00:00Z",
+      r#"<p><a href="https://example.social/@dan" class="u-url mention">@dan</a> hi</p>"#,
+    ),
+  ];
+
+  assert_eq!(
+    Spam::default().reasons(&entries),
+    vec![Some(Reason::LinkOnly), None, None]
+  );
+}
+
+#[test]
+fn links_with_attachments_are_kept() {
+  let entries = vec![entry(
+    "alice@example.social",
+    "2024-01-01T00:00:00Z",
+    json!({
+      "content": r#"<a href="https://photos.example/1">photos.example/1</a>"#,
+      "attachment": [{ "type": "Image", "url": "https://example.social/1.png" }],
+    }),
+  )];
+
+  assert_eq!(Spam::default().reasons(&entries), vec![None]);
+}
+
+#[test]
+fn posts_with_too_many_hashtags_are_filtered() {
+  let entries = vec![hashtags(6), hashtags(7)];
+
+  assert_eq!(
+    Spam::default().reasons(&entries),
+    vec![None, Some(Reason::Hashtags(7))]
+  );
+
+  let unlimited = Spam {
+    max_hashtags: 0,
+    ..Spam::default()
+  };
+
+  assert_eq!(unlimited.reasons(&entries), vec![None, None]);
+}
+
+#[test]
+fn repeats_are_filtered_keeping_the_first_posted() {
+  let entries = vec![
+    said(
+      "bot@example.social",
+      "2024-01-02T00:00:00Z",
+      "<p>Buy  NOW</p>",
+    ),
+    said("bot@spam.example", "2024-01-01T00:00:00Z", "<p>buy now</p>"),
+    said(
+      "alice@example.social",
+      "2024-01-03T00:00:00Z",
+      "<p>Hello</p>",
+    ),
+  ];
+
+  assert_eq!(
+    Spam::default().reasons(&entries),
+    vec![Some(Reason::Repeated), None, None]
+  );
+}
+
+#[test]
+fn the_same_post_boosted_twice_is_not_a_repeat() {
+  let post =
+    json!({ "id": "https://example.social/1", "content": "<p>Hi</p>" });
+  let entries = vec![
+    entry("alice@example.social", "2024-01-01T00:00:00Z", post.clone()),
+    entry("bob@example.social", "2024-01-01T00:00:00Z", post),
+  ];
+
+  assert_eq!(Spam::default().reasons(&entries), vec![None, None]);
+}
+
+#[test]
+fn filtered_posts_are_hidden_or_moved_to_the_end() {
+  let entries = vec![
+    hashtags(9),
+    said("alice@example.social", "2024-01-01T00:00:00Z", "<p>One</p>"),
+    said("bob@example.social", "2024-01-01T00:00:00Z", "<p>Two</p>"),
+  ];
+  let contents = |entries: &[Entry]| {
+    entries
+      .iter()
+      .map(|entry| entry.post.content.clone())
+      .collect::<Vec<_>>()
+  };
+
+  let mut hidden = entries.clone();
+  assert_eq!(Spam::default().apply(&mut hidden), 1);
+  assert_eq!(contents(&hidden), vec!["<p>One</p>", "<p>Two</p>"]);
+
+  let downranked = Spam {
+    action: Action::Downrank,
+    ..Spam::default()
+  };
+  let mut moved = entries;
+  assert_eq!(downranked.apply(&mut moved), 1);
+  assert_eq!(
+    contents(&moved),
+    vec!["<p>One</p>", "<p>Two</p>", "<p>9 tags</p>"]
+  );
+}
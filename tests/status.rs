@@ -0,0 +1,116 @@
+use apread::{
+  locale::Locale,
+  render,
+  status::{self, Report, Status},
+  timeline::Author,
+  watch::Poll,
+  Handle, NothingToRead,
+};
+use chrono::{DateTime, Duration, Utc};
+
+fn author(handle: &str) -> Author {
+  Author::new(Handle::parse_string(handle).unwrap())
+}
+
+fn at(date: &str) -> DateTime<Utc> {
+  DateTime::parse_from_rfc3339(date).unwrap().into()
+}
+
+fn polled() -> Status {
+  let authors = [author("alice@example.social"), author("bob@down.example")];
+  let mut status = Status::new(&authors, 300, at("2023-02-14T18:00:00Z"));
+  let poll = Poll {
+    new: vec![],
+    failed: vec![(author("bob@down.example"), NothingToRead.into())],
+  };
+
+  status.polled(&poll, at("2023-02-14T18:01:00Z"));
+  status
+}
+
+#[test]
+fn polls_refresh_the_accounts_they_fetched() {
+  let status = polled();
+
+  assert_eq!(
+    status.next_poll_at.as_deref(),
+    Some("2023-02-14T18:06:00+00:00")
+  );
+  assert_eq!(
+    status.accounts[0].refreshed_at.as_deref(),
+    Some("2023-02-14T18:01:00+00:00")
+  );
+  assert_eq!(status.accounts[0].error, None);
+  assert_eq!(status.accounts[1].refreshed_at, None);
+  assert!(status.accounts[1].error.is_some());
+}
+
+#[test]
+fn watches_long_overdue_a_poll_have_stopped() {
+  let status = polled();
+
+  assert!(status.running(at("2023-02-14T18:10:00Z")));
+  assert!(!status.running(at("2023-02-14T18:12:00Z")));
+  assert_eq!(
+    status.uptime(at("2023-02-14T19:00:00Z")),
+    Some(Duration::hours(1))
+  );
+}
+
+#[test]
+fn statuses_are_kept_between_processes() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join(status::FILE);
+
+  assert_eq!(Status::load(&path).unwrap(), None);
+
+  polled().save(&path).unwrap();
+
+  assert_eq!(Status::load(&path).unwrap(), Some(polled()));
+}
+
+#[test]
+fn directory_sizes_count_nested_files() {
+  let dir = tempfile::tempdir().unwrap();
+
+  std::fs::write(dir.path().join("a"), "12345").unwrap();
+  std::fs::create_dir(dir.path().join("nested")).unwrap();
+  std::fs::write(dir.path().join("nested/b"), "123").unwrap();
+
+  assert_eq!(status::dir_size(dir.path()), 8);
+  assert_eq!(status::dir_size(&dir.path().join("missing")), 0);
+}
+
+#[test]
+fn statuses_are_written_for_reading() {
+  let report =
+    Report::new(Some(polled()), 3, 1_234_567, at("2023-02-14T18:10:00Z"));
+  let mut out = vec![];
+
+  render::status(&mut out, &report, &Locale::default()).unwrap();
+
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "Watching since 2023-02-14 18:00 (up 10m), as process ".to_owned()
+      + &polled().pid.to_string()
+      + "\nPolling every 5m, last at 2023-02-14 18:01, next at 2023-02-14 18:06\n\
+         3 items queued to read later, and 1.2 MB cached\n\
+         \n  alice@example.social  refreshed 2023-02-14 18:01\n  \
+         bob@down.example      not refreshed yet, failing: No handle given, \
+         and no subscriptions to read\n"
+  );
+}
+
+#[test]
+fn no_watch_is_reported_as_not_running() {
+  let report = Report::new(None, 1, 0, Utc::now());
+  let mut out = vec![];
+
+  render::status(&mut out, &report, &Locale::default()).unwrap();
+
+  assert_eq!(
+    String::from_utf8(out).unwrap(),
+    "apread watch isn't running\n1 item queued to read later, and 0 bytes \
+     cached\n"
+  );
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use apread::{
+  cache::DiscoveryCache,
+  storage::{self, Backend, Disk, Memory, Sqlite, Store},
+  Client,
+};
+use common::FakeInstance;
+
+fn round_trip(store: &dyn Store) {
+  assert_eq!(store.get("https://example.social/users/alice"), None);
+
+  store
+    .put("https://example.social/users/alice", "{\"first\":1}")
+    .unwrap();
+  store
+    .put("https://example.social/users/alice", "{\"second\":2}")
+    .unwrap();
+
+  let stored = store.get("https://example.social/users/alice").unwrap();
+  assert_eq!(stored.body, "{\"second\":2}");
+  assert!(stored.fresh(Duration::from_secs(60)));
+  assert!(!stored.fresh(Duration::ZERO));
+}
+
+#[test]
+fn every_backend_keeps_the_latest_response() {
+  let dir = tempfile::tempdir().unwrap();
+
+  round_trip(&Memory::default());
+  round_trip(&Disk::new(dir.path().join("disk")));
+  round_trip(&Sqlite::open(dir.path().join("cache.sqlite3"), "test").unwrap());
+}
+
+#[test]
+fn disk_files_with_the_same_name_are_told_apart() {
+  let dir = tempfile::tempdir().unwrap();
+  let store = Disk::new(dir.path());
+
+  store.put("https://example.social/a-b", "dash").unwrap();
+
+  assert_eq!(store.get("https://example.social/a_b"), None);
+}
+
+#[test]
+fn stores_opened_on_the_same_directory_share_what_they_keep() {
+  let dir = tempfile::tempdir().unwrap();
+
+  for backend in [Backend::Disk, Backend::Sqlite] {
+    let writer = storage::open(backend, dir.path(), "discovery").unwrap();
+    let reader = storage::open(backend, dir.path(), "discovery").unwrap();
+    let other = storage::open(backend, dir.path(), "objects").unwrap();
+
+    writer.put("https://example.social/", "shared").unwrap();
+
+    assert_eq!(
+      reader.get("https://example.social/").unwrap().body,
+      "shared"
+    );
+    assert_eq!(other.get("https://example.social/"), None, "{:?}", backend);
+  }
+}
+
+#[tokio::test]
+async fn discovery_can_be_cached_in_sqlite() {
+  let instance = FakeInstance::start("mastodon").await;
+  let dir = tempfile::tempdir().unwrap();
+  let store = storage::open(Backend::Sqlite, dir.path(), "discovery").unwrap();
+  let cache = DiscoveryCache::in_store(store, Duration::from_secs(60));
+
+  // Two clients, as a watch and a one-off read would be.
+  for _ in 0..2 {
+    Client::insecure()
+      .caching_discovery(cache.clone())
+      .first_page(&instance.handle("alice"))
+      .await
+      .unwrap();
+  }
+
+  let lookups = instance
+    .requests()
+    .await
+    .iter()
+    .filter(|path| {
+      path.starts_with("/.well-known/webfinger") || *path == "/users/alice"
+    })
+    .count();
+  assert_eq!(lookups, 2, "webfinger and actor, once each");
+}
+
+#[tokio::test]
+async fn boosted_posts_can_be_cached_in_memory() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client =
+    Client::insecure().caching_objects_in(Arc::new(Memory::default()));
+  let url =
+    format!("{}/users/alice/statuses/109000000000000001", instance.uri());
+
+  let first = client.boosted(&url).await.unwrap();
+  let second = client.boosted(&url).await.unwrap();
+
+  assert_eq!(first.content, second.content);
+  assert_eq!(client.stats().requests(), 1);
+  assert_eq!(client.stats().cache_hits(), 1);
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use apread::{
+  login::Login,
+  streaming::{self, Event, Message, Parser, Stream},
+  Client,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{header, method, path, query_param},
+  Mock, MockServer, ResponseTemplate,
+};
+
+fn login(instance: &str) -> Login {
+  Login {
+    instance: instance.to_owned(),
+    account: "alice".to_owned(),
+    access_token: "sekrit".to_owned(),
+    write: false,
+  }
+}
+
+fn status(id: &str, content: &str) -> String {
+  json!({
+    "id": id,
+    "uri": format!("https://example.social/statuses/{}", id),
+    "created_at": "2023-06-01T10:00:00.000Z",
+    "content": content,
+    "account": { "acct": "bob@elsewhere.example" },
+  })
+  .to_string()
+}
+
+#[test]
+fn events_are_read_however_the_body_is_split() {
+  let body = "event: update\r\ndata: one\r\n\r\n:thump\n\nevent: delete\n\
+              data: 7\n\ndata: two\ndata: lines\n\n";
+  let mut parser = Parser::default();
+  let mut messages = vec![];
+
+  for chunk in body.as_bytes().chunks(3) {
+    messages.extend(parser.push(chunk));
+  }
+
+  let message = |event: &str, data: &str| Message {
+    event: event.to_owned(),
+    data: data.to_owned(),
+  };
+  assert_eq!(
+    messages,
+    [
+      message("update", "one"),
+      message("delete", "7"),
+      message("message", "two\nlines"),
+    ]
+  );
+}
+
+#[test]
+fn streams_have_paths_with_their_tag_or_list() {
+  assert_eq!(Stream::Home.path(), "/api/v1/streaming/user");
+  assert_eq!(Stream::Local.path(), "/api/v1/streaming/public/local");
+  assert_eq!(
+    Stream::Hashtag("tomatoes".to_owned()).path(),
+    "/api/v1/streaming/hashtag?tag=tomatoes"
+  );
+  assert_eq!(
+    Stream::List("7".to_owned()).path(),
+    "/api/v1/streaming/list?list=7"
+  );
+}
+
+#[test]
+fn reconnecting_waits_longer_after_each_failure_up_to_a_minute() {
+  assert_eq!(streaming::backoff(0), Duration::from_secs(1));
+  assert_eq!(streaming::backoff(3), Duration::from_secs(8));
+  assert_eq!(streaming::backoff(6), streaming::MAX_BACKOFF);
+  assert_eq!(streaming::backoff(40), streaming::MAX_BACKOFF);
+  assert_eq!(
+    streaming::base_url("wss://streaming.example.social/"),
+    "https://streaming.example.social"
+  );
+}
+
+#[tokio::test]
+async fn posts_are_read_off_the_stream_the_instance_points_to() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+  let body = format!(
+    "event: update\ndata: {}\n\n:thump\n\nevent: notification\ndata: {{}}\n\n\
+     event: delete\ndata: 100\n\nevent: status.update\ndata: {}\n\n",
+    status("100", "<p>first tomato</p>"),
+    status("100", "<p>first tomato, ripe</p>"),
+  );
+
+  Mock::given(method("GET"))
+    .and(path("/api/v2/instance"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "configuration": {
+        "urls": { "streaming": format!("ws://{}/elsewhere", instance) },
+      },
+    })))
+    .mount(&server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/elsewhere/api/v1/streaming/hashtag"))
+    .and(query_param("tag", "tomatoes"))
+    .and(header("authorization", "Bearer sekrit"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_string(body),
+    )
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance));
+  let mut events = client
+    .open_stream(&Stream::Hashtag("tomatoes".to_owned()))
+    .await
+    .unwrap();
+  let mut seen = vec![];
+
+  while let Some(event) = events.next().await.unwrap() {
+    seen.push(match event {
+      Event::Update(entry) => format!("update {}", entry.post.content),
+      Event::Edit(entry) => format!("edit {}", entry.post.content),
+      Event::Delete(id) => format!("delete {}", id),
+    });
+  }
+
+  assert_eq!(
+    seen,
+    [
+      "update <p>first tomato</p>",
+      "delete 100",
+      "edit <p>first tomato, ripe</p>",
+    ]
+  );
+}
+
+#[tokio::test]
+async fn instances_without_a_streaming_host_stream_from_their_own() {
+  let server = MockServer::start().await;
+  let instance = server.address().to_string();
+
+  Mock::given(method("GET"))
+    .and(path("/api/v1/streaming/user"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      "event: update\ndata: {}\n\n",
+      status("5", "<p>hello</p>")
+    )))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().logged_in(login(&instance));
+  let mut events = client.open_stream(&Stream::Home).await.unwrap();
+
+  assert!(matches!(
+    events.next().await.unwrap(),
+    Some(Event::Update(entry)) if entry.post.content == "<p>hello</p>"
+  ));
+  assert!(events.next().await.unwrap().is_none());
+}
@@ -0,0 +1,342 @@
+mod common;
+
+use std::fs;
+
+use chrono::Datelike;
+
+use apread::{
+  i18n::Messages,
+  locale::Locale,
+  render::{self, Color},
+  subscriptions::Subscriptions,
+  timeline::{self, Author, Mix, Period, Reading, Walk},
+  Client,
+};
+use common::FakeInstance;
+
+#[test]
+fn subscriptions_carry_nicknames_and_colors() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("subscriptions.toml");
+
+  fs::write(
+    &path,
+    r#"
+      [[account]]
+      handle = "alice@example.social"
+      nickname = "al"
+      color = "green"
+
+      [[account]]
+      handle = "bob@example.social"
+    "#,
+  )
+  .unwrap();
+
+  let subscriptions = Subscriptions::load(&path).unwrap();
+
+  assert_eq!(subscriptions.accounts.len(), 2);
+  assert_eq!(subscriptions.accounts[0].color, Some(Color::Green));
+  assert_eq!(
+    subscriptions.find("alice@example.social").unwrap().nickname,
+    Some("al".to_owned())
+  );
+  assert_eq!(subscriptions.only(&["al".to_owned()]).len(), 1);
+  assert_eq!(
+    subscriptions.only(&["bob@example.social".to_owned()]).len(),
+    1
+  );
+  assert_eq!(subscriptions.only(&[]).len(), 2);
+}
+
+#[test]
+fn subscriptions_say_how_much_to_show() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("subscriptions.toml");
+
+  fs::write(
+    &path,
+    r#"
+      [[account]]
+      handle = "firehose@example.social"
+      boosts = true
+      replies = false
+      max_per_refresh = 10
+
+      [[account]]
+      handle = "bob@example.social"
+    "#,
+  )
+  .unwrap();
+
+  let subscriptions = Subscriptions::load(&path).unwrap();
+  let mixes: Vec<_> = subscriptions
+    .accounts
+    .iter()
+    .map(|subscription| Author::from_subscription(subscription).unwrap().mix)
+    .collect();
+
+  assert_eq!(
+    mixes,
+    [
+      Mix {
+        boosts: true,
+        replies: false,
+        max_per_refresh: Some(10),
+      },
+      Mix::default(),
+    ]
+  );
+}
+
+#[test]
+fn a_missing_subscriptions_file_is_empty() {
+  let dir = tempfile::tempdir().unwrap();
+  let subscriptions =
+    Subscriptions::load(&dir.path().join("subscriptions.toml")).unwrap();
+
+  assert!(subscriptions.accounts.is_empty());
+}
+
+async fn combined_timeline(
+  mastodon: &FakeInstance,
+  pleroma: &FakeInstance,
+) -> Vec<timeline::Entry> {
+  let client = Client::insecure();
+  let mut entries = vec![];
+
+  for (instance, nickname) in
+    [(&mastodon, "masto-alice"), (&pleroma, "pl-alice")]
+  {
+    let author = Author {
+      nickname: Some(nickname.to_owned()),
+      color: Some(Color::Cyan),
+      ..Author::new(instance.handle("alice"))
+    };
+    entries.extend(timeline::fetch(&client, &author).await.unwrap());
+  }
+
+  timeline::merge(&mut entries);
+
+  entries
+}
+
+#[tokio::test]
+async fn combined_timelines_interleave_and_use_nicknames() {
+  let mastodon = FakeInstance::start("mastodon").await;
+  let pleroma = FakeInstance::start("pleroma").await;
+  let entries = combined_timeline(&mastodon, &pleroma).await;
+
+  let mut out = vec![];
+  render::terminal(&mut out, &entries, 80, false, &Messages::default())
+    .unwrap();
+  let out = String::from_utf8(out)
+    .unwrap()
+    .replace(&mastodon.uri(), "{{base}}")
+    .replace(&pleroma.uri(), "{{base}}");
+
+  insta::assert_snapshot!(out);
+}
+
+#[tokio::test]
+async fn grouped_timelines_cluster_each_account() {
+  let mastodon = FakeInstance::start("mastodon").await;
+  let pleroma = FakeInstance::start("pleroma").await;
+  let entries = combined_timeline(&mastodon, &pleroma).await;
+
+  let mut out = vec![];
+  render::grouped(
+    &mut out,
+    &entries,
+    80,
+    false,
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap();
+  let out = String::from_utf8(out)
+    .unwrap()
+    .replace(&mastodon.uri(), "{{base}}")
+    .replace(&pleroma.uri(), "{{base}}")
+    .replace(&mastodon.handle("alice").to_string(), "alice@{{mastodon}}")
+    .replace(&pleroma.handle("alice").to_string(), "alice@{{pleroma}}");
+
+  insta::assert_snapshot!(out);
+}
+
+#[tokio::test]
+async fn digests_section_timelines_by_day_or_week() {
+  let mastodon = FakeInstance::start("mastodon").await;
+  let pleroma = FakeInstance::start("pleroma").await;
+  let entries = combined_timeline(&mastodon, &pleroma).await;
+
+  let weekly = timeline::digest(&entries, Period::Weekly);
+  let daily = timeline::digest(&entries, Period::Daily);
+  assert!(weekly.len() < daily.len());
+  assert!(weekly.iter().all(|section| {
+    section
+      .start
+      .is_some_and(|day| day.weekday() == chrono::Weekday::Mon)
+  }));
+
+  let mut out = vec![];
+  render::digest(
+    &mut out,
+    &entries,
+    Period::Daily,
+    80,
+    false,
+    &Locale::default(),
+    &Messages::default(),
+  )
+  .unwrap();
+  let out = String::from_utf8(out)
+    .unwrap()
+    .replace(&mastodon.uri(), "{{base}}")
+    .replace(&pleroma.uri(), "{{base}}")
+    .replace(&mastodon.handle("alice").to_string(), "alice@{{mastodon}}")
+    .replace(&pleroma.handle("alice").to_string(), "alice@{{pleroma}}");
+
+  insta::assert_snapshot!(out);
+}
+
+#[test]
+fn colors_only_show_when_asked_for() {
+  let author = Author {
+    color: Some(Color::Red),
+    ..Author::new(apread::Handle::parse_string("alice@example.social").unwrap())
+  };
+  let page = serde_json::from_str(
+    r#"{"orderedItems": [{
+      "type": "Create",
+      "published": "2023-02-14T18:03:11Z",
+      "object": {"content": "<p>hi</p>"}
+    }]}"#,
+  )
+  .unwrap();
+  let entries = timeline::entries(&author, &page);
+  let (mut plain, mut colored) = (vec![], vec![]);
+
+  render::terminal(&mut plain, &entries, 80, false, &Messages::default())
+    .unwrap();
+  render::terminal(&mut colored, &entries, 80, true, &Messages::default())
+    .unwrap();
+
+  assert!(!String::from_utf8(plain).unwrap().contains('\x1b'));
+  assert!(String::from_utf8(colored)
+    .unwrap()
+    .contains("[1]       \x1b[31malice\x1b[0m"));
+}
+
+#[test]
+fn replies_can_be_left_out() {
+  let page = serde_json::from_str(
+    r#"{"orderedItems": [
+      {
+        "type": "Create",
+        "published": "2023-02-14T18:03:11Z",
+        "object": {"content": "<p>hi</p>"}
+      },
+      {
+        "type": "Create",
+        "published": "2023-02-14T18:04:00Z",
+        "object": {
+          "content": "<p>@bob yes</p>",
+          "inReplyTo": "https://example.social/notes/1"
+        }
+      }
+    ]}"#,
+  )
+  .unwrap();
+  let mut author =
+    Author::new(apread::Handle::parse_string("alice@example.social").unwrap());
+
+  assert_eq!(timeline::entries(&author, &page).len(), 2);
+
+  author.mix.replies = false;
+  let entries = timeline::entries(&author, &page);
+
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].post.content, "<p>hi</p>");
+}
+
+#[tokio::test]
+async fn boosts_are_fetched_when_asked_for() {
+  let instance = FakeInstance::start("mastodon").await;
+  let mut author = Author::new(instance.handle("alice"));
+  let client = Client::insecure();
+
+  let shared = timeline::fetch(&client, &author).await.unwrap();
+  assert!(shared.iter().all(|entry| !entry.boosted));
+
+  author.mix.boosts = true;
+  let page = serde_json::from_value(serde_json::json!({
+    "orderedItems": [{
+      "type": "Announce",
+      "published": "2023-02-15T08:00:00Z",
+      "object": format!(
+        "{}/users/alice/statuses/109000000000000001",
+        instance.uri()
+      ),
+    }]
+  }))
+  .unwrap();
+  let boosts = timeline::boosts(&client, &author, &page).await;
+
+  assert_eq!(boosts.len(), 1);
+  assert!(boosts[0].boosted);
+  assert_eq!(boosts[0].published, "2023-02-15T08:00:00Z");
+
+  let mut out = vec![];
+  render::terminal(&mut out, &boosts, 80, false, &Messages::default()).unwrap();
+  assert!(String::from_utf8(out).unwrap().contains("(boosted)"));
+}
+
+#[tokio::test]
+async fn max_per_refresh_caps_each_read() {
+  let instance = FakeInstance::start("paged").await;
+  let mut author = Author::new(instance.handle("bob"));
+  author.mix.max_per_refresh = Some(3);
+  let walk = Walk {
+    pages: 2,
+    ..Walk::default()
+  };
+
+  let entries = timeline::walk(&Client::insecure(), &author, walk)
+    .await
+    .unwrap();
+
+  assert_eq!(entries.len(), 3);
+  assert_eq!(
+    entries[0].post.content,
+    "<p>put the beds to sleep for winter</p>"
+  );
+}
+
+#[tokio::test]
+async fn accounts_that_cant_be_read_are_set_aside() {
+  let mastodon = FakeInstance::start("mastodon").await;
+  let pleroma = FakeInstance::start("pleroma").await;
+  let authors = [
+    Author::new(mastodon.handle("alice")),
+    Author::new(apread::Handle::parse_string("nobody@127.0.0.1:9").unwrap()),
+    Author::new(pleroma.handle("alice")),
+  ];
+
+  let gathered = timeline::gather(
+    &Client::insecure(),
+    &authors,
+    Reading::Walk(Walk::default()),
+  )
+  .await;
+  let sources: Vec<_> = gathered
+    .entries
+    .iter()
+    .map(|entry| entry.author.source.clone())
+    .collect();
+
+  assert_eq!(gathered.failed.len(), 1);
+  assert_eq!(gathered.failed[0].0.label(), "nobody");
+  assert_eq!(sources.first(), Some(&authors[0].source));
+  assert_eq!(sources.last(), Some(&authors[2].source));
+}
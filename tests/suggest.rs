@@ -0,0 +1,133 @@
+use apread::{activity::Post, archive::Archive, suggest, Client};
+use serde_json::json;
+use wiremock::{matchers::path, Mock, MockServer, ResponseTemplate};
+
+fn mention(name: &str, host: &str) -> String {
+  format!(
+    r#"<span class="h-card"><a href="https://{}/@{}" class="u-url mention">@<span>{}</span></a></span>"#,
+    host, name, name
+  )
+}
+
+fn post(id: &str, content: &str, attributed_to: Option<&str>) -> Post {
+  serde_json::from_value(json!({
+    "id": id,
+    "published": "2023-06-01T10:00:00Z",
+    "content": content,
+    "attributedTo": attributed_to,
+  }))
+  .unwrap()
+}
+
+fn pair(by: &str, handle: &str) -> (String, String) {
+  (by.to_owned(), handle.to_owned())
+}
+
+#[test]
+fn accounts_interacted_with_most_come_first_and_followed_ones_not_at_all() {
+  let mentions = [
+    pair("alice@example.social", "bob@bob.example"),
+    pair("alice@example.social", "@Bob@bob.example"),
+    pair("dan@example.social", "carol@carol.example"),
+    pair("alice@example.social", "erin@erin.example"),
+    pair("alice@example.social", "alice@example.social"),
+  ];
+  let boosts = [
+    pair("alice@example.social", "carol@carol.example"),
+    pair("dan@example.social", "erin@erin.example"),
+  ];
+  let suggestions =
+    suggest::rank(&mentions, &boosts, &["@Erin@erin.example".to_owned()]);
+  let ranked: Vec<_> = suggestions
+    .iter()
+    .map(|suggestion| {
+      (
+        suggestion.handle.as_str(),
+        suggestion.mentions,
+        suggestion.boosts,
+        suggestion.by.len(),
+      )
+    })
+    .collect();
+
+  // Carol and bob were interacted with as often, but by more accounts.
+  assert_eq!(
+    ranked,
+    [
+      ("carol@carol.example", 1, 1, 2),
+      ("bob@bob.example", 2, 0, 1)
+    ]
+  );
+}
+
+#[tokio::test]
+async fn suggestions_come_from_archived_mentions_and_noted_boosts() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let server = MockServer::start().await;
+  let carol = format!("http://{}/users/carol", server.address());
+
+  Mock::given(path("/users/carol"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "id": carol,
+      "preferredUsername": "carol",
+      "outbox": format!("{}/outbox", carol),
+    })))
+    .mount(&server)
+    .await;
+
+  let content = format!(
+    "<p>{} and {}, meet {}</p>",
+    mention("bob", "bob.example"),
+    mention("dan", "example.social"),
+    mention("bob", "bob.example"),
+  );
+  archive
+    .store(
+      &post("https://example.social/1", &content, None),
+      Some("alice@example.social"),
+    )
+    .unwrap();
+  archive
+    .store(
+      &post("https://example.social/2", "<p>hello</p>", None),
+      Some("dan@example.social"),
+    )
+    .unwrap();
+  archive
+    .store_boost(
+      "alice@example.social",
+      &post("https://carol.example/9", "<p>hi</p>", Some(&carol)),
+    )
+    .unwrap();
+  // Boosting the same post twice only counts once.
+  archive
+    .store_boost(
+      "alice@example.social",
+      &post("https://carol.example/9", "<p>hi</p>", Some(&carol)),
+    )
+    .unwrap();
+
+  let client = Client::insecure();
+  let suggestions = suggest::suggest(&client, &archive, &[]).await.unwrap();
+  let handles: Vec<_> = suggestions
+    .iter()
+    .map(|suggestion| suggestion.handle.clone())
+    .collect();
+
+  // Dan is archived, so is already being read.
+  assert_eq!(
+    handles,
+    [
+      "bob@bob.example",
+      format!("carol@{}", server.address().ip()).as_str()
+    ]
+  );
+  assert_eq!(suggestions[1].boosts, 1);
+
+  let following = ["bob@bob.example".to_owned()];
+  let suggestions = suggest::suggest(&client, &archive, &following)
+    .await
+    .unwrap();
+  assert_eq!(suggestions.len(), 1);
+}
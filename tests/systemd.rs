@@ -0,0 +1,56 @@
+use apread::systemd;
+
+#[test]
+fn services_run_watch_under_systemd() {
+  let unit = systemd::service(
+    "/usr/bin/apread",
+    &["--every".into(), "60".into()],
+    false,
+  );
+
+  assert!(unit.contains("Type=notify\n"));
+  assert!(
+    unit.contains("ExecStart=/usr/bin/apread watch --systemd --every 60\n")
+  );
+  assert!(!unit.contains("Requires="));
+}
+
+#[test]
+fn services_with_metrics_need_their_socket() {
+  let unit = systemd::service("/usr/bin/apread", &[], true);
+  let socket = systemd::socket("127.0.0.1:9185".parse().unwrap());
+
+  assert!(unit.contains("Requires=apread.socket\nAfter=apread.socket\n"));
+  assert!(socket.contains("ListenStream=127.0.0.1:9185\n"));
+}
+
+#[test]
+fn awkward_paths_are_quoted() {
+  let unit =
+    systemd::service("/home/me/my apps/apread", &["100%".into()], false);
+
+  assert!(unit.contains(
+    "ExecStart=\"/home/me/my apps/apread\" watch --systemd \"100%%\"\n"
+  ));
+}
+
+#[cfg(unix)]
+#[test]
+fn notifications_go_to_the_notify_socket() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("notify");
+  let socket = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+  std::env::set_var("NOTIFY_SOCKET", &path);
+
+  assert!(systemd::notify("READY=1").unwrap());
+
+  let mut received = [0; 64];
+  let count = socket.recv(&mut received).unwrap();
+
+  assert_eq!(&received[..count], b"READY=1");
+
+  std::env::remove_var("NOTIFY_SOCKET");
+
+  assert!(!systemd::notify("READY=1").unwrap());
+}
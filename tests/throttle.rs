@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+use apread::{throttle::Throttle, Client};
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+const DELAY: Duration = Duration::from_millis(200);
+
+#[tokio::test]
+async fn each_host_has_its_own_permits() {
+  let throttle = Throttle::new(2);
+  let waiting = Duration::from_millis(50);
+  let first = throttle.permit("https://example.social/a").await;
+  let second = throttle.permit("https://example.social:443/b").await;
+
+  assert!(first.is_some() && second.is_some());
+  assert!(tokio::time::timeout(
+    waiting,
+    throttle.permit("https://example.social/c")
+  )
+  .await
+  .is_err());
+  assert!(throttle.permit("https://other.example/a").await.is_some());
+
+  drop(first);
+  assert!(throttle.permit("https://example.social/c").await.is_some());
+}
+
+#[tokio::test]
+async fn unlimited_throttles_hand_out_no_permits() {
+  assert!(Throttle::new(0)
+    .permit("https://example.social")
+    .await
+    .is_none());
+}
+
+#[tokio::test]
+async fn clients_keep_to_the_cap_however_many_requests_there_are() {
+  let server = MockServer::start().await;
+
+  Mock::given(matchers::path("/about"))
+    .respond_with(ResponseTemplate::new(200).set_delay(DELAY))
+    .mount(&server)
+    .await;
+
+  let client = Client::insecure().throttled(2);
+  let url = format!("{}/about", server.uri());
+  let started = Instant::now();
+  let requests: Vec<_> = (0..6)
+    .map(|_| {
+      let client = client.clone();
+      let url = url.clone();
+      tokio::spawn(async move { client.web_page(&url).await })
+    })
+    .collect();
+
+  for request in requests {
+    request.await.unwrap().unwrap();
+  }
+
+  // Two at a time, six requests take three rounds.
+  assert!(started.elapsed() >= DELAY * 3, "{:?}", started.elapsed());
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "topics")]
+
+use apread::{activity::Post, archive::Archive, topics};
+use serde_json::json;
+
+const RELEASE: &str = "<p>We’ve shipped the new releases of the parser! \
+  The release notes are at <a href=\"https://example.social/notes\">https://example.social/notes</a>, \
+  thanks <a href=\"https://bob.example/@bob\" class=\"u-url mention\">@<span>bob</span></a>. \
+  Parser fixes, parser speedups, and 2 more releases soon.</p>";
+
+#[test]
+fn posts_are_tagged_with_their_hashtags_then_the_words_they_use_most() {
+  let keywords = topics::keywords(RELEASE, &["RustLang"]);
+
+  assert_eq!(keywords[..3], ["rustlang", "release", "parser"]);
+  assert!(!keywords.iter().any(|keyword| keyword.contains("example")));
+  assert!(!keywords.iter().any(|keyword| keyword == "bob"));
+  assert!(!keywords.iter().any(|keyword| keyword == "the"));
+  assert!(!keywords.iter().any(|keyword| keyword == "we've"));
+  assert!(keywords.len() <= topics::KEYWORDS);
+}
+
+#[test]
+fn topics_match_their_plurals_and_hashtags() {
+  let keywords = topics::keywords(RELEASE, &["RustLang"]);
+
+  assert!(topics::matches(&keywords, "Releases"));
+  assert!(topics::matches(&keywords, "#rustlang"));
+  assert!(!topics::matches(&keywords, "gardening"));
+  assert_eq!(topics::normal("bus"), "bus");
+  assert_eq!(topics::normal("glass"), "glass");
+}
+
+#[test]
+fn archived_posts_keep_the_keywords_they_were_tagged_with() {
+  let dir = tempfile::tempdir().unwrap();
+  let archive = Archive::open(dir.path().join("archive.sqlite3")).unwrap();
+  let post: Post = serde_json::from_value(json!({
+    "id": "https://example.social/1",
+    "content": RELEASE,
+  }))
+  .unwrap();
+
+  assert_eq!(archive.keywords("https://example.social/1").unwrap(), None);
+
+  archive.store(&post, Some("alice@example.social")).unwrap();
+
+  let keywords = archive.keywords("https://example.social/1").unwrap();
+  assert!(keywords.as_ref().unwrap().contains(&"release".to_owned()));
+  assert_eq!(topics::of(Some(&archive), &post), keywords.unwrap());
+}
@@ -0,0 +1,120 @@
+use std::{
+  net::SocketAddr,
+  sync::{Arc, Mutex},
+};
+
+use apread::{settings::Settings, ApreadErrors, Client, Handle};
+use serde_json::json;
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::{TcpListener, TcpStream},
+};
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+const ONION: &str = "abcdefghijklmnop.onion";
+
+/// A SOCKS5 proxy which connects everything to `target`, noting the names
+/// it was asked for.
+async fn proxy(target: SocketAddr) -> (SocketAddr, Arc<Mutex<Vec<String>>>) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let address = listener.local_addr().unwrap();
+  let names = Arc::new(Mutex::new(vec![]));
+  let noted = names.clone();
+
+  tokio::spawn(async move {
+    loop {
+      let (mut client, _) = listener.accept().await.unwrap();
+      let names = noted.clone();
+
+      tokio::spawn(async move {
+        let mut buffer = [0u8; 262];
+
+        // The greeting, offering ways to authenticate; take none.
+        client.read_exact(&mut buffer[..2]).await.unwrap();
+        let methods = usize::from(buffer[1]);
+        client.read_exact(&mut buffer[..methods]).await.unwrap();
+        client.write_all(&[5, 0]).await.unwrap();
+
+        // The request, which should name the host rather than address it.
+        client.read_exact(&mut buffer[..4]).await.unwrap();
+        assert_eq!(buffer[3], 3, "the proxy should be left to look names up");
+        client.read_exact(&mut buffer[..1]).await.unwrap();
+        let length = usize::from(buffer[0]);
+        client.read_exact(&mut buffer[..length + 2]).await.unwrap();
+        names
+          .lock()
+          .unwrap()
+          .push(String::from_utf8_lossy(&buffer[..length]).into_owned());
+
+        let mut upstream = TcpStream::connect(target).await.unwrap();
+        client
+          .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+          .await
+          .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+      });
+    }
+  });
+
+  (address, names)
+}
+
+/// An onion instance with one account, over plain http.
+async fn onion_instance() -> MockServer {
+  let server = MockServer::start().await;
+  let actor = format!("http://{}/users/alice", ONION);
+
+  Mock::given(matchers::path("/.well-known/webfinger"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "subject": format!("acct:alice@{}", ONION),
+      "links": [
+        { "rel": "self", "type": "application/activity+json", "href": actor },
+      ],
+    })))
+    .mount(&server)
+    .await;
+
+  Mock::given(matchers::path("/users/alice"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "id": actor,
+      "preferredUsername": "alice",
+      "outbox": format!("{}/outbox", actor),
+    })))
+    .mount(&server)
+    .await;
+
+  server
+}
+
+#[tokio::test]
+async fn onion_accounts_are_read_through_socks5() {
+  let instance = onion_instance().await;
+  let (proxy, names) = proxy(*instance.address()).await;
+  let dir = tempfile::tempdir().unwrap();
+  let settings = Settings {
+    socks5: Some(proxy.to_string()),
+    cache_dir: Some(dir.path().to_owned()),
+    ..Settings::default()
+  };
+  let handle = Handle::parse_string(&format!("alice@{}", ONION)).unwrap();
+
+  let actor = Client::from_settings(&settings)
+    .unwrap()
+    .resolve(&handle)
+    .await
+    .unwrap();
+
+  assert_eq!(actor.preferred_username.as_deref(), Some("alice"));
+  // The connection is kept alive between requests, so there may be one.
+  let names = names.lock().unwrap();
+  assert!(!names.is_empty());
+  assert!(names.iter().all(|name| name == ONION), "{:?}", names);
+}
+
+#[tokio::test]
+async fn onion_accounts_need_tor() {
+  let handle = Handle::parse_string(&format!("alice@{}", ONION)).unwrap();
+  let error = Client::new().resolve(&handle).await.unwrap_err();
+
+  assert!(matches!(error, ApreadErrors::NeedsTor(_)), "{}", error);
+}
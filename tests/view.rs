@@ -0,0 +1,110 @@
+mod common;
+
+use apread::{
+  settings::{Format, Overrides, Settings},
+  subscriptions::Subscriptions,
+  timeline::{self, Author, Start},
+  view::View,
+  Client,
+};
+use common::FakeInstance;
+
+#[test]
+fn views_read_handles_which_arent_subscribed_to() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("subscriptions.toml");
+
+  std::fs::write(
+    &path,
+    r#"
+      [[account]]
+      handle = "ops@example.social"
+      nickname = "ops"
+    "#,
+  )
+  .unwrap();
+
+  let subscriptions = Subscriptions::load(&path).unwrap();
+  let view = View {
+    accounts: vec![
+      "ops".to_owned(),
+      "carol@elsewhere.example".to_owned(),
+      "ops@example.social".to_owned(),
+      "nobody".to_owned(),
+    ],
+    ..View::default()
+  };
+
+  let accounts = view.accounts(&subscriptions).unwrap();
+  let read: Vec<_> = accounts
+    .authors
+    .iter()
+    .map(|author| author.source.to_string())
+    .collect();
+
+  assert_eq!(read, ["ops@example.social", "carol@elsewhere.example"]);
+  assert_eq!(accounts.authors[0].nickname.as_deref(), Some("ops"));
+  assert_eq!(accounts.unknown, ["nobody"]);
+}
+
+#[test]
+fn views_come_from_the_config_file() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("config.toml");
+
+  std::fs::write(
+    &path,
+    r#"
+      [views.work]
+      accounts = ["alice@example.social", "ops"]
+      tags = ["rust"]
+
+      [views.news]
+      from = "oldest"
+      group_by_author = true
+      format = "json"
+    "#,
+  )
+  .unwrap();
+
+  let settings = Settings::load(Some(&path), Overrides::default()).unwrap();
+
+  assert_eq!(
+    settings.views["work"],
+    View {
+      accounts: vec!["alice@example.social".to_owned(), "ops".to_owned()],
+      tags: vec!["rust".to_owned()],
+      ..View::default()
+    }
+  );
+  assert_eq!(
+    settings.views["news"],
+    View {
+      from: Some(Start::Oldest),
+      group_by_author: true,
+      format: Some(Format::Json),
+      ..View::default()
+    }
+  );
+}
+
+#[tokio::test]
+async fn views_keep_posts_with_their_tags() {
+  let instance = FakeInstance::start("mastodon").await;
+  let author = Author::new(instance.handle("alice"));
+  let entries = timeline::fetch(&Client::insecure(), &author).await.unwrap();
+
+  let tagged = |tags: &[&str]| {
+    let view = View {
+      tags: tags.iter().map(|tag| (*tag).to_owned()).collect(),
+      ..View::default()
+    };
+
+    entries.iter().filter(|entry| view.shows(entry)).count()
+  };
+
+  assert_eq!(tagged(&[]), entries.len());
+  assert_eq!(tagged(&["#Gardening"]), 1);
+  assert_eq!(tagged(&["gardening", "cooking"]), 1);
+  assert_eq!(tagged(&["cooking"]), 0);
+}
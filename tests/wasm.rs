@@ -0,0 +1,126 @@
+#![cfg(feature = "wasm")]
+
+use std::fs;
+
+use apread::{
+  diagnostics::{Diagnostics, Kind},
+  timeline::{Author, Entry},
+  wasm::Filters,
+  Handle,
+};
+use serde_json::json;
+
+/// Hides posts mentioning spoilers, tags and rewrites posts about cats, and
+/// never finishes with posts which go on forever.
+const FILTER: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 16) "{\"hide\":true}")
+  (data (i32.const 64) "{\"tags\":[\"cats\"],\"content\":\"<p>Meow.</p>\"}")
+  (data (i32.const 128) "spoiler")
+  (data (i32.const 144) "forever")
+  (data (i32.const 160) "cats")
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 1024))
+
+  (func $contains
+    (param $ptr i32) (param $len i32) (param $needle i32) (param $size i32)
+    (result i32)
+    (local $i i32) (local $j i32)
+    (block $none
+      (loop $outer
+        (br_if $none
+          (i32.gt_u (i32.add (local.get $i) (local.get $size)) (local.get $len)))
+        (local.set $j (i32.const 0))
+        (block $different
+          (loop $inner
+            (if (i32.eq (local.get $j) (local.get $size))
+              (then (return (i32.const 1))))
+            (br_if $different
+              (i32.ne
+                (i32.load8_u
+                  (i32.add (i32.add (local.get $ptr) (local.get $i)) (local.get $j)))
+                (i32.load8_u (i32.add (local.get $needle) (local.get $j)))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (br $inner)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $outer)))
+    (i32.const 0))
+
+  (func (export "filter") (param $ptr i32) (param $len i32) (result i64)
+    (if (call $contains (local.get $ptr) (local.get $len) (i32.const 128) (i32.const 7))
+      (then (return (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 13)))))
+    (if (call $contains (local.get $ptr) (local.get $len) (i32.const 144) (i32.const 7))
+      (then (loop $forever (br $forever))))
+    (if (call $contains (local.get $ptr) (local.get $len) (i32.const 160) (i32.const 4))
+      (then (return (i64.or (i64.shl (i64.const 64) (i64.const 32)) (i64.const 42)))))
+    (i64.const 0)))
+"#;
+
+fn said(content: &str) -> Entry {
+  Entry {
+    author: Author::new(Handle::parse_string("alice@example.social").unwrap()),
+    post: serde_json::from_value(json!({ "content": content })).unwrap(),
+    published: "2024-01-01T00:00:00Z".to_owned(),
+    boosted: false,
+  }
+}
+
+#[test]
+fn modules_hide_tag_and_rewrite_posts() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("filter.wat");
+
+  fs::write(&path, FILTER).unwrap();
+
+  let mut filters = Filters::load(std::slice::from_ref(&path)).unwrap();
+  let diagnostics = Diagnostics::default();
+  let mut entries = vec![
+    said("<p>Hello!</p>"),
+    said("<p>A spoiler for the finale</p>"),
+    said("<p>Look at these cats</p>"),
+    said("<p>This goes on forever</p>"),
+  ];
+
+  filters.apply(&mut entries, &diagnostics);
+
+  let contents: Vec<_> = entries
+    .iter()
+    .map(|entry| entry.post.content.as_str())
+    .collect();
+
+  assert_eq!(
+    contents,
+    [
+      "<p>Hello!</p>",
+      "<p>Meow.</p>",
+      "<p>This goes on forever</p>"
+    ],
+    "a post the module fails on is left as it was"
+  );
+  assert!(entries[0].post.hashtags().is_empty());
+  assert_eq!(entries[1].post.hashtags(), ["cats"]);
+
+  let diagnostics = diagnostics.take();
+
+  assert_eq!(diagnostics.len(), 1);
+  assert_eq!(diagnostics[0].kind, Kind::Wasm);
+  assert!(diagnostics[0]
+    .message
+    .ends_with("all fuel consumed by WebAssembly"));
+  assert_eq!(diagnostics[0].source, Some(path.display().to_string()));
+}
+
+#[test]
+fn modules_without_a_filter_arent_loaded() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("empty.wat");
+
+  fs::write(&path, r#"(module (memory (export "memory") 1))"#).unwrap();
+
+  let error = Filters::load(std::slice::from_ref(&path)).err().unwrap();
+
+  assert_eq!(error.path, path);
+  assert!(error.to_string().starts_with("Can't filter with "));
+}
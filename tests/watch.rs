@@ -0,0 +1,143 @@
+mod common;
+
+use apread::{
+  hooks::{self, Webhook},
+  render,
+  settings::{Overrides, Settings},
+  timeline::Author,
+  watch::Watch,
+  Client, Handle,
+};
+use common::FakeInstance;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn each_post_is_only_new_once() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let mut watch = Watch::new(vec![
+    Author::new(instance.handle("alice")),
+    Author::new(Handle::parse_string("nobody@127.0.0.1:9").unwrap()),
+  ]);
+
+  let first = watch.poll(&client).await;
+  assert!(!first.new.is_empty());
+  assert_eq!(first.failed.len(), 1);
+  assert_eq!(first.failed[0].0.label(), "nobody");
+
+  let second = watch.poll(&client).await;
+  assert!(second.new.is_empty());
+}
+
+#[tokio::test]
+async fn hooks_get_the_post_as_json_on_stdin() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let mut watch = Watch::new(vec![Author::new(instance.handle("alice"))]);
+  let entry = watch.poll(&client).await.new.remove(0);
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("post.json");
+  hooks::run(&format!("cat > '{}'", path.display()), &entry).unwrap();
+
+  let post: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+  assert_eq!(post["content"], entry.post.content);
+  assert_eq!(post["published"], entry.published);
+}
+
+#[tokio::test]
+async fn hooks_that_fail_say_so() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let mut watch = Watch::new(vec![Author::new(instance.handle("alice"))]);
+  let entry = watch.poll(&client).await.new.remove(0);
+
+  let error = hooks::run("exit 3", &entry).unwrap_err();
+
+  assert_eq!(error.command, "exit 3");
+  assert!(error.reason.contains('3'), "{}", error.reason);
+  assert!(hooks::run("true", &entry).is_ok());
+}
+
+#[test]
+fn signatures_are_hmac_sha256() {
+  // The second test case of RFC 4231.
+  assert_eq!(
+    hooks::sign("Jefe", b"what do ya want for nothing?"),
+    "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+  );
+}
+
+#[test]
+fn webhooks_come_from_the_config_file() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("config.toml");
+
+  std::fs::write(
+    &path,
+    r#"
+      [[webhooks]]
+      url = "https://hooks.example/signed"
+      secret = "hunter2"
+
+      [[webhooks]]
+      url = "https://hooks.example/open"
+    "#,
+  )
+  .unwrap();
+
+  let settings = Settings::load(Some(&path), Overrides::default()).unwrap();
+
+  assert_eq!(
+    settings.webhooks,
+    [
+      Webhook {
+        url: "https://hooks.example/signed".to_owned(),
+        secret: Some("hunter2".to_owned()),
+      },
+      Webhook {
+        url: "https://hooks.example/open".to_owned(),
+        secret: None,
+      },
+    ]
+  );
+}
+
+#[tokio::test]
+async fn webhooks_get_the_post_signed() {
+  let instance = FakeInstance::start("mastodon").await;
+  let client = Client::insecure();
+  let mut watch = Watch::new(vec![Author::new(instance.handle("alice"))]);
+  let entry = watch.poll(&client).await.new.remove(0);
+
+  let mut body = vec![];
+  render::json_entry(&mut body, &entry).unwrap();
+
+  let receiver = MockServer::start().await;
+  Mock::given(matchers::method("POST"))
+    .and(matchers::path("/hook"))
+    .and(matchers::header(
+      hooks::SIGNATURE_HEADER,
+      hooks::sign("hunter2", &body).as_str(),
+    ))
+    .and(matchers::body_bytes(body))
+    .respond_with(ResponseTemplate::new(204))
+    .expect(1)
+    .mount(&receiver)
+    .await;
+  let webhook = Webhook {
+    url: format!("{}/hook", receiver.uri()),
+    secret: Some("hunter2".to_owned()),
+  };
+
+  hooks::deliver(&client, &webhook, &entry).await.unwrap();
+
+  let missing = Webhook {
+    url: format!("{}/elsewhere", receiver.uri()),
+    secret: None,
+  };
+  let error = hooks::deliver(&client, &missing, &entry).await.unwrap_err();
+
+  assert_eq!(error.reason, "answered 404");
+}